@@ -0,0 +1,214 @@
+//! Per-stage benchmarks for the wearable and behavior pipelines
+//!
+//! Each stage is measured on its own input, prepared outside the timed loop,
+//! so a regression can be attributed to a single stage.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use serde_json::json;
+use synheart_flux::adapters::{VendorPayloadAdapter, WhoopAdapter};
+use synheart_flux::baseline::BaselineStore;
+use synheart_flux::behavior::adapter::{parse_session, session_to_canonical};
+use synheart_flux::behavior::baseline::BehaviorBaselineStore;
+use synheart_flux::behavior::encoder::HsiBehaviorEncoder;
+use synheart_flux::behavior::features::BehaviorFeatureDeriver;
+use synheart_flux::behavior::normalizer::BehaviorNormalizer;
+use synheart_flux::encoder::HsiEncoder;
+use synheart_flux::features::FeatureDeriver;
+use synheart_flux::normalizer::Normalizer;
+
+const DAYS: u32 = 30;
+
+/// Thirty days of WHOOP sleep, recovery and cycle records
+fn whoop_fixture() -> String {
+    let mut sleep = Vec::new();
+    let mut recovery = Vec::new();
+    let mut cycle = Vec::new();
+    for day in 1..=DAYS {
+        let jitter = (day % 7) as f64;
+        sleep.push(json!({
+            "id": day,
+            "start": format!("2024-01-{day:02}T22:30:00.000Z"),
+            "end": format!("2024-01-{:02}T06:30:00.000Z", day + 1),
+            "score": {
+                "stage_summary": {
+                    "total_in_bed_time_milli": 28_800_000,
+                    "total_awake_time_milli": 1_800_000,
+                    "total_light_sleep_time_milli": 12_600_000,
+                    "total_slow_wave_sleep_time_milli": 7_200_000,
+                    "total_rem_sleep_time_milli": 7_200_000,
+                    "total_sleep_time_milli": 27_000_000,
+                    "disturbance_count": 3
+                },
+                "sleep_performance_percentage": 80.0 + jitter
+            }
+        }));
+        recovery.push(json!({
+            "cycle_id": day,
+            "created_at": format!("2024-01-{day:02}T07:00:00.000Z"),
+            "score": {
+                "recovery_score": 60.0 + 3.0 * jitter,
+                "resting_heart_rate": 50.0 + jitter,
+                "hrv_rmssd_milli": 55.0 + 2.0 * jitter
+            }
+        }));
+        cycle.push(json!({
+            "id": day,
+            "start": format!("2024-01-{day:02}T07:00:00.000Z"),
+            "score": {
+                "strain": 8.0 + jitter,
+                "kilojoule": 9000.0,
+                "average_heart_rate": 68.0,
+                "max_heart_rate": 150.0
+            }
+        }));
+    }
+    json!({ "sleep": sleep, "recovery": recovery, "cycle": cycle }).to_string()
+}
+
+/// A 30-minute phone session with a few hundred mixed interaction events
+fn behavior_fixture() -> String {
+    let mut events = Vec::new();
+    for i in 0..360u32 {
+        let ts = format!("2024-01-15T14:{:02}:{:02}Z", i / 12, (i % 12) * 5);
+        let event = match i % 6 {
+            0 | 1 => json!({
+                "timestamp": ts, "event_type": "scroll",
+                "scroll": { "velocity": 100.0 + i as f64, "direction": "down", "direction_reversal": i % 5 == 0 }
+            }),
+            2 => json!({
+                "timestamp": ts, "event_type": "tap",
+                "tap": { "tap_duration_ms": 110, "long_press": false }
+            }),
+            3 => json!({
+                "timestamp": ts, "event_type": "typing",
+                "typing": { "typing_speed_cpm": 180.0, "cadence_stability": 0.8 }
+            }),
+            4 => json!({
+                "timestamp": ts, "event_type": "notification",
+                "interruption": { "action": if i % 4 == 0 { "opened" } else { "ignored" } }
+            }),
+            _ => json!({
+                "timestamp": ts, "event_type": "app_switch",
+                "app_switch": { "from_app_id": "com.a", "to_app_id": "com.b" }
+            }),
+        };
+        events.push(event);
+    }
+    json!({
+        "session_id": "bench-session",
+        "device_id": "bench-device",
+        "timezone": "UTC",
+        "start_time": "2024-01-15T14:00:00Z",
+        "end_time": "2024-01-15T14:30:00Z",
+        "events": events
+    })
+    .to_string()
+}
+
+fn bench_wearable(c: &mut Criterion) {
+    let raw = whoop_fixture();
+    let canonical = WhoopAdapter.parse(&raw, "UTC", "bench").unwrap();
+    let normalized: Vec<_> = canonical.iter().map(Normalizer::normalize).collect();
+    let derived: Vec<_> = normalized
+        .iter()
+        .cloned()
+        .map(FeatureDeriver::derive)
+        .collect();
+    let mut store = BaselineStore::default();
+    let contextual: Vec<_> = derived
+        .iter()
+        .cloned()
+        .map(|d| store.update_and_contextualize(d))
+        .collect();
+    let encoder = HsiEncoder::new();
+
+    let mut group = c.benchmark_group("wearable");
+    group.bench_function("adapter_parse", |b| {
+        b.iter(|| WhoopAdapter.parse(black_box(&raw), "UTC", "bench").unwrap())
+    });
+    group.bench_function("normalize", |b| {
+        b.iter(|| {
+            canonical
+                .iter()
+                .map(Normalizer::normalize)
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("derive_features", |b| {
+        b.iter_batched(
+            || normalized.clone(),
+            |n| {
+                n.into_iter()
+                    .map(FeatureDeriver::derive)
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("baseline_update", |b| {
+        b.iter_batched(
+            || (BaselineStore::default(), derived.clone()),
+            |(mut store, d)| {
+                d.into_iter()
+                    .map(|d| store.update_and_contextualize(d))
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("encode", |b| {
+        b.iter(|| {
+            contextual
+                .iter()
+                .map(|c| encoder.encode_to_json(c).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+fn bench_behavior(c: &mut Criterion) {
+    let raw = behavior_fixture();
+    let session = parse_session(&raw).unwrap();
+    let canonical = session_to_canonical(&session).unwrap();
+    let normalized = BehaviorNormalizer::normalize(canonical.clone());
+    let derived = BehaviorFeatureDeriver::derive(normalized.clone());
+    let contextual = BehaviorBaselineStore::default().update_and_contextualize(derived.clone());
+    let encoder = HsiBehaviorEncoder::new();
+
+    let mut group = c.benchmark_group("behavior");
+    group.bench_function("adapter_parse", |b| {
+        b.iter(|| {
+            let session = parse_session(black_box(&raw)).unwrap();
+            session_to_canonical(&session).unwrap()
+        })
+    });
+    group.bench_function("normalize", |b| {
+        b.iter_batched(
+            || canonical.clone(),
+            BehaviorNormalizer::normalize,
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("derive_features", |b| {
+        b.iter_batched(
+            || normalized.clone(),
+            BehaviorFeatureDeriver::derive,
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("baseline_update", |b| {
+        b.iter_batched(
+            || (BehaviorBaselineStore::default(), derived.clone()),
+            |(mut store, d)| store.update_and_contextualize(d),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("encode", |b| {
+        b.iter(|| encoder.encode_to_json(black_box(&contextual)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_wearable, bench_behavior);
+criterion_main!(benches);
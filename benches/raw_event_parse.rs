@@ -0,0 +1,62 @@
+//! Owned vs borrowed parsing of wear.raw_event.v1 NDJSON
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use synheart_flux::schema::RawEventAdapter;
+
+/// A month of minute-level heart rate plus daily HRV, with vendor payloads attached
+fn sample_ndjson() -> String {
+    let mut lines = Vec::new();
+    for day in 1..=28 {
+        for minute in (0..1440).step_by(15) {
+            lines.push(format!(
+                r#"{{"schema_version":"wear.raw_event.v1","event_id":"hr-{day}-{minute}","timestamp":"2024-02-{day:02}T{:02}:{:02}:00Z","source":{{"provider":"garmin","device_model":"Fenix 7","device_id":"watch-1"}},"user_id":"user-1","record_type":"signal","payload":{{"signal":{{"type":"heart_rate","value":{},"unit":"bpm"}}}},"vendor_raw":{{"heartRate":{},"source":"optical","samples":[1,2,3,4]}}}}"#,
+                minute / 60,
+                minute % 60,
+                60 + minute % 30,
+                60 + minute % 30,
+            ));
+        }
+        lines.push(format!(
+            r#"{{"schema_version":"wear.raw_event.v1","event_id":"hrv-{day}","timestamp":"2024-02-{day:02}T07:00:00Z","source":{{"provider":"garmin","device_id":"watch-1"}},"record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":{},"unit":"ms"}}}}}}"#,
+            50 + day,
+        ));
+    }
+    lines.join("\n")
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let ndjson = sample_ndjson();
+    let mut group = c.benchmark_group("raw_event_parse");
+    group.throughput(Throughput::Bytes(ndjson.len() as u64));
+
+    group.bench_function("owned", |b| {
+        b.iter(|| RawEventAdapter::parse_ndjson(black_box(&ndjson)).unwrap())
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| RawEventAdapter::parse_ndjson_borrowed(black_box(&ndjson)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_parse_to_canonical(c: &mut Criterion) {
+    let ndjson = sample_ndjson();
+    let mut group = c.benchmark_group("raw_event_to_canonical");
+    group.throughput(Throughput::Bytes(ndjson.len() as u64));
+
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            let events = RawEventAdapter::parse_ndjson(black_box(&ndjson)).unwrap();
+            RawEventAdapter::to_canonical(&events, "UTC", "watch-1").unwrap()
+        })
+    });
+    group.bench_function("borrowed_without_vendor_raw", |b| {
+        b.iter(|| {
+            let events = RawEventAdapter::parse_ndjson_borrowed(black_box(&ndjson)).unwrap();
+            RawEventAdapter::to_canonical_borrowed(&events, "UTC", "watch-1", false).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_parse_to_canonical);
+criterion_main!(benches);
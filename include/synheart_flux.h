@@ -40,6 +40,12 @@ extern "C" {
  */
 typedef struct FluxProcessorHandle FluxProcessorHandle;
 
+/**
+ * Opaque handle to a BehaviorProcessor instance.
+ * Created with flux_behavior_processor_new(), freed with flux_behavior_processor_free().
+ */
+typedef struct BehaviorProcessorHandle BehaviorProcessorHandle;
+
 /* ============================================================================
  * Stateless API
  * ============================================================================ */
@@ -138,6 +144,39 @@ char* flux_processor_process_garmin(
     const char* device_id
 );
 
+/**
+ * Process wear.raw_event.v1 NDJSON with a stateful processor (maintains baselines).
+ *
+ * Events from any provider are accepted; no vendor-specific function is needed.
+ *
+ * @param processor  FluxProcessor handle.
+ * @param ndjson     Newline-delimited raw events (null-terminated).
+ * @param timezone   User's timezone (null-terminated).
+ * @param device_id  Unique device identifier (null-terminated).
+ *
+ * @return Newly allocated JSON array string containing HSI payloads.
+ *         Returns NULL on error; call flux_last_error() for details.
+ *         Caller must free with flux_free_string().
+ */
+char* flux_processor_process_raw_events(
+    FluxProcessorHandle* processor,
+    const char* ndjson,
+    const char* timezone,
+    const char* device_id
+);
+
+/**
+ * Take the warnings raised since the last call (unknown signal types,
+ * dropped naps, clamped values, ...). Processing succeeds regardless.
+ *
+ * @param processor  FluxProcessor handle.
+ *
+ * @return Newly allocated JSON array of {"code", "date"?, "message"} objects.
+ *         Returns NULL on error; call flux_last_error() for details.
+ *         Caller must free with flux_free_string().
+ */
+char* flux_processor_take_warnings(FluxProcessorHandle* processor);
+
 /**
  * Save processor baselines to JSON for persistence.
  *
@@ -163,6 +202,152 @@ int32_t flux_processor_load_baselines(
     const char* json
 );
 
+/**
+ * Set output options (axis allow/deny lists, meta suppression, compact mode).
+ *
+ * @param processor    FluxProcessor handle.
+ * @param config_json  EncoderConfig JSON (null-terminated); omitted fields keep defaults,
+ *                     e.g. {"compact": true}.
+ *
+ * @return 0 on success, non-zero on error.
+ *         On error, call flux_last_error() for details.
+ */
+int32_t flux_processor_set_encoder_config(
+    FluxProcessorHandle* processor,
+    const char* config_json
+);
+
+/* ============================================================================
+ * Behavioral API
+ * ============================================================================ */
+
+/**
+ * Process behavioral session JSON and return HSI JSON (stateless).
+ *
+ * @param json  Behavioral session JSON (null-terminated).
+ *
+ * @return Newly allocated HSI JSON string.
+ *         Returns NULL on error; call flux_last_error() for details.
+ *         Caller must free with flux_free_string().
+ */
+char* flux_behavior_to_hsi(const char* json);
+
+/**
+ * Create a new BehaviorProcessor.
+ *
+ * @param baseline_window_sessions  Number of sessions in the rolling baseline (<= 0 uses 20).
+ *
+ * @return Processor handle. Caller must free with flux_behavior_processor_free().
+ */
+BehaviorProcessorHandle* flux_behavior_processor_new(int32_t baseline_window_sessions);
+
+/**
+ * Free a BehaviorProcessor.
+ *
+ * @param processor  Handle returned by flux_behavior_processor_new(). May be NULL.
+ */
+void flux_behavior_processor_free(BehaviorProcessorHandle* processor);
+
+/**
+ * Set idle, engagement and deep-focus thresholds.
+ *
+ * @param processor    BehaviorProcessor handle.
+ * @param config_json  BehaviorConfig JSON (null-terminated); omitted fields keep defaults,
+ *                     e.g. {"idle_gap_threshold_sec": 45, "deep_focus_min_duration_sec": 300}.
+ *
+ * @return 0 on success, non-zero on error.
+ *         On error, call flux_last_error() for details.
+ */
+int32_t flux_behavior_processor_set_config(
+    BehaviorProcessorHandle* processor,
+    const char* config_json
+);
+
+/**
+ * Set output options (axis allow/deny lists, meta suppression, compact mode).
+ *
+ * @param processor    BehaviorProcessor handle.
+ * @param config_json  EncoderConfig JSON (null-terminated); omitted fields keep defaults,
+ *                     e.g. {"compact": true}.
+ *
+ * @return 0 on success, non-zero on error.
+ *         On error, call flux_last_error() for details.
+ */
+int32_t flux_behavior_processor_set_encoder_config(
+    BehaviorProcessorHandle* processor,
+    const char* config_json
+);
+
+/**
+ * Attach wearable baselines as bio context on session payloads.
+ *
+ * @param processor       BehaviorProcessor handle.
+ * @param baselines_json  Baselines JSON from flux_processor_save_baselines()
+ *                        (null-terminated), or NULL to detach.
+ *
+ * @return 0 on success, non-zero on error.
+ *         On error, call flux_last_error() for details.
+ */
+int32_t flux_behavior_processor_set_bio_context(
+    BehaviorProcessorHandle* processor,
+    const char* baselines_json
+);
+
+/**
+ * Set how quickly bio context readings lose confidence, per field.
+ *
+ * @param processor     BehaviorProcessor handle.
+ * @param options_json  SnapshotOptions JSON (null-terminated); omitted fields keep defaults,
+ *                      e.g. {"sleep_half_life_days": 4.0}.
+ *
+ * @return 0 on success, non-zero on error.
+ *         On error, call flux_last_error() for details.
+ */
+int32_t flux_behavior_processor_set_snapshot_options(
+    BehaviorProcessorHandle* processor,
+    const char* options_json
+);
+
+/**
+ * Process a behavioral session with a stateful processor.
+ *
+ * @param processor  BehaviorProcessor handle.
+ * @param json       Behavioral session JSON (null-terminated).
+ *
+ * @return Newly allocated HSI JSON string.
+ *         Returns NULL on error; call flux_last_error() for details.
+ *         Caller must free with flux_free_string().
+ */
+char* flux_behavior_processor_process(
+    BehaviorProcessorHandle* processor,
+    const char* json
+);
+
+/**
+ * Save behavioral baselines to JSON for persistence.
+ *
+ * @param processor  BehaviorProcessor handle.
+ *
+ * @return Newly allocated JSON string containing baseline state.
+ *         Returns NULL on error; call flux_last_error() for details.
+ *         Caller must free with flux_free_string().
+ */
+char* flux_behavior_processor_save_baselines(BehaviorProcessorHandle* processor);
+
+/**
+ * Load previously saved behavioral baselines into a processor.
+ *
+ * @param processor  BehaviorProcessor handle.
+ * @param json       JSON string from flux_behavior_processor_save_baselines() (null-terminated).
+ *
+ * @return 0 on success, non-zero on error.
+ *         On error, call flux_last_error() for details.
+ */
+int32_t flux_behavior_processor_load_baselines(
+    BehaviorProcessorHandle* processor,
+    const char* json
+);
+
 /* ============================================================================
  * Memory Management
  * ============================================================================ */
@@ -4,11 +4,13 @@
 
 use crate::error::ComputeError;
 use crate::types::{
-    CanonicalActivity, CanonicalRecovery, CanonicalSleep, CanonicalWearSignals, Vendor,
+    BodyBattery, CanonicalActivity, CanonicalBody, CanonicalRecovery, CanonicalSleep,
+    CanonicalWearSignals, CanonicalWorkout, HrZoneMinutes, RespirationSummary, StressSummary,
+    Vendor,
 };
-use chrono::{TimeZone, Utc};
+use chrono::{Duration, NaiveDateTime, TimeZone, Utc};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::VendorPayloadAdapter;
 
@@ -35,6 +37,7 @@ impl VendorPayloadAdapter for GarminAdapter {
                 date,
                 daily: None,
                 sleep: None,
+                activities: Vec::new(),
             });
             entry.daily = Some(summary);
         }
@@ -46,10 +49,31 @@ impl VendorPayloadAdapter for GarminAdapter {
                 date,
                 daily: None,
                 sleep: None,
+                activities: Vec::new(),
             });
             entry.sleep = Some(sleep);
         }
 
+        // Process activities (grouped by local start date)
+        for activity in payload.activities.unwrap_or_default() {
+            let Some(date) = activity
+                .start_time_local
+                .as_deref()
+                .or(activity.start_time_gmt.as_deref())
+                .and_then(|t| t.get(..10))
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            let entry = by_date.entry(date.clone()).or_insert_with(|| DayData {
+                date,
+                daily: None,
+                sleep: None,
+                activities: Vec::new(),
+            });
+            entry.activities.push(activity);
+        }
+
         // Convert grouped data to canonical signals
         for (_date, day) in by_date {
             let canonical = convert_day_to_canonical(day, timezone, device_id)?;
@@ -68,6 +92,7 @@ struct DayData {
     date: String,
     daily: Option<GarminDaily>,
     sleep: Option<GarminSleep>,
+    activities: Vec<GarminActivity>,
 }
 
 fn convert_day_to_canonical(
@@ -107,6 +132,7 @@ fn convert_day_to_canonical(
             latency_minutes: None, // Garmin doesn't provide sleep latency directly
             vendor_sleep_score: s.sleep_scores.as_ref().and_then(|sc| sc.overall_score),
             respiratory_rate: s.avg_sleep_respiration,
+            hypnogram: Vec::new(),
         }
     } else {
         CanonicalSleep::default()
@@ -116,21 +142,48 @@ fn convert_day_to_canonical(
     let recovery = if let Some(d) = &day.daily {
         CanonicalRecovery {
             hrv_rmssd_ms: d.resting_heart_rate_hrv, // Garmin provides HRV in some endpoints
+            hrv_cv: None,
+            hrv_scale: None,
             resting_hr_bpm: d.resting_heart_rate.map(|hr| hr as f64),
             vendor_recovery_score: d.body_battery_charged_value.map(|bb| bb as f64), // Body Battery as recovery proxy
             skin_temp_deviation_c: None, // Not available in basic Garmin API
             spo2_percentage: d.avg_spo2_value,
+            cycle_phase: None,
+            body_battery: BodyBattery {
+                charged: d.body_battery_charged_value.map(f64::from),
+                drained: d.body_battery_drained_value.map(f64::from),
+                lowest: d.body_battery_lowest_value.map(f64::from),
+                highest: d.body_battery_highest_value.map(f64::from),
+            },
+            stress: StressSummary {
+                average: d.average_stress_level.map(f64::from),
+                max: d.max_stress_level.map(f64::from),
+                rest_minutes: d.rest_stress_duration.map(|secs| secs as f64 / 60.0),
+                low_minutes: d.low_stress_duration.map(|secs| secs as f64 / 60.0),
+                medium_minutes: d.medium_stress_duration.map(|secs| secs as f64 / 60.0),
+                high_minutes: d.high_stress_duration.map(|secs| secs as f64 / 60.0),
+            },
+            respiration: RespirationSummary {
+                waking_average: d.avg_waking_respiration_value,
+                lowest: d.lowest_respiration_value,
+                highest: d.highest_respiration_value,
+            },
         }
     } else {
         CanonicalRecovery::default()
     };
 
+    let mut workouts: Vec<CanonicalWorkout> =
+        day.activities.iter().filter_map(convert_activity).collect();
+    workouts.sort_by_key(|w| w.start_time);
+
     // Build canonical activity (from daily summary)
-    let activity = if let Some(d) = &day.daily {
+    let mut activity = if let Some(d) = &day.daily {
         CanonicalActivity {
             vendor_strain_score: d.training_load_balance, // Garmin's training load
             calories: d.total_kilocalories.map(|c| c as f64),
             active_calories: d.active_kilocalories.map(|c| c as f64),
+            resting_calories: d.bmr_kilocalories.map(|c| c as f64),
             average_hr_bpm: d.average_heart_rate.map(|hr| hr as f64),
             max_hr_bpm: d.max_heart_rate.map(|hr| hr as f64),
             distance_meters: d.total_distance_meters.map(|d| d as f64),
@@ -139,10 +192,23 @@ fn convert_day_to_canonical(
                 .moderate_intensity_minutes
                 .map(|m| m as f64)
                 .and_then(|m| d.vigorous_intensity_minutes.map(|v| m + (v as f64))),
+            moderate_intensity_minutes: d.moderate_intensity_minutes.map(|m| m as f64),
+            vigorous_intensity_minutes: d.vigorous_intensity_minutes.map(|v| v as f64),
+            // Garmin reports fractional floors; count whole ones
+            floors_climbed: d.floors_ascended.map(|f| f.floor() as u32),
+            elevation_gain_meters: d.floors_ascended_in_meters,
+            workouts: Vec::new(),
+            hr_zone_minutes: HrZoneMinutes::default(),
+            vo2_max: d.vo2_max_value,
+            fitness_age: d.fitness_age,
+            hourly: Vec::new(),
         }
     } else {
         CanonicalActivity::default()
     };
+    // Dailies carry no zone times, so the day's come from its activities
+    activity.hr_zone_minutes = HrZoneMinutes::sum(workouts.iter().map(|w| &w.hr_zone_minutes));
+    activity.workouts = workouts;
 
     // Build vendor_raw with original data
     let mut vendor_raw = HashMap::new();
@@ -158,17 +224,78 @@ fn convert_day_to_canonical(
             serde_json::to_value(d).unwrap_or(serde_json::Value::Null),
         );
     }
+    if !day.activities.is_empty() {
+        vendor_raw.insert(
+            "activities".to_string(),
+            serde_json::to_value(&day.activities).unwrap_or(serde_json::Value::Null),
+        );
+    }
+
+    // Daily summaries carry no timestamps, so only sleep and workouts count
+    let latest_event_at = [
+        ("sleep", sleep.end_time),
+        (
+            "activity",
+            activity.workouts.iter().map(|w| w.end_time).max(),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(domain, at)| Some((domain.to_string(), at?)))
+    .collect();
 
     Ok(CanonicalWearSignals {
         vendor: Vendor::Garmin,
         date: day.date,
         device_id: device_id.to_string(),
+        user_id: None,
         timezone: timezone.to_string(),
+        utc_offset_minutes: None,
         observed_at,
+        latest_event_at,
         sleep,
         recovery,
         activity,
+        body: CanonicalBody::default(),
+        input_quality: None,
         vendor_raw,
+        field_sources: HashMap::new(),
+        field_events: HashMap::new(),
+        intraday: BTreeMap::new(),
+        tags: Vec::new(),
+        warnings: Vec::new(),
+        unmapped_signals: BTreeMap::new(),
+    })
+}
+
+/// Convert an activity, skipping ones without a start time or duration
+fn convert_activity(a: &GarminActivity) -> Option<CanonicalWorkout> {
+    let start = NaiveDateTime::parse_from_str(a.start_time_gmt.as_deref()?, "%Y-%m-%d %H:%M:%S")
+        .ok()?
+        .and_utc();
+    let duration = a.duration.filter(|d| *d >= 0.0)?;
+    let minutes = |secs: Option<f64>| secs.map(|s| s / 60.0);
+
+    Some(CanonicalWorkout {
+        start_time: start,
+        end_time: start + Duration::milliseconds((duration * 1000.0) as i64),
+        sport: a
+            .activity_type
+            .as_ref()
+            .and_then(|t| t.type_key.clone())
+            .or_else(|| a.activity_name.clone()),
+        vendor_strain_score: None, // Per-activity training load is on another scale
+        calories: a.calories,
+        average_hr_bpm: a.average_hr,
+        max_hr_bpm: a.max_hr,
+        distance_meters: a.distance,
+        hr_zone_minutes: HrZoneMinutes {
+            zone_0: None,
+            zone_1: minutes(a.hr_time_in_zone_1),
+            zone_2: minutes(a.hr_time_in_zone_2),
+            zone_3: minutes(a.hr_time_in_zone_3),
+            zone_4: minutes(a.hr_time_in_zone_4),
+            zone_5: minutes(a.hr_time_in_zone_5),
+        },
     })
 }
 
@@ -178,6 +305,7 @@ fn convert_day_to_canonical(
 struct GarminPayload {
     dailies: Option<Vec<GarminDaily>>,
     sleep: Option<Vec<GarminSleep>>,
+    activities: Option<Vec<GarminActivity>>,
 }
 
 #[derive(Debug, Deserialize, serde::Serialize)]
@@ -188,6 +316,7 @@ struct GarminDaily {
     total_distance_meters: Option<i64>,
     total_kilocalories: Option<i32>,
     active_kilocalories: Option<i32>,
+    bmr_kilocalories: Option<i32>,
     resting_heart_rate: Option<i32>,
     resting_heart_rate_hrv: Option<f64>,
     average_heart_rate: Option<i32>,
@@ -195,9 +324,80 @@ struct GarminDaily {
     avg_spo2_value: Option<f64>,
     body_battery_charged_value: Option<i32>,
     body_battery_drained_value: Option<i32>,
+    body_battery_highest_value: Option<i32>,
+    body_battery_lowest_value: Option<i32>,
+    // Garmin reports -1 or -2 when there was too little data to measure stress
+    #[serde(default, deserialize_with = "stress_level")]
+    average_stress_level: Option<i32>,
+    #[serde(default, deserialize_with = "stress_level")]
+    max_stress_level: Option<i32>,
+    #[serde(alias = "restStressDurationInSeconds")]
+    rest_stress_duration: Option<i64>,
+    #[serde(alias = "lowStressDurationInSeconds")]
+    low_stress_duration: Option<i64>,
+    #[serde(alias = "mediumStressDurationInSeconds")]
+    medium_stress_duration: Option<i64>,
+    #[serde(alias = "highStressDurationInSeconds")]
+    high_stress_duration: Option<i64>,
+    avg_waking_respiration_value: Option<f64>,
+    lowest_respiration_value: Option<f64>,
+    highest_respiration_value: Option<f64>,
     training_load_balance: Option<f64>,
+    #[serde(alias = "vo2MaxPreciseValue")]
+    vo2_max_value: Option<f64>,
+    fitness_age: Option<f64>,
     moderate_intensity_minutes: Option<i32>,
     vigorous_intensity_minutes: Option<i32>,
+    floors_ascended: Option<f64>,
+    floors_ascended_in_meters: Option<f64>,
+}
+
+/// Activity summary, as in the Garmin Connect activity list
+#[derive(Debug, Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GarminActivity {
+    activity_id: Option<i64>,
+    activity_name: Option<String>,
+    activity_type: Option<GarminActivityType>,
+    /// `YYYY-MM-DD HH:MM:SS` in UTC
+    #[serde(rename = "startTimeGMT")]
+    start_time_gmt: Option<String>,
+    /// `YYYY-MM-DD HH:MM:SS` in the wearer's local time
+    start_time_local: Option<String>,
+    /// Seconds
+    duration: Option<f64>,
+    distance: Option<f64>,
+    calories: Option<f64>,
+    #[serde(rename = "averageHR")]
+    average_hr: Option<f64>,
+    #[serde(rename = "maxHR")]
+    max_hr: Option<f64>,
+    /// Seconds in each heart rate zone
+    #[serde(rename = "hrTimeInZone_1")]
+    hr_time_in_zone_1: Option<f64>,
+    #[serde(rename = "hrTimeInZone_2")]
+    hr_time_in_zone_2: Option<f64>,
+    #[serde(rename = "hrTimeInZone_3")]
+    hr_time_in_zone_3: Option<f64>,
+    #[serde(rename = "hrTimeInZone_4")]
+    hr_time_in_zone_4: Option<f64>,
+    #[serde(rename = "hrTimeInZone_5")]
+    hr_time_in_zone_5: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GarminActivityType {
+    type_key: Option<String>,
+}
+
+/// Deserialize a stress level, mapping Garmin's negative sentinels to `None`
+fn stress_level<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let level = Option::<i32>::deserialize(deserializer)?;
+    Ok(level.filter(|l| *l >= 0))
 }
 
 #[derive(Debug, Deserialize, serde::Serialize)]
@@ -282,4 +482,179 @@ mod tests {
         assert!(sig.recovery.resting_hr_bpm.is_some());
         assert_eq!(sig.recovery.resting_hr_bpm.unwrap(), 55.0);
     }
+
+    #[test]
+    fn test_resting_calories() {
+        let json = r#"{
+            "dailies": [{
+                "calendarDate": "2024-01-15",
+                "totalKilocalories": 2200,
+                "activeKilocalories": 450,
+                "bmrKilocalories": 1750
+            }]
+        }"#;
+
+        let signals = GarminAdapter.parse(json, "UTC", "dev").unwrap();
+        assert_eq!(signals[0].activity.resting_calories, Some(1750.0));
+
+        let hsi = crate::garmin_to_hsi_daily(json.to_string(), "UTC".into(), "dev".into()).unwrap();
+        let hsi: serde_json::Value = serde_json::from_str(&hsi[0]).unwrap();
+        let window = &hsi["windows"][0];
+        assert_eq!(window["activity"]["resting_calories"], 1750.0);
+        assert!(!window["quality"]["flags"]
+            .to_string()
+            .contains("inconsistent_calories"));
+    }
+
+    #[test]
+    fn test_intensity_minutes_split() {
+        let json = r#"{
+            "dailies": [{
+                "calendarDate": "2024-01-15",
+                "moderateIntensityMinutes": 34,
+                "vigorousIntensityMinutes": 8
+            }]
+        }"#;
+
+        let signals = GarminAdapter.parse(json, "UTC", "dev").unwrap();
+        assert_eq!(signals[0].activity.active_minutes, Some(42.0));
+        assert_eq!(signals[0].activity.moderate_intensity_minutes, Some(34.0));
+        assert_eq!(signals[0].activity.vigorous_intensity_minutes, Some(8.0));
+
+        let hsi = crate::garmin_to_hsi_daily(json.to_string(), "UTC".into(), "dev".into()).unwrap();
+        let hsi: serde_json::Value = serde_json::from_str(&hsi[0]).unwrap();
+        let activity = &hsi["windows"][0]["activity"];
+        assert_eq!(activity["moderate_intensity_minutes"], 34.0);
+        assert_eq!(activity["vigorous_intensity_minutes"], 8.0);
+    }
+
+    #[test]
+    fn test_floors_and_elevation() {
+        let json = r#"{
+            "dailies": [{
+                "calendarDate": "2024-01-15",
+                "floorsAscended": 12.74,
+                "floorsAscendedInMeters": 38.8
+            }]
+        }"#;
+
+        let signals = GarminAdapter.parse(json, "UTC", "dev").unwrap();
+        assert_eq!(signals[0].activity.floors_climbed, Some(12));
+        assert_eq!(signals[0].activity.elevation_gain_meters, Some(38.8));
+
+        let hsi = crate::garmin_to_hsi_daily(json.to_string(), "UTC".into(), "dev".into()).unwrap();
+        let hsi: serde_json::Value = serde_json::from_str(&hsi[0]).unwrap();
+        let activity = &hsi["windows"][0]["activity"];
+        assert_eq!(activity["floors_climbed"], 12);
+        assert_eq!(activity["elevation_gain_meters"], 38.8);
+    }
+
+    #[test]
+    fn test_body_battery_dynamics() {
+        let json = r#"{
+            "dailies": [{
+                "calendarDate": "2024-01-15",
+                "bodyBatteryChargedValue": 62,
+                "bodyBatteryDrainedValue": 80,
+                "bodyBatteryHighestValue": 95,
+                "bodyBatteryLowestValue": 15
+            }]
+        }"#;
+
+        let signals = GarminAdapter.parse(json, "UTC", "dev").unwrap();
+        let body_battery = signals[0].recovery.body_battery;
+        assert_eq!(body_battery.drained, Some(80.0));
+        assert_eq!(body_battery.lowest, Some(15.0));
+        assert_eq!(body_battery.highest, Some(95.0));
+        assert!((body_battery.recovery_balance().unwrap() + 0.18).abs() < 1e-9);
+
+        let hsi = crate::garmin_to_hsi_daily(json.to_string(), "UTC".into(), "dev".into()).unwrap();
+        let hsi: serde_json::Value = serde_json::from_str(&hsi[0]).unwrap();
+        let physiology = &hsi["windows"][0]["physiology"];
+        assert!((physiology["recovery_balance"].as_f64().unwrap() + 0.18).abs() < 1e-9);
+        assert_eq!(physiology["vendor"]["garmin_body_battery"]["highest"], 95.0);
+    }
+
+    #[test]
+    fn test_activities_hr_zones() {
+        let json = r#"{
+            "activities": [{
+                "activityId": 101,
+                "activityName": "Evening Run",
+                "activityType": {"typeKey": "running"},
+                "startTimeGMT": "2024-01-16 01:00:00",
+                "startTimeLocal": "2024-01-15 17:00:00",
+                "duration": 2700.0,
+                "distance": 7500.0,
+                "calories": 520.0,
+                "averageHR": 152.0,
+                "maxHR": 181.0,
+                "hrTimeInZone_1": 300.0,
+                "hrTimeInZone_3": 1200.0,
+                "hrTimeInZone_4": 1200.0
+            }, {
+                "activityName": "Walk",
+                "startTimeGMT": "2024-01-15 15:00:00",
+                "startTimeLocal": "2024-01-15 07:00:00",
+                "duration": 1800.0,
+                "hrTimeInZone_1": 1500.0
+            }]
+        }"#;
+
+        let signals = GarminAdapter
+            .parse(json, "America/Los_Angeles", "dev")
+            .unwrap();
+        // Grouped by local date even though the run starts on the 16th in UTC
+        assert_eq!(signals.len(), 1);
+        let activity = &signals[0].activity;
+        assert_eq!(activity.workouts.len(), 2);
+        assert_eq!(activity.workouts[0].sport.as_deref(), Some("Walk"));
+        assert_eq!(activity.workouts[1].sport.as_deref(), Some("running"));
+        assert_eq!(activity.workouts[1].duration_minutes(), 45.0);
+        assert_eq!(activity.hr_zone_minutes.zone_1, Some(30.0));
+        assert_eq!(activity.hr_zone_minutes.zone_4, Some(20.0));
+        assert_eq!(activity.hr_zone_minutes.zone_2, None);
+
+        let hsi = crate::garmin_to_hsi_daily(json.to_string(), "UTC".into(), "dev".into()).unwrap();
+        let hsi: serde_json::Value = serde_json::from_str(&hsi[0]).unwrap();
+        let zones = &hsi["windows"][0]["activity"]["hr_zone_minutes"];
+        assert_eq!(zones["zone_3"], 20.0);
+        assert!(zones.get("zone_2").is_none());
+    }
+
+    #[test]
+    fn test_stress_and_respiration() {
+        let json = r#"{
+            "dailies": [{
+                "calendarDate": "2024-01-15",
+                "averageStressLevel": 32,
+                "maxStressLevel": 88,
+                "restStressDurationInSeconds": 28800,
+                "lowStressDurationInSeconds": 14400,
+                "mediumStressDurationInSeconds": 7200,
+                "highStressDurationInSeconds": 7200,
+                "avgWakingRespirationValue": 15.0,
+                "lowestRespirationValue": 11.0,
+                "highestRespirationValue": 22.0
+            }, {
+                "calendarDate": "2024-01-16",
+                "averageStressLevel": -1
+            }]
+        }"#;
+
+        let signals = GarminAdapter.parse(json, "UTC", "dev").unwrap();
+        let stress = signals[0].recovery.stress;
+        assert_eq!(stress.max, Some(88.0));
+        assert_eq!(stress.rest_minutes, Some(480.0));
+        assert_eq!(signals[0].recovery.respiration.waking_average, Some(15.0));
+        assert!(signals[1].recovery.stress.is_empty());
+
+        let hsi = crate::garmin_to_hsi_daily(json.to_string(), "UTC".into(), "dev".into()).unwrap();
+        let hsi: serde_json::Value = serde_json::from_str(&hsi[0]).unwrap();
+        let physiology = &hsi["windows"][0]["physiology"];
+        assert_eq!(physiology["vendor_stress"], 0.32);
+        assert_eq!(physiology["stress_ratios"]["rest"], 0.5);
+        assert_eq!(physiology["stress_ratios"]["high"], 0.125);
+        assert_eq!(physiology["vendor"]["garmin_respiration"]["highest"], 22.0);
+    }
 }
@@ -4,16 +4,57 @@
 //! to canonical, vendor-agnostic structures.
 
 mod garmin;
+pub mod webhook;
 mod whoop;
 
 pub use garmin::GarminAdapter;
 pub use whoop::WhoopAdapter;
 
 use crate::error::ComputeError;
-use crate::types::CanonicalWearSignals;
+use crate::types::{CanonicalWearSignals, Vendor};
+use serde_json::Value;
 
-/// Trait for vendor payload adapters
-pub trait VendorPayloadAdapter {
+/// Record keys that identify a vendor export: `(vendor, collection, key)`
+/// matches when a record in the top-level `collection` array has `key`
+const VENDOR_MARKERS: [(Vendor, &str, &str); 8] = [
+    (Vendor::Garmin, "dailies", "calendarDate"),
+    (Vendor::Garmin, "sleep", "calendarDate"),
+    (Vendor::Garmin, "activities", "activityId"),
+    (Vendor::Garmin, "activities", "startTimeGMT"),
+    (Vendor::Whoop, "recovery", "created_at"),
+    (Vendor::Whoop, "cycle", "start"),
+    (Vendor::Whoop, "sleep", "start"),
+    (Vendor::Whoop, "workout", "start"),
+];
+
+/// Guess which vendor export a payload is from its shape.
+///
+/// Returns `None` when the payload is not a JSON object, has no records, or
+/// has records of more than one vendor.
+pub fn detect_vendor(raw_json: &str) -> Option<Vendor> {
+    let payload: Value = serde_json::from_str(raw_json).ok()?;
+    let mut detected = None;
+    for (vendor, collection, key) in VENDOR_MARKERS {
+        let Some(records) = payload.get(collection).and_then(Value::as_array) else {
+            continue;
+        };
+        if records.iter().any(|record| record.get(key).is_some()) {
+            if detected.is_some_and(|d| d != vendor) {
+                return None;
+            }
+            detected = Some(vendor);
+        }
+    }
+    detected
+}
+
+/// Trait for vendor payload adapters.
+///
+/// Adapters for other vendors can be registered at runtime with
+/// [`FluxProcessor::register_adapter`].
+///
+/// [`FluxProcessor::register_adapter`]: crate::pipeline::FluxProcessor::register_adapter
+pub trait VendorPayloadAdapter: Send + Sync {
     /// Parse raw JSON and convert to canonical signals
     fn parse(
         &self,
@@ -22,3 +63,28 @@ pub trait VendorPayloadAdapter {
         device_id: &str,
     ) -> Result<Vec<CanonicalWearSignals>, ComputeError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_vendor() {
+        let whoop = r#"{"recovery":[{"cycle_id":1,"created_at":"2024-01-15T06:30:00Z","score":{"hrv_rmssd_milli":65.0}}]}"#;
+        assert_eq!(detect_vendor(whoop), Some(Vendor::Whoop));
+        let whoop_sleep =
+            r#"{"sleep":[{"start":"2024-01-15T22:30:00Z","end":"2024-01-16T06:30:00Z"}]}"#;
+        assert_eq!(detect_vendor(whoop_sleep), Some(Vendor::Whoop));
+        let garmin = r#"{"dailies":[{"calendarDate":"2024-01-15","totalSteps":8000}],"sleep":[]}"#;
+        assert_eq!(detect_vendor(garmin), Some(Vendor::Garmin));
+
+        let mixed = r#"{"dailies":[{"calendarDate":"2024-01-15"}],"cycle":[{"start":"2024-01-15T06:30:00Z"}]}"#;
+        assert_eq!(detect_vendor(mixed), None);
+        assert_eq!(
+            detect_vendor(r#"{"activities":[{"name":"Morning Ride"}]}"#),
+            None
+        );
+        assert_eq!(detect_vendor(r#"{"dailies":[]}"#), None);
+        assert_eq!(detect_vendor("not json"), None);
+    }
+}
@@ -0,0 +1,592 @@
+//! Vendor webhook normalization
+//!
+//! WHOOP and Garmin push webhooks wrap records differently than the REST
+//! exports [`super::WhoopAdapter`] and [`super::GarminAdapter`] read:
+//!
+//! - WHOOP sends one record per call in an envelope
+//!   (`{"type": "recovery.updated", "user_id": .., "data": {..}}`)
+//! - Garmin sends a batch keyed by summary type (`dailies`, `sleeps`, `hrv`,
+//!   `userMetrics`) using the Health API push field names
+//!
+//! These helpers unwrap the body and emit `wear.raw_event.v1` events, which
+//! feed [`crate::schema::RawEventAdapter::to_canonical`] like any other input.
+//! Field names are accepted in snake_case or camelCase. Notification-only
+//! bodies (no inline data, Garmin ping callbacks), deletions and record types
+//! with no canonical mapping produce no events.
+
+use crate::error::ComputeError;
+use crate::schema::{
+    MetricValue, Payload, Provider, RawEvent, RecordType, ScorePayload, ScoreScale, ScoreType,
+    SessionPayload, SessionType, SignalPayload, SignalType, Source, SummaryPayload, SummaryPeriod,
+    Unit, SCHEMA_VERSION,
+};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// WHOOP strain scale maximum
+const WHOOP_STRAIN_MAX: f64 = 21.0;
+
+/// Convert a WHOOP webhook body to raw events
+pub fn whoop_webhook_to_raw_events(body: &str) -> Result<Vec<RawEvent>, ComputeError> {
+    let envelope: Value = serde_json::from_str(body)?;
+    let kind = str_field(&envelope, "type")
+        .ok_or_else(|| ComputeError::MissingField("type".to_string()))?;
+    let Some(data) = field(&envelope, "data").filter(|d| d.is_object()) else {
+        return Ok(Vec::new());
+    };
+    let user_id = field(&envelope, "user_id").and_then(id_string);
+    let source = source(Provider::Whoop);
+
+    let (record, action) = kind.split_once('.').unwrap_or((kind, "updated"));
+    if action == "deleted" {
+        return Ok(Vec::new());
+    }
+    let record_id = field(data, "id")
+        .or_else(|| field(data, "cycle_id"))
+        .and_then(id_string)
+        .unwrap_or_else(|| "unknown".to_string());
+    let event_id = |suffix: &str| format!("whoop-{record}-{record_id}-{suffix}");
+
+    let mut events = Vec::new();
+    match record {
+        "sleep" => {
+            let (Some(start), Some(end)) = (time_field(data, "start"), time_field(data, "end"))
+            else {
+                return Err(ComputeError::MissingField(
+                    "data.start/data.end".to_string(),
+                ));
+            };
+            let score = field(data, "score");
+            let stages = score.and_then(|s| field(s, "stage_summary"));
+            let millis = |name: &str| {
+                stages
+                    .and_then(|s| num_field(s, name))
+                    .map(|ms| MetricValue::Number(ms / 60_000.0))
+            };
+            let mut metrics = HashMap::new();
+            for (key, value) in [
+                ("time_in_bed_minutes", millis("total_in_bed_time_milli")),
+                ("total_sleep_minutes", millis("total_sleep_time_milli")),
+                ("awake_minutes", millis("total_awake_time_milli")),
+                (
+                    "light_sleep_minutes",
+                    millis("total_light_sleep_time_milli"),
+                ),
+                (
+                    "deep_sleep_minutes",
+                    millis("total_slow_wave_sleep_time_milli"),
+                ),
+                ("rem_sleep_minutes", millis("total_rem_sleep_time_milli")),
+                (
+                    "awakenings",
+                    stages
+                        .and_then(|s| num_field(s, "disturbance_count"))
+                        .map(|n| MetricValue::Integer(n as i64)),
+                ),
+                (
+                    "latency_minutes",
+                    score
+                        .and_then(|s| num_field(s, "sleep_latency_time_milli"))
+                        .map(|ms| MetricValue::Number(ms / 60_000.0)),
+                ),
+                (
+                    "sleep_score",
+                    score
+                        .and_then(|s| num_field(s, "sleep_performance_percentage"))
+                        .map(MetricValue::Number),
+                ),
+            ] {
+                if let Some(value) = value {
+                    metrics.insert(key.to_string(), value);
+                }
+            }
+            let session = SessionPayload {
+                session_type: if data.get("nap").and_then(Value::as_bool) == Some(true) {
+                    SessionType::Nap
+                } else {
+                    SessionType::Sleep
+                },
+                start_time: start,
+                end_time: end,
+                metrics,
+            };
+            events.push(event(
+                event_id("session"),
+                end,
+                &source,
+                Payload::Session { session },
+            ));
+        }
+        "recovery" => {
+            let timestamp = time_field(data, "created_at")
+                .or_else(|| time_field(data, "updated_at"))
+                .ok_or_else(|| ComputeError::MissingField("data.created_at".to_string()))?;
+            let score = field(data, "score");
+            let value = |name: &str| score.and_then(|s| num_field(s, name));
+            for (suffix, signal_type, reading, unit) in [
+                (
+                    "hrv",
+                    SignalType::HeartRateVariability,
+                    value("hrv_rmssd_milli"),
+                    Unit::Ms,
+                ),
+                (
+                    "rhr",
+                    SignalType::RestingHeartRate,
+                    value("resting_heart_rate"),
+                    Unit::Bpm,
+                ),
+                (
+                    "spo2",
+                    SignalType::Spo2,
+                    value("spo2_percentage"),
+                    Unit::Percent,
+                ),
+            ] {
+                if let Some(value) = reading {
+                    let signal = SignalPayload {
+                        signal_type,
+                        value,
+                        unit,
+                        quality: None,
+                    };
+                    events.push(event(
+                        event_id(suffix),
+                        timestamp,
+                        &source,
+                        Payload::Signal { signal },
+                    ));
+                }
+            }
+            if let Some(value) = value("recovery_score") {
+                let score = ScorePayload {
+                    score_type: ScoreType::Recovery,
+                    value,
+                    scale: ScoreScale {
+                        min: 0.0,
+                        max: 100.0,
+                    },
+                    components: HashMap::new(),
+                };
+                events.push(event(
+                    event_id("score"),
+                    timestamp,
+                    &source,
+                    Payload::Score { score },
+                ));
+            }
+        }
+        "cycle" => {
+            let timestamp = time_field(data, "start")
+                .ok_or_else(|| ComputeError::MissingField("data.start".to_string()))?;
+            let score = field(data, "score");
+            if let Some(strain) = score.and_then(|s| num_field(s, "strain")) {
+                let score = ScorePayload {
+                    score_type: ScoreType::Strain,
+                    value: strain,
+                    scale: ScoreScale {
+                        min: 0.0,
+                        max: WHOOP_STRAIN_MAX,
+                    },
+                    components: HashMap::new(),
+                };
+                events.push(event(
+                    event_id("strain"),
+                    timestamp,
+                    &source,
+                    Payload::Score { score },
+                ));
+            }
+            if let Some(kilojoule) = score.and_then(|s| num_field(s, "kilojoule")) {
+                let summary = SummaryPayload {
+                    period: SummaryPeriod::Daily,
+                    date: timestamp.date_naive().to_string(),
+                    metrics: HashMap::from([(
+                        "calories".to_string(),
+                        MetricValue::Number(kilojoule / 4.184),
+                    )]),
+                };
+                events.push(event(
+                    event_id("summary"),
+                    timestamp,
+                    &source,
+                    Payload::Summary { summary },
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(events
+        .into_iter()
+        .map(|e| finish(e, user_id.as_deref(), data))
+        .collect())
+}
+
+/// Convert a Garmin Health API push body to raw events
+pub fn garmin_webhook_to_raw_events(body: &str) -> Result<Vec<RawEvent>, ComputeError> {
+    let batch: Value = serde_json::from_str(body)?;
+    let Value::Object(kinds) = &batch else {
+        return Err(ComputeError::ParseError(
+            "Garmin webhook body must be an object".to_string(),
+        ));
+    };
+    let source = source(Provider::Garmin);
+    let mut events = Vec::new();
+
+    for (kind, records) in kinds {
+        let Some(records) = records.as_array() else {
+            continue;
+        };
+        for record in records.iter().filter(|r| r.is_object()) {
+            // Ping notifications carry a callback URL instead of data
+            if field(record, "callback_url").is_some() || field(record, "callbackURL").is_some() {
+                continue;
+            }
+            let user_id = field(record, "user_id").and_then(id_string);
+            let summary_id = field(record, "summary_id")
+                .and_then(id_string)
+                .unwrap_or_else(|| "unknown".to_string());
+            let event_id = |suffix: &str| format!("garmin-{summary_id}-{suffix}");
+            let start = field(record, "start_time_in_seconds")
+                .and_then(Value::as_i64)
+                .and_then(|s| Utc.timestamp_opt(s, 0).single());
+            let end = start
+                .zip(num_field(record, "duration_in_seconds"))
+                .map(|(start, duration)| start + chrono::Duration::seconds(duration as i64));
+            // Summaries are dated by calendar date; noon UTC keeps that date in any timezone
+            let date = str_field(record, "calendar_date")
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+            let noon = date
+                .and_then(|d| d.and_hms_opt(12, 0, 0))
+                .map(|t| t.and_utc());
+
+            let mut record_events = Vec::new();
+            match kind.as_str() {
+                "dailies" => {
+                    let (Some(date), Some(timestamp)) = (date, noon) else {
+                        return Err(ComputeError::MissingField("calendarDate".to_string()));
+                    };
+                    let active = num_field(record, "active_kilocalories");
+                    let total = active
+                        .zip(num_field(record, "bmr_kilocalories"))
+                        .map(|(a, b)| a + b);
+                    let moderate_minutes =
+                        num_field(record, "moderate_intensity_duration_in_seconds")
+                            .map(|s| s / 60.0);
+                    let vigorous_minutes =
+                        num_field(record, "vigorous_intensity_duration_in_seconds")
+                            .map(|s| s / 60.0);
+                    let intensity_minutes = moderate_minutes
+                        .into_iter()
+                        .chain(vigorous_minutes)
+                        .reduce(|a, b| a + b);
+                    let stress_minutes = |bucket: &str| {
+                        num_field(record, &format!("{bucket}_stress_duration_in_seconds"))
+                            .map(|s| s / 60.0)
+                    };
+                    let mut metrics = HashMap::new();
+                    for (key, value) in [
+                        ("steps", num_field(record, "steps")),
+                        ("distance_meters", num_field(record, "distance_in_meters")),
+                        ("floors_climbed", num_field(record, "floors_climbed")),
+                        ("active_calories", active),
+                        ("calories", total),
+                        ("resting_calories", num_field(record, "bmr_kilocalories")),
+                        ("active_minutes", intensity_minutes),
+                        ("moderate_intensity_minutes", moderate_minutes),
+                        ("vigorous_intensity_minutes", vigorous_minutes),
+                        (
+                            "resting_heart_rate",
+                            num_field(record, "resting_heart_rate_in_beats_per_minute"),
+                        ),
+                        (
+                            "body_battery",
+                            num_field(record, "body_battery_charged_value"),
+                        ),
+                        (
+                            "body_battery_charged",
+                            num_field(record, "body_battery_charged_value"),
+                        ),
+                        (
+                            "body_battery_drained",
+                            num_field(record, "body_battery_drained_value"),
+                        ),
+                        (
+                            "body_battery_highest",
+                            num_field(record, "body_battery_highest_value"),
+                        ),
+                        (
+                            "body_battery_lowest",
+                            num_field(record, "body_battery_lowest_value"),
+                        ),
+                        ("stress_avg", num_field(record, "average_stress_level")),
+                        ("stress_max", num_field(record, "max_stress_level")),
+                        ("rest_stress_minutes", stress_minutes("rest")),
+                        ("low_stress_minutes", stress_minutes("low")),
+                        ("medium_stress_minutes", stress_minutes("medium")),
+                        ("high_stress_minutes", stress_minutes("high")),
+                    ] {
+                        if let Some(value) = value {
+                            metrics.insert(key.to_string(), MetricValue::Number(value));
+                        }
+                    }
+                    let summary = SummaryPayload {
+                        period: SummaryPeriod::Daily,
+                        date: date.to_string(),
+                        metrics,
+                    };
+                    record_events.push(event(
+                        event_id("summary"),
+                        timestamp,
+                        &source,
+                        Payload::Summary { summary },
+                    ));
+                }
+                "userMetrics" | "user_metrics" => {
+                    let (Some(date), Some(timestamp)) = (date, noon) else {
+                        return Err(ComputeError::MissingField("calendarDate".to_string()));
+                    };
+                    let metrics: HashMap<_, _> = [
+                        ("vo2_max", num_field(record, "vo2_max")),
+                        ("fitness_age", num_field(record, "fitness_age")),
+                    ]
+                    .into_iter()
+                    .filter_map(|(key, value)| Some((key.to_string(), MetricValue::Number(value?))))
+                    .collect();
+                    if !metrics.is_empty() {
+                        let summary = SummaryPayload {
+                            period: SummaryPeriod::Daily,
+                            date: date.to_string(),
+                            metrics,
+                        };
+                        record_events.push(event(
+                            event_id("metrics"),
+                            timestamp,
+                            &source,
+                            Payload::Summary { summary },
+                        ));
+                    }
+                }
+                "sleeps" | "sleep" => {
+                    let (Some(start_time), Some(end_time)) = (start, end) else {
+                        return Err(ComputeError::MissingField(
+                            "startTimeInSeconds/durationInSeconds".to_string(),
+                        ));
+                    };
+                    let minutes = |name: &str| num_field(record, name).map(|s| s / 60.0);
+                    let light = minutes("light_sleep_duration_in_seconds");
+                    let deep = minutes("deep_sleep_duration_in_seconds");
+                    let rem = minutes("rem_sleep_in_seconds");
+                    let asleep = [light, deep, rem]
+                        .into_iter()
+                        .flatten()
+                        .reduce(|a, b| a + b);
+                    let mut metrics = HashMap::new();
+                    for (key, value) in [
+                        ("time_in_bed_minutes", minutes("duration_in_seconds")),
+                        ("total_sleep_minutes", asleep),
+                        ("light_sleep_minutes", light),
+                        ("deep_sleep_minutes", deep),
+                        ("rem_sleep_minutes", rem),
+                        ("awake_minutes", minutes("awake_duration_in_seconds")),
+                        (
+                            "sleep_score",
+                            field(record, "overall_sleep_score")
+                                .and_then(|s| num_field(s, "value")),
+                        ),
+                    ] {
+                        if let Some(value) = value {
+                            metrics.insert(key.to_string(), MetricValue::Number(value));
+                        }
+                    }
+                    let session = SessionPayload {
+                        session_type: SessionType::Sleep,
+                        start_time,
+                        end_time,
+                        metrics,
+                    };
+                    record_events.push(event(
+                        event_id("session"),
+                        end_time,
+                        &source,
+                        Payload::Session { session },
+                    ));
+                }
+                "hrv" => {
+                    let timestamp = end
+                        .or(noon)
+                        .ok_or_else(|| ComputeError::MissingField("calendarDate".to_string()))?;
+                    if let Some(value) = num_field(record, "last_night_avg") {
+                        let signal = SignalPayload {
+                            signal_type: SignalType::HeartRateVariability,
+                            value,
+                            unit: Unit::Ms,
+                            quality: None,
+                        };
+                        record_events.push(event(
+                            event_id("hrv"),
+                            timestamp,
+                            &source,
+                            Payload::Signal { signal },
+                        ));
+                    }
+                }
+                _ => {}
+            }
+            events.extend(
+                record_events
+                    .into_iter()
+                    .map(|e| finish(e, user_id.as_deref(), record)),
+            );
+        }
+    }
+
+    Ok(events)
+}
+
+fn source(provider: Provider) -> Source {
+    Source {
+        provider,
+        device_model: None,
+        device_id: None,
+        firmware_version: None,
+    }
+}
+
+fn event(
+    event_id: String,
+    timestamp: DateTime<Utc>,
+    source: &Source,
+    payload: Payload,
+) -> RawEvent {
+    let record_type = match &payload {
+        Payload::Signal { .. } => RecordType::Signal,
+        Payload::Session { .. } => RecordType::Session,
+        Payload::Summary { .. } => RecordType::Summary,
+        Payload::Score { .. } => RecordType::Score,
+        Payload::Series { .. } => RecordType::Series,
+    };
+    RawEvent {
+        schema_version: SCHEMA_VERSION.to_string(),
+        event_id: Some(event_id),
+        timestamp,
+        source: source.clone(),
+        user_id: None,
+        record_type,
+        payload,
+        context: None,
+        vendor_raw: None,
+    }
+}
+
+/// Attach the vendor user and the record the event came from
+fn finish(event: RawEvent, user_id: Option<&str>, record: &Value) -> RawEvent {
+    let event = event.with_vendor_raw(record.clone());
+    match user_id {
+        Some(user_id) => event.with_user_id(user_id),
+        None => event,
+    }
+}
+
+/// Look up `name` (snake_case) or its camelCase spelling
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value
+        .get(name)
+        .or_else(|| value.get(camel_case(name)))
+        .filter(|v| !v.is_null())
+}
+
+fn num_field(value: &Value, name: &str) -> Option<f64> {
+    field(value, name).and_then(Value::as_f64)
+}
+
+fn str_field<'a>(value: &'a Value, name: &str) -> Option<&'a str> {
+    field(value, name).and_then(Value::as_str)
+}
+
+fn time_field(value: &Value, name: &str) -> Option<DateTime<Utc>> {
+    str_field(value, name)
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Vendor IDs arrive as numbers or strings
+fn id_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::RawEventAdapter;
+
+    #[test]
+    fn test_whoop_envelopes_unwrap_to_events() {
+        let recovery = r#"{"type":"recovery.updated","user_id":10129,"data":{"cycleId":93845,"createdAt":"2024-01-15T07:00:00Z","score":{"recoveryScore":72,"restingHeartRate":51,"hrvRmssdMilli":68.5}}}"#;
+        let sleep = r#"{"type":"sleep.updated","user_id":10129,"data":{"id":"ecfc6a15","start":"2024-01-14T23:00:00Z","end":"2024-01-15T06:30:00Z","score":{"sleep_performance_percentage":88,"stage_summary":{"total_in_bed_time_milli":27000000,"total_sleep_time_milli":25200000,"total_awake_time_milli":1800000,"disturbance_count":2}}}}"#;
+        let ping = r#"{"type":"workout.updated","user_id":10129,"id":"abc"}"#;
+
+        let mut events = whoop_webhook_to_raw_events(recovery).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].user_id.as_deref(), Some("10129"));
+        assert_eq!(
+            events[0].event_id.as_deref(),
+            Some("whoop-recovery-93845-hrv")
+        );
+        events.extend(whoop_webhook_to_raw_events(sleep).unwrap());
+        assert!(whoop_webhook_to_raw_events(ping).unwrap().is_empty());
+
+        let days = RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].recovery.hrv_rmssd_ms, Some(68.5));
+        assert_eq!(days[0].recovery.vendor_recovery_score, Some(72.0));
+        assert_eq!(days[0].sleep.total_sleep_minutes, Some(420.0));
+        assert_eq!(days[0].sleep.awakenings, Some(2));
+    }
+
+    #[test]
+    fn test_garmin_push_batch() {
+        let body = r#"{
+            "dailies": [{"userId":"u-1","summaryId":"d-1","calendarDate":"2024-01-15","steps":8200,"activeKilocalories":450,"bmrKilocalories":1650,"moderateIntensityDurationInSeconds":1800,"vigorousIntensityDurationInSeconds":600,"restingHeartRateInBeatsPerMinute":54}],
+            "sleeps": [{"userId":"u-1","summaryId":"s-1","calendarDate":"2024-01-15","startTimeInSeconds":1705276800,"durationInSeconds":28800,"deepSleepDurationInSeconds":5400,"lightSleepDurationInSeconds":14400,"remSleepInSeconds":6000,"awakeDurationInSeconds":3000,"overallSleepScore":{"value":81}}],
+            "hrv": [{"userId":"u-1","callbackURL":"https://example.com/pull"}],
+            "userMetrics": [{"userId":"u-1","summaryId":"m-1","calendarDate":"2024-01-15","vo2Max":48.0,"fitnessAge":31}]
+        }"#;
+
+        let events = garmin_webhook_to_raw_events(body).unwrap();
+        assert_eq!(events.len(), 3);
+        let days = RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].activity.steps, Some(8200));
+        assert_eq!(days[0].activity.calories, Some(2100.0));
+        assert_eq!(days[0].activity.active_minutes, Some(40.0));
+        assert_eq!(days[0].activity.moderate_intensity_minutes, Some(30.0));
+        assert_eq!(days[0].activity.vigorous_intensity_minutes, Some(10.0));
+        assert_eq!(days[0].recovery.resting_hr_bpm, Some(54.0));
+        assert_eq!(days[0].sleep.total_sleep_minutes, Some(430.0));
+        assert_eq!(days[0].activity.vo2_max, Some(48.0));
+        assert_eq!(days[0].activity.fitness_age, Some(31.0));
+    }
+}
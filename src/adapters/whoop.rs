@@ -4,11 +4,13 @@
 
 use crate::error::ComputeError;
 use crate::types::{
-    CanonicalActivity, CanonicalRecovery, CanonicalSleep, CanonicalWearSignals, Vendor,
+    BodyBattery, CanonicalActivity, CanonicalBody, CanonicalRecovery, CanonicalSleep,
+    CanonicalWearSignals, CanonicalWorkout, HrZoneMinutes, RespirationSummary, StressSummary,
+    Vendor,
 };
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::VendorPayloadAdapter;
 
@@ -36,6 +38,7 @@ impl VendorPayloadAdapter for WhoopAdapter {
                     sleep: None,
                     recovery: None,
                     cycle: None,
+                    workouts: Vec::new(),
                 });
                 entry.sleep = Some(sleep);
             }
@@ -49,6 +52,7 @@ impl VendorPayloadAdapter for WhoopAdapter {
                     sleep: None,
                     recovery: None,
                     cycle: None,
+                    workouts: Vec::new(),
                 });
                 entry.recovery = Some(recovery);
             }
@@ -62,11 +66,26 @@ impl VendorPayloadAdapter for WhoopAdapter {
                     sleep: None,
                     recovery: None,
                     cycle: None,
+                    workouts: Vec::new(),
                 });
                 entry.cycle = Some(cycle);
             }
         }
 
+        // Process workout records (grouped by start date)
+        for workout in payload.workout.unwrap_or_default() {
+            if let Some(date) = extract_date_from_whoop_time(&workout.start) {
+                let entry = by_date.entry(date.clone()).or_insert_with(|| DayData {
+                    date,
+                    sleep: None,
+                    recovery: None,
+                    cycle: None,
+                    workouts: Vec::new(),
+                });
+                entry.workouts.push(workout);
+            }
+        }
+
         // Convert grouped data to canonical signals
         for (_date, day) in by_date {
             let canonical = convert_day_to_canonical(day, timezone, device_id)?;
@@ -86,6 +105,7 @@ struct DayData {
     sleep: Option<WhoopSleep>,
     recovery: Option<WhoopRecovery>,
     cycle: Option<WhoopCycle>,
+    workouts: Vec<WhoopWorkout>,
 }
 
 fn convert_day_to_canonical(
@@ -143,6 +163,7 @@ fn convert_day_to_canonical(
                 .as_ref()
                 .and_then(|sc| sc.sleep_performance_percentage),
             respiratory_rate: s.score.as_ref().and_then(|sc| sc.respiratory_rate),
+            hypnogram: Vec::new(),
         }
     } else {
         CanonicalSleep::default()
@@ -152,33 +173,70 @@ fn convert_day_to_canonical(
     let recovery = if let Some(r) = &day.recovery {
         CanonicalRecovery {
             hrv_rmssd_ms: r.score.as_ref().and_then(|sc| sc.hrv_rmssd_milli),
+            hrv_cv: None,
+            hrv_scale: None,
             resting_hr_bpm: r.score.as_ref().and_then(|sc| sc.resting_heart_rate),
             vendor_recovery_score: r.score.as_ref().and_then(|sc| sc.recovery_score),
             skin_temp_deviation_c: r.score.as_ref().and_then(|sc| sc.skin_temp_celsius),
             spo2_percentage: r.score.as_ref().and_then(|sc| sc.spo2_percentage),
+            cycle_phase: None,
+            body_battery: BodyBattery::default(),
+            stress: StressSummary::default(),
+            respiration: RespirationSummary::default(),
         }
     } else {
         CanonicalRecovery::default()
     };
 
+    let mut workouts: Vec<CanonicalWorkout> =
+        day.workouts.iter().filter_map(convert_workout).collect();
+    workouts.sort_by_key(|w| w.start_time);
+    // Cycle data has no duration or distance, so those come from workouts
+    let sum = |value: fn(&CanonicalWorkout) -> Option<f64>| {
+        workouts.iter().filter_map(value).reduce(|a, b| a + b)
+    };
+    let workout_minutes = sum(|w| Some(w.duration_minutes()));
+    let workout_distance = sum(|w| w.distance_meters);
+    let workout_calories = sum(|w| w.calories);
+
     // Build canonical activity
-    let activity = if let Some(c) = &day.cycle {
+    let mut activity = if let Some(c) = &day.cycle {
         CanonicalActivity {
             vendor_strain_score: c.score.as_ref().and_then(|sc| sc.strain),
             calories: c
                 .score
                 .as_ref()
                 .and_then(|sc| sc.kilojoule.map(|kj| kj * 0.239006)), // kJ to kcal
-            active_calories: None, // WHOOP doesn't separate active vs total in basic API
+            active_calories: None,  // Derived from workouts below
+            resting_calories: None, // Derived below
             average_hr_bpm: c.score.as_ref().and_then(|sc| sc.average_heart_rate),
             max_hr_bpm: c.score.as_ref().and_then(|sc| sc.max_heart_rate),
             distance_meters: None, // Not in WHOOP cycle data
             steps: None,           // WHOOP doesn't track steps
-            active_minutes: None,  // Could be derived from workouts
+            active_minutes: None,  // Derived from workouts below
+            moderate_intensity_minutes: None,
+            vigorous_intensity_minutes: None,
+            floors_climbed: None,
+            elevation_gain_meters: None,
+            workouts: Vec::new(),
+            hr_zone_minutes: HrZoneMinutes::default(),
+            vo2_max: None, // Not in the WHOOP API
+            fitness_age: None,
+            hourly: Vec::new(),
         }
     } else {
         CanonicalActivity::default()
     };
+    activity.active_minutes = workout_minutes;
+    activity.distance_meters = workout_distance;
+    // A cycle reports one energy total; what its workouts didn't burn counts as resting
+    activity.active_calories = workout_calories;
+    activity.resting_calories = activity
+        .calories
+        .zip(workout_calories)
+        .map(|(total, active)| (total - active).max(0.0));
+    activity.hr_zone_minutes = HrZoneMinutes::sum(workouts.iter().map(|w| &w.hr_zone_minutes));
+    activity.workouts = workouts;
 
     // Build vendor_raw with original data
     let mut vendor_raw = HashMap::new();
@@ -200,17 +258,99 @@ fn convert_day_to_canonical(
             serde_json::to_value(c).unwrap_or(serde_json::Value::Null),
         );
     }
+    if !day.workouts.is_empty() {
+        vendor_raw.insert(
+            "workout".to_string(),
+            serde_json::to_value(&day.workouts).unwrap_or(serde_json::Value::Null),
+        );
+    }
+
+    // Records are complete when the sleep ends, the recovery is scored and
+    // the cycle or workout ends
+    let cycle_end = day
+        .cycle
+        .as_ref()
+        .and_then(|c| parse_whoop_time(c.end.as_deref()?));
+    let latest_event_at = [
+        (
+            "sleep",
+            day.sleep.as_ref().and_then(|s| parse_whoop_time(&s.end)),
+        ),
+        (
+            "physiology",
+            day.recovery
+                .as_ref()
+                .and_then(|r| parse_whoop_time(&r.created_at)),
+        ),
+        (
+            "activity",
+            activity
+                .workouts
+                .iter()
+                .map(|w| w.end_time)
+                .chain(cycle_end)
+                .max(),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(domain, at)| Some((domain.to_string(), at?)))
+    .collect();
 
     Ok(CanonicalWearSignals {
         vendor: Vendor::Whoop,
         date: day.date,
         device_id: device_id.to_string(),
+        user_id: None,
         timezone: timezone.to_string(),
+        utc_offset_minutes: None,
         observed_at,
+        latest_event_at,
         sleep,
         recovery,
         activity,
+        body: CanonicalBody::default(),
+        input_quality: None,
         vendor_raw,
+        field_sources: HashMap::new(),
+        field_events: HashMap::new(),
+        intraday: BTreeMap::new(),
+        tags: Vec::new(),
+        warnings: Vec::new(),
+        unmapped_signals: BTreeMap::new(),
+    })
+}
+
+/// Convert a workout, skipping ones without a valid start and end
+fn convert_workout(w: &WhoopWorkout) -> Option<CanonicalWorkout> {
+    let start_time = parse_whoop_time(&w.start)?;
+    let end_time = parse_whoop_time(w.end.as_deref()?)?;
+    let score = w.score.as_ref();
+    let minutes = |milli: Option<i64>| milli.map(|m| m as f64 / 60_000.0);
+    let hr_zone_minutes = score
+        .and_then(|sc| sc.zone_duration.as_ref())
+        .map(|z| HrZoneMinutes {
+            zone_0: minutes(z.zone_zero_milli),
+            zone_1: minutes(z.zone_one_milli),
+            zone_2: minutes(z.zone_two_milli),
+            zone_3: minutes(z.zone_three_milli),
+            zone_4: minutes(z.zone_four_milli),
+            zone_5: minutes(z.zone_five_milli),
+        })
+        .unwrap_or_default();
+
+    Some(CanonicalWorkout {
+        start_time,
+        end_time,
+        sport: w
+            .sport_name
+            .clone()
+            .or_else(|| w.sport_id.map(|id| format!("whoop_sport_{id}"))),
+        vendor_strain_score: score.and_then(|sc| sc.strain),
+        calories: score.and_then(|sc| sc.kilojoule.map(|kj| kj * 0.239006)), // kJ to kcal
+        average_hr_bpm: score.and_then(|sc| sc.average_heart_rate),
+        max_hr_bpm: score.and_then(|sc| sc.max_heart_rate),
+        distance_meters: score.and_then(|sc| sc.distance_meter),
+        hr_zone_minutes,
     })
 }
 
@@ -234,6 +374,7 @@ struct WhoopPayload {
     sleep: Option<Vec<WhoopSleep>>,
     recovery: Option<Vec<WhoopRecovery>>,
     cycle: Option<Vec<WhoopCycle>>,
+    workout: Option<Vec<WhoopWorkout>>,
 }
 
 #[derive(Debug, Deserialize, serde::Serialize)]
@@ -297,6 +438,37 @@ struct WhoopCycleScore {
     max_heart_rate: Option<f64>,
 }
 
+#[derive(Debug, Deserialize, serde::Serialize)]
+struct WhoopWorkout {
+    /// Integer in API v1, UUID string in v2
+    id: Option<serde_json::Value>,
+    start: String,
+    end: Option<String>,
+    sport_id: Option<i64>,
+    sport_name: Option<String>,
+    score: Option<WhoopWorkoutScore>,
+}
+
+#[derive(Debug, Deserialize, serde::Serialize)]
+struct WhoopWorkoutScore {
+    strain: Option<f64>,
+    average_heart_rate: Option<f64>,
+    max_heart_rate: Option<f64>,
+    kilojoule: Option<f64>,
+    distance_meter: Option<f64>,
+    zone_duration: Option<WhoopZoneDuration>,
+}
+
+#[derive(Debug, Deserialize, serde::Serialize)]
+struct WhoopZoneDuration {
+    zone_zero_milli: Option<i64>,
+    zone_one_milli: Option<i64>,
+    zone_two_milli: Option<i64>,
+    zone_three_milli: Option<i64>,
+    zone_four_milli: Option<i64>,
+    zone_five_milli: Option<i64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,4 +534,75 @@ mod tests {
         assert!(sig.activity.vendor_strain_score.is_some());
         assert_eq!(sig.activity.vendor_strain_score.unwrap(), 12.5);
     }
+
+    #[test]
+    fn test_parse_workouts() {
+        let json = r#"{
+            "cycle": [{
+                "id": 1,
+                "start": "2024-01-15T06:30:00.000Z",
+                "end": "2024-01-15T22:30:00.000Z",
+                "score": {"strain": 12.5, "kilojoule": 8500.0}
+            }],
+            "workout": [{
+                "id": 7,
+                "start": "2024-01-15T17:00:00.000Z",
+                "end": "2024-01-15T17:45:00.000Z",
+                "sport_name": "running",
+                "score": {
+                    "strain": 10.5,
+                    "average_heart_rate": 150.0,
+                    "max_heart_rate": 178.0,
+                    "kilojoule": 2000.0,
+                    "distance_meter": 8000.0,
+                    "zone_duration": {
+                        "zone_two_milli": 600000,
+                        "zone_four_milli": 1200000
+                    }
+                }
+            }, {
+                "id": 8,
+                "start": "2024-01-15T07:00:00.000Z",
+                "end": "2024-01-15T07:30:00.000Z",
+                "sport_id": 45
+            }]
+        }"#;
+
+        let signals = WhoopAdapter
+            .parse(json, "America/New_York", "device-123")
+            .unwrap();
+        assert_eq!(signals.len(), 1);
+        let activity = &signals[0].activity;
+        assert_eq!(activity.workouts.len(), 2);
+        assert_eq!(activity.active_minutes, Some(75.0));
+        assert_eq!(activity.distance_meters, Some(8000.0));
+        // Only the run reports energy; the rest of the cycle's total is resting
+        assert!((activity.active_calories.unwrap() - 478.012).abs() < 1e-6);
+        assert!((activity.resting_calories.unwrap() - (8500.0 * 0.239006 - 478.012)).abs() < 1e-6);
+
+        // Sorted by start time
+        let run = &activity.workouts[1];
+        assert_eq!(
+            activity.workouts[0].sport.as_deref(),
+            Some("whoop_sport_45")
+        );
+        assert_eq!(run.sport.as_deref(), Some("running"));
+        assert!((run.calories.unwrap() - 478.012).abs() < 1e-6);
+        assert_eq!(run.hr_zone_minutes.zone_2, Some(10.0));
+        assert_eq!(run.hr_zone_minutes.zone_4, Some(20.0));
+        assert_eq!(run.hr_zone_minutes.zone_1, None);
+        assert_eq!(activity.hr_zone_minutes.zone_4, Some(20.0));
+
+        let hsi = crate::whoop_to_hsi_daily(
+            json.to_string(),
+            "America/New_York".to_string(),
+            "device-123".to_string(),
+        )
+        .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&hsi[0]).unwrap();
+        let workouts = &payload["windows"][0]["activity"]["workouts"];
+        assert_eq!(workouts[1]["duration_minutes"], 45.0);
+        assert_eq!(workouts[1]["strain_score"], 0.5);
+        assert_eq!(workouts[1]["hr_zone_minutes"]["zone_4"], 20.0);
+    }
 }
@@ -1,28 +1,516 @@
 //! Baseline management
 //!
 //! This module manages rolling baselines for HRV, RHR, and sleep metrics.
-//! Baselines enable relative interpretation of daily signals.
+//! Baselines enable relative interpretation of daily signals. VO2max and body
+//! composition change over weeks, so their baselines cover slow calendar
+//! windows instead. HRV, RHR and sleep duration also carry a trend: their
+//! least-squares slope over the last [`TREND_DAYS`] days.
+//!
+//! The store keeps one entry per processed day ([`BaselineDay`]); each
+//! metric's rolling window is its most recent values across those days, so a
+//! single day can be corrected or removed.
+//!
+//! Days carrying context tags (e.g. `travel`, `sick`) can be kept out of the
+//! baseline or given a baseline of their own via [`BaselineConfig`], which
+//! can also enable CUSUM change-point detection ([`ChangePointConfig`]) to
+//! flag sustained shifts the rolling mean would otherwise absorb.
+//!
+//! The store also notices when days start coming from another device (vendor
+//! or device ID) and, per [`DeviceSwitchPolicy`], keeps, resets or parks the
+//! old device's baseline.
 
-use crate::types::{Baselines, ContextualSignals, DerivedSignals};
+use crate::calibration::HrvOverlaps;
+use crate::normalizer::{linear_scores, ScoreHistory};
+use crate::types::{Baselines, CanonicalWearSignals, ContextualSignals, DerivedSignals, Vendor};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 /// Default baseline window in days
 pub const DEFAULT_BASELINE_WINDOW: usize = 14;
 
+/// Default z-score magnitude above which a day is flagged as anomalous
+pub const DEFAULT_ANOMALY_Z_THRESHOLD: f64 = 2.0;
+
+/// Minimum number of baseline values before z-scores are computed
+pub const MIN_ZSCORE_SAMPLES: usize = 3;
+
+/// Baselines and standard deviations below this are treated as zero, which
+/// keeps deviations and z-scores finite
+const MIN_BASELINE_MAGNITUDE: f64 = 1e-6;
+
+/// Days covered by the rolling goal attainment
+pub const GOAL_TREND_DAYS: i64 = 7;
+
+/// Days covered by the HRV, RHR and sleep trends
+pub const TREND_DAYS: i64 = 7;
+
+/// Minimum number of values a trend is fitted to
+pub const MIN_TREND_SAMPLES: usize = 3;
+
+/// Default per-day slack of the CUSUM detector, in baseline standard deviations
+pub const DEFAULT_CUSUM_SLACK: f64 = 0.5;
+
+/// Default CUSUM alarm threshold, in baseline standard deviations
+pub const DEFAULT_CUSUM_THRESHOLD: f64 = 4.0;
+
+/// Largest deviation one day adds to the CUSUM sums, so a single outlier
+/// cannot flag a shift at the default settings
+const MAX_CUSUM_ZSCORE: f64 = 3.0;
+
+/// Days covered by the slow VO2max baseline
+pub const VO2_MAX_BASELINE_DAYS: i64 = 90;
+
+/// Days covered by the slow weight and body fat baselines
+pub const BODY_BASELINE_DAYS: i64 = 30;
+
+/// Current serialization version of [`BaselineStore`]
+pub const BASELINE_STORE_VERSION: u32 = 3;
+
+/// Baseline metrics recorded for one day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayMetrics {
+    /// HRV (ms, RMSSD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hrv_ms: Option<f64>,
+    /// Resting heart rate (bpm)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rhr_bpm: Option<f64>,
+    /// Sleep duration (minutes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleep_minutes: Option<f64>,
+    /// Sleep efficiency (0-1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleep_efficiency: Option<f64>,
+    /// Activity goal attainment (0-1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal_attainment: Option<f64>,
+    /// VO2max (ml/kg/min)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vo2_max: Option<f64>,
+    /// Body weight (kg)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_kg: Option<f64>,
+    /// Body fat (percentage)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_fat_percentage: Option<f64>,
+    /// Vendor sleep score, scaled linearly to 0-1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleep_score: Option<f64>,
+    /// Vendor recovery score, scaled linearly to 0-1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery_score: Option<f64>,
+    /// Vendor strain score, scaled linearly to 0-1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strain_score: Option<f64>,
+    /// Vendor whose HRV scale `hrv_ms` is on, when HRV calibration is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hrv_scale: Option<Vendor>,
+}
+
+impl DayMetrics {
+    /// Baseline metrics of a processed day
+    pub fn from_derived(derived: &DerivedSignals) -> Self {
+        let canonical = &derived.normalized.canonical;
+        let [sleep_score, recovery_score, strain_score] = linear_scores(canonical);
+        Self {
+            hrv_ms: canonical.recovery.hrv_rmssd_ms,
+            rhr_bpm: canonical.recovery.resting_hr_bpm,
+            sleep_minutes: canonical.sleep.total_sleep_minutes,
+            sleep_efficiency: derived.sleep_efficiency,
+            goal_attainment: derived.goal_attainment,
+            vo2_max: canonical.activity.vo2_max,
+            weight_kg: canonical.body.weight_kg,
+            body_fat_percentage: canonical.body.body_fat_percentage,
+            sleep_score,
+            recovery_score,
+            strain_score,
+            hrv_scale: canonical
+                .recovery
+                .hrv_rmssd_ms
+                .and(canonical.recovery.hrv_scale),
+        }
+    }
+
+    /// Whether no metric is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Clear the metrics a wearable measures, keeping body composition
+    /// (usually from a separate scale)
+    fn clear_device_metrics(&mut self) {
+        *self = Self {
+            weight_kg: self.weight_kg,
+            body_fat_percentage: self.body_fat_percentage,
+            ..Self::default()
+        };
+    }
+}
+
+/// One day's entry in the store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineDay {
+    /// Day the metrics belong to (absent for days migrated from v1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// Vendor the metrics came from (absent for days recorded without one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    #[serde(flatten)]
+    pub metrics: DayMetrics,
+}
+
+impl BaselineDay {
+    /// Whether this entry holds `vendor`'s metrics for `date`. Entries
+    /// without a vendor, and a `vendor` of `None`, match any vendor.
+    fn is_entry(&self, date: &str, vendor: Option<&str>) -> bool {
+        self.date.as_deref() == Some(date)
+            && (vendor.is_none() || self.vendor.is_none() || self.vendor.as_deref() == vendor)
+    }
+}
+
+/// v1/v2 queue entry: v1 stores bare numbers, v2 stores dated objects
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QueueEntry {
+    Dated { date: Option<String>, value: f64 },
+    Plain(f64),
+}
+
+impl QueueEntry {
+    fn into_parts(self) -> (Option<String>, f64) {
+        match self {
+            QueueEntry::Dated { date, value } => (date, value),
+            QueueEntry::Plain(value) => (None, value),
+        }
+    }
+}
+
+/// On-disk store: v3 keeps per-day entries, v1/v2 kept one queue per metric
+#[derive(Deserialize)]
+struct StoredStore {
+    #[serde(default = "default_store_version")]
+    version: u32,
+    #[serde(default)]
+    days: VecDeque<BaselineDay>,
+    #[serde(default)]
+    hrv_values: Vec<QueueEntry>,
+    #[serde(default)]
+    rhr_values: Vec<QueueEntry>,
+    #[serde(default)]
+    sleep_duration_values: Vec<QueueEntry>,
+    #[serde(default)]
+    sleep_efficiency_values: Vec<QueueEntry>,
+    window_size: usize,
+    #[serde(default = "default_anomaly_threshold")]
+    anomaly_threshold: f64,
+    #[serde(default)]
+    revisions: BTreeMap<String, u32>,
+    #[serde(default)]
+    tag_baselines: BTreeMap<String, BaselineStore>,
+    #[serde(default)]
+    user_baselines: BTreeMap<String, BaselineStore>,
+    #[serde(default)]
+    hrv_overlaps: HrvOverlaps,
+    #[serde(default)]
+    device: Option<String>,
+    #[serde(default)]
+    device_switched_on: Option<String>,
+    #[serde(default)]
+    device_baselines: BTreeMap<String, VecDeque<BaselineDay>>,
+    #[serde(default)]
+    last_date: Option<String>,
+    #[serde(default)]
+    instance_id: Option<String>,
+    #[serde(default)]
+    last_utc_offset_minutes: Option<i32>,
+}
+
+impl From<StoredStore> for BaselineStore {
+    fn from(stored: StoredStore) -> Self {
+        let days = if stored.version >= 3 {
+            stored.days
+        } else {
+            days_from_queues([
+                stored.hrv_values,
+                stored.rhr_values,
+                stored.sleep_duration_values,
+                stored.sleep_efficiency_values,
+            ])
+        };
+        // Stores saved before the last date was tracked: use their newest day
+        let last_date = stored
+            .last_date
+            .or_else(|| days.iter().filter_map(|d| d.date.clone()).max());
+        Self {
+            version: BASELINE_STORE_VERSION,
+            days,
+            window_size: stored.window_size,
+            anomaly_threshold: stored.anomaly_threshold,
+            revisions: stored.revisions,
+            tag_baselines: stored.tag_baselines,
+            user_baselines: stored.user_baselines,
+            hrv_overlaps: stored.hrv_overlaps,
+            device: stored.device,
+            device_switched_on: stored.device_switched_on,
+            device_baselines: stored.device_baselines,
+            last_date,
+            instance_id: stored.instance_id,
+            last_utc_offset_minutes: stored.last_utc_offset_minutes,
+            config: BaselineConfig::default(),
+        }
+    }
+}
+
+/// Rebuild per-day entries from v1/v2 metric queues.
+///
+/// Dated values are grouped by date (the n-th value for a date in each queue
+/// joins the n-th entry for that date). Undated v1 values are aligned on their
+/// most recent value and placed before the dated days.
+fn days_from_queues(queues: [Vec<QueueEntry>; 4]) -> VecDeque<BaselineDay> {
+    let setters: [fn(&mut DayMetrics, f64); 4] = [
+        |m, v| m.hrv_ms = Some(v),
+        |m, v| m.rhr_bpm = Some(v),
+        |m, v| m.sleep_minutes = Some(v),
+        |m, v| m.sleep_efficiency = Some(v),
+    ];
+    let undated_count = |queue: &Vec<QueueEntry>| {
+        queue
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e,
+                    QueueEntry::Plain(_) | QueueEntry::Dated { date: None, .. }
+                )
+            })
+            .count()
+    };
+    let undated_len = queues.iter().map(undated_count).max().unwrap_or(0);
+    let mut undated = vec![DayMetrics::default(); undated_len];
+    let mut dated: BTreeMap<(String, usize), DayMetrics> = BTreeMap::new();
+
+    for (queue, set) in queues.into_iter().zip(setters) {
+        let mut slot = undated_len - undated_count(&queue);
+        let mut occurrences: HashMap<String, usize> = HashMap::new();
+        for entry in queue {
+            match entry.into_parts() {
+                (None, value) => {
+                    set(&mut undated[slot], value);
+                    slot += 1;
+                }
+                (Some(date), value) => {
+                    let n = occurrences.entry(date.clone()).or_default();
+                    set(dated.entry((date, *n)).or_default(), value);
+                    *n += 1;
+                }
+            }
+        }
+    }
+
+    undated
+        .into_iter()
+        .map(|metrics| BaselineDay {
+            date: None,
+            vendor: None,
+            metrics,
+        })
+        .chain(dated.into_iter().map(|((date, _), metrics)| BaselineDay {
+            date: Some(date),
+            vendor: None,
+            metrics,
+        }))
+        .collect()
+}
+
+/// How days carrying a context tag feed the baselines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagPolicy {
+    /// Compare the day against the baseline without accumulating it
+    Exclude,
+    /// Compare and accumulate the day in a separate baseline for the tag
+    Separate,
+}
+
+/// What happens to the baseline when days start coming from another device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceSwitchPolicy {
+    /// Keep accumulating into the same baseline
+    #[default]
+    Continue,
+    /// Drop the wearable metrics recorded so far; body composition is kept
+    Reset,
+    /// Keep a baseline per device, resuming a device's baseline when it returns
+    Parallel,
+}
+
+/// Two-sided CUSUM change-point detection on HRV, RHR and sleep duration.
+///
+/// Each day's deviation from the rolling baseline before it, in standard
+/// deviations (capped at 3), is accumulated once it exceeds `slack`; a shift
+/// is flagged while either sum is above `threshold`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChangePointConfig {
+    /// Deviation per day absorbed before accumulating (standard deviations)
+    pub slack: f64,
+    /// Accumulated deviation at which a shift is flagged (standard deviations)
+    pub threshold: f64,
+}
+
+impl Default for ChangePointConfig {
+    fn default() -> Self {
+        Self {
+            slack: DEFAULT_CUSUM_SLACK,
+            threshold: DEFAULT_CUSUM_THRESHOLD,
+        }
+    }
+}
+
+impl ChangePointConfig {
+    /// Set the per-day slack
+    pub fn with_slack(mut self, slack: f64) -> Self {
+        self.slack = slack;
+        self
+    }
+
+    /// Set the alarm threshold
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+/// Baseline accumulation settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BaselineConfig {
+    /// Policy per context tag; days without a listed tag accumulate normally
+    pub tag_policies: BTreeMap<String, TagPolicy>,
+    /// Change-point detection, off when unset
+    pub change_point: Option<ChangePointConfig>,
+    /// Handling of the baseline when the device changes
+    pub device_switch: DeviceSwitchPolicy,
+}
+
+impl BaselineConfig {
+    /// Enable change-point detection
+    pub fn with_change_point(mut self, config: ChangePointConfig) -> Self {
+        self.change_point = Some(config);
+        self
+    }
+
+    /// Set how the baseline is handled when the device changes
+    pub fn with_device_switch(mut self, policy: DeviceSwitchPolicy) -> Self {
+        self.device_switch = policy;
+        self
+    }
+
+    /// Set the policy for days tagged `tag`
+    pub fn with_tag_policy(mut self, tag: impl Into<String>, policy: TagPolicy) -> Self {
+        self.tag_policies.insert(tag.into(), policy);
+        self
+    }
+
+    /// Policy for a day with the given tags.
+    ///
+    /// `Exclude` wins over `Separate`; among several separated tags the first
+    /// in sort order is used.
+    pub fn policy_for(&self, tags: &[String]) -> Option<(String, TagPolicy)> {
+        let mut matched: Vec<_> = tags
+            .iter()
+            .filter_map(|tag| self.tag_policies.get(tag).map(|p| (tag, *p)))
+            .collect();
+        matched.sort_by_key(|(tag, policy)| (*policy != TagPolicy::Exclude, *tag));
+        matched
+            .first()
+            .map(|(tag, policy)| (tag.to_string(), *policy))
+    }
+}
+
+fn default_store_version() -> u32 {
+    1
+}
+
+/// Accessor for one metric of a day
+type MetricFn = fn(&DayMetrics) -> Option<f64>;
+
+/// Metrics baselined over slow calendar windows, with the window length in days
+const SLOW_METRICS: [(MetricFn, i64); 3] = [
+    (|m| m.vo2_max, VO2_MAX_BASELINE_DAYS),
+    (|m| m.weight_kg, BODY_BASELINE_DAYS),
+    (|m| m.body_fat_percentage, BODY_BASELINE_DAYS),
+];
+
+/// Least-squares slope of `(x, y)` points (`None` when x does not vary)
+pub fn linear_slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    (points.len() >= 2 && sxx > 0.0).then(|| sxy / sxx)
+}
+
+fn day_date(day: &BaselineDay) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(day.date.as_deref()?, "%Y-%m-%d").ok()
+}
+
+fn default_anomaly_threshold() -> f64 {
+    DEFAULT_ANOMALY_Z_THRESHOLD
+}
+
 /// Baseline store for managing rolling averages
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredStore")]
 pub struct BaselineStore {
-    /// Rolling HRV values (ms)
-    hrv_values: VecDeque<f64>,
-    /// Rolling RHR values (bpm)
-    rhr_values: VecDeque<f64>,
-    /// Rolling sleep duration values (minutes)
-    sleep_duration_values: VecDeque<f64>,
-    /// Rolling sleep efficiency values (0-1)
-    sleep_efficiency_values: VecDeque<f64>,
-    /// Maximum window size
+    /// Serialization version
+    version: u32,
+    /// Processed days, oldest first; days no metric window reaches are dropped
+    days: VecDeque<BaselineDay>,
+    /// Number of values per metric in the rolling window
     window_size: usize,
+    /// Z-score magnitude above which a metric is flagged as anomalous
+    anomaly_threshold: f64,
+    /// Revision counter per reprocessed date
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    revisions: BTreeMap<String, u32>,
+    /// Separate baselines for days with a `Separate` tag policy
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    tag_baselines: BTreeMap<String, BaselineStore>,
+    /// Separate baselines for the days of each `user_id`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    user_baselines: BTreeMap<String, BaselineStore>,
+    /// HRV per date and vendor, for fitting cross-vendor calibrations
+    #[serde(skip_serializing_if = "HrvOverlaps::is_empty")]
+    hrv_overlaps: HrvOverlaps,
+    /// Device (`vendor:device_id`) of the most recently processed day
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device: Option<String>,
+    /// Date of the most recent device switch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_switched_on: Option<String>,
+    /// Days of devices not in use, kept under the `Parallel` switch policy
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    device_baselines: BTreeMap<String, VecDeque<BaselineDay>>,
+    /// Latest date processed, so older days can be rejected across batches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_date: Option<String>,
+    /// Producer instance ID pinned by the host, kept across restarts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance_id: Option<String>,
+    /// Event-context UTC offset of the most recent day that had one, so
+    /// timezone shifts are detected across restarts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_utc_offset_minutes: Option<i32>,
+    /// Tag policies (runtime setting, not persisted)
+    #[serde(skip)]
+    config: BaselineConfig,
 }
 
 impl Default for BaselineStore {
@@ -35,105 +523,772 @@ impl BaselineStore {
     /// Create a new baseline store with specified window size
     pub fn new(window_size: usize) -> Self {
         Self {
-            hrv_values: VecDeque::with_capacity(window_size),
-            rhr_values: VecDeque::with_capacity(window_size),
-            sleep_duration_values: VecDeque::with_capacity(window_size),
-            sleep_efficiency_values: VecDeque::with_capacity(window_size),
+            version: BASELINE_STORE_VERSION,
+            days: VecDeque::with_capacity(window_size),
             window_size,
+            anomaly_threshold: DEFAULT_ANOMALY_Z_THRESHOLD,
+            revisions: BTreeMap::new(),
+            tag_baselines: BTreeMap::new(),
+            user_baselines: BTreeMap::new(),
+            hrv_overlaps: HrvOverlaps::default(),
+            device: None,
+            device_switched_on: None,
+            device_baselines: BTreeMap::new(),
+            last_date: None,
+            instance_id: None,
+            last_utc_offset_minutes: None,
+            config: BaselineConfig::default(),
         }
     }
 
+    /// Set the z-score magnitude above which a metric is flagged as anomalous
+    pub fn with_anomaly_threshold(mut self, threshold: f64) -> Self {
+        self.anomaly_threshold = threshold;
+        self
+    }
+
+    /// Apply tag policies (builder form of [`Self::set_config`])
+    pub fn with_config(mut self, config: BaselineConfig) -> Self {
+        self.set_config(config);
+        self
+    }
+
+    /// Apply tag policies to days processed from now on
+    pub fn set_config(&mut self, config: BaselineConfig) {
+        self.config = config;
+    }
+
+    /// Current tag policies
+    pub fn config(&self) -> &BaselineConfig {
+        &self.config
+    }
+
+    /// Baselines a day with the given tags is compared against
+    pub fn baselines_for(&self, tags: &[String]) -> Baselines {
+        self.baselines_for_day(tags, None)
+    }
+
+    /// Baselines a day with the given tags and HRV scale is compared against
+    pub fn baselines_for_day(&self, tags: &[String], hrv_scale: Option<Vendor>) -> Baselines {
+        match self.config.policy_for(tags) {
+            Some((tag, TagPolicy::Separate)) => {
+                let mut baselines = self
+                    .tag_baselines
+                    .get(&tag)
+                    .map(|store| store.get_baselines_for_hrv_scale(hrv_scale))
+                    .unwrap_or_default();
+                baselines.tag = Some(tag);
+                baselines
+            }
+            _ => self.get_baselines_for_hrv_scale(hrv_scale),
+        }
+    }
+
+    /// HRV per date and vendor seen so far, for fitting calibrations
+    pub fn hrv_overlaps(&self) -> &HrvOverlaps {
+        &self.hrv_overlaps
+    }
+
+    /// Record days' uncalibrated HRV for fitting calibrations
+    pub fn record_hrv_overlaps(&mut self, days: &[CanonicalWearSignals]) {
+        self.hrv_overlaps.record(days);
+    }
+
     /// Update baselines with new derived signals and return contextual signals
     pub fn update_and_contextualize(&mut self, derived: DerivedSignals) -> ContextualSignals {
-        // Get current baselines before update (for deviation calculation)
-        let old_baselines = self.get_baselines();
+        let date = &derived.normalized.canonical.date;
+        if self.last_date.as_ref() < Some(date) {
+            self.last_date = Some(date.clone());
+        }
+        self.track_device(&derived.normalized.canonical);
+        let contextual = self.accumulate_and_contextualize(derived);
+        self.annotate_device_switch(contextual)
+    }
 
-        // Calculate deviations from baseline (compare current day to historical baseline)
-        let hrv_deviation_pct = self.calculate_deviation(
-            derived.normalized.canonical.recovery.hrv_rmssd_ms,
-            old_baselines.hrv_baseline_ms,
-        );
+    /// Record the day's device, applying the switch policy when it changed
+    fn track_device(&mut self, canonical: &CanonicalWearSignals) {
+        let device = format!("{}:{}", canonical.vendor.as_str(), canonical.device_id);
+        let Some(previous) = self.device.replace(device.clone()) else {
+            return;
+        };
+        if previous == device {
+            return;
+        }
+        self.device_switched_on = Some(canonical.date.clone());
+        match self.config.device_switch {
+            DeviceSwitchPolicy::Continue => {}
+            DeviceSwitchPolicy::Reset => self.clear_device_metrics(),
+            DeviceSwitchPolicy::Parallel => {
+                let resumed = self.device_baselines.remove(&device).unwrap_or_default();
+                let parked = std::mem::replace(&mut self.days, resumed);
+                if !parked.is_empty() {
+                    self.device_baselines.insert(previous, parked);
+                }
+            }
+        }
+    }
 
-        let rhr_deviation_pct = self.calculate_deviation(
-            derived.normalized.canonical.recovery.resting_hr_bpm,
-            old_baselines.rhr_baseline_bpm,
-        );
+    /// Drop wearable metrics from every day, including separate tag baselines
+    fn clear_device_metrics(&mut self) {
+        for day in &mut self.days {
+            day.metrics.clear_device_metrics();
+        }
+        self.days.retain(|d| !d.metrics.is_empty());
+        for store in self.tag_baselines.values_mut() {
+            store.clear_device_metrics();
+        }
+    }
 
-        let sleep_duration_deviation_pct = self.calculate_deviation(
-            derived.normalized.canonical.sleep.total_sleep_minutes,
-            old_baselines.sleep_baseline_minutes,
-        );
+    /// Annotate a day with the most recent device switch on or before it
+    fn annotate_device_switch(&self, mut contextual: ContextualSignals) -> ContextualSignals {
+        let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        let days_since = self
+            .device_switched_on
+            .as_deref()
+            .and_then(parse)
+            .zip(parse(&contextual.derived.normalized.canonical.date))
+            .and_then(|(switched, date)| u32::try_from((date - switched).num_days()).ok());
+        contextual.device_changed = days_since.map(|days| days == 0);
+        contextual.days_since_device_switch = days_since;
+        contextual
+    }
 
-        // Update rolling values with current data
-        if let Some(hrv) = derived.normalized.canonical.recovery.hrv_rmssd_ms {
-            self.hrv_values.push_back(hrv);
-            while self.hrv_values.len() > self.window_size {
-                self.hrv_values.pop_front();
+    fn accumulate_and_contextualize(&mut self, derived: DerivedSignals) -> ContextualSignals {
+        let hrv_scale = derived.normalized.canonical.recovery.hrv_scale;
+        match self.config.policy_for(&derived.normalized.canonical.tags) {
+            Some((_, TagPolicy::Exclude)) => {
+                return self.contextualize(derived, self.get_baselines_for_hrv_scale(hrv_scale));
+            }
+            Some((tag, TagPolicy::Separate)) => {
+                let mut contextual = self.tag_store(&tag).accumulate_and_contextualize(derived);
+                contextual.baselines.tag = Some(tag);
+                return contextual;
             }
+            None => {}
         }
 
-        if let Some(rhr) = derived.normalized.canonical.recovery.resting_hr_bpm {
-            self.rhr_values.push_back(rhr);
-            while self.rhr_values.len() > self.window_size {
-                self.rhr_values.pop_front();
-            }
+        // Get current baselines before update (for deviation calculation)
+        let old_baselines = self.get_baselines_for_hrv_scale(hrv_scale);
+        let contextual = self.contextualize(derived, old_baselines);
+
+        let metrics = DayMetrics::from_derived(&contextual.derived);
+        if !metrics.is_empty() {
+            self.days.push_back(BaselineDay {
+                date: Some(contextual.derived.normalized.canonical.date.clone()),
+                vendor: Some(
+                    contextual
+                        .derived
+                        .normalized
+                        .canonical
+                        .vendor
+                        .as_str()
+                        .to_string(),
+                ),
+                metrics,
+            });
+            self.prune();
+        }
+
+        // Get updated baselines (including current data) for the output
+        ContextualSignals {
+            baselines: self.get_baselines_for_hrv_scale(hrv_scale),
+            ..contextual
         }
+    }
 
-        if let Some(sleep) = derived.normalized.canonical.sleep.total_sleep_minutes {
-            self.sleep_duration_values.push_back(sleep);
-            while self.sleep_duration_values.len() > self.window_size {
-                self.sleep_duration_values.pop_front();
+    /// Replace the baseline entry for an already-processed day and return
+    /// contextual signals for it.
+    ///
+    /// Deviations are computed against the days before it, as when the day
+    /// was first processed. Only the entry of the day's vendor is replaced;
+    /// days that were never recorded are inserted in date order.
+    pub fn replace_and_contextualize(&mut self, derived: DerivedSignals) -> ContextualSignals {
+        let contextual = self.replace_day_and_contextualize(derived);
+        self.annotate_device_switch(contextual)
+    }
+
+    fn replace_day_and_contextualize(&mut self, derived: DerivedSignals) -> ContextualSignals {
+        let date = derived.normalized.canonical.date.clone();
+        let vendor = derived.normalized.canonical.vendor.as_str();
+        let hrv_scale = derived.normalized.canonical.recovery.hrv_scale;
+
+        // The day's tags may have changed, so drop it from every other baseline
+        let policy = self.config.policy_for(&derived.normalized.canonical.tags);
+        let separate_tag = match &policy {
+            Some((tag, TagPolicy::Separate)) => Some(tag.as_str()),
+            _ => None,
+        };
+        for (tag, store) in &mut self.tag_baselines {
+            if Some(tag.as_str()) != separate_tag {
+                store.remove_vendor_day(&date, vendor);
             }
         }
+        match policy {
+            Some((_, TagPolicy::Exclude)) => {
+                self.remove_vendor_day(&date, vendor);
+                return self.contextualize(derived, self.get_baselines_for_hrv_scale(hrv_scale));
+            }
+            Some((tag, TagPolicy::Separate)) => {
+                self.remove_vendor_day(&date, vendor);
+                let mut contextual = self.tag_store(&tag).replace_day_and_contextualize(derived);
+                contextual.baselines.tag = Some(tag);
+                return contextual;
+            }
+            None => {}
+        }
+
+        let baselines = self
+            .days_before(&date)
+            .get_baselines_for_hrv_scale(hrv_scale);
+        let vendor = vendor.to_string();
+        let contextual = self.contextualize(derived, baselines);
+
+        let metrics = DayMetrics::from_derived(&contextual.derived);
+        self.replace_entries(&date, Some(&vendor), metrics);
+
+        ContextualSignals {
+            baselines: self.get_baselines_for_hrv_scale(hrv_scale),
+            ..contextual
+        }
+    }
+
+    /// Separate baseline for a tag, created on first use
+    fn tag_store(&mut self, tag: &str) -> &mut BaselineStore {
+        let (window_size, threshold) = (self.window_size, self.anomaly_threshold);
+        let store = self
+            .tag_baselines
+            .entry(tag.to_string())
+            .or_insert_with(|| BaselineStore::new(window_size).with_anomaly_threshold(threshold));
+        store.config.change_point = self.config.change_point;
+        store
+    }
+
+    /// Baselines of a user's days, created on first use; days without a
+    /// user go to this store
+    pub fn user_store_mut(&mut self, user_id: Option<&str>) -> &mut BaselineStore {
+        let Some(user_id) = user_id else {
+            return self;
+        };
+        let (window_size, threshold) = (self.window_size, self.anomaly_threshold);
+        let store = self
+            .user_baselines
+            .entry(user_id.to_string())
+            .or_insert_with(|| BaselineStore::new(window_size).with_anomaly_threshold(threshold));
+        store.config = self.config.clone();
+        store
+    }
+
+    /// Baselines of a user's days, if any were processed
+    pub fn user_store(&self, user_id: Option<&str>) -> Option<&BaselineStore> {
+        match user_id {
+            Some(user_id) => self.user_baselines.get(user_id),
+            None => Some(self),
+        }
+    }
+
+    /// Processed days in the store, oldest first
+    pub fn days(&self) -> impl Iterator<Item = &BaselineDay> {
+        self.days.iter()
+    }
+
+    /// Remove a day from the baselines, returning whether it was recorded
+    pub fn remove_day(&mut self, date: &str) -> bool {
+        self.remove_entries(date, None)
+    }
+
+    /// Remove one vendor's entry for a day, keeping other vendors' entries
+    pub fn remove_vendor_day(&mut self, date: &str, vendor: &str) -> bool {
+        self.remove_entries(date, Some(vendor))
+    }
+
+    fn remove_entries(&mut self, date: &str, vendor: Option<&str>) -> bool {
+        let before = self.days.len();
+        self.days.retain(|d| !d.is_entry(date, vendor));
+        self.days.len() != before
+    }
 
-        if let Some(efficiency) = derived.sleep_efficiency {
-            self.sleep_efficiency_values.push_back(efficiency);
-            while self.sleep_efficiency_values.len() > self.window_size {
-                self.sleep_efficiency_values.pop_front();
+    /// Set the metrics recorded for a day, collapsing every vendor's entry
+    /// into one.
+    ///
+    /// A day that was never recorded is inserted in date order; empty metrics
+    /// remove the day.
+    pub fn replace_day(&mut self, date: &str, metrics: DayMetrics) {
+        self.replace_entries(date, None, metrics);
+    }
+
+    /// Set one vendor's metrics for a day, keeping other vendors' entries
+    pub fn replace_vendor_day(&mut self, date: &str, vendor: &str, metrics: DayMetrics) {
+        self.replace_entries(date, Some(vendor), metrics);
+    }
+
+    fn replace_entries(&mut self, date: &str, vendor: Option<&str>, metrics: DayMetrics) {
+        if metrics.is_empty() {
+            self.remove_entries(date, vendor);
+            return;
+        }
+        let is_day = |d: &BaselineDay| d.is_entry(date, vendor);
+        match self.days.iter().rposition(is_day) {
+            Some(last) => {
+                self.days[last].metrics = metrics;
+                if vendor.is_some() {
+                    self.days[last].vendor = vendor.map(str::to_string);
+                }
+                // Collapse earlier entries for the same day into this one
+                let mut index = 0;
+                self.days.retain(|d| {
+                    index += 1;
+                    index > last || !is_day(d)
+                });
+            }
+            None => {
+                // Undated (migrated) days come first and count as older
+                let at = self
+                    .days
+                    .iter()
+                    .rposition(|d| d.date.as_deref().is_none_or(|d| d <= date))
+                    .map_or(0, |i| i + 1);
+                self.days.insert(
+                    at,
+                    BaselineDay {
+                        date: Some(date.to_string()),
+                        vendor: vendor.map(str::to_string),
+                        metrics,
+                    },
+                );
             }
         }
+        self.prune();
+    }
 
-        // Get updated baselines (including current data) for the output
-        let baselines = self.get_baselines();
+    /// Most recent values of one metric, up to the window size
+    fn window(&self, metric: MetricFn) -> Vec<f64> {
+        self.days
+            .iter()
+            .rev()
+            .filter_map(|d| metric(&d.metrics))
+            .take(self.window_size)
+            .collect()
+    }
+
+    /// Values of a slow metric in the `days`-long window ending on the newest day
+    fn slow_window(&self, metric: MetricFn, days: i64) -> Vec<f64> {
+        let Some(start) = self.slow_window_start(days) else {
+            return Vec::new();
+        };
+        self.days
+            .iter()
+            .filter(|d| day_date(d).is_some_and(|day| day >= start))
+            .filter_map(|d| metric(&d.metrics))
+            .collect()
+    }
+
+    /// First day of a slow window, counted back from the newest dated day
+    fn slow_window_start(&self, days: i64) -> Option<NaiveDate> {
+        let newest = self.days.iter().rev().find_map(day_date)?;
+        Some(newest - chrono::Duration::days(days - 1))
+    }
+
+    /// Drop leading days that no metric's rolling window reaches
+    fn prune(&mut self) {
+        let metrics: [MetricFn; 8] = [
+            |m| m.hrv_ms,
+            |m| m.rhr_bpm,
+            |m| m.sleep_minutes,
+            |m| m.sleep_efficiency,
+            |m| m.goal_attainment,
+            |m| m.sleep_score,
+            |m| m.recovery_score,
+            |m| m.strain_score,
+        ];
+        let keep_from = metrics
+            .into_iter()
+            .map(|metric| {
+                self.days
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|(_, d)| metric(&d.metrics).is_some())
+                    .take(self.window_size)
+                    .last()
+                    .map_or(self.days.len(), |(i, _)| i)
+            })
+            .chain(SLOW_METRICS.into_iter().filter_map(|(metric, days)| {
+                let start = self.slow_window_start(days)?;
+                Some(
+                    self.days
+                        .iter()
+                        .position(|d| {
+                            metric(&d.metrics).is_some()
+                                && day_date(d).is_some_and(|day| day >= start)
+                        })
+                        .unwrap_or(self.days.len()),
+                )
+            }))
+            .min()
+            .unwrap_or(0);
+        self.days.drain(..keep_from);
+    }
+
+    /// Producer instance ID saved with the store, if one was pinned
+    pub fn instance_id(&self) -> Option<&str> {
+        self.instance_id.as_deref()
+    }
+
+    pub fn set_instance_id(&mut self, instance_id: Option<String>) {
+        self.instance_id = instance_id;
+    }
+
+    /// UTC offset recorded with [`Self::set_last_utc_offset_minutes`]
+    pub fn last_utc_offset_minutes(&self) -> Option<i32> {
+        self.last_utc_offset_minutes
+    }
+
+    pub fn set_last_utc_offset_minutes(&mut self, offset: i32) {
+        self.last_utc_offset_minutes = Some(offset);
+    }
+
+    /// Latest date added with [`Self::update_and_contextualize`]
+    pub fn last_date(&self) -> Option<&str> {
+        self.last_date.as_deref()
+    }
+
+    /// The store as it was before `date`: later and same-day entries removed.
+    /// Undated (migrated) days count as older.
+    pub(crate) fn days_before(&self, date: &str) -> BaselineStore {
+        let mut before = self.clone();
+        before
+            .days
+            .retain(|d| d.date.as_deref().is_none_or(|d| d < date));
+        before
+    }
+
+    /// Bump and return the revision counter for a date
+    pub fn next_revision(&mut self, date: &str) -> u32 {
+        let revision = self.revisions.entry(date.to_string()).or_insert(0);
+        *revision += 1;
+        *revision
+    }
+
+    /// Get current baseline values
+    pub fn get_baselines(&self) -> Baselines {
+        self.get_baselines_for_hrv_scale(None)
+    }
+
+    /// Baseline values with HRV taken only from days on the same HRV scale.
+    ///
+    /// Days recorded without calibration match any scale.
+    pub fn get_baselines_for_hrv_scale(&self, hrv_scale: Option<Vendor>) -> Baselines {
+        let hrv: Vec<f64> = self
+            .days
+            .iter()
+            .rev()
+            .filter(|d| match (d.metrics.hrv_scale, hrv_scale) {
+                (Some(day), Some(wanted)) => day == wanted,
+                _ => true,
+            })
+            .filter_map(|d| d.metrics.hrv_ms)
+            .take(self.window_size)
+            .collect();
+        let rhr = self.window(|m| m.rhr_bpm);
+        let sleep = self.window(|m| m.sleep_minutes);
+        let efficiency = self.window(|m| m.sleep_efficiency);
+        let vo2_max = self.slow_window(|m| m.vo2_max, VO2_MAX_BASELINE_DAYS);
+        let weight = self.slow_window(|m| m.weight_kg, BODY_BASELINE_DAYS);
+        let body_fat = self.slow_window(|m| m.body_fat_percentage, BODY_BASELINE_DAYS);
+        Baselines {
+            hrv_baseline_ms: Self::rolling_average(&hrv),
+            rhr_baseline_bpm: Self::rolling_average(&rhr),
+            sleep_baseline_minutes: Self::rolling_average(&sleep),
+            sleep_efficiency_baseline: Self::rolling_average(&efficiency),
+            hrv_std_ms: Self::rolling_std(&hrv),
+            rhr_std_bpm: Self::rolling_std(&rhr),
+            sleep_std_minutes: Self::rolling_std(&sleep),
+            vo2_max_baseline: Self::rolling_average(&vo2_max),
+            weight_baseline_kg: Self::rolling_average(&weight),
+            body_fat_baseline_pct: Self::rolling_average(&body_fat),
+            vo2_max_std: Self::rolling_std(&vo2_max),
+            weight_std_kg: Self::rolling_std(&weight),
+            body_fat_std_pct: Self::rolling_std(&body_fat),
+            baseline_days: hrv.len().max(rhr.len()) as u32,
+            window_days: self.window_size as u32,
+            tag: None,
+        }
+    }
+
+    /// Vendor scores of the last `window_size` scored days before `date`,
+    /// for quantile normalization
+    pub fn score_history_before(&self, date: &str) -> ScoreHistory {
+        let before = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        let window = |metric: MetricFn| -> Vec<f64> {
+            self.days
+                .iter()
+                .rev()
+                .filter(|d| match (day_date(d), before) {
+                    (Some(day), Some(before)) => day < before,
+                    _ => true,
+                })
+                .filter_map(|d| metric(&d.metrics))
+                .take(self.window_size)
+                .collect()
+        };
+        ScoreHistory {
+            sleep: window(|m| m.sleep_score),
+            recovery: window(|m| m.recovery_score),
+            strain: window(|m| m.strain_score),
+        }
+    }
+
+    /// Build contextual signals by comparing the day against a baseline
+    fn contextualize(&self, derived: DerivedSignals, baselines: Baselines) -> ContextualSignals {
+        let hrv_deviation_pct = self.calculate_deviation(
+            derived.normalized.canonical.recovery.hrv_rmssd_ms,
+            baselines.hrv_baseline_ms,
+        );
+
+        let rhr_deviation_pct = self.calculate_deviation(
+            derived.normalized.canonical.recovery.resting_hr_bpm,
+            baselines.rhr_baseline_bpm,
+        );
+
+        let sleep_duration_deviation_pct = self.calculate_deviation(
+            derived.normalized.canonical.sleep.total_sleep_minutes,
+            baselines.sleep_baseline_minutes,
+        );
+
+        let canonical = &derived.normalized.canonical;
+        let hrv_zscore = z_score(
+            canonical.recovery.hrv_rmssd_ms,
+            baselines.hrv_baseline_ms,
+            baselines.hrv_std_ms,
+        );
+        let rhr_zscore = z_score(
+            canonical.recovery.resting_hr_bpm,
+            baselines.rhr_baseline_bpm,
+            baselines.rhr_std_bpm,
+        );
+        let sleep_duration_zscore = z_score(
+            canonical.sleep.total_sleep_minutes,
+            baselines.sleep_baseline_minutes,
+            baselines.sleep_std_minutes,
+        );
+        let is_anomaly = |z: Option<f64>| z.is_some_and(|z| z.abs() > self.anomaly_threshold);
+        let vo2_max_deviation_pct =
+            self.calculate_deviation(canonical.activity.vo2_max, baselines.vo2_max_baseline);
+        let weight_deviation_pct =
+            self.calculate_deviation(canonical.body.weight_kg, baselines.weight_baseline_kg);
+        let body_fat_deviation_pct = self.calculate_deviation(
+            canonical.body.body_fat_percentage,
+            baselines.body_fat_baseline_pct,
+        );
+        let vo2_max_zscore = z_score(
+            canonical.activity.vo2_max,
+            baselines.vo2_max_baseline,
+            baselines.vo2_max_std,
+        );
+        let weight_zscore = z_score(
+            canonical.body.weight_kg,
+            baselines.weight_baseline_kg,
+            baselines.weight_std_kg,
+        );
+        let body_fat_zscore = z_score(
+            canonical.body.body_fat_percentage,
+            baselines.body_fat_baseline_pct,
+            baselines.body_fat_std_pct,
+        );
+        let goal_attainment_7d = derived
+            .goal_attainment
+            .and_then(|today| self.goal_trend(&canonical.date, today));
+        let trend = |metric: MetricFn, today: Option<f64>| {
+            today.and_then(|today| self.trend(metric, &canonical.date, today))
+        };
+        let hrv_trend = trend(|m| m.hrv_ms, canonical.recovery.hrv_rmssd_ms);
+        let rhr_trend = trend(|m| m.rhr_bpm, canonical.recovery.resting_hr_bpm);
+        let sleep_trend = trend(|m| m.sleep_minutes, canonical.sleep.total_sleep_minutes);
+        let shift = self.config.change_point.map(|config| {
+            let metrics: [(MetricFn, Option<f64>); 3] = [
+                (|m| m.hrv_ms, canonical.recovery.hrv_rmssd_ms),
+                (|m| m.rhr_bpm, canonical.recovery.resting_hr_bpm),
+                (|m| m.sleep_minutes, canonical.sleep.total_sleep_minutes),
+            ];
+            metrics
+                .into_iter()
+                .filter_map(|(metric, today)| {
+                    self.shift_start(&config, metric, &canonical.date, today?)
+                })
+                .min()
+        });
 
         ContextualSignals {
+            hrv_anomaly: is_anomaly(hrv_zscore),
+            rhr_anomaly: is_anomaly(rhr_zscore),
+            sleep_anomaly: is_anomaly(sleep_duration_zscore),
             derived,
             baselines,
             hrv_deviation_pct,
             rhr_deviation_pct,
             sleep_duration_deviation_pct,
+            hrv_zscore,
+            rhr_zscore,
+            sleep_duration_zscore,
+            goal_attainment_7d,
+            vo2_max_deviation_pct,
+            weight_deviation_pct,
+            body_fat_deviation_pct,
+            vo2_max_zscore,
+            weight_zscore,
+            body_fat_zscore,
+            hrv_trend,
+            rhr_trend,
+            sleep_trend,
+            baseline_shift_detected: shift.as_ref().map(Option::is_some),
+            baseline_shift_date: shift.flatten(),
+            device_changed: None,
+            days_since_device_switch: None,
         }
     }
 
-    /// Get current baseline values
-    pub fn get_baselines(&self) -> Baselines {
-        Baselines {
-            hrv_baseline_ms: Self::rolling_average(&self.hrv_values),
-            rhr_baseline_bpm: Self::rolling_average(&self.rhr_values),
-            sleep_baseline_minutes: Self::rolling_average(&self.sleep_duration_values),
-            sleep_efficiency_baseline: Self::rolling_average(&self.sleep_efficiency_values),
-            baseline_days: self.hrv_values.len().max(self.rhr_values.len()) as u32,
+    /// First day of a shift in one metric that the CUSUM sums flag on `date`.
+    ///
+    /// Runs over the dated days before `date` followed by `today`, each
+    /// compared against the rolling window of values before it.
+    fn shift_start(
+        &self,
+        config: &ChangePointConfig,
+        metric: MetricFn,
+        date: &str,
+        today: f64,
+    ) -> Option<String> {
+        let series = self
+            .days
+            .iter()
+            .filter_map(|d| {
+                let day = d.date.as_deref().filter(|day| *day < date)?;
+                Some((day, metric(&d.metrics)?))
+            })
+            .chain([(date, today)]);
+
+        let mut history: Vec<f64> = Vec::new();
+        let (mut high, mut low) = (0.0, 0.0);
+        let (mut high_start, mut low_start) = (date, date);
+        for (day, value) in series {
+            let window = &history[history.len().saturating_sub(self.window_size)..];
+            if let (Some(mean), Some(std)) =
+                (Self::rolling_average(window), Self::rolling_std(window))
+            {
+                if std >= MIN_BASELINE_MAGNITUDE {
+                    let z = ((value - mean) / std).clamp(-MAX_CUSUM_ZSCORE, MAX_CUSUM_ZSCORE);
+                    if high <= 0.0 {
+                        high_start = day;
+                    }
+                    if low <= 0.0 {
+                        low_start = day;
+                    }
+                    high = f64::max(0.0, high + z - config.slack);
+                    low = f64::max(0.0, low - z - config.slack);
+                }
+            }
+            history.push(value);
+        }
+
+        [(high, high_start), (low, low_start)]
+            .into_iter()
+            .filter(|(sum, _)| *sum > config.threshold)
+            .map(|(_, start)| start.to_string())
+            .min()
+    }
+
+    /// Mean attainment over the trailing window ending on `date`, counting
+    /// `today` in place of any stored entry for that date
+    fn goal_trend(&self, date: &str, today: f64) -> Option<f64> {
+        let values: Vec<f64> = self
+            .trailing(|m| m.goal_attainment, date, today, GOAL_TREND_DAYS)?
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        Self::rolling_average(&values)
+    }
+
+    /// Change of a metric across the trend window relative to its mean, from
+    /// a least-squares fit (-1 to 1, positive when rising)
+    fn trend(&self, metric: MetricFn, date: &str, today: f64) -> Option<f64> {
+        let points = self.trailing(metric, date, today, TREND_DAYS)?;
+        if points.len() < MIN_TREND_SAMPLES {
+            return None;
         }
+        let values: Vec<f64> = points.iter().map(|(_, value)| *value).collect();
+        let mean = Self::rolling_average(&values)?;
+        if mean < MIN_BASELINE_MAGNITUDE {
+            return None;
+        }
+        let slope = linear_slope(&points)?;
+        Some((slope * (TREND_DAYS - 1) as f64 / mean).clamp(-1.0, 1.0))
+    }
+
+    /// `(day offset, value)` of a metric over the `days`-long window ending
+    /// on `date`, counting `today` in place of any stored entry for that date
+    fn trailing(
+        &self,
+        metric: MetricFn,
+        date: &str,
+        today: f64,
+        days: i64,
+    ) -> Option<Vec<(f64, f64)>> {
+        let end = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+        let start = end - chrono::Duration::days(days - 1);
+        let offset = |day: NaiveDate| (day - start).num_days() as f64;
+        let earlier = self.days.iter().filter_map(|d| {
+            let day = day_date(d)?;
+            let value = metric(&d.metrics)?;
+            (start <= day && day < end).then_some((offset(day), value))
+        });
+        Some(earlier.chain([(offset(end), today)]).collect())
     }
 
     /// Calculate deviation from baseline as percentage
-    fn calculate_deviation(&self, current: Option<f64>, baseline: Option<f64>) -> Option<f64> {
+    pub(crate) fn calculate_deviation(
+        &self,
+        current: Option<f64>,
+        baseline: Option<f64>,
+    ) -> Option<f64> {
         match (current, baseline) {
-            (Some(curr), Some(base)) if base > 0.0 => Some(((curr - base) / base) * 100.0),
+            (Some(curr), Some(base)) if base >= MIN_BASELINE_MAGNITUDE => {
+                Some(((curr - base) / base) * 100.0)
+            }
             _ => None,
         }
     }
 
-    /// Calculate rolling average of a queue
-    fn rolling_average(queue: &VecDeque<f64>) -> Option<f64> {
-        if queue.is_empty() {
+    /// Calculate rolling average of a window
+    fn rolling_average(values: &[f64]) -> Option<f64> {
+        if values.is_empty() {
             return None;
         }
-        let sum: f64 = queue.iter().sum();
-        Some(sum / queue.len() as f64)
+        let sum: f64 = values.iter().sum();
+        Some(sum / values.len() as f64)
+    }
+
+    /// Calculate sample standard deviation of a window
+    fn rolling_std(values: &[f64]) -> Option<f64> {
+        if values.len() < MIN_ZSCORE_SAMPLES {
+            return None;
+        }
+        let mean = Self::rolling_average(values)?;
+        let sum_sq: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+        Some((sum_sq / (values.len() - 1) as f64).sqrt())
+    }
+
+    /// Serialization version of a stored blob, without migrating it
+    /// (1 for pre-versioned stores)
+    pub fn stored_version(json: &str) -> Result<u32, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            #[serde(default = "default_store_version")]
+            version: u32,
+        }
+        Ok(serde_json::from_str::<Versioned>(json)?.version)
     }
 
-    /// Load baseline store from JSON
+    /// Load baseline store from JSON (v1 and v2 stores are migrated on load)
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
@@ -144,6 +1299,16 @@ impl BaselineStore {
     }
 }
 
+/// Standard score of a value against a baseline mean and standard deviation
+fn z_score(current: Option<f64>, mean: Option<f64>, std: Option<f64>) -> Option<f64> {
+    match (current, mean, std) {
+        (Some(curr), Some(mean), Some(std)) if std >= MIN_BASELINE_MAGNITUDE => {
+            Some((curr - mean) / std)
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,14 +1317,16 @@ mod tests {
         NormalizedSignals, Vendor,
     };
     use chrono::Utc;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     fn make_derived(hrv: f64, rhr: f64, sleep_min: f64) -> DerivedSignals {
         let canonical = CanonicalWearSignals {
             vendor: Vendor::Whoop,
             date: "2024-01-15".to_string(),
             device_id: "test".to_string(),
+            user_id: None,
             timezone: "UTC".to_string(),
+            utc_offset_minutes: None,
             observed_at: Utc::now(),
             sleep: CanonicalSleep {
                 total_sleep_minutes: Some(sleep_min),
@@ -172,7 +1339,16 @@ mod tests {
                 ..Default::default()
             },
             activity: CanonicalActivity::default(),
+            body: Default::default(),
+            input_quality: None,
             vendor_raw: HashMap::new(),
+            field_sources: HashMap::new(),
+            field_events: HashMap::new(),
+            intraday: BTreeMap::new(),
+            latest_event_at: BTreeMap::new(),
+            tags: Vec::new(),
+            warnings: Vec::new(),
+            unmapped_signals: BTreeMap::new(),
         };
 
         let normalized = NormalizedSignals {
@@ -181,7 +1357,9 @@ mod tests {
             recovery_score: Some(0.75),
             strain_score: None,
             coverage: 0.8,
+            class_coverage: 1.0,
             quality_flags: vec![],
+            score_normalization: BTreeMap::new(),
         };
 
         DerivedSignals {
@@ -191,6 +1369,9 @@ mod tests {
             deep_sleep_ratio: None,
             rem_sleep_ratio: None,
             normalized_load: None,
+            hypnogram: None,
+            goal_attainment: None,
+            bmi: None,
         }
     }
 
@@ -250,6 +1431,34 @@ mod tests {
         assert!((contextual.hrv_deviation_pct.unwrap() - expected).abs() < 0.1);
     }
 
+    #[test]
+    fn test_zscore_anomaly() {
+        let mut store = BaselineStore::new(7);
+        for hrv in [58.0, 60.0, 62.0, 60.0, 58.0, 62.0, 60.0] {
+            store.update_and_contextualize(make_derived(hrv, 55.0, 420.0));
+        }
+
+        let baselines = store.get_baselines();
+        let std = baselines.hrv_std_ms.unwrap();
+        assert!((std - (16.0f64 / 6.0).sqrt()).abs() < 1e-9);
+        // Constant RHR has no spread, so no z-score
+        assert_eq!(baselines.rhr_std_bpm, Some(0.0));
+
+        let contextual = store.update_and_contextualize(make_derived(40.0, 55.0, 420.0));
+        assert!((contextual.hrv_zscore.unwrap() - (40.0 - 60.0) / std).abs() < 1e-9);
+        assert!(contextual.hrv_anomaly);
+        assert_eq!(contextual.rhr_zscore, None);
+        assert!(!contextual.rhr_anomaly);
+
+        let mut lenient = BaselineStore::new(7).with_anomaly_threshold(100.0);
+        for hrv in [58.0, 60.0, 62.0] {
+            lenient.update_and_contextualize(make_derived(hrv, 55.0, 420.0));
+        }
+        let contextual = lenient.update_and_contextualize(make_derived(40.0, 55.0, 420.0));
+        assert!(contextual.hrv_zscore.is_some());
+        assert!(!contextual.hrv_anomaly);
+    }
+
     #[test]
     fn test_serialization() {
         let mut store = BaselineStore::new(7);
@@ -267,4 +1476,343 @@ mod tests {
             loaded_baselines.hrv_baseline_ms
         );
     }
+
+    #[test]
+    fn test_load_v1_store() {
+        let v1 = r#"{
+            "hrv_values": [60.0, 70.0],
+            "rhr_values": [55.0],
+            "sleep_duration_values": [],
+            "sleep_efficiency_values": [],
+            "window_size": 7
+        }"#;
+
+        let store = BaselineStore::from_json(v1).unwrap();
+        let baselines = store.get_baselines();
+        assert_eq!(baselines.hrv_baseline_ms, Some(65.0));
+        assert_eq!(baselines.baseline_days, 2);
+
+        let json: serde_json::Value = serde_json::from_str(&store.to_json().unwrap()).unwrap();
+        assert_eq!(json["version"], BASELINE_STORE_VERSION);
+        assert_eq!(json["days"][0]["hrv_ms"], 60.0);
+        // The lone v1 RHR value aligns with the most recent HRV value
+        assert_eq!(json["days"][1]["rhr_bpm"], 55.0);
+        assert!(json["days"][0].get("date").is_none());
+    }
+
+    #[test]
+    fn test_load_v2_store_groups_by_date() {
+        let v2 = r#"{
+            "version": 2,
+            "hrv_values": [{"date": "2024-01-02", "value": 60.0}, {"date": "2024-01-03", "value": 70.0}],
+            "rhr_values": [{"date": "2024-01-01", "value": 50.0}, {"date": "2024-01-03", "value": 55.0}],
+            "sleep_duration_values": [],
+            "sleep_efficiency_values": [],
+            "window_size": 7
+        }"#;
+
+        let store = BaselineStore::from_json(v2).unwrap();
+        let days: Vec<_> = store.days().collect();
+        assert_eq!(days.len(), 3);
+        assert_eq!(days[0].date.as_deref(), Some("2024-01-01"));
+        assert_eq!(days[0].metrics.hrv_ms, None);
+        assert_eq!(days[2].metrics.hrv_ms, Some(70.0));
+        assert_eq!(days[2].metrics.rhr_bpm, Some(55.0));
+        assert_eq!(store.get_baselines().rhr_baseline_bpm, Some(52.5));
+    }
+
+    #[test]
+    fn test_remove_and_replace_day() {
+        let mut store = BaselineStore::new(2);
+        for (date, hrv) in [("2024-01-01", 50.0), ("2024-01-03", 60.0)] {
+            let mut derived = make_derived(hrv, 55.0, 420.0);
+            derived.normalized.canonical.date = date.to_string();
+            store.update_and_contextualize(derived);
+        }
+
+        // A late day lands in date order and the window keeps the latest two
+        let metrics = DayMetrics {
+            hrv_ms: Some(80.0),
+            ..Default::default()
+        };
+        store.replace_day("2024-01-02", metrics);
+        let dates: Vec<_> = store.days().filter_map(|d| d.date.as_deref()).collect();
+        assert_eq!(dates, vec!["2024-01-01", "2024-01-02", "2024-01-03"]);
+        assert_eq!(store.get_baselines().hrv_baseline_ms, Some(70.0));
+
+        // Removing a day brings older values back into the window
+        assert!(store.remove_day("2024-01-03"));
+        assert!(!store.remove_day("2024-01-03"));
+        assert_eq!(store.get_baselines().hrv_baseline_ms, Some(65.0));
+    }
+
+    #[test]
+    fn test_tag_policies() {
+        let config = BaselineConfig::default()
+            .with_tag_policy("sick", TagPolicy::Exclude)
+            .with_tag_policy("travel", TagPolicy::Separate);
+        let mut store = BaselineStore::new(7).with_config(config);
+        let day = |date: &str, hrv: f64, tags: &[&str]| {
+            let mut derived = make_derived(hrv, 55.0, 420.0);
+            derived.normalized.canonical.date = date.to_string();
+            derived.normalized.canonical.tags = tags.iter().map(|t| t.to_string()).collect();
+            derived
+        };
+
+        store.update_and_contextualize(day("2024-01-10", 60.0, &[]));
+        // Excluded days are compared but not accumulated
+        let sick = store.update_and_contextualize(day("2024-01-11", 30.0, &["sick"]));
+        assert_eq!(sick.hrv_deviation_pct, Some(-50.0));
+        assert_eq!(sick.baselines.baseline_days, 1);
+        // Exclude wins over Separate
+        store.update_and_contextualize(day("2024-01-12", 30.0, &["sick", "travel"]));
+
+        let travel = store.update_and_contextualize(day("2024-01-13", 40.0, &["travel"]));
+        assert_eq!(travel.baselines.tag.as_deref(), Some("travel"));
+        assert_eq!(travel.baselines.hrv_baseline_ms, Some(40.0));
+        assert_eq!(travel.hrv_deviation_pct, None);
+        assert_eq!(store.get_baselines().hrv_baseline_ms, Some(60.0));
+        assert_eq!(
+            store.baselines_for(&["travel".to_string()]).baseline_days,
+            1
+        );
+
+        // Re-tagging a day on reprocess moves it between baselines
+        store.replace_and_contextualize(day("2024-01-13", 40.0, &[]));
+        assert_eq!(store.get_baselines().hrv_baseline_ms, Some(50.0));
+        assert_eq!(
+            store.baselines_for(&["travel".to_string()]).baseline_days,
+            0
+        );
+
+        // Tag baselines persist; the config does not
+        let restored = BaselineStore::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(restored.tag_baselines.len(), 1);
+        assert_eq!(restored.config(), &BaselineConfig::default());
+    }
+
+    #[test]
+    fn test_device_switch_policies() {
+        let day = |date: &str, device: &str, hrv: f64| {
+            let mut derived = make_derived(hrv, 55.0, 420.0);
+            derived.normalized.canonical.date = date.to_string();
+            derived.normalized.canonical.device_id = device.to_string();
+            derived
+        };
+        let switch_after_two_days = |policy: DeviceSwitchPolicy| {
+            let config = BaselineConfig::default().with_device_switch(policy);
+            let mut store = BaselineStore::new(7).with_config(config);
+            let first = store.update_and_contextualize(day("2024-01-10", "strap-1", 60.0));
+            assert_eq!(first.device_changed, None);
+            store.update_and_contextualize(day("2024-01-11", "strap-1", 60.0));
+            let switched = store.update_and_contextualize(day("2024-01-12", "strap-2", 90.0));
+            assert_eq!(switched.device_changed, Some(true));
+            assert_eq!(switched.days_since_device_switch, Some(0));
+            (store, switched)
+        };
+
+        let (mut store, switched) = switch_after_two_days(DeviceSwitchPolicy::Continue);
+        assert_eq!(switched.hrv_deviation_pct, Some(50.0));
+        let later = store.update_and_contextualize(day("2024-01-15", "strap-2", 90.0));
+        assert_eq!(later.device_changed, Some(false));
+        assert_eq!(later.days_since_device_switch, Some(3));
+
+        let (_, switched) = switch_after_two_days(DeviceSwitchPolicy::Reset);
+        assert_eq!(switched.hrv_deviation_pct, None);
+        assert_eq!(switched.baselines.baseline_days, 1);
+
+        // The old device's baseline is resumed when it comes back
+        let (store, switched) = switch_after_two_days(DeviceSwitchPolicy::Parallel);
+        assert_eq!(switched.hrv_deviation_pct, None);
+        let mut store = BaselineStore::from_json(&store.to_json().unwrap())
+            .unwrap()
+            .with_config(store.config().clone());
+        let back = store.update_and_contextualize(day("2024-01-13", "strap-1", 60.0));
+        assert_eq!(back.device_changed, Some(true));
+        assert_eq!(back.hrv_deviation_pct, Some(0.0));
+        assert_eq!(back.baselines.baseline_days, 3);
+    }
+
+    #[test]
+    fn test_replace_day() {
+        let mut store = BaselineStore::new(7);
+        for (i, hrv) in [60.0, 60.0, 90.0].into_iter().enumerate() {
+            let mut derived = make_derived(hrv, 55.0, 420.0);
+            derived.normalized.canonical.date = format!("2024-01-1{i}");
+            store.update_and_contextualize(derived);
+        }
+
+        // Amend the last day: 90 was a bad reading, the corrected value is 60
+        let mut derived = make_derived(60.0, 55.0, 420.0);
+        derived.normalized.canonical.date = "2024-01-12".to_string();
+        let contextual = store.replace_and_contextualize(derived);
+
+        assert_eq!(contextual.hrv_deviation_pct, Some(0.0));
+        assert_eq!(contextual.baselines.hrv_baseline_ms, Some(60.0));
+        assert_eq!(contextual.baselines.baseline_days, 3);
+        assert_eq!(store.next_revision("2024-01-12"), 1);
+        assert_eq!(store.next_revision("2024-01-12"), 2);
+    }
+
+    #[test]
+    fn test_replace_day_uses_earlier_days_and_keeps_other_vendors() {
+        let mut store = BaselineStore::new(7);
+        let derived_for = |date: &str, vendor: Vendor, hrv: f64| {
+            let mut derived = make_derived(hrv, 55.0, 420.0);
+            derived.normalized.canonical.date = date.to_string();
+            derived.normalized.canonical.vendor = vendor;
+            derived
+        };
+        for (date, vendor, hrv) in [
+            ("2024-01-10", Vendor::Whoop, 60.0),
+            ("2024-01-11", Vendor::Whoop, 90.0),
+            ("2024-01-11", Vendor::Garmin, 70.0),
+            ("2024-01-12", Vendor::Whoop, 100.0),
+        ] {
+            store.update_and_contextualize(derived_for(date, vendor, hrv));
+        }
+
+        // Amend WHOOP's 2024-01-11: compared with 2024-01-10 only, not the later day
+        let contextual =
+            store.replace_and_contextualize(derived_for("2024-01-11", Vendor::Whoop, 66.0));
+        assert_eq!(contextual.hrv_deviation_pct, Some(10.0));
+
+        // Garmin's entry for the same day survives the amendment
+        let day: Vec<_> = store
+            .days()
+            .filter(|d| d.date.as_deref() == Some("2024-01-11"))
+            .map(|d| (d.vendor.as_deref(), d.metrics.hrv_ms))
+            .collect();
+        assert_eq!(
+            day,
+            vec![(Some("whoop"), Some(66.0)), (Some("garmin"), Some(70.0))]
+        );
+    }
+
+    #[test]
+    fn test_vo2_max_slow_baseline() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut store = BaselineStore::new(3);
+        let mut process = |offset: i64, vo2_max: Option<f64>| {
+            let mut derived = make_derived(60.0, 55.0, 420.0);
+            derived.normalized.canonical.date =
+                (start + chrono::Duration::days(offset)).to_string();
+            derived.normalized.canonical.activity.vo2_max = vo2_max;
+            store.update_and_contextualize(derived)
+        };
+
+        // Only a few days report VO2max; the 3-day window must not drop them
+        process(0, Some(40.0));
+        process(30, Some(44.0));
+        for offset in 31..60 {
+            process(offset, None);
+        }
+        // Deviation is against the baseline before the day: mean(40, 44)
+        let contextual = process(60, Some(46.2));
+        assert!((contextual.vo2_max_deviation_pct.unwrap() - 10.0).abs() < 1e-9);
+        assert_eq!(contextual.baselines.vo2_max_baseline, Some(130.2 / 3.0));
+
+        // Day 0 falls out of the 90-day window ending on day 95
+        let contextual = process(95, None);
+        assert!((contextual.baselines.vo2_max_baseline.unwrap() - 45.1).abs() < 1e-9);
+        assert_eq!(contextual.baselines.hrv_baseline_ms, Some(60.0));
+    }
+
+    #[test]
+    fn test_trends() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut store = BaselineStore::default();
+        let mut process = |offset: i64, hrv: f64| {
+            let mut derived = make_derived(hrv, 55.0, 420.0);
+            derived.normalized.canonical.date =
+                (start + chrono::Duration::days(offset)).to_string();
+            store.update_and_contextualize(derived)
+        };
+
+        assert_eq!(process(0, 50.0).hrv_trend, None);
+        assert_eq!(process(1, 52.0).hrv_trend, None);
+        // Slope of 2 ms/day over 6 days, relative to the mean of 52 ms
+        let contextual = process(2, 54.0);
+        assert!((contextual.hrv_trend.unwrap() - 12.0 / 52.0).abs() < 1e-9);
+        assert_eq!(contextual.rhr_trend, Some(0.0));
+
+        // Only day 3 is left in the 7-day window ending on day 9
+        process(3, 56.0);
+        assert_eq!(process(9, 40.0).hrv_trend, None);
+    }
+
+    #[test]
+    fn test_change_point_detection() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let run = |config: BaselineConfig, hrv: &[f64]| {
+            let mut store = BaselineStore::default().with_config(config);
+            hrv.iter()
+                .enumerate()
+                .map(|(offset, hrv)| {
+                    let mut derived = make_derived(*hrv, 55.0, 420.0);
+                    derived.normalized.canonical.date =
+                        (start + chrono::Duration::days(offset as i64)).to_string();
+                    store.update_and_contextualize(derived)
+                })
+                .collect::<Vec<_>>()
+        };
+        let steady = [60.0, 62.0].repeat(5);
+        let detecting = BaselineConfig::default().with_change_point(ChangePointConfig::default());
+
+        // A single low day is not a sustained shift
+        let outlier = run(
+            detecting.clone(),
+            &[steady.clone(), vec![50.0, 61.0]].concat(),
+        );
+        assert!(outlier
+            .iter()
+            .all(|c| c.baseline_shift_detected == Some(false)));
+
+        let shifted = run(detecting, &[steady.clone(), vec![50.0, 50.0]].concat());
+        assert_eq!(shifted[10].baseline_shift_detected, Some(false));
+        assert_eq!(shifted[11].baseline_shift_detected, Some(true));
+        assert_eq!(
+            shifted[11].baseline_shift_date.as_deref(),
+            Some("2024-01-11")
+        );
+
+        // Detection is off by default
+        let off = run(
+            BaselineConfig::default(),
+            &[steady, vec![50.0, 50.0]].concat(),
+        );
+        assert_eq!(off[11].baseline_shift_detected, None);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_deviations_and_zscores_are_finite(
+            days in proptest::collection::vec(
+                (
+                    proptest::prop_oneof![proptest::strategy::Just(f64::MIN_POSITIVE), 0.0..500.0f64],
+                    0.0..250.0f64,
+                    0.0..1_440.0f64,
+                ),
+                1..40,
+            )
+        ) {
+            let mut store = BaselineStore::new(14);
+            for (hrv, rhr, sleep_min) in days {
+                let contextual = store.update_and_contextualize(make_derived(hrv, rhr, sleep_min));
+                for value in [
+                    contextual.hrv_deviation_pct,
+                    contextual.rhr_deviation_pct,
+                    contextual.sleep_duration_deviation_pct,
+                    contextual.hrv_zscore,
+                    contextual.rhr_zscore,
+                    contextual.sleep_duration_zscore,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    proptest::prop_assert!(value.is_finite(), "{}", value);
+                }
+            }
+        }
+    }
 }
@@ -2,18 +2,22 @@
 //!
 //! Parses behavioral session JSON and converts to canonical signals.
 
+use crate::behavior::config::{BehaviorConfig, OutOfBoundsPolicy};
 use crate::behavior::types::{
-    BehaviorEvent, BehaviorEventType, BehaviorSession, CanonicalBehaviorSignals, EngagementSegment,
-    IdleSegment,
+    AppUsage, BehaviorEvent, BehaviorEventType, BehaviorSession, CanonicalBehaviorSignals,
+    EngagementSegment, IdleSegment, InterruptionAction, ScreenActivity, ScrollDirection,
+    ScrollSegment, TypingSessionMetrics,
 };
 use crate::error::ComputeError;
-use chrono::Utc;
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use std::collections::BTreeMap;
 
-/// Minimum gap duration (in seconds) to be considered idle
-const IDLE_GAP_THRESHOLD_SEC: f64 = 30.0;
+/// Local hour at which late-night usage starts (23:00)
+const LATE_NIGHT_START_HOUR: u32 = 23;
 
-/// Minimum duration (in seconds) for an engagement segment
-const MIN_ENGAGEMENT_DURATION_SEC: f64 = 10.0;
+/// Local hour at which late-night usage ends (05:00)
+const LATE_NIGHT_END_HOUR: u32 = 5;
 
 /// Parse a behavioral session JSON string into a BehaviorSession
 pub fn parse_session(json: &str) -> Result<BehaviorSession, ComputeError> {
@@ -21,22 +25,48 @@ pub fn parse_session(json: &str) -> Result<BehaviorSession, ComputeError> {
         .map_err(|e| ComputeError::ParseError(format!("Failed to parse behavioral session: {e}")))
 }
 
-/// Convert a BehaviorSession to CanonicalBehaviorSignals
+/// Convert a BehaviorSession to CanonicalBehaviorSignals using default thresholds
 pub fn session_to_canonical(
     session: &BehaviorSession,
 ) -> Result<CanonicalBehaviorSignals, ComputeError> {
-    // Validate session
-    if session.start_time >= session.end_time {
+    session_to_canonical_with_config(session, &BehaviorConfig::default())
+}
+
+/// Convert a BehaviorSession to CanonicalBehaviorSignals using the given thresholds
+pub fn session_to_canonical_with_config(
+    session: &BehaviorSession,
+    config: &BehaviorConfig,
+) -> Result<CanonicalBehaviorSignals, ComputeError> {
+    // Validate session (zero-length sessions, e.g. a single glance, are allowed)
+    if session.end_time < session.start_time {
         return Err(ComputeError::ParseError(
-            "Session end time must be after start time".to_string(),
+            "Session end time must not be before start time".to_string(),
         ));
     }
 
+    // An unknown timezone falls back to UTC and is flagged rather than rejected
+    let parsed = session.timezone.parse::<Tz>().ok();
+    let invalid_timezone = parsed.is_none();
+    let tz = parsed.unwrap_or(Tz::UTC);
+
     let duration_sec = (session.end_time - session.start_time).num_milliseconds() as f64 / 1000.0;
 
     // Sort events by timestamp
     let mut events = session.events.clone();
     events.sort_by_key(|e| e.timestamp);
+    let (events, out_of_bounds_events) = bound_events(events, session, config.out_of_bounds)?;
+
+    let check_event_times = events
+        .iter()
+        .filter(|e| is_check_event(e))
+        .map(|e| e.timestamp)
+        .collect();
+
+    // Screen state events are summarized separately from interactions
+    let (screen_events, events): (Vec<_>, Vec<_>) = events
+        .into_iter()
+        .partition(|e| e.event_type.is_screen_state());
+    let screen = extract_screen_activity(&screen_events, &session.start_time, &session.end_time);
 
     // Count events by type
     let (
@@ -51,13 +81,35 @@ pub fn session_to_canonical(
 
     let total_events = events.len() as u32;
 
+    let late_night_events = events
+        .iter()
+        .filter(|e| is_late_night(e.timestamp.with_timezone(&tz).hour()))
+        .count() as u32;
+
     // Count scroll direction reversals
     let scroll_direction_reversals = count_scroll_reversals(&events);
 
+    let long_press_events = events
+        .iter()
+        .filter(|e| e.event_type == BehaviorEventType::Tap)
+        .filter(|e| e.tap.as_ref().is_some_and(|t| t.long_press))
+        .count() as u32;
+
+    let app_usage = extract_app_usage(&events, &session.start_time, &session.end_time);
+
+    // Scroll velocities and same-direction scroll runs
+    let scroll_velocities: Vec<f64> = events
+        .iter()
+        .filter_map(|e| e.scroll.as_ref()?.velocity)
+        .filter(|v| v.is_finite())
+        .map(f64::abs)
+        .collect();
+    let scroll_segments = detect_scroll_segments(&events, config.idle_gap_threshold_sec);
+
     // Extract per-typing-session metrics (if provided by producer)
-    let typing_sessions = extract_typing_sessions(&events);
+    let (typing_sessions, overlapping_typing_sessions) = extract_typing_sessions(&events);
 
-    // Calculate total typing duration
+    // Calculate total typing duration (overlaps already merged)
     let total_typing_duration_sec = typing_sessions
         .iter()
         .map(|s| s.duration as f64)
@@ -67,17 +119,26 @@ pub fn session_to_canonical(
     let inter_event_gaps = compute_inter_event_gaps(&events);
 
     // Detect idle segments
-    let idle_segments = detect_idle_segments(&events, &session.start_time, &session.end_time);
+    let idle_segments = detect_idle_segments(
+        &events,
+        &session.start_time,
+        &session.end_time,
+        config.idle_gap_threshold_sec,
+    );
     let total_idle_time_sec: f64 = idle_segments.iter().map(|s| s.duration_sec).sum();
 
     // Detect engagement segments
     let engagement_segments =
-        detect_engagement_segments(&events, &session.start_time, &session.end_time);
+        detect_engagement_segments(&events, &session.start_time, &session.end_time, config);
 
     Ok(CanonicalBehaviorSignals {
         session_id: session.session_id.clone(),
         device_id: session.device_id.clone(),
-        timezone: session.timezone.clone(),
+        timezone: if invalid_timezone {
+            tz.name().to_string()
+        } else {
+            session.timezone.clone()
+        },
         start_time: session.start_time,
         end_time: session.end_time,
         duration_sec,
@@ -90,16 +151,197 @@ pub fn session_to_canonical(
         typing_events,
         app_switch_events,
         scroll_direction_reversals,
+        long_press_events,
+        out_of_bounds_events,
+        overlapping_typing_sessions,
+        invalid_timezone,
         total_typing_duration_sec,
         typing_sessions,
         idle_segments,
         total_idle_time_sec,
         engagement_segments,
         inter_event_gaps,
+        screen,
+        late_night_events,
+        check_event_times,
+        app_usage,
+        scroll_velocities,
+        scroll_segments,
         computed_at: Utc::now(),
     })
 }
 
+/// Foreground time per app in a session, longest first (see [`extract_app_usage`])
+pub(crate) fn session_app_usage(
+    session: &BehaviorSession,
+    config: &BehaviorConfig,
+) -> Result<Vec<AppUsage>, ComputeError> {
+    let mut switches: Vec<BehaviorEvent> = session
+        .events
+        .iter()
+        .filter(|e| e.event_type == BehaviorEventType::AppSwitch)
+        .cloned()
+        .collect();
+    switches.sort_by_key(|e| e.timestamp);
+    let (switches, _) = bound_events(switches, session, config.out_of_bounds)?;
+    Ok(extract_app_usage(
+        &switches,
+        &session.start_time,
+        &session.end_time,
+    ))
+}
+
+/// Apply the out-of-bounds policy to sorted events.
+///
+/// Events before the session start, after its end or in the future are out
+/// of bounds. Returns the remaining events and how many were out of bounds.
+fn bound_events(
+    mut events: Vec<BehaviorEvent>,
+    session: &BehaviorSession,
+    policy: OutOfBoundsPolicy,
+) -> Result<(Vec<BehaviorEvent>, u32), ComputeError> {
+    let start = session.start_time;
+    let end = session.end_time.min(Utc::now()).max(start);
+    let out_of_bounds = events
+        .iter()
+        .filter(|e| e.timestamp < start || e.timestamp > end)
+        .count() as u32;
+    if out_of_bounds == 0 {
+        return Ok((events, 0));
+    }
+
+    match policy {
+        OutOfBoundsPolicy::Drop => {
+            events.retain(|e| (start..=end).contains(&e.timestamp));
+        }
+        OutOfBoundsPolicy::Clamp => {
+            for event in &mut events {
+                event.timestamp = event.timestamp.clamp(start, end);
+            }
+        }
+        OutOfBoundsPolicy::Fail => {
+            return Err(ComputeError::InvalidBehaviorSession(format!(
+                "{out_of_bounds} events fall outside the session ({} to {}) or in the future",
+                start.to_rfc3339(),
+                end.to_rfc3339()
+            )));
+        }
+    }
+    Ok((events, out_of_bounds))
+}
+
+/// Foreground time per app from the app switch sequence, longest first.
+///
+/// Each switch puts `to_app_id` in the foreground until the next switch or
+/// session end; the first switch's `from_app_id` covers the time before it.
+fn extract_app_usage(
+    events: &[BehaviorEvent],
+    session_start: &DateTime<Utc>,
+    session_end: &DateTime<Utc>,
+) -> Vec<AppUsage> {
+    let switches: Vec<_> = events
+        .iter()
+        .filter(|e| e.event_type == BehaviorEventType::AppSwitch)
+        .filter_map(|e| {
+            let at = e.timestamp.clamp(*session_start, *session_end);
+            Some((at, e.app_switch.as_ref()?))
+        })
+        .collect();
+
+    let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+    let mut add = |app: Option<&String>, from: DateTime<Utc>, to: DateTime<Utc>| {
+        if let Some(app) = app {
+            *totals.entry(app.clone()).or_default() +=
+                (to - from).num_milliseconds() as f64 / 1000.0;
+        }
+    };
+    if let Some((first_at, first)) = switches.first() {
+        add(first.from_app_id.as_ref(), *session_start, *first_at);
+    }
+    for (i, (at, switch)) in switches.iter().enumerate() {
+        let until = switches.get(i + 1).map_or(*session_end, |(next, _)| *next);
+        add(switch.to_app_id.as_ref(), *at, until);
+    }
+
+    let mut usage: Vec<AppUsage> = totals
+        .into_iter()
+        .filter(|(_, duration)| *duration > 0.0)
+        .map(|(app_id, duration_sec)| AppUsage {
+            app_id,
+            duration_sec,
+        })
+        .collect();
+    usage.sort_by(|a, b| b.duration_sec.total_cmp(&a.duration_sec));
+    usage
+}
+
+/// Split scrolling into runs of consecutive same-direction scroll events.
+///
+/// A run ends at a direction change or reversal, at any other interaction, or
+/// at a gap longer than `max_gap_sec`. Scrolls without a direction continue
+/// the current run.
+fn detect_scroll_segments(events: &[BehaviorEvent], max_gap_sec: f64) -> Vec<ScrollSegment> {
+    struct Run<'a> {
+        events: Vec<&'a BehaviorEvent>,
+        direction: Option<ScrollDirection>,
+    }
+
+    fn close(run: Run<'_>) -> Option<ScrollSegment> {
+        let (first, last) = (run.events.first()?, run.events.last()?);
+        let velocities: Vec<f64> = run
+            .events
+            .iter()
+            .filter_map(|e| e.scroll.as_ref()?.velocity)
+            .filter(|v| v.is_finite())
+            .map(f64::abs)
+            .collect();
+        Some(ScrollSegment {
+            start: first.timestamp,
+            end: last.timestamp,
+            duration_sec: (last.timestamp - first.timestamp).num_milliseconds() as f64 / 1000.0,
+            event_count: run.events.len() as u32,
+            mean_velocity: (!velocities.is_empty())
+                .then(|| velocities.iter().sum::<f64>() / velocities.len() as f64),
+        })
+    }
+
+    let mut segments = Vec::new();
+    let mut run: Option<Run> = None;
+    for event in events {
+        if event.event_type != BehaviorEventType::Scroll {
+            segments.extend(run.take().and_then(close));
+            continue;
+        }
+        let direction = event.scroll.as_ref().and_then(|s| s.direction);
+        let reversal = event.scroll.as_ref().is_some_and(|s| s.direction_reversal);
+        let continues = run.as_ref().is_some_and(|r| {
+            let gap_sec = r.events.last().map_or(0.0, |last| {
+                (event.timestamp - last.timestamp).num_milliseconds() as f64 / 1000.0
+            });
+            let same_direction = match (r.direction, direction) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            };
+            !reversal && same_direction && gap_sec <= max_gap_sec
+        });
+        match run.as_mut() {
+            Some(r) if continues => {
+                r.events.push(event);
+                r.direction = r.direction.or(direction);
+            }
+            _ => {
+                segments.extend(run.take().and_then(close));
+                run = Some(Run {
+                    events: vec![event],
+                    direction,
+                });
+            }
+        }
+    }
+    segments.extend(run.and_then(close));
+    segments
+}
+
 /// Count events by type
 fn count_events_by_type(events: &[BehaviorEvent]) -> (u32, u32, u32, u32, u32, u32, u32) {
     let mut scroll = 0;
@@ -119,12 +361,83 @@ fn count_events_by_type(events: &[BehaviorEvent]) -> (u32, u32, u32, u32, u32, u
             BehaviorEventType::Call => call += 1,
             BehaviorEventType::Typing => typing += 1,
             BehaviorEventType::AppSwitch => app_switch += 1,
+            BehaviorEventType::ScreenOn
+            | BehaviorEventType::ScreenOff
+            | BehaviorEventType::Unlock => {}
         }
     }
 
     (scroll, tap, swipe, notification, call, typing, app_switch)
 }
 
+/// Whether a local hour falls in the late-night window (23:00-05:00)
+fn is_late_night(hour: u32) -> bool {
+    !(LATE_NIGHT_END_HOUR..LATE_NIGHT_START_HOUR).contains(&hour)
+}
+
+/// Whether an event is a phone check: an unlock, an app switch or an opened notification
+fn is_check_event(event: &BehaviorEvent) -> bool {
+    match event.event_type {
+        BehaviorEventType::Unlock | BehaviorEventType::AppSwitch => true,
+        BehaviorEventType::Notification => event
+            .interruption
+            .as_ref()
+            .is_some_and(|i| i.action == InterruptionAction::Opened),
+        _ => false,
+    }
+}
+
+/// Pair screen-on and screen-off events into screen sessions.
+///
+/// An unlock without a preceding screen-on opens a screen session, a leading
+/// screen-off closes one that started before the session, and a screen left
+/// on is closed at session end.
+fn extract_screen_activity(
+    events: &[BehaviorEvent],
+    session_start: &chrono::DateTime<Utc>,
+    session_end: &chrono::DateTime<Utc>,
+) -> ScreenActivity {
+    let mut activity = ScreenActivity::default();
+    let mut on_since = None;
+    let mut seen_on = false;
+
+    for event in events {
+        match event.event_type {
+            BehaviorEventType::ScreenOn => {
+                activity.screen_on_events += 1;
+                seen_on = true;
+                on_since.get_or_insert(event.timestamp);
+            }
+            BehaviorEventType::Unlock => {
+                activity.unlock_events += 1;
+                seen_on = true;
+                on_since.get_or_insert(event.timestamp);
+            }
+            BehaviorEventType::ScreenOff => {
+                activity.screen_off_events += 1;
+                let start = match on_since.take() {
+                    Some(start) => start,
+                    None if !seen_on => *session_start,
+                    None => continue,
+                };
+                seen_on = true;
+                activity
+                    .screen_sessions_sec
+                    .push((event.timestamp - start).num_milliseconds() as f64 / 1000.0);
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = on_since {
+        activity
+            .screen_sessions_sec
+            .push((*session_end - start).num_milliseconds() as f64 / 1000.0);
+    }
+
+    activity
+}
+
 /// Count scroll direction reversals
 fn count_scroll_reversals(events: &[BehaviorEvent]) -> u32 {
     events
@@ -135,14 +448,15 @@ fn count_scroll_reversals(events: &[BehaviorEvent]) -> u32 {
         .count() as u32
 }
 
-fn extract_typing_sessions(
-    events: &[BehaviorEvent],
-) -> Vec<crate::behavior::types::TypingSessionMetrics> {
-    events
+///
+/// Overlapping or duplicate sessions are merged; the second value is the
+/// number of sessions folded into another one.
+fn extract_typing_sessions(events: &[BehaviorEvent]) -> (Vec<TypingSessionMetrics>, u32) {
+    let sessions: Vec<TypingSessionMetrics> = events
         .iter()
         .filter(|e| e.event_type == BehaviorEventType::Typing)
         .filter_map(|e| e.typing.as_ref())
-        .map(|t| crate::behavior::types::TypingSessionMetrics {
+        .map(|t| TypingSessionMetrics {
             start_at: t.start_at.clone().unwrap_or_default(),
             end_at: t.end_at.clone().unwrap_or_default(),
             duration: t.duration_sec.unwrap_or(0.0).round().max(0.0) as u32,
@@ -163,7 +477,78 @@ fn extract_typing_sessions(
             typing_activity_ratio: t.typing_activity_ratio.unwrap_or(0.0),
             typing_interaction_intensity: t.typing_interaction_intensity.unwrap_or(0.0),
         })
-        .collect()
+        .collect();
+    merge_overlapping_typing_sessions(sessions)
+}
+
+/// Merge typing sessions whose `[start_at, end_at)` intervals overlap.
+///
+/// A merged session spans the union of its parts. Its duration is the sum of
+/// the parts capped at the union length, so duplicates are counted once. The
+/// per-session rhythm metrics come from the longest part. Sessions without
+/// parseable timestamps are kept as-is.
+fn merge_overlapping_typing_sessions(
+    sessions: Vec<TypingSessionMetrics>,
+) -> (Vec<TypingSessionMetrics>, u32) {
+    let parse = |s: &str| {
+        DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    };
+
+    let mut timed = Vec::new();
+    let mut untimed = Vec::new();
+    for session in sessions {
+        match (parse(&session.start_at), parse(&session.end_at)) {
+            (Some(start), Some(end)) if end >= start => timed.push((start, end, session)),
+            _ => untimed.push(session),
+        }
+    }
+    timed.sort_by_key(|(start, end, _)| (*start, *end));
+
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>, TypingSessionMetrics)> = Vec::new();
+    let mut overlapping = 0u32;
+    for (start, end, session) in timed {
+        let Some((last_start, last_end, last)) = merged.last_mut() else {
+            merged.push((start, end, session));
+            continue;
+        };
+        let duplicate = start == *last_start && end == *last_end;
+        if start >= *last_end && !duplicate {
+            merged.push((start, end, session));
+            continue;
+        }
+
+        overlapping += 1;
+        let union_end = end.max(*last_end);
+        let union_sec = (union_end - *last_start).num_seconds().max(0) as u32;
+        let duration = (last.duration + session.duration)
+            .min(union_sec.max(last.duration).max(session.duration));
+        let start_at = std::mem::take(&mut last.start_at);
+        let end_at = if end > *last_end {
+            session.end_at.clone()
+        } else {
+            std::mem::take(&mut last.end_at)
+        };
+        let deep_typing = last.deep_typing || session.deep_typing;
+        let typing_tap_count = last.typing_tap_count.max(session.typing_tap_count);
+        if session.duration > last.duration {
+            *last = session;
+        }
+        *last_end = union_end;
+        *last = TypingSessionMetrics {
+            start_at,
+            end_at,
+            duration,
+            deep_typing,
+            typing_tap_count,
+            ..last.clone()
+        };
+    }
+
+    let mut sessions: Vec<TypingSessionMetrics> = merged.into_iter().map(|(_, _, s)| s).collect();
+    sessions.extend(untimed);
+    (sessions, overlapping)
 }
 
 /// Compute inter-event gaps (time between consecutive events)
@@ -203,23 +588,24 @@ fn compute_inter_event_gaps(events: &[BehaviorEvent]) -> Vec<f64> {
     gaps.into_iter().map(|(gap, _)| gap).collect()
 }
 
-/// Detect idle segments (gaps > 30 seconds)
+/// Detect idle segments (gaps longer than the idle threshold)
 fn detect_idle_segments(
     events: &[BehaviorEvent],
     session_start: &chrono::DateTime<Utc>,
     session_end: &chrono::DateTime<Utc>,
+    idle_gap_sec: f64,
 ) -> Vec<IdleSegment> {
     let mut segments = Vec::new();
 
     if events.is_empty() {
         // Entire session is idle
         let duration_sec = (*session_end - *session_start).num_milliseconds() as f64 / 1000.0;
-        if duration_sec > IDLE_GAP_THRESHOLD_SEC {
-            // SDK subtracts the 30s threshold from idle time.
-            let idle_duration_sec = (duration_sec - IDLE_GAP_THRESHOLD_SEC).max(0.0);
+        if duration_sec > idle_gap_sec {
+            // SDK subtracts the idle threshold from idle time.
+            let idle_duration_sec = (duration_sec - idle_gap_sec).max(0.0);
             segments.push(IdleSegment {
                 start: *session_start
-                    + chrono::Duration::milliseconds((IDLE_GAP_THRESHOLD_SEC * 1000.0) as i64),
+                    + chrono::Duration::milliseconds((idle_gap_sec * 1000.0) as i64),
                 end: *session_end,
                 duration_sec: idle_duration_sec,
             });
@@ -229,11 +615,10 @@ fn detect_idle_segments(
 
     // Check gap from session start to first event
     let first_gap_sec = (events[0].timestamp - *session_start).num_milliseconds() as f64 / 1000.0;
-    if first_gap_sec > IDLE_GAP_THRESHOLD_SEC {
-        let idle_duration_sec = (first_gap_sec - IDLE_GAP_THRESHOLD_SEC).max(0.0);
+    if first_gap_sec > idle_gap_sec {
+        let idle_duration_sec = (first_gap_sec - idle_gap_sec).max(0.0);
         segments.push(IdleSegment {
-            start: *session_start
-                + chrono::Duration::milliseconds((IDLE_GAP_THRESHOLD_SEC * 1000.0) as i64),
+            start: *session_start + chrono::Duration::milliseconds((idle_gap_sec * 1000.0) as i64),
             end: events[0].timestamp,
             duration_sec: idle_duration_sec,
         });
@@ -242,11 +627,11 @@ fn detect_idle_segments(
     // Check gaps between events
     for pair in events.windows(2) {
         let gap_sec = (pair[1].timestamp - pair[0].timestamp).num_milliseconds() as f64 / 1000.0;
-        if gap_sec > IDLE_GAP_THRESHOLD_SEC {
-            let idle_duration_sec = (gap_sec - IDLE_GAP_THRESHOLD_SEC).max(0.0);
+        if gap_sec > idle_gap_sec {
+            let idle_duration_sec = (gap_sec - idle_gap_sec).max(0.0);
             segments.push(IdleSegment {
                 start: pair[0].timestamp
-                    + chrono::Duration::milliseconds((IDLE_GAP_THRESHOLD_SEC * 1000.0) as i64),
+                    + chrono::Duration::milliseconds((idle_gap_sec * 1000.0) as i64),
                 end: pair[1].timestamp,
                 duration_sec: idle_duration_sec,
             });
@@ -256,11 +641,11 @@ fn detect_idle_segments(
     // Check gap from last event to session end
     let last_gap_sec =
         (*session_end - events.last().unwrap().timestamp).num_milliseconds() as f64 / 1000.0;
-    if last_gap_sec > IDLE_GAP_THRESHOLD_SEC {
-        let idle_duration_sec = (last_gap_sec - IDLE_GAP_THRESHOLD_SEC).max(0.0);
+    if last_gap_sec > idle_gap_sec {
+        let idle_duration_sec = (last_gap_sec - idle_gap_sec).max(0.0);
         segments.push(IdleSegment {
             start: events.last().unwrap().timestamp
-                + chrono::Duration::milliseconds((IDLE_GAP_THRESHOLD_SEC * 1000.0) as i64),
+                + chrono::Duration::milliseconds((idle_gap_sec * 1000.0) as i64),
             end: *session_end,
             duration_sec: idle_duration_sec,
         });
@@ -274,6 +659,7 @@ fn detect_engagement_segments(
     events: &[BehaviorEvent],
     session_start: &chrono::DateTime<Utc>,
     session_end: &chrono::DateTime<Utc>,
+    config: &BehaviorConfig,
 ) -> Vec<EngagementSegment> {
     if events.is_empty() {
         return Vec::new();
@@ -304,7 +690,7 @@ fn detect_engagement_segments(
     // Check if initial gap is too large
     let initial_gap_sec =
         (events[first_idx].timestamp - *session_start).num_milliseconds() as f64 / 1000.0;
-    if initial_gap_sec <= IDLE_GAP_THRESHOLD_SEC {
+    if initial_gap_sec <= config.idle_gap_threshold_sec {
         segment_start = *session_start;
     }
 
@@ -313,7 +699,7 @@ fn detect_engagement_segments(
         let gap_sec = (pair[1].timestamp - pair[0].timestamp).num_milliseconds() as f64 / 1000.0;
         let is_interruption = interruption_types.contains(&current.event_type);
 
-        if is_interruption || gap_sec > IDLE_GAP_THRESHOLD_SEC {
+        if is_interruption || gap_sec > config.idle_gap_threshold_sec {
             // End current segment at the interruption time, or at last event before idle gap
             let segment_end = if is_interruption {
                 current.timestamp
@@ -321,7 +707,7 @@ fn detect_engagement_segments(
                 pair[0].timestamp
             };
             let duration_sec = (segment_end - segment_start).num_milliseconds() as f64 / 1000.0;
-            if duration_sec >= MIN_ENGAGEMENT_DURATION_SEC && segment_event_count > 0 {
+            if duration_sec >= config.min_engagement_duration_sec && segment_event_count > 0 {
                 segments.push(EngagementSegment {
                     start: segment_start,
                     end: segment_end,
@@ -340,14 +726,14 @@ fn detect_engagement_segments(
     // Close final segment
     let last_event_time = events.last().unwrap().timestamp;
     let final_gap_sec = (*session_end - last_event_time).num_milliseconds() as f64 / 1000.0;
-    let segment_end = if final_gap_sec <= IDLE_GAP_THRESHOLD_SEC {
+    let segment_end = if final_gap_sec <= config.idle_gap_threshold_sec {
         *session_end
     } else {
         last_event_time
     };
 
     let duration_sec = (segment_end - segment_start).num_milliseconds() as f64 / 1000.0;
-    if duration_sec >= MIN_ENGAGEMENT_DURATION_SEC && segment_event_count > 0 {
+    if duration_sec >= config.min_engagement_duration_sec && segment_event_count > 0 {
         segments.push(EngagementSegment {
             start: segment_start,
             end: segment_end,
@@ -362,7 +748,9 @@ fn detect_engagement_segments(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::behavior::types::{ScrollDirection, ScrollEvent};
+    use crate::behavior::types::{
+        AppSwitchEvent, BehaviorQualityFlag, ScrollDirection, ScrollEvent,
+    };
     use chrono::TimeZone;
 
     fn make_test_session() -> BehaviorSession {
@@ -461,6 +849,144 @@ mod tests {
         assert!((canonical.inter_event_gaps[1] - 30.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_screen_activity() {
+        let mut session = make_test_session();
+        let screen_event = |minute, event_type| BehaviorEvent {
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 15, 14, minute, 0).unwrap(),
+            event_type,
+            scroll: None,
+            tap: None,
+            swipe: None,
+            interruption: None,
+            typing: None,
+            app_switch: None,
+        };
+        session.events.extend([
+            screen_event(0, BehaviorEventType::Unlock),
+            screen_event(5, BehaviorEventType::ScreenOff),
+            screen_event(10, BehaviorEventType::ScreenOn),
+            screen_event(10, BehaviorEventType::Unlock),
+            screen_event(12, BehaviorEventType::ScreenOff),
+            screen_event(25, BehaviorEventType::ScreenOn),
+        ]);
+
+        let canonical = session_to_canonical(&session).unwrap();
+
+        // Screen state events do not count as interactions
+        assert_eq!(canonical.total_events, 3);
+        assert_eq!(canonical.inter_event_gaps.len(), 2);
+
+        assert_eq!(canonical.screen.unlock_events, 2);
+        assert_eq!(canonical.screen.screen_on_events, 2);
+        assert_eq!(canonical.screen.screen_off_events, 2);
+        // 14:00-14:05, 14:10-14:12, 14:25-session end
+        assert_eq!(
+            canonical.screen.screen_sessions_sec,
+            vec![300.0, 120.0, 300.0]
+        );
+    }
+
+    #[test]
+    fn test_app_usage() {
+        let mut session = make_test_session();
+        let switch = |minute, from: Option<&str>, to: &str| BehaviorEvent {
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 15, 14, minute, 0).unwrap(),
+            event_type: BehaviorEventType::AppSwitch,
+            scroll: None,
+            tap: None,
+            swipe: None,
+            interruption: None,
+            typing: None,
+            app_switch: Some(AppSwitchEvent {
+                from_app_id: from.map(str::to_string),
+                to_app_id: Some(to.to_string()),
+            }),
+        };
+        session.events.extend([
+            switch(5, Some("mail"), "chat"),
+            switch(10, Some("chat"), "browser"),
+            switch(20, Some("browser"), "chat"),
+        ]);
+
+        let canonical = session_to_canonical(&session).unwrap();
+        let usage: Vec<(&str, f64)> = canonical
+            .app_usage
+            .iter()
+            .map(|u| (u.app_id.as_str(), u.duration_sec))
+            .collect();
+        // mail until 14:05, chat 14:05-14:10 and 14:20-14:30, browser 14:10-14:20
+        assert_eq!(
+            usage,
+            vec![("chat", 900.0), ("browser", 600.0), ("mail", 300.0)]
+        );
+    }
+
+    #[test]
+    fn test_scroll_segments() {
+        let scroll = |second: u32, direction, velocity| BehaviorEvent {
+            timestamp: Utc
+                .with_ymd_and_hms(2024, 1, 15, 14, second / 60, second % 60)
+                .unwrap(),
+            event_type: BehaviorEventType::Scroll,
+            scroll: Some(ScrollEvent {
+                velocity: Some(velocity),
+                direction: Some(direction),
+                direction_reversal: false,
+            }),
+            tap: None,
+            swipe: None,
+            interruption: None,
+            typing: None,
+            app_switch: None,
+        };
+        let mut tap = scroll(40, ScrollDirection::Down, 0.0);
+        tap.event_type = BehaviorEventType::Tap;
+        tap.scroll = None;
+        let events = vec![
+            scroll(0, ScrollDirection::Down, 300.0),
+            scroll(10, ScrollDirection::Down, 500.0),
+            scroll(20, ScrollDirection::Down, 400.0),
+            // Direction change starts a new run
+            scroll(30, ScrollDirection::Up, 100.0),
+            // Any other interaction ends the run
+            tap,
+            scroll(50, ScrollDirection::Up, 200.0),
+            // Gap beyond the idle threshold
+            scroll(120, ScrollDirection::Up, 200.0),
+        ];
+
+        let segments = detect_scroll_segments(&events, 30.0);
+        let runs: Vec<(u32, f64)> = segments
+            .iter()
+            .map(|s| (s.event_count, s.duration_sec))
+            .collect();
+        assert_eq!(runs, vec![(3, 20.0), (1, 0.0), (1, 0.0), (1, 0.0)]);
+        assert_eq!(segments[0].mean_velocity, Some(400.0));
+    }
+
+    #[test]
+    fn test_late_night_events_use_session_timezone() {
+        let mut session = make_test_session();
+        // 14:01-14:02 UTC is 23:01-23:02 in Tokyo
+        assert_eq!(session_to_canonical(&session).unwrap().late_night_events, 0);
+
+        session.timezone = "Asia/Tokyo".to_string();
+        assert_eq!(session_to_canonical(&session).unwrap().late_night_events, 3);
+
+        // Unknown zones fall back to UTC and are flagged
+        session.timezone = "Not/AZone".to_string();
+        let canonical = session_to_canonical(&session).unwrap();
+        assert_eq!(canonical.late_night_events, 0);
+        assert_eq!(canonical.timezone, "UTC");
+        assert!(canonical.invalid_timezone);
+        assert!(
+            crate::behavior::normalizer::BehaviorNormalizer::normalize(canonical)
+                .quality_flags
+                .contains(&BehaviorQualityFlag::InvalidTimezone)
+        );
+    }
+
     #[test]
     fn test_idle_segment_detection() {
         let start = Utc.with_ymd_and_hms(2024, 1, 15, 14, 0, 0).unwrap();
@@ -538,4 +1064,104 @@ mod tests {
         let result = session_to_canonical(&session);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_out_of_bounds_policy() {
+        let mut session = make_test_session();
+        let mut early = session.events[0].clone();
+        early.timestamp = Utc.with_ymd_and_hms(2024, 1, 15, 13, 50, 0).unwrap();
+        let mut future = session.events[0].clone();
+        future.timestamp = Utc::now() + chrono::Duration::days(1);
+        session.end_time = Utc::now() + chrono::Duration::days(2);
+        session.events.extend([early, future]);
+
+        let canonical = |policy| {
+            let config = BehaviorConfig::default().with_out_of_bounds(policy);
+            session_to_canonical_with_config(&session, &config)
+        };
+
+        let clamped = canonical(OutOfBoundsPolicy::Clamp).unwrap();
+        assert_eq!(clamped.out_of_bounds_events, 2);
+        assert_eq!(clamped.total_events, 5);
+        // Clamped events stay within the session, so no gap is negative
+        assert!(clamped.inter_event_gaps.iter().all(|g| *g >= 0.0));
+
+        let dropped = canonical(OutOfBoundsPolicy::Drop).unwrap();
+        assert_eq!(dropped.out_of_bounds_events, 2);
+        assert_eq!(dropped.total_events, 3);
+        let normalized = crate::behavior::normalizer::BehaviorNormalizer::normalize(dropped);
+        assert!(normalized
+            .quality_flags
+            .contains(&BehaviorQualityFlag::EventsOutOfBounds));
+
+        assert!(matches!(
+            canonical(OutOfBoundsPolicy::Fail),
+            Err(ComputeError::InvalidBehaviorSession(_))
+        ));
+    }
+
+    #[test]
+    fn test_overlapping_typing_sessions_are_merged() {
+        let typing = |at: &str, start: &str, end: &str, duration: f64, taps: u32| {
+            serde_json::from_value::<BehaviorEvent>(serde_json::json!({
+                "timestamp": at,
+                "event_type": "typing",
+                "typing": {
+                    "start_at": start,
+                    "end_at": end,
+                    "duration_sec": duration,
+                    "typing_tap_count": taps,
+                },
+            }))
+            .unwrap()
+        };
+        let mut session = make_test_session();
+        session.events.extend([
+            typing(
+                "2024-01-15T14:10:00Z",
+                "2024-01-15T14:10:00Z",
+                "2024-01-15T14:11:00Z",
+                60.0,
+                80,
+            ),
+            // Exact duplicate of the first session
+            typing(
+                "2024-01-15T14:10:00Z",
+                "2024-01-15T14:10:00Z",
+                "2024-01-15T14:11:00Z",
+                60.0,
+                80,
+            ),
+            // Overlaps the first session by 30 seconds
+            typing(
+                "2024-01-15T14:10:30Z",
+                "2024-01-15T14:10:30Z",
+                "2024-01-15T14:11:30Z",
+                60.0,
+                90,
+            ),
+            typing(
+                "2024-01-15T14:20:00Z",
+                "2024-01-15T14:20:00Z",
+                "2024-01-15T14:20:40Z",
+                40.0,
+                50,
+            ),
+        ]);
+
+        let canonical = session_to_canonical(&session).unwrap();
+        assert_eq!(canonical.overlapping_typing_sessions, 2);
+        assert_eq!(canonical.typing_sessions.len(), 2);
+        let merged = &canonical.typing_sessions[0];
+        assert_eq!(merged.start_at, "2024-01-15T14:10:00Z");
+        assert_eq!(merged.end_at, "2024-01-15T14:11:30Z");
+        assert_eq!(merged.duration, 90);
+        assert_eq!(merged.typing_tap_count, 90);
+        assert_eq!(canonical.total_typing_duration_sec, 130.0);
+
+        let normalized = crate::behavior::normalizer::BehaviorNormalizer::normalize(canonical);
+        assert!(normalized
+            .quality_flags
+            .contains(&BehaviorQualityFlag::OverlappingTypingSessions));
+    }
 }
@@ -0,0 +1,155 @@
+//! Experimental affect estimation
+//!
+//! Estimates `arousal` and `strain_affect` readings for the HSI affect domain
+//! by combining behavioral load (notification load, burstiness) with optional
+//! physiological deltas from the wearable pipeline (HRV and resting HR
+//! deviation from baseline).
+//!
+//! These estimates are heuristic and not validated against self-report, so
+//! their confidence is discounted relative to the behavioral readings.
+//! Enabled with the `experimental-affect` feature.
+
+use crate::behavior::bio_context::{deviation_to_unit, BioContext};
+use crate::behavior::types::{ContextualBehaviorSignals, HsiAxisReading, HsiDirection};
+use serde::{Deserialize, Serialize};
+
+/// Multiplier applied to the session confidence for affect readings
+pub const AFFECT_CONFIDENCE_DISCOUNT: f64 = 0.5;
+
+/// Arousal weights: HRV drop, RHR rise, notification load, burstiness
+const AROUSAL_WEIGHTS: [f64; 4] = [0.3, 0.3, 0.2, 0.2];
+
+/// Strain weights: HRV drop, RHR rise, notification load, burstiness
+const STRAIN_WEIGHTS: [f64; 4] = [0.4, 0.3, 0.2, 0.1];
+
+/// Physiological context from the wearable pipeline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhysiologyContext {
+    /// HRV deviation from baseline in percent
+    pub hrv_deviation_pct: Option<f64>,
+    /// Resting HR deviation from baseline in percent
+    pub rhr_deviation_pct: Option<f64>,
+}
+
+impl From<&crate::types::HsiBaseline> for PhysiologyContext {
+    fn from(baseline: &crate::types::HsiBaseline) -> Self {
+        Self {
+            hrv_deviation_pct: baseline.hrv_deviation_pct,
+            rhr_deviation_pct: baseline.rhr_deviation_pct,
+        }
+    }
+}
+
+impl From<&crate::types::ContextualSignals> for PhysiologyContext {
+    fn from(signals: &crate::types::ContextualSignals) -> Self {
+        Self {
+            hrv_deviation_pct: signals.hrv_deviation_pct,
+            rhr_deviation_pct: signals.rhr_deviation_pct,
+        }
+    }
+}
+
+impl From<&BioContext> for PhysiologyContext {
+    fn from(context: &BioContext) -> Self {
+        Self {
+            hrv_deviation_pct: context.hrv_deviation_pct,
+            rhr_deviation_pct: context.rhr_deviation_pct,
+        }
+    }
+}
+
+/// Estimate affect readings for a behavioral session.
+///
+/// Each score is a weighted mean of the available components, renormalized
+/// over the weights that have data. Confidence is the session confidence
+/// scaled by [`AFFECT_CONFIDENCE_DISCOUNT`] and by the share of weight backed
+/// by data, so behavior-only estimates are reported with lower confidence.
+pub fn estimate_affect(
+    signals: &ContextualBehaviorSignals,
+    physiology: Option<&PhysiologyContext>,
+    confidence: f64,
+    window_id: &str,
+    source_id: &str,
+) -> Vec<HsiAxisReading> {
+    let physiology = physiology.copied().unwrap_or_default();
+    let components = [
+        // Lower HRV than baseline indicates higher arousal
+        physiology
+            .hrv_deviation_pct
+            .map(|pct| deviation_to_unit(-pct)),
+        physiology.rhr_deviation_pct.map(deviation_to_unit),
+        Some(signals.derived.notification_load.clamp(0.0, 1.0)),
+        Some(signals.derived.burstiness.clamp(0.0, 1.0)),
+    ];
+
+    [
+        ("arousal", AROUSAL_WEIGHTS, HsiDirection::HigherIsMore),
+        ("strain_affect", STRAIN_WEIGHTS, HsiDirection::HigherIsMore),
+    ]
+    .into_iter()
+    .map(|(axis, weights, direction)| {
+        let (score, coverage) = weighted_mean(&components, &weights);
+        HsiAxisReading {
+            axis: axis.to_string(),
+            score: Some(score),
+            confidence: (confidence * AFFECT_CONFIDENCE_DISCOUNT * coverage).clamp(0.0, 1.0),
+            window_id: window_id.to_string(),
+            direction: Some(direction),
+            unit: Some("normalized".to_string()),
+            evidence_source_ids: Some(vec![source_id.to_string()]),
+            notes: Some(format!(
+                "Experimental; weights hrv={} rhr={} notification_load={} burstiness={}",
+                weights[0], weights[1], weights[2], weights[3]
+            )),
+        }
+    })
+    .collect()
+}
+
+/// Weighted mean over available components and the fraction of weight used
+fn weighted_mean(components: &[Option<f64>; 4], weights: &[f64; 4]) -> (f64, f64) {
+    let (sum, used) = components
+        .iter()
+        .zip(weights)
+        .filter_map(|(c, w)| c.map(|v| (v * w, *w)))
+        .fold((0.0, 0.0), |(s, u), (v, w)| (s + v, u + w));
+    let total: f64 = weights.iter().sum();
+    if used > 0.0 {
+        (sum / used, used / total)
+    } else {
+        (0.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deviation_mapping() {
+        assert!((deviation_to_unit(0.0) - 0.5).abs() < 1e-9);
+        assert_eq!(deviation_to_unit(25.0), 1.0);
+        assert_eq!(deviation_to_unit(-40.0), 0.0);
+    }
+
+    #[test]
+    fn test_weighted_mean_renormalizes_missing_components() {
+        let (score, coverage) =
+            weighted_mean(&[None, None, Some(0.8), Some(0.4)], &AROUSAL_WEIGHTS);
+        assert!((score - 0.6).abs() < 1e-9);
+        assert!((coverage - 0.4).abs() < 1e-9);
+
+        // Suppressed HRV and elevated RHR push arousal up
+        let (score, coverage) = weighted_mean(
+            &[
+                Some(deviation_to_unit(20.0)),
+                Some(deviation_to_unit(10.0)),
+                Some(0.5),
+                Some(0.5),
+            ],
+            &AROUSAL_WEIGHTS,
+        );
+        assert!(score > 0.6);
+        assert!((coverage - 1.0).abs() < 1e-9);
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! This module manages rolling baselines for behavioral metrics across sessions.
 //! Baselines enable relative interpretation of distraction, focus, and other signals.
+//! Distraction also carries a trend: its least-squares slope across the
+//! baseline sessions.
 
+use crate::baseline::{linear_slope, MIN_TREND_SAMPLES};
 use crate::behavior::types::{
     BehaviorBaselines, ContextualBehaviorSignals, DerivedBehaviorSignals,
 };
@@ -12,6 +15,9 @@ use std::collections::VecDeque;
 /// Default baseline window in sessions
 pub const DEFAULT_BEHAVIOR_BASELINE_WINDOW: usize = 20;
 
+/// Smallest baseline a percentage deviation is computed against
+const MIN_DEVIATION_BASELINE: f64 = 1e-6;
+
 /// Behavioral baseline store for managing rolling averages
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BehaviorBaselineStore {
@@ -23,8 +29,14 @@ pub struct BehaviorBaselineStore {
     burstiness_values: VecDeque<f64>,
     /// Rolling interaction intensity values
     intensity_values: VecDeque<f64>,
+    /// Rolling late-night usage ratios
+    #[serde(default)]
+    late_night_values: VecDeque<f64>,
     /// Maximum window size (number of sessions)
     window_size: usize,
+    /// Producer instance ID pinned by the host, kept across restarts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    instance_id: Option<String>,
 }
 
 impl Default for BehaviorBaselineStore {
@@ -41,7 +53,9 @@ impl BehaviorBaselineStore {
             focus_values: VecDeque::with_capacity(window_size),
             burstiness_values: VecDeque::with_capacity(window_size),
             intensity_values: VecDeque::with_capacity(window_size),
+            late_night_values: VecDeque::with_capacity(window_size),
             window_size,
+            instance_id: None,
         }
     }
 
@@ -50,17 +64,9 @@ impl BehaviorBaselineStore {
         &mut self,
         derived: DerivedBehaviorSignals,
     ) -> ContextualBehaviorSignals {
-        // Get current baselines before update (for deviation calculation)
-        let old_baselines = self.get_baselines();
-
-        // Calculate deviations from baseline
-        let distraction_deviation_pct = self.calculate_deviation(
-            Some(derived.distraction_score),
-            old_baselines.distraction_baseline,
-        );
-
-        let focus_deviation_pct =
-            self.calculate_deviation(Some(derived.focus_hint), old_baselines.focus_baseline);
+        // Deviations are computed against the baselines before this session
+        let contextual = self.contextualize(derived);
+        let derived = &contextual.derived;
 
         // Update rolling values with current data
         self.distraction_values.push_back(derived.distraction_score);
@@ -84,17 +90,62 @@ impl BehaviorBaselineStore {
             self.intensity_values.pop_front();
         }
 
+        self.late_night_values
+            .push_back(derived.late_night_usage_ratio);
+        while self.late_night_values.len() > self.window_size {
+            self.late_night_values.pop_front();
+        }
+
         // Get updated baselines (including current data) for the output
+        ContextualBehaviorSignals {
+            baselines: self.get_baselines(),
+            ..contextual
+        }
+    }
+
+    /// Compare derived signals against the current baselines without updating them
+    pub fn contextualize(&self, derived: DerivedBehaviorSignals) -> ContextualBehaviorSignals {
         let baselines = self.get_baselines();
 
+        let distraction_deviation_pct = self.calculate_deviation(
+            Some(derived.distraction_score),
+            baselines.distraction_baseline,
+        );
+
+        let focus_deviation_pct =
+            self.calculate_deviation(Some(derived.focus_hint), baselines.focus_baseline);
+
+        let distraction_trend = self.distraction_trend(derived.distraction_score);
+
         ContextualBehaviorSignals {
             derived,
             baselines,
             distraction_deviation_pct,
             focus_deviation_pct,
+            distraction_trend,
         }
     }
 
+    /// Change in distraction across the baseline window ending with `current`,
+    /// from a least-squares fit over session order (-1 to 1, positive when rising)
+    fn distraction_trend(&self, current: f64) -> Option<f64> {
+        let skip = (self.distraction_values.len() + 1).saturating_sub(self.window_size);
+        let points: Vec<(f64, f64)> = self
+            .distraction_values
+            .iter()
+            .copied()
+            .chain([current])
+            .skip(skip)
+            .enumerate()
+            .map(|(i, value)| (i as f64, value))
+            .collect();
+        if points.len() < MIN_TREND_SAMPLES {
+            return None;
+        }
+        let slope = linear_slope(&points)?;
+        Some((slope * (points.len() - 1) as f64).clamp(-1.0, 1.0))
+    }
+
     /// Get current baseline values
     pub fn get_baselines(&self) -> BehaviorBaselines {
         BehaviorBaselines {
@@ -102,15 +153,21 @@ impl BehaviorBaselineStore {
             focus_baseline: Self::rolling_average(&self.focus_values),
             burstiness_baseline: Self::rolling_average(&self.burstiness_values),
             intensity_baseline: Self::rolling_average(&self.intensity_values),
+            late_night_baseline: Self::rolling_average(&self.late_night_values),
             sessions_in_baseline: self.distraction_values.len() as u32,
         }
     }
 
     /// Calculate deviation from baseline as percentage
+    ///
+    /// Baselines below [`MIN_DEVIATION_BASELINE`] count as zero, so the
+    /// percentage stays finite.
     fn calculate_deviation(&self, current: Option<f64>, baseline: Option<f64>) -> Option<f64> {
         match (current, baseline) {
-            (Some(curr), Some(base)) if base > 0.0 => Some(((curr - base) / base) * 100.0),
-            (Some(curr), Some(base)) if base == 0.0 && curr > 0.0 => Some(100.0), // From 0 to something
+            (Some(curr), Some(base)) if base >= MIN_DEVIATION_BASELINE => {
+                Some(((curr - base) / base) * 100.0)
+            }
+            (Some(curr), Some(base)) if base >= 0.0 && curr > 0.0 => Some(100.0), // From 0 to something
             (Some(_curr), Some(_base)) => Some(0.0),                              // Both are 0
             _ => None,
         }
@@ -135,6 +192,15 @@ impl BehaviorBaselineStore {
         serde_json::to_string(self)
     }
 
+    /// Producer instance ID saved with the store, if one was pinned
+    pub fn instance_id(&self) -> Option<&str> {
+        self.instance_id.as_deref()
+    }
+
+    pub fn set_instance_id(&mut self, instance_id: Option<String>) {
+        self.instance_id = instance_id;
+    }
+
     /// Get the number of sessions currently in the baseline
     pub fn session_count(&self) -> usize {
         self.distraction_values.len()
@@ -146,6 +212,7 @@ impl BehaviorBaselineStore {
         self.focus_values.clear();
         self.burstiness_values.clear();
         self.intensity_values.clear();
+        self.late_night_values.clear();
     }
 }
 
@@ -172,12 +239,22 @@ mod tests {
             typing_events: 5,
             app_switch_events: 5,
             scroll_direction_reversals: 10,
+            long_press_events: 0,
+            out_of_bounds_events: 0,
+            overlapping_typing_sessions: 0,
+            invalid_timezone: false,
             total_typing_duration_sec: 60.0,
             typing_sessions: vec![],
             idle_segments: vec![],
             total_idle_time_sec: 60.0,
             engagement_segments: vec![],
             inter_event_gaps: vec![10.0, 12.0, 8.0],
+            screen: Default::default(),
+            late_night_events: 0,
+            check_event_times: vec![],
+            app_usage: vec![],
+            scroll_velocities: vec![],
+            scroll_segments: vec![],
             computed_at: Utc::now(),
         };
 
@@ -189,6 +266,7 @@ mod tests {
             swipes_per_min: 0.17,
             notifications_per_min: 0.17,
             app_switches_per_min: 0.17,
+            unlocks_per_min: 0.0,
             coverage: 0.8,
             quality_flags: vec![],
         };
@@ -205,6 +283,24 @@ mod tests {
             burstiness,
             deep_focus_blocks: 1,
             interaction_intensity: intensity,
+            unlock_frequency: 0.0,
+            avg_screen_session_sec: 0.0,
+            checking_habit_score: 0.0,
+            deep_focus_coverage: 0.0,
+            engagement_stability: None,
+            late_night_usage_ratio: 0.0,
+            app_diversity_entropy: None,
+            long_press_rate: None,
+            swipe_tap_ratio: None,
+            gesture_complexity: None,
+            scroll_velocity_median: None,
+            scroll_velocity_p90: None,
+            doomscroll_segments: 0,
+            doomscroll_segment_ratio: None,
+            doomscroll_score: 0.0,
+            check_frequency_per_hour: 0.0,
+            check_bursts: 0,
+            compulsive_check_score: 0.0,
             distraction_score: distraction,
             focus_hint: 1.0 - distraction,
         }
@@ -335,4 +431,45 @@ mod tests {
         // Average distraction = 0.4, so average focus = 0.6
         assert!((baselines.focus_baseline.unwrap() - 0.6).abs() < 0.001);
     }
+
+    #[test]
+    fn test_distraction_trend() {
+        let mut store = BehaviorBaselineStore::new(4);
+        let mut trends = Vec::new();
+        for distraction in [0.2, 0.3, 0.4, 0.5, 0.4, 0.3] {
+            let contextual = store.update_and_contextualize(make_derived(distraction, 0.5, 0.4));
+            trends.push(contextual.distraction_trend);
+        }
+
+        assert_eq!(trends[..2], [None, None]);
+        // 0.1 per session across the last 3 sessions
+        assert!((trends[2].unwrap() - 0.2).abs() < 1e-9);
+        // The window holds the last 4 sessions: 0.4, 0.5, 0.4, 0.3
+        assert!((trends[5].unwrap() + 0.12).abs() < 1e-9);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_deviations_are_finite(
+            distractions in proptest::collection::vec(
+                proptest::prop_oneof![
+                    proptest::strategy::Just(0.0),
+                    proptest::strategy::Just(f64::MIN_POSITIVE),
+                    0.0..=1.0f64,
+                ],
+                1..30,
+            )
+        ) {
+            let mut store = BehaviorBaselineStore::new(10);
+            for distraction in distractions {
+                let contextual = store.update_and_contextualize(make_derived(distraction, 0.5, 0.4));
+                for deviation in [contextual.distraction_deviation_pct, contextual.focus_deviation_pct]
+                    .into_iter()
+                    .flatten()
+                {
+                    proptest::prop_assert!(deviation.is_finite(), "{}", deviation);
+                }
+            }
+        }
+    }
 }
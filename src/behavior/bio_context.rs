@@ -0,0 +1,362 @@
+//! Wearable bio context for behavioral sessions
+//!
+//! Behavioral payloads are otherwise computed from phone events alone. A
+//! [`BioContext`] takes the latest wearable day recorded in a
+//! [`BaselineStore`] on or before the session's local date and compares it
+//! with the baselines of the days before it, the way the wearable pipeline
+//! does. The result is emitted as readings in the `physiology` and `sleep`
+//! axes domains of the session payload.
+//!
+//! A wearable day is usually hours to days old when a session ends, so the
+//! readings' confidence halves over a half-life set per field by
+//! [`SnapshotOptions`]: sleep stays relevant longest, HRV the shortest. Days
+//! older than [`BIO_CONTEXT_MAX_AGE_DAYS`] are not used.
+//!
+//! The context also feeds the `effective_capacity` axis: the session's focus
+//! down-weighted by recovery and sleep, as set by [`CapacityWeights`].
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::baseline::BaselineStore;
+use crate::behavior::config::CapacityWeights;
+use crate::behavior::types::{CanonicalBehaviorSignals, HsiAxisReading, HsiDirection};
+use crate::error::ComputeError;
+
+/// Default days over which the confidence of HRV and resting HR deviations halves
+pub const DEFAULT_HRV_HALF_LIFE_DAYS: f64 = 1.0;
+
+/// Default days over which the confidence of recovery readings halves
+pub const DEFAULT_RECOVERY_HALF_LIFE_DAYS: f64 = 2.0;
+
+/// Default days over which the confidence of sleep readings halves
+pub const DEFAULT_SLEEP_HALF_LIFE_DAYS: f64 = 3.0;
+
+/// Oldest wearable day, in days before the session, used as context
+pub const BIO_CONTEXT_MAX_AGE_DAYS: i64 = 3;
+
+/// Source ID of the wearable baselines in session payloads
+pub const BIO_CONTEXT_SOURCE_ID: &str = "s_wearable";
+
+/// Baseline deviation (percent) that maps to the edge of the 0-1 range
+const DEVIATION_SCALE_PCT: f64 = 25.0;
+
+/// How quickly bio context readings lose confidence as the wearable day ages
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotOptions {
+    /// Half-life in days of the HRV and resting HR deviations
+    pub hrv_half_life_days: f64,
+    /// Half-life in days of the recovery score, the `s_wearable` source quality
+    /// and `effective_capacity`
+    pub recovery_half_life_days: f64,
+    /// Half-life in days of the sleep readings
+    pub sleep_half_life_days: f64,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            hrv_half_life_days: DEFAULT_HRV_HALF_LIFE_DAYS,
+            recovery_half_life_days: DEFAULT_RECOVERY_HALF_LIFE_DAYS,
+            sleep_half_life_days: DEFAULT_SLEEP_HALF_LIFE_DAYS,
+        }
+    }
+}
+
+impl SnapshotOptions {
+    /// Check that every half-life is a positive number of days
+    pub fn validate(&self) -> Result<(), ComputeError> {
+        for (name, value) in [
+            ("hrv_half_life_days", self.hrv_half_life_days),
+            ("recovery_half_life_days", self.recovery_half_life_days),
+            ("sleep_half_life_days", self.sleep_half_life_days),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(ComputeError::InvalidConfig(format!(
+                    "{name} must be a positive number of days, got {value}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One wearable day relative to its baselines, as seen from a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BioContext {
+    /// Wearable day the context comes from (`YYYY-MM-DD`)
+    pub date: String,
+    /// Days between the wearable day and the session's local date
+    pub age_days: i64,
+    /// HRV deviation from baseline in percent
+    pub hrv_deviation_pct: Option<f64>,
+    /// Resting HR deviation from baseline in percent
+    pub rhr_deviation_pct: Option<f64>,
+    /// Sleep duration deviation from baseline in percent
+    pub sleep_deviation_pct: Option<f64>,
+    /// Sleep efficiency (0-1)
+    pub sleep_efficiency: Option<f64>,
+    /// Vendor sleep score, scaled to 0-1
+    pub sleep_score: Option<f64>,
+    /// Vendor recovery score, scaled to 0-1
+    pub recovery_score: Option<f64>,
+}
+
+impl BioContext {
+    /// Context for a session from the latest wearable day on or before its local date
+    pub fn for_session(store: &BaselineStore, session: &CanonicalBehaviorSignals) -> Option<Self> {
+        let date = match session.timezone.parse::<Tz>() {
+            Ok(tz) => session.end_time.with_timezone(&tz).date_naive(),
+            Err(_) => session.end_time.date_naive(),
+        };
+        Self::from_store(store, date)
+    }
+
+    /// Context from the latest recorded day on or before `as_of`, if it is
+    /// no older than [`BIO_CONTEXT_MAX_AGE_DAYS`]
+    pub fn from_store(store: &BaselineStore, as_of: NaiveDate) -> Option<Self> {
+        let dated = |date: Option<&str>| NaiveDate::parse_from_str(date?, "%Y-%m-%d").ok();
+        let (day_date, day) = store
+            .days()
+            .filter_map(|day| {
+                let date = dated(day.date.as_deref())?;
+                (date <= as_of && !day.metrics.is_empty()).then_some((date, day))
+            })
+            .last()?;
+        let age_days = (as_of - day_date).num_days();
+        if age_days > BIO_CONTEXT_MAX_AGE_DAYS {
+            return None;
+        }
+
+        // Compare against the days before, as the wearable pipeline did
+        let before = store.days_before(day.date.as_deref()?);
+        let metrics = &day.metrics;
+        let baselines = before.get_baselines_for_hrv_scale(metrics.hrv_scale);
+
+        Some(Self {
+            date: day_date.format("%Y-%m-%d").to_string(),
+            age_days,
+            hrv_deviation_pct: before
+                .calculate_deviation(metrics.hrv_ms, baselines.hrv_baseline_ms),
+            rhr_deviation_pct: before
+                .calculate_deviation(metrics.rhr_bpm, baselines.rhr_baseline_bpm),
+            sleep_deviation_pct: before
+                .calculate_deviation(metrics.sleep_minutes, baselines.sleep_baseline_minutes),
+            sleep_efficiency: metrics.sleep_efficiency,
+            sleep_score: metrics.sleep_score,
+            recovery_score: metrics.recovery_score,
+        })
+    }
+
+    /// Confidence after decay over `half_life_days` (1.0 for a same-day context)
+    pub fn confidence(&self, half_life_days: f64) -> f64 {
+        0.5_f64.powf(self.age_days.max(0) as f64 / half_life_days)
+    }
+
+    /// Recovery and sleep combined into 0-1 readiness, or `None` without either.
+    ///
+    /// Recovery is the vendor recovery score, else the HRV deviation; sleep is
+    /// the vendor sleep score, else sleep efficiency.
+    pub fn readiness(&self, weights: &CapacityWeights) -> Option<f64> {
+        let recovery = self
+            .recovery_score
+            .or(self.hrv_deviation_pct.map(deviation_to_unit));
+        let sleep = self.sleep_score.or(self.sleep_efficiency);
+        let parts: Vec<(f64, f64)> = [(recovery, weights.recovery), (sleep, weights.sleep)]
+            .into_iter()
+            .filter_map(|(value, weight)| Some((value?.clamp(0.0, 1.0), weight)))
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect();
+        let total: f64 = parts.iter().map(|(_, weight)| weight).sum();
+        (total > 0.0).then(|| parts.iter().map(|(v, w)| v * w).sum::<f64>() / total)
+    }
+
+    /// Focus down-weighted by readiness (see [`CapacityWeights`])
+    pub fn effective_capacity(&self, focus: f64, weights: &CapacityWeights) -> Option<f64> {
+        let readiness = self.readiness(weights)?;
+        let scale = 1.0 - weights.physiology + weights.physiology * readiness;
+        Some((focus * scale).clamp(0.0, 1.0))
+    }
+
+    /// `physiology` and `sleep` domain readings for a session window
+    pub fn readings(
+        &self,
+        window_id: &str,
+        options: &SnapshotOptions,
+    ) -> (Vec<HsiAxisReading>, Vec<HsiAxisReading>) {
+        let hrv = self.confidence(options.hrv_half_life_days);
+        let recovery = self.confidence(options.recovery_half_life_days);
+        let sleep = self.confidence(options.sleep_half_life_days);
+        let reading =
+            |axis: &str, score: f64, confidence, direction, unit: Option<&str>| HsiAxisReading {
+                axis: axis.to_string(),
+                score: Some(score.clamp(0.0, 1.0)),
+                confidence,
+                window_id: window_id.to_string(),
+                direction: Some(direction),
+                unit: unit.map(str::to_string),
+                evidence_source_ids: Some(vec![BIO_CONTEXT_SOURCE_ID.to_string()]),
+                notes: Some(format!("Wearable day {}", self.date)),
+            };
+        let value = |axis, score: Option<f64>, confidence| {
+            score.map(|score| reading(axis, score, confidence, HsiDirection::HigherIsMore, None))
+        };
+        let deviation = |axis, pct: Option<f64>, confidence| {
+            pct.map(|pct| HsiAxisReading {
+                notes: Some(format!(
+                    "Wearable day {} vs baseline; 0.5 = at baseline, 0 and 1 = {}% below and above",
+                    self.date, DEVIATION_SCALE_PCT
+                )),
+                ..reading(
+                    axis,
+                    deviation_to_unit(pct),
+                    confidence,
+                    HsiDirection::Bidirectional,
+                    Some("deviation"),
+                )
+            })
+        };
+
+        let physiology = [
+            value("recovery", self.recovery_score, recovery),
+            deviation("hrv_deviation", self.hrv_deviation_pct, hrv),
+            deviation("rhr_deviation", self.rhr_deviation_pct, hrv),
+        ];
+        let sleep = [
+            value("sleep_efficiency", self.sleep_efficiency, sleep),
+            value("sleep_score", self.sleep_score, sleep),
+            deviation("sleep_duration_deviation", self.sleep_deviation_pct, sleep),
+        ];
+        (
+            physiology.into_iter().flatten().collect(),
+            sleep.into_iter().flatten().collect(),
+        )
+    }
+}
+
+/// Map a baseline deviation in percent to 0-1, with 0% at 0.5
+pub(crate) fn deviation_to_unit(pct: f64) -> f64 {
+    (0.5 + pct / (2.0 * DEVIATION_SCALE_PCT)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::DayMetrics;
+
+    fn store() -> BaselineStore {
+        let mut store = BaselineStore::default();
+        for (date, hrv) in [
+            ("2024-01-10", 60.0),
+            ("2024-01-11", 60.0),
+            ("2024-01-12", 60.0),
+            ("2024-01-13", 45.0),
+        ] {
+            store.replace_day(
+                date,
+                DayMetrics {
+                    hrv_ms: Some(hrv),
+                    sleep_efficiency: Some(0.9),
+                    ..DayMetrics::default()
+                },
+            );
+        }
+        store
+    }
+
+    #[test]
+    fn test_latest_day_compared_with_days_before() {
+        let as_of = NaiveDate::from_ymd_opt(2024, 1, 14).unwrap();
+        let context = BioContext::from_store(&store(), as_of).unwrap();
+
+        assert_eq!(context.date, "2024-01-13");
+        assert_eq!(context.age_days, 1);
+        assert_eq!(context.hrv_deviation_pct, Some(-25.0));
+        assert_eq!(context.confidence(DEFAULT_HRV_HALF_LIFE_DAYS), 0.5);
+
+        let (physiology, sleep) = context.readings("w_session", &SnapshotOptions::default());
+        assert_eq!(physiology[0].axis, "hrv_deviation");
+        assert_eq!(physiology[0].score, Some(0.0));
+        assert_eq!(physiology[0].confidence, 0.5);
+        assert_eq!(sleep[0].axis, "sleep_efficiency");
+        assert_eq!(sleep[0].confidence, 0.5_f64.powf(1.0 / 3.0));
+
+        // A session before the latest day sees the day before it
+        let earlier = NaiveDate::from_ymd_opt(2024, 1, 12).unwrap();
+        let context = BioContext::from_store(&store(), earlier).unwrap();
+        assert_eq!(context.hrv_deviation_pct, Some(0.0));
+        assert_eq!(context.confidence(DEFAULT_HRV_HALF_LIFE_DAYS), 1.0);
+    }
+
+    #[test]
+    fn test_sleep_decays_slower_than_hrv() {
+        let as_of = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let context = BioContext::from_store(&store(), as_of).unwrap();
+        assert_eq!(context.age_days, 2);
+
+        let (physiology, sleep) = context.readings("w_session", &SnapshotOptions::default());
+        assert_eq!(physiology[0].axis, "hrv_deviation");
+        assert_eq!(physiology[0].confidence, 0.25);
+        assert_eq!(sleep[0].axis, "sleep_efficiency");
+        assert!(sleep[0].confidence > physiology[0].confidence);
+
+        // Custom half-lives apply per field
+        let options = SnapshotOptions {
+            hrv_half_life_days: 2.0,
+            sleep_half_life_days: 1.0,
+            ..SnapshotOptions::default()
+        };
+        let (physiology, sleep) = context.readings("w_session", &options);
+        assert_eq!(physiology[0].confidence, 0.5);
+        assert_eq!(sleep[0].confidence, 0.25);
+
+        let invalid = SnapshotOptions {
+            sleep_half_life_days: 0.0,
+            ..SnapshotOptions::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_effective_capacity() {
+        let as_of = NaiveDate::from_ymd_opt(2024, 1, 13).unwrap();
+        let mut context = BioContext::from_store(&store(), as_of).unwrap();
+        let weights = CapacityWeights::default();
+
+        // HRV 25% below baseline stands in for recovery (0.0); efficiency 0.9 for sleep
+        assert!((context.readiness(&weights).unwrap() - 0.36).abs() < 1e-9);
+        let capacity = context.effective_capacity(0.8, &weights).unwrap();
+        assert!((capacity - 0.8 * 0.68).abs() < 1e-9);
+
+        // Full recovery and sleep leave focus unchanged; physiology 0 ignores them
+        context.recovery_score = Some(1.0);
+        context.sleep_score = Some(1.0);
+        assert_eq!(context.effective_capacity(0.8, &weights), Some(0.8));
+        context.recovery_score = Some(0.0);
+        let ignored = CapacityWeights {
+            physiology: 0.0,
+            ..weights
+        };
+        assert_eq!(context.effective_capacity(0.8, &ignored), Some(0.8));
+
+        // Nothing to weigh focus by
+        let empty = BioContext {
+            hrv_deviation_pct: None,
+            recovery_score: None,
+            sleep_score: None,
+            sleep_efficiency: None,
+            ..context
+        };
+        assert_eq!(empty.effective_capacity(0.8, &weights), None);
+    }
+
+    #[test]
+    fn test_stale_days_are_not_used() {
+        let as_of = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        assert!(BioContext::from_store(&store(), as_of).is_none());
+        let as_of = NaiveDate::from_ymd_opt(2024, 1, 9).unwrap();
+        assert!(BioContext::from_store(&store(), as_of).is_none());
+    }
+}
@@ -0,0 +1,285 @@
+//! Behavioral pipeline configuration
+//!
+//! Thresholds that define idle time, engagement, deep focus and doomscrolling. Products
+//! disagree on these, so they are configurable rather than compile-time.
+
+use crate::error::ComputeError;
+use serde::{Deserialize, Serialize};
+
+/// Default minimum gap (seconds) between events to count as idle
+pub const DEFAULT_IDLE_GAP_THRESHOLD_SEC: f64 = 30.0;
+
+/// Default minimum duration (seconds) for an engagement segment
+pub const DEFAULT_MIN_ENGAGEMENT_DURATION_SEC: f64 = 10.0;
+
+/// Default minimum duration (seconds) for a deep focus block
+pub const DEFAULT_DEEP_FOCUS_MIN_DURATION_SEC: f64 = 120.0;
+
+/// Default minimum duration (seconds) of a scroll run to count as doomscrolling
+pub const DEFAULT_DOOMSCROLL_MIN_DURATION_SEC: f64 = 60.0;
+
+/// Default minimum mean velocity (pixels per second) of a doomscroll run
+pub const DEFAULT_DOOMSCROLL_MIN_VELOCITY: f64 = 200.0;
+
+/// What to do with events outside the session or in the future
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutOfBoundsPolicy {
+    /// Remove the events
+    Drop,
+    /// Move the events to the nearest session bound
+    #[default]
+    Clamp,
+    /// Reject the session
+    Fail,
+}
+
+/// Weights of the distraction composite.
+///
+/// The defaults match the SDK formula; gesture complexity is off unless given
+/// a weight. Weights are applied as given, so keep them summing to 1 to stay
+/// on the 0-1 scale.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistractionWeights {
+    pub task_switch_rate: f64,
+    pub notification_load: f64,
+    pub fragmented_idle_ratio: f64,
+    pub scroll_jitter_rate: f64,
+    pub gesture_complexity: f64,
+}
+
+impl Default for DistractionWeights {
+    fn default() -> Self {
+        Self {
+            task_switch_rate: 0.35,
+            notification_load: 0.30,
+            fragmented_idle_ratio: 0.20,
+            scroll_jitter_rate: 0.15,
+            gesture_complexity: 0.0,
+        }
+    }
+}
+
+impl DistractionWeights {
+    /// Set the weight of gesture complexity
+    pub fn with_gesture_complexity(mut self, weight: f64) -> Self {
+        self.gesture_complexity = weight;
+        self
+    }
+}
+
+/// Weights of the `effective_capacity` fusion of focus with bio context.
+///
+/// Readiness is the weighted mean of the recovery and sleep components the
+/// wearable day has. `physiology` sets how far readiness pulls focus down:
+/// capacity is `focus * (1 - physiology + physiology * readiness)`, so 0
+/// leaves focus unchanged and 1 scales it by readiness alone.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapacityWeights {
+    /// Weight of recovery within readiness
+    pub recovery: f64,
+    /// Weight of sleep within readiness
+    pub sleep: f64,
+    /// How strongly readiness scales focus (0-1)
+    pub physiology: f64,
+}
+
+impl Default for CapacityWeights {
+    fn default() -> Self {
+        Self {
+            recovery: 0.6,
+            sleep: 0.4,
+            physiology: 0.5,
+        }
+    }
+}
+
+/// Thresholds used by the behavioral pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BehaviorConfig {
+    /// Minimum gap between events (seconds) to count as idle
+    pub idle_gap_threshold_sec: f64,
+    /// Minimum duration (seconds) for an engagement segment
+    pub min_engagement_duration_sec: f64,
+    /// Minimum engagement segment duration (seconds) for a deep focus block
+    pub deep_focus_min_duration_sec: f64,
+    /// Minimum same-direction scroll run duration (seconds) for a doomscroll segment
+    pub doomscroll_min_duration_sec: f64,
+    /// Minimum mean scroll velocity (pixels per second) for a doomscroll segment
+    pub doomscroll_min_velocity: f64,
+    /// Weights of the distraction composite
+    pub distraction_weights: DistractionWeights,
+    /// Weights of the `effective_capacity` fusion with bio context
+    pub capacity_weights: CapacityWeights,
+    /// Handling of events outside the session or in the future
+    pub out_of_bounds: OutOfBoundsPolicy,
+}
+
+impl Default for BehaviorConfig {
+    fn default() -> Self {
+        Self {
+            idle_gap_threshold_sec: DEFAULT_IDLE_GAP_THRESHOLD_SEC,
+            min_engagement_duration_sec: DEFAULT_MIN_ENGAGEMENT_DURATION_SEC,
+            deep_focus_min_duration_sec: DEFAULT_DEEP_FOCUS_MIN_DURATION_SEC,
+            doomscroll_min_duration_sec: DEFAULT_DOOMSCROLL_MIN_DURATION_SEC,
+            doomscroll_min_velocity: DEFAULT_DOOMSCROLL_MIN_VELOCITY,
+            distraction_weights: DistractionWeights::default(),
+            capacity_weights: CapacityWeights::default(),
+            out_of_bounds: OutOfBoundsPolicy::default(),
+        }
+    }
+}
+
+impl BehaviorConfig {
+    /// Set the idle gap threshold in seconds
+    pub fn with_idle_gap_threshold_sec(mut self, seconds: f64) -> Self {
+        self.idle_gap_threshold_sec = seconds;
+        self
+    }
+
+    /// Set the minimum engagement segment duration in seconds
+    pub fn with_min_engagement_duration_sec(mut self, seconds: f64) -> Self {
+        self.min_engagement_duration_sec = seconds;
+        self
+    }
+
+    /// Set the minimum deep focus block duration in seconds
+    pub fn with_deep_focus_min_duration_sec(mut self, seconds: f64) -> Self {
+        self.deep_focus_min_duration_sec = seconds;
+        self
+    }
+
+    /// Set the minimum doomscroll segment duration in seconds
+    pub fn with_doomscroll_min_duration_sec(mut self, seconds: f64) -> Self {
+        self.doomscroll_min_duration_sec = seconds;
+        self
+    }
+
+    /// Set the minimum doomscroll velocity in pixels per second
+    pub fn with_doomscroll_min_velocity(mut self, velocity: f64) -> Self {
+        self.doomscroll_min_velocity = velocity;
+        self
+    }
+
+    /// Set the weights of the distraction composite
+    pub fn with_distraction_weights(mut self, weights: DistractionWeights) -> Self {
+        self.distraction_weights = weights;
+        self
+    }
+
+    /// Set the weights of the `effective_capacity` fusion with bio context
+    pub fn with_capacity_weights(mut self, weights: CapacityWeights) -> Self {
+        self.capacity_weights = weights;
+        self
+    }
+
+    /// Set the handling of events outside the session or in the future
+    pub fn with_out_of_bounds(mut self, policy: OutOfBoundsPolicy) -> Self {
+        self.out_of_bounds = policy;
+        self
+    }
+
+    /// Check that every threshold is a positive, finite number and every
+    /// weight a non-negative one
+    pub fn validate(&self) -> Result<(), ComputeError> {
+        for (name, value) in [
+            ("idle_gap_threshold_sec", self.idle_gap_threshold_sec),
+            (
+                "min_engagement_duration_sec",
+                self.min_engagement_duration_sec,
+            ),
+            (
+                "deep_focus_min_duration_sec",
+                self.deep_focus_min_duration_sec,
+            ),
+            (
+                "doomscroll_min_duration_sec",
+                self.doomscroll_min_duration_sec,
+            ),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(ComputeError::InvalidConfig(format!(
+                    "{name} must be a positive number of seconds, got {value}"
+                )));
+            }
+        }
+        if !self.doomscroll_min_velocity.is_finite() || self.doomscroll_min_velocity <= 0.0 {
+            return Err(ComputeError::InvalidConfig(format!(
+                "doomscroll_min_velocity must be a positive number of pixels per second, got {}",
+                self.doomscroll_min_velocity
+            )));
+        }
+        let weights = &self.distraction_weights;
+        for (name, value) in [
+            ("task_switch_rate", weights.task_switch_rate),
+            ("notification_load", weights.notification_load),
+            ("fragmented_idle_ratio", weights.fragmented_idle_ratio),
+            ("scroll_jitter_rate", weights.scroll_jitter_rate),
+            ("gesture_complexity", weights.gesture_complexity),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(ComputeError::InvalidConfig(format!(
+                    "distraction weight {name} must be a non-negative number, got {value}"
+                )));
+            }
+        }
+        let capacity = &self.capacity_weights;
+        for (name, value) in [("recovery", capacity.recovery), ("sleep", capacity.sleep)] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(ComputeError::InvalidConfig(format!(
+                    "capacity weight {name} must be a non-negative number, got {value}"
+                )));
+            }
+        }
+        if !(0.0..=1.0).contains(&capacity.physiology) {
+            return Err(ComputeError::InvalidConfig(format!(
+                "capacity weight physiology must be between 0 and 1, got {}",
+                capacity.physiology
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(BehaviorConfig::default().validate().is_ok());
+        assert!(BehaviorConfig::default()
+            .with_idle_gap_threshold_sec(0.0)
+            .validate()
+            .is_err());
+        assert!(BehaviorConfig::default()
+            .with_deep_focus_min_duration_sec(f64::NAN)
+            .validate()
+            .is_err());
+        assert!(BehaviorConfig::default()
+            .with_distraction_weights(DistractionWeights::default().with_gesture_complexity(-0.1))
+            .validate()
+            .is_err());
+        assert!(BehaviorConfig::default()
+            .with_capacity_weights(CapacityWeights {
+                physiology: 1.5,
+                ..CapacityWeights::default()
+            })
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_partial_json_uses_defaults() {
+        let config: BehaviorConfig =
+            serde_json::from_str(r#"{"deep_focus_min_duration_sec": 300}"#).unwrap();
+        assert_eq!(config.deep_focus_min_duration_sec, 300.0);
+        assert_eq!(
+            config.idle_gap_threshold_sec,
+            DEFAULT_IDLE_GAP_THRESHOLD_SEC
+        );
+    }
+}
@@ -2,12 +2,18 @@
 //!
 //! Encodes contextual behavioral signals into HSI 1.0 compliant JSON payloads.
 
+use crate::baseline::BaselineStore;
+use crate::behavior::bio_context::{BioContext, SnapshotOptions, BIO_CONTEXT_SOURCE_ID};
+use crate::behavior::config::BehaviorConfig;
 use crate::behavior::types::{
     ContextualBehaviorSignals, HsiAxes, HsiAxesDomain, HsiAxisReading, HsiDirection, HsiPayload,
-    HsiPrivacy, HsiProducer, HsiSource, HsiSourceType, HsiWindow,
+    HsiProducer, HsiSource, HsiSourceType, HsiWindow,
 };
+use crate::encoder::EncoderConfig;
+use crate::encoder::{ProducerIdentity, FLUX_VERSION_META_KEY};
 use crate::error::ComputeError;
-use crate::{FLUX_VERSION, PRODUCER_NAME};
+use crate::privacy::{PrivacyPolicy, ScrubMode, Scrubber};
+use crate::FLUX_VERSION;
 use chrono::Utc;
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -15,9 +21,31 @@ use uuid::Uuid;
 /// HSI schema version
 pub const HSI_VERSION: &str = "1.0";
 
+/// Unlocks per hour at which the unlock frequency score reaches ~0.63
+const UNLOCKS_PER_HOUR_SCALE: f64 = 12.0;
+
+/// Checks per hour at which the check frequency score reaches ~0.63
+const CHECKS_PER_HOUR_SCALE: f64 = 12.0;
+
+/// Apps listed in the `top_apps` meta summary
+pub const TOP_APPS: usize = 5;
+
+/// Meta entries dropped in compact mode
+const VERBOSE_META_KEYS: [&str; 2] = ["typing_metrics", "deep_focus_blocks_detail"];
+
 /// HSI 1.0 behavioral encoder
 pub struct HsiBehaviorEncoder {
     instance_id: String,
+    producer: ProducerIdentity,
+    config: BehaviorConfig,
+    output: EncoderConfig,
+    privacy: PrivacyPolicy,
+    bio_baselines: Option<BaselineStore>,
+    snapshot: SnapshotOptions,
+    #[cfg(feature = "experimental-affect")]
+    physiology: Option<crate::behavior::affect::PhysiologyContext>,
+    #[cfg(feature = "integrity")]
+    integrity: Option<crate::integrity::IntegrityConfig>,
 }
 
 impl Default for HsiBehaviorEncoder {
@@ -29,14 +57,79 @@ impl Default for HsiBehaviorEncoder {
 impl HsiBehaviorEncoder {
     /// Create a new encoder with a unique instance ID
     pub fn new() -> Self {
-        Self {
-            instance_id: Uuid::new_v4().to_string(),
-        }
+        Self::with_instance_id(Uuid::new_v4().to_string())
     }
 
     /// Create an encoder with a specific instance ID
     pub fn with_instance_id(instance_id: String) -> Self {
-        Self { instance_id }
+        Self {
+            instance_id,
+            producer: ProducerIdentity::default(),
+            config: BehaviorConfig::default(),
+            output: EncoderConfig::default(),
+            privacy: PrivacyPolicy::default(),
+            bio_baselines: None,
+            snapshot: SnapshotOptions::default(),
+            #[cfg(feature = "experimental-affect")]
+            physiology: None,
+            #[cfg(feature = "integrity")]
+            integrity: None,
+        }
+    }
+
+    /// Stamp payloads with a host's producer name, version and instance ID
+    pub fn set_producer(&mut self, producer: ProducerIdentity) {
+        self.producer = producer;
+    }
+
+    /// The producer identity stamped into payloads
+    pub fn producer_identity(&self) -> &ProducerIdentity {
+        &self.producer
+    }
+
+    /// Set the thresholds used when describing deep focus blocks
+    pub fn set_config(&mut self, config: BehaviorConfig) {
+        self.config = config;
+    }
+
+    /// Filter emitted axes and metadata
+    pub fn set_encoder_config(&mut self, config: EncoderConfig) {
+        self.output = config;
+    }
+
+    /// Set the privacy declaration and metadata enforcement policy
+    pub fn set_privacy_policy(&mut self, policy: PrivacyPolicy) {
+        self.privacy = policy;
+    }
+
+    /// The privacy policy applied to emitted payloads
+    pub fn privacy_policy(&self) -> &PrivacyPolicy {
+        &self.privacy
+    }
+
+    /// Seal serialized payloads with an integrity hash (and signature)
+    #[cfg(feature = "integrity")]
+    pub fn set_integrity(&mut self, integrity: Option<crate::integrity::IntegrityConfig>) {
+        self.integrity = integrity;
+    }
+
+    /// Attach bio context from wearable baselines to session payloads, or stop with `None`
+    pub fn set_bio_context(&mut self, baselines: Option<BaselineStore>) {
+        self.bio_baselines = baselines;
+    }
+
+    /// Set how quickly bio context readings lose confidence
+    pub fn set_snapshot_options(&mut self, options: SnapshotOptions) {
+        self.snapshot = options;
+    }
+
+    /// Set the wearable physiology context used for affect estimation
+    #[cfg(feature = "experimental-affect")]
+    pub fn set_physiology_context(
+        &mut self,
+        physiology: Option<crate::behavior::affect::PhysiologyContext>,
+    ) {
+        self.physiology = physiology;
     }
 
     /// Encode contextual behavioral signals into an HSI 1.0 compliant payload
@@ -50,9 +143,14 @@ impl HsiBehaviorEncoder {
 
         // Build producer
         let producer = HsiProducer {
-            name: PRODUCER_NAME.to_string(),
-            version: FLUX_VERSION.to_string(),
-            instance_id: Some(self.instance_id.clone()),
+            name: self.producer.name.clone(),
+            version: self.producer.version.clone(),
+            instance_id: Some(
+                self.producer
+                    .instance_id
+                    .clone()
+                    .unwrap_or_else(|| self.instance_id.clone()),
+            ),
         };
 
         // Build window
@@ -66,6 +164,12 @@ impl HsiBehaviorEncoder {
             },
         );
 
+        // Wearable day behind the bio context readings, if recent enough
+        let bio = self
+            .bio_baselines
+            .as_ref()
+            .and_then(|store| BioContext::for_session(store, canonical));
+
         // Build source
         let source_id = format!("s_{}", canonical.device_id.replace('-', "_"));
         let mut sources = HashMap::new();
@@ -75,7 +179,9 @@ impl HsiBehaviorEncoder {
                 source_type: HsiSourceType::App,
                 quality: signals.derived.normalized.coverage,
                 degraded: !signals.derived.normalized.quality_flags.is_empty(),
-                notes: if !signals.derived.normalized.quality_flags.is_empty() {
+                notes: if !self.output.compact
+                    && !signals.derived.normalized.quality_flags.is_empty()
+                {
                     Some(format!(
                         "Quality flags: {:?}",
                         signals.derived.normalized.quality_flags
@@ -96,7 +202,7 @@ impl HsiBehaviorEncoder {
         let confidence = (base_confidence + baseline_bonus).min(1.0);
 
         // Build behavioral axis readings
-        let behavior_readings = vec![
+        let mut behavior_readings = vec![
             // Distraction score
             HsiAxisReading {
                 axis: "distraction".to_string(),
@@ -218,27 +324,234 @@ impl HsiBehaviorEncoder {
                 evidence_source_ids: Some(vec![source_id.clone()]),
                 notes: Some("1 - idle_time_ratio - task_switch_cost contribution".to_string()),
             },
+            // Phone check frequency
+            HsiAxisReading {
+                axis: "check_frequency_per_hour".to_string(),
+                score: Some(1.0 - (-derived.check_frequency_per_hour / CHECKS_PER_HOUR_SCALE).exp()),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("normalized".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some(
+                    "Exponential saturation of check clusters (unlock, app switch, opened notification) per hour"
+                        .to_string(),
+                ),
+            },
+            // Compulsive checking
+            HsiAxisReading {
+                axis: "compulsive_check_score".to_string(),
+                score: Some(derived.compulsive_check_score.clamp(0.0, 1.0)),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("normalized".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some("Exponential saturation of check bursts (3+ checks) per hour".to_string()),
+            },
+            // Late-night usage across sessions (falls back to this session without a baseline)
+            HsiAxisReading {
+                axis: "late_night_usage_ratio".to_string(),
+                score: Some(
+                    signals
+                        .baselines
+                        .late_night_baseline
+                        .unwrap_or(derived.late_night_usage_ratio)
+                        .clamp(0.0, 1.0),
+                ),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsLess),
+                unit: Some("ratio".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some(
+                    "Fraction of interaction events between 23:00 and 05:00 local time across baseline sessions"
+                        .to_string(),
+                ),
+            },
+            // Sustained fast same-direction scrolling
+            HsiAxisReading {
+                axis: "doomscroll_score".to_string(),
+                score: Some(derived.doomscroll_score.clamp(0.0, 1.0)),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("ratio".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some(format!(
+                    "Fraction of session in same-direction scroll runs (>= {}s at >= {} px/s)",
+                    self.config.doomscroll_min_duration_sec, self.config.doomscroll_min_velocity
+                )),
+            },
         ];
 
+        // App diversity only when the producer reports app switches with app IDs
+        if let Some(entropy) = derived.app_diversity_entropy {
+            behavior_readings.push(HsiAxisReading {
+                axis: "app_diversity_entropy".to_string(),
+                score: Some(entropy),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("normalized".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some(
+                    "Shannon entropy of foreground time per app / ln(app count)".to_string(),
+                ),
+            });
+        }
+
+        // Gesture mix only when the session has taps, swipes or scrolls
+        if let Some(complexity) = derived.gesture_complexity {
+            behavior_readings.push(HsiAxisReading {
+                axis: "gesture_complexity".to_string(),
+                score: Some(complexity),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("normalized".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some(
+                    "Shannon entropy of taps, long presses, swipes and scrolls / ln(4)".to_string(),
+                ),
+            });
+        }
+
+        // Distraction trend once the baseline holds enough sessions
+        if let Some(trend) = signals.distraction_trend {
+            behavior_readings.push(HsiAxisReading {
+                axis: "distraction_trend".to_string(),
+                score: Some((1.0 + trend) / 2.0),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::Bidirectional),
+                unit: Some("trend".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some(
+                    "Least-squares distraction slope across baseline sessions; 0.5 = flat, above = rising"
+                        .to_string(),
+                ),
+            });
+        }
+
+        // Screen state readings only when the producer reports screen events
+        if canonical.screen.has_events() {
+            behavior_readings.push(HsiAxisReading {
+                axis: "unlock_frequency".to_string(),
+                score: Some(1.0 - (-derived.unlock_frequency / UNLOCKS_PER_HOUR_SCALE).exp()),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("normalized".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some("Exponential saturation of unlocks per hour".to_string()),
+            });
+            behavior_readings.push(HsiAxisReading {
+                axis: "checking_habit".to_string(),
+                score: Some(derived.checking_habit_score.clamp(0.0, 1.0)),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("normalized".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some("Exponential saturation of screen checks (< 30s) per hour".to_string()),
+            });
+        }
+
         // Build axes
+        // Engagement readings
+        let engagement_readings = vec![
+            HsiAxisReading {
+                axis: "sustained_engagement".to_string(),
+                score: Some(derived.deep_focus_coverage.clamp(0.0, 1.0)),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("ratio".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some(format!(
+                    "Fraction of session in deep focus blocks (>= {}s)",
+                    self.config.deep_focus_min_duration_sec
+                )),
+            },
+            HsiAxisReading {
+                axis: "engagement_stability".to_string(),
+                score: derived.engagement_stability.map(|s| s.clamp(0.0, 1.0)),
+                confidence,
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: Some("normalized".to_string()),
+                evidence_source_ids: Some(vec![source_id.clone()]),
+                notes: Some(
+                    "1 / (1 + coefficient of variation of engagement segment lengths)".to_string(),
+                ),
+            },
+        ];
+
+        #[cfg(feature = "experimental-affect")]
+        let physiology = self.physiology.or_else(|| bio.as_ref().map(Into::into));
+        #[cfg(feature = "experimental-affect")]
+        let affect = Some(HsiAxesDomain {
+            readings: crate::behavior::affect::estimate_affect(
+                signals,
+                physiology.as_ref(),
+                confidence,
+                &window_id,
+                &source_id,
+            ),
+        });
+        #[cfg(not(feature = "experimental-affect"))]
+        let affect = None;
+
+        let (physiology_readings, sleep_readings) = match &bio {
+            Some(bio) => {
+                sources.insert(
+                    BIO_CONTEXT_SOURCE_ID.to_string(),
+                    HsiSource {
+                        source_type: HsiSourceType::Sensor,
+                        quality: bio.confidence(self.snapshot.recovery_half_life_days),
+                        degraded: bio.age_days > 0,
+                        notes: (!self.output.compact)
+                            .then(|| format!("Wearable baselines as of {}", bio.date)),
+                    },
+                );
+                bio.readings(&window_id, &self.snapshot)
+            }
+            None => Default::default(),
+        };
+
+        // Focus fused with the wearable day's recovery and sleep
+        let weights = &self.config.capacity_weights;
+        let capacity = bio
+            .as_ref()
+            .and_then(|bio| Some((bio, bio.effective_capacity(derived.focus_hint, weights)?)));
+        if let Some((bio, score)) = capacity {
+            behavior_readings.push(HsiAxisReading {
+                axis: "effective_capacity".to_string(),
+                score: Some(score),
+                confidence: confidence * bio.confidence(self.snapshot.recovery_half_life_days),
+                window_id: window_id.clone(),
+                direction: Some(HsiDirection::HigherIsMore),
+                unit: None,
+                evidence_source_ids: Some(vec![
+                    source_id.clone(),
+                    BIO_CONTEXT_SOURCE_ID.to_string(),
+                ]),
+                notes: Some("Focus scaled by wearable recovery and sleep".to_string()),
+            });
+        }
+
         let axes = HsiAxes {
-            affect: None,
-            engagement: None,
-            behavior: Some(HsiAxesDomain {
-                readings: behavior_readings,
-            }),
+            affect: affect.and_then(|d: HsiAxesDomain| self.filter_domain(d.readings)),
+            engagement: self.filter_domain(engagement_readings),
+            behavior: self.filter_domain(behavior_readings),
+            sleep: self.filter_domain(sleep_readings),
+            physiology: self.filter_domain(physiology_readings),
+            ..Default::default()
         };
 
         // Build privacy
-        let privacy = HsiPrivacy {
-            contains_pii: false,
-            raw_biosignals_allowed: false,
-            derived_metrics_allowed: true,
-            embedding_allowed: None,
-            consent: None,
-            purposes: Some(vec!["behavioral_research".to_string()]),
-            notes: None,
-        };
+        let privacy = self.privacy.to_hsi_privacy();
 
         // Build metadata with baseline and event summary info
         let mut meta = HashMap::new();
@@ -264,7 +577,7 @@ impl HsiBehaviorEncoder {
         let deep_focus_detail: Vec<serde_json::Value> = canonical
             .engagement_segments
             .iter()
-            .filter(|s| s.duration_sec >= 120.0)
+            .filter(|s| s.duration_sec >= self.config.deep_focus_min_duration_sec)
             .map(|s| {
                 serde_json::json!({
                     "start_at": s.start.to_rfc3339(),
@@ -278,6 +591,107 @@ impl HsiBehaviorEncoder {
             serde_json::Value::Array(deep_focus_detail),
         );
 
+        meta.insert(
+            "late_night_usage_ratio_session".to_string(),
+            serde_json::Value::from(derived.late_night_usage_ratio),
+        );
+
+        // Check burst summary
+        meta.insert(
+            "check_frequency_per_hour".to_string(),
+            serde_json::Value::from(derived.check_frequency_per_hour),
+        );
+        meta.insert(
+            "check_bursts".to_string(),
+            serde_json::Value::from(derived.check_bursts),
+        );
+
+        // Gesture summary
+        if let Some(rate) = derived.long_press_rate {
+            meta.insert("long_press_rate".to_string(), serde_json::Value::from(rate));
+        }
+        if let Some(ratio) = derived.swipe_tap_ratio {
+            meta.insert(
+                "swipe_tap_ratio".to_string(),
+                serde_json::Value::from(ratio),
+            );
+        }
+
+        // Scroll summary
+        if let Some(median) = derived.scroll_velocity_median {
+            meta.insert(
+                "scroll_velocity_median".to_string(),
+                serde_json::Value::from(median),
+            );
+        }
+        if let Some(p90) = derived.scroll_velocity_p90 {
+            meta.insert(
+                "scroll_velocity_p90".to_string(),
+                serde_json::Value::from(p90),
+            );
+        }
+        if let Some(ratio) = derived.doomscroll_segment_ratio {
+            meta.insert(
+                "doomscroll_segments".to_string(),
+                serde_json::Value::from(derived.doomscroll_segments),
+            );
+            meta.insert(
+                "doomscroll_segment_ratio".to_string(),
+                serde_json::Value::from(ratio),
+            );
+        }
+
+        // Screen state summary
+        if canonical.screen.has_events() {
+            meta.insert(
+                "unlock_count".to_string(),
+                serde_json::Value::from(canonical.screen.unlock_events),
+            );
+            meta.insert(
+                "unlocks_per_hour".to_string(),
+                serde_json::Value::from(derived.unlock_frequency),
+            );
+            meta.insert(
+                "screen_session_count".to_string(),
+                serde_json::Value::from(canonical.screen.screen_sessions_sec.len()),
+            );
+            meta.insert(
+                "average_screen_session_sec".to_string(),
+                serde_json::Value::from(derived.avg_screen_session_sec),
+            );
+        }
+
+        // App usage summary; app identifiers are always pseudonymized, and
+        // the per-app list is left out when the scrubber drops identifiers
+        let scrubber = self.privacy.scrub.clone().unwrap_or_default();
+        if !canonical.app_usage.is_empty() {
+            meta.insert(
+                "app_count".to_string(),
+                serde_json::Value::from(canonical.app_usage.len()),
+            );
+        }
+        if !canonical.app_usage.is_empty() && scrubber.mode != ScrubMode::Drop {
+            let total: f64 = canonical.app_usage.iter().map(|a| a.duration_sec).sum();
+            let top_apps: Vec<serde_json::Value> = canonical
+                .app_usage
+                .iter()
+                .take(TOP_APPS)
+                .map(|a| {
+                    let app = if Scrubber::is_pseudonym(&a.app_id) {
+                        a.app_id.clone()
+                    } else {
+                        scrubber.pseudonym(&a.app_id)
+                    };
+                    serde_json::json!({
+                        "app": app,
+                        "duration_sec": a.duration_sec,
+                        "share": a.duration_sec / total,
+                    })
+                })
+                .collect();
+            meta.insert("top_apps".to_string(), serde_json::Value::Array(top_apps));
+        }
+
         // Typing session summary (SDK-compatible keys)
         let typing_sessions = &canonical.typing_sessions;
         if typing_sessions.is_empty() {
@@ -466,6 +880,34 @@ impl HsiBehaviorEncoder {
                 signals.baselines.sessions_in_baseline,
             )),
         );
+        if capacity.is_some() {
+            meta.insert(
+                "effective_capacity_weights".to_string(),
+                serde_json::json!({
+                    "recovery": weights.recovery,
+                    "sleep": weights.sleep,
+                    "physiology": weights.physiology,
+                }),
+            );
+        }
+
+        self.privacy.enforce(&mut meta);
+        if self.output.compact {
+            for key in VERBOSE_META_KEYS {
+                meta.remove(key);
+            }
+        }
+        let mut meta: HashMap<String, serde_json::Value> = if self.output.suppress_meta {
+            HashMap::new()
+        } else {
+            meta.into_iter()
+                .map(|(key, value)| (self.output.profile.meta_key(&key).to_string(), value))
+                .collect()
+        };
+        // Kept even with meta suppressed, so output stays traceable to the engine
+        if self.producer.is_overridden() {
+            meta.insert(FLUX_VERSION_META_KEY.to_string(), FLUX_VERSION.into());
+        }
 
         Ok(HsiPayload {
             hsi_version: HSI_VERSION.to_string(),
@@ -474,21 +916,66 @@ impl HsiBehaviorEncoder {
             producer,
             window_ids: vec![window_id],
             windows,
-            source_ids: Some(vec![source_id]),
+            source_ids: Some(
+                std::iter::once(source_id)
+                    .chain(bio.map(|_| BIO_CONTEXT_SOURCE_ID.to_string()))
+                    .collect(),
+            ),
             sources: Some(sources),
             axes: Some(axes),
             privacy,
-            meta: Some(meta),
+            meta: (!self.output.suppress_meta || !meta.is_empty()).then_some(meta),
         })
     }
 
+    /// Apply the axis allow/deny lists, naming profile and compact mode; a domain left without readings is omitted
+    fn filter_domain(&self, mut readings: Vec<HsiAxisReading>) -> Option<HsiAxesDomain> {
+        readings.retain(|r| self.output.allows(&r.axis));
+        for reading in &mut readings {
+            reading.axis = self.output.profile.axis_name(&reading.axis).to_string();
+            if self.output.compact {
+                reading.notes = None;
+                reading.evidence_source_ids = None;
+            }
+        }
+        (!readings.is_empty()).then_some(HsiAxesDomain { readings })
+    }
+
     /// Encode to JSON string
     pub fn encode_to_json(
         &self,
         signals: &ContextualBehaviorSignals,
     ) -> Result<String, ComputeError> {
         let payload = self.encode(signals)?;
-        serde_json::to_string_pretty(&payload).map_err(ComputeError::JsonError)
+        self.payload_to_json(&payload)
+    }
+
+    /// Embed the integrity block into a typed payload, as serialization would.
+    ///
+    /// Does nothing unless an integrity config is set.
+    #[cfg_attr(not(feature = "integrity"), allow(unused_variables))]
+    pub fn seal(&self, payload: &mut HsiPayload) -> Result<(), ComputeError> {
+        #[cfg(feature = "integrity")]
+        if let Some(integrity) = &self.integrity {
+            let block = integrity.compute(&serde_json::to_value(&*payload)?)?;
+            payload.meta.get_or_insert_with(Default::default).insert(
+                crate::integrity::INTEGRITY_META_KEY.to_string(),
+                serde_json::to_value(block)?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Serialize an already-encoded payload
+    pub fn payload_to_json(&self, payload: &HsiPayload) -> Result<String, ComputeError> {
+        #[cfg(feature = "integrity")]
+        if let Some(integrity) = &self.integrity {
+            let mut value = serde_json::to_value(payload)?;
+            integrity.seal(&mut value)?;
+            return self.output.to_json(&value);
+        }
+
+        self.output.to_json(payload)
     }
 }
 
@@ -496,9 +983,10 @@ impl HsiBehaviorEncoder {
 mod tests {
     use super::*;
     use crate::behavior::types::{
-        BehaviorBaselines, BehaviorQualityFlag, CanonicalBehaviorSignals, DerivedBehaviorSignals,
-        NormalizedBehaviorSignals,
+        AppUsage, BehaviorBaselines, BehaviorQualityFlag, CanonicalBehaviorSignals,
+        DerivedBehaviorSignals, NormalizedBehaviorSignals,
     };
+    use crate::PRODUCER_NAME;
     use chrono::{TimeZone, Utc};
 
     fn make_test_contextual() -> ContextualBehaviorSignals {
@@ -518,12 +1006,22 @@ mod tests {
             typing_events: 4,
             app_switch_events: 8,
             scroll_direction_reversals: 15,
+            long_press_events: 0,
+            out_of_bounds_events: 0,
+            overlapping_typing_sessions: 0,
+            invalid_timezone: false,
             total_typing_duration_sec: 90.0,
             typing_sessions: vec![],
             idle_segments: vec![],
             total_idle_time_sec: 60.0,
             engagement_segments: vec![],
             inter_event_gaps: vec![5.0, 8.0, 12.0, 6.0],
+            screen: Default::default(),
+            late_night_events: 0,
+            check_event_times: vec![],
+            app_usage: vec![],
+            scroll_velocities: vec![],
+            scroll_segments: vec![],
             computed_at: Utc::now(),
         };
 
@@ -535,6 +1033,7 @@ mod tests {
             swipes_per_min: 0.5,
             notifications_per_min: 0.4,
             app_switches_per_min: 0.27,
+            unlocks_per_min: 0.0,
             coverage: 0.95,
             quality_flags: vec![],
         };
@@ -551,6 +1050,24 @@ mod tests {
             burstiness: 0.55,
             deep_focus_blocks: 2,
             interaction_intensity: 0.78,
+            unlock_frequency: 0.0,
+            avg_screen_session_sec: 0.0,
+            checking_habit_score: 0.0,
+            deep_focus_coverage: 0.0,
+            engagement_stability: None,
+            late_night_usage_ratio: 0.0,
+            app_diversity_entropy: None,
+            long_press_rate: None,
+            swipe_tap_ratio: None,
+            gesture_complexity: None,
+            scroll_velocity_median: None,
+            scroll_velocity_p90: None,
+            doomscroll_segments: 0,
+            doomscroll_segment_ratio: None,
+            doomscroll_score: 0.0,
+            check_frequency_per_hour: 0.0,
+            check_bursts: 0,
+            compulsive_check_score: 0.0,
             distraction_score: 0.35,
             focus_hint: 0.65,
         };
@@ -560,6 +1077,7 @@ mod tests {
             focus_baseline: Some(0.62),
             burstiness_baseline: Some(0.50),
             intensity_baseline: Some(0.70),
+            late_night_baseline: Some(0.10),
             sessions_in_baseline: 15,
         };
 
@@ -568,9 +1086,30 @@ mod tests {
             baselines,
             distraction_deviation_pct: Some(-7.9),
             focus_deviation_pct: Some(4.8),
+            distraction_trend: None,
         }
     }
 
+    #[test]
+    fn test_producer_override_keeps_flux_version() {
+        let signals = make_test_contextual();
+        let mut encoder = HsiBehaviorEncoder::with_instance_id("test-instance".to_string());
+        encoder.set_producer(ProducerIdentity::new("acme-health", "3.2.0"));
+        encoder.set_encoder_config(EncoderConfig::default().with_meta_suppressed(true));
+        let payload = encoder.encode(&signals).unwrap();
+
+        assert_eq!(payload.producer.name, "acme-health");
+        assert_eq!(payload.producer.version, "3.2.0");
+        assert_eq!(
+            payload.producer.instance_id,
+            Some("test-instance".to_string())
+        );
+        // Suppressed meta still records the engine version
+        let meta = payload.meta.unwrap();
+        assert_eq!(meta.len(), 1);
+        assert_eq!(meta[FLUX_VERSION_META_KEY], FLUX_VERSION);
+    }
+
     #[test]
     fn test_encode_hsi_compliant_payload() {
         let signals = make_test_contextual();
@@ -687,6 +1226,39 @@ mod tests {
         assert!(meta.contains_key("sessions_in_baseline"));
     }
 
+    #[test]
+    fn test_app_usage_summary_is_pseudonymized() {
+        let mut signals = make_test_contextual();
+        signals.derived.normalized.canonical.app_usage = vec![
+            AppUsage {
+                app_id: "com.example.chat".to_string(),
+                duration_sec: 900.0,
+            },
+            AppUsage {
+                app_id: "com.example.mail".to_string(),
+                duration_sec: 300.0,
+            },
+        ];
+        signals.derived.app_diversity_entropy = Some(0.81);
+
+        let payload = HsiBehaviorEncoder::new().encode(&signals).unwrap();
+        let meta = payload.meta.unwrap();
+        assert_eq!(meta["app_count"], 2);
+        let top = &meta["top_apps"][0];
+        assert!(Scrubber::is_pseudonym(top["app"].as_str().unwrap()));
+        assert_eq!(top["duration_sec"], 900.0);
+        assert_eq!(top["share"], 0.75);
+        assert!(!serde_json::to_string(&meta)
+            .unwrap()
+            .contains("com.example"));
+
+        let behavior = payload.axes.unwrap().behavior.unwrap();
+        assert!(behavior
+            .readings
+            .iter()
+            .any(|r| r.axis == "app_diversity_entropy"));
+    }
+
     #[test]
     fn test_quality_flags_in_source() {
         let mut signals = make_test_contextual();
@@ -3,17 +3,40 @@
 //! Computes derived behavioral metrics from normalized signals using the formulas
 //! from synheart-behavior-dart.
 
-use crate::behavior::types::{DerivedBehaviorSignals, NormalizedBehaviorSignals};
+use crate::behavior::config::{BehaviorConfig, DistractionWeights};
+use crate::behavior::types::{
+    AppUsage, DerivedBehaviorSignals, NormalizedBehaviorSignals, ScrollSegment,
+};
 
-/// Minimum duration for deep focus block (120 seconds = 2 minutes)
-const DEEP_FOCUS_MIN_DURATION_SEC: f64 = 120.0;
+/// Screen-on periods shorter than this count as a "check" (30 seconds)
+const CHECK_MAX_DURATION_SEC: f64 = 30.0;
+
+/// Checks per hour at which the checking-habit score reaches ~0.63
+const CHECKS_PER_HOUR_SCALE: f64 = 6.0;
+
+/// Maximum gap between check events in the same cluster (60 seconds)
+const CHECK_CLUSTER_GAP_SEC: f64 = 60.0;
+
+/// Minimum check events in a cluster for it to count as a burst
+const MIN_CHECK_BURST_EVENTS: usize = 3;
+
+/// Bursts per hour at which the compulsive check score reaches ~0.63
+const BURSTS_PER_HOUR_SCALE: f64 = 2.0;
 
 /// Feature deriver for behavioral signals
 pub struct BehaviorFeatureDeriver;
 
 impl BehaviorFeatureDeriver {
-    /// Derive behavioral features from normalized signals
+    /// Derive behavioral features from normalized signals using default thresholds
     pub fn derive(normalized: NormalizedBehaviorSignals) -> DerivedBehaviorSignals {
+        Self::derive_with_config(normalized, &BehaviorConfig::default())
+    }
+
+    /// Derive behavioral features from normalized signals using the given thresholds
+    pub fn derive_with_config(
+        normalized: NormalizedBehaviorSignals,
+        config: &BehaviorConfig,
+    ) -> DerivedBehaviorSignals {
         let canonical = &normalized.canonical;
 
         // Core metrics
@@ -29,7 +52,10 @@ impl BehaviorFeatureDeriver {
             canonical.scroll_events,
         );
         let burstiness = compute_burstiness(&canonical.inter_event_gaps);
-        let deep_focus_blocks = count_deep_focus_blocks(&canonical.engagement_segments);
+        let deep_focus_blocks = count_deep_focus_blocks(
+            &canonical.engagement_segments,
+            config.deep_focus_min_duration_sec,
+        );
         let task_switch_cost_ms =
             compute_task_switch_cost_ms(canonical.duration_sec, canonical.app_switch_events);
         let task_switch_cost_norm = (task_switch_cost_ms as f64 / 10_000.0).clamp(0.0, 1.0);
@@ -46,12 +72,94 @@ impl BehaviorFeatureDeriver {
             canonical.duration_sec,
         );
 
+        // Screen state metrics
+        let unlock_frequency = normalized.unlocks_per_min * 60.0;
+        let avg_screen_session_sec =
+            compute_avg_screen_session(&canonical.screen.screen_sessions_sec);
+        let checking_habit_score = compute_checking_habit_score(
+            &canonical.screen.screen_sessions_sec,
+            canonical.duration_sec,
+        );
+
+        // Engagement metrics
+        let deep_focus_coverage = compute_deep_focus_coverage(
+            &canonical.engagement_segments,
+            canonical.duration_sec,
+            config.deep_focus_min_duration_sec,
+        );
+        let engagement_stability = compute_engagement_stability(&canonical.engagement_segments);
+
+        let late_night_usage_ratio = if canonical.total_events > 0 {
+            canonical.late_night_events as f64 / canonical.total_events as f64
+        } else {
+            0.0
+        };
+        let app_diversity_entropy = compute_app_diversity_entropy(&canonical.app_usage);
+
+        // Gesture metrics
+        let (long_press_rate, swipe_tap_ratio) = if canonical.tap_events > 0 {
+            let taps = canonical.tap_events as f64;
+            (
+                Some(canonical.long_press_events as f64 / taps),
+                Some(canonical.swipe_events as f64 / taps),
+            )
+        } else {
+            (None, None)
+        };
+        let gesture_complexity = normalized_entropy(&[
+            canonical
+                .tap_events
+                .saturating_sub(canonical.long_press_events) as f64,
+            canonical.long_press_events as f64,
+            canonical.swipe_events as f64,
+            canonical.scroll_events as f64,
+        ]);
+
+        // Scroll metrics
+        let mut velocities = canonical.scroll_velocities.clone();
+        velocities.sort_by(f64::total_cmp);
+        let scroll_velocity_median = percentile(&velocities, 0.5);
+        let scroll_velocity_p90 = percentile(&velocities, 0.9);
+        let doomscrolls: Vec<&ScrollSegment> = canonical
+            .scroll_segments
+            .iter()
+            .filter(|s| is_doomscroll(s, config))
+            .collect();
+        let doomscroll_segments = doomscrolls.len() as u32;
+        let doomscroll_segment_ratio = (!canonical.scroll_segments.is_empty())
+            .then(|| doomscrolls.len() as f64 / canonical.scroll_segments.len() as f64);
+        // Fold from +0.0: an empty f64 sum is -0.0
+        let doomscroll_sec = doomscrolls.iter().fold(0.0, |acc, s| acc + s.duration_sec);
+        let doomscroll_score = if canonical.duration_sec > 0.0 {
+            (doomscroll_sec / canonical.duration_sec).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        // Check burst metrics
+        let check_clusters = cluster_check_events(&canonical.check_event_times);
+        let hours = canonical.duration_sec / 3600.0;
+        let check_bursts = check_clusters
+            .iter()
+            .filter(|size| **size >= MIN_CHECK_BURST_EVENTS)
+            .count() as u32;
+        let (check_frequency_per_hour, compulsive_check_score) = if hours > 0.0 {
+            (
+                check_clusters.len() as f64 / hours,
+                1.0 - (-(check_bursts as f64 / hours) / BURSTS_PER_HOUR_SCALE).exp(),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
         // Composite scores
         let distraction_score = compute_distraction_score(
+            &config.distraction_weights,
             task_switch_rate,
             notification_load,
             fragmented_idle_ratio,
             scroll_jitter_rate,
+            gesture_complexity.unwrap_or(0.0),
         );
         let focus_hint = 1.0 - distraction_score;
 
@@ -67,6 +175,24 @@ impl BehaviorFeatureDeriver {
             burstiness,
             deep_focus_blocks,
             interaction_intensity,
+            unlock_frequency,
+            avg_screen_session_sec,
+            checking_habit_score,
+            deep_focus_coverage,
+            engagement_stability,
+            late_night_usage_ratio,
+            app_diversity_entropy,
+            long_press_rate,
+            swipe_tap_ratio,
+            gesture_complexity,
+            scroll_velocity_median,
+            scroll_velocity_p90,
+            doomscroll_segments,
+            doomscroll_segment_ratio,
+            doomscroll_score,
+            check_frequency_per_hour,
+            check_bursts,
+            compulsive_check_score,
             distraction_score,
             focus_hint,
         }
@@ -129,25 +255,33 @@ fn compute_scroll_jitter_rate(direction_reversals: u32, scroll_events: u32) -> f
 /// Formula: `((σ - μ) / (σ + μ) + 1) / 2`
 /// Where σ is standard deviation and μ is mean of inter-event gaps
 ///
-/// Result: 0.0 = perfectly regular (Poisson), 0.5 = random, 1.0 = very bursty
+/// Result: 0.0 = perfectly regular (Poisson), 0.5 = random, 1.0 = very bursty.
+/// Negative and non-finite gaps are ignored.
 fn compute_burstiness(inter_event_gaps: &[f64]) -> f64 {
-    if inter_event_gaps.is_empty() {
+    let gaps: Vec<f64> = inter_event_gaps
+        .iter()
+        .copied()
+        .filter(|g| g.is_finite() && *g >= 0.0)
+        .collect();
+    if gaps.is_empty() {
         return 0.5; // Default to neutral when no data
     }
 
-    let n = inter_event_gaps.len() as f64;
-    let mean: f64 = inter_event_gaps.iter().sum::<f64>() / n;
+    let n = gaps.len() as f64;
+    // Divide before summing so huge gaps cannot overflow to infinity
+    let mean: f64 = gaps.iter().map(|g| g / n).sum();
 
     if mean <= 0.0 {
         return 0.5;
     }
 
-    let variance: f64 = inter_event_gaps
+    let variance: f64 = gaps
         .iter()
-        .map(|x| (x - mean).powi(2))
+        .map(|x| ((x - mean) / mean).powi(2))
         .sum::<f64>()
         / n;
-    let std_dev = variance.sqrt();
+    // Variance is relative to μ², so σ is expressed in units of μ
+    let std_dev = variance.sqrt() * mean;
 
     // Barabási burstiness formula: B = (σ - μ) / (σ + μ)
     // This gives values from -1 (periodic) to 1 (bursty)
@@ -156,16 +290,106 @@ fn compute_burstiness(inter_event_gaps: &[f64]) -> f64 {
     ((barabasi + 1.0) / 2.0).clamp(0.0, 1.0)
 }
 
-/// Count deep focus blocks (engagement segments >= 120 seconds without interruptions)
+/// Count deep focus blocks (engagement segments of at least `min_duration_sec` without interruptions)
 fn count_deep_focus_blocks(
     engagement_segments: &[crate::behavior::types::EngagementSegment],
+    min_duration_sec: f64,
 ) -> u32 {
     engagement_segments
         .iter()
-        .filter(|s| s.duration_sec >= DEEP_FOCUS_MIN_DURATION_SEC)
+        .filter(|s| s.duration_sec >= min_duration_sec)
         .count() as u32
 }
 
+/// Compute the fraction of the session covered by deep focus blocks
+fn compute_deep_focus_coverage(
+    engagement_segments: &[crate::behavior::types::EngagementSegment],
+    session_duration_sec: f64,
+    min_duration_sec: f64,
+) -> f64 {
+    if session_duration_sec <= 0.0 {
+        return 0.0;
+    }
+    let focused_sec: f64 = engagement_segments
+        .iter()
+        .filter(|s| s.duration_sec >= min_duration_sec)
+        .map(|s| s.duration_sec)
+        .sum();
+    (focused_sec / session_duration_sec).clamp(0.0, 1.0)
+}
+
+/// Compute app diversity from foreground time per app
+///
+/// Formula: `-sum(p * ln p) / ln(app_count)` over each app's share of the
+/// foreground time. One app gives 0.0; equal time in every app gives 1.0.
+fn compute_app_diversity_entropy(usage: &[AppUsage]) -> Option<f64> {
+    let durations: Vec<f64> = usage.iter().map(|u| u.duration_sec).collect();
+    normalized_entropy(&durations)
+}
+
+/// Shannon entropy of the shares of `amounts`, divided by `ln(amounts.len())`
+///
+/// None when the amounts sum to zero; 0.0 with fewer than two amounts.
+fn normalized_entropy(amounts: &[f64]) -> Option<f64> {
+    let total: f64 = amounts.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    if amounts.len() < 2 {
+        return Some(0.0);
+    }
+    let entropy: f64 = amounts
+        .iter()
+        .map(|a| a / total)
+        .filter(|p| *p > 0.0)
+        .map(|p| -p * p.ln())
+        .sum();
+    Some((entropy / (amounts.len() as f64).ln()).clamp(0.0, 1.0))
+}
+
+/// Linearly interpolated percentile (`p` in 0-1) of sorted values
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let rank = p.clamp(0.0, 1.0) * last as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// Whether a scroll run is sustained, fast same-direction scrolling
+fn is_doomscroll(segment: &ScrollSegment, config: &BehaviorConfig) -> bool {
+    segment.duration_sec >= config.doomscroll_min_duration_sec
+        && segment
+            .mean_velocity
+            .is_some_and(|v| v >= config.doomscroll_min_velocity)
+}
+
+/// Compute engagement stability from the variance of engagement segment lengths
+///
+/// Formula: `1 / (1 + CV)` where CV is the coefficient of variation (std / mean).
+/// Equal-length segments give 1.0; highly uneven segments approach 0.
+fn compute_engagement_stability(
+    engagement_segments: &[crate::behavior::types::EngagementSegment],
+) -> Option<f64> {
+    if engagement_segments.is_empty() {
+        return None;
+    }
+    let n = engagement_segments.len() as f64;
+    let mean = engagement_segments
+        .iter()
+        .map(|s| s.duration_sec)
+        .sum::<f64>()
+        / n;
+    if mean <= 0.0 {
+        return None;
+    }
+    let variance = engagement_segments
+        .iter()
+        .map(|s| (s.duration_sec - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    Some(1.0 / (1.0 + variance.sqrt() / mean))
+}
+
 /// Compute interaction intensity
 ///
 /// Formula: `(non_interruption_events + typing_duration/10) / session_duration`
@@ -219,25 +443,81 @@ fn compute_active_time_ratio(
     (active_interaction_time_ms / duration_ms).clamp(0.0, 1.0)
 }
 
+/// Average screen-on period length in seconds (0 when none observed)
+fn compute_avg_screen_session(screen_sessions_sec: &[f64]) -> f64 {
+    if screen_sessions_sec.is_empty() {
+        return 0.0;
+    }
+    screen_sessions_sec.iter().sum::<f64>() / screen_sessions_sec.len() as f64
+}
+
+/// Compute checking-habit score using exponential saturation
+///
+/// Formula: `1.0 - exp(-checks_per_hour / 6.0)` where a check is a screen-on
+/// period shorter than 30 seconds
+fn compute_checking_habit_score(screen_sessions_sec: &[f64], session_duration_sec: f64) -> f64 {
+    if session_duration_sec <= 0.0 {
+        return 0.0;
+    }
+    let checks = screen_sessions_sec
+        .iter()
+        .filter(|d| **d < CHECK_MAX_DURATION_SEC)
+        .count() as f64;
+    let checks_per_hour = checks / session_duration_sec * 3600.0;
+    1.0 - (-checks_per_hour / CHECKS_PER_HOUR_SCALE).exp()
+}
+
+/// Group sorted check event timestamps into clusters and return each cluster's size.
+///
+/// Consecutive events at most 60 seconds apart belong to the same cluster, so
+/// one pickup that unlocks, opens a notification and switches apps counts as
+/// a single check.
+fn cluster_check_events(times: &[chrono::DateTime<chrono::Utc>]) -> Vec<usize> {
+    let mut clusters = Vec::new();
+    let mut size = 0;
+    let mut previous = None;
+
+    for time in times {
+        let joins = previous.is_some_and(|p: chrono::DateTime<chrono::Utc>| {
+            (*time - p).num_milliseconds() as f64 / 1000.0 <= CHECK_CLUSTER_GAP_SEC
+        });
+        if !joins && size > 0 {
+            clusters.push(size);
+            size = 0;
+        }
+        size += 1;
+        previous = Some(*time);
+    }
+    if size > 0 {
+        clusters.push(size);
+    }
+
+    clusters
+}
+
 /// Compute distraction score (weighted combination)
 ///
-/// Formula:
+/// Formula with the default weights:
 /// ```text
 /// Distraction Score = 0.35 * task_switch_rate
 ///                   + 0.30 * notification_load
 ///                   + 0.20 * fragmented_idle_ratio
 ///                   + 0.15 * scroll_jitter_rate
+///                   + 0.00 * gesture_complexity
 /// ```
 fn compute_distraction_score(
+    weights: &DistractionWeights,
     task_switch_rate: f64,
     notification_load: f64,
     fragmented_idle_ratio: f64,
     scroll_jitter_rate: f64,
+    gesture_complexity: f64,
 ) -> f64 {
-    let score = 0.35 * task_switch_rate
-        + 0.30 * notification_load
-        + 0.20 * fragmented_idle_ratio
-        + 0.15 * scroll_jitter_rate;
+    let score = weights.task_switch_rate * task_switch_rate
+        + weights.notification_load * notification_load
+        + weights.fragmented_idle_ratio * fragmented_idle_ratio
+        + weights.scroll_jitter_rate * scroll_jitter_rate
+        + weights.gesture_complexity * gesture_complexity;
     score.clamp(0.0, 1.0)
 }
 
@@ -264,6 +544,10 @@ mod tests {
             typing_events: 3,
             app_switch_events: 6,
             scroll_direction_reversals: 12,
+            long_press_events: 0,
+            out_of_bounds_events: 0,
+            overlapping_typing_sessions: 0,
+            invalid_timezone: false,
             total_typing_duration_sec: 120.0,
             typing_sessions: vec![],
             idle_segments: vec![IdleSegment {
@@ -287,6 +571,12 @@ mod tests {
                 },
             ],
             inter_event_gaps: vec![10.0, 5.0, 15.0, 8.0, 12.0, 3.0, 20.0, 7.0],
+            screen: Default::default(),
+            late_night_events: 0,
+            check_event_times: vec![],
+            app_usage: vec![],
+            scroll_velocities: vec![],
+            scroll_segments: vec![],
             computed_at: Utc::now(),
         };
 
@@ -298,6 +588,7 @@ mod tests {
             swipes_per_min: 0.17,
             notifications_per_min: 0.27, // 8 notifications in 30 min
             app_switches_per_min: 0.2,   // 6 switches in 30 min
+            unlocks_per_min: 0.0,
             coverage: 0.85,
             quality_flags: vec![],
         }
@@ -391,6 +682,96 @@ mod tests {
 
         // Should have 1 deep focus block (300 seconds >= 120 seconds)
         assert_eq!(derived.deep_focus_blocks, 1);
+
+        // Thresholds are configurable
+        let config = BehaviorConfig::default().with_deep_focus_min_duration_sec(60.0);
+        let derived = BehaviorFeatureDeriver::derive_with_config(make_test_normalized(), &config);
+        assert_eq!(derived.deep_focus_blocks, 2);
+    }
+
+    #[test]
+    fn test_checking_habit_score() {
+        // No screen sessions -> no checking habit
+        assert_eq!(compute_checking_habit_score(&[], 1800.0), 0.0);
+
+        // 3 checks (< 30s) in 30 min = 6 checks/hour -> 1 - e^-1
+        let sessions = [10.0, 20.0, 5.0, 600.0];
+        let expected = 1.0 - (-1.0_f64).exp();
+        assert!((compute_checking_habit_score(&sessions, 1800.0) - expected).abs() < 0.001);
+        assert!((compute_avg_screen_session(&sessions) - 158.75).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_check_clusters() {
+        let at = |sec: i64| {
+            Utc.with_ymd_and_hms(2024, 1, 15, 14, 0, 0).unwrap() + chrono::Duration::seconds(sec)
+        };
+        assert!(cluster_check_events(&[]).is_empty());
+
+        // Burst of 3 within a minute, an isolated check, then a pair
+        let times = [at(0), at(20), at(70), at(600), at(1200), at(1230)];
+        assert_eq!(cluster_check_events(&times), vec![3, 1, 2]);
+
+        let mut normalized = make_test_normalized();
+        normalized.canonical.check_event_times = times.to_vec();
+        let derived = BehaviorFeatureDeriver::derive(normalized);
+
+        // 3 checks and 1 burst in 30 minutes
+        assert!((derived.check_frequency_per_hour - 6.0).abs() < 0.001);
+        assert_eq!(derived.check_bursts, 1);
+        let expected = 1.0 - (-1.0_f64).exp();
+        assert!((derived.compulsive_check_score - expected).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_engagement_metrics() {
+        let normalized = make_test_normalized();
+        let segments = &normalized.canonical.engagement_segments;
+
+        // One 300s deep focus block in a 1800s session
+        assert!(
+            (compute_deep_focus_coverage(segments, 1800.0, 120.0) - 300.0 / 1800.0).abs() < 0.001
+        );
+
+        // Segments of 300s and 60s: mean 180, std 120, CV 2/3
+        let stability = compute_engagement_stability(segments).unwrap();
+        assert!((stability - 0.6).abs() < 0.001);
+        assert_eq!(compute_engagement_stability(&[]), None);
+    }
+
+    #[test]
+    fn test_scroll_metrics() {
+        let mut normalized = make_test_normalized();
+        let at = |minute| Utc.with_ymd_and_hms(2024, 1, 15, 14, minute, 0).unwrap();
+        let segment = |start, end, velocity| ScrollSegment {
+            start: at(start),
+            end: at(end),
+            duration_sec: f64::from(end - start) * 60.0,
+            event_count: 10,
+            mean_velocity: Some(velocity),
+        };
+        let canonical = &mut normalized.canonical;
+        canonical.scroll_velocities = vec![300.0, 100.0, 200.0, 500.0, 400.0];
+        canonical.scroll_segments = vec![
+            // Fast and sustained
+            segment(0, 3, 450.0),
+            // Sustained but slow
+            segment(5, 8, 80.0),
+            // Fast but too short
+            segment(10, 10, 600.0),
+        ];
+
+        let derived = BehaviorFeatureDeriver::derive(normalized);
+        assert_eq!(derived.scroll_velocity_median, Some(300.0));
+        assert!((derived.scroll_velocity_p90.unwrap() - 460.0).abs() < 1e-9);
+        assert_eq!(derived.doomscroll_segments, 1);
+        assert!((derived.doomscroll_segment_ratio.unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        // 3 of 30 minutes
+        assert!((derived.doomscroll_score - 0.1).abs() < 1e-9);
+
+        let derived = BehaviorFeatureDeriver::derive(make_test_normalized());
+        assert_eq!(derived.scroll_velocity_median, None);
+        assert_eq!(derived.doomscroll_segment_ratio, None);
     }
 
     #[test]
@@ -410,21 +791,47 @@ mod tests {
 
     #[test]
     fn test_distraction_score_weights() {
+        let w = DistractionWeights::default();
+
         // Test that weights sum correctly
         // Max distraction: all components at 1.0
-        let max_distraction = compute_distraction_score(1.0, 1.0, 1.0, 1.0);
+        let max_distraction = compute_distraction_score(&w, 1.0, 1.0, 1.0, 1.0, 1.0);
         assert!((max_distraction - 1.0).abs() < 0.001);
 
         // Zero distraction: all components at 0.0
-        let min_distraction = compute_distraction_score(0.0, 0.0, 0.0, 0.0);
+        let min_distraction = compute_distraction_score(&w, 0.0, 0.0, 0.0, 0.0, 0.0);
         assert!((min_distraction - 0.0).abs() < 0.001);
 
         // Individual weight contributions
-        let task_only = compute_distraction_score(1.0, 0.0, 0.0, 0.0);
+        let task_only = compute_distraction_score(&w, 1.0, 0.0, 0.0, 0.0, 0.0);
         assert!((task_only - 0.35).abs() < 0.001);
 
-        let notif_only = compute_distraction_score(0.0, 1.0, 0.0, 0.0);
+        let notif_only = compute_distraction_score(&w, 0.0, 1.0, 0.0, 0.0, 0.0);
         assert!((notif_only - 0.30).abs() < 0.001);
+
+        // Gesture complexity only counts once it is given a weight
+        let gesture_only = compute_distraction_score(&w, 0.0, 0.0, 0.0, 0.0, 1.0);
+        assert_eq!(gesture_only, 0.0);
+        let weighted = w.with_gesture_complexity(0.1);
+        let gesture_only = compute_distraction_score(&weighted, 0.0, 0.0, 0.0, 0.0, 1.0);
+        assert!((gesture_only - 0.1).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_gesture_metrics() {
+        let mut normalized = make_test_normalized();
+        normalized.canonical.long_press_events = 10;
+        let derived = BehaviorFeatureDeriver::derive(normalized);
+
+        // 40 taps (10 long presses), 5 swipes, 60 scrolls
+        assert_eq!(derived.long_press_rate, Some(0.25));
+        assert_eq!(derived.swipe_tap_ratio, Some(5.0 / 40.0));
+        let complexity = derived.gesture_complexity.unwrap();
+        assert!(complexity > 0.0 && complexity < 1.0);
+
+        assert_eq!(normalized_entropy(&[5.0, 0.0, 0.0, 0.0]), Some(0.0));
+        assert_eq!(normalized_entropy(&[2.0, 2.0, 2.0, 2.0]), Some(1.0));
+        assert_eq!(normalized_entropy(&[0.0, 0.0]), None);
     }
 
     #[test]
@@ -451,4 +858,58 @@ mod tests {
         assert!(derived.focus_hint >= 0.0 && derived.focus_hint <= 1.0);
         assert!(derived.interaction_intensity >= 0.0);
     }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_burstiness_defined_for_any_gaps(
+            gaps in proptest::collection::vec(proptest::num::f64::ANY, 0..64)
+        ) {
+            let burstiness = compute_burstiness(&gaps);
+            proptest::prop_assert!((0.0..=1.0).contains(&burstiness), "{}", burstiness);
+        }
+
+        #[test]
+        fn prop_derived_scores_are_bounded(
+            duration_sec in proptest::prop_oneof![proptest::strategy::Just(0.0), 0.0..86_400.0f64],
+            total_idle_time_sec in 0.0..200_000.0f64,
+            scroll_events in 0u32..5_000,
+            reversals in 0u32..10_000,
+            app_switches_per_min in 0.0..1_000.0f64,
+            notifications_per_min in 0.0..1_000.0f64,
+            gaps in proptest::collection::vec(0.0..10_000.0f64, 0..64),
+        ) {
+            let mut normalized = make_test_normalized();
+            normalized.canonical.duration_sec = duration_sec;
+            normalized.canonical.total_idle_time_sec = total_idle_time_sec;
+            normalized.canonical.scroll_events = scroll_events;
+            normalized.canonical.scroll_direction_reversals = reversals;
+            normalized.canonical.inter_event_gaps = gaps;
+            normalized.app_switches_per_min = app_switches_per_min;
+            normalized.notifications_per_min = notifications_per_min;
+
+            let derived = BehaviorFeatureDeriver::derive(normalized);
+            for score in [
+                derived.task_switch_rate,
+                derived.task_switch_cost,
+                derived.active_time_ratio,
+                derived.notification_load,
+                derived.idle_ratio,
+                derived.scroll_jitter_rate,
+                derived.burstiness,
+                derived.interaction_intensity,
+                derived.checking_habit_score,
+                derived.deep_focus_coverage,
+                derived.late_night_usage_ratio,
+                derived.compulsive_check_score,
+                derived.distraction_score,
+                derived.focus_hint,
+            ] {
+                proptest::prop_assert!((0.0..=1.0).contains(&score), "{:?}", derived);
+            }
+            proptest::prop_assert!(derived.fragmented_idle_ratio.is_finite());
+            proptest::prop_assert!(
+                (derived.focus_hint + derived.distraction_score - 1.0).abs() < 1e-12
+            );
+        }
+    }
 }
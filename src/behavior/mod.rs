@@ -6,17 +6,27 @@
 //! Pipeline: Session JSON → Adapter → Normalizer → Features → Baseline → Encoder → HSI JSON
 
 pub mod adapter;
+#[cfg(feature = "experimental-affect")]
+pub mod affect;
 pub mod baseline;
+pub mod bio_context;
+pub mod config;
 pub mod encoder;
 pub mod features;
 pub mod normalizer;
 pub mod pipeline;
+pub mod session;
 pub mod types;
+pub mod validation;
 
-pub use pipeline::{behavior_to_hsi, BehaviorProcessor};
+pub use bio_context::{BioContext, SnapshotOptions};
+pub use config::{BehaviorConfig, CapacityWeights, DistractionWeights, OutOfBoundsPolicy};
+pub use pipeline::{behavior_to_hsi, behavior_to_hsi_payload, BehaviorProcessor};
+pub use session::BehaviorSessionBuilder;
 pub use types::{
     BehaviorEvent, BehaviorEventType, BehaviorSession, CanonicalBehaviorSignals,
     ContextualBehaviorSignals, DerivedBehaviorSignals, HsiAxes, HsiAxesDomain, HsiAxisReading,
-    HsiDirection, HsiPayload, HsiPrivacy, HsiProducer, HsiSource, HsiSourceType, HsiWindow,
-    NormalizedBehaviorSignals,
+    HsiConsent, HsiDirection, HsiPayload, HsiPrivacy, HsiProducer, HsiSource, HsiSourceType,
+    HsiWindow, NormalizedBehaviorSignals,
 };
+pub use validation::{validate_session_json, SessionViolation};
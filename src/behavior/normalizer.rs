@@ -60,6 +60,12 @@ impl BehaviorNormalizer {
             0.0
         };
 
+        let unlocks_per_min = if duration_min > 0.0 {
+            canonical.screen.unlock_events as f64 / duration_min
+        } else {
+            0.0
+        };
+
         // Calculate coverage based on event diversity
         let coverage = calculate_coverage(&canonical);
 
@@ -74,6 +80,7 @@ impl BehaviorNormalizer {
             swipes_per_min,
             notifications_per_min,
             app_switches_per_min,
+            unlocks_per_min,
             coverage,
             quality_flags,
         }
@@ -151,6 +158,18 @@ fn determine_quality_flags(canonical: &CanonicalBehaviorSignals) -> Vec<Behavior
         flags.push(BehaviorQualityFlag::SessionGaps);
     }
 
+    if canonical.out_of_bounds_events > 0 {
+        flags.push(BehaviorQualityFlag::EventsOutOfBounds);
+    }
+
+    if canonical.overlapping_typing_sessions > 0 {
+        flags.push(BehaviorQualityFlag::OverlappingTypingSessions);
+    }
+
+    if canonical.invalid_timezone {
+        flags.push(BehaviorQualityFlag::InvalidTimezone);
+    }
+
     flags
 }
 
@@ -203,12 +222,22 @@ mod tests {
             typing_events: 3,
             app_switch_events: 4,
             scroll_direction_reversals: 10,
+            long_press_events: 0,
+            out_of_bounds_events: 0,
+            overlapping_typing_sessions: 0,
+            invalid_timezone: false,
             total_typing_duration_sec: 45.0,
             typing_sessions: vec![],
             idle_segments: vec![],
             total_idle_time_sec: 120.0,
             engagement_segments: vec![],
             inter_event_gaps: vec![10.0, 15.0, 8.0, 12.0],
+            screen: Default::default(),
+            late_night_events: 0,
+            check_event_times: vec![],
+            app_usage: vec![],
+            scroll_velocities: vec![],
+            scroll_segments: vec![],
             computed_at: Utc::now(),
         }
     }
@@ -3,12 +3,26 @@
 //! This module provides the public API for behavioral metrics processing.
 //! It orchestrates the full pipeline from behavioral session JSON to HSI output.
 
-use crate::behavior::adapter::{parse_session, session_to_canonical};
+use crate::baseline::BaselineStore;
+use crate::behavior::adapter::{
+    parse_session, session_app_usage, session_to_canonical, session_to_canonical_with_config,
+};
 use crate::behavior::baseline::BehaviorBaselineStore;
+use crate::behavior::bio_context::SnapshotOptions;
+use crate::behavior::config::BehaviorConfig;
 use crate::behavior::encoder::HsiBehaviorEncoder;
 use crate::behavior::features::BehaviorFeatureDeriver;
 use crate::behavior::normalizer::BehaviorNormalizer;
+use crate::behavior::session::BehaviorSessionBuilder;
+use crate::behavior::types::{BehaviorSession, ContextualBehaviorSignals, HsiPayload};
+use crate::encoder::{device_instance_id, EncoderConfig, ProducerIdentity};
 use crate::error::ComputeError;
+use crate::explain::StagedWindow;
+use crate::observer::{observe, NoopObserver, PipelineKind, PipelineObserver, ProcessedWindow};
+use crate::privacy::PrivacyPolicy;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Convert behavioral session JSON to HSI-compliant JSON (stateless, one-shot).
 ///
@@ -23,8 +37,23 @@ use crate::error::ComputeError;
 /// let hsi_json = behavior_to_hsi(session_json)?;
 /// ```
 pub fn behavior_to_hsi(session_json: String) -> Result<String, ComputeError> {
+    let encoder = HsiBehaviorEncoder::new();
+    encoder.payload_to_json(&encode_stateless(&session_json, &encoder)?)
+}
+
+/// Convert behavioral session JSON to a typed HSI payload (stateless, one-shot).
+///
+/// Same as [`behavior_to_hsi`] without the JSON round trip.
+pub fn behavior_to_hsi_payload(session_json: &str) -> Result<HsiPayload, ComputeError> {
+    encode_stateless(session_json, &HsiBehaviorEncoder::new())
+}
+
+fn encode_stateless(
+    session_json: &str,
+    encoder: &HsiBehaviorEncoder,
+) -> Result<HsiPayload, ComputeError> {
     // Stage 1: Parse session JSON
-    let session = parse_session(&session_json)?;
+    let session = parse_session(session_json)?;
 
     // Stage 2: Convert to canonical signals
     let canonical = session_to_canonical(&session)?;
@@ -39,9 +68,8 @@ pub fn behavior_to_hsi(session_json: String) -> Result<String, ComputeError> {
     let mut baseline_store = BehaviorBaselineStore::default();
     let contextual = baseline_store.update_and_contextualize(derived);
 
-    // Stage 6: Encode to HSI JSON
-    let encoder = HsiBehaviorEncoder::new();
-    encoder.encode_to_json(&contextual)
+    // Stage 6: Encode to HSI
+    encoder.encode(&contextual)
 }
 
 /// Stateful processor for incremental processing with persistent baselines.
@@ -50,6 +78,8 @@ pub fn behavior_to_hsi(session_json: String) -> Result<String, ComputeError> {
 pub struct BehaviorProcessor {
     baseline_store: BehaviorBaselineStore,
     encoder: HsiBehaviorEncoder,
+    config: BehaviorConfig,
+    observer: Arc<dyn PipelineObserver>,
 }
 
 impl Default for BehaviorProcessor {
@@ -64,6 +94,8 @@ impl BehaviorProcessor {
         Self {
             baseline_store: BehaviorBaselineStore::default(),
             encoder: HsiBehaviorEncoder::new(),
+            config: BehaviorConfig::default(),
+            observer: Arc::new(NoopObserver),
         }
     }
 
@@ -72,7 +104,147 @@ impl BehaviorProcessor {
         Self {
             baseline_store: BehaviorBaselineStore::new(sessions),
             encoder: HsiBehaviorEncoder::new(),
+            config: BehaviorConfig::default(),
+            observer: Arc::new(NoopObserver),
+        }
+    }
+
+    /// Use custom idle/engagement/deep-focus thresholds
+    pub fn with_config(mut self, config: BehaviorConfig) -> Result<Self, ComputeError> {
+        self.set_config(config)?;
+        Ok(self)
+    }
+
+    /// Replace the idle/engagement/deep-focus thresholds
+    pub fn set_config(&mut self, config: BehaviorConfig) -> Result<(), ComputeError> {
+        config.validate()?;
+        self.config = config;
+        self.encoder.set_config(config);
+        Ok(())
+    }
+
+    /// Stamp payloads with a host's producer identity (builder form of [`Self::set_producer`])
+    pub fn with_producer(mut self, producer: ProducerIdentity) -> Self {
+        self.set_producer(producer);
+        self
+    }
+
+    /// Stamp payloads with a host's producer name, version and instance ID.
+    ///
+    /// Without an instance ID, a pinned one ([`Self::set_instance_id`]) is kept.
+    pub fn set_producer(&mut self, mut producer: ProducerIdentity) {
+        if producer.instance_id.is_none() {
+            producer.instance_id = self.baseline_store.instance_id().map(str::to_string);
         }
+        self.encoder.set_producer(producer);
+    }
+
+    /// Pin the producer instance ID (builder form of [`Self::set_instance_id`])
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.set_instance_id(instance_id);
+        self
+    }
+
+    /// Pin the producer instance ID, derived from the device ID with
+    /// [`device_instance_id`]
+    pub fn with_device_instance_id(self, device_id: &str) -> Self {
+        self.with_instance_id(device_instance_id(device_id))
+    }
+
+    /// Pin the producer instance ID instead of generating one per processor.
+    ///
+    /// The ID is saved with the baselines and restored by
+    /// [`Self::load_baselines`].
+    pub fn set_instance_id(&mut self, instance_id: impl Into<String>) {
+        let instance_id = instance_id.into();
+        self.baseline_store
+            .set_instance_id(Some(instance_id.clone()));
+        let producer = self
+            .encoder
+            .producer_identity()
+            .clone()
+            .with_instance_id(instance_id);
+        self.encoder.set_producer(producer);
+    }
+
+    /// Filter emitted axes and metadata (builder form of [`Self::set_encoder_config`])
+    pub fn with_encoder_config(mut self, config: EncoderConfig) -> Self {
+        self.set_encoder_config(config);
+        self
+    }
+
+    /// Filter emitted axes and metadata
+    pub fn set_encoder_config(&mut self, config: EncoderConfig) {
+        self.encoder.set_encoder_config(config);
+    }
+
+    /// Set the privacy declaration and metadata policy (builder form of [`Self::set_privacy_policy`])
+    pub fn with_privacy_policy(mut self, policy: PrivacyPolicy) -> Self {
+        self.set_privacy_policy(policy);
+        self
+    }
+
+    /// Set the privacy declaration and metadata policy
+    pub fn set_privacy_policy(&mut self, policy: PrivacyPolicy) {
+        self.encoder.set_privacy_policy(policy);
+    }
+
+    /// Seal emitted payloads with `meta.integrity` (builder form of [`Self::set_integrity`])
+    #[cfg(feature = "integrity")]
+    pub fn with_integrity(mut self, integrity: crate::integrity::IntegrityConfig) -> Self {
+        self.set_integrity(Some(integrity));
+        self
+    }
+
+    /// Seal emitted payloads with `meta.integrity`, or stop sealing with `None`
+    #[cfg(feature = "integrity")]
+    pub fn set_integrity(&mut self, integrity: Option<crate::integrity::IntegrityConfig>) {
+        self.encoder.set_integrity(integrity);
+    }
+
+    /// Attach bio context from wearable baselines (builder form of [`Self::set_bio_context`])
+    pub fn with_bio_context(mut self, baselines: &BaselineStore) -> Self {
+        self.set_bio_context(Some(baselines));
+        self
+    }
+
+    /// Attach the latest wearable day in `baselines`, relative to its baselines,
+    /// as `physiology` and `sleep` readings on session payloads; `None` stops.
+    ///
+    /// The store is copied, so set it again after the wearable pipeline has
+    /// processed newer days.
+    pub fn set_bio_context(&mut self, baselines: Option<&BaselineStore>) {
+        self.encoder.set_bio_context(baselines.cloned());
+    }
+
+    /// Set bio context decay (builder form of [`Self::set_snapshot_options`])
+    pub fn with_snapshot_options(mut self, options: SnapshotOptions) -> Result<Self, ComputeError> {
+        self.set_snapshot_options(options)?;
+        Ok(self)
+    }
+
+    /// Set the half-life, per field, over which bio context readings lose
+    /// confidence as the wearable day ages
+    pub fn set_snapshot_options(&mut self, options: SnapshotOptions) -> Result<(), ComputeError> {
+        options.validate()?;
+        self.encoder.set_snapshot_options(options);
+        Ok(())
+    }
+
+    /// Report pipeline metrics to `observer` (builder form of [`Self::set_observer`])
+    pub fn with_observer(mut self, observer: Arc<dyn PipelineObserver>) -> Self {
+        self.set_observer(observer);
+        self
+    }
+
+    /// Report pipeline metrics to `observer`, replacing any previous one
+    pub fn set_observer(&mut self, observer: Arc<dyn PipelineObserver>) {
+        self.observer = observer;
+    }
+
+    /// Current pipeline thresholds
+    pub fn config(&self) -> &BehaviorConfig {
+        &self.config
     }
 
     /// Process a behavioral session and return HSI JSON
@@ -84,21 +256,168 @@ impl BehaviorProcessor {
     /// HSI JSON payload string
     pub fn process(&mut self, session_json: &str) -> Result<String, ComputeError> {
         // Stage 1: Parse session JSON
-        let session = parse_session(session_json)?;
+        let result = parse_session(session_json)
+            .and_then(|session| self.run_session(&session))
+            .and_then(|(payload, _)| self.encoder.payload_to_json(&payload));
+        observe(self.observer.as_ref(), PipelineKind::Behavior, result)
+    }
+
+    /// Process a behavioral session like [`Self::process`], returning a typed payload
+    pub fn process_payload(&mut self, session_json: &str) -> Result<HsiPayload, ComputeError> {
+        let result = parse_session(session_json)
+            .and_then(|session| self.run_session(&session))
+            .and_then(|(payload, _)| self.sealed(payload));
+        observe(self.observer.as_ref(), PipelineKind::Behavior, result)
+    }
+
+    /// Process an already-parsed session (e.g. from [`BehaviorSessionBuilder::finalize`])
+    pub fn process_session(&mut self, session: &BehaviorSession) -> Result<String, ComputeError> {
+        let result = self
+            .run_session(session)
+            .and_then(|(payload, _)| self.encoder.payload_to_json(&payload));
+        observe(self.observer.as_ref(), PipelineKind::Behavior, result)
+    }
+
+    /// Process an already-parsed session, returning a typed payload
+    pub fn process_session_payload(
+        &mut self,
+        session: &BehaviorSession,
+    ) -> Result<HsiPayload, ComputeError> {
+        let result = self
+            .run_session(session)
+            .and_then(|(payload, _)| self.sealed(payload));
+        observe(self.observer.as_ref(), PipelineKind::Behavior, result)
+    }
+
+    /// Process an already-parsed session, keeping its intermediate signals for inspection
+    pub fn process_session_with_stages(
+        &mut self,
+        session: &BehaviorSession,
+    ) -> Result<StagedWindow<ContextualBehaviorSignals, HsiPayload>, ComputeError> {
+        let result = self.run_session(session).and_then(|(hsi, contextual)| {
+            Ok(StagedWindow {
+                contextual,
+                hsi: self.sealed(hsi)?,
+            })
+        });
+        observe(self.observer.as_ref(), PipelineKind::Behavior, result)
+    }
+
+    fn sealed(&self, mut payload: HsiPayload) -> Result<HsiPayload, ComputeError> {
+        self.encoder.seal(&mut payload)?;
+        Ok(payload)
+    }
+
+    /// Run a parsed session through the remaining stages, reporting it to the observer
+    fn run_session(
+        &mut self,
+        session: &BehaviorSession,
+    ) -> Result<(HsiPayload, ContextualBehaviorSignals), ComputeError> {
+        let started = Instant::now();
+        self.observer
+            .on_events_parsed(PipelineKind::Behavior, session.events.len());
+
+        // Scrub identifiers before any stage can see them. App usage is
+        // measured first so dropped app identifiers still count toward app
+        // diversity; only their pseudonyms are kept.
+        let scrubbed;
+        let (session, app_usage) = match &self.encoder.privacy_policy().scrub {
+            Some(scrubber) => {
+                let mut app_usage = session_app_usage(session, &self.config)?;
+                for usage in &mut app_usage {
+                    usage.app_id = scrubber.pseudonym(&usage.app_id);
+                }
+                let mut copy = session.clone();
+                scrubber.scrub_session(&mut copy);
+                scrubbed = copy;
+                (&scrubbed, Some(app_usage))
+            }
+            None => (session, None),
+        };
+
+        trace_span!(
+            INFO,
+            "session",
+            session_id = %session.session_id,
+            events = session.events.len()
+        );
 
         // Stage 2: Convert to canonical signals
-        let canonical = session_to_canonical(&session)?;
+        let canonical = {
+            trace_span!(DEBUG, "adapt");
+            let mut canonical = session_to_canonical_with_config(session, &self.config)?;
+            if let Some(app_usage) = app_usage {
+                canonical.app_usage = app_usage;
+            }
+            canonical
+        };
 
         // Stage 3: Normalize signals
-        let normalized = BehaviorNormalizer::normalize(canonical);
+        let normalized = {
+            trace_span!(DEBUG, "normalize");
+            BehaviorNormalizer::normalize(canonical)
+        };
+        trace_event!(
+            DEBUG,
+            coverage = normalized.coverage,
+            flags = ?normalized.quality_flags,
+            "session normalized"
+        );
+        for flag in &normalized.quality_flags {
+            self.observer.on_quality_flag(
+                PipelineKind::Behavior,
+                &session.session_id,
+                flag.as_str(),
+            );
+        }
+        let coverage = normalized.coverage;
 
         // Stage 4: Derive features
-        let derived = BehaviorFeatureDeriver::derive(normalized);
+        let derived = {
+            trace_span!(DEBUG, "derive");
+            BehaviorFeatureDeriver::derive_with_config(normalized, &self.config)
+        };
 
         // Stage 5: Apply baselines
-        let contextual = self.baseline_store.update_and_contextualize(derived);
+        let contextual = {
+            trace_span!(DEBUG, "baseline");
+            self.baseline_store.update_and_contextualize(derived)
+        };
+
+        // Stage 6: Encode to HSI
+        let payload = {
+            trace_span!(DEBUG, "encode");
+            self.encoder.encode(&contextual)?
+        };
+        self.observer.on_day_processed(&ProcessedWindow {
+            pipeline: PipelineKind::Behavior,
+            id: &session.session_id,
+            coverage,
+            elapsed: started.elapsed(),
+        });
+        Ok((payload, contextual))
+    }
 
-        // Stage 6: Encode to HSI JSON
+    /// Set the wearable physiology context (HRV/RHR deviation) used for affect readings
+    #[cfg(feature = "experimental-affect")]
+    pub fn set_physiology_context(
+        &mut self,
+        physiology: Option<crate::behavior::affect::PhysiologyContext>,
+    ) {
+        self.encoder.set_physiology_context(physiology);
+    }
+
+    /// Encode a live HSI payload for an in-progress session ending at `as_of`.
+    ///
+    /// Deviations are computed against the current baselines, which are not
+    /// updated; call [`Self::process_session`] once the session is finalized.
+    pub fn snapshot(
+        &self,
+        builder: &BehaviorSessionBuilder,
+        as_of: DateTime<Utc>,
+    ) -> Result<String, ComputeError> {
+        let derived = builder.partial_snapshot_with_config(as_of, &self.config)?;
+        let contextual = self.baseline_store.contextualize(derived);
         self.encoder.encode_to_json(&contextual)
     }
 
@@ -109,10 +428,22 @@ impl BehaviorProcessor {
             .map_err(|e| ComputeError::EncodingError(e.to_string()))
     }
 
-    /// Load baseline state from JSON
+    /// Load baseline state from JSON.
+    ///
+    /// A saved instance ID is restored; a store saved without one keeps the
+    /// currently pinned ID, if any.
     pub fn load_baselines(&mut self, json: &str) -> Result<(), ComputeError> {
+        let pinned = self.baseline_store.instance_id().map(str::to_string);
         self.baseline_store = BehaviorBaselineStore::from_json(json)
             .map_err(|e| ComputeError::ParseError(e.to_string()))?;
+        if let Some(instance_id) = self
+            .baseline_store
+            .instance_id()
+            .map(str::to_string)
+            .or(pinned)
+        {
+            self.set_instance_id(instance_id);
+        }
         Ok(())
     }
 
@@ -329,6 +660,79 @@ mod tests {
         assert_eq!(new_processor.baseline_session_count(), 2);
     }
 
+    #[test]
+    fn test_instance_id_persisted_in_baselines() {
+        let mut processor = BehaviorProcessor::new().with_instance_id("phone-1");
+        processor.process(sample_behavior_session_json()).unwrap();
+        let saved = processor.save_baselines().unwrap();
+
+        let mut restarted = BehaviorProcessor::new();
+        restarted.load_baselines(&saved).unwrap();
+        let hsi: serde_json::Value =
+            serde_json::from_str(&restarted.process(sample_behavior_session_json()).unwrap())
+                .unwrap();
+        assert_eq!(hsi["producer"]["instance_id"], "phone-1");
+    }
+
+    #[test]
+    fn test_bio_context_from_wearable_baselines() {
+        use crate::pipeline::FluxProcessor;
+
+        let ndjson: Vec<String> = [(11, 60), (12, 60), (13, 60), (14, 60), (15, 45)]
+            .iter()
+            .map(|(day, hrv)| {
+                format!(
+                    r#"{{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-{day}T07:00:00Z","source":{{"provider":"garmin","device_id":"watch-1"}},"record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":{hrv},"unit":"ms"}}}}}}"#
+                )
+            })
+            .collect();
+        let mut wearable = FluxProcessor::new();
+        wearable
+            .process_raw_events(&ndjson.join("\n"), "UTC", "watch-1")
+            .unwrap();
+
+        let plain = BehaviorProcessor::new()
+            .process(sample_behavior_session_json())
+            .unwrap();
+        let plain: serde_json::Value = serde_json::from_str(&plain).unwrap();
+        assert!(plain["axes"].get("physiology").is_none());
+
+        let result = BehaviorProcessor::new()
+            .with_bio_context(wearable.baseline_store())
+            .process(sample_behavior_session_json())
+            .unwrap();
+        assert!(crate::validation::validate_hsi_json(&result)
+            .unwrap()
+            .is_empty());
+        let payload: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let hrv = &payload["axes"]["physiology"]["readings"][0];
+        assert_eq!(hrv["axis"], "hrv_deviation");
+        assert_eq!(hrv["score"], 0.0);
+        assert_eq!(hrv["confidence"], 1.0);
+        assert_eq!(payload["source_ids"][1], "s_wearable");
+
+        // The behavior axes are unchanged, plus focus fused with recovery:
+        // HRV 25% below baseline and no sleep data make readiness 0
+        let readings = |payload: &serde_json::Value| -> Vec<serde_json::Value> {
+            payload["axes"]["behavior"]["readings"]
+                .as_array()
+                .unwrap()
+                .clone()
+        };
+        let (capacity, rest): (Vec<_>, Vec<_>) = readings(&payload)
+            .into_iter()
+            .partition(|r| r["axis"] == "effective_capacity");
+        assert_eq!(rest, readings(&plain));
+        let focus = rest.iter().find(|r| r["axis"] == "focus").unwrap();
+        let expected = focus["score"].as_f64().unwrap() * 0.5;
+        assert!((capacity[0]["score"].as_f64().unwrap() - expected).abs() < 1e-9);
+        assert_eq!(
+            payload["meta"]["effective_capacity_weights"],
+            serde_json::json!({"recovery": 0.6, "sleep": 0.4, "physiology": 0.5})
+        );
+        assert!(plain["meta"].get("effective_capacity_weights").is_none());
+    }
+
     #[test]
     fn test_clear_baselines() {
         let mut processor = BehaviorProcessor::new();
@@ -409,4 +813,311 @@ mod tests {
         // jitter = 1 / (3 - 1) = 0.5
         assert!((scroll_jitter_score - 0.5).abs() < 0.001);
     }
+
+    #[test]
+    fn test_incremental_session_matches_batch() {
+        let session = parse_session(sample_behavior_session_json()).unwrap();
+        let mut builder = BehaviorSessionBuilder::new(
+            &session.session_id,
+            &session.device_id,
+            session.start_time,
+        )
+        .with_timezone(&session.timezone);
+        for event in &session.events {
+            builder.push_event(event.clone());
+        }
+
+        let mut processor = BehaviorProcessor::new();
+        let live = processor.snapshot(&builder, session.end_time).unwrap();
+        assert_eq!(processor.baseline_session_count(), 0);
+
+        let finalized = builder.finalize(session.end_time).unwrap();
+        let incremental = processor.process_session(&finalized).unwrap();
+        assert_eq!(processor.baseline_session_count(), 1);
+
+        let batch = behavior_to_hsi(sample_behavior_session_json().to_string()).unwrap();
+        let readings = |json: &str| {
+            let payload: serde_json::Value = serde_json::from_str(json).unwrap();
+            payload["axes"]["behavior"]["readings"].clone()
+        };
+        assert_eq!(readings(&incremental), readings(&batch));
+        assert_eq!(readings(&live), readings(&batch));
+    }
+
+    #[cfg(feature = "experimental-affect")]
+    #[test]
+    fn test_affect_readings_with_physiology() {
+        use crate::behavior::affect::{PhysiologyContext, AFFECT_CONFIDENCE_DISCOUNT};
+
+        let mut processor = BehaviorProcessor::new();
+        processor.set_physiology_context(Some(PhysiologyContext {
+            hrv_deviation_pct: Some(-20.0),
+            rhr_deviation_pct: Some(8.0),
+        }));
+        let result = processor.process(sample_behavior_session_json()).unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        let affect = payload["axes"]["affect"]["readings"].as_array().unwrap();
+        let axes: Vec<&str> = affect.iter().map(|r| r["axis"].as_str().unwrap()).collect();
+        assert_eq!(axes, vec!["arousal", "strain_affect"]);
+
+        let focus = payload["axes"]["behavior"]["readings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["axis"] == "focus")
+            .unwrap();
+        let expected = focus["confidence"].as_f64().unwrap() * AFFECT_CONFIDENCE_DISCOUNT;
+        assert!((affect[0]["confidence"].as_f64().unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_config_thresholds() {
+        assert!(BehaviorProcessor::new()
+            .with_config(BehaviorConfig::default().with_idle_gap_threshold_sec(-1.0))
+            .is_err());
+
+        let deep_focus_blocks = |config: BehaviorConfig| {
+            let mut processor = BehaviorProcessor::new().with_config(config).unwrap();
+            let result = processor.process(sample_behavior_session_json()).unwrap();
+            let payload: serde_json::Value = serde_json::from_str(&result).unwrap();
+            payload["meta"]["deep_focus_blocks"].as_u64().unwrap()
+        };
+        let strict = BehaviorConfig::default().with_deep_focus_min_duration_sec(3600.0);
+        assert_eq!(deep_focus_blocks(strict), 0);
+        let lenient = BehaviorConfig::default()
+            .with_idle_gap_threshold_sec(600.0)
+            .with_deep_focus_min_duration_sec(1.0);
+        assert!(deep_focus_blocks(lenient) > 0);
+    }
+
+    #[test]
+    fn test_encoder_config_limits_axes() {
+        let config = EncoderConfig::default()
+            .with_include_axes(["focus", "distraction", "burstiness", "idle_ratio"])
+            .with_meta_suppressed(true);
+        let mut processor = BehaviorProcessor::new().with_encoder_config(config);
+        let result = processor.process(sample_behavior_session_json()).unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        let readings = payload["axes"]["behavior"]["readings"].as_array().unwrap();
+        assert_eq!(readings.len(), 4);
+        assert!(payload["axes"].get("engagement").is_none());
+        assert!(payload.get("meta").is_none());
+    }
+
+    #[test]
+    fn test_sdk_v1_profile_renames_axes_and_meta() {
+        use crate::encoder::OutputProfile;
+
+        // Allow/deny lists keep using Flux names
+        let config = EncoderConfig::default()
+            .with_include_axes(["focus", "distraction", "idle_ratio"])
+            .with_profile(OutputProfile::SdkV1);
+        let mut processor = BehaviorProcessor::new().with_encoder_config(config);
+        let result = processor.process(sample_behavior_session_json()).unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        let axes: Vec<&str> = payload["axes"]["behavior"]["readings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["axis"].as_str().unwrap())
+            .collect();
+        assert_eq!(axes, ["distraction_score", "focus_hint", "idle_ratio"]);
+        assert!(payload["meta"].get("late_night_usage_ratio").is_some());
+        assert!(payload["meta"]
+            .get("late_night_usage_ratio_session")
+            .is_none());
+        assert!(crate::validation::validate_hsi(&payload).is_empty());
+    }
+
+    #[test]
+    fn test_privacy_policy_sets_declaration() {
+        use crate::behavior::types::HsiConsent;
+
+        let policy = PrivacyPolicy::default()
+            .with_consent(HsiConsent::Explicit)
+            .with_purposes(["wellness"])
+            .with_embedding_allowed(false);
+        let mut processor = BehaviorProcessor::new().with_privacy_policy(policy);
+        let result = processor.process(sample_behavior_session_json()).unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(payload["privacy"]["consent"], "explicit");
+        assert_eq!(payload["privacy"]["purposes"][0], "wellness");
+        assert_eq!(payload["privacy"]["embedding_allowed"], false);
+        assert_eq!(payload["privacy"]["contains_pii"], false);
+    }
+
+    #[test]
+    fn test_scrubbed_session_matches_unscrubbed_readings() {
+        use crate::privacy::{ScrubMode, Scrubber};
+
+        let policy = PrivacyPolicy::default().with_scrubber(Scrubber::new(ScrubMode::Drop));
+        let mut scrubbed = BehaviorProcessor::new().with_privacy_policy(policy);
+        let mut plain = BehaviorProcessor::new();
+
+        let a: serde_json::Value =
+            serde_json::from_str(&scrubbed.process(sample_behavior_session_json()).unwrap())
+                .unwrap();
+        let b: serde_json::Value =
+            serde_json::from_str(&plain.process(sample_behavior_session_json()).unwrap()).unwrap();
+        assert_eq!(a["axes"], b["axes"]);
+    }
+
+    #[test]
+    fn test_compact_encoding() {
+        let mut processor = BehaviorProcessor::new()
+            .with_encoder_config(EncoderConfig::default().with_compact(true));
+        let result = processor.process(sample_behavior_session_json()).unwrap();
+        assert!(!result.contains('\n'));
+
+        let payload: serde_json::Value = serde_json::from_str(&result).unwrap();
+        for reading in payload["axes"]["behavior"]["readings"].as_array().unwrap() {
+            assert!(reading.get("notes").is_none());
+            assert!(reading.get("evidence_source_ids").is_none());
+        }
+        assert!(payload["meta"].get("typing_metrics").is_none());
+        assert!(payload["meta"].get("session_id").is_some());
+    }
+
+    #[test]
+    fn test_observer_sees_sessions_and_errors() {
+        use crate::observer::RecordingObserver;
+
+        let observer = Arc::new(RecordingObserver::default());
+        let mut processor = BehaviorProcessor::new().with_observer(observer.clone());
+        processor.process(sample_behavior_session_json()).unwrap();
+        assert_eq!(observer.count("Behavior processed sess-123-abc"), 1);
+        assert_eq!(observer.count("Behavior parsed"), 1);
+
+        assert!(processor.process("not json").is_err());
+        assert_eq!(observer.count("Behavior error"), 1);
+        assert_eq!(observer.count("Behavior processed"), 1);
+    }
+
+    #[cfg(feature = "integrity")]
+    #[test]
+    fn test_integrity_sealed_output_verifies() {
+        use crate::integrity::{verify_json, IntegrityConfig};
+
+        let config = IntegrityConfig::signed("device-key", &[3; 32]);
+        let public_key = config.verifying_key().unwrap();
+        let mut processor = BehaviorProcessor::new().with_integrity(config);
+        let result = processor.process(sample_behavior_session_json()).unwrap();
+
+        let integrity = verify_json(&result, Some(&public_key)).unwrap();
+        assert_eq!(integrity.key_id.as_deref(), Some("device-key"));
+
+        let tampered = result.replacen("\"sessions_in_baseline\"", "\"sessions_in_baseline_x\"", 1);
+        assert!(verify_json(&tampered, Some(&public_key)).is_err());
+    }
+
+    #[cfg(feature = "integrity")]
+    #[test]
+    fn test_typed_payload_is_sealed() {
+        use crate::integrity::{verify_json, IntegrityConfig};
+
+        let mut processor = BehaviorProcessor::new().with_integrity(IntegrityConfig::hash_only());
+        let payload = processor
+            .process_payload(sample_behavior_session_json())
+            .unwrap();
+        assert!(payload.meta.as_ref().unwrap().contains_key("integrity"));
+        verify_json(&serde_json::to_string(&payload).unwrap(), None).unwrap();
+
+        let unsealed = behavior_to_hsi_payload(sample_behavior_session_json()).unwrap();
+        assert!(!unsealed
+            .meta
+            .is_some_and(|meta| meta.contains_key("integrity")));
+        assert_eq!(unsealed.window_ids.len(), 1);
+    }
+
+    /// An event of any kind at `offset_ms` after the session start
+    fn arbitrary_event(kind: u8, offset_ms: i64, magnitude: f64) -> serde_json::Value {
+        use serde_json::json;
+        let timestamp = (chrono::DateTime::<chrono::Utc>::UNIX_EPOCH
+            + chrono::Duration::milliseconds(offset_ms))
+        .to_rfc3339();
+        match kind % 10 {
+            0 => json!({ "timestamp": timestamp, "event_type": "scroll",
+                "scroll": { "velocity": magnitude, "direction": "up", "direction_reversal": magnitude > 500.0 } }),
+            1 => json!({ "timestamp": timestamp, "event_type": "tap",
+                "tap": { "tap_duration_ms": magnitude as u32, "long_press": false } }),
+            2 => json!({ "timestamp": timestamp, "event_type": "swipe",
+                "swipe": { "direction": "left", "velocity": magnitude } }),
+            3 => json!({ "timestamp": timestamp, "event_type": "notification",
+                "interruption": { "action": "opened" } }),
+            4 => json!({ "timestamp": timestamp, "event_type": "call",
+                "interruption": { "action": "ignored" } }),
+            5 => json!({ "timestamp": timestamp, "event_type": "typing",
+                "typing": { "typing_speed_cpm": magnitude, "cadence_stability": magnitude / 1000.0, "duration_sec": magnitude / 10.0 } }),
+            6 => json!({ "timestamp": timestamp, "event_type": "app_switch",
+                "app_switch": { "from_app_id": "a", "to_app_id": "b" } }),
+            7 => json!({ "timestamp": timestamp, "event_type": "screen_on" }),
+            8 => json!({ "timestamp": timestamp, "event_type": "screen_off" }),
+            _ => json!({ "timestamp": timestamp, "event_type": "unlock" }),
+        }
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::test_runner::Config::with_cases(64))]
+
+        #[test]
+        fn prop_hsi_readings_are_bounded(
+            duration_ms in proptest::prop_oneof![proptest::strategy::Just(0i64), 0i64..7_200_000],
+            events in proptest::collection::vec(
+                (0u8..10, 0.0..=1.0f64, 0.0..1_000.0f64),
+                0..80,
+            ),
+        ) {
+            let events: Vec<serde_json::Value> = events
+                .iter()
+                .map(|(kind, at, magnitude)| {
+                    arbitrary_event(*kind, (*at * duration_ms as f64) as i64, *magnitude)
+                })
+                .collect();
+            let session = serde_json::json!({
+                "session_id": "prop",
+                "device_id": "device",
+                "start_time": chrono::DateTime::<chrono::Utc>::UNIX_EPOCH.to_rfc3339(),
+                "end_time": (chrono::DateTime::<chrono::Utc>::UNIX_EPOCH
+                    + chrono::Duration::milliseconds(duration_ms)).to_rfc3339(),
+                "events": events,
+            });
+
+            let mut processor = BehaviorProcessor::new();
+            for _ in 0..2 {
+                let output = processor.process(&session.to_string()).unwrap();
+                let payload: crate::behavior::types::HsiPayload =
+                    serde_json::from_str(&output).unwrap();
+
+                let axes = payload.axes.unwrap();
+                let mut scores = std::collections::HashMap::new();
+                for domain in [&axes.behavior, &axes.engagement, &axes.affect]
+                    .into_iter()
+                    .flatten()
+                {
+                    for reading in &domain.readings {
+                        proptest::prop_assert!((0.0..=1.0).contains(&reading.confidence));
+                        if let Some(score) = reading.score {
+                            proptest::prop_assert!(
+                                (0.0..=1.0).contains(&score),
+                                "{} = {}", reading.axis, score
+                            );
+                            scores.insert(reading.axis.clone(), score);
+                        }
+                    }
+                }
+                proptest::prop_assert!(
+                    (scores["focus"] + scores["distraction"] - 1.0).abs() < 1e-9
+                );
+
+                let meta = payload.meta.unwrap();
+                for value in meta.values().filter_map(serde_json::Value::as_f64) {
+                    proptest::prop_assert!(value.is_finite());
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,180 @@
+//! Incremental behavioral sessions
+//!
+//! [`BehaviorSessionBuilder`] collects events one at a time as they happen on
+//! the device, so hosts can read live metrics for the in-progress session and
+//! finalize it without assembling and re-parsing a full session JSON.
+
+use crate::behavior::adapter::session_to_canonical_with_config;
+use crate::behavior::config::BehaviorConfig;
+use crate::behavior::features::BehaviorFeatureDeriver;
+use crate::behavior::normalizer::BehaviorNormalizer;
+use crate::behavior::types::{BehaviorEvent, BehaviorSession, DerivedBehaviorSignals};
+use crate::error::ComputeError;
+use chrono::{DateTime, Utc};
+
+/// Builder accumulating behavioral events for a session in progress
+#[derive(Debug, Clone)]
+pub struct BehaviorSessionBuilder {
+    session_id: String,
+    device_id: String,
+    timezone: String,
+    start_time: DateTime<Utc>,
+    events: Vec<BehaviorEvent>,
+}
+
+impl BehaviorSessionBuilder {
+    /// Start a session (timezone defaults to UTC)
+    pub fn new(
+        session_id: impl Into<String>,
+        device_id: impl Into<String>,
+        start_time: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            session_id: session_id.into(),
+            device_id: device_id.into(),
+            timezone: "UTC".to_string(),
+            start_time,
+            events: Vec::new(),
+        }
+    }
+
+    /// Set the user's timezone
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = timezone.into();
+        self
+    }
+
+    /// Add an event to the session
+    pub fn push_event(&mut self, event: BehaviorEvent) {
+        self.events.push(event);
+    }
+
+    /// Parse and add a single event JSON object
+    pub fn push_event_json(&mut self, json: &str) -> Result<(), ComputeError> {
+        let event = serde_json::from_str(json).map_err(|e| {
+            ComputeError::ParseError(format!("Failed to parse behavioral event: {e}"))
+        })?;
+        self.push_event(event);
+        Ok(())
+    }
+
+    /// Number of events collected so far
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Session start time
+    pub fn start_time(&self) -> DateTime<Utc> {
+        self.start_time
+    }
+
+    /// Derive metrics for the session as if it ended at `as_of`
+    pub fn partial_snapshot(
+        &self,
+        as_of: DateTime<Utc>,
+    ) -> Result<DerivedBehaviorSignals, ComputeError> {
+        self.partial_snapshot_with_config(as_of, &BehaviorConfig::default())
+    }
+
+    /// Like [`Self::partial_snapshot`], using the given thresholds
+    pub fn partial_snapshot_with_config(
+        &self,
+        as_of: DateTime<Utc>,
+        config: &BehaviorConfig,
+    ) -> Result<DerivedBehaviorSignals, ComputeError> {
+        let session = self.to_session(as_of);
+        let canonical = session_to_canonical_with_config(&session, config)?;
+        let normalized = BehaviorNormalizer::normalize(canonical);
+        Ok(BehaviorFeatureDeriver::derive_with_config(
+            normalized, config,
+        ))
+    }
+
+    /// Close the session at `end_time`
+    pub fn finalize(self, end_time: DateTime<Utc>) -> Result<BehaviorSession, ComputeError> {
+        if end_time <= self.start_time {
+            return Err(ComputeError::ParseError(
+                "Session end time must be after start time".to_string(),
+            ));
+        }
+        Ok(BehaviorSession {
+            session_id: self.session_id,
+            device_id: self.device_id,
+            timezone: self.timezone,
+            start_time: self.start_time,
+            end_time,
+            events: self.events,
+        })
+    }
+
+    /// The in-progress session ending at `end_time`
+    pub(crate) fn to_session(&self, end_time: DateTime<Utc>) -> BehaviorSession {
+        BehaviorSession {
+            session_id: self.session_id.clone(),
+            device_id: self.device_id.clone(),
+            timezone: self.timezone.clone(),
+            start_time: self.start_time,
+            end_time,
+            events: self.events.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior::adapter::parse_session;
+    use chrono::TimeZone;
+
+    fn event(minute: u32, json: &str) -> String {
+        format!(r#"{{"timestamp":"2024-01-15T14:{minute:02}:00Z",{json}}}"#)
+    }
+
+    fn builder() -> BehaviorSessionBuilder {
+        let start = Utc.with_ymd_and_hms(2024, 1, 15, 14, 0, 0).unwrap();
+        let mut builder = BehaviorSessionBuilder::new("sess-1", "device-1", start);
+        for (minute, body) in [
+            (
+                1,
+                r#""event_type":"scroll","scroll":{"velocity":120.0,"direction":"down"}"#,
+            ),
+            (2, r#""event_type":"tap","tap":{"tap_duration_ms":100}"#),
+            (
+                5,
+                r#""event_type":"notification","interruption":{"action":"opened"}"#,
+            ),
+            (
+                6,
+                r#""event_type":"app_switch","app_switch":{"from_app_id":"a","to_app_id":"b"}"#,
+            ),
+        ] {
+            builder.push_event_json(&event(minute, body)).unwrap();
+        }
+        builder
+    }
+
+    #[test]
+    fn test_partial_snapshot_and_finalize() {
+        let builder = builder();
+        assert_eq!(builder.event_count(), 4);
+
+        let as_of = Utc.with_ymd_and_hms(2024, 1, 15, 14, 10, 0).unwrap();
+        let partial = builder.partial_snapshot(as_of).unwrap();
+        assert_eq!(partial.normalized.canonical.total_events, 4);
+        assert_eq!(partial.normalized.canonical.duration_sec, 600.0);
+
+        let end = Utc.with_ymd_and_hms(2024, 1, 15, 14, 30, 0).unwrap();
+        let session = builder.finalize(end).unwrap();
+        let json = serde_json::to_string(&session).unwrap();
+        let reparsed = parse_session(&json).unwrap();
+        assert_eq!(reparsed.events.len(), 4);
+        assert_eq!(reparsed.end_time, end);
+    }
+
+    #[test]
+    fn test_finalize_rejects_early_end() {
+        let builder = builder();
+        let start = builder.start_time();
+        assert!(builder.finalize(start).is_err());
+    }
+}
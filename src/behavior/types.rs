@@ -17,6 +17,40 @@ pub enum BehaviorEventType {
     Call,
     Typing,
     AppSwitch,
+    /// Display turned on
+    ScreenOn,
+    /// Display turned off
+    ScreenOff,
+    /// Device unlocked
+    Unlock,
+}
+
+impl BehaviorEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BehaviorEventType::Scroll => "scroll",
+            BehaviorEventType::Tap => "tap",
+            BehaviorEventType::Swipe => "swipe",
+            BehaviorEventType::Notification => "notification",
+            BehaviorEventType::Call => "call",
+            BehaviorEventType::Typing => "typing",
+            BehaviorEventType::AppSwitch => "app_switch",
+            BehaviorEventType::ScreenOn => "screen_on",
+            BehaviorEventType::ScreenOff => "screen_off",
+            BehaviorEventType::Unlock => "unlock",
+        }
+    }
+
+    /// Whether this event describes screen state rather than an interaction.
+    ///
+    /// Screen state events feed [`ScreenActivity`] and are excluded from
+    /// interaction counts, gaps and idle/engagement detection.
+    pub fn is_screen_state(&self) -> bool {
+        matches!(
+            self,
+            BehaviorEventType::ScreenOn | BehaviorEventType::ScreenOff | BehaviorEventType::Unlock
+        )
+    }
 }
 
 /// Scroll direction
@@ -245,6 +279,50 @@ pub struct EngagementSegment {
     pub event_count: u32,
 }
 
+/// Run of consecutive same-direction scroll events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrollSegment {
+    /// First scroll event of the run
+    pub start: DateTime<Utc>,
+    /// Last scroll event of the run
+    pub end: DateTime<Utc>,
+    /// Duration in seconds
+    pub duration_sec: f64,
+    /// Number of scroll events in the run
+    pub event_count: u32,
+    /// Mean scroll velocity in pixels per second; None when no event reports one
+    pub mean_velocity: Option<f64>,
+}
+
+/// Screen state activity extracted from screen-on/off and unlock events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScreenActivity {
+    /// Number of screen-on events
+    pub screen_on_events: u32,
+    /// Number of screen-off events
+    pub screen_off_events: u32,
+    /// Number of unlock events
+    pub unlock_events: u32,
+    /// Durations of screen-on periods in seconds, in session order
+    pub screen_sessions_sec: Vec<f64>,
+}
+
+impl ScreenActivity {
+    /// Whether the producer reported any screen state events
+    pub fn has_events(&self) -> bool {
+        self.screen_on_events + self.screen_off_events + self.unlock_events > 0
+    }
+}
+
+/// Foreground time of one app, from the app switch sequence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppUsage {
+    /// App identifier as received (a pseudonym when the session was scrubbed)
+    pub app_id: String,
+    /// Seconds in the foreground
+    pub duration_sec: f64,
+}
+
 /// Canonical behavioral signals extracted from a session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanonicalBehaviorSignals {
@@ -262,7 +340,7 @@ pub struct CanonicalBehaviorSignals {
     pub duration_sec: f64,
 
     // Event counts by type
-    /// Total number of events
+    /// Total number of interaction events (screen state events are in `screen`)
     pub total_events: u32,
     /// Number of scroll events
     pub scroll_events: u32,
@@ -283,6 +361,23 @@ pub struct CanonicalBehaviorSignals {
     /// Number of scroll direction reversals
     pub scroll_direction_reversals: u32,
 
+    /// Number of tap events that were long presses
+    #[serde(default)]
+    pub long_press_events: u32,
+
+    /// Number of events outside the session or in the future, before the
+    /// out-of-bounds policy was applied
+    #[serde(default)]
+    pub out_of_bounds_events: u32,
+
+    /// Number of typing sessions merged into an overlapping or duplicate one
+    #[serde(default)]
+    pub overlapping_typing_sessions: u32,
+
+    /// The session timezone was not a known IANA name, so UTC was used
+    #[serde(default)]
+    pub invalid_timezone: bool,
+
     // Typing metrics
     /// Total typing duration in seconds
     pub total_typing_duration_sec: f64,
@@ -304,6 +399,29 @@ pub struct CanonicalBehaviorSignals {
     /// Inter-event gaps in seconds (for burstiness calculation)
     pub inter_event_gaps: Vec<f64>,
 
+    /// Screen-on/off and unlock activity
+    #[serde(default)]
+    pub screen: ScreenActivity,
+
+    /// Number of interaction events between 23:00 and 05:00 in the session timezone
+    #[serde(default)]
+    pub late_night_events: u32,
+
+    /// Timestamps of phone-check events (unlocks, app switches, opened notifications), sorted
+    #[serde(default)]
+    pub check_event_times: Vec<DateTime<Utc>>,
+
+    /// Foreground time per app, longest first
+    #[serde(default)]
+    pub app_usage: Vec<AppUsage>,
+
+    /// Absolute scroll velocities in pixels per second, in event order
+    #[serde(default)]
+    pub scroll_velocities: Vec<f64>,
+    /// Runs of consecutive same-direction scroll events
+    #[serde(default)]
+    pub scroll_segments: Vec<ScrollSegment>,
+
     /// When the canonical signals were computed
     pub computed_at: DateTime<Utc>,
 }
@@ -343,6 +461,27 @@ pub enum BehaviorQualityFlag {
     LowEventDiversity,
     /// Session has gaps suggesting device was off
     SessionGaps,
+    /// Events fell outside the session or in the future
+    EventsOutOfBounds,
+    /// Typing sessions overlapped and were merged
+    OverlappingTypingSessions,
+    /// The session timezone was unknown and UTC was used
+    InvalidTimezone,
+}
+
+impl BehaviorQualityFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BehaviorQualityFlag::ShortSession => "short_session",
+            BehaviorQualityFlag::LowEventCount => "low_event_count",
+            BehaviorQualityFlag::HighIdleRatio => "high_idle_ratio",
+            BehaviorQualityFlag::LowEventDiversity => "low_event_diversity",
+            BehaviorQualityFlag::SessionGaps => "session_gaps",
+            BehaviorQualityFlag::EventsOutOfBounds => "events_out_of_bounds",
+            BehaviorQualityFlag::OverlappingTypingSessions => "overlapping_typing_sessions",
+            BehaviorQualityFlag::InvalidTimezone => "invalid_timezone",
+        }
+    }
 }
 
 /// Normalized behavioral signals with rates per minute and quality metrics
@@ -364,6 +503,9 @@ pub struct NormalizedBehaviorSignals {
     pub notifications_per_min: f64,
     /// App switches per minute
     pub app_switches_per_min: f64,
+    /// Unlocks per minute
+    #[serde(default)]
+    pub unlocks_per_min: f64,
 
     // Quality metrics
     /// Data coverage (0-1, based on event diversity)
@@ -400,6 +542,75 @@ pub struct DerivedBehaviorSignals {
     /// Interaction intensity ((events + typing_duration/10) / session_duration)
     pub interaction_intensity: f64,
 
+    // Screen state metrics
+    /// Unlocks per hour
+    #[serde(default)]
+    pub unlock_frequency: f64,
+    /// Average screen-on period length in seconds (0 when none observed)
+    #[serde(default)]
+    pub avg_screen_session_sec: f64,
+    /// Checking-habit score (0-1, exponential saturation of short screen sessions per hour)
+    #[serde(default)]
+    pub checking_habit_score: f64,
+
+    // Engagement metrics
+    /// Fraction of the session spent in deep focus blocks (0-1)
+    #[serde(default)]
+    pub deep_focus_coverage: f64,
+    /// Engagement stability (0-1, `1 / (1 + CV)` of engagement segment lengths);
+    /// None without engagement segments
+    #[serde(default)]
+    pub engagement_stability: Option<f64>,
+
+    /// Fraction of interaction events between 23:00 and 05:00 local time (0-1)
+    #[serde(default)]
+    pub late_night_usage_ratio: f64,
+
+    /// Shannon entropy of foreground time per app, normalized by the number
+    /// of apps (0-1); None without app switch data
+    #[serde(default)]
+    pub app_diversity_entropy: Option<f64>,
+
+    // Gesture metrics
+    /// Share of taps that were long presses; None without taps
+    #[serde(default)]
+    pub long_press_rate: Option<f64>,
+    /// Swipes per tap; None without taps
+    #[serde(default)]
+    pub swipe_tap_ratio: Option<f64>,
+    /// Normalized entropy of the gesture mix (taps, long presses, swipes,
+    /// scrolls; 0-1); None without gestures
+    #[serde(default)]
+    pub gesture_complexity: Option<f64>,
+
+    // Scroll metrics
+    /// Median scroll velocity in pixels per second; None without velocities
+    #[serde(default)]
+    pub scroll_velocity_median: Option<f64>,
+    /// 90th percentile scroll velocity in pixels per second; None without velocities
+    #[serde(default)]
+    pub scroll_velocity_p90: Option<f64>,
+    /// Number of doomscroll segments (sustained fast same-direction scrolling)
+    #[serde(default)]
+    pub doomscroll_segments: u32,
+    /// Share of scroll segments that are doomscroll segments; None without scrolling
+    #[serde(default)]
+    pub doomscroll_segment_ratio: Option<f64>,
+    /// Fraction of the session spent in doomscroll segments (0-1)
+    #[serde(default)]
+    pub doomscroll_score: f64,
+
+    // Check burst metrics
+    /// Phone checks per hour (clusters of check events count once)
+    #[serde(default)]
+    pub check_frequency_per_hour: f64,
+    /// Number of check bursts (clusters of 3+ check events)
+    #[serde(default)]
+    pub check_bursts: u32,
+    /// Compulsive check score (0-1, exponential saturation of bursts per hour)
+    #[serde(default)]
+    pub compulsive_check_score: f64,
+
     // Composite scores
     /// Distraction score (weighted combination, 0-1)
     pub distraction_score: f64,
@@ -418,6 +629,9 @@ pub struct BehaviorBaselines {
     pub burstiness_baseline: Option<f64>,
     /// Baseline interaction intensity
     pub intensity_baseline: Option<f64>,
+    /// Baseline late-night usage ratio
+    #[serde(default)]
+    pub late_night_baseline: Option<f64>,
     /// Number of sessions in the baseline
     pub sessions_in_baseline: u32,
 }
@@ -433,6 +647,9 @@ pub struct ContextualBehaviorSignals {
     pub distraction_deviation_pct: Option<f64>,
     /// Focus deviation from baseline (percentage)
     pub focus_deviation_pct: Option<f64>,
+    /// Distraction change across the baseline sessions (-1 to 1)
+    #[serde(default)]
+    pub distraction_trend: Option<f64>,
 }
 
 // ============================================================================
@@ -537,6 +754,31 @@ pub struct HsiAxes {
     /// Behavior domain readings
     #[serde(skip_serializing_if = "Option::is_none")]
     pub behavior: Option<HsiAxesDomain>,
+    /// Sleep domain readings (daily payloads converted to axes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleep: Option<HsiAxesDomain>,
+    /// Physiology domain readings (daily payloads converted to axes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physiology: Option<HsiAxesDomain>,
+    /// Activity domain readings (daily payloads converted to axes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<HsiAxesDomain>,
+}
+
+impl HsiAxes {
+    /// Present domains, mutably
+    pub fn domains_mut(&mut self) -> impl Iterator<Item = &mut HsiAxesDomain> {
+        [
+            &mut self.affect,
+            &mut self.engagement,
+            &mut self.behavior,
+            &mut self.sleep,
+            &mut self.physiology,
+            &mut self.activity,
+        ]
+        .into_iter()
+        .flatten()
+    }
 }
 
 /// HSI 1.0 source definition
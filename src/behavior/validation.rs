@@ -0,0 +1,190 @@
+//! Behavioral session validation
+//!
+//! The pipeline tolerates a lot: it sorts out-of-order events, clamps events
+//! outside the session and ignores payloads it does not expect. Validation
+//! reports those problems instead, so producers can fix them at the source.
+//! Every violation is reported, not just the first.
+
+use crate::behavior::types::{BehaviorEventType, BehaviorSession};
+use crate::error::ComputeError;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// A single problem found in a behavioral session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionViolation {
+    /// Path of the offending field (e.g. `events[3].timestamp`)
+    pub path: String,
+    /// Human-readable description of the violation
+    pub message: String,
+}
+
+/// Validate a behavioral session given as a JSON string.
+///
+/// Returns an error only if the input is not a well-formed session.
+pub fn validate_session_json(json: &str) -> Result<Vec<SessionViolation>, ComputeError> {
+    let session: BehaviorSession = serde_json::from_str(json)?;
+    Ok(session.validate())
+}
+
+/// Payload fields of a behavioral event, by field name
+const PAYLOAD_FIELDS: [&str; 6] = [
+    "scroll",
+    "tap",
+    "swipe",
+    "interruption",
+    "typing",
+    "app_switch",
+];
+
+/// The payload field an event type carries, if any
+fn payload_field(event_type: BehaviorEventType) -> Option<&'static str> {
+    match event_type {
+        BehaviorEventType::Scroll => Some("scroll"),
+        BehaviorEventType::Tap => Some("tap"),
+        BehaviorEventType::Swipe => Some("swipe"),
+        BehaviorEventType::Notification | BehaviorEventType::Call => Some("interruption"),
+        BehaviorEventType::Typing => Some("typing"),
+        BehaviorEventType::AppSwitch => Some("app_switch"),
+        BehaviorEventType::ScreenOn | BehaviorEventType::ScreenOff | BehaviorEventType::Unlock => {
+            None
+        }
+    }
+}
+
+impl BehaviorSession {
+    /// Check the session and return every violation found.
+    ///
+    /// Checks that identifiers are set, the timezone is known, the session
+    /// does not end before it starts, event timestamps are in order and
+    /// within the session, and each event carries exactly the payload its
+    /// type calls for.
+    pub fn validate(&self) -> Vec<SessionViolation> {
+        let mut violations = Vec::new();
+        let mut push = |path: String, message: String| {
+            violations.push(SessionViolation { path, message });
+        };
+
+        for (path, value) in [
+            ("session_id", &self.session_id),
+            ("device_id", &self.device_id),
+        ] {
+            if value.trim().is_empty() {
+                push(path.to_string(), "must not be empty".to_string());
+            }
+        }
+        if self.timezone.parse::<Tz>().is_err() {
+            push(
+                "timezone".to_string(),
+                format!("'{}' is not an IANA timezone", self.timezone),
+            );
+        }
+        if self.end_time < self.start_time {
+            push(
+                "end_time".to_string(),
+                "must not be before start_time".to_string(),
+            );
+        }
+
+        for (i, event) in self.events.iter().enumerate() {
+            let prefix = format!("events[{i}]");
+            if event.timestamp < self.start_time || event.timestamp > self.end_time {
+                push(
+                    format!("{prefix}.timestamp"),
+                    format!(
+                        "{} is outside the session ({} to {})",
+                        event.timestamp.to_rfc3339(),
+                        self.start_time.to_rfc3339(),
+                        self.end_time.to_rfc3339()
+                    ),
+                );
+            }
+            if let Some(previous) = i.checked_sub(1).map(|p| &self.events[p]) {
+                if event.timestamp < previous.timestamp {
+                    push(
+                        format!("{prefix}.timestamp"),
+                        "is earlier than the previous event".to_string(),
+                    );
+                }
+            }
+
+            let expected = payload_field(event.event_type);
+            let present = [
+                event.scroll.is_some(),
+                event.tap.is_some(),
+                event.swipe.is_some(),
+                event.interruption.is_some(),
+                event.typing.is_some(),
+                event.app_switch.is_some(),
+            ];
+            let event_type = event.event_type.as_str();
+            for (field, present) in PAYLOAD_FIELDS.into_iter().zip(present) {
+                if present && Some(field) != expected {
+                    push(
+                        format!("{prefix}.{field}"),
+                        format!("unexpected on a {event_type} event"),
+                    );
+                } else if !present && Some(field) == expected {
+                    push(
+                        format!("{prefix}.{field}"),
+                        format!("required on a {event_type} event"),
+                    );
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_json(events: &str) -> String {
+        format!(
+            r#"{{
+                "session_id": "sess-1",
+                "device_id": "device-1",
+                "timezone": "UTC",
+                "start_time": "2024-01-15T14:00:00Z",
+                "end_time": "2024-01-15T14:30:00Z",
+                "events": [{events}]
+            }}"#
+        )
+    }
+
+    #[test]
+    fn test_valid_session() {
+        let json = session_json(
+            r#"{"timestamp": "2024-01-15T14:01:00Z", "event_type": "scroll", "scroll": {"velocity": 100.0}},
+               {"timestamp": "2024-01-15T14:02:00Z", "event_type": "unlock"},
+               {"timestamp": "2024-01-15T14:03:00Z", "event_type": "call", "interruption": {"action": "ignored"}}"#,
+        );
+        assert_eq!(validate_session_json(&json).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_reports_every_violation() {
+        let json = session_json(
+            r#"{"timestamp": "2024-01-15T14:05:00Z", "event_type": "tap", "scroll": {"velocity": 100.0}},
+               {"timestamp": "2024-01-15T14:04:00Z", "event_type": "screen_on"},
+               {"timestamp": "2024-01-15T15:00:00Z", "event_type": "typing", "typing": {}}"#,
+        );
+        let mut session: BehaviorSession = serde_json::from_str(&json).unwrap();
+        session.timezone = "Mars/Olympus".to_string();
+
+        let paths: Vec<String> = session.validate().into_iter().map(|v| v.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "timezone",
+                "events[0].scroll",
+                "events[0].tap",
+                "events[1].timestamp",
+                "events[2].timestamp",
+            ]
+        );
+        assert!(validate_session_json("{}").is_err());
+    }
+}
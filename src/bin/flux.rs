@@ -3,19 +3,60 @@
 //! Commands:
 //! - transform: Process raw events into HSI output (batch mode)
 //! - run: Process streaming input from stdin (streaming mode)
+//! - backfill: Process historical events day by day over a date range
 //! - validate: Validate raw event schema
+//! - validate-hsi: Validate produced HSI payloads
 //! - doctor: Diagnose pipeline health and configuration
-
-use clap::{Parser, Subcommand, ValueEnum};
+//! - behavior transform: Process behavioral sessions into HSI output
+//! - behavior validate: Validate behavioral sessions
+//! - generate: Produce synthetic input data for tests and demos
+//! - conformance: Check golden fixtures against the pipeline
+//! - diff: Compare two sets of HSI output window by window
+//! - migrate-check: Replay a fixture on a migrated baseline store
+//! - rollup: Summarize HSI daily output by week or month
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{self, BufRead, Read, Write};
+use std::io::{self, BufRead, Read, Seek, Write};
 use std::path::PathBuf;
 use std::process::ExitCode;
-
-use synheart_flux::pipeline::FluxProcessor;
-use synheart_flux::schema::{RawEvent, RawEventAdapter, SCHEMA_VERSION};
-use synheart_flux::types::HsiPayload;
-use synheart_flux::{FLUX_VERSION, PRODUCER_NAME};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use synheart_flux::adapters::{detect_vendor, GarminAdapter, VendorPayloadAdapter, WhoopAdapter};
+use synheart_flux::baseline::BaselineStore;
+use synheart_flux::behavior::config::{
+    DEFAULT_DEEP_FOCUS_MIN_DURATION_SEC, DEFAULT_DOOMSCROLL_MIN_DURATION_SEC,
+    DEFAULT_DOOMSCROLL_MIN_VELOCITY, DEFAULT_IDLE_GAP_THRESHOLD_SEC,
+    DEFAULT_MIN_ENGAGEMENT_DURATION_SEC,
+};
+use synheart_flux::behavior::{
+    BehaviorConfig, BehaviorProcessor, BehaviorSession, OutOfBoundsPolicy, SessionViolation,
+};
+use synheart_flux::conformance::{self, CompareConfig, ConformanceConfig, Fixture};
+use synheart_flux::diff::{self, ChangeKind};
+use synheart_flux::encoder::{EncoderConfig, OutputProfile, SourceEvents};
+use synheart_flux::explain::TracedPayload;
+use synheart_flux::interop::{binary, fhir};
+use synheart_flux::observer::{PipelineKind, PipelineObserver, ProcessedWindow, ProgressCounters};
+use synheart_flux::pipeline::{DeviceFusion, FluxProcessor};
+use synheart_flux::privacy::{PrivacyPolicy, ScrubMode, Scrubber};
+use synheart_flux::report::{CheckStatus, DoctorCheck, DoctorReport, ValidationReport};
+use synheart_flux::rollup::{self, RollupPeriod};
+use synheart_flux::schema::{
+    DisorderPolicy, DroppedEvent, OrderingConfig, ParseMode, ParsedEvents, RawEvent,
+    RawEventAdapter, DEFAULT_MAX_OUT_OF_ORDER, SCHEMA_VERSION,
+};
+use synheart_flux::testkit::{Generator, SyntheticConfig};
+use synheart_flux::types::{CanonicalWearSignals, HsiPayload, Vendor};
+use synheart_flux::validation::validate_hsi;
+use synheart_flux::warnings::{ProcessOutput, Warning};
+use synheart_flux::watermark::{DayWatermark, LateEventPolicy};
+use synheart_flux::FLUX_VERSION;
 
 /// Flux - On-device compute engine for HSI-compliant human state signals
 #[derive(Parser)]
@@ -26,6 +67,55 @@ use synheart_flux::{FLUX_VERSION, PRODUCER_NAME};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Pipeline log level (logs go to stderr)
+    #[arg(long, global = true, value_enum, default_value = "off")]
+    log_level: LogLevel,
+
+    /// Emit logs as JSON lines (with span timings)
+    #[arg(long, global = true)]
+    log_json: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> tracing_subscriber::filter::LevelFilter {
+        use tracing_subscriber::filter::LevelFilter;
+        match self {
+            LogLevel::Off => LevelFilter::OFF,
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Install a stderr subscriber; closed spans are logged with their busy/idle time
+fn init_logging(level: LogLevel, json: bool) {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    if matches!(level, LogLevel::Off) {
+        return;
+    }
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level.filter())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(io::stderr);
+    if json {
+        builder.json().init();
+    } else {
+        builder.with_ansi(atty::is(atty::Stream::Stderr)).init();
+    }
 }
 
 #[derive(Subcommand)]
@@ -44,6 +134,10 @@ enum Commands {
         #[arg(long, default_value = "ndjson")]
         input_format: InputFormat,
 
+        /// What the input holds: raw events or a vendor export
+        #[arg(long, default_value = "raw-events")]
+        vendor: InputVendor,
+
         /// Output format
         #[arg(long, default_value = "ndjson")]
         output_format: OutputFormat,
@@ -67,6 +161,45 @@ enum Commands {
         /// Save baselines to file after processing
         #[arg(long)]
         save_baselines: Option<PathBuf>,
+
+        /// Merge same-day data from multiple devices into one window
+        #[arg(long)]
+        fuse_devices: bool,
+
+        /// Process (user, provider) partitions on N threads, each with its own
+        /// baselines; without --jobs one baseline store covers the whole input
+        #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
+
+        /// Dry run: emit {"hsi", "trace"} records explaining how each metric was computed
+        #[arg(long, conflicts_with_all = ["save_baselines", "jobs"])]
+        explain: bool,
+
+        /// Record progress in <OUTPUT>.resume and continue an interrupted run from it
+        #[arg(long, conflicts_with_all = ["explain", "jobs"])]
+        resume: bool,
+
+        /// Write the output of an intermediate stage instead of HSI
+        #[arg(long, value_name = "STAGE")]
+        stop_after: Option<Stage>,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+
+        #[command(flatten)]
+        order: OrderArgs,
+
+        #[command(flatten)]
+        determinism: DeterminismArgs,
+
+        #[command(flatten)]
+        scrub: ScrubArgs,
+
+        #[command(flatten)]
+        content: ContentArgs,
+
+        #[command(flatten)]
+        progress: ProgressArgs,
     },
 
     /// Process streaming input from stdin (streaming mode)
@@ -98,6 +231,93 @@ enum Commands {
         /// Flush output after each record
         #[arg(long, default_value = "true")]
         flush: bool,
+
+        /// Also save baselines at this interval while streaming (e.g. 30s, 5m, 1h)
+        #[arg(long, value_parser = parse_interval, requires = "save_baselines")]
+        checkpoint_interval: Option<Duration>,
+
+        /// Merge same-day data from multiple devices into one window
+        #[arg(long)]
+        fuse_devices: bool,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+
+        #[command(flatten)]
+        watermark: WatermarkArgs,
+
+        #[command(flatten)]
+        determinism: DeterminismArgs,
+
+        #[command(flatten)]
+        scrub: ScrubArgs,
+
+        #[command(flatten)]
+        content: ContentArgs,
+    },
+
+    /// Process historical events day by day over a date range
+    Backfill {
+        /// Input file path (use - for stdin)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output file path (use - for stdout)
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+
+        /// Input format
+        #[arg(long, default_value = "ndjson")]
+        input_format: InputFormat,
+
+        /// Output format
+        #[arg(long, default_value = "ndjson")]
+        output_format: OutputFormat,
+
+        /// First date to produce (YYYY-MM-DD, inclusive); earlier days only warm up baselines
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last date to produce (YYYY-MM-DD, inclusive)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// User timezone (IANA format, e.g., "America/New_York")
+        #[arg(long, default_value = "UTC")]
+        timezone: String,
+
+        /// Device ID for provenance tracking
+        #[arg(long, default_value = "unknown")]
+        device_id: String,
+
+        /// Baseline window in days
+        #[arg(long, default_value = "14")]
+        baseline_days: usize,
+
+        /// Load baselines from file
+        #[arg(long)]
+        load_baselines: Option<PathBuf>,
+
+        /// Save baselines to file after processing
+        #[arg(long)]
+        save_baselines: Option<PathBuf>,
+
+        /// Only report which days would be produced
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Merge same-day data from multiple devices into one window
+        #[arg(long)]
+        fuse_devices: bool,
+
+        #[command(flatten)]
+        determinism: DeterminismArgs,
+
+        #[command(flatten)]
+        scrub: ScrubArgs,
+
+        #[command(flatten)]
+        progress: ProgressArgs,
     },
 
     /// Validate raw event schema
@@ -115,293 +335,2313 @@ enum Commands {
         json: bool,
     },
 
+    /// Validate HSI output payloads
+    ValidateHsi {
+        /// Input file path (use - for stdin)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Input format
+        #[arg(long, default_value = "ndjson")]
+        input_format: InputFormat,
+
+        /// Output validation report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Diagnose pipeline health and configuration
     Doctor {
         /// Check baselines file
         #[arg(long)]
         baselines: Option<PathBuf>,
 
+        /// Run the full pipeline on a raw event NDJSON sample and report per-stage results
+        #[arg(long)]
+        sample_input: Option<PathBuf>,
+
+        /// Timezone for the sample run
+        #[arg(long, default_value = "UTC", requires = "sample_input")]
+        timezone: String,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
     },
 
-    /// Print schema information
-    Schema {
-        /// Schema to print (input or output)
-        #[arg(value_enum)]
-        schema_type: SchemaType,
+    /// Print one day's intermediate pipeline output as JSON
+    Inspect {
+        /// Input file path: raw events or a vendor export (use - for stdin)
+        #[arg(short, long)]
+        input: PathBuf,
 
-        /// Output as JSON schema
+        /// Input format (raw events)
+        #[arg(long, default_value = "ndjson")]
+        input_format: InputFormat,
+
+        /// What the input holds: raw events or a vendor export
+        #[arg(long, default_value = "auto")]
+        vendor: InputVendor,
+
+        /// Pipeline stage to print
         #[arg(long)]
-        json_schema: bool,
-    },
-}
+        stage: InspectStage,
 
-#[derive(Clone, ValueEnum)]
-enum InputFormat {
-    /// Newline-delimited JSON (one event per line)
-    Ndjson,
-    /// JSON array of events
-    Json,
-}
+        /// Day to inspect (needed when the input spans several days); earlier
+        /// days are processed first so baselines match a full run
+        #[arg(long)]
+        date: Option<NaiveDate>,
 
-#[derive(Clone, ValueEnum)]
-enum OutputFormat {
-    /// Newline-delimited JSON (one HSI record per line)
-    Ndjson,
-    /// JSON array of HSI records
-    Json,
-    /// Pretty-printed JSON
-    JsonPretty,
-}
+        /// User timezone (IANA format, e.g., "America/New_York")
+        #[arg(long, default_value = "UTC")]
+        timezone: String,
 
-#[derive(Clone, ValueEnum)]
-enum SchemaType {
-    /// Input schema (wear.raw_event.v1)
-    Input,
-    /// Output schema (hsi.snapshot.v1)
-    Output,
-}
+        /// Device ID for provenance tracking
+        #[arg(long, default_value = "unknown")]
+        device_id: String,
 
-fn main() -> ExitCode {
-    let cli = Cli::parse();
+        /// Baseline window in days
+        #[arg(long, default_value = "14")]
+        baseline_days: usize,
 
-    match run(cli) {
-        Ok(()) => ExitCode::SUCCESS,
-        Err(e) => {
-            eprintln!(
-                "{}",
-                serde_json::to_string(&CliError::from(e))
-                    .unwrap_or_else(|_| "Unknown error".to_string())
-            );
-            ExitCode::FAILURE
-        }
-    }
-}
+        /// Load baselines from file
+        #[arg(long)]
+        load_baselines: Option<PathBuf>,
+    },
 
-fn run(cli: Cli) -> Result<(), FluxCliError> {
-    match cli.command {
-        Commands::Transform {
-            input,
-            output,
-            input_format,
-            output_format,
-            timezone,
-            device_id,
-            baseline_days,
-            load_baselines,
-            save_baselines,
-        } => cmd_transform(
-            &input,
-            &output,
-            input_format,
-            output_format,
-            &timezone,
-            &device_id,
-            baseline_days,
-            load_baselines.as_deref(),
-            save_baselines.as_deref(),
-        ),
+    /// Process behavioral sessions
+    Behavior {
+        #[command(subcommand)]
+        command: BehaviorCommands,
+    },
 
-        Commands::Run {
-            output_format,
-            timezone,
-            device_id,
-            baseline_days,
-            load_baselines,
-            save_baselines,
-            flush,
-        } => cmd_run(
-            output_format,
-            &timezone,
-            &device_id,
-            baseline_days,
-            load_baselines.as_deref(),
-            save_baselines.as_deref(),
-            flush,
-        ),
+    /// Generate synthetic input data (no real user data involved)
+    Generate {
+        /// Kind of data to generate
+        #[arg(long, value_enum)]
+        kind: GenerateKind,
 
-        Commands::Validate {
-            input,
-            input_format,
-            json,
-        } => cmd_validate(&input, input_format, json),
+        /// Output file path (use - for stdout)
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
 
-        Commands::Doctor { baselines, json } => cmd_doctor(baselines.as_deref(), json),
+        /// Number of days (one behavioral session per day)
+        #[arg(long, default_value = "14")]
+        days: u32,
 
-        Commands::Schema {
-            schema_type,
-            json_schema,
-        } => cmd_schema(schema_type, json_schema),
-    }
-}
+        /// RNG seed; the same seed always produces the same data
+        #[arg(long, default_value = "0")]
+        seed: u64,
 
-fn cmd_transform(
-    input: &PathBuf,
-    output: &PathBuf,
-    input_format: InputFormat,
-    output_format: OutputFormat,
-    timezone: &str,
-    device_id: &str,
-    baseline_days: usize,
-    load_baselines: Option<&std::path::Path>,
-    save_baselines: Option<&std::path::Path>,
-) -> Result<(), FluxCliError> {
-    // Read input
-    let input_data = if input.to_string_lossy() == "-" {
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        buffer
-    } else {
-        fs::read_to_string(input)?
-    };
+        /// First generated date (YYYY-MM-DD)
+        #[arg(long, default_value = "2024-01-01")]
+        start_date: NaiveDate,
 
-    // Parse events
-    let events = match input_format {
-        InputFormat::Ndjson => RawEventAdapter::parse_ndjson(&input_data)?,
-        InputFormat::Json => RawEventAdapter::parse_array(&input_data)?,
-    };
+        /// Change in sleep quality (0-1) per day, e.g. -0.02 for a worsening trend
+        #[arg(long, default_value = "0.0", allow_negative_numbers = true)]
+        sleep_trend: f64,
 
-    if events.is_empty() {
-        return Err(FluxCliError::NoEvents);
-    }
+        /// Notifications per hour in behavioral sessions
+        #[arg(long, default_value = "6.0")]
+        notification_rate: f64,
 
-    // Convert to canonical signals
-    let canonical_signals = RawEventAdapter::to_canonical(&events, timezone, device_id)?;
+        /// Probability (0-1) that a daily record or session is missing
+        #[arg(long, default_value = "0.0")]
+        missing_prob: f64,
 
-    if canonical_signals.is_empty() {
-        return Err(FluxCliError::NoSignals);
-    }
+        /// Device ID stamped on raw events and sessions
+        #[arg(long, default_value = "synthetic-device")]
+        device_id: String,
 
-    // Create processor with baselines
-    let mut processor = FluxProcessor::with_baseline_window(baseline_days);
+        /// Provider name for raw events
+        #[arg(long, default_value = "whoop")]
+        provider: String,
+    },
 
-    // Load existing baselines if provided
+    /// Run golden input/expected-output fixtures and report differences
+    Conformance {
+        /// Fixture directory
+        #[arg(short, long)]
+        dir: PathBuf,
+
+        /// Maximum absolute difference between numbers considered equal
+        #[arg(long, default_value = "1e-9")]
+        tolerance: f64,
+
+        /// Additional JSON pointer paths to skip (repeatable)
+        #[arg(long)]
+        ignore: Vec<String>,
+
+        /// Overwrite expected files with the current output instead of comparing
+        #[arg(long)]
+        bless: bool,
+
+        /// Output report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Migrate a saved baseline store and check a fixture still reproduces its expected output
+    MigrateCheck {
+        /// Baseline store JSON saved by an earlier Flux version
+        #[arg(long)]
+        baselines: PathBuf,
+
+        /// Wearable fixture input (`<case>.raw.ndjson`, `.whoop.json` or `.garmin.json`)
+        /// with `<case>.expected.ndjson` next to it
+        #[arg(long)]
+        fixture: PathBuf,
+
+        /// Maximum absolute difference between numbers considered equal
+        #[arg(long, default_value = "1e-9")]
+        tolerance: f64,
+
+        /// Additional JSON pointer paths to skip (repeatable)
+        #[arg(long)]
+        ignore: Vec<String>,
+
+        /// Output report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compare two HSI NDJSON files window by window
+    Diff {
+        /// Baseline HSI output
+        before: PathBuf,
+
+        /// HSI output to compare against the baseline
+        after: PathBuf,
+
+        /// Maximum absolute difference between numbers considered equal
+        #[arg(long, default_value = "1e-6")]
+        tolerance: f64,
+
+        /// Additional JSON pointer paths to skip (repeatable)
+        #[arg(long)]
+        ignore: Vec<String>,
+
+        /// Output report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Summarize HSI daily output by week or month
+    Rollup {
+        /// HSI daily output (NDJSON, use - for stdin)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output file path (use - for stdout)
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+
+        /// Rollup period
+        #[arg(long, value_enum, default_value = "weekly")]
+        period: RollupPeriodArg,
+
+        /// First date to cover (YYYY-MM-DD, inclusive; default: first date in the input)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last date to cover (YYYY-MM-DD, inclusive; default: last date in the input)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+    },
+
+    /// Serve HTTP ingestion endpoints with per-user stateful processors
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+
+        /// Directory for per-user baseline files (kept in memory only if omitted)
+        #[arg(long)]
+        state_dir: Option<PathBuf>,
+
+        /// Seconds between baseline writes
+        #[arg(long, default_value = "60")]
+        persist_secs: u64,
+
+        /// Largest accepted request body in bytes
+        #[arg(long, default_value = "16777216")]
+        max_body_bytes: usize,
+
+        /// Wearable baseline window in days
+        #[arg(long, default_value = "14")]
+        baseline_days: usize,
+
+        /// Behavioral baseline window in sessions
+        #[arg(long, default_value = "20")]
+        baseline_sessions: usize,
+    },
+
+    /// Print schema information
+    Schema {
+        /// Schema to print (input or output)
+        #[arg(value_enum)]
+        schema_type: SchemaType,
+
+        /// Output as JSON schema
+        #[arg(long)]
+        json_schema: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BehaviorCommands {
+    /// Transform behavioral sessions into HSI output, in input order
+    Transform {
+        /// Input file path (use - for stdin)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output file path (use - for stdout)
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+
+        /// Input format (ndjson: one session per line; json: a session or an array of sessions)
+        #[arg(long, default_value = "ndjson")]
+        input_format: InputFormat,
+
+        /// Output format (fhir is not supported for behavioral payloads)
+        #[arg(long, default_value = "ndjson")]
+        output_format: OutputFormat,
+
+        /// Baseline window in sessions
+        #[arg(long, default_value = "20")]
+        baseline_sessions: usize,
+
+        /// Load behavior baselines from file
+        #[arg(long)]
+        load_behavior_baselines: Option<PathBuf>,
+
+        /// Save behavior baselines to file after processing
+        #[arg(long)]
+        save_behavior_baselines: Option<PathBuf>,
+
+        /// Attach bio context from wearable baselines saved by `transform --save-baselines`
+        #[arg(long)]
+        bio_baselines: Option<PathBuf>,
+
+        /// Naming of axes and meta keys (sdk-v1: names expected by v1 SDKs)
+        #[arg(long, value_enum, default_value = "flux")]
+        profile: ProfileArg,
+
+        #[command(flatten)]
+        config: BehaviorConfigArgs,
+
+        #[command(flatten)]
+        scrub: ScrubArgs,
+    },
+
+    /// Validate behavioral sessions
+    Validate {
+        /// Input file path (use - for stdin)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Input format (ndjson: one session per line; json: a session or an array of sessions)
+        #[arg(long, default_value = "ndjson")]
+        input_format: InputFormat,
+
+        /// Output validation report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print one session's intermediate pipeline output as JSON
+    Inspect {
+        /// Input file path (use - for stdin)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Input format (ndjson: one session per line; json: a session or an array of sessions)
+        #[arg(long, default_value = "ndjson")]
+        input_format: InputFormat,
+
+        /// Pipeline stage to print
+        #[arg(long)]
+        stage: InspectStage,
+
+        /// Session to inspect (needed when the input holds several); earlier
+        /// sessions are processed first so baselines match a full run
+        #[arg(long)]
+        session_id: Option<String>,
+
+        /// Baseline window in sessions
+        #[arg(long, default_value = "20")]
+        baseline_sessions: usize,
+
+        /// Load behavior baselines from file
+        #[arg(long)]
+        load_behavior_baselines: Option<PathBuf>,
+
+        #[command(flatten)]
+        config: BehaviorConfigArgs,
+    },
+}
+
+/// Behavioral pipeline thresholds
+#[derive(Args)]
+struct BehaviorConfigArgs {
+    /// Minimum gap between events (seconds) counted as idle
+    #[arg(long, default_value_t = DEFAULT_IDLE_GAP_THRESHOLD_SEC)]
+    idle_gap_sec: f64,
+
+    /// Minimum engagement segment duration (seconds)
+    #[arg(long, default_value_t = DEFAULT_MIN_ENGAGEMENT_DURATION_SEC)]
+    min_engagement_sec: f64,
+
+    /// Minimum engagement segment duration (seconds) for a deep focus block
+    #[arg(long, default_value_t = DEFAULT_DEEP_FOCUS_MIN_DURATION_SEC)]
+    deep_focus_sec: f64,
+
+    /// Minimum same-direction scroll run duration (seconds) for doomscrolling
+    #[arg(long, default_value_t = DEFAULT_DOOMSCROLL_MIN_DURATION_SEC)]
+    doomscroll_sec: f64,
+
+    /// Minimum mean scroll velocity (pixels per second) for doomscrolling
+    #[arg(long, default_value_t = DEFAULT_DOOMSCROLL_MIN_VELOCITY)]
+    doomscroll_velocity: f64,
+
+    /// Handling of events outside the session or in the future
+    #[arg(long, value_enum, default_value = "clamp")]
+    out_of_bounds: OutOfBoundsArg,
+}
+
+impl BehaviorConfigArgs {
+    fn to_config(&self) -> BehaviorConfig {
+        BehaviorConfig::default()
+            .with_idle_gap_threshold_sec(self.idle_gap_sec)
+            .with_min_engagement_duration_sec(self.min_engagement_sec)
+            .with_deep_focus_min_duration_sec(self.deep_focus_sec)
+            .with_doomscroll_min_duration_sec(self.doomscroll_sec)
+            .with_doomscroll_min_velocity(self.doomscroll_velocity)
+            .with_out_of_bounds(self.out_of_bounds.into())
+    }
+}
+
+/// Options for removing identifiers before encoding
+#[derive(Args)]
+struct ScrubArgs {
+    /// Hash (default) or drop user IDs, app identifiers and device serials
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "hash")]
+    scrub: Option<ScrubArg>,
+
+    /// Salt mixed into hashed pseudonyms
+    #[arg(long, default_value = "", requires = "scrub")]
+    scrub_salt: String,
+
+    /// Emit a hashed user ID in wearable provenance
+    #[arg(long)]
+    include_user_id: bool,
+}
+
+#[derive(Clone, ValueEnum)]
+enum ScrubArg {
+    /// Replace identifiers with salted hash pseudonyms
+    Hash,
+    /// Remove identifiers entirely
+    Drop,
+}
+
+impl ScrubArgs {
+    fn policy(&self) -> Option<PrivacyPolicy> {
+        if self.scrub.is_none() && !self.include_user_id {
+            return None;
+        }
+        let mut policy = PrivacyPolicy::default().with_user_id_included(self.include_user_id);
+        if let Some(scrub) = &self.scrub {
+            let mode = match scrub {
+                ScrubArg::Hash => ScrubMode::Hash,
+                ScrubArg::Drop => ScrubMode::Drop,
+            };
+            policy = policy.with_scrubber(Scrubber::new(mode).with_salt(self.scrub_salt.clone()));
+        }
+        Some(policy)
+    }
+
+    fn apply(&self, processor: &mut FluxProcessor) {
+        if let Some(policy) = self.policy() {
+            processor.set_privacy_policy(policy);
+        }
+    }
+}
+
+/// Options for handling malformed input
+#[derive(Args)]
+struct ParseArgs {
+    /// strict: abort on the first malformed line; lenient: skip and count bad lines/events
+    #[arg(long, value_enum, env = "FLUX_PARSE_MODE", default_value = "strict")]
+    parse_mode: ParseModeArg,
+
+    /// Write a JSON processing report (including dropped_events) to this file
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ParseModeArg {
+    Strict,
+    Lenient,
+}
+
+impl ParseArgs {
+    fn mode(&self) -> ParseMode {
+        match self.parse_mode {
+            ParseModeArg::Strict => ParseMode::Strict,
+            ParseModeArg::Lenient => ParseMode::Lenient,
+        }
+    }
+
+    /// Write the processing report, or warn on stderr about dropped events and
+    /// processing warnings
+    fn finish(
+        &self,
+        events_parsed: usize,
+        days_produced: usize,
+        dropped: Vec<DroppedEvent>,
+        (warnings, unmapped_signals): (Vec<Warning>, BTreeMap<String, usize>),
+    ) -> Result<(), FluxCliError> {
+        self.finish_with_late(
+            events_parsed,
+            days_produced,
+            dropped,
+            None,
+            (warnings, unmapped_signals),
+        )
+    }
+
+    /// Like [`ParseArgs::finish`], also reporting events that arrived after their day completed
+    fn finish_with_late(
+        &self,
+        events_parsed: usize,
+        days_produced: usize,
+        dropped: Vec<DroppedEvent>,
+        late: Option<Vec<DroppedEvent>>,
+        (warnings, unmapped_signals): (Vec<Warning>, BTreeMap<String, usize>),
+    ) -> Result<(), FluxCliError> {
+        match &self.report {
+            Some(path) => {
+                let report = ProcessingReport {
+                    parse_mode: self.mode(),
+                    events_parsed,
+                    days_produced,
+                    dropped_events: DroppedEventsReport {
+                        count: dropped.len(),
+                        events: dropped,
+                    },
+                    late_events: late.map(|events| DroppedEventsReport {
+                        count: events.len(),
+                        events,
+                    }),
+                    warnings,
+                    unmapped_signals,
+                };
+                fs::write(path, serde_json::to_string_pretty(&report)?)?;
+            }
+            None => {
+                if !dropped.is_empty() {
+                    eprintln!(
+                        "warning: skipped {} malformed or invalid events (use --report for details)",
+                        dropped.len()
+                    );
+                }
+                if let Some(late) = late.filter(|late| !late.is_empty()) {
+                    eprintln!(
+                        "warning: {} events arrived after their day completed (use --report for details)",
+                        late.len()
+                    );
+                }
+                for warning in &warnings {
+                    eprintln!("warning: {warning}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options for input that is heavily out of chronological order
+#[derive(Args)]
+struct OrderArgs {
+    /// Share of events allowed to be earlier than an event before them
+    #[arg(long, default_value_t = DEFAULT_MAX_OUT_OF_ORDER)]
+    max_out_of_order: f64,
+
+    /// What to do when more events than that are out of order (they are sorted either way)
+    #[arg(long, value_enum, default_value = "warn")]
+    out_of_order: OutOfOrderArg,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutOfOrderArg {
+    Warn,
+    Fail,
+}
+
+impl OrderArgs {
+    /// Warn on stderr about, or reject, heavily out-of-order events
+    fn check(&self, events: &[RawEvent]) -> Result<(), FluxCliError> {
+        let policy = match self.out_of_order {
+            OutOfOrderArg::Warn => DisorderPolicy::Warn,
+            OutOfOrderArg::Fail => DisorderPolicy::Fail,
+        };
+        let config = OrderingConfig::default()
+            .with_max_out_of_order(self.max_out_of_order)
+            .with_policy(policy);
+        if let Some(order) = config.check(events)? {
+            eprintln!("warning: input is out of chronological order: {order}");
+        }
+        Ok(())
+    }
+}
+
+/// Options for deciding when a streamed day is complete
+#[derive(Args)]
+struct WatermarkArgs {
+    /// Keep each day open until events this many minutes past its end arrive
+    #[arg(long, default_value = "60")]
+    allowed_lateness_minutes: i64,
+
+    /// What to do with events for days that already completed
+    #[arg(long, value_enum, default_value = "drop")]
+    late_events: LateEventsArg,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LateEventsArg {
+    /// Skip the event and report it
+    Drop,
+    /// Re-emit the day as a revision including the event
+    Amend,
+    /// Stop with an error
+    Error,
+}
+
+impl WatermarkArgs {
+    fn watermark(&self) -> DayWatermark {
+        let policy = match self.late_events {
+            LateEventsArg::Drop => LateEventPolicy::Drop,
+            LateEventsArg::Amend => LateEventPolicy::Amend,
+            LateEventsArg::Error => LateEventPolicy::Error,
+        };
+        DayWatermark::new(chrono::Duration::minutes(self.allowed_lateness_minutes))
+            .with_late_events(policy)
+    }
+}
+
+/// Options for reproducible (golden) output
+#[derive(Args)]
+struct DeterminismArgs {
+    /// Produce byte-identical output (seeded instance ID, fixed clock, sorted keys)
+    #[arg(long)]
+    deterministic: bool,
+
+    /// Seed for the producer instance ID in deterministic mode
+    #[arg(long, default_value = "0", requires = "deterministic")]
+    seed: u64,
+
+    /// Timestamp used for computed_at_utc in deterministic mode (RFC3339);
+    /// days that had not ended by then are flagged partial_day
+    #[arg(
+        long,
+        default_value = "1970-01-01T00:00:00Z",
+        requires = "deterministic"
+    )]
+    computed_at: DateTime<Utc>,
+}
+
+impl DeterminismArgs {
+    fn apply(&self, processor: &mut FluxProcessor) {
+        if self.deterministic {
+            processor.set_deterministic(self.seed, self.computed_at);
+        }
+    }
+
+    /// Pin canonical days' ingest time, which the processor would otherwise pin
+    fn pin_clock(&self, days: &mut [CanonicalWearSignals]) {
+        if self.deterministic {
+            for day in days {
+                day.observed_at = self.computed_at;
+            }
+        }
+    }
+}
+
+/// Progress reporting for long batch runs
+#[derive(Args)]
+struct ProgressArgs {
+    /// Print events processed, days emitted and ETA to stderr
+    #[arg(long)]
+    progress: bool,
+
+    /// Like --progress, as one JSON object per line
+    #[arg(long)]
+    progress_json: bool,
+}
+
+impl ProgressArgs {
+    fn reporter(&self) -> Option<Arc<ProgressReporter>> {
+        (self.progress || self.progress_json)
+            .then(|| Arc::new(ProgressReporter::new(self.progress_json)))
+    }
+}
+
+/// Prints progress from the pipeline's [`ProgressCounters`], at most once a second
+struct ProgressReporter {
+    counters: ProgressCounters,
+    days_total: AtomicUsize,
+    json: bool,
+    started: Instant,
+    last_report: Mutex<Option<Instant>>,
+}
+
+impl ProgressReporter {
+    fn new(json: bool) -> Self {
+        Self {
+            counters: ProgressCounters::new(),
+            days_total: AtomicUsize::new(0),
+            json,
+            started: Instant::now(),
+            last_report: Mutex::new(None),
+        }
+    }
+
+    /// Register days about to be processed, for the ETA
+    fn add_days(&self, days: usize) {
+        self.days_total.fetch_add(days, Ordering::Relaxed);
+    }
+
+    fn apply(self: &Arc<Self>, processor: &mut FluxProcessor) {
+        processor.set_observer(self.clone());
+    }
+
+    fn report(&self, force: bool) {
+        let mut last_report = self.last_report.lock().unwrap_or_else(|e| e.into_inner());
+        if !force && last_report.is_some_and(|t| t.elapsed() < Duration::from_secs(1)) {
+            return;
+        }
+        *last_report = Some(Instant::now());
+
+        let events = self.counters.events_parsed();
+        let days = self.counters.windows_processed();
+        let total = self.days_total.load(Ordering::Relaxed);
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let eta =
+            (days > 0 && total >= days).then(|| elapsed / days as f64 * (total - days) as f64);
+
+        if self.json {
+            let line = serde_json::json!({
+                "events_processed": events,
+                "days_emitted": days,
+                "days_total": total,
+                "errors": self.counters.errors(),
+                "elapsed_secs": elapsed,
+                "eta_secs": eta,
+            });
+            eprintln!("{line}");
+        } else {
+            let eta = eta.map_or("unknown".to_string(), |eta| format!("{eta:.0}s"));
+            eprintln!(
+                "progress: {events} events, {days}/{total} days, {elapsed:.1}s elapsed, ETA {eta}"
+            );
+        }
+    }
+
+    /// Print the final counts
+    fn finish(&self) {
+        self.report(true);
+    }
+}
+
+impl PipelineObserver for ProgressReporter {
+    fn on_events_parsed(&self, pipeline: PipelineKind, count: usize) {
+        self.counters.on_events_parsed(pipeline, count);
+    }
+
+    fn on_day_processed(&self, window: &ProcessedWindow<'_>) {
+        self.counters.on_day_processed(window);
+        self.report(false);
+    }
+
+    fn on_quality_flag(&self, pipeline: PipelineKind, id: &str, flag: &str) {
+        self.counters.on_quality_flag(pipeline, id, flag);
+    }
+
+    fn on_error(&self, pipeline: PipelineKind, error: &synheart_flux::ComputeError) {
+        self.counters.on_error(pipeline, error);
+    }
+}
+
+/// Optional window content: intraday series and source event provenance
+#[derive(Args)]
+struct ContentArgs {
+    /// Emit heart rate and HRV series in each window, averaged over buckets of this many minutes
+    #[arg(long, value_name = "MINUTES", value_parser = clap::value_parser!(u32).range(1..))]
+    series_minutes: Option<u32>,
+
+    /// Record each window's contributing raw event IDs in its provenance
+    #[arg(long, value_enum, default_value = "off")]
+    source_events: SourceEventsArg,
+
+    /// Pass signals with no canonical field through to meta.unmapped_signals
+    #[arg(long)]
+    unmapped_signals: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SourceEventsArg {
+    Off,
+    /// Count and SHA-256 digest
+    Digest,
+    /// Count, digest and the IDs
+    List,
+}
+
+impl ContentArgs {
+    fn apply(&self, processor: &mut FluxProcessor) {
+        let source_events = match self.source_events {
+            SourceEventsArg::Off => SourceEvents::Off,
+            SourceEventsArg::Digest => SourceEvents::Digest,
+            SourceEventsArg::List => SourceEvents::List,
+        };
+        if self.series_minutes.is_some()
+            || source_events != SourceEvents::Off
+            || self.unmapped_signals
+        {
+            processor.set_encoder_config(
+                EncoderConfig::default()
+                    .with_series_minutes(self.series_minutes)
+                    .with_source_events(source_events)
+                    .with_unmapped_signals(self.unmapped_signals),
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ProfileArg {
+    Flux,
+    SdkV1,
+}
+
+impl From<ProfileArg> for OutputProfile {
+    fn from(profile: ProfileArg) -> Self {
+        match profile {
+            ProfileArg::Flux => OutputProfile::Flux,
+            ProfileArg::SdkV1 => OutputProfile::SdkV1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutOfBoundsArg {
+    /// Remove the events
+    Drop,
+    /// Move the events to the nearest session bound
+    Clamp,
+    /// Reject the session
+    Fail,
+}
+
+impl From<OutOfBoundsArg> for OutOfBoundsPolicy {
+    fn from(policy: OutOfBoundsArg) -> Self {
+        match policy {
+            OutOfBoundsArg::Drop => OutOfBoundsPolicy::Drop,
+            OutOfBoundsArg::Clamp => OutOfBoundsPolicy::Clamp,
+            OutOfBoundsArg::Fail => OutOfBoundsPolicy::Fail,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RollupPeriodArg {
+    Weekly,
+    Monthly,
+}
+
+impl From<RollupPeriodArg> for RollupPeriod {
+    fn from(period: RollupPeriodArg) -> Self {
+        match period {
+            RollupPeriodArg::Weekly => RollupPeriod::Weekly,
+            RollupPeriodArg::Monthly => RollupPeriod::Monthly,
+        }
+    }
+}
+
+#[derive(Clone, ValueEnum)]
+enum InputFormat {
+    /// Newline-delimited JSON (one event per line)
+    Ndjson,
+    /// JSON array of events
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum InputVendor {
+    /// wear.raw_event.v1 events
+    RawEvents,
+    /// WHOOP API export (JSON)
+    Whoop,
+    /// Garmin API export (JSON)
+    Garmin,
+    /// Detect a WHOOP or Garmin export from its shape, else read raw events
+    Auto,
+}
+
+impl InputVendor {
+    /// Vendor whose export the input is, or `None` for raw events
+    fn resolve(self, input: &str) -> Option<Vendor> {
+        match self {
+            Self::RawEvents => None,
+            Self::Whoop => Some(Vendor::Whoop),
+            Self::Garmin => Some(Vendor::Garmin),
+            Self::Auto => detect_vendor(input),
+        }
+    }
+}
+
+#[derive(Clone, ValueEnum)]
+enum OutputFormat {
+    /// Newline-delimited JSON (one HSI record per line)
+    Ndjson,
+    /// JSON array of HSI records
+    Json,
+    /// Pretty-printed JSON
+    JsonPretty,
+    /// FHIR R4 Observation resources (one per line)
+    Fhir,
+    /// CBOR sequence (one binary HSI record per item, sorted keys)
+    Cbor,
+    /// MessagePack stream (one binary HSI record per item, sorted keys)
+    Msgpack,
+    /// Canonical signals instead of HSI (one day per line); transform only
+    Canonical,
+}
+
+/// Stage printed by `flux inspect`
+#[derive(Clone, Copy, ValueEnum)]
+enum InspectStage {
+    /// Normalized signals: scores on 0-1, coverage and quality flags
+    Normalize,
+    /// Derived features computed from the normalized signals
+    Features,
+    /// Baselines and deviations from them
+    Baseline,
+}
+
+impl InspectStage {
+    /// The stage's own fields from a window's contextual signals, leaving out
+    /// the earlier stages they embed
+    fn select(self, contextual: &impl serde::Serialize) -> Result<serde_json::Value, FluxCliError> {
+        let mut value = serde_json::to_value(contextual)?;
+        let mut stage = match self {
+            Self::Normalize => return Ok(value["derived"]["normalized"].take()),
+            Self::Features => value["derived"].take(),
+            Self::Baseline => value,
+        };
+        if let Some(fields) = stage.as_object_mut() {
+            fields.remove("normalized");
+            fields.remove("derived");
+        }
+        Ok(stage)
+    }
+}
+
+/// Intermediate pipeline stage whose output can be written instead of HSI
+#[derive(Clone, Copy, ValueEnum)]
+enum Stage {
+    /// Vendor-agnostic days, before normalization and baselines
+    Canonical,
+}
+
+#[derive(Clone, ValueEnum)]
+enum GenerateKind {
+    /// WHOOP API payload (JSON)
+    Whoop,
+    /// Garmin Health API payload (JSON)
+    Garmin,
+    /// wear.raw_event.v1 events (NDJSON)
+    RawEvents,
+    /// Behavioral sessions (NDJSON)
+    Behavior,
+}
+
+#[derive(Clone, ValueEnum)]
+enum SchemaType {
+    /// Input schema (wear.raw_event.v1)
+    Input,
+    /// Output schema (hsi.snapshot.v1)
+    Output,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_logging(cli.log_level, cli.log_json);
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                serde_json::to_string(&CliError::from(e))
+                    .unwrap_or_else(|_| "Unknown error".to_string())
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), FluxCliError> {
+    match cli.command {
+        Commands::Transform {
+            input,
+            output,
+            input_format,
+            vendor,
+            output_format,
+            timezone,
+            device_id,
+            baseline_days,
+            load_baselines,
+            save_baselines,
+            fuse_devices,
+            jobs,
+            explain,
+            resume,
+            stop_after,
+            parse,
+            order,
+            determinism,
+            scrub,
+            content,
+            progress,
+        } => cmd_transform(
+            &input,
+            &output,
+            (input_format, vendor),
+            output_format,
+            &timezone,
+            &device_id,
+            baseline_days,
+            load_baselines.as_deref(),
+            save_baselines.as_deref(),
+            fuse_devices,
+            jobs.map(usize::from),
+            (explain, resume, stop_after),
+            &parse,
+            &order,
+            &determinism,
+            &scrub,
+            &content,
+            &progress,
+        ),
+
+        Commands::Run {
+            output_format,
+            timezone,
+            device_id,
+            baseline_days,
+            load_baselines,
+            save_baselines,
+            flush,
+            checkpoint_interval,
+            fuse_devices,
+            parse,
+            watermark,
+            determinism,
+            scrub,
+            content,
+        } => cmd_run(
+            output_format,
+            &timezone,
+            &device_id,
+            baseline_days,
+            load_baselines.as_deref(),
+            save_baselines.as_deref(),
+            (flush, checkpoint_interval),
+            fuse_devices,
+            &parse,
+            &watermark,
+            &determinism,
+            &scrub,
+            &content,
+        ),
+
+        Commands::Backfill {
+            input,
+            output,
+            input_format,
+            output_format,
+            from,
+            to,
+            timezone,
+            device_id,
+            baseline_days,
+            load_baselines,
+            save_baselines,
+            dry_run,
+            fuse_devices,
+            determinism,
+            scrub,
+            progress,
+        } => cmd_backfill(
+            &input,
+            &output,
+            input_format,
+            output_format,
+            (from, to),
+            &timezone,
+            &device_id,
+            baseline_days,
+            load_baselines.as_deref(),
+            save_baselines.as_deref(),
+            dry_run,
+            fuse_devices,
+            &determinism,
+            &scrub,
+            &progress,
+        ),
+
+        Commands::Validate {
+            input,
+            input_format,
+            json,
+        } => cmd_validate(&input, input_format, json),
+
+        Commands::ValidateHsi {
+            input,
+            input_format,
+            json,
+        } => cmd_validate_hsi(&input, input_format, json),
+
+        Commands::Doctor {
+            baselines,
+            sample_input,
+            timezone,
+            json,
+        } => cmd_doctor(
+            baselines.as_deref(),
+            sample_input.as_deref(),
+            &timezone,
+            json,
+        ),
+
+        Commands::Inspect {
+            input,
+            input_format,
+            vendor,
+            stage,
+            date,
+            timezone,
+            device_id,
+            baseline_days,
+            load_baselines,
+        } => cmd_inspect(
+            &input,
+            (input_format, vendor),
+            stage,
+            date,
+            &timezone,
+            &device_id,
+            baseline_days,
+            load_baselines.as_deref(),
+        ),
+
+        Commands::Behavior { command } => match command {
+            BehaviorCommands::Transform {
+                input,
+                output,
+                input_format,
+                output_format,
+                baseline_sessions,
+                load_behavior_baselines,
+                save_behavior_baselines,
+                bio_baselines,
+                profile,
+                config,
+                scrub,
+            } => cmd_behavior_transform(
+                &input,
+                &output,
+                input_format,
+                output_format,
+                baseline_sessions,
+                load_behavior_baselines.as_deref(),
+                save_behavior_baselines.as_deref(),
+                bio_baselines.as_deref(),
+                profile.into(),
+                &config,
+                &scrub,
+            ),
+            BehaviorCommands::Validate {
+                input,
+                input_format,
+                json,
+            } => cmd_behavior_validate(&input, input_format, json),
+            BehaviorCommands::Inspect {
+                input,
+                input_format,
+                stage,
+                session_id,
+                baseline_sessions,
+                load_behavior_baselines,
+                config,
+            } => cmd_behavior_inspect(
+                &input,
+                input_format,
+                stage,
+                session_id.as_deref(),
+                baseline_sessions,
+                load_behavior_baselines.as_deref(),
+                &config,
+            ),
+        },
+
+        Commands::Generate {
+            kind,
+            output,
+            days,
+            seed,
+            start_date,
+            sleep_trend,
+            notification_rate,
+            missing_prob,
+            device_id,
+            provider,
+        } => {
+            let config = SyntheticConfig::default()
+                .with_days(days)
+                .with_seed(seed)
+                .with_start_date(start_date)
+                .with_sleep_quality_trend(sleep_trend)
+                .with_notification_rate_per_hour(notification_rate)
+                .with_missing_probability(missing_prob)
+                .with_device_id(device_id);
+            cmd_generate(kind, &output, config, &provider)
+        }
+
+        Commands::Conformance {
+            dir,
+            tolerance,
+            ignore,
+            bless,
+            json,
+        } => {
+            let config = ignore.into_iter().fold(
+                ConformanceConfig::default().with_tolerance(tolerance),
+                ConformanceConfig::with_ignored_path,
+            );
+            cmd_conformance(&dir, &config, bless, json)
+        }
+
+        Commands::MigrateCheck {
+            baselines,
+            fixture,
+            tolerance,
+            ignore,
+            json,
+        } => {
+            let config = ignore.into_iter().fold(
+                ConformanceConfig::default().with_tolerance(tolerance),
+                ConformanceConfig::with_ignored_path,
+            );
+            cmd_migrate_check(&baselines, &fixture, &config, json)
+        }
+
+        Commands::Rollup {
+            input,
+            output,
+            period,
+            from,
+            to,
+        } => cmd_rollup(&input, &output, period.into(), from, to),
+
+        Commands::Diff {
+            before,
+            after,
+            tolerance,
+            ignore,
+            json,
+        } => {
+            let config = ignore.into_iter().fold(
+                CompareConfig::default().with_tolerance(tolerance),
+                CompareConfig::with_ignored_path,
+            );
+            cmd_diff(&before, &after, &config, json)
+        }
+
+        #[cfg(feature = "serve")]
+        Commands::Serve {
+            listen,
+            state_dir,
+            persist_secs,
+            max_body_bytes,
+            baseline_days,
+            baseline_sessions,
+        } => {
+            let mut config = synheart_flux::serve::ServeConfig {
+                baseline_days,
+                baseline_sessions,
+                ..Default::default()
+            }
+            .with_persist_interval(std::time::Duration::from_secs(persist_secs))
+            .with_max_body_bytes(max_body_bytes);
+            if let Some(dir) = state_dir {
+                config = config.with_state_dir(dir);
+            }
+            eprintln!("flux serve listening on {listen}");
+            Ok(synheart_flux::serve::serve(&listen, config)?)
+        }
+
+        Commands::Schema {
+            schema_type,
+            json_schema,
+        } => cmd_schema(schema_type, json_schema),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_transform(
+    input: &PathBuf,
+    output: &PathBuf,
+    (input_format, vendor): (InputFormat, InputVendor),
+    output_format: OutputFormat,
+    timezone: &str,
+    device_id: &str,
+    baseline_days: usize,
+    load_baselines: Option<&std::path::Path>,
+    save_baselines: Option<&std::path::Path>,
+    fuse_devices: bool,
+    jobs: Option<usize>,
+    (explain, resume, stop_after): (bool, bool, Option<Stage>),
+    parse: &ParseArgs,
+    order: &OrderArgs,
+    determinism: &DeterminismArgs,
+    scrub: &ScrubArgs,
+    content: &ContentArgs,
+    progress: &ProgressArgs,
+) -> Result<(), FluxCliError> {
+    if resume && output.to_string_lossy() == "-" {
+        return Err(FluxCliError::InvalidArgument(
+            "--resume needs an output file, not stdout".to_string(),
+        ));
+    }
+
+    if explain
+        && !matches!(
+            output_format,
+            OutputFormat::Ndjson | OutputFormat::Json | OutputFormat::JsonPretty
+        )
+    {
+        return Err(FluxCliError::InvalidArgument(
+            "--explain output is JSON; use --output-format ndjson, json or json-pretty".to_string(),
+        ));
+    }
+
+    if jobs.is_some() && (load_baselines.is_some() || save_baselines.is_some()) {
+        return Err(FluxCliError::InvalidArgument(
+            "--load-baselines/--save-baselines hold a single baseline store and cannot be combined with --jobs".to_string(),
+        ));
+    }
+
+    // `--output-format canonical` is short for `--stop-after canonical` as NDJSON
+    let stop_after =
+        stop_after.or(matches!(output_format, OutputFormat::Canonical).then_some(Stage::Canonical));
+    if stop_after.is_some()
+        && (explain
+            || resume
+            || jobs.is_some()
+            || load_baselines.is_some()
+            || save_baselines.is_some())
+    {
+        return Err(FluxCliError::InvalidArgument(
+            "canonical output stops before baselines are used and cannot be combined with --explain, --resume, --jobs or --load-baselines/--save-baselines".to_string(),
+        ));
+    }
+
+    // Read input
+    let input_data = read_input(input)?;
+    let progress = progress.reporter();
+
+    let (canonical_signals, events_parsed, dropped, unmapped) = match vendor.resolve(&input_data) {
+        // A vendor export maps straight to canonical days
+        Some(vendor) => {
+            if jobs.is_some() {
+                return Err(FluxCliError::InvalidArgument(format!(
+                    "--jobs partitions raw events and cannot read a {} export",
+                    vendor.as_str()
+                )));
+            }
+            let canonical_signals = match vendor {
+                Vendor::Whoop => WhoopAdapter.parse(&input_data, timezone, device_id)?,
+                Vendor::Garmin => GarminAdapter.parse(&input_data, timezone, device_id)?,
+            };
+            (canonical_signals, 0, Vec::new(), BTreeMap::new())
+        }
+        None => {
+            // Parse events
+            let ParsedEvents { events, dropped } = match input_format {
+                InputFormat::Ndjson => {
+                    RawEventAdapter::parse_ndjson_with_mode(&input_data, parse.mode())?
+                }
+                InputFormat::Json => {
+                    RawEventAdapter::parse_array_with_mode(&input_data, parse.mode())?
+                }
+            };
+            let events_parsed = events.len();
+
+            if events.is_empty() {
+                return Err(FluxCliError::NoEvents);
+            }
+            let unmapped = RawEventAdapter::unmapped_signals(&events);
+
+            if let Some(progress) = &progress {
+                progress.on_events_parsed(PipelineKind::Wearable, events_parsed);
+            }
+
+            if let Some(jobs) = jobs {
+                let (payloads, warnings) = transform_partitioned(
+                    events,
+                    timezone,
+                    device_id,
+                    baseline_days,
+                    fuse_devices,
+                    jobs,
+                    order,
+                    determinism,
+                    scrub,
+                    content,
+                    progress.as_ref(),
+                )?;
+                let mut writer = PayloadWriter::new(
+                    open_output(output)?,
+                    &output_format,
+                    determinism.deterministic,
+                );
+                for payload in &payloads {
+                    writer.write(payload)?;
+                }
+                let days_produced = writer.count();
+                writer.finish()?;
+                commit_output(output)?;
+                if let Some(progress) = &progress {
+                    progress.finish();
+                }
+                return parse.finish(events_parsed, days_produced, dropped, (warnings, unmapped));
+            }
+            order.check(&events)?;
+
+            // Convert to canonical signals
+            let canonical_signals = RawEventAdapter::to_canonical(&events, timezone, device_id)?;
+            (canonical_signals, events_parsed, dropped, unmapped)
+        }
+    };
+    let canonical_signals = fuse_if_requested(canonical_signals, fuse_devices);
+
+    if canonical_signals.is_empty() {
+        return Err(FluxCliError::NoSignals);
+    }
+
+    if let Some(Stage::Canonical) = stop_after {
+        let mut days = canonical_signals;
+        determinism.pin_clock(&mut days);
+        write_output(output, &format_canonical(&days, &output_format)?)?;
+        let warnings = days.iter().flat_map(|d| d.warnings.clone()).collect();
+        return parse.finish(events_parsed, days.len(), dropped, (warnings, unmapped));
+    }
+
+    // Create processor with baselines
+    let mut processor = FluxProcessor::with_baseline_window(baseline_days);
+    determinism.apply(&mut processor);
+    scrub.apply(&mut processor);
+    content.apply(&mut processor);
+    if let Some(progress) = &progress {
+        progress.add_days(canonical_signals.len());
+        progress.apply(&mut processor);
+    }
+
+    // Load existing baselines if provided
     if let Some(baselines_path) = load_baselines {
         let baselines_json = fs::read_to_string(baselines_path)?;
         processor.load_baselines(&baselines_json)?;
     }
 
-    // Process each day's signals through the pipeline
-    let mut hsi_outputs: Vec<HsiPayload> = Vec::new();
+    if explain {
+        let traced = processor.process_with_trace(&canonical_signals)?;
+        let output_data = format_explained(&traced, &output_format)?;
+        write_output(output, &output_data)?;
+        if let Some(progress) = &progress {
+            progress.finish();
+        }
+        return parse.finish(
+            events_parsed,
+            traced.len(),
+            dropped,
+            (processor.take_warnings(), unmapped),
+        );
+    }
+
+    // Pick up after the last window an interrupted run completed
+    let mut resume_log = resume.then(|| ResumeLog::load(output)).transpose()?;
+    let previous = resume_log.as_ref().and_then(|log| log.last.clone());
+    let out = match (&previous, &resume_log) {
+        (Some(record), Some(log)) => {
+            processor.load_baselines(&log.baselines.to_string())?;
+            eprintln!(
+                "resuming after {} completed windows",
+                resume_log.as_ref().map_or(0, |log| log.windows.len())
+            );
+            reopen_output(output, record.output_bytes)?
+        }
+        _ => open_output(output)?,
+    };
+
+    // Process each day through the pipeline, writing its payload as soon as it completes
+    let mut writer = PayloadWriter::new(out, &output_format, determinism.deterministic)
+        .with_count(previous.map_or(0, |record| record.payloads));
+    for day in &canonical_signals {
+        let window = ResumeLog::window_key(day);
+        let event_ids = ResumeLog::event_ids(day);
+        if let Some(done) = resume_log.as_ref().and_then(|log| log.windows.get(&window)) {
+            if *done != event_ids {
+                return Err(FluxCliError::InvalidArgument(format!(
+                    "input for {window} changed since the interrupted run; rerun without --resume"
+                )));
+            }
+            continue;
+        }
+
+        for hsi_json in processor.process_canonical(std::slice::from_ref(day))? {
+            writer.write(&serde_json::from_str(&hsi_json)?)?;
+        }
+        writer.flush()?;
+
+        if let Some(log) = &mut resume_log {
+            log.append(
+                ResumeRecord {
+                    window,
+                    event_ids,
+                    payloads: writer.count(),
+                    output_bytes: fs::metadata(partial_path(output))?.len(),
+                },
+                serde_json::from_str(&processor.save_baselines()?)?,
+            )?;
+        }
+    }
+    let days_produced = writer.count();
+    writer.finish()?;
+    commit_output(output)?;
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
+
+    // Save baselines if requested
+    if let Some(baselines_path) = save_baselines {
+        write_atomic(baselines_path, processor.save_baselines()?.as_bytes())?;
+    }
+    if let Some(log) = resume_log {
+        log.remove()?;
+    }
+
+    parse.finish(
+        events_parsed,
+        days_produced,
+        dropped,
+        (processor.take_warnings(), unmapped),
+    )
+}
+
+/// One completed window in the sidecar log of `flux transform --resume`
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct ResumeRecord {
+    /// Window key (`date/vendor/device_id/user_id`)
+    window: String,
+    /// Raw event IDs that fed the window
+    event_ids: Vec<String>,
+    /// Payloads written so far
+    payloads: usize,
+    /// Length of the partial output after the window
+    output_bytes: u64,
+}
+
+/// Baseline store after the last completed window (`<OUTPUT>.resume.baselines`)
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ResumeSnapshot {
+    /// Window key of the last completed window
+    window: String,
+    baselines: serde_json::Value,
+}
+
+/// Sidecar log (`<OUTPUT>.resume`) of the windows a transform has completed,
+/// with the baselines after the latest one kept in a separate snapshot
+struct ResumeLog {
+    path: PathBuf,
+    snapshot_path: PathBuf,
+    /// Event IDs of each completed window, by window key
+    windows: BTreeMap<String, Vec<String>>,
+    last: Option<ResumeRecord>,
+    /// Baseline store after `last`
+    baselines: serde_json::Value,
+}
+
+impl ResumeLog {
+    /// Read the log next to `output`, if any. Records after the one the
+    /// baseline snapshot was taken at (a torn record, or one whose snapshot a
+    /// crash cut short) are dropped.
+    fn load(output: &std::path::Path) -> Result<Self, FluxCliError> {
+        let mut log = Self {
+            path: sibling_path(output, "resume"),
+            snapshot_path: sibling_path(output, "resume.baselines"),
+            windows: BTreeMap::new(),
+            last: None,
+            baselines: serde_json::Value::Null,
+        };
+        let contents = match fs::read_to_string(&log.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(log),
+            Err(e) => return Err(e.into()),
+        };
+        let snapshot = match fs::read_to_string(&log.snapshot_path) {
+            Ok(snapshot) => serde_json::from_str::<ResumeSnapshot>(&snapshot).ok(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut records = Vec::new();
+        let mut end = 0;
+        for line in contents.split_inclusive('\n') {
+            let Ok(record) = serde_json::from_str::<ResumeRecord>(line) else {
+                break;
+            };
+            end += line.len();
+            records.push((record, end));
+        }
+        let mut valid_bytes = 0;
+        if let Some(snapshot) = snapshot {
+            if let Some(keep) = records
+                .iter()
+                .rposition(|(r, _)| r.window == snapshot.window)
+            {
+                records.truncate(keep + 1);
+                valid_bytes = records[keep].1;
+                log.baselines = snapshot.baselines;
+                for (record, _) in records {
+                    log.windows
+                        .insert(record.window.clone(), record.event_ids.clone());
+                    log.last = Some(record);
+                }
+            }
+        }
+        // Drop the unusable tail so new records append cleanly
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&log.path)?
+            .set_len(valid_bytes as u64)?;
+        Ok(log)
+    }
+
+    fn window_key(day: &CanonicalWearSignals) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            day.date,
+            day.vendor.as_str(),
+            day.device_id,
+            day.user_id.as_deref().unwrap_or_default()
+        )
+    }
+
+    fn event_ids(day: &CanonicalWearSignals) -> Vec<String> {
+        let mut ids: Vec<String> = day.field_events.values().flatten().cloned().collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Log a completed window, then replace the snapshot with its baselines
+    fn append(
+        &mut self,
+        record: ResumeRecord,
+        baselines: serde_json::Value,
+    ) -> Result<(), FluxCliError> {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        file.sync_data()?;
+        let snapshot = ResumeSnapshot {
+            window: record.window,
+            baselines,
+        };
+        write_atomic(&self.snapshot_path, &serde_json::to_vec(&snapshot)?)?;
+        Ok(())
+    }
+
+    /// Delete the log and snapshot once the output is complete
+    fn remove(self) -> io::Result<()> {
+        for path in [&self.path, &self.snapshot_path] {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_run(
+    output_format: OutputFormat,
+    timezone: &str,
+    device_id: &str,
+    baseline_days: usize,
+    load_baselines: Option<&std::path::Path>,
+    save_baselines: Option<&std::path::Path>,
+    (flush, checkpoint_interval): (bool, Option<Duration>),
+    fuse_devices: bool,
+    parse: &ParseArgs,
+    watermark: &WatermarkArgs,
+    determinism: &DeterminismArgs,
+    scrub: &ScrubArgs,
+    content: &ContentArgs,
+) -> Result<(), FluxCliError> {
+    if matches!(output_format, OutputFormat::Canonical) {
+        return Err(canonical_unsupported());
+    }
+    let mut processor = FluxProcessor::with_baseline_window(baseline_days);
+    determinism.apply(&mut processor);
+    scrub.apply(&mut processor);
+    content.apply(&mut processor);
 
-    for signals in canonical_signals {
-        // Convert signals to vendor JSON format for processing
-        // (This is a bridge until we refactor the pipeline to accept canonical directly)
-        let vendor_json = convert_canonical_to_vendor_json(&signals)?;
+    // Load existing baselines if provided
+    if let Some(baselines_path) = load_baselines {
+        let baselines_json = fs::read_to_string(baselines_path)?;
+        processor.load_baselines(&baselines_json)?;
+    }
+
+    // On SIGINT/SIGTERM, stop reading and finish as if the input had ended
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&shutdown);
+    if let Err(e) = ctrlc::set_handler(move || handler_flag.store(true, Ordering::SeqCst)) {
+        eprintln!("warning: cannot handle shutdown signals: {e}");
+    }
 
-        let hsi_jsons = match signals.vendor {
-            synheart_flux::types::Vendor::Whoop => {
-                processor.process_whoop(&vendor_json, timezone, device_id)?
+    // Read stdin on its own thread so signals and checkpoints are not blocked on input
+    let (lines_tx, lines_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if lines_tx.send(line).is_err() {
+                break;
             }
-            synheart_flux::types::Vendor::Garmin => {
-                processor.process_garmin(&vendor_json, timezone, device_id)?
+        }
+    });
+
+    let mut stdout = io::stdout();
+    let mut watermark = watermark.watermark();
+    let mut dropped: Vec<DroppedEvent> = Vec::new();
+    let mut late: Vec<DroppedEvent> = Vec::new();
+    let (mut events_parsed, mut days_produced) = (0, 0);
+    let mut last_checkpoint = Instant::now();
+    let mut line_num = 0;
+
+    loop {
+        if shutdown.load(Ordering::SeqCst) {
+            eprintln!("received shutdown signal, finishing open days");
+            break;
+        }
+        if let (Some(interval), Some(baselines_path)) = (checkpoint_interval, save_baselines) {
+            if last_checkpoint.elapsed() >= interval {
+                write_atomic(baselines_path, processor.save_baselines()?.as_bytes())?;
+                last_checkpoint = Instant::now();
             }
+        }
+
+        let line = match lines_rx.recv_timeout(SHUTDOWN_POLL_INTERVAL) {
+            Ok(line) => line?,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        line_num += 1;
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let Some(event) =
+            RawEventAdapter::parse_line(trimmed, line_num, parse.mode(), &mut dropped)?
+        else {
+            continue;
         };
+        if parse.mode() == ParseMode::Strict {
+            event.validate()?;
+        }
+        events_parsed += 1;
+
+        let event_id = event.event_id.clone();
+        let date = RawEventAdapter::event_date(&event);
+        let pushed = watermark.push(event)?;
+        if pushed.late {
+            let outcome = if pushed.completed.is_empty() {
+                "dropped"
+            } else {
+                "amended"
+            };
+            late.push(DroppedEvent {
+                line: line_num,
+                event_id,
+                reason: format!("arrived after {date} completed; {outcome}"),
+            });
+        }
+
+        // Process the days the watermark has passed
+        for day in pushed.completed {
+            let (output, days) = if day.amended {
+                let mut hsi_outputs: Vec<HsiPayload> = Vec::new();
+                for hsi_json in processor.reprocess_day(&day.date, &day.events, timezone)? {
+                    hsi_outputs.push(serde_json::from_str(&hsi_json)?);
+                }
+                let output =
+                    format_output(&hsi_outputs, &output_format, determinism.deterministic)?;
+                (output, hsi_outputs.len())
+            } else {
+                process_event_buffer(
+                    &mut processor,
+                    &day.events,
+                    timezone,
+                    device_id,
+                    &output_format,
+                    determinism.deterministic,
+                    fuse_devices,
+                )?
+            };
+            days_produced += days;
+
+            stdout.write_all(&output)?;
+            if flush {
+                stdout.flush()?;
+            }
+        }
+    }
+
+    // Process days still open at the end of the input
+    for day in watermark.finish() {
+        let (output, days) = process_event_buffer(
+            &mut processor,
+            &day.events,
+            timezone,
+            device_id,
+            &output_format,
+            determinism.deterministic,
+            fuse_devices,
+        )?;
+        days_produced += days;
 
-        // Parse each HSI JSON output
-        for hsi_json in hsi_jsons {
-            let hsi_record: HsiPayload = serde_json::from_str(&hsi_json)?;
-            hsi_outputs.push(hsi_record);
-        }
+        stdout.write_all(&output)?;
     }
+    stdout.flush()?;
 
     // Save baselines if requested
     if let Some(baselines_path) = save_baselines {
-        let baselines_json = processor.save_baselines()?;
-        fs::write(baselines_path, baselines_json)?;
+        write_atomic(baselines_path, processor.save_baselines()?.as_bytes())?;
     }
 
-    // Write output
-    let output_data = format_output(&hsi_outputs, &output_format)?;
+    parse.finish_with_late(
+        events_parsed,
+        days_produced,
+        dropped,
+        Some(late),
+        (processor.take_warnings(), BTreeMap::new()),
+    )
+}
 
-    if output.to_string_lossy() == "-" {
-        print!("{}", output_data);
-    } else {
-        fs::write(output, output_data)?;
+/// How often `flux run` checks for shutdown signals and due checkpoints while idle
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Parse an interval such as `90s`, `5m` or `1h` (bare numbers are seconds)
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let (value, unit) = s
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((s, ""), |i| s.split_at(i));
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("invalid interval '{s}'"))?;
+    let seconds = match unit {
+        "" | "s" => value,
+        "m" => value * 60,
+        "h" => value * 3600,
+        _ => return Err(format!("unknown unit '{unit}' (use s, m or h)")),
+    };
+    if seconds == 0 {
+        return Err("interval must be positive".to_string());
     }
+    Ok(Duration::from_secs(seconds))
+}
 
-    Ok(())
+/// Write a file by writing a temporary sibling and renaming it over the target,
+/// so readers (and crashes) never see a partially written file
+fn write_atomic(path: impl AsRef<std::path::Path>, data: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    let tmp = sibling_path(path, "tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
 }
 
-fn cmd_run(
+/// `path` with `.suffix` appended (`out.ndjson` -> `out.ndjson.suffix`)
+fn sibling_path(path: &std::path::Path, suffix: &str) -> PathBuf {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(".");
+    sibling.push(suffix);
+    PathBuf::from(sibling)
+}
+
+/// Process one day's buffered events, returning the formatted output and the number of days
+fn process_event_buffer(
+    processor: &mut FluxProcessor,
+    events: &[RawEvent],
+    timezone: &str,
+    device_id: &str,
+    output_format: &OutputFormat,
+    sort_keys: bool,
+    fuse_devices: bool,
+) -> Result<(Vec<u8>, usize), FluxCliError> {
+    let canonical_signals = RawEventAdapter::to_canonical(events, timezone, device_id)?;
+    let canonical_signals = fuse_if_requested(canonical_signals, fuse_devices);
+
+    let mut hsi_outputs: Vec<HsiPayload> = Vec::new();
+    for hsi_json in processor.process_canonical(&canonical_signals)? {
+        hsi_outputs.push(serde_json::from_str(&hsi_json)?);
+    }
+
+    let output = format_output(&hsi_outputs, output_format, sort_keys)?;
+    Ok((output, hsi_outputs.len()))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_backfill(
+    input: &PathBuf,
+    output: &PathBuf,
+    input_format: InputFormat,
     output_format: OutputFormat,
+    (from, to): (Option<NaiveDate>, Option<NaiveDate>),
     timezone: &str,
     device_id: &str,
     baseline_days: usize,
     load_baselines: Option<&std::path::Path>,
     save_baselines: Option<&std::path::Path>,
-    flush: bool,
+    dry_run: bool,
+    fuse_devices: bool,
+    determinism: &DeterminismArgs,
+    scrub: &ScrubArgs,
+    progress: &ProgressArgs,
 ) -> Result<(), FluxCliError> {
+    if matches!(output_format, OutputFormat::Canonical) {
+        return Err(canonical_unsupported());
+    }
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(FluxCliError::InvalidArgument(format!(
+                "--from {} is after --to {}",
+                from, to
+            )));
+        }
+    }
+
+    let input_data = read_input(input)?;
+
+    let mut events = match input_format {
+        InputFormat::Ndjson => RawEventAdapter::parse_ndjson(&input_data)?,
+        InputFormat::Json => RawEventAdapter::parse_array(&input_data)?,
+    };
+
+    if events.is_empty() {
+        return Err(FluxCliError::NoEvents);
+    }
+
+    // Process strictly in chronological order
+    events.sort_by_key(|e| e.timestamp);
+
+    let canonical_signals = RawEventAdapter::to_canonical(&events, timezone, device_id)?;
+    let canonical_signals = fuse_if_requested(canonical_signals, fuse_devices);
+
+    // Days before --from are processed without output, so the first days in
+    // range see the same baselines as in a full run
+    let date_of = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+    let total_days = canonical_signals.len();
+    let (warmup, selected): (Vec<_>, Vec<_>) = canonical_signals
+        .into_iter()
+        .filter(|s| date_of(&s.date).is_some_and(|d| to.is_none_or(|to| d <= to)))
+        .partition(|s| date_of(&s.date).is_some_and(|d| from.is_some_and(|from| d < from)));
+
+    if dry_run {
+        let report = BackfillPlan {
+            from: from.map(|d| d.to_string()),
+            to: to.map(|d| d.to_string()),
+            total_events: events.len(),
+            days_in_input: total_days,
+            days_warmup: warmup.len(),
+            days_skipped: total_days - warmup.len() - selected.len(),
+            days: selected
+                .iter()
+                .map(|s| BackfillDay {
+                    date: s.date.clone(),
+                    vendor: s.vendor.as_str().to_string(),
+                })
+                .collect(),
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if selected.is_empty() {
+        return Err(FluxCliError::NoSignals);
+    }
+
     let mut processor = FluxProcessor::with_baseline_window(baseline_days);
+    determinism.apply(&mut processor);
+    scrub.apply(&mut processor);
 
-    // Load existing baselines if provided
     if let Some(baselines_path) = load_baselines {
         let baselines_json = fs::read_to_string(baselines_path)?;
         processor.load_baselines(&baselines_json)?;
     }
+    processor.process_canonical(&warmup)?;
 
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
-    let mut event_buffer: Vec<RawEvent> = Vec::new();
-    let mut current_date: Option<String> = None;
+    let progress = progress.reporter();
+    if let Some(progress) = &progress {
+        progress.on_events_parsed(PipelineKind::Wearable, events.len());
+        progress.add_days(selected.len());
+        progress.apply(&mut processor);
+    }
 
-    for line in stdin.lock().lines() {
-        let line = line?;
-        let trimmed = line.trim();
+    // Baselines are updated day by day as each canonical day is processed
+    let mut hsi_outputs: Vec<HsiPayload> = Vec::new();
+    for hsi_json in processor.process_canonical(&selected)? {
+        hsi_outputs.push(serde_json::from_str(&hsi_json)?);
+    }
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
 
-        if trimmed.is_empty() {
-            continue;
+    if let Some(baselines_path) = save_baselines {
+        let baselines_json = processor.save_baselines()?;
+        fs::write(baselines_path, baselines_json)?;
+    }
+
+    let output_data = format_output(&hsi_outputs, &output_format, determinism.deterministic)?;
+
+    write_output(output, &output_data)?;
+
+    Ok(())
+}
+
+fn cmd_validate(
+    input: &PathBuf,
+    input_format: InputFormat,
+    json: bool,
+) -> Result<(), FluxCliError> {
+    // Read input
+    let input_data = read_input(input)?;
+
+    // Parse events
+    let events = match input_format {
+        InputFormat::Ndjson => RawEventAdapter::parse_ndjson(&input_data)?,
+        InputFormat::Json => RawEventAdapter::parse_array(&input_data)?,
+    };
+
+    // Validate each event
+    let report = ValidationReport::from_events(&events);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Validation Report");
+        println!("=================");
+        println!("Total events:   {}", report.total_events);
+        println!("Valid events:   {}", report.valid_events);
+        println!("Invalid events: {}", report.invalid_events);
+
+        if !report.unmapped_signals.is_empty() {
+            println!("\nUnmapped signals:");
+            for (key, count) in &report.unmapped_signals {
+                println!("  - {key}: {count}");
+            }
         }
 
-        // Parse the event
-        let event: RawEvent = serde_json::from_str(trimmed)
-            .map_err(|e| FluxCliError::ParseError(format!("Failed to parse event: {}", e)))?;
+        if !report.errors.is_empty() {
+            println!("\nErrors:");
+            for err in &report.errors {
+                println!(
+                    "  - Event {} (index {}): {}",
+                    err.event_id.as_deref().unwrap_or("unknown"),
+                    err.index,
+                    err.error
+                );
+            }
+        }
+    }
 
-        // Validate the event
-        event.validate()?;
+    if !report.is_valid() {
+        Err(FluxCliError::ValidationFailed(report.invalid_events))
+    } else {
+        Ok(())
+    }
+}
 
-        // Check if we need to flush the buffer (date changed)
-        let event_date = event.timestamp.format("%Y-%m-%d").to_string();
+fn cmd_validate_hsi(
+    input: &PathBuf,
+    input_format: InputFormat,
+    json: bool,
+) -> Result<(), FluxCliError> {
+    let input_data = read_input(input)?;
+
+    // Parse payloads as untyped JSON so every rule violation can be reported
+    let payloads: Vec<serde_json::Value> = match input_format {
+        InputFormat::Ndjson => input_data
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?,
+        InputFormat::Json => serde_json::from_str(&input_data)?,
+    };
 
-        if let Some(ref date) = current_date {
-            if &event_date != date && !event_buffer.is_empty() {
-                // Process buffered events
-                let output = process_event_buffer(
-                    &mut processor,
-                    &event_buffer,
-                    timezone,
-                    device_id,
-                    &output_format,
-                )?;
+    if payloads.is_empty() {
+        return Err(FluxCliError::NoEvents);
+    }
 
-                write!(stdout, "{}", output)?;
-                if flush {
-                    stdout.flush()?;
-                }
+    let mut errors: Vec<HsiValidationErrorDetail> = Vec::new();
+    let mut invalid_payloads = 0;
+
+    for (index, payload) in payloads.iter().enumerate() {
+        let violations = validate_hsi(payload);
+        if !violations.is_empty() {
+            invalid_payloads += 1;
+        }
+        errors.extend(violations.into_iter().map(|v| HsiValidationErrorDetail {
+            index,
+            path: v.path,
+            error: v.message,
+        }));
+    }
+
+    let report = HsiValidationReport {
+        total_payloads: payloads.len(),
+        valid_payloads: payloads.len() - invalid_payloads,
+        invalid_payloads,
+        errors,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("HSI Validation Report");
+        println!("=====================");
+        println!("Total payloads:   {}", report.total_payloads);
+        println!("Valid payloads:   {}", report.valid_payloads);
+        println!("Invalid payloads: {}", report.invalid_payloads);
 
-                event_buffer.clear();
+        if !report.errors.is_empty() {
+            println!("\nErrors:");
+            for err in &report.errors {
+                println!("  - Payload {} at '{}': {}", err.index, err.path, err.error);
             }
         }
+    }
 
-        current_date = Some(event_date);
-        event_buffer.push(event);
+    if report.invalid_payloads > 0 {
+        Err(FluxCliError::HsiValidationFailed(report.invalid_payloads))
+    } else {
+        Ok(())
     }
+}
 
-    // Process remaining events
-    if !event_buffer.is_empty() {
-        let output = process_event_buffer(
-            &mut processor,
-            &event_buffer,
-            timezone,
-            device_id,
-            &output_format,
-        )?;
+fn cmd_doctor(
+    baselines: Option<&std::path::Path>,
+    sample_input: Option<&std::path::Path>,
+    timezone: &str,
+    json: bool,
+) -> Result<(), FluxCliError> {
+    let mut report = DoctorReport::new();
 
-        write!(stdout, "{}", output)?;
-        stdout.flush()?;
+    // Check baselines file if provided
+    if let Some(baselines_path) = baselines {
+        if baselines_path.exists() {
+            report.add_check(match fs::read_to_string(baselines_path) {
+                Ok(content) => DoctorCheck::baselines(&content),
+                Err(e) => DoctorCheck::new(
+                    "baselines",
+                    CheckStatus::Error,
+                    format!("Cannot read baselines file: {}", e),
+                ),
+            });
+        } else {
+            report.add_check(DoctorCheck::new(
+                "baselines",
+                CheckStatus::Warning,
+                "Baselines file does not exist",
+            ));
+        }
+    }
+
+    // Check stdin is available (for streaming mode)
+    report.add_check(DoctorCheck::new(
+        "stdin",
+        CheckStatus::Ok,
+        if atty::is(atty::Stream::Stdin) {
+            "stdin is a TTY (interactive mode)"
+        } else {
+            "stdin is a pipe (streaming mode ready)"
+        },
+    ));
+
+    if let Some(path) = sample_input {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                let store = baselines
+                    .and_then(|p| fs::read_to_string(p).ok())
+                    .and_then(|json| BaselineStore::from_json(&json).ok())
+                    .unwrap_or_default();
+                report.add_sample(&content, store, timezone);
+            }
+            Err(e) => report.add_check(DoctorCheck::new(
+                "sample_input",
+                CheckStatus::Error,
+                format!("Cannot read sample input: {}", e),
+            )),
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Flux Doctor Report");
+        println!("==================");
+        println!("Producer: {}", report.producer);
+        println!("Version:  {}", report.version);
+        println!("\nChecks:");
+
+        for check in &report.checks {
+            let status_icon = match check.status {
+                CheckStatus::Ok => "[OK]",
+                CheckStatus::Warning => "[WARN]",
+                CheckStatus::Error => "[ERR]",
+            };
+            println!("  {} {}: {}", status_icon, check.name, check.message);
+        }
+
+        if let Some(sample) = &report.sample {
+            println!("\nSample run:");
+            println!(
+                "  Events: {} total, {} parsed, {} dropped",
+                sample.events_total, sample.events_parsed, sample.events_dropped
+            );
+            println!("  Days produced: {}", sample.days_produced);
+            println!("  Stage timings (ms):");
+            for timing in &sample.stage_timings {
+                println!("    {:<10} {:>10.3}", timing.stage, timing.duration_ms);
+            }
+            if !sample.quality_flags.is_empty() {
+                println!("  Quality flags:");
+                for (flag, count) in &sample.quality_flags {
+                    println!("    {}: {} days", flag, count);
+                }
+            }
+            println!("  Baseline delta:");
+            for (metric, delta) in &sample.baseline_delta {
+                println!(
+                    "    {:<14} {} -> {}",
+                    metric,
+                    display_baseline(delta.before),
+                    display_baseline(delta.after)
+                );
+            }
+        }
+    }
+
+    if report.has_errors() {
+        Err(FluxCliError::DoctorFailed)
+    } else {
+        Ok(())
+    }
+}
+
+fn display_baseline(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.1}", v))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_behavior_transform(
+    input: &PathBuf,
+    output: &PathBuf,
+    input_format: InputFormat,
+    output_format: OutputFormat,
+    baseline_sessions: usize,
+    load_baselines: Option<&std::path::Path>,
+    save_baselines: Option<&std::path::Path>,
+    bio_baselines: Option<&std::path::Path>,
+    profile: OutputProfile,
+    config: &BehaviorConfigArgs,
+    scrub: &ScrubArgs,
+) -> Result<(), FluxCliError> {
+    if matches!(output_format, OutputFormat::Fhir) {
+        return Err(FluxCliError::InvalidArgument(
+            "FHIR output is only available for wearable payloads".to_string(),
+        ));
+    }
+    if matches!(output_format, OutputFormat::Canonical) {
+        return Err(canonical_unsupported());
+    }
+
+    let mut processor = BehaviorProcessor::with_baseline_window(baseline_sessions)
+        .with_config(config.to_config())
+        .map_err(|e| FluxCliError::InvalidArgument(e.to_string()))?
+        .with_encoder_config(EncoderConfig::default().with_profile(profile));
+    if let Some(policy) = scrub.policy() {
+        processor.set_privacy_policy(policy);
+    }
+
+    let input_data = read_input(input)?;
+    let sessions = parse_behavior_sessions(&input_data, &input_format)?;
+    if sessions.is_empty() {
+        return Err(FluxCliError::NoEvents);
+    }
+
+    // Load existing baselines if provided
+    if let Some(baselines_path) = load_baselines {
+        let baselines_json = fs::read_to_string(baselines_path)?;
+        processor.load_baselines(&baselines_json)?;
     }
+    if let Some(bio_path) = bio_baselines {
+        let store = BaselineStore::from_json(&fs::read_to_string(bio_path)?)?;
+        processor.set_bio_context(Some(&store));
+    }
+
+    let mut payloads = Vec::with_capacity(sessions.len());
+    for session in &sessions {
+        let hsi_json = processor.process_session(session)?;
+        payloads.push(serde_json::from_str::<serde_json::Value>(&hsi_json)?);
+    }
+
+    let output_data = match output_format {
+        OutputFormat::Cbor => binary_records(&payloads, binary::to_cbor)?,
+        OutputFormat::Msgpack => binary_records(&payloads, binary::to_msgpack)?,
+        OutputFormat::Json => serde_json::to_vec(&payloads)?,
+        OutputFormat::JsonPretty => serde_json::to_vec_pretty(&payloads)?,
+        _ => {
+            let lines = payloads
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<Vec<_>, _>>()?;
+            (lines.join("\n") + "\n").into_bytes()
+        }
+    };
+
+    write_output(output, &output_data)?;
 
     // Save baselines if requested
     if let Some(baselines_path) = save_baselines {
@@ -412,214 +2652,454 @@ fn cmd_run(
     Ok(())
 }
 
-fn process_event_buffer(
-    processor: &mut FluxProcessor,
-    events: &[RawEvent],
+#[allow(clippy::too_many_arguments)]
+fn cmd_inspect(
+    input: &PathBuf,
+    (input_format, vendor): (InputFormat, InputVendor),
+    stage: InspectStage,
+    date: Option<NaiveDate>,
     timezone: &str,
     device_id: &str,
-    output_format: &OutputFormat,
-) -> Result<String, FluxCliError> {
-    let canonical_signals = RawEventAdapter::to_canonical(events, timezone, device_id)?;
+    baseline_days: usize,
+    load_baselines: Option<&std::path::Path>,
+) -> Result<(), FluxCliError> {
+    let input_data = read_input(input)?;
+    let days = match vendor.resolve(&input_data) {
+        Some(Vendor::Whoop) => WhoopAdapter.parse(&input_data, timezone, device_id)?,
+        Some(Vendor::Garmin) => GarminAdapter.parse(&input_data, timezone, device_id)?,
+        None => {
+            let events = match input_format {
+                InputFormat::Ndjson => RawEventAdapter::parse_ndjson(&input_data)?,
+                InputFormat::Json => RawEventAdapter::parse_array(&input_data)?,
+            };
+            RawEventAdapter::to_canonical(&events, timezone, device_id)?
+        }
+    };
+    let date = match (date, days.as_slice()) {
+        (_, []) => return Err(FluxCliError::NoSignals),
+        (Some(date), _) => date.to_string(),
+        (None, [day]) => day.date.clone(),
+        (None, [first, .., last]) => {
+            return Err(FluxCliError::InvalidArgument(format!(
+                "the input spans {} days ({} to {}); pick one with --date",
+                days.len(),
+                first.date,
+                last.date
+            )))
+        }
+    };
+    let Some(end) = days.iter().rposition(|day| day.date == date) else {
+        return Err(FluxCliError::InvalidArgument(format!(
+            "the input has no data for {date}"
+        )));
+    };
 
-    let mut hsi_outputs: Vec<HsiPayload> = Vec::new();
+    let mut processor = FluxProcessor::with_baseline_window(baseline_days);
+    if let Some(path) = load_baselines {
+        processor.load_baselines(&fs::read_to_string(path)?)?;
+    }
+    let staged = processor.process_with_stages(&days[..=end])?;
+    let window = staged.last().ok_or(FluxCliError::NoSignals)?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&stage.select(&window.contextual)?)?
+    );
+    Ok(())
+}
+
+fn cmd_behavior_inspect(
+    input: &PathBuf,
+    input_format: InputFormat,
+    stage: InspectStage,
+    session_id: Option<&str>,
+    baseline_sessions: usize,
+    load_baselines: Option<&std::path::Path>,
+    config: &BehaviorConfigArgs,
+) -> Result<(), FluxCliError> {
+    let sessions = parse_behavior_sessions(&read_input(input)?, &input_format)?;
+    let end = match (session_id, sessions.as_slice()) {
+        (_, []) => return Err(FluxCliError::NoEvents),
+        (Some(id), _) => sessions
+            .iter()
+            .rposition(|s| s.session_id == id)
+            .ok_or_else(|| {
+                FluxCliError::InvalidArgument(format!("the input has no session {id}"))
+            })?,
+        (None, [_]) => 0,
+        (None, _) => {
+            return Err(FluxCliError::InvalidArgument(format!(
+                "the input holds {} sessions; pick one with --session-id",
+                sessions.len()
+            )))
+        }
+    };
 
-    for signals in canonical_signals {
-        let vendor_json = convert_canonical_to_vendor_json(&signals)?;
+    let mut processor = BehaviorProcessor::with_baseline_window(baseline_sessions)
+        .with_config(config.to_config())
+        .map_err(|e| FluxCliError::InvalidArgument(e.to_string()))?;
+    if let Some(path) = load_baselines {
+        processor.load_baselines(&fs::read_to_string(path)?)?;
+    }
+    for session in &sessions[..end] {
+        processor.process_session(session)?;
+    }
+    let window = processor.process_session_with_stages(&sessions[end])?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&stage.select(&window.contextual)?)?
+    );
+    Ok(())
+}
 
-        let hsi_jsons = match signals.vendor {
-            synheart_flux::types::Vendor::Whoop => {
-                processor.process_whoop(&vendor_json, timezone, device_id)?
-            }
-            synheart_flux::types::Vendor::Garmin => {
-                processor.process_garmin(&vendor_json, timezone, device_id)?
+fn parse_behavior_sessions(
+    input: &str,
+    format: &InputFormat,
+) -> Result<Vec<BehaviorSession>, FluxCliError> {
+    match format {
+        InputFormat::Ndjson => input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect(),
+        InputFormat::Json => {
+            let value: serde_json::Value = serde_json::from_str(input)?;
+            if value.is_array() {
+                Ok(serde_json::from_value(value)?)
+            } else {
+                Ok(vec![serde_json::from_value(value)?])
             }
-        };
-
-        for hsi_json in hsi_jsons {
-            let hsi_record: HsiPayload = serde_json::from_str(&hsi_json)?;
-            hsi_outputs.push(hsi_record);
         }
     }
-
-    format_output(&hsi_outputs, output_format)
 }
 
-fn cmd_validate(
+fn cmd_behavior_validate(
     input: &PathBuf,
     input_format: InputFormat,
     json: bool,
 ) -> Result<(), FluxCliError> {
-    // Read input
-    let input_data = if input.to_string_lossy() == "-" {
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        buffer
-    } else {
-        fs::read_to_string(input)?
+    let input_data = read_input(input)?;
+
+    // Parse sessions as untyped JSON so a malformed session is reported
+    // alongside the others instead of aborting the run
+    let sessions: Vec<serde_json::Value> = match input_format {
+        InputFormat::Ndjson => input_data
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?,
+        InputFormat::Json => match serde_json::from_str(&input_data)? {
+            serde_json::Value::Array(sessions) => sessions,
+            session => vec![session],
+        },
     };
 
-    // Parse events
-    let events = match input_format {
-        InputFormat::Ndjson => RawEventAdapter::parse_ndjson(&input_data)?,
-        InputFormat::Json => RawEventAdapter::parse_array(&input_data)?,
-    };
+    if sessions.is_empty() {
+        return Err(FluxCliError::NoEvents);
+    }
 
-    // Validate each event
-    let results = RawEventAdapter::validate_events(&events);
+    let mut errors: Vec<BehaviorValidationErrorDetail> = Vec::new();
+    let mut invalid_sessions = 0;
+
+    for (index, value) in sessions.iter().enumerate() {
+        let session_id = value
+            .get("session_id")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string);
+        let violations = match serde_json::from_value::<BehaviorSession>(value.clone()) {
+            Ok(session) => session.validate(),
+            Err(e) => vec![SessionViolation {
+                path: String::new(),
+                message: e.to_string(),
+            }],
+        };
+        if !violations.is_empty() {
+            invalid_sessions += 1;
+        }
+        errors.extend(
+            violations
+                .into_iter()
+                .map(|v| BehaviorValidationErrorDetail {
+                    index,
+                    session_id: session_id.clone(),
+                    path: v.path,
+                    error: v.message,
+                }),
+        );
+    }
 
-    let report = ValidationReport {
-        total_events: events.len(),
-        valid_events: events.len() - results.len(),
-        invalid_events: results.len(),
-        errors: results
-            .iter()
-            .map(|r| ValidationErrorDetail {
-                index: r.index,
-                event_id: r.event_id.clone(),
-                error: r.result.as_ref().map(|e| e.to_string()).unwrap_or_default(),
-            })
-            .collect(),
+    let report = BehaviorValidationReport {
+        total_sessions: sessions.len(),
+        valid_sessions: sessions.len() - invalid_sessions,
+        invalid_sessions,
+        errors,
     };
 
     if json {
         println!("{}", serde_json::to_string_pretty(&report)?);
     } else {
-        println!("Validation Report");
-        println!("=================");
-        println!("Total events:   {}", report.total_events);
-        println!("Valid events:   {}", report.valid_events);
-        println!("Invalid events: {}", report.invalid_events);
+        println!("Behavior Validation Report");
+        println!("==========================");
+        println!("Total sessions:   {}", report.total_sessions);
+        println!("Valid sessions:   {}", report.valid_sessions);
+        println!("Invalid sessions: {}", report.invalid_sessions);
 
         if !report.errors.is_empty() {
             println!("\nErrors:");
             for err in &report.errors {
                 println!(
-                    "  - Event {} (index {}): {}",
-                    err.event_id.as_deref().unwrap_or("unknown"),
+                    "  - Session {} (index {}) at '{}': {}",
+                    err.session_id.as_deref().unwrap_or("unknown"),
                     err.index,
+                    err.path,
                     err.error
                 );
             }
         }
     }
 
-    if report.invalid_events > 0 {
-        Err(FluxCliError::ValidationFailed(report.invalid_events))
+    if report.invalid_sessions > 0 {
+        Err(FluxCliError::BehaviorValidationFailed(
+            report.invalid_sessions,
+        ))
     } else {
         Ok(())
     }
 }
 
-fn cmd_doctor(baselines: Option<&std::path::Path>, json: bool) -> Result<(), FluxCliError> {
-    let mut checks: Vec<DoctorCheck> = Vec::new();
+fn cmd_generate(
+    kind: GenerateKind,
+    output: &PathBuf,
+    config: SyntheticConfig,
+    provider: &str,
+) -> Result<(), FluxCliError> {
+    let mut generator = Generator::new(config)?;
+    let mut data = match kind {
+        GenerateKind::Whoop => serde_json::to_string_pretty(&generator.whoop_payload())?,
+        GenerateKind::Garmin => serde_json::to_string_pretty(&generator.garmin_payload())?,
+        GenerateKind::RawEvents => ndjson(&generator.raw_events(provider)?)?,
+        GenerateKind::Behavior => ndjson(&generator.behavior_sessions()?)?,
+    };
+    if !data.ends_with('\n') {
+        data.push('\n');
+    }
+    write_output(output, data.as_bytes())
+}
 
-    // Check Flux version
-    checks.push(DoctorCheck {
-        name: "flux_version".to_string(),
-        status: CheckStatus::Ok,
-        message: format!("Flux version {}", FLUX_VERSION),
-    });
+fn ndjson<T: serde::Serialize>(records: &[T]) -> Result<String, FluxCliError> {
+    records
+        .iter()
+        .map(|r| Ok(serde_json::to_string(r)? + "\n"))
+        .collect()
+}
 
-    // Check schema version
-    checks.push(DoctorCheck {
-        name: "schema_version".to_string(),
-        status: CheckStatus::Ok,
-        message: format!("Input schema: {}", SCHEMA_VERSION),
-    });
+fn cmd_conformance(
+    dir: &std::path::Path,
+    config: &ConformanceConfig,
+    bless: bool,
+    json: bool,
+) -> Result<(), FluxCliError> {
+    if bless {
+        let count = conformance::bless(dir, config)?;
+        eprintln!("Wrote expected output for {} fixtures", count);
+        return Ok(());
+    }
 
-    // Check baselines file if provided
-    if let Some(baselines_path) = baselines {
-        if baselines_path.exists() {
-            match fs::read_to_string(baselines_path) {
-                Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
-                    Ok(value) => {
-                        let days = value
-                            .get("baseline_days")
-                            .and_then(|v| v.as_u64())
-                            .unwrap_or(0);
-                        checks.push(DoctorCheck {
-                            name: "baselines".to_string(),
-                            status: CheckStatus::Ok,
-                            message: format!("Baselines file valid ({} days of data)", days),
-                        });
-                    }
-                    Err(e) => {
-                        checks.push(DoctorCheck {
-                            name: "baselines".to_string(),
-                            status: CheckStatus::Error,
-                            message: format!("Invalid baselines JSON: {}", e),
-                        });
-                    }
-                },
-                Err(e) => {
-                    checks.push(DoctorCheck {
-                        name: "baselines".to_string(),
-                        status: CheckStatus::Error,
-                        message: format!("Cannot read baselines file: {}", e),
-                    });
-                }
+    let report = conformance::run_dir(dir, config)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Conformance Report");
+        println!("==================");
+        for case in &report.cases {
+            let status = if case.passed() { "ok" } else { "FAILED" };
+            println!("{:<8} {} ({:?})", status, case.name, case.kind);
+            if let Some(error) = &case.error {
+                println!("    error: {}", error);
+            }
+            for diff in &case.differences {
+                println!(
+                    "    {}: expected {}, got {}",
+                    diff.path,
+                    display_value(diff.expected.as_ref()),
+                    display_value(diff.actual.as_ref())
+                );
             }
-        } else {
-            checks.push(DoctorCheck {
-                name: "baselines".to_string(),
-                status: CheckStatus::Warning,
-                message: "Baselines file does not exist".to_string(),
-            });
         }
     }
 
-    // Check stdin is available (for streaming mode)
-    let stdin_check = if atty::is(atty::Stream::Stdin) {
-        DoctorCheck {
-            name: "stdin".to_string(),
-            status: CheckStatus::Ok,
-            message: "stdin is a TTY (interactive mode)".to_string(),
+    let failed = report.failures().count();
+    if failed > 0 {
+        Err(FluxCliError::ConformanceFailed(failed))
+    } else {
+        Ok(())
+    }
+}
+
+fn cmd_migrate_check(
+    baselines: &PathBuf,
+    fixture: &std::path::Path,
+    config: &ConformanceConfig,
+    json: bool,
+) -> Result<(), FluxCliError> {
+    let fixture = Fixture::from_path(fixture).ok_or_else(|| {
+        FluxCliError::InvalidArgument(format!(
+            "{} is not a fixture input (expected <case>.raw.ndjson, .whoop.json or .garmin.json)",
+            fixture.display()
+        ))
+    })?;
+    let report = conformance::migrate_check(&read_input(baselines)?, &fixture, config)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Migration Check");
+        println!("===============");
+        println!(
+            "Store version:    {} -> {}",
+            report.from_version, report.to_version
+        );
+        println!(
+            "Round trip:       {}",
+            if report.roundtrip_stable {
+                "stable"
+            } else {
+                "UNSTABLE"
+            }
+        );
+        println!("Baseline days:    {}", report.baselines.baseline_days);
+        let status = if report.case.passed() { "ok" } else { "FAILED" };
+        println!(
+            "{:<8} {} ({:?})",
+            status, report.case.name, report.case.kind
+        );
+        if let Some(error) = &report.case.error {
+            println!("    error: {}", error);
+        }
+        for diff in &report.case.differences {
+            println!(
+                "    {}: expected {}, got {}",
+                diff.path,
+                display_value(diff.expected.as_ref()),
+                display_value(diff.actual.as_ref())
+            );
         }
+    }
+
+    if report.passed() {
+        Ok(())
     } else {
-        DoctorCheck {
-            name: "stdin".to_string(),
-            status: CheckStatus::Ok,
-            message: "stdin is a pipe (streaming mode ready)".to_string(),
+        Err(FluxCliError::MigrateCheckFailed)
+    }
+}
+
+fn cmd_rollup(
+    input: &PathBuf,
+    output: &PathBuf,
+    period: RollupPeriod,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<(), FluxCliError> {
+    let payloads: Vec<HsiPayload> = read_input(input)?
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    if payloads.is_empty() {
+        return Err(FluxCliError::NoEvents);
+    }
+
+    // Open-ended bounds default to the first and last dates in the input
+    let dates = payloads
+        .iter()
+        .flat_map(|p| &p.windows)
+        .filter_map(|w| NaiveDate::parse_from_str(&w.date, "%Y-%m-%d").ok());
+    let range = match (from, to) {
+        (None, None) => None,
+        (from, to) => {
+            let (first, last) = dates.fold((None, None), |(first, last), d| {
+                (
+                    Some(first.map_or(d, |f: NaiveDate| f.min(d))),
+                    Some(last.map_or(d, |l: NaiveDate| l.max(d))),
+                )
+            });
+            from.or(first).zip(to.or(last)).map(|(from, to)| from..=to)
         }
     };
-    checks.push(stdin_check);
 
-    let report = DoctorReport {
-        producer: PRODUCER_NAME.to_string(),
-        version: FLUX_VERSION.to_string(),
-        checks,
-    };
+    let mut writer = open_output(output)?;
+    for summary in rollup::rollup(&payloads, period, range)? {
+        writeln!(writer, "{}", serde_json::to_string(&summary)?)?;
+    }
+    writer.flush()?;
+    drop(writer);
+    commit_output(output)
+}
+
+fn cmd_diff(
+    before: &PathBuf,
+    after: &PathBuf,
+    config: &CompareConfig,
+    json: bool,
+) -> Result<(), FluxCliError> {
+    let report = diff::diff_ndjson(&read_input(before)?, &read_input(after)?, config)?;
 
     if json {
         println!("{}", serde_json::to_string_pretty(&report)?);
     } else {
-        println!("Flux Doctor Report");
-        println!("==================");
-        println!("Producer: {}", report.producer);
-        println!("Version:  {}", report.version);
-        println!("\nChecks:");
-
-        for check in &report.checks {
-            let status_icon = match check.status {
-                CheckStatus::Ok => "[OK]",
-                CheckStatus::Warning => "[WARN]",
-                CheckStatus::Error => "[ERR]",
-            };
-            println!("  {} {}: {}", status_icon, check.name, check.message);
+        println!("HSI Diff");
+        println!("========");
+        println!("Windows compared: {}", report.windows_compared);
+        for window in &report.windows_removed {
+            println!("removed  {}", window);
+        }
+        for window in &report.windows_added {
+            println!("added    {}", window);
+        }
+        for change in &report.changes {
+            match change.kind {
+                ChangeKind::Added => println!(
+                    "{} {}: added {}",
+                    change.window,
+                    change.path,
+                    display_value(change.after.as_ref())
+                ),
+                ChangeKind::Removed => println!(
+                    "{} {}: removed {}",
+                    change.window,
+                    change.path,
+                    display_value(change.before.as_ref())
+                ),
+                ChangeKind::Changed => {
+                    let delta = change
+                        .delta
+                        .map(|d| format!(" ({:+})", d))
+                        .unwrap_or_default();
+                    println!(
+                        "{} {}: {} -> {}{}",
+                        change.window,
+                        change.path,
+                        display_value(change.before.as_ref()),
+                        display_value(change.after.as_ref()),
+                        delta
+                    );
+                }
+            }
         }
     }
 
-    let has_errors = report
-        .checks
-        .iter()
-        .any(|c| matches!(c.status, CheckStatus::Error));
-    if has_errors {
-        Err(FluxCliError::DoctorFailed)
-    } else {
+    if report.is_empty() {
         Ok(())
+    } else {
+        Err(FluxCliError::DiffFound(report.difference_count()))
     }
 }
 
+fn display_value(value: Option<&serde_json::Value>) -> String {
+    value.map_or_else(|| "<missing>".to_string(), |v| v.to_string())
+}
+
 fn cmd_schema(schema_type: SchemaType, json_schema: bool) -> Result<(), FluxCliError> {
     match schema_type {
         SchemaType::Input => {
@@ -628,13 +3108,14 @@ fn cmd_schema(schema_type: SchemaType, json_schema: bool) -> Result<(), FluxCliE
             } else {
                 println!("Input Schema: {}", SCHEMA_VERSION);
                 println!();
-                println!("The wear.raw_event.v1 schema supports four record types:");
+                println!("The wear.raw_event.v1 schema supports five record types:");
                 println!();
                 println!("1. signal - Individual point-in-time readings");
                 println!("   - heart_rate, heart_rate_variability, resting_heart_rate");
                 println!("   - respiratory_rate, spo2");
                 println!("   - steps, calories, distance, active_minutes");
                 println!("   - skin_temperature, weight, body_fat");
+                println!("   - cycle_phase (0 menstrual, 1 follicular, 2 ovulatory, 3 luteal)");
                 println!();
                 println!("2. session - Sleep, workout, and other sessions");
                 println!("   - sleep, nap, workout, meditation, recovery");
@@ -648,6 +3129,10 @@ fn cmd_schema(schema_type: SchemaType, json_schema: bool) -> Result<(), FluxCliE
                 println!("   - recovery, strain, sleep, readiness, stress, body_battery");
                 println!("   - Contains value, scale (min/max), and optional components");
                 println!();
+                println!("5. series - Timestamped samples of one signal");
+                println!("   - e.g. per-minute heart_rate, 5-minute heart_rate_variability");
+                println!("   - Contains type, unit, and samples (timestamp, value)");
+                println!();
                 println!("Supported providers: whoop, garmin, apple, oura, fitbit, polar, coros, suunto, samsung, withings");
             }
         }
@@ -662,7 +3147,9 @@ fn cmd_schema(schema_type: SchemaType, json_schema: bool) -> Result<(), FluxCliE
                 println!("- hsi_version: Schema version (1.0.0)");
                 println!("- producer: {{ name, version, instance_id }}");
                 println!("- provenance: {{ source_vendor, source_device_id, timestamps }}");
-                println!("- quality: {{ coverage, freshness_sec, confidence, flags }}");
+                println!(
+                    "- quality: {{ coverage, class_coverage, freshness_sec, confidence, flags }}"
+                );
                 println!("- windows: Array of daily windows containing:");
                 println!("  - date, timezone");
                 println!("  - sleep: {{ duration, efficiency, fragmentation, deep_ratio, rem_ratio, ... }}");
@@ -682,112 +3169,330 @@ fn cmd_schema(schema_type: SchemaType, json_schema: bool) -> Result<(), FluxCliE
 
 // Helper functions
 
+/// Process events partitioned by (user_id, provider) on a thread pool.
+///
+/// Each partition gets its own baseline store and its own ordering check.
+/// Output is merged in date order, ties in partition key order, so it does
+/// not depend on thread scheduling or the job count. With `--fuse-devices`
+/// partitions are per user so devices can still be merged.
+#[allow(clippy::too_many_arguments)]
+fn transform_partitioned(
+    events: Vec<RawEvent>,
+    timezone: &str,
+    device_id: &str,
+    baseline_days: usize,
+    fuse_devices: bool,
+    jobs: usize,
+    order: &OrderArgs,
+    determinism: &DeterminismArgs,
+    scrub: &ScrubArgs,
+    content: &ContentArgs,
+    progress: Option<&Arc<ProgressReporter>>,
+) -> Result<(Vec<HsiPayload>, Vec<Warning>), FluxCliError> {
+    let mut partitions: BTreeMap<(String, String), Vec<RawEvent>> = BTreeMap::new();
+    for event in events {
+        let user = event.user_id.clone().unwrap_or_default();
+        let provider = if fuse_devices {
+            String::new()
+        } else {
+            event.source.provider.as_str().to_string()
+        };
+        partitions.entry((user, provider)).or_default().push(event);
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(|e| FluxCliError::InvalidArgument(format!("failed to start {jobs} jobs: {e}")))?;
+
+    let results: Vec<Result<ProcessOutput, FluxCliError>> = pool.install(|| {
+        partitions
+            .into_par_iter()
+            .map(|(_, events)| {
+                order.check(&events)?;
+                let canonical_signals =
+                    RawEventAdapter::to_canonical(&events, timezone, device_id)?;
+                let canonical_signals = fuse_if_requested(canonical_signals, fuse_devices);
+
+                let mut processor = FluxProcessor::with_baseline_window(baseline_days);
+                determinism.apply(&mut processor);
+                scrub.apply(&mut processor);
+                content.apply(&mut processor);
+                if let Some(progress) = progress {
+                    progress.add_days(canonical_signals.len());
+                    progress.apply(&mut processor);
+                }
+                let payloads = processor.process_canonical(&canonical_signals)?;
+                Ok(processor.take_output(payloads))
+            })
+            .collect()
+    });
+
+    let mut payloads = Vec::new();
+    let mut warnings = Vec::new();
+    for result in results {
+        let partition = result?;
+        for hsi_json in partition.payloads {
+            payloads.push(serde_json::from_str::<HsiPayload>(&hsi_json)?);
+        }
+        warnings.extend(partition.warnings);
+    }
+
+    if payloads.is_empty() {
+        return Err(FluxCliError::NoSignals);
+    }
+    // Stable, so same-day payloads keep partition key order
+    payloads.sort_by_key(|p| p.windows.first().map(|w| w.date.clone()));
+    Ok((payloads, warnings))
+}
+
+fn fuse_if_requested(
+    signals: Vec<CanonicalWearSignals>,
+    fuse_devices: bool,
+) -> Vec<CanonicalWearSignals> {
+    if fuse_devices {
+        DeviceFusion::new().fuse(&signals)
+    } else {
+        signals
+    }
+}
+
+fn write_output(output: &PathBuf, data: &[u8]) -> Result<(), FluxCliError> {
+    if output.to_string_lossy() == "-" {
+        let mut stdout = io::stdout();
+        stdout.write_all(data)?;
+        stdout.flush()?;
+    } else {
+        write_atomic(output, data)?;
+    }
+    Ok(())
+}
+
+fn read_input(input: &PathBuf) -> Result<String, FluxCliError> {
+    if input.to_string_lossy() == "-" {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        Ok(buffer)
+    } else {
+        Ok(fs::read_to_string(input)?)
+    }
+}
+
+/// Error for commands that only write HSI
+fn canonical_unsupported() -> FluxCliError {
+    FluxCliError::InvalidArgument(
+        "canonical output is only available from flux transform".to_string(),
+    )
+}
+
+/// Format canonical days (keys always sorted)
+fn format_canonical(
+    days: &[CanonicalWearSignals],
+    format: &OutputFormat,
+) -> Result<Vec<u8>, FluxCliError> {
+    // serde_json::Value objects are ordered maps, so converting sorts every key
+    let records = days
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    let data = match format {
+        OutputFormat::Json => serde_json::to_vec(&records)?,
+        OutputFormat::JsonPretty => serde_json::to_vec_pretty(&records)?,
+        OutputFormat::Cbor => binary_records(&records, binary::to_cbor)?,
+        OutputFormat::Msgpack => binary_records(&records, binary::to_msgpack)?,
+        OutputFormat::Fhir => {
+            return Err(FluxCliError::InvalidArgument(
+                "FHIR output is only available for HSI payloads".to_string(),
+            ))
+        }
+        OutputFormat::Ndjson | OutputFormat::Canonical => {
+            let mut lines = String::new();
+            for record in &records {
+                lines.push_str(&serde_json::to_string(record)?);
+                lines.push('\n');
+            }
+            lines.into_bytes()
+        }
+    };
+    Ok(data)
+}
+
+/// Format `{"hsi", "trace"}` explain records (keys always sorted)
+fn format_explained(
+    traced: &[TracedPayload],
+    format: &OutputFormat,
+) -> Result<Vec<u8>, FluxCliError> {
+    let records = traced
+        .iter()
+        .map(|t| {
+            let hsi: serde_json::Value = serde_json::from_str(&t.hsi)?;
+            Ok(serde_json::json!({ "hsi": hsi, "trace": t.trace }))
+        })
+        .collect::<Result<Vec<_>, FluxCliError>>()?;
+
+    let text = match format {
+        OutputFormat::Json => serde_json::to_string(&records)?,
+        OutputFormat::JsonPretty => serde_json::to_string_pretty(&records)?,
+        _ => {
+            let mut lines = Vec::new();
+            for record in &records {
+                lines.push(serde_json::to_string(record)?);
+            }
+            lines.join("\n") + "\n"
+        }
+    };
+    Ok(text.into_bytes())
+}
+
 fn format_output(
     hsi_outputs: &[HsiPayload],
     format: &OutputFormat,
-) -> Result<String, FluxCliError> {
-    match format {
-        OutputFormat::Ndjson => {
-            let mut lines: Vec<String> = Vec::new();
-            for hsi in hsi_outputs {
-                lines.push(serde_json::to_string(hsi)?);
-            }
-            Ok(lines.join("\n") + "\n")
-        }
-        OutputFormat::Json => Ok(serde_json::to_string(hsi_outputs)?),
-        OutputFormat::JsonPretty => Ok(serde_json::to_string_pretty(hsi_outputs)?),
-    }
-}
-
-fn convert_canonical_to_vendor_json(
-    signals: &synheart_flux::types::CanonicalWearSignals,
-) -> Result<String, FluxCliError> {
-    // Convert canonical signals back to vendor format for processing
-    // This is a temporary bridge until we refactor the pipeline
-
-    match signals.vendor {
-        synheart_flux::types::Vendor::Whoop => {
-            let payload = serde_json::json!({
-                "sleep": [{
-                    "id": 1,
-                    "start": signals.sleep.start_time.map(|t| t.to_rfc3339()).unwrap_or_default(),
-                    "end": signals.sleep.end_time.map(|t| t.to_rfc3339()).unwrap_or_default(),
-                    "score": {
-                        "stage_summary": {
-                            "total_in_bed_time_milli": signals.sleep.time_in_bed_minutes.map(|m| (m * 60_000.0) as i64).unwrap_or(0),
-                            "total_awake_time_milli": signals.sleep.awake_minutes.map(|m| (m * 60_000.0) as i64).unwrap_or(0),
-                            "total_light_sleep_time_milli": signals.sleep.light_sleep_minutes.map(|m| (m * 60_000.0) as i64).unwrap_or(0),
-                            "total_slow_wave_sleep_time_milli": signals.sleep.deep_sleep_minutes.map(|m| (m * 60_000.0) as i64).unwrap_or(0),
-                            "total_rem_sleep_time_milli": signals.sleep.rem_sleep_minutes.map(|m| (m * 60_000.0) as i64).unwrap_or(0),
-                            "total_sleep_time_milli": signals.sleep.total_sleep_minutes.map(|m| (m * 60_000.0) as i64).unwrap_or(0),
-                            "disturbance_count": signals.sleep.awakenings.unwrap_or(0)
-                        },
-                        "sleep_performance_percentage": signals.sleep.vendor_sleep_score,
-                        "respiratory_rate": signals.sleep.respiratory_rate
-                    }
-                }],
-                "recovery": [{
-                    "cycle_id": 1,
-                    "created_at": signals.observed_at.to_rfc3339(),
-                    "score": {
-                        "recovery_score": signals.recovery.vendor_recovery_score,
-                        "resting_heart_rate": signals.recovery.resting_hr_bpm,
-                        "hrv_rmssd_milli": signals.recovery.hrv_rmssd_ms,
-                        "spo2_percentage": signals.recovery.spo2_percentage,
-                        "skin_temp_celsius": signals.recovery.skin_temp_deviation_c
-                    }
-                }],
-                "cycle": [{
-                    "id": 1,
-                    "start": signals.sleep.end_time.map(|t| t.to_rfc3339()).unwrap_or_default(),
-                    "end": signals.observed_at.to_rfc3339(),
-                    "score": {
-                        "strain": signals.activity.vendor_strain_score,
-                        "kilojoule": signals.activity.calories.map(|c| c / 0.239006),
-                        "average_heart_rate": signals.activity.average_hr_bpm,
-                        "max_heart_rate": signals.activity.max_hr_bpm
-                    }
-                }]
-            });
-            Ok(serde_json::to_string(&payload)?)
-        }
-        synheart_flux::types::Vendor::Garmin => {
-            let payload = serde_json::json!({
-                "dailies": [{
-                    "calendarDate": signals.date,
-                    "totalSteps": signals.activity.steps,
-                    "totalDistanceMeters": signals.activity.distance_meters.map(|d| d as i64),
-                    "totalKilocalories": signals.activity.calories.map(|c| c as i32),
-                    "activeKilocalories": signals.activity.active_calories.map(|c| c as i32),
-                    "restingHeartRate": signals.recovery.resting_hr_bpm.map(|h| h as i32),
-                    "restingHeartRateHrv": signals.recovery.hrv_rmssd_ms,
-                    "averageHeartRate": signals.activity.average_hr_bpm.map(|h| h as i32),
-                    "maxHeartRate": signals.activity.max_hr_bpm.map(|h| h as i32),
-                    "avgSpo2Value": signals.recovery.spo2_percentage,
-                    "bodyBatteryChargedValue": signals.recovery.vendor_recovery_score.map(|r| r as i32),
-                    "trainingLoadBalance": signals.activity.vendor_strain_score,
-                    "moderateIntensityMinutes": signals.activity.active_minutes.map(|m| (m * 0.7) as i32),
-                    "vigorousIntensityMinutes": signals.activity.active_minutes.map(|m| (m * 0.3) as i32)
-                }],
-                "sleep": [{
-                    "calendarDate": signals.date,
-                    "sleepStartTimestampGmt": signals.sleep.start_time.map(|t| t.timestamp_millis()),
-                    "sleepEndTimestampGmt": signals.sleep.end_time.map(|t| t.timestamp_millis()),
-                    "sleepTimeSeconds": signals.sleep.total_sleep_minutes.map(|m| (m * 60.0) as i64),
-                    "awakeSleepSeconds": signals.sleep.awake_minutes.map(|m| (m * 60.0) as i64),
-                    "lightSleepSeconds": signals.sleep.light_sleep_minutes.map(|m| (m * 60.0) as i64),
-                    "deepSleepSeconds": signals.sleep.deep_sleep_minutes.map(|m| (m * 60.0) as i64),
-                    "remSleepSeconds": signals.sleep.rem_sleep_minutes.map(|m| (m * 60.0) as i64),
-                    "awakeCount": signals.sleep.awakenings,
-                    "avgSleepRespiration": signals.sleep.respiratory_rate,
-                    "sleepScores": {
-                        "overallScore": signals.sleep.vendor_sleep_score
-                    }
-                }]
-            });
-            Ok(serde_json::to_string(&payload)?)
+    sort_keys: bool,
+) -> Result<Vec<u8>, FluxCliError> {
+    let mut writer = PayloadWriter::new(Vec::new(), format, sort_keys);
+    for hsi in hsi_outputs {
+        writer.write(hsi)?;
+    }
+    writer.finish()
+}
+
+/// Writes HSI payloads to an output as they are produced.
+///
+/// NDJSON, FHIR and binary records are written independently. JSON arrays are
+/// opened by the first payload and closed by [`PayloadWriter::finish`], so no
+/// format needs the whole result set in memory.
+struct PayloadWriter<W: Write> {
+    out: W,
+    format: OutputFormat,
+    sort_keys: bool,
+    count: usize,
+}
+
+impl<W: Write> PayloadWriter<W> {
+    fn new(out: W, format: &OutputFormat, sort_keys: bool) -> Self {
+        Self {
+            out,
+            format: format.clone(),
+            sort_keys,
+            count: 0,
         }
     }
+
+    /// Continue a stream that already holds `count` payloads
+    fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Number of payloads written so far
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn write(&mut self, hsi: &HsiPayload) -> Result<(), FluxCliError> {
+        match self.format {
+            OutputFormat::Cbor => self.out.write_all(&binary::to_cbor(hsi)?)?,
+            OutputFormat::Msgpack => self.out.write_all(&binary::to_msgpack(hsi)?)?,
+            OutputFormat::Ndjson => {
+                let line = self.to_json(hsi, false)?;
+                writeln!(self.out, "{line}")?;
+            }
+            OutputFormat::Json => {
+                let record = self.to_json(hsi, false)?;
+                let separator = if self.count == 0 { "[" } else { "," };
+                write!(self.out, "{separator}{record}")?;
+            }
+            OutputFormat::JsonPretty => {
+                // Indent each record one level, as inside a pretty-printed array
+                let record = self.to_json(hsi, true)?.replace('\n', "\n  ");
+                let separator = if self.count == 0 { "[\n  " } else { ",\n  " };
+                write!(self.out, "{separator}{record}")?;
+            }
+            OutputFormat::Fhir => {
+                for observation in fhir::payload_to_observations(hsi, None) {
+                    writeln!(self.out, "{}", serde_json::to_string(&observation)?)?;
+                }
+            }
+            OutputFormat::Canonical => return Err(canonical_unsupported()),
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn to_json(&self, hsi: &HsiPayload, pretty: bool) -> Result<String, FluxCliError> {
+        // serde_json::Value objects are ordered maps, so converting sorts every key
+        let text = match (self.sort_keys, pretty) {
+            (true, false) => serde_json::to_string(&serde_json::to_value(hsi)?)?,
+            (true, true) => serde_json::to_string_pretty(&serde_json::to_value(hsi)?)?,
+            (false, false) => serde_json::to_string(hsi)?,
+            (false, true) => serde_json::to_string_pretty(hsi)?,
+        };
+        Ok(text)
+    }
+
+    fn flush(&mut self) -> Result<(), FluxCliError> {
+        Ok(self.out.flush()?)
+    }
+
+    /// Close any open JSON array and flush, returning the underlying writer
+    fn finish(mut self) -> Result<W, FluxCliError> {
+        match (&self.format, self.count) {
+            (OutputFormat::Json | OutputFormat::JsonPretty, 0) => self.out.write_all(b"[]")?,
+            (OutputFormat::Json, _) => self.out.write_all(b"]")?,
+            (OutputFormat::JsonPretty, _) => self.out.write_all(b"\n]")?,
+            _ => {}
+        }
+        self.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Open the output for streaming (`-` for stdout)
+///
+/// Files are written as `<OUTPUT>.partial` and only take their real name in
+/// [`commit_output`], so an interrupted run never leaves a truncated output.
+fn open_output(output: &PathBuf) -> Result<Box<dyn Write>, FluxCliError> {
+    if output.to_string_lossy() == "-" {
+        Ok(Box::new(io::BufWriter::new(io::stdout())))
+    } else {
+        let file = fs::File::create(partial_path(output))?;
+        Ok(Box::new(io::BufWriter::new(file)))
+    }
+}
+
+/// Reopen a partial output to continue it after its first `bytes` bytes
+fn reopen_output(output: &PathBuf, bytes: u64) -> Result<Box<dyn Write>, FluxCliError> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .open(partial_path(output))?;
+    file.set_len(bytes)?;
+    file.seek(io::SeekFrom::End(0))?;
+    Ok(Box::new(io::BufWriter::new(file)))
+}
+
+/// Where [`open_output`] writes a file output until it is complete
+fn partial_path(output: impl AsRef<std::path::Path>) -> PathBuf {
+    sibling_path(output.as_ref(), "partial")
+}
+
+/// Give a completed output opened with [`open_output`] its real name
+fn commit_output(output: &PathBuf) -> Result<(), FluxCliError> {
+    if output.to_string_lossy() != "-" {
+        fs::rename(partial_path(output), output)?;
+    }
+    Ok(())
+}
+
+/// Concatenate binary-encoded records (a CBOR sequence or MessagePack stream)
+fn binary_records<T: serde::Serialize>(
+    records: &[T],
+    encode: fn(&T) -> Result<Vec<u8>, synheart_flux::ComputeError>,
+) -> Result<Vec<u8>, FluxCliError> {
+    let mut bytes = Vec::new();
+    for record in records {
+        bytes.extend(encode(record)?);
+    }
+    Ok(bytes)
 }
 
 fn get_input_json_schema() -> String {
@@ -818,7 +3523,7 @@ fn get_input_json_schema() -> String {
             "user_id": { "type": "string" },
             "record_type": {
                 "type": "string",
-                "enum": ["signal", "session", "summary", "score"]
+                "enum": ["signal", "session", "summary", "score", "series"]
             },
             "payload": { "type": "object" },
             "context": {
@@ -867,7 +3572,12 @@ fn get_output_json_schema() -> String {
                 "type": "object",
                 "properties": {
                     "coverage": { "type": "number" },
+                    "class_coverage": { "type": "number" },
                     "freshness_sec": { "type": "integer" },
+                    "domain_freshness_sec": {
+                        "type": "object",
+                        "additionalProperties": { "type": "integer" }
+                    },
                     "confidence": { "type": "number" },
                     "flags": { "type": "array", "items": { "type": "string" } }
                 }
@@ -892,8 +3602,13 @@ enum FluxCliError {
     NoEvents,
     NoSignals,
     ValidationFailed(usize),
+    HsiValidationFailed(usize),
+    BehaviorValidationFailed(usize),
+    ConformanceFailed(usize),
+    DiffFound(usize),
+    MigrateCheckFailed,
     DoctorFailed,
-    ParseError(String),
+    InvalidArgument(String),
 }
 
 impl From<io::Error> for FluxCliError {
@@ -938,7 +3653,7 @@ impl From<FluxCliError> for CliError {
             FluxCliError::Parse(e) => CliError {
                 code: "PARSE_ERROR".to_string(),
                 message: e.to_string(),
-                hint: Some("Ensure input matches wear.raw_event.v1 schema".to_string()),
+                hint: Some("Ensure input matches the wear.raw_event.v1 or v2 schema".to_string()),
             },
             FluxCliError::Json(e) => CliError {
                 code: "JSON_ERROR".to_string(),
@@ -967,15 +3682,46 @@ impl From<FluxCliError> for CliError {
                 message: format!("{} events failed validation", count),
                 hint: Some("Fix validation errors and retry".to_string()),
             },
+            FluxCliError::HsiValidationFailed(count) => CliError {
+                code: "HSI_VALIDATION_FAILED".to_string(),
+                message: format!("{} HSI payloads failed validation", count),
+                hint: Some("Run 'flux validate-hsi --json' for details".to_string()),
+            },
+            FluxCliError::BehaviorValidationFailed(count) => CliError {
+                code: "BEHAVIOR_VALIDATION_FAILED".to_string(),
+                message: format!("{} behavioral sessions failed validation", count),
+                hint: Some("Run 'flux behavior validate --json' for details".to_string()),
+            },
+            FluxCliError::ConformanceFailed(count) => CliError {
+                code: "CONFORMANCE_FAILED".to_string(),
+                message: format!("{} fixtures did not match expected output", count),
+                hint: Some(
+                    "Review the differences, or re-run with --bless if the change is intended"
+                        .to_string(),
+                ),
+            },
+            FluxCliError::DiffFound(count) => CliError {
+                code: "DIFF_FOUND".to_string(),
+                message: format!("{} differences between the HSI outputs", count),
+                hint: Some(
+                    "Raise --tolerance or add --ignore paths for expected changes".to_string(),
+                ),
+            },
+            FluxCliError::MigrateCheckFailed => CliError {
+                code: "MIGRATE_CHECK_FAILED".to_string(),
+                message: "The migrated baseline store does not reproduce the expected output"
+                    .to_string(),
+                hint: Some("Review the reported differences before upgrading stores".to_string()),
+            },
             FluxCliError::DoctorFailed => CliError {
                 code: "DOCTOR_FAILED".to_string(),
                 message: "One or more health checks failed".to_string(),
                 hint: Some("Review the doctor report for details".to_string()),
             },
-            FluxCliError::ParseError(msg) => CliError {
-                code: "PARSE_ERROR".to_string(),
+            FluxCliError::InvalidArgument(msg) => CliError {
+                code: "INVALID_ARGUMENT".to_string(),
                 message: msg,
-                hint: Some("Check input format".to_string()),
+                hint: Some("Run with --help for usage".to_string()),
             },
         }
     }
@@ -983,38 +3729,76 @@ impl From<FluxCliError> for CliError {
 
 // Report types
 
+/// Summary of a transform/run invocation, written with `--report`
+#[derive(serde::Serialize)]
+struct ProcessingReport {
+    parse_mode: ParseMode,
+    events_parsed: usize,
+    days_produced: usize,
+    dropped_events: DroppedEventsReport,
+    /// Events that arrived after their day completed (`flux run` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    late_events: Option<DroppedEventsReport>,
+    /// Input adjusted or left out without failing processing
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<Warning>,
+    /// Readings with no canonical field, by `signal.<type>`, `summary.<metric>`
+    /// or `score.<type>` key (`flux transform` only)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    unmapped_signals: BTreeMap<String, usize>,
+}
+
+#[derive(serde::Serialize)]
+struct DroppedEventsReport {
+    count: usize,
+    events: Vec<DroppedEvent>,
+}
+
 #[derive(serde::Serialize)]
-struct ValidationReport {
+struct BackfillPlan {
+    from: Option<String>,
+    to: Option<String>,
     total_events: usize,
-    valid_events: usize,
-    invalid_events: usize,
-    errors: Vec<ValidationErrorDetail>,
+    days_in_input: usize,
+    /// Days before `--from`, processed only to warm up baselines
+    days_warmup: usize,
+    days_skipped: usize,
+    days: Vec<BackfillDay>,
 }
 
 #[derive(serde::Serialize)]
-struct ValidationErrorDetail {
-    index: usize,
-    event_id: Option<String>,
-    error: String,
+struct BackfillDay {
+    date: String,
+    vendor: String,
 }
 
 #[derive(serde::Serialize)]
-struct DoctorReport {
-    producer: String,
-    version: String,
-    checks: Vec<DoctorCheck>,
+struct HsiValidationReport {
+    total_payloads: usize,
+    valid_payloads: usize,
+    invalid_payloads: usize,
+    errors: Vec<HsiValidationErrorDetail>,
 }
 
 #[derive(serde::Serialize)]
-struct DoctorCheck {
-    name: String,
-    status: CheckStatus,
-    message: String,
+struct HsiValidationErrorDetail {
+    index: usize,
+    path: String,
+    error: String,
 }
 
 #[derive(serde::Serialize)]
-enum CheckStatus {
-    Ok,
-    Warning,
-    Error,
+struct BehaviorValidationReport {
+    total_sessions: usize,
+    valid_sessions: usize,
+    invalid_sessions: usize,
+    errors: Vec<BehaviorValidationErrorDetail>,
+}
+
+#[derive(serde::Serialize)]
+struct BehaviorValidationErrorDetail {
+    index: usize,
+    session_id: Option<String>,
+    path: String,
+    error: String,
 }
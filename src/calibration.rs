@@ -0,0 +1,233 @@
+//! Cross-vendor HRV calibration
+//!
+//! Vendors measure HRV differently: WHOOP reports RMSSD from the last slow-wave
+//! sleep stage, Garmin an overnight average. Mixing them in one baseline turns
+//! a device switch into a bogus deviation. Calibration maps each vendor's HRV
+//! onto a reference vendor's scale with a linear `scale * hrv + offset_ms`,
+//! either configured or fitted on days both vendors reported.
+
+use crate::baseline::linear_slope;
+use crate::types::{CanonicalWearSignals, Vendor};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Overlapping days needed before a calibration is fitted
+pub const MIN_CALIBRATION_DAYS: usize = 5;
+
+/// Most recent dates kept for fitting calibrations
+pub const MAX_CALIBRATION_DAYS: usize = 90;
+
+/// Field key of HRV in `field_sources` and `intraday`
+const HRV_FIELD: &str = "hrv_rmssd_ms";
+
+/// Linear map from one vendor's HRV onto the reference vendor's
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HrvCalibration {
+    pub scale: f64,
+    pub offset_ms: f64,
+}
+
+impl HrvCalibration {
+    /// Calibration that leaves values unchanged
+    pub const IDENTITY: Self = Self {
+        scale: 1.0,
+        offset_ms: 0.0,
+    };
+
+    pub fn new(scale: f64, offset_ms: f64) -> Self {
+        Self { scale, offset_ms }
+    }
+
+    /// Map a vendor HRV value (ms) onto the reference scale
+    pub fn apply(&self, hrv_ms: f64) -> f64 {
+        self.scale * hrv_ms + self.offset_ms
+    }
+
+    /// Least-squares fit of reference HRV on vendor HRV over `(vendor, reference)` pairs.
+    ///
+    /// Needs [`MIN_CALIBRATION_DAYS`] pairs. When the fitted slope is not
+    /// positive, or vendor HRV does not vary, only an offset is fitted.
+    pub fn fit(pairs: &[(f64, f64)]) -> Option<Self> {
+        if pairs.len() < MIN_CALIBRATION_DAYS {
+            return None;
+        }
+        let n = pairs.len() as f64;
+        let mean_vendor = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_reference = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let scale = linear_slope(pairs)
+            .filter(|slope| *slope > 0.0)
+            .unwrap_or(1.0);
+        Some(Self::new(scale, mean_reference - scale * mean_vendor))
+    }
+}
+
+/// HRV values per date and vendor, for fitting calibrations
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HrvOverlaps(BTreeMap<String, BTreeMap<Vendor, f64>>);
+
+impl HrvOverlaps {
+    /// Record each day's uncalibrated HRV under the vendor that supplied it
+    pub fn record(&mut self, days: &[CanonicalWearSignals]) {
+        for day in days {
+            if let Some(hrv) = day.recovery.hrv_rmssd_ms {
+                self.0
+                    .entry(day.date.clone())
+                    .or_default()
+                    .insert(day.source_of(HRV_FIELD), hrv);
+            }
+        }
+        while self.0.len() > MAX_CALIBRATION_DAYS {
+            self.0.pop_first();
+        }
+    }
+
+    /// `(vendor, reference)` HRV pairs from dates both vendors reported
+    pub fn pairs(&self, vendor: Vendor, reference: Vendor) -> Vec<(f64, f64)> {
+        self.0
+            .values()
+            .filter_map(|by_vendor| Some((*by_vendor.get(&vendor)?, *by_vendor.get(&reference)?)))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Which vendor's HRV scale to use, and any fixed calibrations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HrvCalibrationConfig {
+    /// Vendor whose HRV scale other vendors are mapped onto
+    pub reference: Vendor,
+    /// Configured calibrations, used instead of fitted ones
+    #[serde(default)]
+    pub fixed: BTreeMap<Vendor, HrvCalibration>,
+}
+
+impl HrvCalibrationConfig {
+    pub fn new(reference: Vendor) -> Self {
+        Self {
+            reference,
+            fixed: BTreeMap::new(),
+        }
+    }
+
+    /// Use a fixed calibration for `vendor` instead of fitting one
+    pub fn with_calibration(mut self, vendor: Vendor, calibration: HrvCalibration) -> Self {
+        self.fixed.insert(vendor, calibration);
+        self
+    }
+
+    /// Calibration for a vendor's HRV, if one is configured or can be fitted
+    pub fn calibration_for(
+        &self,
+        vendor: Vendor,
+        overlaps: &HrvOverlaps,
+    ) -> Option<HrvCalibration> {
+        if vendor == self.reference {
+            return Some(HrvCalibration::IDENTITY);
+        }
+        self.fixed
+            .get(&vendor)
+            .copied()
+            .or_else(|| HrvCalibration::fit(&overlaps.pairs(vendor, self.reference)))
+    }
+
+    /// Map a day's HRV onto the reference scale and record the scale it ends up in.
+    ///
+    /// Without a calibration for its vendor, HRV keeps that vendor's scale and
+    /// is only compared with baseline days on the same scale.
+    pub fn calibrate(
+        &self,
+        day: &CanonicalWearSignals,
+        overlaps: &HrvOverlaps,
+    ) -> CanonicalWearSignals {
+        let mut day = day.clone();
+        let Some(hrv) = day.recovery.hrv_rmssd_ms else {
+            return day;
+        };
+        let source = day.source_of(HRV_FIELD);
+        match self.calibration_for(source, overlaps) {
+            Some(calibration) => {
+                day.recovery.hrv_rmssd_ms = Some(calibration.apply(hrv));
+                if let Some(samples) = day.intraday.get_mut(HRV_FIELD) {
+                    for sample in samples {
+                        sample.value = calibration.apply(sample.value);
+                    }
+                }
+                day.recovery.hrv_scale = Some(self.reference);
+            }
+            None => day.recovery.hrv_scale = Some(source),
+        }
+        day
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CanonicalRecovery;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn day(date: &str, vendor: Vendor, hrv: f64) -> CanonicalWearSignals {
+        CanonicalWearSignals {
+            vendor,
+            date: date.to_string(),
+            device_id: "dev".to_string(),
+            user_id: None,
+            timezone: "UTC".to_string(),
+            utc_offset_minutes: None,
+            observed_at: Utc::now(),
+            sleep: Default::default(),
+            recovery: CanonicalRecovery {
+                hrv_rmssd_ms: Some(hrv),
+                ..Default::default()
+            },
+            activity: Default::default(),
+            body: Default::default(),
+            vendor_raw: HashMap::new(),
+            field_sources: HashMap::new(),
+            field_events: HashMap::new(),
+            intraday: BTreeMap::new(),
+            latest_event_at: BTreeMap::new(),
+            tags: vec![],
+            warnings: Vec::new(),
+            unmapped_signals: BTreeMap::new(),
+            input_quality: None,
+        }
+    }
+
+    #[test]
+    fn test_calibration_fitted_from_overlapping_days() {
+        let config = HrvCalibrationConfig::new(Vendor::Whoop);
+        let mut overlaps = HrvOverlaps::default();
+        let garmin_day = day("2024-01-20", Vendor::Garmin, 50.0);
+
+        // Garmin reads 0.8x WHOOP minus 4 ms
+        for (i, whoop) in [60.0, 70.0, 65.0, 80.0].into_iter().enumerate() {
+            let date = format!("2024-01-{:02}", i + 1);
+            overlaps.record(&[
+                day(&date, Vendor::Whoop, whoop),
+                day(&date, Vendor::Garmin, whoop * 0.8 - 4.0),
+            ]);
+        }
+        let uncalibrated = config.calibrate(&garmin_day, &overlaps);
+        assert_eq!(uncalibrated.recovery.hrv_rmssd_ms, Some(50.0));
+        assert_eq!(uncalibrated.recovery.hrv_scale, Some(Vendor::Garmin));
+
+        overlaps.record(&[
+            day("2024-01-05", Vendor::Whoop, 90.0),
+            day("2024-01-05", Vendor::Garmin, 68.0),
+        ]);
+        let calibrated = config.calibrate(&garmin_day, &overlaps);
+        assert!((calibrated.recovery.hrv_rmssd_ms.unwrap() - 67.5).abs() < 1e-9);
+        assert_eq!(calibrated.recovery.hrv_scale, Some(Vendor::Whoop));
+
+        // A configured calibration wins over the fitted one
+        let fixed = config.with_calibration(Vendor::Garmin, HrvCalibration::new(1.0, 10.0));
+        let calibrated = fixed.calibrate(&garmin_day, &overlaps);
+        assert_eq!(calibrated.recovery.hrv_rmssd_ms, Some(60.0));
+    }
+}
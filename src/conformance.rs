@@ -0,0 +1,636 @@
+//! Golden fixture conformance harness
+//!
+//! Runs a directory of input/expected-output fixture pairs through the
+//! pipeline and reports every difference, so ports of Flux calculations
+//! (native SDKs, server reimplementations) can be checked against the
+//! reference implementation mechanically.
+//!
+//! A fixture is a pair of files sharing a case name:
+//!
+//! ```text
+//! fixtures/
+//!   short_sleep.whoop.json         WHOOP API payload
+//!   short_sleep.expected.ndjson    one HSI payload per line, in output order
+//!   busy_day.garmin.json           Garmin Health API payload
+//!   busy_day.expected.ndjson
+//!   week.raw.ndjson                wear.raw_event.v1 events
+//!   week.expected.ndjson
+//!   sessions.behavior.ndjson       one behavioral session per line
+//!   sessions.expected.ndjson
+//! ```
+//!
+//! Each case runs on a fresh processor, so baselines build up only within the
+//! case. Wearable cases run in deterministic mode. Numbers are compared with
+//! an absolute tolerance, and paths that legitimately differ between
+//! implementations (by default `/producer` and `computed_at_utc`) are skipped.
+//!
+//! [`migrate_check`] runs a wearable case on a processor seeded with a baseline
+//! store saved by an earlier Flux version, so store format upgrades can be
+//! audited against known output.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::baseline::{BaselineStore, BASELINE_STORE_VERSION};
+use crate::behavior::BehaviorProcessor;
+use crate::error::ComputeError;
+use crate::pipeline::FluxProcessor;
+use crate::types::Baselines;
+
+/// Suffix of expected-output files
+pub const EXPECTED_SUFFIX: &str = ".expected.ndjson";
+
+/// JSON pointer paths skipped by default
+pub const DEFAULT_IGNORE_PATHS: &[&str] = &[
+    "/producer",
+    "/computed_at_utc",
+    "/provenance/computed_at_utc",
+];
+
+/// Input format of a fixture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixtureKind {
+    /// `<case>.whoop.json`
+    Whoop,
+    /// `<case>.garmin.json`
+    Garmin,
+    /// `<case>.raw.ndjson`
+    RawEvents,
+    /// `<case>.behavior.ndjson`
+    Behavior,
+}
+
+impl FixtureKind {
+    const ALL: [FixtureKind; 4] = [
+        FixtureKind::Whoop,
+        FixtureKind::Garmin,
+        FixtureKind::RawEvents,
+        FixtureKind::Behavior,
+    ];
+
+    /// File name suffix identifying this kind
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            FixtureKind::Whoop => ".whoop.json",
+            FixtureKind::Garmin => ".garmin.json",
+            FixtureKind::RawEvents => ".raw.ndjson",
+            FixtureKind::Behavior => ".behavior.ndjson",
+        }
+    }
+}
+
+/// One input/expected-output pair
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    /// Case name (file name without suffix)
+    pub name: String,
+    /// Input format
+    pub kind: FixtureKind,
+    /// Input file
+    pub input_path: PathBuf,
+    /// Expected output file (may not exist yet)
+    pub expected_path: PathBuf,
+}
+
+impl Fixture {
+    /// Fixture for an input file, with the expected file next to it
+    /// (`None` if the file name has no fixture suffix)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        FixtureKind::ALL.into_iter().find_map(|kind| {
+            let name = file_name.strip_suffix(kind.suffix())?;
+            Some(Fixture {
+                name: name.to_string(),
+                kind,
+                expected_path: path.with_file_name(format!("{}{}", name, EXPECTED_SUFFIX)),
+                input_path: path.to_path_buf(),
+            })
+        })
+    }
+}
+
+/// How JSON values are compared, here and in [`crate::diff`]
+#[derive(Debug, Clone)]
+pub struct CompareConfig {
+    /// Maximum absolute difference between numbers considered equal
+    pub tolerance: f64,
+    /// JSON pointer paths (and everything below them) excluded from comparison
+    pub ignore_paths: Vec<String>,
+}
+
+impl Default for CompareConfig {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-9,
+            ignore_paths: DEFAULT_IGNORE_PATHS.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+impl CompareConfig {
+    /// Set the numeric tolerance
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Skip an additional JSON pointer path
+    pub fn with_ignored_path(mut self, path: impl Into<String>) -> Self {
+        self.ignore_paths.push(path.into());
+        self
+    }
+
+    fn is_ignored(&self, path: &str) -> bool {
+        self.ignore_paths.iter().any(|ignored| {
+            path.strip_prefix(ignored.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+/// How fixtures are run and compared
+#[derive(Debug, Clone)]
+pub struct ConformanceConfig {
+    /// Numeric tolerance and skipped paths
+    pub compare: CompareConfig,
+    /// Timezone passed to the wearable pipeline
+    pub timezone: String,
+    /// Device ID passed to the wearable pipeline
+    pub device_id: String,
+    /// `computed_at_utc` used by deterministic wearable encoding (days that
+    /// had not ended by then are flagged `partial_day`)
+    pub computed_at: DateTime<Utc>,
+    /// Baseline store JSON loaded before each wearable case
+    pub baselines: Option<String>,
+}
+
+impl Default for ConformanceConfig {
+    fn default() -> Self {
+        Self {
+            compare: CompareConfig::default(),
+            timezone: "UTC".to_string(),
+            device_id: "conformance".to_string(),
+            computed_at: DateTime::UNIX_EPOCH,
+            baselines: None,
+        }
+    }
+}
+
+impl ConformanceConfig {
+    /// Set the numeric tolerance
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.compare = self.compare.with_tolerance(tolerance);
+        self
+    }
+
+    /// Skip an additional JSON pointer path
+    pub fn with_ignored_path(mut self, path: impl Into<String>) -> Self {
+        self.compare = self.compare.with_ignored_path(path);
+        self
+    }
+
+    /// Set the timezone for wearable fixtures
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = timezone.into();
+        self
+    }
+
+    /// Set the device ID for wearable fixtures
+    pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.device_id = device_id.into();
+        self
+    }
+
+    /// Seed wearable cases with a saved baseline store
+    pub fn with_baselines(mut self, json: impl Into<String>) -> Self {
+        self.baselines = Some(json.into());
+        self
+    }
+}
+
+/// A single mismatch between expected and actual output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Difference {
+    /// JSON pointer, prefixed with the record index (`/0/windows/0/sleep/score`)
+    pub path: String,
+    /// Expected value (`None` when the actual output has an extra value)
+    pub expected: Option<Value>,
+    /// Actual value (`None` when the actual output is missing a value)
+    pub actual: Option<Value>,
+}
+
+/// Result of one fixture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseReport {
+    /// Case name
+    pub name: String,
+    /// Input format
+    pub kind: FixtureKind,
+    /// Output differences
+    pub differences: Vec<Difference>,
+    /// Error that prevented the comparison
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CaseReport {
+    /// Whether the output matched
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.differences.is_empty()
+    }
+}
+
+/// Results for a fixture directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConformanceReport {
+    /// One entry per fixture, sorted by case name
+    pub cases: Vec<CaseReport>,
+}
+
+impl ConformanceReport {
+    /// Whether every fixture matched
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(CaseReport::passed)
+    }
+
+    /// Fixtures that did not match
+    pub fn failures(&self) -> impl Iterator<Item = &CaseReport> {
+        self.cases.iter().filter(|case| !case.passed())
+    }
+}
+
+/// Find fixtures in a directory (not recursive), sorted by case name
+pub fn discover(dir: &Path) -> Result<Vec<Fixture>, ComputeError> {
+    let mut fixtures = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| io_error(dir, e))? {
+        let path = entry.map_err(|e| io_error(dir, e))?.path();
+        fixtures.extend(Fixture::from_path(&path));
+    }
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fixtures)
+}
+
+/// Run every fixture in a directory
+pub fn run_dir(dir: &Path, config: &ConformanceConfig) -> Result<ConformanceReport, ComputeError> {
+    Ok(ConformanceReport {
+        cases: discover(dir)?
+            .iter()
+            .map(|fixture| run_fixture(fixture, config))
+            .collect(),
+    })
+}
+
+/// Run one fixture from disk
+pub fn run_fixture(fixture: &Fixture, config: &ConformanceConfig) -> CaseReport {
+    let files = fs::read_to_string(&fixture.input_path)
+        .map_err(|e| io_error(&fixture.input_path, e))
+        .and_then(|input| {
+            fs::read_to_string(&fixture.expected_path)
+                .map(|expected| (input, expected))
+                .map_err(|e| io_error(&fixture.expected_path, e))
+        });
+    match files {
+        Ok((input, expected)) => run_case(&fixture.name, fixture.kind, &input, &expected, config),
+        Err(e) => CaseReport {
+            name: fixture.name.clone(),
+            kind: fixture.kind,
+            differences: Vec::new(),
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Run an in-memory case: `expected` holds one HSI payload per line
+pub fn run_case(
+    name: &str,
+    kind: FixtureKind,
+    input: &str,
+    expected: &str,
+    config: &ConformanceConfig,
+) -> CaseReport {
+    let result = parse_records(expected).and_then(|expected| {
+        let actual = produce(kind, input, config)?
+            .iter()
+            .map(|json| Ok(serde_json::from_str(json)?))
+            .collect::<Result<Vec<Value>, ComputeError>>()?;
+        Ok(diff_records(&expected, &actual, &config.compare))
+    });
+    let (differences, error) = match result {
+        Ok(differences) => (differences, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    CaseReport {
+        name: name.to_string(),
+        kind,
+        differences,
+        error,
+    }
+}
+
+/// Result of replaying a fixture on a migrated baseline store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// Store version of the input blob (1 for pre-versioned stores)
+    pub from_version: u32,
+    /// Store version after migration
+    pub to_version: u32,
+    /// Saving and reloading the migrated store reproduces it exactly
+    pub roundtrip_stable: bool,
+    /// Baselines of the migrated store before the fixture is processed
+    pub baselines: Baselines,
+    /// Fixture output compared with its expected file
+    pub case: CaseReport,
+}
+
+impl MigrationReport {
+    /// Whether the migrated store is stable and reproduces the expected output
+    pub fn passed(&self) -> bool {
+        self.roundtrip_stable && self.case.passed()
+    }
+}
+
+/// Migrate a saved baseline store, then run a wearable fixture seeded with it
+pub fn migrate_check(
+    baselines: &str,
+    fixture: &Fixture,
+    config: &ConformanceConfig,
+) -> Result<MigrationReport, ComputeError> {
+    if fixture.kind == FixtureKind::Behavior {
+        return Err(ComputeError::InvalidConfig(
+            "migrate-check needs a wearable fixture".to_string(),
+        ));
+    }
+    let from_version = BaselineStore::stored_version(baselines)?;
+    let store = BaselineStore::from_json(baselines)?;
+    let migrated = store.to_json()?;
+    let roundtrip_stable = BaselineStore::from_json(&migrated)?.to_json()? == migrated;
+
+    let config = config.clone().with_baselines(migrated);
+    Ok(MigrationReport {
+        from_version,
+        to_version: BASELINE_STORE_VERSION,
+        roundtrip_stable,
+        baselines: store.get_baselines(),
+        case: run_fixture(fixture, &config),
+    })
+}
+
+/// Write the current pipeline output as each fixture's expected file.
+///
+/// Use this to create fixtures for new inputs, then review the result before
+/// committing it: blessed output is only as correct as the code that produced it.
+pub fn bless(dir: &Path, config: &ConformanceConfig) -> Result<usize, ComputeError> {
+    let fixtures = discover(dir)?;
+    for fixture in &fixtures {
+        let input = fs::read_to_string(&fixture.input_path)
+            .map_err(|e| io_error(&fixture.input_path, e))?;
+        let mut ndjson = String::new();
+        for json in produce(fixture.kind, &input, config)? {
+            let value: Value = serde_json::from_str(&json)?;
+            ndjson.push_str(&serde_json::to_string(&value)?);
+            ndjson.push('\n');
+        }
+        fs::write(&fixture.expected_path, ndjson)
+            .map_err(|e| io_error(&fixture.expected_path, e))?;
+    }
+    Ok(fixtures.len())
+}
+
+/// Compare expected and actual record lists
+pub fn diff_records(
+    expected: &[Value],
+    actual: &[Value],
+    config: &CompareConfig,
+) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        diff_at(
+            &format!("/{}", i),
+            "",
+            expected.get(i),
+            actual.get(i),
+            config,
+            &mut differences,
+        );
+    }
+    differences
+}
+
+/// Compare two JSON values
+pub fn diff_values(expected: &Value, actual: &Value, config: &CompareConfig) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    diff_at(
+        "",
+        "",
+        Some(expected),
+        Some(actual),
+        config,
+        &mut differences,
+    );
+    differences
+}
+
+/// Recursive comparison; `path` is reported, `pointer` is matched against ignore paths
+fn diff_at(
+    prefix: &str,
+    pointer: &str,
+    expected: Option<&Value>,
+    actual: Option<&Value>,
+    config: &CompareConfig,
+    out: &mut Vec<Difference>,
+) {
+    if !pointer.is_empty() && config.is_ignored(pointer) {
+        return;
+    }
+    let mismatch = |out: &mut Vec<Difference>| {
+        out.push(Difference {
+            path: format!("{}{}", prefix, pointer),
+            expected: expected.cloned(),
+            actual: actual.cloned(),
+        })
+    };
+    match (expected, actual) {
+        (Some(Value::Object(e)), Some(Value::Object(a))) => {
+            let mut keys: Vec<&String> = e.keys().chain(a.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{}/{}", pointer, escape_pointer(key));
+                diff_at(prefix, &child, e.get(key), a.get(key), config, out);
+            }
+        }
+        (Some(Value::Array(e)), Some(Value::Array(a))) => {
+            for i in 0..e.len().max(a.len()) {
+                let child = format!("{}/{}", pointer, i);
+                diff_at(prefix, &child, e.get(i), a.get(i), config, out);
+            }
+        }
+        (Some(Value::Number(e)), Some(Value::Number(a))) => {
+            let equal = match (e.as_f64(), a.as_f64()) {
+                (Some(e), Some(a)) => (e - a).abs() <= config.tolerance,
+                _ => e == a,
+            };
+            if !equal {
+                mismatch(out);
+            }
+        }
+        (e, a) if e == a => {}
+        _ => mismatch(out),
+    }
+}
+
+/// Escape a key per RFC 6901
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Run the pipeline for a fixture input
+fn produce(
+    kind: FixtureKind,
+    input: &str,
+    config: &ConformanceConfig,
+) -> Result<Vec<String>, ComputeError> {
+    let mut processor = FluxProcessor::new();
+    processor.set_deterministic(0, config.computed_at);
+    if let Some(baselines) = &config.baselines {
+        processor.load_baselines(baselines)?;
+    }
+    match kind {
+        FixtureKind::Whoop => processor.process_whoop(input, &config.timezone, &config.device_id),
+        FixtureKind::Garmin => processor.process_garmin(input, &config.timezone, &config.device_id),
+        FixtureKind::RawEvents => {
+            processor.process_raw_events(input, &config.timezone, &config.device_id)
+        }
+        FixtureKind::Behavior => {
+            let mut processor = BehaviorProcessor::new();
+            input
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| processor.process(line))
+                .collect()
+        }
+    }
+}
+
+/// Parse NDJSON into one JSON value per non-empty line
+pub(crate) fn parse_records(ndjson: &str) -> Result<Vec<Value>, ComputeError> {
+    ndjson
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ComputeError {
+    ComputeError::ParseError(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fixtures_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/conformance")
+    }
+
+    #[test]
+    fn test_checked_in_fixtures_conform() {
+        let config = ConformanceConfig::default();
+        // Fixtures are blessed without optional features
+        #[cfg(feature = "experimental-affect")]
+        let config = config.with_ignored_path("/axes/affect");
+
+        let report = run_dir(&fixtures_dir(), &config).unwrap();
+        assert_eq!(report.cases.len(), 4);
+        let failures: Vec<_> = report
+            .failures()
+            .map(|case| (&case.name, &case.error, case.differences.first()))
+            .collect();
+        assert!(failures.is_empty(), "{:?}", failures);
+    }
+
+    #[test]
+    fn test_migrate_check_v1_store() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/migration");
+        let fixture = Fixture::from_path(&dir.join("week.raw.ndjson")).unwrap();
+        let v1 = fs::read_to_string(dir.join("store.v1.json")).unwrap();
+
+        let report = migrate_check(&v1, &fixture, &ConformanceConfig::default()).unwrap();
+        assert_eq!(report.from_version, 1);
+        assert_eq!(report.to_version, BASELINE_STORE_VERSION);
+        assert_eq!(report.baselines.baseline_days, 5);
+        assert!(report.passed(), "{:?}", report.case.differences.first());
+
+        // The same values saved by a v2 store (dated queues) reproduce it too;
+        // only its dated days reach into the trend window
+        let v2 = fs::read_to_string(dir.join("store.v2.json")).unwrap();
+        let config =
+            ["hrv", "rhr", "sleep"]
+                .iter()
+                .fold(ConformanceConfig::default(), |config, metric| {
+                    config.with_ignored_path(format!("/windows/0/baseline/{metric}_trend"))
+                });
+        let report = migrate_check(&v2, &fixture, &config).unwrap();
+        assert_eq!(report.from_version, 2);
+        assert!(report.passed(), "{:?}", report.case.differences.first());
+
+        // A store that migrates to different values no longer reproduces the output
+        let altered = v1.replace("62.0", "92.0");
+        let report = migrate_check(&altered, &fixture, &ConformanceConfig::default()).unwrap();
+        assert!(report.roundtrip_stable);
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn test_diff_reports_paths_and_respects_tolerance() {
+        let config = CompareConfig::default().with_tolerance(1e-3);
+        let expected = json!({ "a": { "score": 0.5, "tags": ["x"] }, "producer": { "v": 1 } });
+        let actual =
+            json!({ "a": { "score": 0.5004, "tags": ["x", "y"] }, "producer": { "v": 2 } });
+        let differences = diff_values(&expected, &actual, &config);
+        assert_eq!(
+            differences,
+            vec![Difference {
+                path: "/a/tags/1".to_string(),
+                expected: None,
+                actual: Some(json!("y")),
+            }]
+        );
+
+        let strict = diff_values(&expected, &actual, &CompareConfig::default());
+        assert_eq!(strict.len(), 2);
+    }
+
+    #[test]
+    fn test_modified_expectation_fails() {
+        let fixture = discover(&fixtures_dir())
+            .unwrap()
+            .into_iter()
+            .find(|f| f.kind == FixtureKind::Whoop)
+            .unwrap();
+        let input = fs::read_to_string(&fixture.input_path).unwrap();
+        let mut expected =
+            parse_records(&fs::read_to_string(&fixture.expected_path).unwrap()).unwrap();
+        expected[0]["windows"][0]["physiology"]["hrv_rmssd_ms"] = json!(1.0);
+        let expected: String = expected.iter().map(|v| format!("{}\n", v)).collect();
+
+        let report = run_case(
+            "edited",
+            FixtureKind::Whoop,
+            &input,
+            &expected,
+            &Default::default(),
+        );
+        assert!(!report.passed());
+        assert_eq!(
+            report.differences[0].path,
+            "/0/windows/0/physiology/hrv_rmssd_ms"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Semantic comparison of HSI output
+//!
+//! Compares two sets of HSI payloads window by window, for validating Flux
+//! upgrades and parity with SDK-produced output. Unlike the byte-level
+//! comparison in [`crate::conformance`], windows are matched by key rather
+//! than position:
+//!
+//! - wearable (HSI 1.0.0) windows by `date`
+//! - behavioral (HSI 1.0) payloads by window ID
+//!
+//! Within a window, readings carrying an `axis` field are matched by axis
+//! name, and a `null` counts the same as a missing value. The keyed windows
+//! are then compared with [`conformance::diff_values`]: a value or subtree
+//! present on only one side is reported as added or removed at its top path,
+//! and numbers that differ by more than the tolerance with their delta.
+
+use crate::conformance::{self, CompareConfig};
+use crate::error::ComputeError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Kind of change to one value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// Present only in the second set
+    Added,
+    /// Present only in the first set
+    Removed,
+    /// Present in both with different values
+    Changed,
+}
+
+/// One differing value within a matched window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange {
+    /// Window key (date or window ID)
+    pub window: String,
+    /// JSON pointer within the window's payload, with readings keyed by axis
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+    /// `after - before` for numeric changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta: Option<f64>,
+}
+
+/// Result of comparing two payload sets
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiffReport {
+    /// Windows present only in the second set
+    pub windows_added: Vec<String>,
+    /// Windows present only in the first set
+    pub windows_removed: Vec<String>,
+    /// Number of windows present in both sets
+    pub windows_compared: usize,
+    /// Value changes within matched windows, ordered by window and path
+    pub changes: Vec<ValueChange>,
+}
+
+impl DiffReport {
+    /// Whether the two sets are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.windows_added.is_empty() && self.windows_removed.is_empty() && self.changes.is_empty()
+    }
+
+    /// Number of differences (unmatched windows plus value changes)
+    pub fn difference_count(&self) -> usize {
+        self.windows_added.len() + self.windows_removed.len() + self.changes.len()
+    }
+}
+
+/// Compare two NDJSON streams of HSI payloads
+pub fn diff_ndjson(
+    before: &str,
+    after: &str,
+    config: &CompareConfig,
+) -> Result<DiffReport, ComputeError> {
+    Ok(diff_payloads(
+        &conformance::parse_records(before)?,
+        &conformance::parse_records(after)?,
+        config,
+    ))
+}
+
+/// Compare two sets of HSI payloads
+pub fn diff_payloads(before: &[Value], after: &[Value], config: &CompareConfig) -> DiffReport {
+    let before = index_windows(before);
+    let mut after = index_windows(after);
+    let mut report = DiffReport::default();
+
+    for (window, old) in before {
+        let Some(new) = after.remove(&window) else {
+            report.windows_removed.push(window);
+            continue;
+        };
+        report.windows_compared += 1;
+
+        for difference in conformance::diff_values(&old, &new, config) {
+            let (kind, delta) = match (&difference.expected, &difference.actual) {
+                (Some(_), None) => (ChangeKind::Removed, None),
+                (None, Some(_)) => (ChangeKind::Added, None),
+                (Some(b), Some(a)) => (
+                    ChangeKind::Changed,
+                    a.as_f64().zip(b.as_f64()).map(|(a, b)| a - b),
+                ),
+                (None, None) => continue,
+            };
+            report.changes.push(ValueChange {
+                window: window.clone(),
+                path: difference.path,
+                kind,
+                before: difference.expected,
+                after: difference.actual,
+                delta,
+            });
+        }
+    }
+    report.windows_added = after.into_keys().collect();
+    report
+}
+
+/// Split payloads into one keyed value per window
+fn index_windows(payloads: &[Value]) -> BTreeMap<String, Value> {
+    let mut windows = BTreeMap::new();
+    for payload in payloads {
+        let Value::Object(fields) = payload else {
+            continue;
+        };
+        let shared: Map<String, Value> = fields
+            .iter()
+            .filter(|(key, _)| key.as_str() != "windows")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        match fields.get("windows") {
+            // Wearable payloads: one entry per daily window, merged into the payload
+            Some(Value::Array(days)) => {
+                for day in days {
+                    let date = day.get("date").and_then(Value::as_str).unwrap_or("unknown");
+                    let mut window = shared.clone();
+                    if let Value::Object(day) = day {
+                        window.extend(day.clone());
+                    }
+                    insert_unique(&mut windows, date, keyed(Value::Object(window)));
+                }
+            }
+            // Behavioral payloads: the window map is part of the payload
+            Some(Value::Object(by_id)) => {
+                let key = by_id.keys().cloned().collect::<Vec<_>>().join(",");
+                insert_unique(&mut windows, &key, keyed(payload.clone()));
+            }
+            _ => insert_unique(&mut windows, "unknown", keyed(Value::Object(shared))),
+        }
+    }
+    windows
+}
+
+/// Insert under `key`, suffixing `#2`, `#3`, ... for repeated keys
+fn insert_unique(windows: &mut BTreeMap<String, Value>, key: &str, window: Value) {
+    let mut unique = key.to_string();
+    let mut n = 1;
+    while windows.contains_key(&unique) {
+        n += 1;
+        unique = format!("{key}#{n}");
+    }
+    windows.insert(unique, window);
+}
+
+/// Drop `null` fields and key arrays of readings by their `axis`
+fn keyed(value: Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .filter(|(_, child)| !child.is_null())
+                .map(|(key, child)| (key, keyed(child)))
+                .collect(),
+        ),
+        Value::Array(items) => {
+            let axes: Vec<String> = items
+                .iter()
+                .filter_map(|item| item.get("axis").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect();
+            let mut unique = axes.clone();
+            unique.sort();
+            unique.dedup();
+            if !items.is_empty() && axes.len() == items.len() && unique.len() == axes.len() {
+                Value::Object(axes.into_iter().zip(items.into_iter().map(keyed)).collect())
+            } else {
+                Value::Array(items.into_iter().map(keyed).collect())
+            }
+        }
+        leaf => leaf,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn day(date: &str, efficiency: Value, score: Value) -> Value {
+        json!({
+            "hsi_version": "1.0.0",
+            "producer": { "name": "synheart-flux", "version": "0.1.0", "instance_id": "x" },
+            "provenance": { "computed_at_utc": date },
+            "windows": [{ "date": date, "sleep": { "efficiency": efficiency, "score": score } }]
+        })
+    }
+
+    #[test]
+    fn test_diff_matches_windows_by_date() {
+        let before = [
+            day("2024-01-15", json!(0.9), json!(80.0)),
+            day("2024-01-16", json!(0.8), Value::Null),
+        ];
+        // Reordered, one day removed, one added
+        let after = [
+            day("2024-01-17", json!(0.7), json!(70.0)),
+            day("2024-01-15", json!(0.9 + 1e-9), json!(82.5)),
+        ];
+
+        let config = CompareConfig::default().with_tolerance(1e-6);
+        let report = diff_payloads(&before, &after, &config);
+        assert_eq!(report.windows_compared, 1);
+        assert_eq!(report.windows_removed, vec!["2024-01-16"]);
+        assert_eq!(report.windows_added, vec!["2024-01-17"]);
+        assert_eq!(report.changes.len(), 1);
+        let change = &report.changes[0];
+        assert_eq!(change.path, "/sleep/score");
+        assert_eq!(change.kind, ChangeKind::Changed);
+        assert_eq!(change.delta, Some(2.5));
+    }
+
+    #[test]
+    fn test_diff_reports_added_axes_and_readings_by_axis() {
+        let before = [json!({
+            "windows": { "w1": { "label": "session:1" } },
+            "axes": { "behavior": { "readings": [
+                { "axis": "focus", "score": 0.6 },
+                { "axis": "distraction", "score": 0.4 }
+            ] } }
+        })];
+        let after = [json!({
+            "windows": { "w1": { "label": "session:1" } },
+            "axes": { "behavior": { "readings": [
+                { "axis": "distraction", "score": 0.4 },
+                { "axis": "focus", "score": 0.6 },
+                { "axis": "burstiness", "score": 0.5 }
+            ] } }
+        })];
+
+        let report = diff_payloads(&before, &after, &CompareConfig::default());
+        let paths: Vec<_> = report
+            .changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![("/axes/behavior/readings/burstiness", ChangeKind::Added)]
+        );
+        assert_eq!(report.difference_count(), 1);
+    }
+}
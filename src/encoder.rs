@@ -3,22 +3,359 @@
 //! This module encodes contextual signals into HSI-compliant JSON payloads.
 //! Ensures all required fields are present and properly formatted.
 
+use crate::baseline::DEFAULT_BASELINE_WINDOW;
 use crate::error::ComputeError;
+use crate::normalizer::vendor_score_scale;
+use crate::privacy::PrivacyPolicy;
 use crate::types::{
-    ContextualSignals, HsiActivity, HsiBaseline, HsiDailyWindow, HsiPayload, HsiPhysiology,
-    HsiProducer, HsiProvenance, HsiQuality, HsiSleep,
+    CanonicalWearSignals, ContextualSignals, DerivedSignals, HsiActivity, HsiBaseline, HsiBody,
+    HsiDailyWindow, HsiHourlyActivity, HsiPayload, HsiPhysiology, HsiProducer, HsiProvenance,
+    HsiQuality, HsiSeriesPoint, HsiSleep, HsiSourceEvents, HsiWorkout, QualityFlag,
 };
 use crate::{FLUX_VERSION, PRODUCER_NAME};
-use chrono::Utc;
-use std::collections::HashMap;
-use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use uuid::{Builder, Uuid};
 
 /// Current HSI schema version
 pub const HSI_VERSION: &str = "1.0.0";
 
+/// Default age (seconds) of a domain's newest event past which a window is
+/// flagged `stale_data`; also where the default confidence starts to lose
+/// its freshness term
+pub const DEFAULT_STALE_AFTER_SEC: i64 = 36 * 3600;
+
+/// Wearable meta key of passed-through unmapped signals
+pub const UNMAPPED_SIGNALS_META_KEY: &str = "unmapped_signals";
+
+/// Meta key holding the Flux version when the producer identity is overridden
+pub const FLUX_VERSION_META_KEY: &str = "flux_version";
+
+/// Name and version written to the `producer` block of emitted payloads.
+///
+/// Defaults to Flux's own ([`PRODUCER_NAME`], [`FLUX_VERSION`]). Hosts that
+/// embed Flux can stamp their own identity; payloads then carry the Flux
+/// version in `meta.flux_version` so output stays traceable to the engine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProducerIdentity {
+    pub name: String,
+    pub version: String,
+    /// Fixed producer instance ID, replacing the generated (or seeded) one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+}
+
+impl Default for ProducerIdentity {
+    fn default() -> Self {
+        Self::new(PRODUCER_NAME, FLUX_VERSION)
+    }
+}
+
+impl ProducerIdentity {
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            instance_id: None,
+        }
+    }
+
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.instance_id = Some(instance_id.into());
+        self
+    }
+
+    /// Whether the name or version differs from Flux's own
+    pub fn is_overridden(&self) -> bool {
+        self.name != PRODUCER_NAME || self.version != FLUX_VERSION
+    }
+}
+
+/// Naming contract for behavioral axis names and meta keys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputProfile {
+    /// Flux names
+    #[default]
+    #[serde(rename = "flux")]
+    Flux,
+    /// Names used by v1 of the Synheart SDKs, which match Flux's derived
+    /// signal fields (e.g. `distraction_score` for the `distraction` axis)
+    #[serde(rename = "sdk-v1")]
+    SdkV1,
+}
+
+/// Flux axis names renamed by the `sdk-v1` profile
+const SDK_V1_AXES: [(&str, &str); 4] = [
+    ("distraction", "distraction_score"),
+    ("focus", "focus_hint"),
+    ("checking_habit", "checking_habit_score"),
+    ("sustained_engagement", "deep_focus_coverage"),
+];
+
+/// Flux meta keys renamed by the `sdk-v1` profile
+const SDK_V1_META_KEYS: [(&str, &str); 4] = [
+    ("baseline_distraction", "distraction_baseline"),
+    ("late_night_usage_ratio_session", "late_night_usage_ratio"),
+    ("unlocks_per_hour", "unlock_frequency"),
+    ("average_screen_session_sec", "avg_screen_session_sec"),
+];
+
+impl OutputProfile {
+    /// Profile name as accepted in configs (`flux`, `sdk-v1`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputProfile::Flux => "flux",
+            OutputProfile::SdkV1 => "sdk-v1",
+        }
+    }
+
+    /// Emitted name of a Flux axis
+    pub fn axis_name<'a>(&self, axis: &'a str) -> &'a str {
+        self.rename(&SDK_V1_AXES, axis)
+    }
+
+    /// Emitted name of a Flux meta key
+    pub fn meta_key<'a>(&self, key: &'a str) -> &'a str {
+        self.rename(&SDK_V1_META_KEYS, key)
+    }
+
+    fn rename<'a>(&self, sdk_v1: &[(&str, &'static str)], name: &'a str) -> &'a str {
+        match self {
+            OutputProfile::Flux => name,
+            OutputProfile::SdkV1 => sdk_v1
+                .iter()
+                .find(|(flux, _)| *flux == name)
+                .map_or(name, |(_, sdk)| sdk),
+        }
+    }
+}
+
+/// Output filtering shared by the wearable and behavioral encoders.
+///
+/// Wearable axes are named `namespace.field` (e.g. `physiology.hrv_rmssd_ms`)
+/// and a bare namespace (`sleep`) matches all of its fields; behavioral axes
+/// use the reading's `axis` name. The denylist wins over the allowlist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncoderConfig {
+    /// Only emit these axes (all axes when `None`)
+    pub include_axes: Option<BTreeSet<String>>,
+    /// Never emit these axes
+    pub exclude_axes: BTreeSet<String>,
+    /// Drop free-form metadata (behavioral `meta`, wearable `vendor` maps)
+    pub suppress_meta: bool,
+    /// Bandwidth-saving mode: drop reading notes, evidence source IDs and
+    /// verbose metadata (per-session typing metrics, deep focus block details,
+    /// raw vendor payloads) and emit non-pretty JSON
+    pub compact: bool,
+    /// Emit intraday series (axes `series.<name>`) downsampled to buckets of
+    /// this many minutes; series are omitted when `None`
+    pub series_minutes: Option<u32>,
+    /// Naming of behavioral axes and meta keys; the allow/deny lists always
+    /// use Flux names
+    pub profile: OutputProfile,
+    /// Flag wearable windows `stale_data` when any domain's newest event is
+    /// older than this many seconds (never when `None`)
+    pub stale_after_sec: Option<i64>,
+    /// Record the raw event IDs behind each wearable window in its provenance
+    pub source_events: SourceEvents,
+    /// Pass signals with no canonical field through to wearable
+    /// `meta.unmapped_signals`
+    pub unmapped_signals: bool,
+}
+
+/// How much of a window's contributing raw event IDs goes into its provenance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceEvents {
+    /// Nothing
+    #[default]
+    Off,
+    /// Count and SHA-256 digest of the sorted IDs
+    Digest,
+    /// Count, digest and the IDs themselves
+    List,
+}
+
+impl Default for EncoderConfig {
+    fn default() -> Self {
+        Self {
+            include_axes: None,
+            exclude_axes: BTreeSet::new(),
+            suppress_meta: false,
+            compact: false,
+            series_minutes: None,
+            profile: OutputProfile::default(),
+            stale_after_sec: Some(DEFAULT_STALE_AFTER_SEC),
+            source_events: SourceEvents::Off,
+            unmapped_signals: false,
+        }
+    }
+}
+
+impl EncoderConfig {
+    /// Emit only the given axes
+    pub fn with_include_axes<I, S>(mut self, axes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.include_axes = Some(axes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Never emit the given axes
+    pub fn with_exclude_axes<I, S>(mut self, axes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude_axes = axes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Drop free-form metadata from payloads
+    pub fn with_meta_suppressed(mut self, suppress: bool) -> Self {
+        self.suppress_meta = suppress;
+        self
+    }
+
+    /// Enable or disable compact encoding
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Emit downsampled intraday series (omitted when `None` or zero)
+    pub fn with_series_minutes(mut self, minutes: Option<u32>) -> Self {
+        self.series_minutes = minutes.filter(|&m| m > 0);
+        self
+    }
+
+    /// Rename behavioral axes and meta keys to a profile's contract
+    pub fn with_profile(mut self, profile: OutputProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Set the age past which windows are flagged `stale_data` (`None` disables the flag)
+    pub fn with_stale_after_sec(mut self, seconds: Option<i64>) -> Self {
+        self.stale_after_sec = seconds;
+        self
+    }
+
+    /// Record contributing raw event IDs in provenance
+    pub fn with_source_events(mut self, source_events: SourceEvents) -> Self {
+        self.source_events = source_events;
+        self
+    }
+
+    /// Pass unmapped signals through to `meta.unmapped_signals`
+    pub fn with_unmapped_signals(mut self, pass_through: bool) -> Self {
+        self.unmapped_signals = pass_through;
+        self
+    }
+
+    /// Serialize a payload, pretty-printed unless compact
+    pub(crate) fn to_json<T: Serialize>(&self, payload: &T) -> Result<String, ComputeError> {
+        if self.compact {
+            serde_json::to_string(payload).map_err(ComputeError::JsonError)
+        } else {
+            serde_json::to_string_pretty(payload).map_err(ComputeError::JsonError)
+        }
+    }
+
+    /// Whether an axis passes the allow/deny lists
+    pub fn allows(&self, axis: &str) -> bool {
+        let matches = |set: &BTreeSet<String>| {
+            set.contains(axis)
+                || axis
+                    .split_once('.')
+                    .is_some_and(|(namespace, _)| set.contains(namespace))
+        };
+        !matches(&self.exclude_axes) && self.include_axes.as_ref().is_none_or(matches)
+    }
+
+    fn filter<T>(&self, axis: &str, value: &mut Option<T>) {
+        if !self.allows(axis) {
+            *value = None;
+        }
+    }
+}
+
+/// Computes the `quality.confidence` of an encoded window.
+///
+/// Implement this to replace [`DefaultConfidencePolicy`] in [`HsiEncoder`].
+pub trait ConfidencePolicy: Send + Sync {
+    /// Confidence in `signals` (0-1), given seconds since observation
+    fn confidence(&self, signals: &ContextualSignals, freshness_sec: i64) -> f64;
+}
+
+/// Default confidence model.
+///
+/// ```text
+/// confidence = 0.50 * coverage
+///            + 0.20 * freshness      (1 up to 36 h old, linear to 0 at 7 days)
+///            + 0.15 * vendor_scores  (fraction of sleep/recovery/strain scores present)
+///            + 0.15 * baseline       (days_in_baseline / baseline window, capped at 1)
+/// ```
+///
+/// When the input signals reported their quality, the result is scaled by
+/// the mean reported quality.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultConfidencePolicy;
+
+impl ConfidencePolicy for DefaultConfidencePolicy {
+    fn confidence(&self, signals: &ContextualSignals, freshness_sec: i64) -> f64 {
+        const FRESH_SEC: f64 = DEFAULT_STALE_AFTER_SEC as f64;
+        const STALE_SEC: f64 = 7.0 * 24.0 * 3600.0;
+
+        let normalized = &signals.derived.normalized;
+
+        let age = freshness_sec.max(0) as f64;
+        let freshness = if age <= FRESH_SEC {
+            1.0
+        } else {
+            (1.0 - (age - FRESH_SEC) / (STALE_SEC - FRESH_SEC)).max(0.0)
+        };
+
+        let scores = [
+            normalized.sleep_score,
+            normalized.recovery_score,
+            normalized.strain_score,
+        ];
+        let vendor_scores =
+            scores.iter().filter(|s| s.is_some()).count() as f64 / scores.len() as f64;
+
+        // Hand-built baselines may not record their window
+        let window = match signals.baselines.window_days {
+            0 => DEFAULT_BASELINE_WINDOW as u32,
+            days => days,
+        };
+        let baseline = (signals.baselines.baseline_days as f64 / window as f64).min(1.0);
+
+        let input_quality = normalized.canonical.input_quality.unwrap_or(1.0);
+
+        ((0.5 * normalized.coverage + 0.2 * freshness + 0.15 * vendor_scores + 0.15 * baseline)
+            * input_quality)
+            .clamp(0.0, 1.0)
+    }
+}
+
 /// HSI encoder for producing compliant JSON payloads
 pub struct HsiEncoder {
     instance_id: String,
+    producer: ProducerIdentity,
+    /// Fixed `computed_at` timestamp; set only in deterministic mode
+    fixed_computed_at: Option<DateTime<Utc>>,
+    confidence_policy: Arc<dyn ConfidencePolicy>,
+    output: EncoderConfig,
+    privacy: PrivacyPolicy,
+    #[cfg(feature = "integrity")]
+    integrity: Option<crate::integrity::IntegrityConfig>,
 }
 
 impl Default for HsiEncoder {
@@ -32,39 +369,207 @@ impl HsiEncoder {
     pub fn new() -> Self {
         Self {
             instance_id: Uuid::new_v4().to_string(),
+            producer: ProducerIdentity::default(),
+            fixed_computed_at: None,
+            confidence_policy: Arc::new(DefaultConfidencePolicy),
+            output: EncoderConfig::default(),
+            privacy: PrivacyPolicy::default(),
+            #[cfg(feature = "integrity")]
+            integrity: None,
         }
     }
 
     /// Create an encoder with a specific instance ID
     pub fn with_instance_id(instance_id: String) -> Self {
-        Self { instance_id }
+        Self {
+            instance_id,
+            producer: ProducerIdentity::default(),
+            fixed_computed_at: None,
+            confidence_policy: Arc::new(DefaultConfidencePolicy),
+            output: EncoderConfig::default(),
+            privacy: PrivacyPolicy::default(),
+            #[cfg(feature = "integrity")]
+            integrity: None,
+        }
+    }
+
+    /// Create an encoder that produces byte-identical output across runs.
+    ///
+    /// The instance ID is derived from `seed`, `computed_at_utc` is fixed to
+    /// `computed_at`, and JSON object keys are emitted in sorted order.
+    pub fn deterministic(seed: u64, computed_at: DateTime<Utc>) -> Self {
+        Self {
+            instance_id: seeded_uuid(seed).to_string(),
+            producer: ProducerIdentity::default(),
+            fixed_computed_at: Some(computed_at),
+            confidence_policy: Arc::new(DefaultConfidencePolicy),
+            output: EncoderConfig::default(),
+            privacy: PrivacyPolicy::default(),
+            #[cfg(feature = "integrity")]
+            integrity: None,
+        }
+    }
+
+    /// Stamp payloads with a host's producer name, version and instance ID
+    pub fn with_producer(mut self, producer: ProducerIdentity) -> Self {
+        self.producer = producer;
+        self
+    }
+
+    /// The producer identity stamped into payloads
+    pub fn producer_identity(&self) -> &ProducerIdentity {
+        &self.producer
+    }
+
+    /// Replace the policy used to compute `quality.confidence`
+    pub fn with_confidence_policy(mut self, policy: Arc<dyn ConfidencePolicy>) -> Self {
+        self.confidence_policy = policy;
+        self
+    }
+
+    /// The policy used to compute `quality.confidence`
+    pub fn confidence_policy(&self) -> Arc<dyn ConfidencePolicy> {
+        Arc::clone(&self.confidence_policy)
+    }
+
+    /// Filter emitted axes and metadata
+    pub fn with_encoder_config(mut self, config: EncoderConfig) -> Self {
+        self.output = config;
+        self
+    }
+
+    /// The axis/meta filter applied to emitted windows
+    pub fn encoder_config(&self) -> &EncoderConfig {
+        &self.output
+    }
+
+    /// Strip metadata fields the privacy policy does not allow
+    pub fn with_privacy_policy(mut self, policy: PrivacyPolicy) -> Self {
+        self.privacy = policy;
+        self
+    }
+
+    /// The privacy policy enforced on vendor metadata
+    pub fn privacy_policy(&self) -> &PrivacyPolicy {
+        &self.privacy
+    }
+
+    /// Seal serialized payloads with an integrity hash (and signature)
+    #[cfg(feature = "integrity")]
+    pub fn with_integrity(mut self, integrity: Option<crate::integrity::IntegrityConfig>) -> Self {
+        self.integrity = integrity;
+        self
+    }
+
+    /// The integrity sealing applied to serialized payloads, if any
+    #[cfg(feature = "integrity")]
+    pub fn integrity(&self) -> Option<&crate::integrity::IntegrityConfig> {
+        self.integrity.as_ref()
+    }
+
+    /// The fixed clock used in deterministic mode, if any
+    pub fn fixed_computed_at(&self) -> Option<DateTime<Utc>> {
+        self.fixed_computed_at
+    }
+
+    /// Producer metadata of emitted payloads
+    pub fn producer(&self) -> HsiProducer {
+        HsiProducer {
+            name: self.producer.name.clone(),
+            version: self.producer.version.clone(),
+            instance_id: self
+                .producer
+                .instance_id
+                .clone()
+                .unwrap_or_else(|| self.instance_id.clone()),
+        }
     }
 
     /// Encode contextual signals into an HSI payload
     pub fn encode(&self, signals: &ContextualSignals) -> Result<HsiPayload, ComputeError> {
         let canonical = &signals.derived.normalized.canonical;
-        let computed_at = Utc::now();
+        let computed_at = self.fixed_computed_at.unwrap_or_else(Utc::now);
 
         // Build producer metadata
-        let producer = HsiProducer {
-            name: PRODUCER_NAME.to_string(),
-            version: FLUX_VERSION.to_string(),
-            instance_id: self.instance_id.clone(),
-        };
+        let producer = self.producer();
+
+        // Build daily window
+        let window = self.build_daily_window(signals);
 
         // Build provenance
+        let flux_computed: Vec<String> = [
+            (
+                "sleep.fragmentation_index",
+                window.sleep.fragmentation_index.is_some(),
+            ),
+            (
+                "sleep.stage_transitions",
+                window.sleep.stage_transitions.is_some(),
+            ),
+            ("sleep.waso_minutes", window.sleep.waso_minutes.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, present)| *present)
+        .map(|(axis, _)| axis.to_string())
+        .collect();
         let provenance = HsiProvenance {
-            source_vendor: canonical.vendor.as_str().to_string(),
+            source_vendor: source_vendor(canonical),
             source_device_id: canonical.device_id.clone(),
+            source_user_id: canonical
+                .user_id
+                .as_deref()
+                .and_then(|user_id| self.privacy.user_pseudonym(user_id)),
             observed_at_utc: canonical.observed_at.to_rfc3339(),
             computed_at_utc: computed_at.to_rfc3339(),
+            field_sources: if canonical.field_sources.is_empty() {
+                None
+            } else {
+                Some(
+                    canonical
+                        .field_sources
+                        .iter()
+                        .map(|(field, vendor)| (field.clone(), vendor.as_str().to_string()))
+                        .collect(),
+                )
+            },
+            revision: None,
+            flux_computed: (!flux_computed.is_empty()).then_some(flux_computed),
+            score_normalization: {
+                let used = &signals.derived.normalized.score_normalization;
+                (!used.is_empty()).then(|| {
+                    used.iter()
+                        .map(|(score, strategy)| (score.clone(), strategy.as_str().to_string()))
+                        .collect()
+                })
+            },
+            hrv_calibrated_from: {
+                let measured_by = canonical.source_of("hrv_rmssd_ms");
+                canonical
+                    .recovery
+                    .hrv_scale
+                    .filter(|scale| *scale != measured_by)
+                    .map(|_| measured_by.as_str().to_string())
+            },
+            source_events: source_events(canonical, self.output.source_events),
         };
 
         // Build quality metrics
         let quality = self.build_quality(signals, computed_at);
 
-        // Build daily window
-        let window = self.build_daily_window(signals);
+        let mut meta = BTreeMap::new();
+        if self.output.unmapped_signals
+            && !self.output.suppress_meta
+            && !canonical.unmapped_signals.is_empty()
+        {
+            meta.insert(
+                UNMAPPED_SIGNALS_META_KEY.to_string(),
+                serde_json::to_value(&canonical.unmapped_signals)?,
+            );
+        }
+        // Kept even with meta suppressed, so output stays traceable to the engine
+        if self.producer.is_overridden() {
+            meta.insert(FLUX_VERSION_META_KEY.to_string(), FLUX_VERSION.into());
+        }
 
         Ok(HsiPayload {
             hsi_version: HSI_VERSION.to_string(),
@@ -72,13 +577,48 @@ impl HsiEncoder {
             provenance,
             quality,
             windows: vec![window],
+            meta: (!meta.is_empty()).then_some(meta),
         })
     }
 
     /// Encode to JSON string
     pub fn encode_to_json(&self, signals: &ContextualSignals) -> Result<String, ComputeError> {
         let payload = self.encode(signals)?;
-        serde_json::to_string_pretty(&payload).map_err(ComputeError::JsonError)
+        self.payload_to_json(&payload)
+    }
+
+    /// Embed the integrity block into a typed payload, as serialization would.
+    ///
+    /// Does nothing unless an integrity config is set.
+    #[cfg_attr(not(feature = "integrity"), allow(unused_variables))]
+    pub fn seal(&self, payload: &mut HsiPayload) -> Result<(), ComputeError> {
+        #[cfg(feature = "integrity")]
+        if let Some(integrity) = &self.integrity {
+            let block = integrity.compute(&serde_json::to_value(&*payload)?)?;
+            payload.meta.get_or_insert_with(Default::default).insert(
+                crate::integrity::INTEGRITY_META_KEY.to_string(),
+                serde_json::to_value(block)?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Serialize an already-encoded payload
+    pub fn payload_to_json(&self, payload: &HsiPayload) -> Result<String, ComputeError> {
+        #[cfg(feature = "integrity")]
+        if let Some(integrity) = &self.integrity {
+            let mut value = serde_json::to_value(payload)?;
+            integrity.seal(&mut value)?;
+            return self.output.to_json(&value);
+        }
+
+        if self.fixed_computed_at.is_some() {
+            // serde_json::Value objects are ordered maps, so this sorts every key
+            let value = serde_json::to_value(payload)?;
+            self.output.to_json(&value)
+        } else {
+            self.output.to_json(payload)
+        }
     }
 
     fn build_quality(
@@ -87,29 +627,51 @@ impl HsiEncoder {
         computed_at: chrono::DateTime<Utc>,
     ) -> HsiQuality {
         let canonical = &signals.derived.normalized.canonical;
-        let freshness_sec = (computed_at - canonical.observed_at).num_seconds();
+        // Events timestamped after the computation (clock skew) count as fresh
+        let domain_freshness_sec: BTreeMap<String, i64> = canonical
+            .latest_event_at
+            .iter()
+            .map(|(domain, at)| (domain.clone(), (computed_at - *at).num_seconds().max(0)))
+            .collect();
+        // Age of the newest event, or of the ingest when events carry no timestamps
+        let freshness_sec = domain_freshness_sec
+            .values()
+            .min()
+            .copied()
+            .unwrap_or_else(|| (computed_at - canonical.observed_at).num_seconds());
+        let oldest_sec = domain_freshness_sec
+            .values()
+            .max()
+            .copied()
+            .unwrap_or(freshness_sec);
 
-        // Calculate confidence based on coverage and baseline availability
-        let base_confidence = signals.derived.normalized.coverage;
-        let baseline_bonus = if signals.baselines.baseline_days >= 7 {
-            0.1
-        } else {
-            0.0
-        };
-        let confidence = (base_confidence + baseline_bonus).min(1.0);
+        let confidence = self
+            .confidence_policy
+            .confidence(signals, freshness_sec)
+            .clamp(0.0, 1.0);
 
-        let flags: Vec<String> = signals
+        let mut flags: Vec<String> = signals
             .derived
             .normalized
             .quality_flags
             .iter()
-            .map(|f| format!("{f:?}").to_lowercase())
+            .map(|f| f.as_str().to_string())
             .collect();
+        if self
+            .output
+            .stale_after_sec
+            .is_some_and(|threshold| oldest_sec > threshold)
+        {
+            flags.push(QualityFlag::StaleData.as_str().to_string());
+        }
 
         HsiQuality {
             coverage: signals.derived.normalized.coverage,
+            class_coverage: signals.derived.normalized.class_coverage,
             freshness_sec,
+            domain_freshness_sec,
             confidence,
+            input_quality: canonical.input_quality,
             flags,
         }
     }
@@ -128,16 +690,24 @@ impl HsiEncoder {
             rem_ratio: derived.rem_sleep_ratio,
             latency_minutes: canonical.sleep.latency_minutes,
             score: normalized.sleep_score,
+            fragmentation_index: derived.hypnogram.map(|h| h.fragmentation_index),
+            stage_transitions: derived.hypnogram.map(|h| h.stage_transitions),
+            waso_minutes: derived.hypnogram.map(|h| h.waso_minutes),
             vendor: self.extract_vendor_sleep(canonical),
         };
 
         // Build physiology namespace
         let physiology = HsiPhysiology {
             hrv_rmssd_ms: canonical.recovery.hrv_rmssd_ms,
+            hrv_cv: canonical.recovery.hrv_cv,
             resting_hr_bpm: canonical.recovery.resting_hr_bpm,
             respiratory_rate: canonical.sleep.respiratory_rate,
             spo2_percentage: canonical.recovery.spo2_percentage,
             recovery_score: normalized.recovery_score,
+            recovery_balance: canonical.recovery.body_battery.recovery_balance(),
+            vendor_stress: canonical.recovery.stress.normalized_average(),
+            stress_ratios: canonical.recovery.stress.duration_ratios(),
+            cycle_phase_context: canonical.recovery.cycle_phase,
             vendor: self.extract_vendor_recovery(canonical),
         };
 
@@ -147,9 +717,21 @@ impl HsiEncoder {
             normalized_load: derived.normalized_load,
             calories: canonical.activity.calories,
             active_calories: canonical.activity.active_calories,
+            resting_calories: canonical.activity.resting_calories,
             steps: canonical.activity.steps,
             active_minutes: canonical.activity.active_minutes,
             distance_meters: canonical.activity.distance_meters,
+            moderate_intensity_minutes: canonical.activity.moderate_intensity_minutes,
+            vigorous_intensity_minutes: canonical.activity.vigorous_intensity_minutes,
+            floors_climbed: canonical.activity.floors_climbed,
+            elevation_gain_meters: canonical.activity.elevation_gain_meters,
+            workouts: encode_workouts(canonical),
+            hourly: encode_hourly(canonical),
+            hr_zone_minutes: Some(canonical.activity.hr_zone_minutes).filter(|z| !z.is_empty()),
+            activity_goal_attainment: derived.goal_attainment,
+            activity_goal_attainment_7d: signals.goal_attainment_7d,
+            vo2_max: canonical.activity.vo2_max,
+            fitness_age: canonical.activity.fitness_age,
             vendor: self.extract_vendor_activity(canonical),
         };
 
@@ -162,17 +744,279 @@ impl HsiEncoder {
             hrv_deviation_pct: signals.hrv_deviation_pct,
             rhr_deviation_pct: signals.rhr_deviation_pct,
             sleep_deviation_pct: signals.sleep_duration_deviation_pct,
+            hrv_zscore: signals.hrv_zscore,
+            rhr_zscore: signals.rhr_zscore,
+            sleep_zscore: signals.sleep_duration_zscore,
+            hrv_std_ms: signals.baselines.hrv_std_ms,
+            rhr_std_bpm: signals.baselines.rhr_std_bpm,
+            sleep_std_minutes: signals.baselines.sleep_std_minutes,
+            hrv_anomaly: signals.hrv_anomaly,
+            rhr_anomaly: signals.rhr_anomaly,
+            sleep_anomaly: signals.sleep_anomaly,
+            vo2_max: signals.baselines.vo2_max_baseline,
+            vo2_max_deviation_pct: signals.vo2_max_deviation_pct,
+            vo2_max_std: signals.baselines.vo2_max_std,
+            vo2_max_zscore: signals.vo2_max_zscore,
+            weight_kg: signals.baselines.weight_baseline_kg,
+            body_fat_percentage: signals.baselines.body_fat_baseline_pct,
+            weight_deviation_pct: signals.weight_deviation_pct,
+            body_fat_deviation_pct: signals.body_fat_deviation_pct,
+            weight_std_kg: signals.baselines.weight_std_kg,
+            weight_zscore: signals.weight_zscore,
+            body_fat_std_pct: signals.baselines.body_fat_std_pct,
+            body_fat_zscore: signals.body_fat_zscore,
+            hrv_trend: signals.hrv_trend,
+            rhr_trend: signals.rhr_trend,
+            sleep_trend: signals.sleep_trend,
+            baseline_shift_detected: signals.baseline_shift_detected,
+            baseline_shift_date: signals.baseline_shift_date.clone(),
+            device_changed: signals.device_changed,
+            days_since_device_switch: signals.days_since_device_switch,
             days_in_baseline: signals.baselines.baseline_days,
+            tag: signals.baselines.tag.clone(),
         };
 
-        HsiDailyWindow {
+        let mut window = HsiDailyWindow {
             date: canonical.date.clone(),
             timezone: canonical.timezone.clone(),
             sleep,
             physiology,
             activity,
             baseline,
+            body: Self::build_body(canonical, derived),
+            series: self.build_series(canonical),
+        };
+        self.apply_encoder_config(&mut window);
+        window
+    }
+
+    /// Body block of a day with scale data
+    fn build_body(canonical: &CanonicalWearSignals, derived: &DerivedSignals) -> Option<HsiBody> {
+        let body = HsiBody {
+            weight_kg: canonical.body.weight_kg,
+            body_fat_percentage: canonical.body.body_fat_percentage,
+            bmi: derived.bmi,
+        };
+        (!body.is_empty()).then_some(body)
+    }
+
+    /// Downsample intraday series to bucket means, if enabled
+    fn build_series(
+        &self,
+        canonical: &CanonicalWearSignals,
+    ) -> Option<BTreeMap<String, Vec<HsiSeriesPoint>>> {
+        let bucket_sec = i64::from(self.output.series_minutes?) * 60;
+        let mut series = BTreeMap::new();
+        for (field, samples) in &canonical.intraday {
+            let name = match field.as_str() {
+                "heart_rate" => "heart_rate_bpm",
+                other => other,
+            };
+            if !self.output.allows(&format!("series.{name}")) {
+                continue;
+            }
+
+            // Samples are in time order, so each bucket is a contiguous run
+            let mut points: Vec<(i64, f64, u32)> = Vec::new();
+            for sample in samples {
+                let start = sample.timestamp.timestamp().div_euclid(bucket_sec) * bucket_sec;
+                match points.last_mut() {
+                    Some((bucket, sum, count)) if *bucket == start => {
+                        *sum += sample.value;
+                        *count += 1;
+                    }
+                    _ => points.push((start, sample.value, 1)),
+                }
+            }
+            let points = points
+                .into_iter()
+                .filter_map(|(start, sum, count)| {
+                    Some(HsiSeriesPoint {
+                        start_utc: DateTime::from_timestamp(start, 0)?.to_rfc3339(),
+                        value: sum / f64::from(count),
+                        samples: count,
+                    })
+                })
+                .collect();
+            series.insert(name.to_string(), points);
         }
+        (!series.is_empty()).then_some(series)
+    }
+
+    /// Null out filtered axes, enforce the privacy policy on vendor maps and
+    /// drop them when meta is suppressed
+    fn apply_encoder_config(&self, window: &mut HsiDailyWindow) {
+        self.privacy.enforce(&mut window.sleep.vendor);
+        self.privacy.enforce(&mut window.physiology.vendor);
+        self.privacy.enforce(&mut window.activity.vendor);
+
+        let config = &self.output;
+        if config.suppress_meta {
+            window.sleep.vendor.clear();
+            window.physiology.vendor.clear();
+            window.activity.vendor.clear();
+        } else if config.compact {
+            window.sleep.vendor.remove("raw");
+            window.physiology.vendor.remove("raw");
+            window.activity.vendor.remove("raw");
+        }
+        if config.include_axes.is_none() && config.exclude_axes.is_empty() {
+            return;
+        }
+
+        let sleep = &mut window.sleep;
+        config.filter("sleep.duration_minutes", &mut sleep.duration_minutes);
+        config.filter("sleep.efficiency", &mut sleep.efficiency);
+        config.filter("sleep.fragmentation", &mut sleep.fragmentation);
+        config.filter("sleep.deep_ratio", &mut sleep.deep_ratio);
+        config.filter("sleep.rem_ratio", &mut sleep.rem_ratio);
+        config.filter("sleep.latency_minutes", &mut sleep.latency_minutes);
+        config.filter("sleep.score", &mut sleep.score);
+        config.filter("sleep.fragmentation_index", &mut sleep.fragmentation_index);
+        config.filter("sleep.stage_transitions", &mut sleep.stage_transitions);
+        config.filter("sleep.waso_minutes", &mut sleep.waso_minutes);
+
+        let physiology = &mut window.physiology;
+        config.filter("physiology.hrv_rmssd_ms", &mut physiology.hrv_rmssd_ms);
+        config.filter("physiology.hrv_cv", &mut physiology.hrv_cv);
+        config.filter(
+            "physiology.cycle_phase_context",
+            &mut physiology.cycle_phase_context,
+        );
+        config.filter("physiology.resting_hr_bpm", &mut physiology.resting_hr_bpm);
+        config.filter(
+            "physiology.respiratory_rate",
+            &mut physiology.respiratory_rate,
+        );
+        config.filter(
+            "physiology.spo2_percentage",
+            &mut physiology.spo2_percentage,
+        );
+        config.filter("physiology.recovery_score", &mut physiology.recovery_score);
+        config.filter(
+            "physiology.recovery_balance",
+            &mut physiology.recovery_balance,
+        );
+        config.filter("physiology.vendor_stress", &mut physiology.vendor_stress);
+        config.filter("physiology.stress_ratios", &mut physiology.stress_ratios);
+
+        let activity = &mut window.activity;
+        config.filter("activity.strain_score", &mut activity.strain_score);
+        config.filter("activity.normalized_load", &mut activity.normalized_load);
+        config.filter("activity.calories", &mut activity.calories);
+        config.filter("activity.active_calories", &mut activity.active_calories);
+        config.filter("activity.resting_calories", &mut activity.resting_calories);
+        config.filter("activity.steps", &mut activity.steps);
+        config.filter("activity.active_minutes", &mut activity.active_minutes);
+        config.filter("activity.distance_meters", &mut activity.distance_meters);
+        config.filter("activity.hr_zone_minutes", &mut activity.hr_zone_minutes);
+        config.filter(
+            "activity.activity_goal_attainment",
+            &mut activity.activity_goal_attainment,
+        );
+        config.filter(
+            "activity.activity_goal_attainment_7d",
+            &mut activity.activity_goal_attainment_7d,
+        );
+        config.filter("activity.vo2_max", &mut activity.vo2_max);
+        config.filter("activity.fitness_age", &mut activity.fitness_age);
+        config.filter(
+            "activity.moderate_intensity_minutes",
+            &mut activity.moderate_intensity_minutes,
+        );
+        config.filter(
+            "activity.vigorous_intensity_minutes",
+            &mut activity.vigorous_intensity_minutes,
+        );
+        config.filter("activity.floors_climbed", &mut activity.floors_climbed);
+        config.filter(
+            "activity.elevation_gain_meters",
+            &mut activity.elevation_gain_meters,
+        );
+        if !config.allows("activity.workouts") {
+            activity.workouts.clear();
+        }
+        if !config.allows("activity.hourly") {
+            activity.hourly.clear();
+        }
+
+        let baseline = &mut window.baseline;
+        config.filter("baseline.hrv_ms", &mut baseline.hrv_ms);
+        config.filter("baseline.resting_hr_bpm", &mut baseline.resting_hr_bpm);
+        config.filter(
+            "baseline.sleep_duration_minutes",
+            &mut baseline.sleep_duration_minutes,
+        );
+        config.filter("baseline.sleep_efficiency", &mut baseline.sleep_efficiency);
+        config.filter(
+            "baseline.hrv_deviation_pct",
+            &mut baseline.hrv_deviation_pct,
+        );
+        config.filter(
+            "baseline.rhr_deviation_pct",
+            &mut baseline.rhr_deviation_pct,
+        );
+        config.filter(
+            "baseline.sleep_deviation_pct",
+            &mut baseline.sleep_deviation_pct,
+        );
+        config.filter("baseline.hrv_zscore", &mut baseline.hrv_zscore);
+        config.filter("baseline.rhr_zscore", &mut baseline.rhr_zscore);
+        config.filter("baseline.sleep_zscore", &mut baseline.sleep_zscore);
+        config.filter("baseline.hrv_std_ms", &mut baseline.hrv_std_ms);
+        config.filter("baseline.rhr_std_bpm", &mut baseline.rhr_std_bpm);
+        config.filter(
+            "baseline.sleep_std_minutes",
+            &mut baseline.sleep_std_minutes,
+        );
+        config.filter("baseline.vo2_max", &mut baseline.vo2_max);
+        config.filter(
+            "baseline.vo2_max_deviation_pct",
+            &mut baseline.vo2_max_deviation_pct,
+        );
+        config.filter("baseline.vo2_max_std", &mut baseline.vo2_max_std);
+        config.filter("baseline.vo2_max_zscore", &mut baseline.vo2_max_zscore);
+        config.filter("baseline.weight_kg", &mut baseline.weight_kg);
+        config.filter(
+            "baseline.body_fat_percentage",
+            &mut baseline.body_fat_percentage,
+        );
+        config.filter(
+            "baseline.weight_deviation_pct",
+            &mut baseline.weight_deviation_pct,
+        );
+        config.filter(
+            "baseline.body_fat_deviation_pct",
+            &mut baseline.body_fat_deviation_pct,
+        );
+        config.filter("baseline.weight_std_kg", &mut baseline.weight_std_kg);
+        config.filter("baseline.weight_zscore", &mut baseline.weight_zscore);
+        config.filter("baseline.body_fat_std_pct", &mut baseline.body_fat_std_pct);
+        config.filter("baseline.body_fat_zscore", &mut baseline.body_fat_zscore);
+        config.filter("baseline.hrv_trend", &mut baseline.hrv_trend);
+        config.filter("baseline.rhr_trend", &mut baseline.rhr_trend);
+        config.filter("baseline.sleep_trend", &mut baseline.sleep_trend);
+        config.filter(
+            "baseline.baseline_shift_detected",
+            &mut baseline.baseline_shift_detected,
+        );
+        config.filter(
+            "baseline.baseline_shift_date",
+            &mut baseline.baseline_shift_date,
+        );
+        config.filter("baseline.device_changed", &mut baseline.device_changed);
+        config.filter(
+            "baseline.days_since_device_switch",
+            &mut baseline.days_since_device_switch,
+        );
+        config.filter("baseline.tag", &mut baseline.tag);
+
+        if let Some(body) = &mut window.body {
+            config.filter("body.weight_kg", &mut body.weight_kg);
+            config.filter("body.body_fat_percentage", &mut body.body_fat_percentage);
+            config.filter("body.bmi", &mut body.bmi);
+        }
+        window.body = window.body.take().filter(|b| !b.is_empty());
     }
 
     fn extract_vendor_sleep(
@@ -184,7 +1028,7 @@ impl HsiEncoder {
         // Include vendor-specific raw sleep score
         if let Some(score) = canonical.sleep.vendor_sleep_score {
             vendor.insert(
-                format!("{}_sleep_score", canonical.vendor.as_str()),
+                format!("{}_sleep_score", canonical.source_of("sleep").as_str()),
                 serde_json::Value::from(score),
             );
         }
@@ -205,11 +1049,44 @@ impl HsiEncoder {
 
         if let Some(score) = canonical.recovery.vendor_recovery_score {
             vendor.insert(
-                format!("{}_recovery_score", canonical.vendor.as_str()),
+                format!(
+                    "{}_recovery_score",
+                    canonical.source_of("recovery_score").as_str()
+                ),
                 serde_json::Value::from(score),
             );
         }
 
+        let body_battery = &canonical.recovery.body_battery;
+        if !body_battery.is_empty() {
+            vendor.insert(
+                format!(
+                    "{}_body_battery",
+                    canonical.source_of("recovery_score").as_str()
+                ),
+                serde_json::to_value(body_battery).unwrap_or(serde_json::Value::Null),
+            );
+        }
+
+        let stress = &canonical.recovery.stress;
+        if !stress.is_empty() {
+            vendor.insert(
+                format!("{}_stress", canonical.source_of("stress").as_str()),
+                serde_json::to_value(stress).unwrap_or(serde_json::Value::Null),
+            );
+        }
+
+        let respiration = &canonical.recovery.respiration;
+        if !respiration.is_empty() {
+            vendor.insert(
+                format!(
+                    "{}_respiration",
+                    canonical.source_of("respiration").as_str()
+                ),
+                serde_json::to_value(respiration).unwrap_or(serde_json::Value::Null),
+            );
+        }
+
         if let Some(raw) = canonical.vendor_raw.get("recovery") {
             vendor.insert("raw".to_string(), raw.clone());
         }
@@ -225,7 +1102,10 @@ impl HsiEncoder {
 
         if let Some(score) = canonical.activity.vendor_strain_score {
             vendor.insert(
-                format!("{}_strain_score", canonical.vendor.as_str()),
+                format!(
+                    "{}_strain_score",
+                    canonical.source_of("strain_score").as_str()
+                ),
                 serde_json::Value::from(score),
             );
         }
@@ -241,12 +1121,114 @@ impl HsiEncoder {
     }
 }
 
+/// Per-workout output, with strain scaled like the day's `strain_score`
+fn encode_workouts(canonical: &crate::types::CanonicalWearSignals) -> Vec<HsiWorkout> {
+    let scale = vendor_score_scale("strain_score", canonical.source_of("workouts"));
+    canonical
+        .activity
+        .workouts
+        .iter()
+        .map(|w| HsiWorkout {
+            start_utc: w.start_time.to_rfc3339(),
+            end_utc: w.end_time.to_rfc3339(),
+            duration_minutes: w.duration_minutes(),
+            sport: w.sport.clone(),
+            strain_score: w.vendor_strain_score.map(|s| (s / scale).clamp(0.0, 1.0)),
+            calories: w.calories,
+            average_hr_bpm: w.average_hr_bpm,
+            max_hr_bpm: w.max_hr_bpm,
+            distance_meters: w.distance_meters,
+            hr_zone_minutes: w.hr_zone_minutes,
+        })
+        .collect()
+}
+
+fn encode_hourly(canonical: &crate::types::CanonicalWearSignals) -> Vec<HsiHourlyActivity> {
+    canonical
+        .activity
+        .hourly
+        .iter()
+        .map(|h| HsiHourlyActivity {
+            start_utc: h.start.to_rfc3339(),
+            steps: h.steps,
+            calories: h.calories,
+            active_minutes: h.active_minutes,
+            average_hr_bpm: h.average_hr_bpm,
+            max_hr_bpm: h.max_hr_bpm,
+            coverage: h.coverage,
+        })
+        .collect()
+}
+
+/// Contributing raw event IDs, summarized per the configured mode
+fn source_events(canonical: &CanonicalWearSignals, mode: SourceEvents) -> Option<HsiSourceEvents> {
+    if mode == SourceEvents::Off {
+        return None;
+    }
+    let ids: BTreeSet<&String> = canonical.field_events.values().flatten().collect();
+    let mut hasher = Sha256::new();
+    for id in &ids {
+        hasher.update(id.as_bytes());
+        hasher.update(b"\n");
+    }
+    let digest: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Some(HsiSourceEvents {
+        count: ids.len(),
+        digest: format!("sha256:{digest}"),
+        ids: (mode == SourceEvents::List).then(|| ids.into_iter().cloned().collect()),
+    })
+}
+
+/// Source vendor label; fused days list every contributing vendor (e.g. `garmin+whoop`)
+fn source_vendor(canonical: &crate::types::CanonicalWearSignals) -> String {
+    if canonical.field_sources.is_empty() {
+        return canonical.vendor.as_str().to_string();
+    }
+    let mut vendors: Vec<&str> = canonical
+        .field_sources
+        .values()
+        .map(|v| v.as_str())
+        .collect();
+    vendors.sort_unstable();
+    vendors.dedup();
+    vendors.join("+")
+}
+
+/// Stable producer instance ID for a device: a UUID derived from a SHA-256
+/// hash of `device_id`, so the same device gets the same ID after restarts
+/// without the ID revealing the device
+pub fn device_instance_id(device_id: &str) -> String {
+    let digest = Sha256::digest(format!("{PRODUCER_NAME}:instance:{device_id}"));
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    Builder::from_random_bytes(bytes).into_uuid().to_string()
+}
+
+/// Derive a version 4 UUID from a seed using the splitmix64 generator
+fn seeded_uuid(seed: u64) -> Uuid {
+    let mut state = seed;
+    let mut bytes = [0u8; 16];
+    for chunk in bytes.chunks_mut(8) {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        chunk.copy_from_slice(&z.to_le_bytes());
+    }
+    Builder::from_random_bytes(bytes).into_uuid()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::{
         Baselines, CanonicalActivity, CanonicalRecovery, CanonicalSleep, CanonicalWearSignals,
-        DerivedSignals, NormalizedSignals, Vendor,
+        DerivedSignals, NormalizedSignals, UnmappedSignal, Vendor,
     };
 
     fn make_test_contextual() -> ContextualSignals {
@@ -254,7 +1236,9 @@ mod tests {
             vendor: Vendor::Whoop,
             date: "2024-01-15".to_string(),
             device_id: "test-device".to_string(),
+            user_id: None,
             timezone: "America/New_York".to_string(),
+            utc_offset_minutes: None,
             observed_at: Utc::now(),
             sleep: CanonicalSleep {
                 total_sleep_minutes: Some(420.0),
@@ -268,6 +1252,7 @@ mod tests {
             },
             recovery: CanonicalRecovery {
                 hrv_rmssd_ms: Some(65.0),
+                hrv_cv: None,
                 resting_hr_bpm: Some(55.0),
                 vendor_recovery_score: Some(75.0),
                 spo2_percentage: Some(97.0),
@@ -280,7 +1265,16 @@ mod tests {
                 steps: Some(8500),
                 ..Default::default()
             },
+            body: Default::default(),
+            input_quality: None,
             vendor_raw: HashMap::new(),
+            field_sources: HashMap::new(),
+            field_events: HashMap::new(),
+            intraday: BTreeMap::new(),
+            latest_event_at: BTreeMap::new(),
+            tags: Vec::new(),
+            warnings: Vec::new(),
+            unmapped_signals: BTreeMap::new(),
         };
 
         let normalized = NormalizedSignals {
@@ -289,7 +1283,9 @@ mod tests {
             recovery_score: Some(0.75),
             strain_score: Some(0.595),
             coverage: 0.9,
+            class_coverage: 1.0,
             quality_flags: vec![],
+            score_normalization: BTreeMap::new(),
         };
 
         let derived = DerivedSignals {
@@ -299,6 +1295,9 @@ mod tests {
             deep_sleep_ratio: Some(0.2),
             rem_sleep_ratio: Some(0.25),
             normalized_load: Some(0.79),
+            hypnogram: None,
+            goal_attainment: None,
+            bmi: None,
         };
 
         let baselines = Baselines {
@@ -307,6 +1306,7 @@ mod tests {
             sleep_baseline_minutes: Some(410.0),
             sleep_efficiency_baseline: Some(0.86),
             baseline_days: 14,
+            ..Default::default()
         };
 
         ContextualSignals {
@@ -315,9 +1315,80 @@ mod tests {
             hrv_deviation_pct: Some(4.8),
             rhr_deviation_pct: Some(1.9),
             sleep_duration_deviation_pct: Some(2.4),
+            hrv_zscore: Some(0.6),
+            rhr_zscore: Some(0.4),
+            sleep_duration_zscore: Some(0.3),
+            hrv_anomaly: false,
+            rhr_anomaly: false,
+            sleep_anomaly: false,
+            goal_attainment_7d: None,
+            vo2_max_deviation_pct: None,
+            weight_deviation_pct: None,
+            body_fat_deviation_pct: None,
+            vo2_max_zscore: None,
+            weight_zscore: None,
+            body_fat_zscore: None,
+            hrv_trend: None,
+            rhr_trend: None,
+            sleep_trend: None,
+            baseline_shift_detected: None,
+            baseline_shift_date: None,
+            device_changed: None,
+            days_since_device_switch: None,
         }
     }
 
+    #[test]
+    fn test_default_confidence_policy() {
+        let mut signals = make_test_contextual();
+        let policy = DefaultConfidencePolicy;
+
+        // coverage 0.9, fresh, all three vendor scores, full baseline window
+        let full = policy.confidence(&signals, 0);
+        assert!((full - (0.45 + 0.2 + 0.15 + 0.15)).abs() < 1e-9);
+
+        // A week-old reading loses the freshness term
+        assert!((policy.confidence(&signals, 7 * 24 * 3600) - (full - 0.2)).abs() < 1e-9);
+
+        signals.baselines.baseline_days = 7;
+        signals.derived.normalized.strain_score = None;
+        let partial = policy.confidence(&signals, 0);
+        assert!((partial - (0.45 + 0.2 + 0.1 + 0.075)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_confidence_uses_configured_baseline_window() {
+        let mut signals = make_test_contextual();
+        let policy = DefaultConfidencePolicy;
+
+        // Seven days fill a 7-day window but only half of the default one
+        signals.baselines.baseline_days = 7;
+        signals.baselines.window_days = crate::baseline::BaselineStore::new(7)
+            .get_baselines()
+            .window_days;
+        assert_eq!(signals.baselines.window_days, 7);
+        let full = policy.confidence(&signals, 0);
+        assert!((full - (0.45 + 0.2 + 0.15 + 0.15)).abs() < 1e-9);
+
+        signals.baselines.window_days = 28;
+        let quarter = policy.confidence(&signals, 0);
+        assert!((quarter - (0.45 + 0.2 + 0.15 + 0.0375)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_custom_confidence_policy() {
+        struct Fixed;
+        impl ConfidencePolicy for Fixed {
+            fn confidence(&self, _: &ContextualSignals, _: i64) -> f64 {
+                0.42
+            }
+        }
+
+        let encoder = HsiEncoder::new().with_confidence_policy(Arc::new(Fixed));
+        let payload = encoder.encode(&make_test_contextual()).unwrap();
+        assert_eq!(payload.quality.confidence, 0.42);
+    }
+
     #[test]
     fn test_encode_hsi_payload() {
         let signals = make_test_contextual();
@@ -374,4 +1445,143 @@ mod tests {
         assert!(parsed.get("quality").is_some());
         assert!(parsed.get("windows").is_some());
     }
+
+    #[test]
+    fn test_encoder_config_filters_axes() {
+        let config = EncoderConfig::default()
+            .with_include_axes(["physiology", "sleep.duration_minutes"])
+            .with_exclude_axes(["physiology.recovery_score"])
+            .with_meta_suppressed(true);
+        assert!(config.allows("physiology.hrv_rmssd_ms"));
+        assert!(!config.allows("physiology.recovery_score"));
+        assert!(!config.allows("sleep.efficiency"));
+
+        let encoder = HsiEncoder::new().with_encoder_config(config);
+        let payload = encoder.encode(&make_test_contextual()).unwrap();
+        let window = &payload.windows[0];
+        assert!(window.physiology.hrv_rmssd_ms.is_some());
+        assert!(window.physiology.recovery_score.is_none());
+        assert!(window.sleep.duration_minutes.is_some());
+        assert!(window.sleep.efficiency.is_none());
+        assert!(window.activity.strain_score.is_none());
+        assert!(window.sleep.vendor.is_empty());
+    }
+
+    #[test]
+    fn test_privacy_policy_strips_vendor_raw() {
+        let mut signals = make_test_contextual();
+        signals
+            .derived
+            .normalized
+            .canonical
+            .vendor_raw
+            .insert("recovery".to_string(), serde_json::json!({"score": 75}));
+
+        let default = HsiEncoder::new().encode(&signals).unwrap();
+        assert!(default.windows[0].physiology.vendor.contains_key("raw"));
+
+        let encoder = HsiEncoder::new()
+            .with_privacy_policy(PrivacyPolicy::default().with_vendor_raw_allowed(false));
+        let payload = encoder.encode(&signals).unwrap();
+        assert!(!payload.windows[0].physiology.vendor.contains_key("raw"));
+    }
+
+    #[test]
+    fn test_scrubber_hashes_identifiers_in_vendor_raw() {
+        use crate::privacy::{ScrubMode, Scrubber};
+
+        let mut signals = make_test_contextual();
+        signals.derived.normalized.canonical.vendor_raw.insert(
+            "recovery".to_string(),
+            serde_json::json!({"user_id": 10129, "score": 75}),
+        );
+
+        let scrubber = Scrubber::new(ScrubMode::Hash);
+        let encoder = HsiEncoder::new()
+            .with_privacy_policy(PrivacyPolicy::default().with_scrubber(scrubber.clone()));
+        let payload = encoder.encode(&signals).unwrap();
+        let raw = &payload.windows[0].physiology.vendor["raw"];
+
+        assert_eq!(raw["user_id"], scrubber.pseudonym("10129"));
+        assert_eq!(raw["score"], 75);
+    }
+
+    #[test]
+    fn test_unmapped_signals_passed_through_to_meta() {
+        let mut signals = make_test_contextual();
+        signals
+            .derived
+            .normalized
+            .canonical
+            .unmapped_signals
+            .insert(
+                "signal.glucose".to_string(),
+                UnmappedSignal {
+                    count: 2,
+                    mean: Some(5.5),
+                    unit: Some("mmol/l".to_string()),
+                },
+            );
+
+        assert!(HsiEncoder::new().encode(&signals).unwrap().meta.is_none());
+        let config = EncoderConfig::default().with_unmapped_signals(true);
+        let payload = HsiEncoder::new()
+            .with_encoder_config(config.clone())
+            .encode(&signals)
+            .unwrap();
+        let meta = payload.meta.unwrap();
+        assert_eq!(
+            meta[UNMAPPED_SIGNALS_META_KEY]["signal.glucose"]["mean"],
+            5.5
+        );
+
+        let suppressed = HsiEncoder::new()
+            .with_encoder_config(config.with_meta_suppressed(true))
+            .encode(&signals)
+            .unwrap();
+        assert!(suppressed.meta.is_none());
+    }
+
+    #[test]
+    fn test_compact_encoding() {
+        let signals = make_test_contextual();
+        let pretty = HsiEncoder::new().encode_to_json(&signals).unwrap();
+        let compact = HsiEncoder::new()
+            .with_encoder_config(EncoderConfig::default().with_compact(true))
+            .encode_to_json(&signals)
+            .unwrap();
+        assert!(!compact.contains('\n'));
+        assert!(compact.len() < pretty.len());
+        assert!(!compact.contains("\"raw\""));
+    }
+
+    #[test]
+    fn test_deterministic_encoder() {
+        let signals = make_test_contextual();
+        let computed_at = DateTime::parse_from_rfc3339("2024-02-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let a = HsiEncoder::deterministic(42, computed_at)
+            .encode_to_json(&signals)
+            .unwrap();
+        let b = HsiEncoder::deterministic(42, computed_at)
+            .encode_to_json(&signals)
+            .unwrap();
+        assert_eq!(a, b);
+
+        let parsed: serde_json::Value = serde_json::from_str(&a).unwrap();
+        assert_eq!(
+            parsed["provenance"]["computed_at_utc"],
+            "2024-02-01T00:00:00+00:00"
+        );
+
+        let other = HsiEncoder::deterministic(7, computed_at)
+            .encode(&signals)
+            .unwrap();
+        assert_ne!(
+            parsed["producer"]["instance_id"],
+            other.producer.instance_id.as_str()
+        );
+    }
 }
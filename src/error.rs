@@ -37,4 +37,16 @@ pub enum ComputeError {
 
     #[error("Insufficient events for computation: {0}")]
     InsufficientEvents(String),
+
+    #[error("No data for date: {0}")]
+    NoDataForDate(String),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Integrity check failed: {0}")]
+    IntegrityError(String),
+
+    #[error("Out-of-order input: {0}")]
+    OutOfOrder(String),
 }
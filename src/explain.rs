@@ -0,0 +1,650 @@
+//! Metric provenance for explain mode
+//!
+//! [`explain`] reconstructs how each emitted wearable metric was computed from
+//! a day's contextual signals: the formula, the intermediate values it used
+//! and, for raw-event input, the IDs of the events behind those values. This
+//! is what `FluxProcessor::process_with_trace` and `flux transform --explain`
+//! emit alongside each HSI payload.
+//!
+//! [`StagedWindow`] keeps a window's intermediate signals instead, for
+//! `flux inspect`.
+
+use crate::normalizer::{signal_classes, vendor_score_scale};
+use crate::types::{Baselines, ContextualSignals};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How one output metric was computed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricTrace {
+    /// Output axis, named like [`EncoderConfig`](crate::encoder::EncoderConfig) axes
+    pub metric: String,
+    /// Emitted value
+    pub value: f64,
+    /// Expression over `inputs` that produced `value`
+    pub formula: String,
+    /// Intermediate values the formula used
+    pub inputs: BTreeMap<String, f64>,
+    /// Raw event IDs behind the inputs (raw-event input only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_events: Vec<String>,
+}
+
+/// Provenance of every metric in one daily window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayTrace {
+    /// Day (`YYYY-MM-DD`)
+    pub date: String,
+    /// Quality flags raised while normalizing the day
+    pub quality_flags: Vec<String>,
+    /// One entry per emitted metric
+    pub metrics: Vec<MetricTrace>,
+}
+
+/// An HSI payload together with the trace explaining it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TracedPayload {
+    /// HSI JSON, exactly as the non-tracing entry points emit it
+    pub hsi: String,
+    /// How each metric in `hsi` was computed
+    pub trace: DayTrace,
+}
+
+/// A window's intermediate signals together with the payload encoded from them
+/// (wearable days or behavioral sessions)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedWindow<C, P> {
+    /// Contextual signals, which embed the derived and normalized signals
+    pub contextual: C,
+    /// HSI payload, as the typed entry points return it
+    pub hsi: P,
+}
+
+/// Builds the metric list for a day
+struct Tracer<'a> {
+    signals: &'a ContextualSignals,
+    metrics: Vec<MetricTrace>,
+}
+
+impl Tracer<'_> {
+    /// Record `metric` when `value` is present
+    fn push(
+        &mut self,
+        metric: &str,
+        value: Option<f64>,
+        formula: &str,
+        inputs: &[(&str, Option<f64>)],
+        fields: &[&str],
+    ) {
+        let Some(value) = value else {
+            return;
+        };
+        let field_events = &self.signals.derived.normalized.canonical.field_events;
+        let mut source_events: Vec<String> = fields
+            .iter()
+            .filter_map(|field| field_events.get(*field))
+            .flatten()
+            .cloned()
+            .collect();
+        source_events.sort();
+        source_events.dedup();
+
+        self.metrics.push(MetricTrace {
+            metric: metric.to_string(),
+            value,
+            formula: formula.to_string(),
+            inputs: inputs
+                .iter()
+                .filter_map(|(name, value)| value.map(|v| (name.to_string(), v)))
+                .collect(),
+            source_events,
+        });
+    }
+
+    /// Record a metric copied straight from a canonical field
+    fn passthrough(&mut self, metric: &str, input: &str, value: Option<f64>, field: &str) {
+        self.push(metric, value, input, &[(input, value)], &[field]);
+    }
+}
+
+/// Explain every metric of a day's contextual signals.
+///
+/// `prior` are the baselines the day was compared against, i.e. the store's
+/// baselines before the day was added.
+pub fn explain(signals: &ContextualSignals, prior: &Baselines) -> DayTrace {
+    let derived = &signals.derived;
+    let normalized = &derived.normalized;
+    let canonical = &normalized.canonical;
+    let sleep = &canonical.sleep;
+    let recovery = &canonical.recovery;
+    let activity = &canonical.activity;
+    let body = &canonical.body;
+
+    let mut t = Tracer {
+        signals,
+        metrics: Vec::new(),
+    };
+
+    // Sleep
+    let total = sleep.total_sleep_minutes;
+    let in_bed = sleep.time_in_bed_minutes;
+    t.passthrough(
+        "sleep.duration_minutes",
+        "total_sleep_minutes",
+        total,
+        "sleep",
+    );
+    t.push(
+        "sleep.efficiency",
+        derived.sleep_efficiency,
+        "min(total_sleep_minutes / time_in_bed_minutes, 1)",
+        &[
+            ("total_sleep_minutes", total),
+            ("time_in_bed_minutes", in_bed),
+        ],
+        &["sleep"],
+    );
+    let awakenings = sleep.awakenings.map(f64::from);
+    if awakenings.is_some() && total.is_some_and(|m| m > 0.0) {
+        t.push(
+            "sleep.fragmentation",
+            derived.sleep_fragmentation,
+            "min(awakenings / (total_sleep_minutes / 60) / 6, 1)",
+            &[("awakenings", awakenings), ("total_sleep_minutes", total)],
+            &["sleep"],
+        );
+    } else {
+        t.push(
+            "sleep.fragmentation",
+            derived.sleep_fragmentation,
+            "min(awake_minutes / time_in_bed_minutes, 1)",
+            &[
+                ("awake_minutes", sleep.awake_minutes),
+                ("time_in_bed_minutes", in_bed),
+            ],
+            &["sleep"],
+        );
+    }
+    t.push(
+        "sleep.deep_ratio",
+        derived.deep_sleep_ratio,
+        "min(deep_sleep_minutes / total_sleep_minutes, 1)",
+        &[
+            ("deep_sleep_minutes", sleep.deep_sleep_minutes),
+            ("total_sleep_minutes", total),
+        ],
+        &["sleep"],
+    );
+    t.push(
+        "sleep.rem_ratio",
+        derived.rem_sleep_ratio,
+        "min(rem_sleep_minutes / total_sleep_minutes, 1)",
+        &[
+            ("rem_sleep_minutes", sleep.rem_sleep_minutes),
+            ("total_sleep_minutes", total),
+        ],
+        &["sleep"],
+    );
+    t.passthrough(
+        "sleep.latency_minutes",
+        "latency_minutes",
+        sleep.latency_minutes,
+        "sleep",
+    );
+    let scale = vendor_score_scale("sleep", canonical.source_of("sleep"));
+    t.push(
+        "sleep.score",
+        normalized.sleep_score,
+        &format!("clamp(vendor_sleep_score / {scale}, 0, 1)"),
+        &[("vendor_sleep_score", sleep.vendor_sleep_score)],
+        &["sleep"],
+    );
+    if let Some(stats) = derived.hypnogram {
+        let segments = [("hypnogram_segments", Some(sleep.hypnogram.len() as f64))];
+        t.push(
+            "sleep.stage_transitions",
+            Some(stats.stage_transitions as f64),
+            "count(stage changes between sleep onset and final awakening)",
+            &segments,
+            &["sleep"],
+        );
+        t.push(
+            "sleep.waso_minutes",
+            Some(stats.waso_minutes),
+            "sum(awake minutes between sleep onset and final awakening)",
+            &segments,
+            &["sleep"],
+        );
+        t.push(
+            "sleep.fragmentation_index",
+            Some(stats.fragmentation_index),
+            "stage_transitions / (asleep minutes / 60)",
+            &[("stage_transitions", Some(stats.stage_transitions as f64))],
+            &["sleep"],
+        );
+    }
+
+    // Physiology
+    match canonical.intraday.get("hrv_rmssd_ms") {
+        Some(samples) => {
+            let inputs = [("rmssd_samples", Some(samples.len() as f64))];
+            t.push(
+                "physiology.hrv_rmssd_ms",
+                recovery.hrv_rmssd_ms,
+                "median(nightly rmssd samples)",
+                &inputs,
+                &["hrv_rmssd_ms"],
+            );
+            t.push(
+                "physiology.hrv_cv",
+                recovery.hrv_cv,
+                "stddev(nightly rmssd samples) / mean(nightly rmssd samples)",
+                &inputs,
+                &["hrv_rmssd_ms"],
+            );
+        }
+        None => t.passthrough(
+            "physiology.hrv_rmssd_ms",
+            "hrv_rmssd_ms",
+            recovery.hrv_rmssd_ms,
+            "hrv_rmssd_ms",
+        ),
+    }
+    t.passthrough(
+        "physiology.resting_hr_bpm",
+        "resting_hr_bpm",
+        recovery.resting_hr_bpm,
+        "resting_hr_bpm",
+    );
+    t.passthrough(
+        "physiology.respiratory_rate",
+        "respiratory_rate",
+        sleep.respiratory_rate,
+        "sleep",
+    );
+    t.passthrough(
+        "physiology.spo2_percentage",
+        "spo2_percentage",
+        recovery.spo2_percentage,
+        "spo2_percentage",
+    );
+    let scale = vendor_score_scale("recovery_score", canonical.source_of("recovery_score"));
+    t.push(
+        "physiology.recovery_score",
+        normalized.recovery_score,
+        &format!("clamp(vendor_recovery_score / {scale}, 0, 1)"),
+        &[("vendor_recovery_score", recovery.vendor_recovery_score)],
+        &["recovery_score"],
+    );
+    t.push(
+        "physiology.recovery_balance",
+        recovery.body_battery.recovery_balance(),
+        "clamp((body_battery_charged - body_battery_drained) / 100, -1, 1)",
+        &[
+            ("body_battery_charged", recovery.body_battery.charged),
+            ("body_battery_drained", recovery.body_battery.drained),
+        ],
+        &["body_battery"],
+    );
+    t.push(
+        "physiology.vendor_stress",
+        recovery.stress.normalized_average(),
+        "clamp(stress_average / 100, 0, 1)",
+        &[("stress_average", recovery.stress.average)],
+        &["stress"],
+    );
+
+    // Activity
+    let scale = vendor_score_scale("strain_score", canonical.source_of("strain_score"));
+    t.push(
+        "activity.strain_score",
+        normalized.strain_score,
+        &format!("clamp(vendor_strain_score / {scale}, 0, 1)"),
+        &[("vendor_strain_score", activity.vendor_strain_score)],
+        &["strain_score"],
+    );
+    let load_formula = if normalized.recovery_score.is_some() {
+        "clamp(strain_score / recovery_score, 0, 2)"
+    } else {
+        "strain_score"
+    };
+    t.push(
+        "activity.normalized_load",
+        derived.normalized_load,
+        load_formula,
+        &[
+            ("strain_score", normalized.strain_score),
+            ("recovery_score", normalized.recovery_score),
+        ],
+        &["strain_score", "recovery_score"],
+    );
+    t.passthrough(
+        "activity.calories",
+        "calories",
+        activity.calories,
+        "calories",
+    );
+    t.passthrough(
+        "activity.active_calories",
+        "active_calories",
+        activity.active_calories,
+        "calories",
+    );
+    t.passthrough(
+        "activity.resting_calories",
+        "resting_calories",
+        activity.resting_calories,
+        "calories",
+    );
+    t.passthrough(
+        "activity.steps",
+        "steps",
+        activity.steps.map(f64::from),
+        "steps",
+    );
+    t.passthrough(
+        "activity.active_minutes",
+        "active_minutes",
+        activity.active_minutes,
+        "active_minutes",
+    );
+    t.passthrough(
+        "activity.moderate_intensity_minutes",
+        "moderate_intensity_minutes",
+        activity.moderate_intensity_minutes,
+        "active_minutes",
+    );
+    t.passthrough(
+        "activity.vigorous_intensity_minutes",
+        "vigorous_intensity_minutes",
+        activity.vigorous_intensity_minutes,
+        "active_minutes",
+    );
+    t.passthrough(
+        "activity.distance_meters",
+        "distance_meters",
+        activity.distance_meters,
+        "distance_meters",
+    );
+    t.push(
+        "activity.activity_goal_attainment",
+        derived.goal_attainment,
+        "mean(min(steps / goal_steps, 1), min(active_minutes / goal_active_minutes, 1))",
+        &[
+            ("steps", activity.steps.map(f64::from)),
+            ("active_minutes", activity.active_minutes),
+        ],
+        &["steps", "active_minutes"],
+    );
+    t.passthrough(
+        "activity.floors_climbed",
+        "floors_climbed",
+        activity.floors_climbed.map(f64::from),
+        "floors",
+    );
+    t.passthrough(
+        "activity.elevation_gain_meters",
+        "elevation_gain_meters",
+        activity.elevation_gain_meters,
+        "floors",
+    );
+    t.passthrough("activity.vo2_max", "vo2_max", activity.vo2_max, "vo2_max");
+    t.passthrough(
+        "activity.fitness_age",
+        "fitness_age",
+        activity.fitness_age,
+        "vo2_max",
+    );
+    t.passthrough("body.weight_kg", "weight_kg", body.weight_kg, "body");
+    t.passthrough(
+        "body.body_fat_percentage",
+        "body_fat_percentage",
+        body.body_fat_percentage,
+        "body",
+    );
+    t.push(
+        "body.bmi",
+        derived.bmi,
+        "weight_kg / (height_cm / 100) ^ 2",
+        &[("weight_kg", body.weight_kg)],
+        &["body"],
+    );
+
+    // Baseline comparisons use the baselines from before this day was added
+    let comparisons = [
+        (
+            "hrv",
+            ("hrv_rmssd_ms", recovery.hrv_rmssd_ms),
+            (prior.hrv_baseline_ms, prior.hrv_std_ms),
+            (signals.hrv_deviation_pct, signals.hrv_zscore),
+            signals.hrv_trend,
+        ),
+        (
+            "rhr",
+            ("resting_hr_bpm", recovery.resting_hr_bpm),
+            (prior.rhr_baseline_bpm, prior.rhr_std_bpm),
+            (signals.rhr_deviation_pct, signals.rhr_zscore),
+            signals.rhr_trend,
+        ),
+        (
+            "sleep",
+            ("total_sleep_minutes", total),
+            (prior.sleep_baseline_minutes, prior.sleep_std_minutes),
+            (
+                signals.sleep_duration_deviation_pct,
+                signals.sleep_duration_zscore,
+            ),
+            signals.sleep_trend,
+        ),
+    ];
+    t.push(
+        "baseline.vo2_max_deviation_pct",
+        signals.vo2_max_deviation_pct,
+        "(vo2_max - baseline_mean) / baseline_mean * 100",
+        &[
+            ("vo2_max", activity.vo2_max),
+            ("baseline_mean", prior.vo2_max_baseline),
+        ],
+        &["vo2_max"],
+    );
+    t.push(
+        "baseline.weight_deviation_pct",
+        signals.weight_deviation_pct,
+        "(weight_kg - baseline_mean) / baseline_mean * 100",
+        &[
+            ("weight_kg", body.weight_kg),
+            ("baseline_mean", prior.weight_baseline_kg),
+        ],
+        &["body"],
+    );
+    t.push(
+        "baseline.body_fat_deviation_pct",
+        signals.body_fat_deviation_pct,
+        "(body_fat_percentage - baseline_mean) / baseline_mean * 100",
+        &[
+            ("body_fat_percentage", body.body_fat_percentage),
+            ("baseline_mean", prior.body_fat_baseline_pct),
+        ],
+        &["body"],
+    );
+    let slow_zscores = [
+        (
+            "vo2_max",
+            ("vo2_max", activity.vo2_max),
+            (prior.vo2_max_baseline, prior.vo2_max_std),
+            signals.vo2_max_zscore,
+        ),
+        (
+            "weight",
+            ("weight_kg", body.weight_kg),
+            (prior.weight_baseline_kg, prior.weight_std_kg),
+            signals.weight_zscore,
+        ),
+        (
+            "body_fat",
+            ("body_fat_percentage", body.body_fat_percentage),
+            (prior.body_fat_baseline_pct, prior.body_fat_std_pct),
+            signals.body_fat_zscore,
+        ),
+    ];
+    for (metric, (input, current), (mean, std), zscore) in slow_zscores {
+        let field = if metric == "vo2_max" {
+            "vo2_max"
+        } else {
+            "body"
+        };
+        t.push(
+            &format!("baseline.{metric}_zscore"),
+            zscore,
+            &format!("({input} - baseline_mean) / baseline_std"),
+            &[
+                (input, current),
+                ("baseline_mean", mean),
+                ("baseline_std", std),
+            ],
+            &[field],
+        );
+    }
+    for (metric, (input, current), (mean, std), (deviation, zscore), trend) in comparisons {
+        let field = if metric == "sleep" { "sleep" } else { input };
+        t.push(
+            &format!("baseline.{metric}_deviation_pct"),
+            deviation,
+            &format!("({input} - baseline_mean) / baseline_mean * 100"),
+            &[(input, current), ("baseline_mean", mean)],
+            &[field],
+        );
+        t.push(
+            &format!("baseline.{metric}_zscore"),
+            zscore,
+            &format!("({input} - baseline_mean) / baseline_std"),
+            &[
+                (input, current),
+                ("baseline_mean", mean),
+                ("baseline_std", std),
+            ],
+            &[field],
+        );
+        t.push(
+            &format!("baseline.{metric}_trend"),
+            trend,
+            &format!("clamp(slope_7d({input}) * 6 / mean_7d({input}), -1, 1)"),
+            &[(input, current)],
+            &[field],
+        );
+    }
+
+    // Quality
+    let classes = signal_classes(canonical);
+    let formula = classes
+        .iter()
+        .map(|c| format!("{} * {}", c.weight, c.name))
+        .collect::<Vec<_>>()
+        .join(" + ");
+    let inputs: Vec<_> = classes
+        .iter()
+        .map(|c| (c.name, Some(c.completeness())))
+        .collect();
+    t.push(
+        "quality.coverage",
+        Some(normalized.coverage),
+        &formula,
+        &inputs,
+        &[],
+    );
+
+    DayTrace {
+        date: canonical.date.clone(),
+        quality_flags: normalized
+            .quality_flags
+            .iter()
+            .map(|f| f.as_str().to_string())
+            .collect(),
+        metrics: t.metrics,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::BaselineStore;
+    use crate::features::FeatureDeriver;
+    use crate::normalizer::Normalizer;
+    use crate::types::{CanonicalRecovery, CanonicalSleep, CanonicalWearSignals, Vendor};
+    use chrono::Utc;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn make_canonical(hrv: f64) -> CanonicalWearSignals {
+        CanonicalWearSignals {
+            vendor: Vendor::Whoop,
+            date: "2024-01-15".to_string(),
+            device_id: "test-device".to_string(),
+            user_id: None,
+            timezone: "UTC".to_string(),
+            utc_offset_minutes: None,
+            observed_at: Utc::now(),
+            sleep: CanonicalSleep {
+                total_sleep_minutes: Some(420.0),
+                time_in_bed_minutes: Some(480.0),
+                awakenings: Some(3),
+                ..Default::default()
+            },
+            recovery: CanonicalRecovery {
+                hrv_rmssd_ms: Some(hrv),
+                vendor_recovery_score: Some(75.0),
+                ..Default::default()
+            },
+            activity: Default::default(),
+            body: Default::default(),
+            input_quality: None,
+            vendor_raw: HashMap::new(),
+            field_sources: HashMap::new(),
+            field_events: HashMap::from([("sleep".to_string(), vec!["evt-1".to_string()])]),
+            intraday: BTreeMap::new(),
+            latest_event_at: BTreeMap::new(),
+            tags: Vec::new(),
+            warnings: Vec::new(),
+            unmapped_signals: BTreeMap::new(),
+        }
+    }
+
+    fn metric<'a>(trace: &'a DayTrace, name: &str) -> &'a MetricTrace {
+        trace.metrics.iter().find(|m| m.metric == name).unwrap()
+    }
+
+    #[test]
+    fn test_explains_derived_metrics() {
+        let mut store = BaselineStore::default();
+        let prior = store.get_baselines();
+        let derived = FeatureDeriver::derive(Normalizer::normalize(&make_canonical(60.0)));
+        let trace = explain(&store.update_and_contextualize(derived), &prior);
+
+        let efficiency = metric(&trace, "sleep.efficiency");
+        assert!((efficiency.value - 420.0 / 480.0).abs() < 1e-12);
+        assert_eq!(efficiency.inputs["time_in_bed_minutes"], 480.0);
+        assert_eq!(efficiency.source_events, vec!["evt-1"]);
+
+        let recovery = metric(&trace, "physiology.recovery_score");
+        assert_eq!(recovery.formula, "clamp(vendor_recovery_score / 100, 0, 1)");
+        assert!(recovery.source_events.is_empty());
+
+        // Absent values are not explained
+        assert!(trace.metrics.iter().all(|m| m.metric != "activity.steps"));
+    }
+
+    #[test]
+    fn test_deviation_uses_prior_baseline() {
+        let mut store = BaselineStore::default();
+        store.update_and_contextualize(FeatureDeriver::derive(Normalizer::normalize(
+            &make_canonical(60.0),
+        )));
+
+        let prior = store.get_baselines();
+        let derived = FeatureDeriver::derive(Normalizer::normalize(&make_canonical(72.0)));
+        let trace = explain(&store.update_and_contextualize(derived), &prior);
+
+        let deviation = metric(&trace, "baseline.hrv_deviation_pct");
+        assert_eq!(deviation.inputs["baseline_mean"], 60.0);
+        assert!((deviation.value - 20.0).abs() < 1e-9);
+    }
+}
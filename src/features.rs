@@ -3,9 +3,127 @@
 //! This module derives higher-order features from normalized signals:
 //! - Sleep efficiency and fragmentation
 //! - Sleep stage ratios
+//! - Hypnogram continuity (transitions, WASO)
 //! - Load normalization
+//! - Activity goal attainment
+//! - BMI, when the user's height is configured
+//!
+//! Embedders can add their own metrics with a [`FeatureStage`].
+
+use crate::types::{
+    CanonicalActivity, DerivedSignals, HypnogramStats, NormalizedSignals, SleepStage,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Meta key under which [`FeatureStage`] metrics are emitted
+pub const FEATURE_STAGES_META_KEY: &str = "features";
+
+/// Custom metrics derived alongside the built-in features.
+///
+/// Register a stage on [`FluxProcessor`]; its metrics are emitted in
+/// `meta.features.<name>` of each day's payload and take no part in
+/// baselines or scores.
+///
+/// [`FluxProcessor`]: crate::pipeline::FluxProcessor
+pub trait FeatureStage: Send + Sync {
+    /// Stage name, the key of its metrics in `meta.features`
+    fn name(&self) -> &str;
+
+    /// Metrics for one day; non-finite values are dropped
+    fn derive(&self, normalized: &NormalizedSignals) -> BTreeMap<String, f64>;
+}
+
+/// Run feature stages over a day, keyed by stage name.
+///
+/// Stages sharing a name are merged, later stages winning. Returns `None`
+/// when no stage derived anything.
+pub fn derive_stages(
+    stages: &[Arc<dyn FeatureStage>],
+    normalized: &NormalizedSignals,
+) -> Option<BTreeMap<String, BTreeMap<String, f64>>> {
+    let mut metrics: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+    for stage in stages {
+        let derived: BTreeMap<String, f64> = stage
+            .derive(normalized)
+            .into_iter()
+            .filter(|(_, value)| value.is_finite())
+            .collect();
+        if !derived.is_empty() {
+            metrics
+                .entry(stage.name().to_string())
+                .or_default()
+                .extend(derived);
+        }
+    }
+    (!metrics.is_empty()).then_some(metrics)
+}
+
+/// Daily activity goal; either target may be left unset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActivityGoal {
+    /// Daily step target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps: Option<u32>,
+    /// Daily active-minute target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_minutes: Option<f64>,
+}
+
+impl ActivityGoal {
+    /// Set the daily step target
+    pub fn with_steps(mut self, steps: u32) -> Self {
+        self.steps = Some(steps);
+        self
+    }
+
+    /// Set the daily active-minute target
+    pub fn with_active_minutes(mut self, minutes: f64) -> Self {
+        self.active_minutes = Some(minutes);
+        self
+    }
+
+    /// Progress toward the goal (0-1): the mean over targets the day has data
+    /// for, each capped at 1
+    pub fn attainment(&self, activity: &CanonicalActivity) -> Option<f64> {
+        let progress = |value: Option<f64>, target: Option<f64>| match (value, target) {
+            (Some(value), Some(target)) if target > 0.0 => Some((value / target).min(1.0)),
+            _ => None,
+        };
+        let parts: Vec<f64> = [
+            progress(activity.steps.map(f64::from), self.steps.map(f64::from)),
+            progress(activity.active_minutes, self.active_minutes),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        (!parts.is_empty()).then(|| parts.iter().sum::<f64>() / parts.len() as f64)
+    }
+}
+
+/// Per-user settings for features that need more than the day's signals
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureConfig {
+    /// Goal that activity is scored against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity_goal: Option<ActivityGoal>,
+    /// Height (cm), for BMI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height_cm: Option<f64>,
+}
 
-use crate::types::{DerivedSignals, NormalizedSignals};
+impl FeatureConfig {
+    pub fn with_activity_goal(mut self, goal: ActivityGoal) -> Self {
+        self.activity_goal = Some(goal);
+        self
+    }
+
+    pub fn with_height_cm(mut self, height_cm: f64) -> Self {
+        self.height_cm = Some(height_cm);
+        self
+    }
+}
 
 /// Feature deriver for computing derived signals
 pub struct FeatureDeriver;
@@ -13,11 +131,24 @@ pub struct FeatureDeriver;
 impl FeatureDeriver {
     /// Derive features from normalized signals
     pub fn derive(normalized: NormalizedSignals) -> DerivedSignals {
+        Self::derive_with_config(normalized, &FeatureConfig::default())
+    }
+
+    /// Derive features using per-user settings
+    pub fn derive_with_config(
+        normalized: NormalizedSignals,
+        config: &FeatureConfig,
+    ) -> DerivedSignals {
         let sleep_efficiency = compute_sleep_efficiency(&normalized);
         let sleep_fragmentation = compute_sleep_fragmentation(&normalized);
         let deep_sleep_ratio = compute_deep_sleep_ratio(&normalized);
         let rem_sleep_ratio = compute_rem_sleep_ratio(&normalized);
         let normalized_load = compute_normalized_load(&normalized);
+        let hypnogram = compute_hypnogram_stats(&normalized);
+        let goal_attainment = config
+            .activity_goal
+            .and_then(|g| g.attainment(&normalized.canonical.activity));
+        let bmi = compute_bmi(&normalized, config.height_cm);
 
         DerivedSignals {
             normalized,
@@ -26,10 +157,21 @@ impl FeatureDeriver {
             deep_sleep_ratio,
             rem_sleep_ratio,
             normalized_load,
+            hypnogram,
+            goal_attainment,
+            bmi,
         }
     }
 }
 
+/// Calculate BMI: weight (kg) / height (m) squared
+fn compute_bmi(signals: &NormalizedSignals, height_cm: Option<f64>) -> Option<f64> {
+    match (signals.canonical.body.weight_kg, height_cm) {
+        (Some(weight), Some(height)) if height > 0.0 => Some(weight / (height / 100.0).powi(2)),
+        _ => None,
+    }
+}
+
 /// Calculate sleep efficiency: actual sleep time / time in bed
 fn compute_sleep_efficiency(signals: &NormalizedSignals) -> Option<f64> {
     let sleep = &signals.canonical.sleep;
@@ -90,6 +232,46 @@ fn compute_rem_sleep_ratio(signals: &NormalizedSignals) -> Option<f64> {
     }
 }
 
+/// Calculate continuity metrics from the hypnogram.
+///
+/// Only the sleep period counts: from the first asleep segment to the end of
+/// the last one. Unknown segments are ignored.
+fn compute_hypnogram_stats(signals: &NormalizedSignals) -> Option<HypnogramStats> {
+    let segments: Vec<_> = signals
+        .canonical
+        .sleep
+        .hypnogram
+        .iter()
+        .filter(|s| s.stage != SleepStage::Unknown)
+        .collect();
+    let onset = segments.iter().position(|s| s.stage != SleepStage::Awake)?;
+    let last = segments
+        .iter()
+        .rposition(|s| s.stage != SleepStage::Awake)?;
+    let period = &segments[onset..=last];
+
+    let stage_transitions = period
+        .windows(2)
+        .filter(|w| w[0].stage != w[1].stage)
+        .count() as u32;
+    let (waso_minutes, sleep_minutes) =
+        period
+            .iter()
+            .fold((0.0, 0.0), |(awake, asleep), s| match s.stage {
+                SleepStage::Awake => (awake + s.duration_minutes, asleep),
+                _ => (awake, asleep + s.duration_minutes),
+            });
+    if sleep_minutes <= 0.0 {
+        return None;
+    }
+
+    Some(HypnogramStats {
+        stage_transitions,
+        waso_minutes,
+        fragmentation_index: stage_transitions as f64 / (sleep_minutes / 60.0),
+    })
+}
+
 /// Calculate normalized load: strain adjusted by recovery
 /// Higher recovery allows for higher sustainable load
 fn compute_normalized_load(signals: &NormalizedSignals) -> Option<f64> {
@@ -114,14 +296,16 @@ mod tests {
         CanonicalActivity, CanonicalRecovery, CanonicalSleep, CanonicalWearSignals, Vendor,
     };
     use chrono::Utc;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     fn make_test_normalized() -> NormalizedSignals {
         let canonical = CanonicalWearSignals {
             vendor: Vendor::Whoop,
             date: "2024-01-15".to_string(),
             device_id: "test-device".to_string(),
+            user_id: None,
             timezone: "UTC".to_string(),
+            utc_offset_minutes: None,
             observed_at: Utc::now(),
             sleep: CanonicalSleep {
                 total_sleep_minutes: Some(420.0),
@@ -143,7 +327,16 @@ mod tests {
                 calories: Some(2200.0),
                 ..Default::default()
             },
+            body: Default::default(),
+            input_quality: None,
             vendor_raw: HashMap::new(),
+            field_sources: HashMap::new(),
+            field_events: HashMap::new(),
+            intraday: BTreeMap::new(),
+            latest_event_at: BTreeMap::new(),
+            tags: Vec::new(),
+            warnings: Vec::new(),
+            unmapped_signals: BTreeMap::new(),
         };
 
         NormalizedSignals {
@@ -152,7 +345,9 @@ mod tests {
             recovery_score: Some(0.75),
             strain_score: Some(0.595),
             coverage: 0.9,
+            class_coverage: 1.0,
             quality_flags: vec![],
+            score_normalization: BTreeMap::new(),
         }
     }
 
@@ -206,4 +401,42 @@ mod tests {
         // strain 0.595 / recovery 0.75 = 0.793
         assert!((derived.normalized_load.unwrap() - 0.793).abs() < 0.01);
     }
+
+    #[test]
+    fn test_hypnogram_stats() {
+        use crate::types::HypnogramSegment;
+
+        let mut normalized = make_test_normalized();
+        assert!(FeatureDeriver::derive(normalized.clone())
+            .hypnogram
+            .is_none());
+
+        let start = "2024-01-14T23:00:00Z"
+            .parse::<chrono::DateTime<Utc>>()
+            .unwrap();
+        let mut offset = 0.0;
+        for (stage, minutes) in [
+            (SleepStage::Awake, 15.0),
+            (SleepStage::Light, 120.0),
+            (SleepStage::Deep, 90.0),
+            (SleepStage::Awake, 15.0),
+            (SleepStage::Unknown, 5.0),
+            (SleepStage::Rem, 240.0),
+            (SleepStage::Awake, 10.0),
+        ] {
+            normalized.canonical.sleep.hypnogram.push(HypnogramSegment {
+                stage,
+                start: start + chrono::Duration::minutes(offset as i64),
+                duration_minutes: minutes,
+            });
+            offset += minutes;
+        }
+
+        let stats = FeatureDeriver::derive(normalized).hypnogram.unwrap();
+        // light -> deep -> awake -> rem; leading and trailing wake excluded
+        assert_eq!(stats.stage_transitions, 3);
+        assert_eq!(stats.waso_minutes, 15.0);
+        // 3 transitions over 450 asleep minutes
+        assert!((stats.fragmentation_index - 0.4).abs() < 1e-9);
+    }
 }
@@ -9,7 +9,10 @@ use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::ptr;
 
-use crate::behavior::{behavior_to_hsi, BehaviorProcessor};
+use crate::baseline::BaselineStore;
+use crate::behavior::{behavior_to_hsi, BehaviorConfig, BehaviorProcessor, SnapshotOptions};
+use crate::encoder::EncoderConfig;
+use crate::error::ComputeError;
 use crate::pipeline::{garmin_to_hsi_daily, whoop_to_hsi_daily, FluxProcessor};
 
 // Thread-local storage for the last error message
@@ -324,6 +327,99 @@ pub unsafe extern "C" fn flux_processor_process_garmin(
     }
 }
 
+/// Process wear.raw_event.v1 NDJSON with a stateful processor.
+///
+/// # Safety
+/// - `processor` must be a valid pointer returned by `flux_processor_new`.
+/// - `ndjson`, `timezone`, and `device_id` must be valid null-terminated C strings.
+/// - Returns a newly allocated string that must be freed with `flux_free_string`.
+/// - Returns NULL on error; call `flux_last_error` to get the error message.
+#[no_mangle]
+pub unsafe extern "C" fn flux_processor_process_raw_events(
+    processor: *mut FluxProcessorHandle,
+    ndjson: *const c_char,
+    timezone: *const c_char,
+    device_id: *const c_char,
+) -> *mut c_char {
+    clear_last_error();
+
+    if processor.is_null() {
+        set_last_error("Null processor pointer");
+        return ptr::null_mut();
+    }
+
+    let handle = &mut *processor;
+
+    let ndjson_str = match cstr_to_string(ndjson) {
+        Some(s) => s,
+        None => {
+            set_last_error("Invalid NDJSON string pointer");
+            return ptr::null_mut();
+        }
+    };
+
+    let tz_str = match cstr_to_string(timezone) {
+        Some(s) => s,
+        None => {
+            set_last_error("Invalid timezone string pointer");
+            return ptr::null_mut();
+        }
+    };
+
+    let device_str = match cstr_to_string(device_id) {
+        Some(s) => s,
+        None => {
+            set_last_error("Invalid device_id string pointer");
+            return ptr::null_mut();
+        }
+    };
+
+    match handle
+        .processor
+        .process_raw_events(&ndjson_str, &tz_str, &device_str)
+    {
+        Ok(payloads) => {
+            let result = vec_to_json_array(payloads);
+            string_to_cstr(&result)
+        }
+        Err(e) => {
+            set_last_error(&e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Take the warnings raised by the processor since the last call.
+///
+/// Warnings report input that was adjusted or left out without failing
+/// processing (e.g. unknown signal types, dropped naps, clamped values).
+///
+/// # Safety
+/// - `processor` must be a valid pointer returned by `flux_processor_new`.
+/// - Returns a newly allocated JSON array string that must be freed with `flux_free_string`.
+/// - Returns NULL on error; call `flux_last_error` to get the error message.
+#[no_mangle]
+pub unsafe extern "C" fn flux_processor_take_warnings(
+    processor: *mut FluxProcessorHandle,
+) -> *mut c_char {
+    clear_last_error();
+
+    if processor.is_null() {
+        set_last_error("Null processor pointer");
+        return ptr::null_mut();
+    }
+
+    let handle = &mut *processor;
+
+    match serde_json::to_string(&handle.processor.take_warnings()) {
+        Ok(json) => string_to_cstr(&json),
+        Err(e) => {
+            set_last_error(&e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
 /// Save processor baselines to JSON.
 ///
 /// # Safety
@@ -390,6 +486,49 @@ pub unsafe extern "C" fn flux_processor_load_baselines(
     }
 }
 
+/// Set output options from a JSON `EncoderConfig`.
+///
+/// Omitted fields keep their defaults, e.g. `{"compact": true}`.
+///
+/// # Safety
+/// - `processor` must be a valid pointer returned by `flux_processor_new`.
+/// - `config_json` must be a valid null-terminated C string.
+/// - Returns 0 on success, non-zero on error.
+/// - On error, call `flux_last_error` to get the error message.
+#[no_mangle]
+pub unsafe extern "C" fn flux_processor_set_encoder_config(
+    processor: *mut FluxProcessorHandle,
+    config_json: *const c_char,
+) -> i32 {
+    clear_last_error();
+
+    if processor.is_null() {
+        set_last_error("Null processor pointer");
+        return -1;
+    }
+
+    let handle = &mut *processor;
+
+    let json_str = match cstr_to_string(config_json) {
+        Some(s) => s,
+        None => {
+            set_last_error("Invalid config string pointer");
+            return -1;
+        }
+    };
+
+    match serde_json::from_str::<EncoderConfig>(&json_str) {
+        Ok(config) => {
+            handle.processor.set_encoder_config(config);
+            0
+        }
+        Err(e) => {
+            set_last_error(&ComputeError::from(e).to_string());
+            -1
+        }
+    }
+}
+
 // ============================================================================
 // Behavioral Stateless API
 // ============================================================================
@@ -503,6 +642,183 @@ pub unsafe extern "C" fn flux_behavior_processor_process(
     }
 }
 
+/// Set behavioral thresholds from a JSON `BehaviorConfig`.
+///
+/// Omitted fields keep their defaults, e.g. `{"deep_focus_min_duration_sec": 300}`.
+///
+/// # Safety
+/// - `processor` must be a valid pointer returned by `flux_behavior_processor_new`.
+/// - `config_json` must be a valid null-terminated C string.
+/// - Returns 0 on success, non-zero on error.
+/// - On error, call `flux_last_error` to get the error message.
+#[no_mangle]
+pub unsafe extern "C" fn flux_behavior_processor_set_config(
+    processor: *mut BehaviorProcessorHandle,
+    config_json: *const c_char,
+) -> i32 {
+    clear_last_error();
+
+    if processor.is_null() {
+        set_last_error("Null processor pointer");
+        return -1;
+    }
+
+    let handle = &mut *processor;
+
+    let json_str = match cstr_to_string(config_json) {
+        Some(s) => s,
+        None => {
+            set_last_error("Invalid config string pointer");
+            return -1;
+        }
+    };
+
+    let result = serde_json::from_str::<BehaviorConfig>(&json_str)
+        .map_err(ComputeError::from)
+        .and_then(|config| handle.processor.set_config(config));
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            set_last_error(&e.to_string());
+            -1
+        }
+    }
+}
+
+/// Set output options from a JSON `EncoderConfig`.
+///
+/// Omitted fields keep their defaults, e.g. `{"compact": true}`.
+///
+/// # Safety
+/// - `processor` must be a valid pointer returned by `flux_behavior_processor_new`.
+/// - `config_json` must be a valid null-terminated C string.
+/// - Returns 0 on success, non-zero on error.
+/// - On error, call `flux_last_error` to get the error message.
+#[no_mangle]
+pub unsafe extern "C" fn flux_behavior_processor_set_encoder_config(
+    processor: *mut BehaviorProcessorHandle,
+    config_json: *const c_char,
+) -> i32 {
+    clear_last_error();
+
+    if processor.is_null() {
+        set_last_error("Null processor pointer");
+        return -1;
+    }
+
+    let handle = &mut *processor;
+
+    let json_str = match cstr_to_string(config_json) {
+        Some(s) => s,
+        None => {
+            set_last_error("Invalid config string pointer");
+            return -1;
+        }
+    };
+
+    match serde_json::from_str::<EncoderConfig>(&json_str) {
+        Ok(config) => {
+            handle.processor.set_encoder_config(config);
+            0
+        }
+        Err(e) => {
+            set_last_error(&ComputeError::from(e).to_string());
+            -1
+        }
+    }
+}
+
+/// Attach wearable baselines (JSON saved by `flux_processor_save_baselines`)
+/// as bio context on session payloads.
+///
+/// # Safety
+/// - `processor` must be a valid pointer returned by `flux_behavior_processor_new`.
+/// - `baselines_json` must be a valid null-terminated C string, or NULL to detach.
+/// - Returns 0 on success, non-zero on error.
+/// - On error, call `flux_last_error` to get the error message.
+#[no_mangle]
+pub unsafe extern "C" fn flux_behavior_processor_set_bio_context(
+    processor: *mut BehaviorProcessorHandle,
+    baselines_json: *const c_char,
+) -> i32 {
+    clear_last_error();
+
+    if processor.is_null() {
+        set_last_error("Null processor pointer");
+        return -1;
+    }
+
+    let handle = &mut *processor;
+
+    if baselines_json.is_null() {
+        handle.processor.set_bio_context(None);
+        return 0;
+    }
+    let json_str = match cstr_to_string(baselines_json) {
+        Some(s) => s,
+        None => {
+            set_last_error("Invalid baselines string pointer");
+            return -1;
+        }
+    };
+
+    match BaselineStore::from_json(&json_str) {
+        Ok(store) => {
+            handle.processor.set_bio_context(Some(&store));
+            0
+        }
+        Err(e) => {
+            set_last_error(&ComputeError::from(e).to_string());
+            -1
+        }
+    }
+}
+
+/// Set bio context decay from a JSON `SnapshotOptions`.
+///
+/// Omitted fields keep their defaults, e.g. `{"sleep_half_life_days": 4.0}`.
+///
+/// # Safety
+/// - `processor` must be a valid pointer returned by `flux_behavior_processor_new`.
+/// - `options_json` must be a valid null-terminated C string.
+/// - Returns 0 on success, non-zero on error.
+/// - On error, call `flux_last_error` to get the error message.
+#[no_mangle]
+pub unsafe extern "C" fn flux_behavior_processor_set_snapshot_options(
+    processor: *mut BehaviorProcessorHandle,
+    options_json: *const c_char,
+) -> i32 {
+    clear_last_error();
+
+    if processor.is_null() {
+        set_last_error("Null processor pointer");
+        return -1;
+    }
+
+    let handle = &mut *processor;
+
+    let json_str = match cstr_to_string(options_json) {
+        Some(s) => s,
+        None => {
+            set_last_error("Invalid options string pointer");
+            return -1;
+        }
+    };
+
+    let result = serde_json::from_str::<SnapshotOptions>(&json_str)
+        .map_err(ComputeError::from)
+        .and_then(|options| handle.processor.set_snapshot_options(options));
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            set_last_error(&e.to_string());
+            -1
+        }
+    }
+}
+
 /// Save behavioral processor baselines to JSON.
 ///
 /// # Safety
@@ -753,6 +1069,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ffi_processor_raw_events() {
+        unsafe {
+            let processor = flux_processor_new(7);
+            let ndjson = CString::new(
+                r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"whoop"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":65.0,"unit":"ms"}}}"#,
+            )
+            .unwrap();
+            let tz = CString::new("UTC").unwrap();
+            let device = CString::new("device").unwrap();
+
+            let result = flux_processor_process_raw_events(
+                processor,
+                ndjson.as_ptr(),
+                tz.as_ptr(),
+                device.as_ptr(),
+            );
+            assert!(!result.is_null());
+            let result_str = CStr::from_ptr(result).to_str().unwrap();
+            assert!(result_str.contains("\"hrv_rmssd_ms\": 65.0"));
+            flux_free_string(result);
+
+            let warnings = flux_processor_take_warnings(processor);
+            assert_eq!(CStr::from_ptr(warnings).to_str().unwrap(), "[]");
+            flux_free_string(warnings);
+
+            let invalid = CString::new("not json").unwrap();
+            let result = flux_processor_process_raw_events(
+                processor,
+                invalid.as_ptr(),
+                tz.as_ptr(),
+                device.as_ptr(),
+            );
+            assert!(result.is_null());
+            assert!(!flux_last_error().is_null());
+
+            flux_processor_free(processor);
+        }
+    }
+
     #[test]
     fn test_ffi_error_handling() {
         unsafe {
@@ -874,6 +1230,132 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ffi_behavior_processor_set_config() {
+        unsafe {
+            let processor = flux_behavior_processor_new(10);
+
+            let config = CString::new(r#"{"deep_focus_min_duration_sec": 300}"#).unwrap();
+            assert_eq!(
+                flux_behavior_processor_set_config(processor, config.as_ptr()),
+                0
+            );
+            assert_eq!(
+                (*processor).processor.config().deep_focus_min_duration_sec,
+                300.0
+            );
+
+            let invalid = CString::new(r#"{"idle_gap_threshold_sec": 0}"#).unwrap();
+            assert_ne!(
+                flux_behavior_processor_set_config(processor, invalid.as_ptr()),
+                0
+            );
+            let error = CStr::from_ptr(flux_last_error()).to_str().unwrap();
+            assert!(error.contains("idle_gap_threshold_sec"));
+
+            flux_behavior_processor_free(processor);
+        }
+    }
+
+    #[test]
+    fn test_ffi_behavior_processor_set_encoder_config() {
+        unsafe {
+            let processor = flux_behavior_processor_new(10);
+
+            let config = CString::new(r#"{"compact": true}"#).unwrap();
+            assert_eq!(
+                flux_behavior_processor_set_encoder_config(processor, config.as_ptr()),
+                0
+            );
+
+            let session = sample_behavior_session_json();
+            let result = flux_behavior_processor_process(processor, session.as_ptr());
+            assert!(!result.is_null());
+            let output = CStr::from_ptr(result).to_str().unwrap();
+            assert!(!output.contains('\n'));
+            flux_free_string(result);
+
+            let invalid = CString::new(r#"{"compact": "yes"}"#).unwrap();
+            assert_ne!(
+                flux_behavior_processor_set_encoder_config(processor, invalid.as_ptr()),
+                0
+            );
+
+            flux_behavior_processor_free(processor);
+        }
+    }
+
+    #[test]
+    fn test_ffi_behavior_processor_bio_context_decay() {
+        use crate::baseline::DayMetrics;
+
+        let mut store = BaselineStore::default();
+        for (date, hrv) in [
+            ("2024-01-11", 60.0),
+            ("2024-01-12", 60.0),
+            ("2024-01-13", 45.0),
+        ] {
+            store.replace_day(
+                date,
+                DayMetrics {
+                    hrv_ms: Some(hrv),
+                    sleep_efficiency: Some(0.9),
+                    ..DayMetrics::default()
+                },
+            );
+        }
+        let baselines = CString::new(store.to_json().unwrap()).unwrap();
+        let confidence = |payload: &serde_json::Value, domain: &str| {
+            payload["axes"][domain]["readings"][0]["confidence"]
+                .as_f64()
+                .unwrap()
+        };
+
+        unsafe {
+            let processor = flux_behavior_processor_new(10);
+            assert_eq!(
+                flux_behavior_processor_set_bio_context(processor, baselines.as_ptr()),
+                0
+            );
+            let options = CString::new(r#"{"hrv_half_life_days": 2.0}"#).unwrap();
+            assert_eq!(
+                flux_behavior_processor_set_snapshot_options(processor, options.as_ptr()),
+                0
+            );
+
+            // The wearable day is two days old: HRV halves once, sleep less
+            let session = sample_behavior_session_json();
+            let result = flux_behavior_processor_process(processor, session.as_ptr());
+            assert!(!result.is_null());
+            let payload: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(result).to_str().unwrap()).unwrap();
+            flux_free_string(result);
+            assert_eq!(confidence(&payload, "physiology"), 0.5);
+            assert!(confidence(&payload, "sleep") > 0.5);
+
+            let invalid = CString::new(r#"{"sleep_half_life_days": -1}"#).unwrap();
+            assert_ne!(
+                flux_behavior_processor_set_snapshot_options(processor, invalid.as_ptr()),
+                0
+            );
+            let error = CStr::from_ptr(flux_last_error()).to_str().unwrap();
+            assert!(error.contains("sleep_half_life_days"));
+
+            // NULL detaches the bio context
+            assert_eq!(
+                flux_behavior_processor_set_bio_context(processor, ptr::null()),
+                0
+            );
+            let result = flux_behavior_processor_process(processor, session.as_ptr());
+            let payload: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(result).to_str().unwrap()).unwrap();
+            flux_free_string(result);
+            assert!(payload["axes"].get("physiology").is_none());
+
+            flux_behavior_processor_free(processor);
+        }
+    }
+
     #[test]
     fn test_ffi_behavior_error_handling() {
         unsafe {
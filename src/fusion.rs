@@ -0,0 +1,475 @@
+//! Multi-device fusion
+//!
+//! When a user wears several devices, `RawEventAdapter::to_canonical` produces
+//! one canonical day per vendor. This module merges those into a single day
+//! using per-field vendor priorities and records which vendor supplied each
+//! field.
+
+use crate::types::{CanonicalWearSignals, Vendor};
+use std::collections::{BTreeMap, HashMap};
+
+/// Signal groups that fusion rules apply to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FusionField {
+    /// The whole sleep block (stages, timing and score stay consistent)
+    Sleep,
+    Hrv,
+    RestingHr,
+    RecoveryScore,
+    Spo2,
+    SkinTemperature,
+    StrainScore,
+    /// Total, active and resting calories
+    Calories,
+    /// Average and max heart rate
+    HeartRate,
+    Steps,
+    Distance,
+    /// Active minutes and their moderate/vigorous split
+    ActiveMinutes,
+    CyclePhase,
+    /// Individual workout sessions
+    Workouts,
+    /// Day-level time in heart rate zones
+    HrZones,
+    /// VO2max and the fitness age estimated from it
+    Vo2Max,
+    /// Floors climbed and elevation gain
+    Floors,
+    /// All-day stress levels and bucket durations
+    Stress,
+    /// All-day respiration rates
+    Respiration,
+    /// Weight and body fat from a scale
+    Body,
+    /// Hour-level activity from hourly summaries
+    Hourly,
+}
+
+impl FusionField {
+    /// All fusion fields, in merge order
+    pub const ALL: [FusionField; 21] = [
+        FusionField::Sleep,
+        FusionField::Hrv,
+        FusionField::RestingHr,
+        FusionField::RecoveryScore,
+        FusionField::Spo2,
+        FusionField::SkinTemperature,
+        FusionField::StrainScore,
+        FusionField::Calories,
+        FusionField::HeartRate,
+        FusionField::Steps,
+        FusionField::Distance,
+        FusionField::ActiveMinutes,
+        FusionField::CyclePhase,
+        FusionField::Workouts,
+        FusionField::HrZones,
+        FusionField::Vo2Max,
+        FusionField::Floors,
+        FusionField::Stress,
+        FusionField::Respiration,
+        FusionField::Body,
+        FusionField::Hourly,
+    ];
+
+    /// Key used in `field_sources` provenance
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FusionField::Sleep => "sleep",
+            FusionField::Hrv => "hrv_rmssd_ms",
+            FusionField::RestingHr => "resting_hr_bpm",
+            FusionField::RecoveryScore => "recovery_score",
+            FusionField::Spo2 => "spo2_percentage",
+            FusionField::SkinTemperature => "skin_temp_deviation_c",
+            FusionField::StrainScore => "strain_score",
+            FusionField::Calories => "calories",
+            FusionField::HeartRate => "heart_rate",
+            FusionField::Steps => "steps",
+            FusionField::Distance => "distance_meters",
+            FusionField::ActiveMinutes => "active_minutes",
+            FusionField::CyclePhase => "cycle_phase",
+            FusionField::Workouts => "workouts",
+            FusionField::HrZones => "hr_zone_minutes",
+            FusionField::Vo2Max => "vo2_max",
+            FusionField::Floors => "floors",
+            FusionField::Stress => "stress",
+            FusionField::Respiration => "respiration",
+            FusionField::Body => "body",
+            FusionField::Hourly => "hourly",
+        }
+    }
+
+    fn is_present(&self, s: &CanonicalWearSignals) -> bool {
+        match self {
+            FusionField::Sleep => {
+                s.sleep.total_sleep_minutes.is_some() || s.sleep.start_time.is_some()
+            }
+            FusionField::Hrv => s.recovery.hrv_rmssd_ms.is_some(),
+            FusionField::RestingHr => s.recovery.resting_hr_bpm.is_some(),
+            FusionField::RecoveryScore => s.recovery.vendor_recovery_score.is_some(),
+            FusionField::Spo2 => s.recovery.spo2_percentage.is_some(),
+            FusionField::SkinTemperature => s.recovery.skin_temp_deviation_c.is_some(),
+            FusionField::StrainScore => s.activity.vendor_strain_score.is_some(),
+            FusionField::Calories => {
+                s.activity.calories.is_some()
+                    || s.activity.active_calories.is_some()
+                    || s.activity.resting_calories.is_some()
+            }
+            FusionField::HeartRate => {
+                s.activity.average_hr_bpm.is_some() || s.activity.max_hr_bpm.is_some()
+            }
+            FusionField::Steps => s.activity.steps.is_some(),
+            FusionField::Distance => s.activity.distance_meters.is_some(),
+            FusionField::ActiveMinutes => {
+                s.activity.active_minutes.is_some()
+                    || s.activity.moderate_intensity_minutes.is_some()
+                    || s.activity.vigorous_intensity_minutes.is_some()
+            }
+            FusionField::CyclePhase => s.recovery.cycle_phase.is_some(),
+            FusionField::Workouts => !s.activity.workouts.is_empty(),
+            FusionField::HrZones => !s.activity.hr_zone_minutes.is_empty(),
+            FusionField::Vo2Max => s.activity.vo2_max.is_some() || s.activity.fitness_age.is_some(),
+            FusionField::Floors => {
+                s.activity.floors_climbed.is_some() || s.activity.elevation_gain_meters.is_some()
+            }
+            FusionField::Stress => !s.recovery.stress.is_empty(),
+            FusionField::Respiration => !s.recovery.respiration.is_empty(),
+            FusionField::Body => !s.body.is_empty(),
+            FusionField::Hourly => !s.activity.hourly.is_empty(),
+        }
+    }
+
+    fn copy(&self, from: &CanonicalWearSignals, to: &mut CanonicalWearSignals) {
+        match self {
+            FusionField::Sleep => to.sleep = from.sleep.clone(),
+            FusionField::Hrv => {
+                to.recovery.hrv_rmssd_ms = from.recovery.hrv_rmssd_ms;
+                to.recovery.hrv_cv = from.recovery.hrv_cv;
+                copy_intraday(self.as_str(), from, to);
+            }
+            FusionField::RestingHr => to.recovery.resting_hr_bpm = from.recovery.resting_hr_bpm,
+            FusionField::RecoveryScore => {
+                to.recovery.vendor_recovery_score = from.recovery.vendor_recovery_score;
+                to.recovery.body_battery = from.recovery.body_battery;
+            }
+            FusionField::Spo2 => to.recovery.spo2_percentage = from.recovery.spo2_percentage,
+            FusionField::SkinTemperature => {
+                to.recovery.skin_temp_deviation_c = from.recovery.skin_temp_deviation_c
+            }
+            FusionField::StrainScore => {
+                to.activity.vendor_strain_score = from.activity.vendor_strain_score
+            }
+            FusionField::Calories => {
+                to.activity.calories = from.activity.calories;
+                to.activity.active_calories = from.activity.active_calories;
+                to.activity.resting_calories = from.activity.resting_calories;
+            }
+            FusionField::HeartRate => {
+                to.activity.average_hr_bpm = from.activity.average_hr_bpm;
+                to.activity.max_hr_bpm = from.activity.max_hr_bpm;
+                copy_intraday(self.as_str(), from, to);
+            }
+            FusionField::Steps => to.activity.steps = from.activity.steps,
+            FusionField::Distance => to.activity.distance_meters = from.activity.distance_meters,
+            FusionField::ActiveMinutes => {
+                to.activity.active_minutes = from.activity.active_minutes;
+                to.activity.moderate_intensity_minutes = from.activity.moderate_intensity_minutes;
+                to.activity.vigorous_intensity_minutes = from.activity.vigorous_intensity_minutes;
+            }
+            FusionField::CyclePhase => to.recovery.cycle_phase = from.recovery.cycle_phase,
+            FusionField::Workouts => to.activity.workouts = from.activity.workouts.clone(),
+            FusionField::HrZones => to.activity.hr_zone_minutes = from.activity.hr_zone_minutes,
+            FusionField::Vo2Max => {
+                to.activity.vo2_max = from.activity.vo2_max;
+                to.activity.fitness_age = from.activity.fitness_age;
+            }
+            FusionField::Floors => {
+                to.activity.floors_climbed = from.activity.floors_climbed;
+                to.activity.elevation_gain_meters = from.activity.elevation_gain_meters;
+            }
+            FusionField::Stress => to.recovery.stress = from.recovery.stress,
+            FusionField::Respiration => to.recovery.respiration = from.recovery.respiration,
+            FusionField::Body => to.body = from.body,
+            FusionField::Hourly => to.activity.hourly = from.activity.hourly.clone(),
+        }
+    }
+}
+
+/// Take a field's intraday samples from the device that supplied the field
+fn copy_intraday(field: &str, from: &CanonicalWearSignals, to: &mut CanonicalWearSignals) {
+    match from.intraday.get(field) {
+        Some(samples) => {
+            to.intraday.insert(field.to_string(), samples.clone());
+        }
+        None => {
+            to.intraday.remove(field);
+        }
+    }
+}
+
+/// Fusion stage merging same-day canonical signals from several devices.
+///
+/// For each field the first vendor in its priority list that has a value
+/// wins; vendors missing from the list are tried afterwards in default order.
+#[derive(Debug, Clone)]
+pub struct DeviceFusion {
+    default_priority: Vec<Vendor>,
+    field_priority: HashMap<FusionField, Vec<Vendor>>,
+}
+
+impl Default for DeviceFusion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceFusion {
+    /// Create a fusion stage with default rules: WHOOP for sleep and
+    /// physiology, Garmin for movement (steps, distance, calories, heart rate).
+    pub fn new() -> Self {
+        let movement = vec![Vendor::Garmin, Vendor::Whoop];
+        let field_priority = [
+            FusionField::Steps,
+            FusionField::Distance,
+            FusionField::Calories,
+            FusionField::HeartRate,
+            FusionField::ActiveMinutes,
+        ]
+        .into_iter()
+        .map(|f| (f, movement.clone()))
+        .collect();
+
+        Self {
+            default_priority: vec![Vendor::Whoop, Vendor::Garmin],
+            field_priority,
+        }
+    }
+
+    /// Set the vendor priority used for fields without a specific rule
+    pub fn with_default_priority(mut self, priority: Vec<Vendor>) -> Self {
+        self.default_priority = priority;
+        self
+    }
+
+    /// Set the vendor priority for a single field
+    pub fn with_field_priority(mut self, field: FusionField, priority: Vec<Vendor>) -> Self {
+        self.field_priority.insert(field, priority);
+        self
+    }
+
+    /// Merge canonical signals so that each date yields a single day per user.
+    ///
+    /// Dates with one vendor pass through unchanged. Output is sorted by date,
+    /// then user.
+    pub fn fuse(&self, signals: &[CanonicalWearSignals]) -> Vec<CanonicalWearSignals> {
+        let mut by_day: BTreeMap<(&str, Option<&str>), Vec<&CanonicalWearSignals>> =
+            BTreeMap::new();
+        for s in signals {
+            by_day
+                .entry((s.date.as_str(), s.user_id.as_deref()))
+                .or_default()
+                .push(s);
+        }
+
+        by_day
+            .into_values()
+            .map(|days| {
+                if days.len() == 1 {
+                    days[0].clone()
+                } else {
+                    self.fuse_day(days)
+                }
+            })
+            .collect()
+    }
+
+    fn fuse_day(&self, mut days: Vec<&CanonicalWearSignals>) -> CanonicalWearSignals {
+        days.sort_by_key(|d| rank(&self.default_priority, d.vendor));
+
+        let primary = days[0];
+        let mut fused = primary.clone();
+        fused.field_sources = HashMap::new();
+        fused.field_events = HashMap::new();
+        // A fused day is only as reliable as its weakest input
+        fused.input_quality = days.iter().filter_map(|d| d.input_quality).reduce(f64::min);
+        fused.utc_offset_minutes = days.iter().find_map(|d| d.utc_offset_minutes);
+        fused.observed_at = days
+            .iter()
+            .map(|d| d.observed_at)
+            .max()
+            .unwrap_or(primary.observed_at);
+        for day in &days[1..] {
+            for (domain, at) in &day.latest_event_at {
+                let latest = fused.latest_event_at.entry(domain.clone()).or_insert(*at);
+                *latest = (*latest).max(*at);
+            }
+        }
+
+        for field in FusionField::ALL {
+            let priority = self
+                .field_priority
+                .get(&field)
+                .unwrap_or(&self.default_priority);
+
+            let mut candidates = days.clone();
+            candidates.sort_by_key(|d| {
+                (
+                    rank(priority, d.vendor),
+                    rank(&self.default_priority, d.vendor),
+                )
+            });
+
+            if let Some(source) = candidates.into_iter().find(|d| field.is_present(d)) {
+                field.copy(source, &mut fused);
+                fused
+                    .field_sources
+                    .insert(field.as_str().to_string(), source.vendor);
+                if let Some(events) = source.field_events.get(field.as_str()) {
+                    fused
+                        .field_events
+                        .insert(field.as_str().to_string(), events.clone());
+                }
+            }
+        }
+
+        let mut tags: Vec<String> = days.iter().flat_map(|d| d.tags.clone()).collect();
+        tags.sort();
+        tags.dedup();
+        fused.tags = tags;
+        fused.warnings = days.iter().flat_map(|d| d.warnings.clone()).collect();
+        for day in &days[1..] {
+            for (key, unmapped) in &day.unmapped_signals {
+                fused
+                    .unmapped_signals
+                    .entry(key.clone())
+                    .or_insert_with(|| unmapped.clone());
+            }
+        }
+
+        // Keep raw data from every device, prefixed by vendor for secondary ones
+        for day in &days[1..] {
+            for (key, value) in &day.vendor_raw {
+                fused
+                    .vendor_raw
+                    .entry(format!("{}_{}", day.vendor.as_str(), key))
+                    .or_insert_with(|| value.clone());
+            }
+        }
+
+        fused
+    }
+}
+
+fn rank(priority: &[Vendor], vendor: Vendor) -> usize {
+    priority
+        .iter()
+        .position(|v| *v == vendor)
+        .unwrap_or(priority.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CanonicalActivity, CanonicalRecovery, CanonicalSleep};
+    use chrono::Utc;
+
+    fn make_day(vendor: Vendor, date: &str) -> CanonicalWearSignals {
+        CanonicalWearSignals {
+            vendor,
+            date: date.to_string(),
+            device_id: "device".to_string(),
+            user_id: None,
+            timezone: "UTC".to_string(),
+            utc_offset_minutes: None,
+            observed_at: Utc::now(),
+            sleep: CanonicalSleep::default(),
+            recovery: CanonicalRecovery::default(),
+            activity: CanonicalActivity::default(),
+            body: Default::default(),
+            input_quality: None,
+            vendor_raw: HashMap::new(),
+            field_sources: HashMap::new(),
+            field_events: HashMap::new(),
+            intraday: BTreeMap::new(),
+            latest_event_at: BTreeMap::new(),
+            tags: Vec::new(),
+            warnings: Vec::new(),
+            unmapped_signals: BTreeMap::new(),
+        }
+    }
+
+    fn whoop_and_garmin() -> Vec<CanonicalWearSignals> {
+        let mut whoop = make_day(Vendor::Whoop, "2024-01-15");
+        whoop.recovery.hrv_rmssd_ms = Some(65.0);
+        whoop.recovery.resting_hr_bpm = Some(52.0);
+        whoop.activity.vendor_strain_score = Some(12.0);
+        whoop.sleep.total_sleep_minutes = Some(420.0);
+
+        let mut garmin = make_day(Vendor::Garmin, "2024-01-15");
+        garmin.recovery.hrv_rmssd_ms = Some(48.0);
+        garmin.activity.steps = Some(9000);
+        garmin.activity.calories = Some(2300.0);
+        garmin.sleep.total_sleep_minutes = Some(400.0);
+
+        vec![garmin, whoop]
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let fused = DeviceFusion::new().fuse(&whoop_and_garmin());
+        assert_eq!(fused.len(), 1);
+
+        let day = &fused[0];
+        assert_eq!(day.vendor, Vendor::Whoop);
+        assert_eq!(day.recovery.hrv_rmssd_ms, Some(65.0));
+        assert_eq!(day.sleep.total_sleep_minutes, Some(420.0));
+        assert_eq!(day.activity.steps, Some(9000));
+        assert_eq!(day.activity.calories, Some(2300.0));
+        assert_eq!(day.source_of("hrv_rmssd_ms"), Vendor::Whoop);
+        assert_eq!(day.source_of("steps"), Vendor::Garmin);
+        assert!(!day.field_sources.contains_key("spo2_percentage"));
+    }
+
+    #[test]
+    fn test_custom_priority() {
+        let fusion =
+            DeviceFusion::new().with_field_priority(FusionField::Hrv, vec![Vendor::Garmin]);
+        let fused = fusion.fuse(&whoop_and_garmin());
+        assert_eq!(fused[0].recovery.hrv_rmssd_ms, Some(48.0));
+        assert_eq!(fused[0].source_of("hrv_rmssd_ms"), Vendor::Garmin);
+    }
+
+    #[test]
+    fn test_falls_back_to_other_vendor() {
+        let mut days = whoop_and_garmin();
+        days[1].recovery.hrv_rmssd_ms = None;
+        let fused = DeviceFusion::new().fuse(&days);
+        assert_eq!(fused[0].recovery.hrv_rmssd_ms, Some(48.0));
+        assert_eq!(fused[0].source_of("hrv_rmssd_ms"), Vendor::Garmin);
+    }
+
+    #[test]
+    fn test_single_vendor_days_pass_through() {
+        let days = vec![
+            make_day(Vendor::Garmin, "2024-01-16"),
+            make_day(Vendor::Whoop, "2024-01-15"),
+        ];
+        let fused = DeviceFusion::new().fuse(&days);
+        assert_eq!(fused.len(), 2);
+        assert_eq!(fused[0].date, "2024-01-15");
+        assert!(fused[0].field_sources.is_empty());
+    }
+
+    #[test]
+    fn test_users_fused_separately() {
+        let mut days = whoop_and_garmin();
+        days.extend(whoop_and_garmin());
+        for (i, day) in days.iter_mut().enumerate() {
+            day.user_id = Some(if i < 2 { "b" } else { "a" }.to_string());
+        }
+        let fused = DeviceFusion::new().fuse(&days);
+        let users: Vec<_> = fused.iter().map(|d| d.user_id.as_deref()).collect();
+        assert_eq!(users, vec![Some("a"), Some("b")]);
+        assert!(fused.iter().all(|d| !d.field_sources.is_empty()));
+    }
+}
@@ -0,0 +1,253 @@
+//! Payload integrity hashes and signatures
+//!
+//! A sealed payload carries `meta.integrity = { hash, signature, key_id }`.
+//! The hash is SHA-256 over the canonical form of the payload: compact JSON
+//! with sorted keys and the `integrity` entry itself removed (an emptied
+//! `meta` object is dropped too). When a signing key is configured the same
+//! canonical bytes are signed with ed25519, so downstream services can check
+//! that Flux output was not modified in transit.
+//!
+//! The `integrity` feature turns on serde_json's `float_roundtrip` so a
+//! payload parsed back from JSON hashes to the same canonical bytes.
+
+use std::fmt;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::ComputeError;
+
+/// Meta key holding the integrity block
+pub const INTEGRITY_META_KEY: &str = "integrity";
+
+/// Prefix identifying the hash algorithm
+const HASH_PREFIX: &str = "sha256:";
+
+/// Integrity block embedded in payload meta
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Integrity {
+    /// `sha256:<hex>` digest of the canonical payload
+    pub hash: String,
+    /// Hex-encoded ed25519 signature of the canonical payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Caller-assigned identifier of the signing key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+/// How payloads are sealed: hash only, or hash plus signature
+#[derive(Clone, Default)]
+pub struct IntegrityConfig {
+    signer: Option<(String, SigningKey)>,
+}
+
+impl IntegrityConfig {
+    /// Embed a content hash without signing
+    pub fn hash_only() -> Self {
+        Self::default()
+    }
+
+    /// Embed a content hash and sign it with a caller-provided ed25519 secret key
+    pub fn signed(key_id: impl Into<String>, secret_key: &[u8; 32]) -> Self {
+        Self {
+            signer: Some((key_id.into(), SigningKey::from_bytes(secret_key))),
+        }
+    }
+
+    /// Public half of the signing key, if one is configured
+    pub fn verifying_key(&self) -> Option<[u8; 32]> {
+        self.signer
+            .as_ref()
+            .map(|(_, key)| key.verifying_key().to_bytes())
+    }
+
+    /// Compute the integrity block for a payload
+    pub fn compute(&self, payload: &Value) -> Result<Integrity, ComputeError> {
+        let canonical = canonical_bytes(payload)?;
+        let (signature, key_id) = match &self.signer {
+            Some((key_id, key)) => (
+                Some(to_hex(&key.sign(&canonical).to_bytes())),
+                Some(key_id.clone()),
+            ),
+            None => (None, None),
+        };
+        Ok(Integrity {
+            hash: format!("{}{}", HASH_PREFIX, to_hex(&Sha256::digest(&canonical))),
+            signature,
+            key_id,
+        })
+    }
+
+    /// Embed `meta.integrity` into a serialized payload, replacing any previous block
+    pub fn seal(&self, payload: &mut Value) -> Result<(), ComputeError> {
+        let integrity = serde_json::to_value(self.compute(payload)?)?;
+        let object = payload
+            .as_object_mut()
+            .ok_or_else(|| ComputeError::EncodingError("payload is not an object".to_string()))?;
+        let meta = object
+            .entry("meta")
+            .or_insert_with(|| Value::Object(Default::default()));
+        match meta.as_object_mut() {
+            Some(meta) => {
+                meta.insert(INTEGRITY_META_KEY.to_string(), integrity);
+                Ok(())
+            }
+            None => Err(ComputeError::EncodingError(
+                "payload meta is not an object".to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for IntegrityConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret key
+        f.debug_struct("IntegrityConfig")
+            .field("key_id", &self.signer.as_ref().map(|(id, _)| id))
+            .finish()
+    }
+}
+
+/// Verify a sealed payload.
+///
+/// The hash is always checked. When `public_key` is given the payload must also
+/// carry a valid signature from that key.
+pub fn verify(payload: &Value, public_key: Option<&[u8; 32]>) -> Result<Integrity, ComputeError> {
+    let integrity: Integrity = payload
+        .get("meta")
+        .and_then(|meta| meta.get(INTEGRITY_META_KEY))
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()?
+        .ok_or_else(|| ComputeError::IntegrityError("payload is not sealed".to_string()))?;
+
+    let canonical = canonical_bytes(payload)?;
+    let expected = format!("{}{}", HASH_PREFIX, to_hex(&Sha256::digest(&canonical)));
+    if integrity.hash != expected {
+        return Err(ComputeError::IntegrityError(
+            "content hash mismatch".to_string(),
+        ));
+    }
+
+    if let Some(public_key) = public_key {
+        let key = VerifyingKey::from_bytes(public_key)
+            .map_err(|e| ComputeError::IntegrityError(format!("invalid public key: {}", e)))?;
+        let signature = integrity
+            .signature
+            .as_deref()
+            .ok_or_else(|| ComputeError::IntegrityError("payload is not signed".to_string()))?;
+        let bytes: [u8; 64] = from_hex(signature)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| ComputeError::IntegrityError("malformed signature".to_string()))?;
+        key.verify(&canonical, &Signature::from_bytes(&bytes))
+            .map_err(|_| ComputeError::IntegrityError("signature mismatch".to_string()))?;
+    }
+
+    Ok(integrity)
+}
+
+/// Verify a sealed payload given as JSON
+pub fn verify_json(json: &str, public_key: Option<&[u8; 32]>) -> Result<Integrity, ComputeError> {
+    verify(&serde_json::from_str(json)?, public_key)
+}
+
+/// Compact, key-sorted JSON of the payload without its integrity block
+fn canonical_bytes(payload: &Value) -> Result<Vec<u8>, ComputeError> {
+    let mut canonical = payload.clone();
+    if let Some(object) = canonical.as_object_mut() {
+        let emptied = match object.get_mut("meta").and_then(Value::as_object_mut) {
+            Some(meta) => {
+                meta.remove(INTEGRITY_META_KEY);
+                meta.is_empty()
+            }
+            None => false,
+        };
+        if emptied {
+            object.remove("meta");
+        }
+    }
+    // serde_json::Value objects are ordered maps, so keys serialize sorted
+    Ok(serde_json::to_vec(&canonical)?)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SECRET: [u8; 32] = [7; 32];
+
+    fn sample_payload() -> Value {
+        json!({
+            "hsi_version": "1.0",
+            "axes": { "behavior": { "readings": [{ "axis": "focus", "score": 0.7 }] } },
+            "meta": { "session_id": "sess-1" }
+        })
+    }
+
+    #[test]
+    fn test_hash_only_round_trip() {
+        let mut payload = sample_payload();
+        IntegrityConfig::hash_only().seal(&mut payload).unwrap();
+
+        let integrity = verify(&payload, None).unwrap();
+        assert!(integrity.hash.starts_with("sha256:"));
+        assert_eq!(integrity.hash.len(), HASH_PREFIX.len() + 64);
+        assert!(integrity.signature.is_none());
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let mut payload = sample_payload();
+        IntegrityConfig::hash_only().seal(&mut payload).unwrap();
+        payload["axes"]["behavior"]["readings"][0]["score"] = json!(0.9);
+
+        assert!(matches!(
+            verify(&payload, None),
+            Err(ComputeError::IntegrityError(_))
+        ));
+    }
+
+    #[test]
+    fn test_signed_round_trip() {
+        let config = IntegrityConfig::signed("device-key-1", &SECRET);
+        let public_key = config.verifying_key().unwrap();
+        let mut payload = json!({ "hsi_version": "1.0.0", "windows": [] });
+        config.seal(&mut payload).unwrap();
+
+        let integrity = verify(&payload, Some(&public_key)).unwrap();
+        assert_eq!(integrity.key_id.as_deref(), Some("device-key-1"));
+
+        let other = IntegrityConfig::signed("other", &[9; 32])
+            .verifying_key()
+            .unwrap();
+        assert!(verify(&payload, Some(&other)).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_payload_fails_key_check() {
+        let mut payload = sample_payload();
+        IntegrityConfig::hash_only().seal(&mut payload).unwrap();
+        let public_key = IntegrityConfig::signed("k", &SECRET)
+            .verifying_key()
+            .unwrap();
+        assert!(verify(&payload, Some(&public_key)).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Binary encodings of HSI payloads
+//!
+//! CBOR and MessagePack are far more compact than JSON for storing large
+//! numbers of snapshots on device. Payloads are routed through
+//! `serde_json::Value` before encoding so every map is written with sorted
+//! keys: the same payload always produces the same bytes, which keeps content
+//! hashes stable.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::ComputeError;
+
+/// Convert a payload into a value with canonically ordered (sorted) keys
+fn canonical<T: Serialize>(payload: &T) -> Result<serde_json::Value, ComputeError> {
+    Ok(serde_json::to_value(payload)?)
+}
+
+/// Encode a payload as CBOR with canonical field ordering
+pub fn to_cbor<T: Serialize>(payload: &T) -> Result<Vec<u8>, ComputeError> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&canonical(payload)?, &mut bytes)
+        .map_err(|e| ComputeError::EncodingError(format!("CBOR: {}", e)))?;
+    Ok(bytes)
+}
+
+/// Decode a payload from CBOR
+pub fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ComputeError> {
+    ciborium::from_reader(bytes).map_err(|e| ComputeError::ParseError(format!("CBOR: {}", e)))
+}
+
+/// Encode a payload as MessagePack (named fields) with canonical field ordering
+pub fn to_msgpack<T: Serialize>(payload: &T) -> Result<Vec<u8>, ComputeError> {
+    rmp_serde::to_vec_named(&canonical(payload)?)
+        .map_err(|e| ComputeError::EncodingError(format!("MessagePack: {}", e)))
+}
+
+/// Decode a payload from MessagePack
+pub fn from_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ComputeError> {
+    rmp_serde::from_slice(bytes)
+        .map_err(|e| ComputeError::ParseError(format!("MessagePack: {}", e)))
+}
+
+macro_rules! impl_binary_encoding {
+    ($payload:ty) => {
+        impl $payload {
+            /// Encode as CBOR with canonical field ordering
+            pub fn to_cbor(&self) -> Result<Vec<u8>, ComputeError> {
+                to_cbor(self)
+            }
+
+            /// Decode from CBOR
+            pub fn from_cbor(bytes: &[u8]) -> Result<Self, ComputeError> {
+                from_cbor(bytes)
+            }
+
+            /// Encode as MessagePack with canonical field ordering
+            pub fn to_msgpack(&self) -> Result<Vec<u8>, ComputeError> {
+                to_msgpack(self)
+            }
+
+            /// Decode from MessagePack
+            pub fn from_msgpack(bytes: &[u8]) -> Result<Self, ComputeError> {
+                from_msgpack(bytes)
+            }
+        }
+    };
+}
+
+impl_binary_encoding!(crate::types::HsiPayload);
+impl_binary_encoding!(crate::behavior::types::HsiPayload);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior::behavior_to_hsi;
+    use crate::pipeline::whoop_to_hsi_daily;
+
+    const WHOOP_JSON: &str = r#"{
+        "sleep": [{
+            "id": 1,
+            "start": "2024-01-15T22:00:00.000Z",
+            "end": "2024-01-16T06:00:00.000Z",
+            "score": {
+                "stage_summary": {
+                    "total_in_bed_time_milli": 28800000,
+                    "total_awake_time_milli": 1800000,
+                    "total_light_sleep_time_milli": 12600000,
+                    "total_slow_wave_sleep_time_milli": 7200000,
+                    "total_rem_sleep_time_milli": 7200000,
+                    "total_sleep_time_milli": 27000000,
+                    "disturbance_count": 3
+                },
+                "sleep_efficiency_percentage": 93.75
+            }
+        }],
+        "recovery": [{
+            "cycle_id": 1,
+            "created_at": "2024-01-16T06:30:00.000Z",
+            "score": {
+                "recovery_score": 75.0,
+                "resting_heart_rate": 52.0,
+                "hrv_rmssd_milli": 65.0
+            }
+        }],
+        "cycle": []
+    }"#;
+
+    #[test]
+    fn test_wearable_cbor_round_trip() {
+        let json = whoop_to_hsi_daily(WHOOP_JSON.to_string(), "UTC".into(), "dev".into())
+            .unwrap()
+            .remove(0);
+        let payload: crate::types::HsiPayload = serde_json::from_str(&json).unwrap();
+
+        let bytes = payload.to_cbor().unwrap();
+        assert!(bytes.len() < json.len());
+        let decoded = crate::types::HsiPayload::from_cbor(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&payload).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_behavior_msgpack_round_trip_is_deterministic() {
+        let session = r#"{
+            "session_id": "sess-1",
+            "device_id": "device-1",
+            "timezone": "UTC",
+            "start_time": "2024-01-15T14:00:00Z",
+            "end_time": "2024-01-15T14:30:00Z",
+            "events": [
+                {"timestamp": "2024-01-15T14:01:00Z", "event_type": "scroll", "scroll": {"velocity": 150.0, "direction": "down"}},
+                {"timestamp": "2024-01-15T14:02:00Z", "event_type": "app_switch", "app_switch": {"from_app_id": "a", "to_app_id": "b"}}
+            ]
+        }"#;
+        let json = behavior_to_hsi(session.to_string()).unwrap();
+        let payload: crate::behavior::types::HsiPayload = serde_json::from_str(&json).unwrap();
+
+        // A fresh parse rebuilds every HashMap with a new iteration order
+        let reparsed: crate::behavior::types::HsiPayload = serde_json::from_str(&json).unwrap();
+        let bytes = payload.to_msgpack().unwrap();
+        assert_eq!(bytes, reparsed.to_msgpack().unwrap());
+        let decoded = crate::behavior::types::HsiPayload::from_msgpack(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&payload).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_cbor_is_parse_error() {
+        let result = from_cbor::<crate::types::HsiPayload>(&[0xff, 0x00]);
+        assert!(matches!(result, Err(ComputeError::ParseError(_))));
+    }
+}
@@ -0,0 +1,317 @@
+//! FHIR R4 export
+//!
+//! Converts HSI daily windows into FHIR R4 `Observation` resources for
+//! clinical integrations. Resting heart rate and sleep duration are LOINC
+//! coded. HRV is exported as RMSSD under a Flux code: LOINC's HRV code is
+//! defined as SDNN, a different measure.
+
+use crate::types::{HsiDailyWindow, HsiPayload, HsiProvenance};
+use serde::{Deserialize, Serialize};
+
+/// LOINC code system URI
+pub const LOINC_SYSTEM: &str = "http://loinc.org";
+
+/// UCUM unit system URI
+pub const UCUM_SYSTEM: &str = "http://unitsofmeasure.org";
+
+/// FHIR observation category code system URI
+pub const OBSERVATION_CATEGORY_SYSTEM: &str =
+    "http://terminology.hl7.org/CodeSystem/observation-category";
+
+/// Code system for signals without a matching LOINC code
+pub const FLUX_CODE_SYSTEM: &str = "https://synheart.ai/fhir/CodeSystem/flux";
+
+/// Flux code for heart rate variability as RMSSD.
+///
+/// LOINC 80404-7 is the SDNN measure, so RMSSD values are not coded with it.
+pub const FLUX_HRV_RMSSD: &str = "hrv-rmssd";
+
+/// LOINC code for resting heart rate
+pub const LOINC_RESTING_HR: &str = "40443-4";
+
+/// LOINC code for sleep duration
+pub const LOINC_SLEEP_DURATION: &str = "93832-4";
+
+/// FHIR Coding element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FhirCoding {
+    pub system: String,
+    pub code: String,
+    pub display: String,
+}
+
+/// FHIR CodeableConcept element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FhirCodeableConcept {
+    pub coding: Vec<FhirCoding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// FHIR Quantity element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FhirQuantity {
+    pub value: f64,
+    pub unit: String,
+    pub system: String,
+    pub code: String,
+}
+
+/// FHIR Reference element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FhirReference {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+/// FHIR R4 Observation resource (subset used by Flux)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FhirObservation {
+    pub resource_type: String,
+    pub status: String,
+    pub category: Vec<FhirCodeableConcept>,
+    pub code: FhirCodeableConcept,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<FhirReference>,
+    pub effective_date_time: String,
+    pub issued: String,
+    pub value_quantity: FhirQuantity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<FhirReference>,
+}
+
+/// Convert every window of an HSI payload into FHIR observations.
+///
+/// `subject` is an optional FHIR reference (e.g. `"Patient/123"`).
+pub fn payload_to_observations(
+    payload: &HsiPayload,
+    subject: Option<&str>,
+) -> Vec<FhirObservation> {
+    payload
+        .windows
+        .iter()
+        .flat_map(|w| window_to_observations(w, &payload.provenance, subject))
+        .collect()
+}
+
+/// Convert a single HSI daily window into FHIR observations.
+///
+/// Signals that are missing in the window are skipped.
+pub fn window_to_observations(
+    window: &HsiDailyWindow,
+    provenance: &HsiProvenance,
+    subject: Option<&str>,
+) -> Vec<FhirObservation> {
+    let mut observations = Vec::new();
+
+    if let Some(hrv) = window.physiology.hrv_rmssd_ms {
+        observations.push(build_observation(
+            window,
+            provenance,
+            subject,
+            coding(
+                FLUX_CODE_SYSTEM,
+                FLUX_HRV_RMSSD,
+                "Heart rate variability RMSSD",
+            ),
+            "Heart rate variability (RMSSD)",
+            "vital-signs",
+            quantity(hrv, "ms", "ms"),
+        ));
+    }
+
+    if let Some(rhr) = window.physiology.resting_hr_bpm {
+        observations.push(build_observation(
+            window,
+            provenance,
+            subject,
+            coding(LOINC_SYSTEM, LOINC_RESTING_HR, "Heart rate --resting"),
+            "Resting heart rate",
+            "vital-signs",
+            quantity(rhr, "beats/minute", "/min"),
+        ));
+    }
+
+    if let Some(duration) = window.sleep.duration_minutes {
+        observations.push(build_observation(
+            window,
+            provenance,
+            subject,
+            coding(LOINC_SYSTEM, LOINC_SLEEP_DURATION, "Sleep duration"),
+            "Sleep duration",
+            "activity",
+            quantity(duration, "min", "min"),
+        ));
+    }
+
+    observations
+}
+
+fn coding(system: &str, code: &str, display: &str) -> FhirCoding {
+    FhirCoding {
+        system: system.to_string(),
+        code: code.to_string(),
+        display: display.to_string(),
+    }
+}
+
+fn quantity(value: f64, unit: &str, code: &str) -> FhirQuantity {
+    FhirQuantity {
+        value,
+        unit: unit.to_string(),
+        system: UCUM_SYSTEM.to_string(),
+        code: code.to_string(),
+    }
+}
+
+fn build_observation(
+    window: &HsiDailyWindow,
+    provenance: &HsiProvenance,
+    subject: Option<&str>,
+    code: FhirCoding,
+    text: &str,
+    category: &str,
+    value: FhirQuantity,
+) -> FhirObservation {
+    FhirObservation {
+        resource_type: "Observation".to_string(),
+        status: "final".to_string(),
+        category: vec![FhirCodeableConcept {
+            coding: vec![FhirCoding {
+                system: OBSERVATION_CATEGORY_SYSTEM.to_string(),
+                code: category.to_string(),
+                display: category_display(category).to_string(),
+            }],
+            text: None,
+        }],
+        code: FhirCodeableConcept {
+            coding: vec![code],
+            text: Some(text.to_string()),
+        },
+        subject: subject.map(|s| FhirReference {
+            reference: Some(s.to_string()),
+            display: None,
+        }),
+        effective_date_time: window.date.clone(),
+        issued: provenance.computed_at_utc.clone(),
+        value_quantity: value,
+        device: Some(FhirReference {
+            reference: None,
+            display: Some(format!(
+                "{} {}",
+                provenance.source_vendor, provenance.source_device_id
+            )),
+        }),
+    }
+}
+
+fn category_display(category: &str) -> &'static str {
+    match category {
+        "vital-signs" => "Vital Signs",
+        "activity" => "Activity",
+        _ => "Other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::whoop_to_hsi_daily;
+
+    fn sample_payload() -> HsiPayload {
+        let json = r#"{
+            "sleep": [{
+                "id": 1,
+                "start": "2024-01-15T22:30:00.000Z",
+                "end": "2024-01-16T06:30:00.000Z",
+                "score": {
+                    "stage_summary": {
+                        "total_in_bed_time_milli": 28800000,
+                        "total_awake_time_milli": 1800000,
+                        "total_light_sleep_time_milli": 12600000,
+                        "total_slow_wave_sleep_time_milli": 7200000,
+                        "total_rem_sleep_time_milli": 7200000,
+                        "total_sleep_time_milli": 27000000,
+                        "disturbance_count": 3
+                    }
+                }
+            }],
+            "recovery": [{
+                "cycle_id": 1,
+                "created_at": "2024-01-15T07:00:00.000Z",
+                "score": {
+                    "recovery_score": 75.0,
+                    "resting_heart_rate": 52.0,
+                    "hrv_rmssd_milli": 65.0
+                }
+            }],
+            "cycle": []
+        }"#;
+
+        let out = whoop_to_hsi_daily(json.to_string(), "UTC".to_string(), "device-1".to_string())
+            .unwrap();
+        serde_json::from_str(&out[0]).unwrap()
+    }
+
+    #[test]
+    fn test_payload_to_observations() {
+        let payload = sample_payload();
+        let observations = payload_to_observations(&payload, Some("Patient/abc"));
+
+        assert_eq!(observations.len(), 3);
+
+        let hrv = observations
+            .iter()
+            .find(|o| o.code.coding[0].code == FLUX_HRV_RMSSD)
+            .unwrap();
+        assert_eq!(hrv.code.coding.len(), 1);
+        assert_eq!(hrv.code.coding[0].system, FLUX_CODE_SYSTEM);
+        assert_eq!(hrv.value_quantity.value, 65.0);
+        assert_eq!(hrv.value_quantity.code, "ms");
+        assert_eq!(hrv.effective_date_time, "2024-01-15");
+        assert_eq!(
+            hrv.subject.as_ref().unwrap().reference.as_deref(),
+            Some("Patient/abc")
+        );
+
+        let rhr = observations
+            .iter()
+            .find(|o| o.code.coding[0].code == LOINC_RESTING_HR)
+            .unwrap();
+        assert_eq!(rhr.value_quantity.value, 52.0);
+
+        let sleep = observations
+            .iter()
+            .find(|o| o.code.coding[0].code == LOINC_SLEEP_DURATION)
+            .unwrap();
+        assert_eq!(sleep.category[0].coding[0].code, "activity");
+    }
+
+    #[test]
+    fn test_missing_signals_are_skipped() {
+        let mut payload = sample_payload();
+        payload.windows[0].physiology.hrv_rmssd_ms = None;
+        payload.windows[0].sleep.duration_minutes = None;
+
+        let observations = payload_to_observations(&payload, None);
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].code.coding[0].code, LOINC_RESTING_HR);
+        assert!(observations[0].subject.is_none());
+    }
+
+    #[test]
+    fn test_observation_serializes_as_fhir() {
+        let payload = sample_payload();
+        let observations = payload_to_observations(&payload, None);
+        let value = serde_json::to_value(&observations[0]).unwrap();
+
+        assert_eq!(value["resourceType"], "Observation");
+        assert_eq!(value["status"], "final");
+        assert!(value["effectiveDateTime"].is_string());
+        assert!(value["valueQuantity"]["value"].is_number());
+        assert!(value.get("subject").is_none());
+    }
+}
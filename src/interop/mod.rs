@@ -0,0 +1,9 @@
+//! Interoperability converters
+//!
+//! This module converts HSI output into formats used by external systems.
+//! Converters are pure functions over already-encoded payloads and never
+//! feed back into the pipeline.
+
+#[cfg(feature = "binary")]
+pub mod binary;
+pub mod fhir;
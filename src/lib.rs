@@ -8,29 +8,61 @@
 //!
 //! - **Wearable Pipeline**: Process wearable device data (WHOOP, Garmin) into HSI signals
 //! - **Behavior Module**: Process smartphone behavioral data into HSI signals
+//! - **Interop**: Export HSI output to external formats (FHIR R4)
+//! - **Testkit**: Generate synthetic vendor payloads and behavior sessions
+
+#[macro_use]
+mod trace;
 
 pub mod adapters;
 pub mod baseline;
 pub mod behavior;
+pub mod calibration;
+pub mod conformance;
+pub mod diff;
 pub mod encoder;
 pub mod error;
+pub mod explain;
 pub mod features;
+pub mod fusion;
+#[cfg(feature = "integrity")]
+pub mod integrity;
+pub mod interop;
 pub mod normalizer;
+pub mod observer;
+pub mod payload;
 pub mod pipeline;
+pub mod privacy;
+pub mod report;
+pub mod rollup;
 pub mod schema;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod testkit;
 pub mod types;
+pub mod validation;
+pub mod warnings;
+pub mod watermark;
 
-// FFI bindings for C interop (always available for cdylib/staticlib builds)
+// FFI bindings for C interop (native and WASI builds)
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 pub mod ffi;
 
+// wasm-bindgen bindings for browser hosts
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
 pub use error::ComputeError;
-pub use pipeline::{garmin_to_hsi_daily, whoop_to_hsi_daily, FluxProcessor};
+pub use pipeline::{
+    garmin_to_hsi_daily, garmin_to_hsi_payloads, whoop_to_hsi_daily, whoop_to_hsi_payloads,
+    FluxProcessor,
+};
 
 // Schema exports
 pub use schema::{RawEvent, RawEventAdapter, SCHEMA_VERSION};
 
 // Behavioral exports
-pub use behavior::{behavior_to_hsi, BehaviorProcessor};
+pub use behavior::{behavior_to_hsi, behavior_to_hsi_payload, BehaviorProcessor};
 
 /// Flux version embedded in all HSI payloads
 pub const FLUX_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,11 +1,72 @@
 //! Signal normalization
 //!
 //! This module normalizes canonical wear signals into consistent units and scales.
-//! - Vendor scores normalized to 0-1
+//! - Vendor scores normalized to 0-1 (linear, log or quantile per score)
 //! - Coverage and quality flags computed
 //! - Missing data detection
 
-use crate::types::{CanonicalWearSignals, NormalizedSignals, QualityFlag, Vendor};
+use crate::types::{
+    CanonicalActivity, CanonicalWearSignals, NormalizedSignals, QualityFlag, ScoreNormalization,
+    Vendor,
+};
+use chrono::{NaiveDate, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Plausible HRV RMSSD range in ms (a 0 ms reading is a sensor dropout)
+pub const HRV_RMSSD_RANGE_MS: (f64, f64) = (0.0, 300.0);
+
+/// Plausible resting heart rate range in bpm
+pub const RESTING_HR_RANGE_BPM: (f64, f64) = (25.0, 120.0);
+
+/// Plausible SpO2 range in percent
+pub const SPO2_RANGE_PERCENT: (f64, f64) = (70.0, 100.0);
+
+/// Longest plausible sleep in minutes (16 h)
+pub const MAX_SLEEP_MINUTES: f64 = 960.0;
+
+/// Share of total calories by which active plus resting calories may differ from it
+pub const CALORIE_TOLERANCE: f64 = 0.05;
+
+/// Past scores needed before quantile normalization replaces linear scaling
+pub const MIN_QUANTILE_HISTORY: usize = 7;
+
+/// Normalization strategy for each vendor score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormalizationConfig {
+    #[serde(default)]
+    pub sleep: ScoreNormalization,
+    #[serde(default)]
+    pub recovery: ScoreNormalization,
+    #[serde(default)]
+    pub strain: ScoreNormalization,
+}
+
+impl NormalizationConfig {
+    pub fn with_sleep(mut self, strategy: ScoreNormalization) -> Self {
+        self.sleep = strategy;
+        self
+    }
+
+    pub fn with_recovery(mut self, strategy: ScoreNormalization) -> Self {
+        self.recovery = strategy;
+        self
+    }
+
+    pub fn with_strain(mut self, strategy: ScoreNormalization) -> Self {
+        self.strain = strategy;
+        self
+    }
+}
+
+/// The user's recent linearly scaled scores, for quantile normalization
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoreHistory {
+    pub sleep: Vec<f64>,
+    pub recovery: Vec<f64>,
+    pub strain: Vec<f64>,
+}
 
 /// Normalizer for converting canonical signals to normalized signals
 pub struct Normalizer;
@@ -13,52 +74,89 @@ pub struct Normalizer;
 impl Normalizer {
     /// Normalize canonical signals
     pub fn normalize(signals: &CanonicalWearSignals) -> NormalizedSignals {
+        Self::normalize_with_config(
+            signals,
+            &NormalizationConfig::default(),
+            &ScoreHistory::default(),
+        )
+    }
+
+    /// Normalize canonical signals with per-score strategies.
+    ///
+    /// Quantile normalization ranks each score against `history` and falls
+    /// back to linear scaling until [`MIN_QUANTILE_HISTORY`] scores are known.
+    pub fn normalize_with_config(
+        signals: &CanonicalWearSignals,
+        config: &NormalizationConfig,
+        history: &ScoreHistory,
+    ) -> NormalizedSignals {
         let mut quality_flags = Vec::new();
-        let mut coverage_count = 0;
-        let total_fields = 6; // Key fields we track for coverage
 
-        // Check sleep data
-        let sleep_score = normalize_sleep_score(signals);
-        if signals.sleep.total_sleep_minutes.is_some() {
-            coverage_count += 1;
-        } else {
-            quality_flags.push(QualityFlag::MissingSleepData);
+        let mut guarded = signals.clone();
+        if apply_range_guards(&mut guarded) {
+            quality_flags.push(QualityFlag::ImplausibleValue);
         }
+        let signals = &guarded;
 
-        // Check recovery data
-        let recovery_score = normalize_recovery_score(signals);
-        if signals.recovery.hrv_rmssd_ms.is_some() {
-            coverage_count += 1;
-        } else {
-            quality_flags.push(QualityFlag::MissingHrv);
-        }
+        let [sleep, recovery, strain] = linear_scores(signals);
+        let mut score_normalization = BTreeMap::new();
+        let mut scale = |name: &str, linear: Option<f64>, strategy, history: &[f64], field| {
+            let (score, used) = apply_strategy(
+                linear?,
+                strategy,
+                vendor_score_scale(field, signals.source_of(field)),
+                history,
+            );
+            if *config != NormalizationConfig::default() {
+                score_normalization.insert(name.to_string(), used);
+            }
+            Some(score)
+        };
+        let sleep_score = scale("sleep_score", sleep, config.sleep, &history.sleep, "sleep");
+        let recovery_score = scale(
+            "recovery_score",
+            recovery,
+            config.recovery,
+            &history.recovery,
+            "recovery_score",
+        );
+        let strain_score = scale(
+            "strain_score",
+            strain,
+            config.strain,
+            &history.strain,
+            "strain_score",
+        );
 
-        if signals.recovery.resting_hr_bpm.is_some() {
-            coverage_count += 1;
-        } else {
+        if signals.sleep.total_sleep_minutes.is_none() {
+            quality_flags.push(QualityFlag::MissingSleep);
+        }
+        if signals.recovery.hrv_rmssd_ms.is_none() {
+            quality_flags.push(QualityFlag::NoHrv);
+        }
+        if signals.recovery.resting_hr_bpm.is_none() {
             quality_flags.push(QualityFlag::MissingRestingHr);
         }
-
-        if recovery_score.is_some() {
-            coverage_count += 1;
-        } else if signals.recovery.vendor_recovery_score.is_none() {
+        if recovery_score.is_none() {
             quality_flags.push(QualityFlag::MissingRecoveryData);
         }
-
-        // Check activity data
-        let strain_score = normalize_strain_score(signals);
-        if strain_score.is_some() {
-            coverage_count += 1;
-        } else {
+        if strain_score.is_none() {
             quality_flags.push(QualityFlag::MissingActivityData);
         }
-
-        // Check for additional activity coverage
-        if signals.activity.calories.is_some() || signals.activity.steps.is_some() {
-            coverage_count += 1;
+        if is_partial_day(signals) {
+            quality_flags.push(QualityFlag::PartialDay);
+        }
+        if calorie_discrepancy(&signals.activity)
+            .zip(signals.activity.calories)
+            .is_some_and(|(diff, total)| diff.abs() > total * CALORIE_TOLERANCE)
+        {
+            quality_flags.push(QualityFlag::InconsistentCalories);
         }
 
-        let coverage = (coverage_count as f64) / (total_fields as f64);
+        let classes = signal_classes(signals);
+        let present = classes.iter().filter(|c| c.present).count();
+        let class_coverage = present as f64 / classes.len() as f64;
+        let coverage = classes.iter().map(|c| c.weight * c.completeness()).sum();
 
         NormalizedSignals {
             canonical: signals.clone(),
@@ -66,57 +164,248 @@ impl Normalizer {
             recovery_score,
             strain_score,
             coverage,
+            class_coverage,
             quality_flags,
+            score_normalization,
         }
     }
-}
 
-/// Normalize vendor sleep score to 0-1 scale
-fn normalize_sleep_score(signals: &CanonicalWearSignals) -> Option<f64> {
-    signals.sleep.vendor_sleep_score.map(|score| {
-        match signals.vendor {
-            Vendor::Whoop => {
-                // WHOOP sleep performance is 0-100%
-                (score / 100.0).clamp(0.0, 1.0)
-            }
-            Vendor::Garmin => {
-                // Garmin sleep score is 0-100
-                (score / 100.0).clamp(0.0, 1.0)
+    /// Correct sleep durations for a day on which the user's timezone changed.
+    ///
+    /// Vendors often compute time in bed from local wall-clock times, which is off
+    /// by the UTC offset change when the night spans a timezone switch. When the
+    /// session has absolute start/end instants, time in bed is recomputed from them
+    /// and sleep durations are capped to it.
+    pub fn adjust_for_timezone_shift(signals: &CanonicalWearSignals) -> CanonicalWearSignals {
+        let mut adjusted = signals.clone();
+        let sleep = &mut adjusted.sleep;
+
+        if let (Some(start), Some(end)) = (sleep.start_time, sleep.end_time) {
+            let absolute_minutes = (end - start).num_minutes() as f64;
+            if absolute_minutes > 0.0 {
+                sleep.time_in_bed_minutes = Some(absolute_minutes);
+                if let Some(total) = sleep.total_sleep_minutes {
+                    if total > absolute_minutes {
+                        sleep.total_sleep_minutes = Some(absolute_minutes);
+                    }
+                }
+                if let Some(awake) = sleep.awake_minutes {
+                    let asleep = sleep.total_sleep_minutes.unwrap_or(0.0);
+                    sleep.awake_minutes = Some(awake.min((absolute_minutes - asleep).max(0.0)));
+                }
             }
         }
-    })
+
+        adjusted
+    }
 }
 
-/// Normalize vendor recovery score to 0-1 scale
-fn normalize_recovery_score(signals: &CanonicalWearSignals) -> Option<f64> {
-    signals.recovery.vendor_recovery_score.map(|score| {
-        match signals.vendor {
-            Vendor::Whoop => {
-                // WHOOP recovery is 0-100%
-                (score / 100.0).clamp(0.0, 1.0)
-            }
-            Vendor::Garmin => {
-                // Garmin Body Battery is 0-100
-                (score / 100.0).clamp(0.0, 1.0)
+/// Drop physiologically impossible vendor values and cap sleep length.
+///
+/// Out-of-range HRV, RHR and SpO2 readings are dropped rather than clamped so
+/// they never reach the baselines. Returns whether anything was changed.
+fn apply_range_guards(signals: &mut CanonicalWearSignals) -> bool {
+    let mut changed = false;
+    let mut drop_outside = |value: &mut Option<f64>, (min, max): (f64, f64), min_inclusive| {
+        if let Some(v) = *value {
+            let above_min = if min_inclusive { v >= min } else { v > min };
+            if !(above_min && v <= max) {
+                *value = None;
+                changed = true;
             }
         }
-    })
-}
+    };
 
-/// Normalize vendor strain/load score to 0-1 scale
-fn normalize_strain_score(signals: &CanonicalWearSignals) -> Option<f64> {
-    signals.activity.vendor_strain_score.map(|score| {
-        match signals.vendor {
-            Vendor::Whoop => {
-                // WHOOP strain is 0-21 scale
-                (score / 21.0).clamp(0.0, 1.0)
+    let recovery = &mut signals.recovery;
+    drop_outside(&mut recovery.hrv_rmssd_ms, HRV_RMSSD_RANGE_MS, false);
+    drop_outside(&mut recovery.resting_hr_bpm, RESTING_HR_RANGE_BPM, true);
+    drop_outside(&mut recovery.spo2_percentage, SPO2_RANGE_PERCENT, true);
+
+    let sleep = &mut signals.sleep;
+    for minutes in [
+        &mut sleep.total_sleep_minutes,
+        &mut sleep.time_in_bed_minutes,
+    ] {
+        match *minutes {
+            Some(m) if m < 0.0 => {
+                *minutes = None;
+                changed = true;
             }
-            Vendor::Garmin => {
-                // Garmin training load balance varies; normalize assuming typical range 0-150
-                (score / 150.0).clamp(0.0, 1.0)
+            Some(m) if m > MAX_SLEEP_MINUTES => {
+                *minutes = Some(MAX_SLEEP_MINUTES);
+                changed = true;
             }
+            _ => {}
+        }
+    }
+
+    changed
+}
+
+/// An expected signal class and how completely it was reported
+pub(crate) struct SignalClass {
+    /// Class name (`sleep`, `hrv`, `resting_hr` or `activity`)
+    pub name: &'static str,
+    /// Share of the day's coverage score (weights sum to 1)
+    pub weight: f64,
+    /// Whether the class's primary signal is present
+    present: bool,
+    /// Supporting fields present, out of `detail_total`
+    details: usize,
+    detail_total: usize,
+}
+
+impl SignalClass {
+    /// Half credit for the primary signal, half for supporting detail
+    pub fn completeness(&self) -> f64 {
+        if !self.present {
+            return 0.0;
         }
-    })
+        if self.detail_total == 0 {
+            return 1.0;
+        }
+        0.5 + 0.5 * self.details as f64 / self.detail_total as f64
+    }
+}
+
+/// Expected signal classes for a wearable day: sleep, HRV, RHR and activity
+pub(crate) fn signal_classes(signals: &CanonicalWearSignals) -> [SignalClass; 4] {
+    let sleep = &signals.sleep;
+    let activity = &signals.activity;
+    let count = |fields: &[bool]| fields.iter().filter(|f| **f).count();
+
+    let sleep_details = [
+        sleep.vendor_sleep_score.is_some(),
+        sleep.deep_sleep_minutes.is_some() || sleep.rem_sleep_minutes.is_some(),
+        sleep.time_in_bed_minutes.is_some(),
+    ];
+    let activity_details = [
+        activity.vendor_strain_score.is_some(),
+        activity.steps.is_some() || activity.calories.is_some(),
+        activity.active_minutes.is_some(),
+    ];
+
+    [
+        SignalClass {
+            name: "sleep",
+            weight: 0.35,
+            present: sleep.total_sleep_minutes.is_some(),
+            details: count(&sleep_details),
+            detail_total: sleep_details.len(),
+        },
+        SignalClass {
+            name: "hrv",
+            weight: 0.25,
+            present: signals.recovery.hrv_rmssd_ms.is_some(),
+            details: 0,
+            detail_total: 0,
+        },
+        SignalClass {
+            name: "resting_hr",
+            weight: 0.15,
+            present: signals.recovery.resting_hr_bpm.is_some(),
+            details: 0,
+            detail_total: 0,
+        },
+        SignalClass {
+            name: "activity",
+            weight: 0.25,
+            present: activity_details.iter().any(|f| *f)
+                || activity.distance_meters.is_some()
+                || activity.average_hr_bpm.is_some(),
+            details: count(&activity_details),
+            detail_total: activity_details.len(),
+        },
+    ]
+}
+
+/// A day observed before it ended (at local midnight in its timezone; UTC
+/// for unknown zones) can still accumulate activity
+fn is_partial_day(signals: &CanonicalWearSignals) -> bool {
+    let Some(end) = NaiveDate::parse_from_str(&signals.date, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.succ_opt())
+        .and_then(|next| next.and_hms_opt(0, 0, 0))
+    else {
+        return false;
+    };
+    let end = match signals.timezone.parse::<Tz>() {
+        Ok(tz) => tz
+            .from_local_datetime(&end)
+            .earliest()
+            .map_or(end, |end| end.naive_utc()),
+        Err(_) => end,
+    };
+    signals.observed_at.naive_utc() < end
+}
+
+/// Total calories minus active and resting calories, when all three are known
+pub fn calorie_discrepancy(activity: &CanonicalActivity) -> Option<f64> {
+    let parts = activity.active_calories? + activity.resting_calories?;
+    Some(activity.calories? - parts)
+}
+
+/// Full-scale value of a vendor score (`sleep`, `recovery_score` or `strain_score`)
+pub(crate) fn vendor_score_scale(field: &str, vendor: Vendor) -> f64 {
+    match (field, vendor) {
+        // WHOOP strain is 0-21 scale
+        ("strain_score", Vendor::Whoop) => 21.0,
+        // Garmin training load balance varies; normalize assuming typical range 0-150
+        ("strain_score", Vendor::Garmin) => 150.0,
+        // WHOOP sleep performance/recovery and Garmin sleep score/Body Battery are 0-100
+        _ => 100.0,
+    }
+}
+
+/// Scale a vendor score to 0-1 using the scale of the vendor that supplied it
+fn normalize_vendor_score(
+    signals: &CanonicalWearSignals,
+    field: &str,
+    score: Option<f64>,
+) -> Option<f64> {
+    let scale = vendor_score_scale(field, signals.source_of(field));
+    score.map(|score| (score / scale).clamp(0.0, 1.0))
+}
+
+/// Sleep, recovery and strain scores scaled linearly to 0-1
+pub(crate) fn linear_scores(signals: &CanonicalWearSignals) -> [Option<f64>; 3] {
+    [
+        normalize_vendor_score(signals, "sleep", signals.sleep.vendor_sleep_score),
+        normalize_vendor_score(
+            signals,
+            "recovery_score",
+            signals.recovery.vendor_recovery_score,
+        ),
+        normalize_vendor_score(
+            signals,
+            "strain_score",
+            signals.activity.vendor_strain_score,
+        ),
+    ]
+}
+
+/// Rescale a linearly scaled score, returning the strategy actually used
+fn apply_strategy(
+    linear: f64,
+    strategy: ScoreNormalization,
+    full_scale: f64,
+    history: &[f64],
+) -> (f64, ScoreNormalization) {
+    match strategy {
+        ScoreNormalization::Log => (
+            (linear * full_scale).ln_1p() / full_scale.ln_1p(),
+            ScoreNormalization::Log,
+        ),
+        ScoreNormalization::Quantile if history.len() >= MIN_QUANTILE_HISTORY => {
+            let below = history.iter().filter(|v| **v < linear).count() as f64;
+            let equal = history.iter().filter(|v| **v == linear).count() as f64;
+            (
+                (below + equal / 2.0) / history.len() as f64,
+                ScoreNormalization::Quantile,
+            )
+        }
+        _ => (linear, ScoreNormalization::Linear),
+    }
 }
 
 #[cfg(test)]
@@ -124,14 +413,16 @@ mod tests {
     use super::*;
     use crate::types::{CanonicalActivity, CanonicalRecovery, CanonicalSleep};
     use chrono::Utc;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     fn make_test_signals(vendor: Vendor) -> CanonicalWearSignals {
         CanonicalWearSignals {
             vendor,
             date: "2024-01-15".to_string(),
             device_id: "test-device".to_string(),
+            user_id: None,
             timezone: "UTC".to_string(),
+            utc_offset_minutes: None,
             observed_at: Utc::now(),
             sleep: CanonicalSleep {
                 total_sleep_minutes: Some(420.0),
@@ -149,7 +440,16 @@ mod tests {
                 calories: Some(2200.0),
                 ..Default::default()
             },
+            body: Default::default(),
+            input_quality: None,
             vendor_raw: HashMap::new(),
+            field_sources: HashMap::new(),
+            field_events: HashMap::new(),
+            intraday: BTreeMap::new(),
+            latest_event_at: BTreeMap::new(),
+            tags: Vec::new(),
+            warnings: Vec::new(),
+            unmapped_signals: BTreeMap::new(),
         }
     }
 
@@ -166,6 +466,46 @@ mod tests {
 
         assert!(normalized.strain_score.is_some());
         assert!((normalized.strain_score.unwrap() - 12.5 / 21.0).abs() < 0.001);
+        assert!(normalized.score_normalization.is_empty());
+    }
+
+    #[test]
+    fn test_score_normalization_strategies() {
+        let signals = make_test_signals(Vendor::Whoop);
+        let config = NormalizationConfig::default()
+            .with_strain(ScoreNormalization::Log)
+            .with_recovery(ScoreNormalization::Quantile);
+        let history = ScoreHistory {
+            recovery: vec![0.5, 0.6, 0.7, 0.75, 0.8, 0.9, 0.95, 1.0],
+            ..Default::default()
+        };
+
+        let normalized = Normalizer::normalize_with_config(&signals, &config, &history);
+        let strain = normalized.strain_score.unwrap();
+        assert!((strain - 13.5f64.ln() / 22.0f64.ln()).abs() < 1e-9);
+        // 3 of 8 past scores are lower and one is equal
+        assert!((normalized.recovery_score.unwrap() - 3.5 / 8.0).abs() < 1e-9);
+        assert!((normalized.sleep_score.unwrap() - 0.85).abs() < 1e-9);
+        assert_eq!(
+            normalized.score_normalization["recovery_score"],
+            ScoreNormalization::Quantile
+        );
+        assert_eq!(
+            normalized.score_normalization["sleep_score"],
+            ScoreNormalization::Linear
+        );
+
+        // Too little history falls back to linear scaling, and says so
+        let short = ScoreHistory {
+            recovery: vec![0.5, 0.6],
+            ..Default::default()
+        };
+        let normalized = Normalizer::normalize_with_config(&signals, &config, &short);
+        assert!((normalized.recovery_score.unwrap() - 0.75).abs() < 1e-9);
+        assert_eq!(
+            normalized.score_normalization["recovery_score"],
+            ScoreNormalization::Linear
+        );
     }
 
     #[test]
@@ -185,7 +525,103 @@ mod tests {
         assert!(normalized.coverage < 0.8);
         assert!(normalized
             .quality_flags
-            .contains(&QualityFlag::MissingSleepData));
-        assert!(normalized.quality_flags.contains(&QualityFlag::MissingHrv));
+            .contains(&QualityFlag::MissingSleep));
+        assert!(normalized.quality_flags.contains(&QualityFlag::NoHrv));
+        assert_eq!(normalized.class_coverage, 0.5);
+    }
+
+    #[test]
+    fn test_coverage_weights_detail() {
+        let mut signals = make_test_signals(Vendor::Whoop);
+        let sparse = Normalizer::normalize(&signals).coverage;
+
+        signals.sleep.deep_sleep_minutes = Some(90.0);
+        signals.sleep.time_in_bed_minutes = Some(450.0);
+        signals.activity.active_minutes = Some(45.0);
+        let full = Normalizer::normalize(&signals);
+
+        assert!((full.coverage - 1.0).abs() < 1e-9);
+        assert!(full.coverage > sparse);
+        assert_eq!(full.class_coverage, 1.0);
+    }
+
+    #[test]
+    fn test_implausible_values_are_dropped() {
+        let mut signals = make_test_signals(Vendor::Garmin);
+        signals.recovery.hrv_rmssd_ms = Some(0.0);
+        signals.recovery.resting_hr_bpm = Some(180.0);
+        signals.recovery.spo2_percentage = Some(42.0);
+        signals.sleep.total_sleep_minutes = Some(1200.0);
+
+        let normalized = Normalizer::normalize(&signals);
+        let recovery = &normalized.canonical.recovery;
+        assert_eq!(recovery.hrv_rmssd_ms, None);
+        assert_eq!(recovery.resting_hr_bpm, None);
+        assert_eq!(recovery.spo2_percentage, None);
+        assert_eq!(
+            normalized.canonical.sleep.total_sleep_minutes,
+            Some(MAX_SLEEP_MINUTES)
+        );
+        assert!(normalized
+            .quality_flags
+            .contains(&QualityFlag::ImplausibleValue));
+        assert!(normalized.quality_flags.contains(&QualityFlag::NoHrv));
+
+        let normalized = Normalizer::normalize(&make_test_signals(Vendor::Garmin));
+        assert!(!normalized
+            .quality_flags
+            .contains(&QualityFlag::ImplausibleValue));
+    }
+
+    #[test]
+    fn test_partial_day_flag() {
+        let mut signals = make_test_signals(Vendor::Garmin);
+        signals.observed_at = "2024-01-15T18:00:00Z".parse().unwrap();
+        let normalized = Normalizer::normalize(&signals);
+        assert!(normalized.quality_flags.contains(&QualityFlag::PartialDay));
+
+        signals.observed_at = "2024-01-16T06:00:00Z".parse().unwrap();
+        let normalized = Normalizer::normalize(&signals);
+        assert!(!normalized.quality_flags.contains(&QualityFlag::PartialDay));
+
+        // Still 22:00 on the 15th in Los Angeles
+        signals.timezone = "America/Los_Angeles".to_string();
+        let normalized = Normalizer::normalize(&signals);
+        assert!(normalized.quality_flags.contains(&QualityFlag::PartialDay));
+    }
+
+    #[test]
+    fn test_inconsistent_calories_flag() {
+        let mut signals = make_test_signals(Vendor::Garmin);
+        signals.activity.active_calories = Some(450.0);
+        signals.activity.resting_calories = Some(1730.0);
+        let normalized = Normalizer::normalize(&signals);
+        assert_eq!(calorie_discrepancy(&signals.activity), Some(20.0));
+        assert!(!normalized
+            .quality_flags
+            .contains(&QualityFlag::InconsistentCalories));
+
+        signals.activity.resting_calories = Some(1200.0);
+        let normalized = Normalizer::normalize(&signals);
+        assert!(normalized
+            .quality_flags
+            .contains(&QualityFlag::InconsistentCalories));
+    }
+
+    #[test]
+    fn test_adjust_for_timezone_shift() {
+        let mut signals = make_test_signals(Vendor::Garmin);
+        let start = "2024-01-15T04:00:00Z"
+            .parse::<chrono::DateTime<Utc>>()
+            .unwrap();
+        // Flew east overnight: local wall clock says 9h in bed, the real night was 6h
+        signals.sleep.start_time = Some(start);
+        signals.sleep.end_time = Some(start + chrono::Duration::hours(6));
+        signals.sleep.time_in_bed_minutes = Some(540.0);
+        signals.sleep.total_sleep_minutes = Some(500.0);
+
+        let adjusted = Normalizer::adjust_for_timezone_shift(&signals);
+        assert_eq!(adjusted.sleep.time_in_bed_minutes, Some(360.0));
+        assert_eq!(adjusted.sleep.total_sleep_minutes, Some(360.0));
     }
 }
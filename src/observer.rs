@@ -0,0 +1,164 @@
+//! Pipeline observer hooks
+//!
+//! Embedders register a [`PipelineObserver`] on [`FluxProcessor`] or
+//! [`BehaviorProcessor`] to feed their own metrics systems (counters,
+//! histograms, error rates). Every method has a no-op default, so an observer
+//! only implements the hooks it needs.
+//!
+//! [`FluxProcessor`]: crate::pipeline::FluxProcessor
+//! [`BehaviorProcessor`]: crate::behavior::BehaviorProcessor
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::error::ComputeError;
+
+/// Which pipeline produced a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineKind {
+    /// Wearable pipeline (one window per day)
+    Wearable,
+    /// Behavioral pipeline (one window per session)
+    Behavior,
+}
+
+/// Summary of one emitted window
+#[derive(Debug, Clone)]
+pub struct ProcessedWindow<'a> {
+    /// Pipeline that produced the window
+    pub pipeline: PipelineKind,
+    /// Day (`YYYY-MM-DD`) for wearables, session ID for behavior
+    pub id: &'a str,
+    /// Data coverage (0-1)
+    pub coverage: f64,
+    /// Time spent normalizing, deriving, baselining and encoding the window
+    pub elapsed: Duration,
+}
+
+/// Hooks called as data moves through the pipeline
+pub trait PipelineObserver: Send + Sync {
+    /// Input events were parsed (raw events, or the events of a behavioral session)
+    fn on_events_parsed(&self, _pipeline: PipelineKind, _count: usize) {}
+
+    /// A day (wearable) or session (behavior) was encoded
+    fn on_day_processed(&self, _window: &ProcessedWindow<'_>) {}
+
+    /// A quality flag was raised for a window (`flag` is the snake_case name)
+    fn on_quality_flag(&self, _pipeline: PipelineKind, _id: &str, _flag: &str) {}
+
+    /// A processing call failed
+    fn on_error(&self, _pipeline: PipelineKind, _error: &ComputeError) {}
+}
+
+/// Observer that ignores every hook
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl PipelineObserver for NoopObserver {}
+
+/// Observer counting events, windows, flags and errors, for progress reporting.
+///
+/// Counters are atomic, so one instance can be shared (via `Arc`) across
+/// processors running on different threads.
+#[derive(Debug, Default)]
+pub struct ProgressCounters {
+    events_parsed: AtomicUsize,
+    windows_processed: AtomicUsize,
+    quality_flags: AtomicUsize,
+    errors: AtomicUsize,
+}
+
+impl ProgressCounters {
+    /// Create counters starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Input events parsed so far
+    pub fn events_parsed(&self) -> usize {
+        self.events_parsed.load(Ordering::Relaxed)
+    }
+
+    /// Days (wearable) or sessions (behavior) encoded so far
+    pub fn windows_processed(&self) -> usize {
+        self.windows_processed.load(Ordering::Relaxed)
+    }
+
+    /// Quality flags raised so far
+    pub fn quality_flags(&self) -> usize {
+        self.quality_flags.load(Ordering::Relaxed)
+    }
+
+    /// Failed processing calls so far
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::Relaxed)
+    }
+}
+
+impl PipelineObserver for ProgressCounters {
+    fn on_events_parsed(&self, _pipeline: PipelineKind, count: usize) {
+        self.events_parsed.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn on_day_processed(&self, _window: &ProcessedWindow<'_>) {
+        self.windows_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_quality_flag(&self, _pipeline: PipelineKind, _id: &str, _flag: &str) {
+        self.quality_flags.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_error(&self, _pipeline: PipelineKind, _error: &ComputeError) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Report an error to `observer` and pass the result through
+pub(crate) fn observe<T>(
+    observer: &dyn PipelineObserver,
+    pipeline: PipelineKind,
+    result: Result<T, ComputeError>,
+) -> Result<T, ComputeError> {
+    if let Err(e) = &result {
+        observer.on_error(pipeline, e);
+    }
+    result
+}
+
+/// Observer that records every hook call, for tests
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct RecordingObserver {
+    pub events: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl PipelineObserver for RecordingObserver {
+    fn on_events_parsed(&self, pipeline: PipelineKind, count: usize) {
+        self.push(format!("{pipeline:?} parsed {count}"));
+    }
+
+    fn on_day_processed(&self, window: &ProcessedWindow<'_>) {
+        self.push(format!("{:?} processed {}", window.pipeline, window.id));
+    }
+
+    fn on_quality_flag(&self, pipeline: PipelineKind, id: &str, flag: &str) {
+        self.push(format!("{pipeline:?} flag {id} {flag}"));
+    }
+
+    fn on_error(&self, pipeline: PipelineKind, _error: &ComputeError) {
+        self.push(format!("{pipeline:?} error"));
+    }
+}
+
+#[cfg(test)]
+impl RecordingObserver {
+    fn push(&self, event: String) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    pub fn count(&self, needle: &str) -> usize {
+        let events = self.events.lock().unwrap();
+        events.iter().filter(|e| e.contains(needle)).count()
+    }
+}
@@ -0,0 +1,311 @@
+//! Unified HSI payload model
+//!
+//! Flux emits two HSI shapes:
+//!
+//! - [`HsiDailyPayload`] (wearable pipeline, `hsi_version` 1.0.0): provenance,
+//!   quality and an array of daily windows with namespaced signals
+//! - [`HsiAxesPayload`] (behavior pipeline, `hsi_version` 1.0): a window map
+//!   and axis readings grouped by domain
+//!
+//! [`AnyHsiPayload`] parses either shape, so consumers need one parser.
+//! [`daily_to_axes`] re-expresses a daily payload in the axes shape, with one
+//! window per day and readings in the `sleep`, `physiology` and `activity`
+//! domains. Only signals already scaled to 0-1 become readings; absolute
+//! values (HRV in ms, resting HR in bpm) have no axes equivalent and are
+//! dropped.
+
+use crate::behavior::types::{
+    HsiAxes, HsiAxesDomain, HsiAxisReading, HsiDirection, HsiPrivacy, HsiProducer, HsiSource,
+    HsiSourceType, HsiWindow,
+};
+use crate::error::ComputeError;
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub use crate::behavior::types::HsiPayload as HsiAxesPayload;
+pub use crate::types::HsiPayload as HsiDailyPayload;
+
+/// An HSI payload of either shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged, try_from = "Value")]
+pub enum AnyHsiPayload {
+    /// Wearable daily payload (`windows` is an array)
+    Daily(HsiDailyPayload),
+    /// Axes payload (`windows` is a map keyed by window ID)
+    Axes(HsiAxesPayload),
+}
+
+impl AnyHsiPayload {
+    /// Parse an HSI payload of either shape from JSON
+    pub fn parse(json: &str) -> Result<Self, ComputeError> {
+        Self::try_from(serde_json::from_str::<Value>(json)?)
+    }
+
+    /// The payload's `hsi_version`
+    pub fn hsi_version(&self) -> &str {
+        match self {
+            Self::Daily(payload) => &payload.hsi_version,
+            Self::Axes(payload) => &payload.hsi_version,
+        }
+    }
+
+    /// The daily payload, if this is one
+    pub fn as_daily(&self) -> Option<&HsiDailyPayload> {
+        match self {
+            Self::Daily(payload) => Some(payload),
+            Self::Axes(_) => None,
+        }
+    }
+
+    /// The axes payload, if this is one
+    pub fn as_axes(&self) -> Option<&HsiAxesPayload> {
+        match self {
+            Self::Daily(_) => None,
+            Self::Axes(payload) => Some(payload),
+        }
+    }
+
+    /// The payload in the axes shape, converting daily payloads
+    pub fn into_axes(self) -> Result<HsiAxesPayload, ComputeError> {
+        match self {
+            Self::Daily(payload) => daily_to_axes(&payload),
+            Self::Axes(payload) => Ok(payload),
+        }
+    }
+}
+
+impl TryFrom<Value> for AnyHsiPayload {
+    type Error = ComputeError;
+
+    /// Dispatch on the shape of `windows`, as [`crate::validation`] does
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value.get("windows") {
+            Some(Value::Array(_)) => Ok(Self::Daily(serde_json::from_value(value)?)),
+            Some(Value::Object(_)) => Ok(Self::Axes(serde_json::from_value(value)?)),
+            Some(_) => Err(ComputeError::ParseError(
+                "HSI windows must be an array or an object".to_string(),
+            )),
+            None => Err(ComputeError::MissingField("windows".to_string())),
+        }
+    }
+}
+
+impl From<HsiDailyPayload> for AnyHsiPayload {
+    fn from(payload: HsiDailyPayload) -> Self {
+        Self::Daily(payload)
+    }
+}
+
+impl From<HsiAxesPayload> for AnyHsiPayload {
+    fn from(payload: HsiAxesPayload) -> Self {
+        Self::Axes(payload)
+    }
+}
+
+/// Re-express a daily payload as an axes payload.
+///
+/// Each daily window becomes a window spanning local midnight to midnight in
+/// the day's timezone. Readings take the payload's quality confidence, and the
+/// device is listed as a single sensor source. Baseline trends (-1 to 1)
+/// become bidirectional `*_trend` readings scored `(1 + trend) / 2`.
+pub fn daily_to_axes(payload: &HsiDailyPayload) -> Result<HsiAxesPayload, ComputeError> {
+    let provenance = &payload.provenance;
+    let source_id = format!("s_{}", provenance.source_device_id.replace('-', "_"));
+    let confidence = payload.quality.confidence;
+
+    let mut window_ids = Vec::new();
+    let mut windows = HashMap::new();
+    let mut sleep = Vec::new();
+    let mut physiology = Vec::new();
+    let mut activity = Vec::new();
+
+    for day in &payload.windows {
+        let window_id = format!("w_{}", day.date.replace('-', "_"));
+        let (start, end) = day_bounds(&day.date, &day.timezone)?;
+        windows.insert(
+            window_id.clone(),
+            HsiWindow {
+                start,
+                end,
+                label: Some(format!("day:{}", day.date)),
+            },
+        );
+
+        let reading = |axis: &str, score: f64| HsiAxisReading {
+            axis: axis.to_string(),
+            score: Some(score),
+            confidence,
+            window_id: window_id.clone(),
+            direction: Some(HsiDirection::HigherIsMore),
+            unit: None,
+            evidence_source_ids: Some(vec![source_id.clone()]),
+            notes: None,
+        };
+        let readings = |signals: &[(&str, Option<f64>)]| -> Vec<HsiAxisReading> {
+            signals
+                .iter()
+                .filter_map(|(axis, score)| score.map(|score| reading(axis, score)))
+                .collect()
+        };
+        let trends = |signals: &[(&str, Option<f64>)]| -> Vec<HsiAxisReading> {
+            signals
+                .iter()
+                .filter_map(|(axis, trend)| {
+                    trend.map(|trend| HsiAxisReading {
+                        direction: Some(HsiDirection::Bidirectional),
+                        unit: Some("trend".to_string()),
+                        ..reading(axis, (1.0 + trend) / 2.0)
+                    })
+                })
+                .collect()
+        };
+        sleep.extend(readings(&[
+            ("sleep_efficiency", day.sleep.efficiency),
+            ("sleep_score", day.sleep.score),
+            ("sleep_fragmentation", day.sleep.fragmentation),
+            ("deep_sleep_ratio", day.sleep.deep_ratio),
+            ("rem_sleep_ratio", day.sleep.rem_ratio),
+        ]));
+        sleep.extend(trends(&[("sleep_trend", day.baseline.sleep_trend)]));
+        physiology.extend(readings(&[("recovery", day.physiology.recovery_score)]));
+        physiology.extend(trends(&[
+            ("hrv_trend", day.baseline.hrv_trend),
+            ("rhr_trend", day.baseline.rhr_trend),
+        ]));
+        activity.extend(readings(&[
+            ("strain", day.activity.strain_score),
+            ("load", day.activity.normalized_load),
+        ]));
+        window_ids.push(window_id);
+    }
+
+    let domain = |readings: Vec<HsiAxisReading>| {
+        (!readings.is_empty()).then_some(HsiAxesDomain { readings })
+    };
+    let axes = HsiAxes {
+        sleep: domain(sleep),
+        physiology: domain(physiology),
+        activity: domain(activity),
+        ..Default::default()
+    };
+
+    let mut sources = HashMap::new();
+    sources.insert(
+        source_id.clone(),
+        HsiSource {
+            source_type: HsiSourceType::Sensor,
+            quality: payload.quality.coverage,
+            degraded: !payload.quality.flags.is_empty(),
+            notes: None,
+        },
+    );
+
+    let mut meta = HashMap::new();
+    meta.insert(
+        "source_vendor".to_string(),
+        Value::String(provenance.source_vendor.clone()),
+    );
+    meta.insert(
+        "converted_from".to_string(),
+        Value::String(format!("hsi {} daily", payload.hsi_version)),
+    );
+
+    Ok(HsiAxesPayload {
+        hsi_version: crate::behavior::encoder::HSI_VERSION.to_string(),
+        observed_at_utc: provenance.observed_at_utc.clone(),
+        computed_at_utc: provenance.computed_at_utc.clone(),
+        producer: HsiProducer {
+            name: payload.producer.name.clone(),
+            version: payload.producer.version.clone(),
+            instance_id: Some(payload.producer.instance_id.clone()),
+        },
+        window_ids,
+        windows,
+        source_ids: Some(vec![source_id]),
+        sources: Some(sources),
+        axes: (axes.sleep.is_some() || axes.physiology.is_some() || axes.activity.is_some())
+            .then_some(axes),
+        privacy: HsiPrivacy::default(),
+        meta: Some(meta),
+    })
+}
+
+/// Local midnight to midnight of a day, as RFC3339 UTC timestamps
+fn day_bounds(date: &str, timezone: &str) -> Result<(String, String), ComputeError> {
+    let tz: Tz = timezone
+        .parse()
+        .map_err(|_| ComputeError::InvalidTimezone(timezone.to_string()))?;
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| ComputeError::DateParseError(format!("{date}: {e}")))?;
+    let midnight = |day: NaiveDate| {
+        tz.from_local_datetime(&day.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+            .earliest()
+            .map(|t| t.with_timezone(&Utc).to_rfc3339())
+            .ok_or_else(|| ComputeError::DateParseError(format!("no local midnight on {day}")))
+    };
+    let next = day
+        .succ_opt()
+        .ok_or_else(|| ComputeError::DateParseError(date.to_string()))?;
+    Ok((midnight(day)?, midnight(next)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{Generator, SyntheticConfig};
+    use crate::validation::validate_hsi;
+
+    fn generator() -> Generator {
+        Generator::new(SyntheticConfig::default().with_days(1)).unwrap()
+    }
+
+    fn whoop_payload() -> String {
+        crate::whoop_to_hsi_daily(
+            generator().whoop_payload().to_string(),
+            "America/New_York".to_string(),
+            "dev-1".to_string(),
+        )
+        .unwrap()
+        .remove(0)
+    }
+
+    #[test]
+    fn test_parse_dispatches_on_shape() {
+        let daily = AnyHsiPayload::parse(&whoop_payload()).unwrap();
+        assert_eq!(daily.hsi_version(), "1.0.0");
+        assert!(daily.as_daily().is_some());
+
+        let session = generator().behavior_sessions().unwrap().remove(0);
+        let behavior = crate::behavior_to_hsi(serde_json::to_string(&session).unwrap()).unwrap();
+        let axes = AnyHsiPayload::parse(&behavior).unwrap();
+        assert_eq!(axes.hsi_version(), "1.0");
+        assert!(axes.as_axes().is_some());
+
+        assert!(matches!(
+            AnyHsiPayload::parse("{}"),
+            Err(ComputeError::MissingField(_))
+        ));
+    }
+
+    #[test]
+    fn test_daily_to_axes_is_valid_hsi() {
+        let daily = AnyHsiPayload::parse(&whoop_payload()).unwrap();
+        let axes = daily.into_axes().unwrap();
+
+        let window = &axes.windows[&axes.window_ids[0]];
+        // Local midnight in New York is 05:00 UTC in winter
+        assert!(
+            window.start.ends_with("T05:00:00+00:00"),
+            "{}",
+            window.start
+        );
+        let readings = &axes.axes.as_ref().unwrap().sleep.as_ref().unwrap().readings;
+        assert!(readings.iter().any(|r| r.axis == "sleep_efficiency"));
+
+        let violations = validate_hsi(&serde_json::to_value(&axes).unwrap());
+        assert!(violations.is_empty(), "{violations:?}");
+    }
+}
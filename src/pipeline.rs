@@ -3,12 +3,36 @@
 //! This module provides the public API for Synheart Flux.
 //! It orchestrates the full pipeline from raw vendor JSON to HSI output.
 
-use crate::adapters::{GarminAdapter, VendorPayloadAdapter, WhoopAdapter};
-use crate::baseline::BaselineStore;
-use crate::encoder::HsiEncoder;
+use crate::adapters::{self, GarminAdapter, VendorPayloadAdapter, WhoopAdapter};
+use crate::baseline::{BaselineConfig, BaselineStore};
+use crate::calibration::HrvCalibrationConfig;
+use crate::encoder::{
+    device_instance_id, ConfidencePolicy, EncoderConfig, HsiEncoder, ProducerIdentity,
+};
 use crate::error::ComputeError;
-use crate::features::FeatureDeriver;
-use crate::normalizer::Normalizer;
+use crate::explain::{self, DayTrace, StagedWindow, TracedPayload};
+use crate::features::{self, ActivityGoal, FeatureConfig, FeatureDeriver, FeatureStage};
+use crate::normalizer::{NormalizationConfig, Normalizer, ScoreHistory};
+use crate::observer::{observe, NoopObserver, PipelineKind, PipelineObserver, ProcessedWindow};
+use crate::privacy::PrivacyPolicy;
+use crate::rollup::{self, HsiRollup, RollupDay, RollupPeriod, ROLLUP_HISTORY_DAYS};
+use crate::schema::{OrderingConfig, RawEvent, RawEventAdapter};
+use crate::types::{
+    CanonicalWearSignals, ContextualSignals, HsiPayload, NormalizedSignals, QualityFlag,
+    ScoreNormalization, Vendor,
+};
+use crate::warnings::{ProcessOutput, Warning, WarningCode};
+use crate::watermark::{CompletedDay, DayWatermark, LateEventPolicy};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub use crate::fusion::{DeviceFusion, FusionField};
+
+/// Warnings a processor holds until they are taken; older ones are dropped
+pub const MAX_PENDING_WARNINGS: usize = 1000;
 
 /// Convert raw WHOOP JSON payload to HSI-compliant daily payloads.
 ///
@@ -33,8 +57,30 @@ pub fn whoop_to_hsi_daily(
     timezone: String,
     device_id: String,
 ) -> Result<Vec<String>, ComputeError> {
-    let adapter = WhoopAdapter;
-    process_vendor_payload(&adapter, &raw_json, &timezone, &device_id)
+    let encoder = HsiEncoder::new();
+    let payloads =
+        process_vendor_payload(&WhoopAdapter, &raw_json, &timezone, &device_id, &encoder)?;
+    payloads
+        .iter()
+        .map(|p| encoder.payload_to_json(p))
+        .collect()
+}
+
+/// Convert raw WHOOP JSON payload to typed HSI daily payloads.
+///
+/// Same as [`whoop_to_hsi_daily`] without the JSON round trip.
+pub fn whoop_to_hsi_payloads(
+    raw_json: &str,
+    timezone: &str,
+    device_id: &str,
+) -> Result<Vec<HsiPayload>, ComputeError> {
+    process_vendor_payload(
+        &WhoopAdapter,
+        raw_json,
+        timezone,
+        device_id,
+        &HsiEncoder::new(),
+    )
 }
 
 /// Convert raw Garmin JSON payload to HSI-compliant daily payloads.
@@ -60,8 +106,30 @@ pub fn garmin_to_hsi_daily(
     timezone: String,
     device_id: String,
 ) -> Result<Vec<String>, ComputeError> {
-    let adapter = GarminAdapter;
-    process_vendor_payload(&adapter, &raw_json, &timezone, &device_id)
+    let encoder = HsiEncoder::new();
+    let payloads =
+        process_vendor_payload(&GarminAdapter, &raw_json, &timezone, &device_id, &encoder)?;
+    payloads
+        .iter()
+        .map(|p| encoder.payload_to_json(p))
+        .collect()
+}
+
+/// Convert raw Garmin JSON payload to typed HSI daily payloads.
+///
+/// Same as [`garmin_to_hsi_daily`] without the JSON round trip.
+pub fn garmin_to_hsi_payloads(
+    raw_json: &str,
+    timezone: &str,
+    device_id: &str,
+) -> Result<Vec<HsiPayload>, ComputeError> {
+    process_vendor_payload(
+        &GarminAdapter,
+        raw_json,
+        timezone,
+        device_id,
+        &HsiEncoder::new(),
+    )
 }
 
 /// Process vendor payload through the full pipeline.
@@ -71,13 +139,14 @@ pub fn garmin_to_hsi_daily(
 /// 2. Normalizer - Normalize units and scales
 /// 3. FeatureDeriver - Compute derived features
 /// 4. BaselineStore - Apply baseline context
-/// 5. HsiEncoder - Encode to HSI JSON
+/// 5. HsiEncoder - Encode to HSI payloads
 fn process_vendor_payload(
     adapter: &dyn VendorPayloadAdapter,
     raw_json: &str,
     timezone: &str,
     device_id: &str,
-) -> Result<Vec<String>, ComputeError> {
+    encoder: &HsiEncoder,
+) -> Result<Vec<HsiPayload>, ComputeError> {
     // Stage 1: Parse vendor payload to canonical signals
     let canonical_signals = adapter.parse(raw_json, timezone, device_id)?;
 
@@ -85,9 +154,8 @@ fn process_vendor_payload(
         return Ok(Vec::new());
     }
 
-    // Initialize baseline store and encoder
+    // Initialize baseline store
     let mut baseline_store = BaselineStore::default();
-    let encoder = HsiEncoder::new();
 
     let mut hsi_payloads = Vec::new();
 
@@ -100,11 +168,12 @@ fn process_vendor_payload(
         let derived = FeatureDeriver::derive(normalized);
 
         // Stage 4: Apply baselines and create contextual signals
-        let contextual = baseline_store.update_and_contextualize(derived);
+        let contextual = baseline_store
+            .user_store_mut(canonical.user_id.as_deref())
+            .update_and_contextualize(derived);
 
-        // Stage 5: Encode to HSI JSON
-        let hsi_json = encoder.encode_to_json(&contextual)?;
-        hsi_payloads.push(hsi_json);
+        // Stage 5: Encode to HSI
+        hsi_payloads.push(encoder.encode(&contextual)?);
     }
 
     Ok(hsi_payloads)
@@ -116,6 +185,19 @@ fn process_vendor_payload(
 pub struct FluxProcessor {
     baseline_store: BaselineStore,
     encoder: HsiEncoder,
+    observer: Arc<dyn PipelineObserver>,
+    features: FeatureConfig,
+    normalization: NormalizationConfig,
+    hrv_calibration: Option<HrvCalibrationConfig>,
+    ordering: OrderingConfig,
+    /// Custom metrics emitted in `meta.features`
+    feature_stages: Vec<Arc<dyn FeatureStage>>,
+    /// Adapters registered for `process_vendor`, by name
+    adapters: BTreeMap<String, Arc<dyn VendorPayloadAdapter>>,
+    /// Recently emitted days, for rollups
+    rollup_days: BTreeMap<NaiveDate, RollupDay>,
+    /// Warnings not yet taken, oldest first
+    warnings: VecDeque<Warning>,
 }
 
 impl Default for FluxProcessor {
@@ -130,21 +212,245 @@ impl FluxProcessor {
         Self {
             baseline_store: BaselineStore::default(),
             encoder: HsiEncoder::new(),
+            observer: Arc::new(NoopObserver),
+            features: FeatureConfig::default(),
+            normalization: NormalizationConfig::default(),
+            hrv_calibration: None,
+            ordering: OrderingConfig::default(),
+            feature_stages: Vec::new(),
+            adapters: BTreeMap::new(),
+            rollup_days: BTreeMap::new(),
+            warnings: VecDeque::new(),
         }
     }
 
     /// Create a processor with a specific baseline window size
     pub fn with_baseline_window(window_days: usize) -> Self {
-        Self {
-            baseline_store: BaselineStore::new(window_days),
-            encoder: HsiEncoder::new(),
+        let mut processor = Self::new();
+        processor.baseline_store = BaselineStore::new(window_days);
+        processor
+    }
+
+    /// Enable deterministic mode for reproducible (golden) output.
+    ///
+    /// The producer instance ID is derived from `seed`, every clock read is
+    /// pinned to `computed_at`, and JSON keys are emitted in sorted order.
+    pub fn set_deterministic(&mut self, seed: u64, computed_at: DateTime<Utc>) {
+        let encoder = HsiEncoder::deterministic(seed, computed_at)
+            .with_producer(self.encoder.producer_identity().clone())
+            .with_confidence_policy(self.encoder.confidence_policy())
+            .with_encoder_config(self.encoder.encoder_config().clone())
+            .with_privacy_policy(self.encoder.privacy_policy().clone());
+        #[cfg(feature = "integrity")]
+        let encoder = encoder.with_integrity(self.encoder.integrity().cloned());
+        self.encoder = encoder;
+    }
+
+    /// Stamp payloads with a host's producer identity (builder form of [`Self::set_producer`])
+    pub fn with_producer(mut self, producer: ProducerIdentity) -> Self {
+        self.set_producer(producer);
+        self
+    }
+
+    /// Stamp payloads with a host's producer name, version and instance ID.
+    ///
+    /// Without an instance ID, a pinned one ([`Self::set_instance_id`]) is kept.
+    pub fn set_producer(&mut self, mut producer: ProducerIdentity) {
+        if producer.instance_id.is_none() {
+            producer.instance_id = self.baseline_store.instance_id().map(str::to_string);
         }
+        let encoder = std::mem::take(&mut self.encoder);
+        self.encoder = encoder.with_producer(producer);
+    }
+
+    /// Pin the producer instance ID (builder form of [`Self::set_instance_id`])
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.set_instance_id(instance_id);
+        self
+    }
+
+    /// Pin the producer instance ID, derived from the device ID with
+    /// [`device_instance_id`]
+    pub fn with_device_instance_id(self, device_id: &str) -> Self {
+        self.with_instance_id(device_instance_id(device_id))
+    }
+
+    /// Pin the producer instance ID instead of generating one per processor.
+    ///
+    /// The ID is saved with the baselines and restored by
+    /// [`Self::load_baselines`], so payloads from one device keep the same
+    /// `producer.instance_id` across restarts.
+    pub fn set_instance_id(&mut self, instance_id: impl Into<String>) {
+        let instance_id = instance_id.into();
+        self.baseline_store
+            .set_instance_id(Some(instance_id.clone()));
+        let producer = self
+            .encoder
+            .producer_identity()
+            .clone()
+            .with_instance_id(instance_id);
+        let encoder = std::mem::take(&mut self.encoder);
+        self.encoder = encoder.with_producer(producer);
+    }
+
+    /// Override how `quality.confidence` is computed for emitted windows
+    pub fn set_confidence_policy(&mut self, policy: Arc<dyn ConfidencePolicy>) {
+        let encoder = std::mem::take(&mut self.encoder);
+        self.encoder = encoder.with_confidence_policy(policy);
+    }
+
+    /// Filter emitted axes and metadata (builder form of [`Self::set_encoder_config`])
+    pub fn with_encoder_config(mut self, config: EncoderConfig) -> Self {
+        self.set_encoder_config(config);
+        self
+    }
+
+    /// Filter emitted axes and metadata
+    pub fn set_encoder_config(&mut self, config: EncoderConfig) {
+        let encoder = std::mem::take(&mut self.encoder);
+        self.encoder = encoder.with_encoder_config(config);
+    }
+
+    /// Apply a privacy policy to vendor metadata (builder form of [`Self::set_privacy_policy`])
+    pub fn with_privacy_policy(mut self, policy: PrivacyPolicy) -> Self {
+        self.set_privacy_policy(policy);
+        self
+    }
+
+    /// Apply a privacy policy to vendor metadata
+    pub fn set_privacy_policy(&mut self, policy: PrivacyPolicy) {
+        let encoder = std::mem::take(&mut self.encoder);
+        self.encoder = encoder.with_privacy_policy(policy);
+    }
+
+    /// Seal emitted payloads with `meta.integrity` (builder form of [`Self::set_integrity`])
+    #[cfg(feature = "integrity")]
+    pub fn with_integrity(mut self, integrity: crate::integrity::IntegrityConfig) -> Self {
+        self.set_integrity(Some(integrity));
+        self
+    }
+
+    /// Seal emitted payloads with `meta.integrity`, or stop sealing with `None`
+    #[cfg(feature = "integrity")]
+    pub fn set_integrity(&mut self, integrity: Option<crate::integrity::IntegrityConfig>) {
+        let encoder = std::mem::take(&mut self.encoder);
+        self.encoder = encoder.with_integrity(integrity);
+    }
+
+    /// Report pipeline metrics to `observer` (builder form of [`Self::set_observer`])
+    pub fn with_observer(mut self, observer: Arc<dyn PipelineObserver>) -> Self {
+        self.set_observer(observer);
+        self
+    }
+
+    /// Report pipeline metrics to `observer`, replacing any previous one
+    pub fn set_observer(&mut self, observer: Arc<dyn PipelineObserver>) {
+        self.observer = observer;
+    }
+
+    /// Emit a custom stage's metrics with each day (builder form of [`Self::add_feature_stage`])
+    pub fn with_feature_stage(mut self, stage: Arc<dyn FeatureStage>) -> Self {
+        self.add_feature_stage(stage);
+        self
+    }
+
+    /// Emit a custom stage's metrics with each day, after any stages already added
+    pub fn add_feature_stage(&mut self, stage: Arc<dyn FeatureStage>) {
+        self.feature_stages.push(stage);
+    }
+
+    /// Exclude or separate tagged days in the baselines (builder form of [`Self::set_baseline_config`])
+    pub fn with_baseline_config(mut self, config: BaselineConfig) -> Self {
+        self.set_baseline_config(config);
+        self
+    }
+
+    /// Exclude or separate tagged days in the baselines
+    pub fn set_baseline_config(&mut self, config: BaselineConfig) {
+        self.baseline_store.set_config(config);
+    }
+
+    /// Score each day against a step/active-minute goal (builder form of [`Self::set_activity_goal`])
+    pub fn with_activity_goal(mut self, goal: ActivityGoal) -> Self {
+        self.set_activity_goal(Some(goal));
+        self
+    }
+
+    /// Score each day against a step/active-minute goal, or stop with `None`
+    pub fn set_activity_goal(&mut self, goal: Option<ActivityGoal>) {
+        self.features.activity_goal = goal;
     }
 
-    /// Load baseline state from JSON
+    /// Compute BMI from weight with the user's height (builder form of [`Self::set_height_cm`])
+    pub fn with_height_cm(mut self, height_cm: f64) -> Self {
+        self.set_height_cm(Some(height_cm));
+        self
+    }
+
+    /// Compute BMI from weight with the user's height, or stop with `None`
+    pub fn set_height_cm(&mut self, height_cm: Option<f64>) {
+        self.features.height_cm = height_cm;
+    }
+
+    /// Choose how each vendor score is scaled to 0-1 (builder form of [`Self::set_normalization`])
+    pub fn with_normalization(mut self, config: NormalizationConfig) -> Self {
+        self.set_normalization(config);
+        self
+    }
+
+    /// Choose how each vendor score is scaled to 0-1
+    pub fn set_normalization(&mut self, config: NormalizationConfig) {
+        self.normalization = config;
+    }
+
+    /// Map HRV from every vendor onto one vendor's scale (builder form of [`Self::set_hrv_calibration`])
+    pub fn with_hrv_calibration(mut self, config: HrvCalibrationConfig) -> Self {
+        self.set_hrv_calibration(Some(config));
+        self
+    }
+
+    /// Map HRV from every vendor onto one vendor's scale, or `None` to use HRV as reported
+    pub fn set_hrv_calibration(&mut self, config: Option<HrvCalibrationConfig>) {
+        self.hrv_calibration = config;
+    }
+
+    /// Set how heavily out-of-order raw event input is treated
+    pub fn with_ordering(mut self, config: OrderingConfig) -> Self {
+        self.set_ordering(config);
+        self
+    }
+
+    /// Set how heavily out-of-order raw event input is treated
+    pub fn set_ordering(&mut self, config: OrderingConfig) {
+        self.ordering = config;
+    }
+
+    /// Record per-vendor HRV for fitting calibrations without processing the days.
+    ///
+    /// Processed days are recorded automatically; use this for the
+    /// single-vendor days of hosts that fuse devices before processing.
+    pub fn observe_hrv(&mut self, days: &[CanonicalWearSignals]) {
+        self.baseline_store.record_hrv_overlaps(days);
+    }
+
+    /// Load baseline state from JSON (the baseline config is kept).
+    ///
+    /// A saved instance ID is restored; a store saved without one keeps the
+    /// currently pinned ID, if any.
     pub fn load_baselines(&mut self, json: &str) -> Result<(), ComputeError> {
-        self.baseline_store =
-            BaselineStore::from_json(json).map_err(|e| ComputeError::ParseError(e.to_string()))?;
+        let config = self.baseline_store.config().clone();
+        let pinned = self.baseline_store.instance_id().map(str::to_string);
+        self.baseline_store = BaselineStore::from_json(json)
+            .map_err(|e| ComputeError::ParseError(e.to_string()))?
+            .with_config(config);
+        if let Some(instance_id) = self
+            .baseline_store
+            .instance_id()
+            .map(str::to_string)
+            .or(pinned)
+        {
+            self.set_instance_id(instance_id);
+        }
         Ok(())
     }
 
@@ -155,6 +461,11 @@ impl FluxProcessor {
             .map_err(|e| ComputeError::EncodingError(e.to_string()))
     }
 
+    /// The baselines accumulated so far
+    pub fn baseline_store(&self) -> &BaselineStore {
+        &self.baseline_store
+    }
+
     /// Process WHOOP payload with persistent baselines
     pub fn process_whoop(
         &mut self,
@@ -162,8 +473,23 @@ impl FluxProcessor {
         timezone: &str,
         device_id: &str,
     ) -> Result<Vec<String>, ComputeError> {
-        let adapter = WhoopAdapter;
-        self.process_with_adapter(&adapter, raw_json, timezone, device_id)
+        let result = self
+            .process_with_adapter(&WhoopAdapter, raw_json, timezone, device_id)
+            .and_then(|payloads| self.payloads_to_json(&payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
+
+    /// Process WHOOP payload with persistent baselines, returning typed payloads
+    pub fn process_whoop_payloads(
+        &mut self,
+        raw_json: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let result = self
+            .process_with_adapter(&WhoopAdapter, raw_json, timezone, device_id)
+            .and_then(|payloads| self.sealed(payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
     }
 
     /// Process Garmin payload with persistent baselines
@@ -173,150 +499,871 @@ impl FluxProcessor {
         timezone: &str,
         device_id: &str,
     ) -> Result<Vec<String>, ComputeError> {
-        let adapter = GarminAdapter;
-        self.process_with_adapter(&adapter, raw_json, timezone, device_id)
+        let result = self
+            .process_with_adapter(&GarminAdapter, raw_json, timezone, device_id)
+            .and_then(|payloads| self.payloads_to_json(&payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
     }
 
-    fn process_with_adapter(
+    /// Process Garmin payload with persistent baselines, returning typed payloads
+    pub fn process_garmin_payloads(
+        &mut self,
+        raw_json: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let result = self
+            .process_with_adapter(&GarminAdapter, raw_json, timezone, device_id)
+            .and_then(|payloads| self.sealed(payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
+
+    /// Process a WHOOP or Garmin payload with persistent baselines, detecting
+    /// the vendor from its shape (see [`adapters::detect_vendor`])
+    pub fn process_auto(
         &mut self,
-        adapter: &dyn VendorPayloadAdapter,
         raw_json: &str,
         timezone: &str,
         device_id: &str,
     ) -> Result<Vec<String>, ComputeError> {
-        let canonical_signals = adapter.parse(raw_json, timezone, device_id)?;
+        let result = self
+            .process_detected(raw_json, timezone, device_id)
+            .and_then(|payloads| self.payloads_to_json(&payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
 
-        let mut hsi_payloads = Vec::new();
+    /// Process a WHOOP or Garmin payload with persistent baselines, detecting
+    /// the vendor from its shape and returning typed payloads
+    pub fn process_auto_payloads(
+        &mut self,
+        raw_json: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let result = self
+            .process_detected(raw_json, timezone, device_id)
+            .and_then(|payloads| self.sealed(payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
 
-        for canonical in canonical_signals {
-            let normalized = Normalizer::normalize(&canonical);
-            let derived = FeatureDeriver::derive(normalized);
-            let contextual = self.baseline_store.update_and_contextualize(derived);
-            let hsi_json = self.encoder.encode_to_json(&contextual)?;
-            hsi_payloads.push(hsi_json);
+    fn process_detected(
+        &mut self,
+        raw_json: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        match adapters::detect_vendor(raw_json) {
+            Some(Vendor::Whoop) => {
+                self.process_with_adapter(&WhoopAdapter, raw_json, timezone, device_id)
+            }
+            Some(Vendor::Garmin) => {
+                self.process_with_adapter(&GarminAdapter, raw_json, timezone, device_id)
+            }
+            None => Err(ComputeError::UnsupportedVendor(
+                "payload is neither a WHOOP nor a Garmin export".to_string(),
+            )),
         }
-
-        Ok(hsi_payloads)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Process `wear.raw_event.v1` NDJSON with persistent baselines.
+    ///
+    /// Events from any provider are grouped into canonical days, so hosts do
+    /// not need to pick a vendor-specific entry point.
+    pub fn process_raw_events(
+        &mut self,
+        ndjson: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<String>, ComputeError> {
+        let result = self
+            .process_raw_event_days(ndjson, timezone, device_id)
+            .and_then(|payloads| self.payloads_to_json(&payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
 
-    fn sample_whoop_json() -> &'static str {
-        r#"{
-            "sleep": [{
-                "id": 1,
-                "start": "2024-01-15T22:30:00.000Z",
-                "end": "2024-01-16T06:30:00.000Z",
-                "score": {
-                    "stage_summary": {
-                        "total_in_bed_time_milli": 28800000,
-                        "total_awake_time_milli": 1800000,
-                        "total_light_sleep_time_milli": 12600000,
-                        "total_slow_wave_sleep_time_milli": 7200000,
-                        "total_rem_sleep_time_milli": 7200000,
-                        "total_sleep_time_milli": 27000000,
-                        "disturbance_count": 3
-                    },
-                    "sleep_performance_percentage": 85.0,
-                    "sleep_efficiency_percentage": 93.75,
-                    "respiratory_rate": 14.5
-                }
-            }],
-            "recovery": [{
-                "cycle_id": 1,
-                "created_at": "2024-01-15T06:30:00.000Z",
-                "score": {
-                    "recovery_score": 75.0,
-                    "resting_heart_rate": 52.0,
-                    "hrv_rmssd_milli": 65.0,
-                    "spo2_percentage": 97.0
-                }
-            }],
-            "cycle": [{
-                "id": 1,
-                "start": "2024-01-15T06:30:00.000Z",
-                "end": "2024-01-15T22:30:00.000Z",
-                "score": {
-                    "strain": 12.5,
-                    "kilojoule": 8500.0,
-                    "average_heart_rate": 72.0,
-                    "max_heart_rate": 165.0
-                }
-            }]
-        }"#
+    /// Process raw event NDJSON like [`Self::process_raw_events`], returning typed payloads
+    pub fn process_raw_event_payloads(
+        &mut self,
+        ndjson: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let result = self
+            .process_raw_event_days(ndjson, timezone, device_id)
+            .and_then(|payloads| self.sealed(payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
     }
 
-    fn sample_garmin_json() -> &'static str {
-        r#"{
-            "dailies": [{
-                "calendarDate": "2024-01-15",
-                "totalSteps": 8500,
-                "totalDistanceMeters": 6500,
-                "totalKilocalories": 2200,
-                "activeKilocalories": 450,
-                "restingHeartRate": 55,
-                "averageHeartRate": 68,
-                "maxHeartRate": 145,
-                "bodyBatteryChargedValue": 72,
-                "trainingLoadBalance": 45.5
-            }],
-            "sleep": [{
-                "calendarDate": "2024-01-15",
-                "sleepTimeSeconds": 25200,
-                "awakeSleepSeconds": 1800,
-                "lightSleepSeconds": 10800,
-                "deepSleepSeconds": 6300,
-                "remSleepSeconds": 6300,
-                "sleepScores": {
-                    "overallScore": 78.0
-                }
-            }]
-        }"#
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn process_raw_event_days(
+        &mut self,
+        ndjson: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let events = {
+            trace_span!(DEBUG, "parse", bytes = ndjson.len());
+            RawEventAdapter::parse_any(ndjson)?
+        };
+        self.observer
+            .on_events_parsed(PipelineKind::Wearable, events.len());
+        if let Some(order) = self.ordering.check(&events)? {
+            trace_event!(WARN, %order, "input heavily out of order");
+            self.push_warning(Warning::for_input(
+                WarningCode::OutOfOrderInput,
+                order.to_string(),
+            ));
+        }
+        let canonical_signals = RawEventAdapter::to_canonical(&events, timezone, device_id)?;
+        self.process_days(&canonical_signals)
     }
 
-    #[test]
-    fn test_whoop_to_hsi_daily() {
-        let result = whoop_to_hsi_daily(
-            sample_whoop_json().to_string(),
-            "America/New_York".to_string(),
-            "test-device".to_string(),
-        );
+    /// Process a stream of raw events, yielding HSI payloads as each day completes.
+    ///
+    /// Only open days are held in memory. By default a day completes as soon as
+    /// an event for a later day arrives, so events must arrive grouped by date
+    /// in ascending order (as in a chronological archive), and an event for an
+    /// already-completed day ends the stream with an error. Use
+    /// [`EventStream::with_allowed_lateness`] and [`EventStream::with_late_events`]
+    /// to accept out-of-order events.
+    pub fn process_event_stream<I>(
+        &mut self,
+        events: I,
+        timezone: &str,
+        device_id: &str,
+    ) -> EventStream<'_, I::IntoIter>
+    where
+        I: IntoIterator<Item = Result<RawEvent, ComputeError>>,
+    {
+        EventStream {
+            processor: self,
+            events: events.into_iter(),
+            timezone: timezone.to_string(),
+            device_id: device_id.to_string(),
+            watermark: DayWatermark::new(Duration::zero()).with_late_events(LateEventPolicy::Error),
+            pending: VecDeque::new(),
+            done: false,
+        }
+    }
 
-        assert!(result.is_ok());
-        let payloads = result.unwrap();
-        assert_eq!(payloads.len(), 1);
+    /// Register a vendor adapter under `name`, for [`Self::process_vendor`].
+    ///
+    /// Replaces any adapter already registered under that name; registering
+    /// `whoop` or `garmin` overrides the built-in adapter.
+    pub fn register_adapter(
+        &mut self,
+        name: impl Into<String>,
+        adapter: Box<dyn VendorPayloadAdapter>,
+    ) {
+        self.adapters.insert(name.into(), Arc::from(adapter));
+    }
 
-        // Verify JSON is valid and contains expected fields
-        let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
-        assert_eq!(payload["hsi_version"], "1.0.0");
-        assert_eq!(payload["producer"]["name"], "synheart-flux");
-        assert_eq!(payload["provenance"]["source_vendor"], "whoop");
+    /// Names accepted by [`Self::process_vendor`]: the built-in adapters and
+    /// every registered one
+    pub fn adapter_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.adapters.keys().map(String::as_str).collect();
+        for builtin in [Vendor::Whoop.as_str(), Vendor::Garmin.as_str()] {
+            if !self.adapters.contains_key(builtin) {
+                names.push(builtin);
+            }
+        }
+        names.sort_unstable();
+        names
+    }
 
-        // Verify sleep data
-        let sleep = &payload["windows"][0]["sleep"];
-        assert_eq!(sleep["duration_minutes"], 450.0);
-        assert!(sleep["efficiency"].as_f64().unwrap() > 0.9);
+    /// Process a payload with the adapter registered under `name` (or the
+    /// built-in `whoop` / `garmin` adapter), with persistent baselines.
+    ///
+    /// Payloads from registered adapters carry `name` as their source vendor.
+    pub fn process_vendor(
+        &mut self,
+        name: &str,
+        raw_json: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<String>, ComputeError> {
+        let result = self
+            .process_with_named_adapter(name, raw_json, timezone, device_id)
+            .and_then(|payloads| self.payloads_to_json(&payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
 
-        // Verify physiology data
-        let physiology = &payload["windows"][0]["physiology"];
-        assert_eq!(physiology["hrv_rmssd_ms"], 65.0);
-        assert_eq!(physiology["resting_hr_bpm"], 52.0);
+    /// Process a payload with the adapter registered under `name`, returning typed payloads
+    pub fn process_vendor_payloads(
+        &mut self,
+        name: &str,
+        raw_json: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let result = self
+            .process_with_named_adapter(name, raw_json, timezone, device_id)
+            .and_then(|payloads| self.sealed(payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
     }
 
-    #[test]
-    fn test_garmin_to_hsi_daily() {
-        let result = garmin_to_hsi_daily(
-            sample_garmin_json().to_string(),
-            "America/Los_Angeles".to_string(),
-            "garmin-device".to_string(),
-        );
+    fn process_with_named_adapter(
+        &mut self,
+        name: &str,
+        raw_json: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let Some(adapter) = self.adapters.get(name).cloned() else {
+            return match name {
+                "whoop" => self.process_with_adapter(&WhoopAdapter, raw_json, timezone, device_id),
+                "garmin" => {
+                    self.process_with_adapter(&GarminAdapter, raw_json, timezone, device_id)
+                }
+                _ => Err(ComputeError::UnsupportedVendor(format!(
+                    "{name} (available: {})",
+                    self.adapter_names().join(", ")
+                ))),
+            };
+        };
+        let mut payloads =
+            self.process_with_adapter(adapter.as_ref(), raw_json, timezone, device_id)?;
+        for payload in &mut payloads {
+            payload.provenance.source_vendor = name.to_string();
+        }
+        Ok(payloads)
+    }
 
-        assert!(result.is_ok());
-        let payloads = result.unwrap();
-        assert_eq!(payloads.len(), 1);
+    fn process_with_adapter(
+        &mut self,
+        adapter: &dyn VendorPayloadAdapter,
+        raw_json: &str,
+        timezone: &str,
+        device_id: &str,
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let canonical_signals = {
+            trace_span!(DEBUG, "adapt", bytes = raw_json.len());
+            adapter.parse(raw_json, timezone, device_id)?
+        };
+        trace_event!(
+            DEBUG,
+            days = canonical_signals.len(),
+            "vendor payload adapted"
+        );
+        self.process_days(&canonical_signals)
+    }
+
+    /// Process already-canonical signals with persistent baselines.
+    ///
+    /// Days are processed in the given order, so callers that need baselines
+    /// to evolve chronologically must pass them sorted by date.
+    pub fn process_canonical(
+        &mut self,
+        canonical_signals: &[CanonicalWearSignals],
+    ) -> Result<Vec<String>, ComputeError> {
+        let result = self
+            .process_days(canonical_signals)
+            .and_then(|payloads| self.payloads_to_json(&payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
+
+    /// Process canonical signals like [`Self::process_canonical`], returning typed payloads
+    pub fn process_canonical_payloads(
+        &mut self,
+        canonical_signals: &[CanonicalWearSignals],
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        let result = self
+            .process_days(canonical_signals)
+            .and_then(|payloads| self.sealed(payloads));
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
+
+    /// Process canonical signals like [`Self::process_canonical`], pairing each
+    /// payload with a trace of how its metrics were computed.
+    ///
+    /// Baselines are updated exactly as in the non-tracing entry points; for a
+    /// dry run, do not save them afterwards.
+    pub fn process_with_trace(
+        &mut self,
+        canonical_signals: &[CanonicalWearSignals],
+    ) -> Result<Vec<TracedPayload>, ComputeError> {
+        let result = canonical_signals
+            .iter()
+            .map(|canonical| {
+                let (payload, _, trace) = self.process_day(canonical, true)?;
+                Ok(TracedPayload {
+                    hsi: self.encoder.payload_to_json(&payload)?,
+                    trace: trace.expect("trace requested"),
+                })
+            })
+            .collect();
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
+
+    /// Process canonical signals like [`Self::process_canonical_payloads`],
+    /// keeping each day's intermediate signals for inspection.
+    ///
+    /// Baselines are updated exactly as in the other entry points.
+    pub fn process_with_stages(
+        &mut self,
+        canonical_signals: &[CanonicalWearSignals],
+    ) -> Result<Vec<StagedWindow<ContextualSignals, HsiPayload>>, ComputeError> {
+        let result = canonical_signals
+            .iter()
+            .map(|canonical| {
+                let (mut hsi, contextual, _) = self.process_day(canonical, false)?;
+                self.encoder.seal(&mut hsi)?;
+                Ok(StagedWindow { contextual, hsi })
+            })
+            .collect();
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
+
+    /// Run canonical days through the pipeline, reporting each to the observer
+    fn process_days(
+        &mut self,
+        canonical_signals: &[CanonicalWearSignals],
+    ) -> Result<Vec<HsiPayload>, ComputeError> {
+        canonical_signals
+            .iter()
+            .map(|canonical| Ok(self.process_day(canonical, false)?.0))
+            .collect()
+    }
+
+    /// Run one canonical day through the pipeline, optionally tracing its metrics
+    fn process_day(
+        &mut self,
+        canonical: &CanonicalWearSignals,
+        with_trace: bool,
+    ) -> Result<(HsiPayload, ContextualSignals, Option<DayTrace>), ComputeError> {
+        trace_span!(
+            INFO,
+            "day",
+            date = %canonical.date,
+            vendor = canonical.vendor.as_str()
+        );
+        let started = Instant::now();
+        // Each user's days are baselined separately
+        let user_id = canonical.user_id.as_deref();
+        let store = self.baseline_store.user_store_mut(user_id);
+        if let Some(last) = store.last_date() {
+            if canonical.date.as_str() < last {
+                return Err(ComputeError::OutOfOrder(format!(
+                    "{} is older than the last processed day {last}; use reprocess_day to amend it",
+                    canonical.date
+                )));
+            }
+        }
+        // A UTC offset change since the previous day (travel or DST) distorts
+        // wall-clock durations. Only offsets from event context are compared,
+        // so days that fell back to the default timezone never flag a shift.
+        // The last offset is saved with the baselines.
+        let shifted = canonical
+            .utc_offset_minutes
+            .zip(store.last_utc_offset_minutes())
+            .is_some_and(|(offset, last)| offset != last);
+        if let Some(offset) = canonical.utc_offset_minutes {
+            store.set_last_utc_offset_minutes(offset);
+        }
+        let mut canonical = self
+            .calibrate_hrv(canonical)
+            .unwrap_or_else(|| canonical.clone());
+        self.pin_clock(&mut canonical);
+        let canonical = &canonical;
+
+        let normalized = {
+            trace_span!(DEBUG, "normalize");
+            let history = self.score_history(&canonical.date, user_id);
+            if shifted {
+                let mut normalized = Normalizer::normalize_with_config(
+                    &Normalizer::adjust_for_timezone_shift(canonical),
+                    &self.normalization,
+                    &history,
+                );
+                normalized.quality_flags.push(QualityFlag::TimezoneShift);
+                normalized
+            } else {
+                Normalizer::normalize_with_config(canonical, &self.normalization, &history)
+            }
+        };
+        trace_event!(
+            DEBUG,
+            coverage = normalized.coverage,
+            flags = ?normalized.quality_flags,
+            "day normalized"
+        );
+        for flag in &normalized.quality_flags {
+            self.observer
+                .on_quality_flag(PipelineKind::Wearable, &canonical.date, flag.as_str());
+        }
+        self.record_warnings(&normalized);
+        let coverage = normalized.coverage;
+        let derived = {
+            trace_span!(DEBUG, "derive");
+            FeatureDeriver::derive_with_config(normalized, &self.features)
+        };
+        let prior = with_trace.then(|| {
+            let canonical = &derived.normalized.canonical;
+            self.baseline_store
+                .user_store_mut(user_id)
+                .baselines_for_day(&canonical.tags, canonical.recovery.hrv_scale)
+        });
+        let contextual = {
+            trace_span!(DEBUG, "baseline");
+            self.baseline_store
+                .user_store_mut(user_id)
+                .update_and_contextualize(derived)
+        };
+        let payload = {
+            trace_span!(DEBUG, "encode");
+            self.encode_day(&contextual)?
+        };
+        self.record_rollup_days(&payload)?;
+        self.observer.on_day_processed(&ProcessedWindow {
+            pipeline: PipelineKind::Wearable,
+            id: &canonical.date,
+            coverage,
+            elapsed: started.elapsed(),
+        });
+
+        let trace = prior.map(|prior| {
+            let mut trace = explain::explain(&contextual, &prior);
+            let config = self.encoder.encoder_config();
+            trace
+                .metrics
+                .retain(|m| m.metric.starts_with("quality.") || config.allows(&m.metric));
+            trace
+        });
+        Ok((payload, contextual, trace))
+    }
+
+    /// Encode a day, adding the metrics of registered feature stages
+    fn encode_day(&self, contextual: &ContextualSignals) -> Result<HsiPayload, ComputeError> {
+        let mut payload = self.encoder.encode(contextual)?;
+        if self.encoder.encoder_config().suppress_meta {
+            return Ok(payload);
+        }
+        if let Some(metrics) =
+            features::derive_stages(&self.feature_stages, &contextual.derived.normalized)
+        {
+            payload.meta.get_or_insert_with(Default::default).insert(
+                features::FEATURE_STAGES_META_KEY.to_string(),
+                serde_json::to_value(metrics)?,
+            );
+        }
+        Ok(payload)
+    }
+
+    /// Serialize payloads for the JSON entry points
+    fn payloads_to_json(&self, payloads: &[HsiPayload]) -> Result<Vec<String>, ComputeError> {
+        payloads
+            .iter()
+            .map(|p| self.encoder.payload_to_json(p))
+            .collect()
+    }
+
+    /// Seal payloads for the typed entry points
+    fn sealed(&self, mut payloads: Vec<HsiPayload>) -> Result<Vec<HsiPayload>, ComputeError> {
+        for payload in &mut payloads {
+            self.encoder.seal(payload)?;
+        }
+        Ok(payloads)
+    }
+
+    /// Amend an already-emitted day with corrected or late-arriving events.
+    ///
+    /// The day's baseline entries are replaced rather than appended, and the
+    /// payload carries an incrementing `revision` in its provenance. Device ID
+    /// is taken from the events (defaulting to `unknown`); `timezone` applies
+    /// when the events carry none, as in [`Self::process_raw_events`].
+    pub fn reprocess_day(
+        &mut self,
+        date: &str,
+        events: &[RawEvent],
+        timezone: &str,
+    ) -> Result<Vec<String>, ComputeError> {
+        let result = self.reprocess_events(date, events, timezone);
+        observe(self.observer.as_ref(), PipelineKind::Wearable, result)
+    }
+
+    fn reprocess_events(
+        &mut self,
+        date: &str,
+        events: &[RawEvent],
+        timezone: &str,
+    ) -> Result<Vec<String>, ComputeError> {
+        let device_id = events
+            .iter()
+            .find_map(|e| e.source.device_id.as_deref())
+            .unwrap_or("unknown");
+        let canonical_signals: Vec<_> = RawEventAdapter::to_canonical(events, timezone, device_id)?
+            .into_iter()
+            .filter(|c| c.date == date)
+            .collect();
+
+        if canonical_signals.is_empty() {
+            return Err(ComputeError::NoDataForDate(date.to_string()));
+        }
+
+        let mut revisions = BTreeMap::new();
+        let mut hsi_payloads = Vec::new();
+
+        for canonical in &canonical_signals {
+            let user_id = canonical.user_id.as_deref();
+            let revision = *revisions.entry(user_id).or_insert_with(|| {
+                self.baseline_store
+                    .user_store_mut(user_id)
+                    .next_revision(date)
+            });
+            let mut canonical = self
+                .calibrate_hrv(canonical)
+                .unwrap_or_else(|| canonical.clone());
+            self.pin_clock(&mut canonical);
+            let history = self.score_history(&canonical.date, user_id);
+            let normalized =
+                Normalizer::normalize_with_config(&canonical, &self.normalization, &history);
+            self.record_warnings(&normalized);
+            let derived = FeatureDeriver::derive_with_config(normalized, &self.features);
+            let contextual = self
+                .baseline_store
+                .user_store_mut(user_id)
+                .replace_and_contextualize(derived);
+            let mut payload = self.encode_day(&contextual)?;
+            payload.provenance.revision = Some(revision);
+            self.record_rollup_days(&payload)?;
+            hsi_payloads.push(self.encoder.payload_to_json(&payload)?);
+        }
+
+        Ok(hsi_payloads)
+    }
+
+    /// Take the warnings raised since the last call, oldest first.
+    ///
+    /// Warnings report input that was adjusted or left out without failing
+    /// processing. Up to [`MAX_PENDING_WARNINGS`] are held until taken.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        self.warnings.drain(..).collect()
+    }
+
+    /// Pair the payloads of a processing call with the warnings it raised
+    ///
+    /// ```ignore
+    /// let payloads = processor.process_raw_events(ndjson, "UTC", "watch-1")?;
+    /// let output = processor.take_output(payloads);
+    /// ```
+    pub fn take_output<P>(&mut self, payloads: Vec<P>) -> ProcessOutput<P> {
+        ProcessOutput {
+            payloads,
+            warnings: self.take_warnings(),
+        }
+    }
+
+    /// Summarize emitted days by week or month over `range`.
+    ///
+    /// Covers the last [`ROLLUP_HISTORY_DAYS`] days emitted by this processor;
+    /// the history is not part of saved baselines.
+    pub fn rollup(&self, period: RollupPeriod, range: RangeInclusive<NaiveDate>) -> Vec<HsiRollup> {
+        if range.is_empty() {
+            return Vec::new();
+        }
+        let days: Vec<RollupDay> = self
+            .rollup_days
+            .range(range.clone())
+            .map(|(_, d)| d.clone())
+            .collect();
+        rollup::rollup_days(&days, period, Some(range), &self.encoder.producer())
+    }
+
+    /// Keep an emitted payload's days for rollups, replacing earlier revisions
+    fn record_rollup_days(&mut self, payload: &HsiPayload) -> Result<(), ComputeError> {
+        for day in RollupDay::from_payload(payload)? {
+            self.rollup_days.insert(day.date, day);
+        }
+        while self.rollup_days.len() > ROLLUP_HISTORY_DAYS {
+            self.rollup_days.pop_first();
+        }
+        Ok(())
+    }
+
+    /// Record a day's HRV and map it onto the reference scale, when calibration is on
+    fn calibrate_hrv(&mut self, canonical: &CanonicalWearSignals) -> Option<CanonicalWearSignals> {
+        let config = self.hrv_calibration.as_ref()?;
+        self.baseline_store
+            .record_hrv_overlaps(std::slice::from_ref(canonical));
+        Some(config.calibrate(canonical, self.baseline_store.hrv_overlaps()))
+    }
+
+    /// Past scores of a user for quantile normalization (skipped when no score uses it)
+    fn score_history(&self, date: &str, user_id: Option<&str>) -> ScoreHistory {
+        let strategies = [
+            self.normalization.sleep,
+            self.normalization.recovery,
+            self.normalization.strain,
+        ];
+        if strategies.contains(&ScoreNormalization::Quantile) {
+            self.baseline_store
+                .user_store(user_id)
+                .map(|store| store.score_history_before(date))
+                .unwrap_or_default()
+        } else {
+            ScoreHistory::default()
+        }
+    }
+
+    /// Keep a day's warnings: those raised building it, plus implausible values
+    fn record_warnings(&mut self, normalized: &NormalizedSignals) {
+        let canonical = &normalized.canonical;
+        for warning in &canonical.warnings {
+            self.push_warning(warning.clone());
+        }
+        if normalized
+            .quality_flags
+            .contains(&QualityFlag::ImplausibleValue)
+        {
+            self.push_warning(Warning::for_day(
+                WarningCode::ImplausibleValue,
+                &canonical.date,
+                "implausible HRV, heart rate, SpO2 or sleep duration dropped or capped",
+            ));
+        }
+        if normalized
+            .quality_flags
+            .contains(&QualityFlag::InconsistentCalories)
+        {
+            let activity = &canonical.activity;
+            self.push_warning(Warning::for_day(
+                WarningCode::InconsistentCalories,
+                &canonical.date,
+                format!(
+                    "total calories {:.0} differ from active {:.0} plus resting {:.0}",
+                    activity.calories.unwrap_or_default(),
+                    activity.active_calories.unwrap_or_default(),
+                    activity.resting_calories.unwrap_or_default(),
+                ),
+            ));
+        }
+    }
+
+    fn push_warning(&mut self, warning: Warning) {
+        trace_event!(DEBUG, %warning, "warning");
+        if self.warnings.len() == MAX_PENDING_WARNINGS {
+            self.warnings.pop_front();
+        }
+        self.warnings.push_back(warning);
+    }
+
+    /// Ingest time is a wall-clock read too; pin it in deterministic mode,
+    /// before normalization checks it against the end of the day
+    fn pin_clock(&self, canonical: &mut CanonicalWearSignals) {
+        if let Some(clock) = self.encoder.fixed_computed_at() {
+            canonical.observed_at = clock;
+        }
+    }
+}
+
+/// Iterator returned by [`FluxProcessor::process_event_stream`]
+pub struct EventStream<'a, I> {
+    processor: &'a mut FluxProcessor,
+    events: I,
+    timezone: String,
+    device_id: String,
+    watermark: DayWatermark,
+    pending: VecDeque<String>,
+    done: bool,
+}
+
+impl<I> EventStream<'_, I> {
+    /// Keep each day open until events are `allowed_lateness` past its end
+    pub fn with_allowed_lateness(mut self, allowed_lateness: Duration) -> Self {
+        self.watermark = self.watermark.with_allowed_lateness(allowed_lateness);
+        self
+    }
+
+    /// Set how events for already-completed days are handled
+    pub fn with_late_events(mut self, policy: LateEventPolicy) -> Self {
+        self.watermark = self.watermark.with_late_events(policy);
+        self
+    }
+
+    /// Number of events that arrived after their day completed
+    pub fn late_events(&self) -> usize {
+        self.watermark.late_events()
+    }
+
+    fn flush_days(&mut self, days: Vec<CompletedDay>) -> Result<(), ComputeError> {
+        for day in days {
+            self.processor
+                .observer
+                .on_events_parsed(PipelineKind::Wearable, day.events.len());
+            if day.amended {
+                let payloads =
+                    self.processor
+                        .reprocess_events(&day.date, &day.events, &self.timezone)?;
+                self.pending.extend(payloads);
+                continue;
+            }
+            let canonical =
+                RawEventAdapter::to_canonical(&day.events, &self.timezone, &self.device_id)?;
+            let payloads = self.processor.process_days(&canonical)?;
+            self.pending
+                .extend(self.processor.payloads_to_json(&payloads)?);
+        }
+        Ok(())
+    }
+}
+
+impl<I> Iterator for EventStream<'_, I>
+where
+    I: Iterator<Item = Result<RawEvent, ComputeError>>,
+{
+    type Item = Result<String, ComputeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(payload) = self.pending.pop_front() {
+                return Some(Ok(payload));
+            }
+            if self.done {
+                return None;
+            }
+
+            let result = match self.events.next() {
+                Some(Ok(event)) => match self.watermark.push(event) {
+                    Ok(pushed) => self.flush_days(pushed.completed),
+                    Err(e) => Err(e),
+                },
+                Some(Err(e)) => Err(e),
+                None => {
+                    self.done = true;
+                    let days = self.watermark.finish();
+                    self.flush_days(days)
+                }
+            };
+
+            if let Err(e) = result {
+                self.done = true;
+                self.processor.observer.on_error(PipelineKind::Wearable, &e);
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::TagPolicy;
+    use crate::encoder::SourceEvents;
+    use crate::types::Vendor;
+
+    fn sample_whoop_json() -> &'static str {
+        r#"{
+            "sleep": [{
+                "id": 1,
+                "start": "2024-01-15T22:30:00.000Z",
+                "end": "2024-01-16T06:30:00.000Z",
+                "score": {
+                    "stage_summary": {
+                        "total_in_bed_time_milli": 28800000,
+                        "total_awake_time_milli": 1800000,
+                        "total_light_sleep_time_milli": 12600000,
+                        "total_slow_wave_sleep_time_milli": 7200000,
+                        "total_rem_sleep_time_milli": 7200000,
+                        "total_sleep_time_milli": 27000000,
+                        "disturbance_count": 3
+                    },
+                    "sleep_performance_percentage": 85.0,
+                    "sleep_efficiency_percentage": 93.75,
+                    "respiratory_rate": 14.5
+                }
+            }],
+            "recovery": [{
+                "cycle_id": 1,
+                "created_at": "2024-01-15T06:30:00.000Z",
+                "score": {
+                    "recovery_score": 75.0,
+                    "resting_heart_rate": 52.0,
+                    "hrv_rmssd_milli": 65.0,
+                    "spo2_percentage": 97.0
+                }
+            }],
+            "cycle": [{
+                "id": 1,
+                "start": "2024-01-15T06:30:00.000Z",
+                "end": "2024-01-15T22:30:00.000Z",
+                "score": {
+                    "strain": 12.5,
+                    "kilojoule": 8500.0,
+                    "average_heart_rate": 72.0,
+                    "max_heart_rate": 165.0
+                }
+            }]
+        }"#
+    }
+
+    fn sample_garmin_json() -> &'static str {
+        r#"{
+            "dailies": [{
+                "calendarDate": "2024-01-15",
+                "totalSteps": 8500,
+                "totalDistanceMeters": 6500,
+                "totalKilocalories": 2200,
+                "activeKilocalories": 450,
+                "restingHeartRate": 55,
+                "averageHeartRate": 68,
+                "maxHeartRate": 145,
+                "bodyBatteryChargedValue": 72,
+                "trainingLoadBalance": 45.5
+            }],
+            "sleep": [{
+                "calendarDate": "2024-01-15",
+                "sleepTimeSeconds": 25200,
+                "awakeSleepSeconds": 1800,
+                "lightSleepSeconds": 10800,
+                "deepSleepSeconds": 6300,
+                "remSleepSeconds": 6300,
+                "sleepScores": {
+                    "overallScore": 78.0
+                }
+            }]
+        }"#
+    }
+
+    #[test]
+    fn test_whoop_to_hsi_daily() {
+        let result = whoop_to_hsi_daily(
+            sample_whoop_json().to_string(),
+            "America/New_York".to_string(),
+            "test-device".to_string(),
+        );
+
+        assert!(result.is_ok());
+        let payloads = result.unwrap();
+        assert_eq!(payloads.len(), 1);
+
+        // Verify JSON is valid and contains expected fields
+        let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
+        assert_eq!(payload["hsi_version"], "1.0.0");
+        assert_eq!(payload["producer"]["name"], "synheart-flux");
+        assert_eq!(payload["provenance"]["source_vendor"], "whoop");
+
+        // Verify sleep data
+        let sleep = &payload["windows"][0]["sleep"];
+        assert_eq!(sleep["duration_minutes"], 450.0);
+        assert!(sleep["efficiency"].as_f64().unwrap() > 0.9);
+
+        // Verify physiology data
+        let physiology = &payload["windows"][0]["physiology"];
+        assert_eq!(physiology["hrv_rmssd_ms"], 65.0);
+        assert_eq!(physiology["resting_hr_bpm"], 52.0);
+    }
+
+    #[test]
+    fn test_garmin_to_hsi_daily() {
+        let result = garmin_to_hsi_daily(
+            sample_garmin_json().to_string(),
+            "America/Los_Angeles".to_string(),
+            "garmin-device".to_string(),
+        );
+
+        assert!(result.is_ok());
+        let payloads = result.unwrap();
+        assert_eq!(payloads.len(), 1);
 
         let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
         assert_eq!(payload["provenance"]["source_vendor"], "garmin");
@@ -349,6 +1396,137 @@ mod tests {
         assert_eq!(baseline["days_in_baseline"], 2);
     }
 
+    #[test]
+    fn test_user_id_propagated_only_when_policy_opts_in() {
+        use crate::privacy::{ScrubMode, Scrubber};
+
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"garmin","device_id":"watch-1"},"user_id":"athlete-7","record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":60,"unit":"ms"}}}"#;
+
+        let payloads = FluxProcessor::new()
+            .process_raw_events(ndjson, "UTC", "watch-1")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
+        assert!(payload["provenance"].get("source_user_id").is_none());
+
+        let scrubber = Scrubber::new(ScrubMode::Hash).with_salt("tenant-a");
+        let policy = PrivacyPolicy::default()
+            .with_scrubber(scrubber.clone())
+            .with_user_id_included(true);
+        let payloads = FluxProcessor::new()
+            .with_privacy_policy(policy.clone())
+            .process_raw_events(ndjson, "UTC", "watch-1")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
+        assert_eq!(
+            payload["provenance"]["source_user_id"],
+            scrubber.pseudonym("athlete-7")
+        );
+
+        // Two users on the same day and provider stay separate days
+        let two_users = format!("{ndjson}\n{}", ndjson.replace("athlete-7", "athlete-9"));
+        let payloads = FluxProcessor::new()
+            .with_privacy_policy(policy.clone())
+            .process_raw_events(&two_users, "UTC", "watch-1")
+            .unwrap();
+        let users: Vec<String> = payloads
+            .iter()
+            .map(|p| serde_json::from_str::<serde_json::Value>(p).unwrap())
+            .map(|p| {
+                p["provenance"]["source_user_id"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        let expected = [
+            scrubber.pseudonym("athlete-7"),
+            scrubber.pseudonym("athlete-9"),
+        ];
+        assert_eq!(users.len(), 2);
+        assert_eq!(
+            users.iter().collect::<std::collections::BTreeSet<_>>(),
+            expected.iter().collect()
+        );
+
+        // A scrubber in drop mode wins over the opt-in
+        let payloads = FluxProcessor::new()
+            .with_privacy_policy(policy.with_scrubber(Scrubber::new(ScrubMode::Drop)))
+            .process_raw_events(ndjson, "UTC", "watch-1")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
+        assert!(payload["provenance"].get("source_user_id").is_none());
+    }
+
+    #[test]
+    fn test_users_have_separate_baselines() {
+        let hrv = |date: &str, user: &str, value: f64| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{date}T07:00:00Z","source":{{"provider":"garmin","device_id":"watch-1"}},"user_id":"{user}","record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":{value},"unit":"ms"}}}}}}"#
+            )
+        };
+        let ndjson = [
+            hrv("2024-01-15", "athlete-7", 60.0),
+            hrv("2024-01-15", "athlete-9", 30.0),
+            hrv("2024-01-16", "athlete-7", 66.0),
+            hrv("2024-01-16", "athlete-9", 33.0),
+        ]
+        .join("\n");
+
+        let mut processor = FluxProcessor::new();
+        let payloads = processor
+            .process_raw_events(&ndjson, "UTC", "watch-1")
+            .unwrap();
+        assert_eq!(payloads.len(), 4);
+
+        // Each user's second day is compared only with their own first day
+        for payload in &payloads[2..] {
+            let payload: serde_json::Value = serde_json::from_str(payload).unwrap();
+            let baseline = &payload["windows"][0]["baseline"];
+            assert_eq!(baseline["days_in_baseline"], 2);
+            assert!((baseline["hrv_deviation_pct"].as_f64().unwrap() - 10.0).abs() < 1e-9);
+        }
+
+        let saved = processor.save_baselines().unwrap();
+        let restored = BaselineStore::from_json(&saved).unwrap();
+        assert_eq!(restored.days().count(), 0);
+        for (user, mean) in [("athlete-7", 63.0), ("athlete-9", 31.5)] {
+            let store = restored.user_store(Some(user)).unwrap();
+            assert_eq!(store.days().count(), 2);
+            assert_eq!(store.get_baselines().hrv_baseline_ms, Some(mean));
+        }
+    }
+
+    #[test]
+    fn test_instance_id_persisted_in_baselines() {
+        use crate::encoder::device_instance_id;
+
+        let instance_id = device_instance_id("test-device");
+        assert_eq!(instance_id, device_instance_id("test-device"));
+        assert_ne!(instance_id, device_instance_id("other-device"));
+
+        let mut processor = FluxProcessor::new().with_device_instance_id("test-device");
+        processor
+            .process_whoop(sample_whoop_json(), "UTC", "test-device")
+            .unwrap();
+        let saved = processor.save_baselines().unwrap();
+
+        // A restarted processor picks the ID up from the saved baselines
+        let mut restarted = FluxProcessor::new();
+        restarted.load_baselines(&saved).unwrap();
+        let results = restarted
+            .process_whoop(sample_whoop_json(), "UTC", "test-device")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&results[0]).unwrap();
+        assert_eq!(payload["producer"]["instance_id"], instance_id.as_str());
+
+        // A store saved without one keeps the pinned ID
+        let mut pinned = FluxProcessor::new().with_instance_id("node-7");
+        pinned
+            .load_baselines(&FluxProcessor::new().save_baselines().unwrap())
+            .unwrap();
+        assert!(pinned.save_baselines().unwrap().contains("node-7"));
+    }
+
     #[test]
     fn test_baseline_serialization() {
         let mut processor = FluxProcessor::new();
@@ -377,6 +1555,605 @@ mod tests {
         assert_eq!(baseline["days_in_baseline"], 2);
     }
 
+    #[test]
+    fn test_process_canonical_from_raw_events() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":55,"unit":"bpm"}}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-16T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":57,"unit":"bpm"}}}"#;
+        let events = crate::schema::RawEventAdapter::parse_ndjson(ndjson).unwrap();
+        let canonical =
+            crate::schema::RawEventAdapter::to_canonical(&events, "UTC", "device").unwrap();
+
+        let mut processor = FluxProcessor::new();
+        let result = processor.process_canonical(&canonical).unwrap();
+        assert_eq!(result.len(), 2);
+
+        let payload: serde_json::Value = serde_json::from_str(&result[1]).unwrap();
+        assert_eq!(payload["windows"][0]["date"], "2024-01-16");
+        assert_eq!(payload["windows"][0]["baseline"]["days_in_baseline"], 2);
+    }
+
+    #[test]
+    fn test_deterministic_output_is_byte_identical() {
+        let computed_at = DateTime::parse_from_rfc3339("2024-02-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let run = || {
+            let mut processor = FluxProcessor::new();
+            processor.set_deterministic(1, computed_at);
+            processor
+                .process_whoop(sample_whoop_json(), "UTC", "test-device")
+                .unwrap()
+        };
+
+        let first = run();
+        assert_eq!(first, run());
+
+        let payload: serde_json::Value = serde_json::from_str(&first[0]).unwrap();
+        // Measured from the newest record (sleep ending 2024-01-16T06:30Z), not the wall clock
+        assert_eq!(payload["quality"]["freshness_sec"], 1_359_000);
+    }
+
+    #[test]
+    fn test_producer_override_survives_deterministic_mode() {
+        use crate::encoder::{ProducerIdentity, FLUX_VERSION_META_KEY};
+
+        let producer = ProducerIdentity::new("acme-health", "3.2.0").with_instance_id("node-7");
+        let mut processor = FluxProcessor::new().with_producer(producer);
+        processor.set_deterministic(1, Utc::now());
+        let results = processor
+            .process_whoop(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+
+        let payload: serde_json::Value = serde_json::from_str(&results[0]).unwrap();
+        assert_eq!(payload["producer"]["name"], "acme-health");
+        assert_eq!(payload["producer"]["version"], "3.2.0");
+        assert_eq!(payload["producer"]["instance_id"], "node-7");
+        assert_eq!(payload["meta"][FLUX_VERSION_META_KEY], crate::FLUX_VERSION);
+
+        let results = FluxProcessor::new()
+            .process_whoop(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&results[0]).unwrap();
+        assert!(payload.get("meta").is_none());
+    }
+
+    #[test]
+    fn test_partial_day_uses_pinned_clock_and_local_day_end() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T20:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"steps","value":9000,"unit":"count"}}}"#;
+        let partial = |computed_at: &str| {
+            let mut processor = FluxProcessor::new();
+            processor.set_deterministic(1, computed_at.parse().unwrap());
+            let payloads = processor
+                .process_raw_events(ndjson, "America/Los_Angeles", "dev")
+                .unwrap();
+            let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
+            payload["quality"]["flags"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|f| f == "partial_day")
+        };
+
+        // Past UTC midnight, but still the 15th in Los Angeles
+        assert!(partial("2024-01-16T06:00:00Z"));
+        assert!(!partial("2024-01-16T09:00:00Z"));
+    }
+
+    #[test]
+    fn test_freshness_per_domain_and_stale_flag() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"whoop"},"record_type":"session","payload":{"session":{"type":"sleep","start_time":"2024-01-14T23:00:00Z","end_time":"2024-01-15T07:00:00Z","metrics":{"total_sleep_minutes":450}}}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T20:00:00Z","source":{"provider":"whoop"},"record_type":"signal","payload":{"signal":{"type":"steps","value":9000,"unit":"count"}}}"#;
+        let quality = |computed_at: &str, config: EncoderConfig| {
+            let mut processor = FluxProcessor::new().with_encoder_config(config);
+            processor.set_deterministic(1, computed_at.parse().unwrap());
+            let payloads = processor.process_raw_events(ndjson, "UTC", "dev").unwrap();
+            let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
+            payload["quality"].clone()
+        };
+
+        let quality_next_day = quality("2024-01-16T08:00:00Z", EncoderConfig::default());
+        assert_eq!(quality_next_day["freshness_sec"], 12 * 3600);
+        assert_eq!(quality_next_day["domain_freshness_sec"]["sleep"], 25 * 3600);
+        assert_eq!(
+            quality_next_day["domain_freshness_sec"]["activity"],
+            12 * 3600
+        );
+        assert!(!quality_next_day["flags"].to_string().contains("stale_data"));
+
+        // The sleep is now past the 36 h default, even though steps are not
+        let quality_later = quality("2024-01-17T08:00:00Z", EncoderConfig::default());
+        assert!(quality_later["flags"].to_string().contains("stale_data"));
+        let unflagged = quality(
+            "2024-01-17T08:00:00Z",
+            EncoderConfig::default().with_stale_after_sec(None),
+        );
+        assert!(!unflagged["flags"].to_string().contains("stale_data"));
+    }
+
+    #[test]
+    fn test_timezone_shift_is_flagged() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":55,"unit":"bpm"}},"context":{"timezone":"America/New_York"}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-16T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":57,"unit":"bpm"}},"context":{"timezone":"Europe/London"}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-17T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":56,"unit":"bpm"}},"context":{"timezone":"Europe/London"}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-18T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":56,"unit":"bpm"}}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-19T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":56,"unit":"bpm"}},"context":{"timezone":"Europe/London"}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-03-30T12:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":56,"unit":"bpm"}},"context":{"timezone":"Europe/London"}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-03-31T12:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":56,"unit":"bpm"}},"context":{"timezone":"Europe/London"}}"#;
+        let events = crate::schema::RawEventAdapter::parse_ndjson(ndjson).unwrap();
+        // Days without event context fall back to a different default zone
+        let canonical =
+            crate::schema::RawEventAdapter::to_canonical(&events, "America/Los_Angeles", "device")
+                .unwrap();
+        assert_eq!(canonical[3].utc_offset_minutes, None);
+        assert_eq!(canonical[6].utc_offset_minutes, Some(60));
+
+        let mut processor = FluxProcessor::new();
+        let result = processor.process_canonical(&canonical).unwrap();
+
+        let flags = |i: usize| {
+            let payload: serde_json::Value = serde_json::from_str(&result[i]).unwrap();
+            assert!(payload["windows"][0]["timezone"].is_string());
+            payload["quality"]["flags"].clone()
+        };
+        let has_shift = |i: usize| {
+            flags(i)
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|f| f == "timezone_shift")
+        };
+
+        assert!(!has_shift(0));
+        assert!(has_shift(1));
+        assert!(!has_shift(2));
+        // No event context: no offset to compare, before or after
+        assert!(!has_shift(3));
+        assert!(!has_shift(4));
+        assert!(!has_shift(5));
+        // Same zone name, but British Summer Time started overnight
+        assert!(has_shift(6));
+
+        // A restarted processor still compares against the saved offset
+        let mut processor = FluxProcessor::new();
+        processor.process_canonical(&canonical[..1]).unwrap();
+        let saved = processor.save_baselines().unwrap();
+        let mut restarted = FluxProcessor::new();
+        restarted.load_baselines(&saved).unwrap();
+        let result = restarted.process_canonical(&canonical[1..2]).unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&result[0]).unwrap();
+        assert!(payload["quality"]["flags"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|f| f == "timezone_shift"));
+    }
+
+    #[test]
+    fn test_fused_day_has_field_provenance() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"whoop"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":65,"unit":"ms"}}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T20:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"steps","value":9000,"unit":"count"}}}"#;
+        let events = crate::schema::RawEventAdapter::parse_ndjson(ndjson).unwrap();
+        let canonical =
+            crate::schema::RawEventAdapter::to_canonical(&events, "UTC", "device").unwrap();
+        assert_eq!(canonical.len(), 2);
+
+        let mut processor = FluxProcessor::new();
+        let result = processor
+            .process_canonical(&DeviceFusion::new().fuse(&canonical))
+            .unwrap();
+        assert_eq!(result.len(), 1);
+
+        let payload: serde_json::Value = serde_json::from_str(&result[0]).unwrap();
+        assert_eq!(payload["provenance"]["source_vendor"], "garmin+whoop");
+        assert_eq!(
+            payload["provenance"]["field_sources"]["hrv_rmssd_ms"],
+            "whoop"
+        );
+        assert_eq!(payload["provenance"]["field_sources"]["steps"], "garmin");
+        assert_eq!(payload["windows"][0]["activity"]["steps"], 9000);
+    }
+
+    #[test]
+    fn test_reprocess_day_replaces_baseline_entry() {
+        let event = |date: &str, hrv: f64| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{date}T07:00:00Z","source":{{"provider":"garmin","device_id":"watch-1"}},"record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":{hrv},"unit":"ms"}}}}}}"#
+            )
+        };
+        let ndjson = [event("2024-01-15", 60.0), event("2024-01-16", 0.5)].join("\n");
+        let events = RawEventAdapter::parse_ndjson(&ndjson).unwrap();
+        let canonical = RawEventAdapter::to_canonical(&events, "UTC", "watch-1").unwrap();
+
+        let mut processor = FluxProcessor::new();
+        processor.process_canonical(&canonical).unwrap();
+
+        // Late-arriving correction for 2024-01-16
+        let amended = RawEventAdapter::parse_ndjson(&event("2024-01-16", 62.0)).unwrap();
+        let result = processor
+            .reprocess_day("2024-01-16", &amended, "Europe/Berlin")
+            .unwrap();
+        assert_eq!(result.len(), 1);
+
+        let payload: serde_json::Value = serde_json::from_str(&result[0]).unwrap();
+        assert_eq!(payload["windows"][0]["timezone"], "Europe/Berlin");
+        assert_eq!(payload["provenance"]["revision"], 1);
+        assert_eq!(payload["provenance"]["source_device_id"], "watch-1");
+        assert_eq!(payload["windows"][0]["physiology"]["hrv_rmssd_ms"], 62.0);
+        assert_eq!(payload["windows"][0]["baseline"]["days_in_baseline"], 2);
+        assert_eq!(payload["windows"][0]["baseline"]["hrv_ms"], 61.0);
+
+        let again = processor
+            .reprocess_day("2024-01-16", &amended, "UTC")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&again[0]).unwrap();
+        assert_eq!(payload["provenance"]["revision"], 2);
+
+        assert!(matches!(
+            processor.reprocess_day("2024-02-01", &amended, "UTC"),
+            Err(ComputeError::NoDataForDate(_))
+        ));
+    }
+
+    #[test]
+    fn test_process_event_stream_matches_batch() {
+        let event = |ts: &str, hrv: f64| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{ts}","source":{{"provider":"garmin"}},"record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":{hrv},"unit":"ms"}}}}}}"#
+            )
+        };
+        let ndjson = [
+            event("2024-01-15T07:00:00Z", 60.0),
+            event("2024-01-15T19:00:00Z", 64.0),
+            event("2024-01-16T07:00:00Z", 70.0),
+            event("2024-01-17T07:00:00Z", 55.0),
+        ]
+        .join("\n");
+
+        let mut batch = FluxProcessor::new();
+        batch.set_deterministic(1, DateTime::UNIX_EPOCH);
+        let expected = batch.process_raw_events(&ndjson, "UTC", "dev").unwrap();
+
+        let mut streaming = FluxProcessor::new();
+        streaming.set_deterministic(1, DateTime::UNIX_EPOCH);
+        let streamed: Vec<String> = streaming
+            .process_event_stream(
+                RawEventAdapter::iter_ndjson(ndjson.as_bytes()),
+                "UTC",
+                "dev",
+            )
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(streamed, expected);
+        assert_eq!(streamed.len(), 3);
+    }
+
+    #[test]
+    fn test_process_event_stream_rejects_completed_day() {
+        let events = RawEventAdapter::parse_ndjson(concat!(
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-16T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":60.0,"unit":"ms"}}}"#,
+            "\n",
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-17T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":60.0,"unit":"ms"}}}"#,
+            "\n",
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":60.0,"unit":"ms"}}}"#,
+        ))
+        .unwrap();
+
+        let mut processor = FluxProcessor::new();
+        let results: Vec<_> = processor
+            .process_event_stream(events.into_iter().map(Ok), "UTC", "dev")
+            .collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+
+    #[test]
+    fn test_process_with_stages_matches_payloads() {
+        let days = WhoopAdapter
+            .parse(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        let staged = FluxProcessor::new().process_with_stages(&days).unwrap();
+        let payloads = FluxProcessor::new()
+            .process_canonical_payloads(&days)
+            .unwrap();
+        assert_eq!(staged.len(), payloads.len());
+
+        let window = &staged[0];
+        let derived = &window.contextual.derived;
+        assert_eq!(derived.normalized.canonical.date, "2024-01-15");
+        assert_eq!(derived.sleep_efficiency, Some(0.9375));
+        assert_eq!(
+            window.hsi.windows[0].sleep.efficiency,
+            payloads[0].windows[0].sleep.efficiency
+        );
+    }
+
+    #[test]
+    fn test_canonical_round_trip() {
+        let days = WhoopAdapter
+            .parse(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        let json = serde_json::to_string(&days).unwrap();
+        let parsed: Vec<CanonicalWearSignals> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&days).unwrap()
+        );
+
+        let now = Utc::now();
+        let encode = |days: &[CanonicalWearSignals]| {
+            let mut processor = FluxProcessor::new();
+            processor.set_deterministic(7, now);
+            processor.process_canonical(days).unwrap()
+        };
+        assert_eq!(encode(&parsed), encode(&days));
+    }
+
+    #[test]
+    fn test_process_auto_detects_vendor() {
+        let mut processor = FluxProcessor::new();
+        let whoop = processor
+            .process_auto_payloads(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        assert_eq!(whoop[0].provenance.source_vendor, "whoop");
+        let garmin = processor
+            .process_auto_payloads(sample_garmin_json(), "UTC", "dev")
+            .unwrap();
+        assert_eq!(garmin[0].provenance.source_vendor, "garmin");
+
+        let err = processor.process_auto(r#"{"days":[]}"#, "UTC", "dev");
+        assert!(matches!(err, Err(ComputeError::UnsupportedVendor(_))));
+    }
+
+    #[test]
+    fn test_registered_vendor_adapter() {
+        /// Vendor whose export wraps a WHOOP-shaped body
+        struct Acme;
+        impl VendorPayloadAdapter for Acme {
+            fn parse(
+                &self,
+                raw_json: &str,
+                timezone: &str,
+                device_id: &str,
+            ) -> Result<Vec<CanonicalWearSignals>, ComputeError> {
+                let export: serde_json::Value = serde_json::from_str(raw_json)?;
+                WhoopAdapter.parse(&export["body"].to_string(), timezone, device_id)
+            }
+        }
+
+        let mut processor = FluxProcessor::new();
+        processor.register_adapter("acme", Box::new(Acme));
+        assert_eq!(processor.adapter_names(), vec!["acme", "garmin", "whoop"]);
+
+        let export = format!(r#"{{"body":{}}}"#, sample_whoop_json());
+        let payload = processor
+            .process_vendor_payloads("acme", &export, "UTC", "dev")
+            .unwrap()
+            .remove(0);
+        assert_eq!(payload.provenance.source_vendor, "acme");
+        assert_eq!(payload.windows[0].sleep.efficiency, Some(0.9375));
+
+        let builtin = processor
+            .process_vendor("garmin", sample_garmin_json(), "UTC", "dev")
+            .unwrap();
+        let builtin: serde_json::Value = serde_json::from_str(&builtin[0]).unwrap();
+        assert_eq!(builtin["provenance"]["source_vendor"], "garmin");
+
+        let err = processor
+            .process_vendor("oura", "{}", "UTC", "dev")
+            .unwrap_err();
+        assert!(matches!(err, ComputeError::UnsupportedVendor(_)));
+        assert!(err.to_string().contains("acme, garmin, whoop"));
+    }
+
+    #[test]
+    fn test_feature_stage_metrics_in_meta() {
+        struct SleepDebt;
+        impl FeatureStage for SleepDebt {
+            fn name(&self) -> &str {
+                "acme"
+            }
+            fn derive(&self, normalized: &NormalizedSignals) -> BTreeMap<String, f64> {
+                let sleep = normalized
+                    .canonical
+                    .sleep
+                    .total_sleep_minutes
+                    .unwrap_or(0.0);
+                BTreeMap::from([
+                    ("sleep_debt_minutes".to_string(), 480.0 - sleep),
+                    ("undefined".to_string(), f64::NAN),
+                ])
+            }
+        }
+
+        let mut processor = FluxProcessor::new().with_feature_stage(Arc::new(SleepDebt));
+        let payload = processor
+            .process_whoop_payloads(sample_whoop_json(), "UTC", "dev")
+            .unwrap()
+            .remove(0);
+        let metrics = &payload.meta.unwrap()[features::FEATURE_STAGES_META_KEY]["acme"];
+        assert_eq!(metrics["sleep_debt_minutes"], 30.0);
+        assert!(metrics.get("undefined").is_none());
+
+        // Custom metrics are free-form metadata
+        let mut processor = FluxProcessor::new()
+            .with_feature_stage(Arc::new(SleepDebt))
+            .with_encoder_config(EncoderConfig::default().with_meta_suppressed(true));
+        let payload = processor
+            .process_whoop_payloads(sample_whoop_json(), "UTC", "dev")
+            .unwrap()
+            .remove(0);
+        assert!(payload.meta.is_none());
+    }
+
+    #[test]
+    fn test_source_events_in_provenance() {
+        let hrv = |id: &str, hour: u32| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","event_id":"{id}","timestamp":"2024-01-15T{hour:02}:00:00Z","source":{{"provider":"garmin"}},"record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":60.0,"unit":"ms"}}}}}}"#
+            )
+        };
+        let ndjson = [hrv("evt-b", 3), hrv("evt-a", 4)].join("\n");
+        let provenance = |mode: SourceEvents| {
+            FluxProcessor::new()
+                .with_encoder_config(EncoderConfig::default().with_source_events(mode))
+                .process_raw_event_payloads(&ndjson, "UTC", "dev")
+                .unwrap()
+                .remove(0)
+                .provenance
+                .source_events
+        };
+
+        assert!(provenance(SourceEvents::Off).is_none());
+        let digest = provenance(SourceEvents::Digest).unwrap();
+        assert_eq!(digest.count, 2);
+        assert!(digest.digest.starts_with("sha256:") && digest.ids.is_none());
+        let listed = provenance(SourceEvents::List).unwrap();
+        assert_eq!(listed.digest, digest.digest);
+        assert_eq!(listed.ids.unwrap(), vec!["evt-a", "evt-b"]);
+    }
+
+    #[test]
+    fn test_warnings_collected_without_failing() {
+        let event = |record_type: &str, payload: &str| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{{"provider":"garmin"}},"record_type":"{record_type}","payload":{payload}}}"#
+            )
+        };
+        let sleep = |start: &str, end: &str, kind: &str| {
+            event(
+                "session",
+                &format!(
+                    r#"{{"session":{{"type":"{kind}","start_time":"{start}","end_time":"{end}","metrics":{{}}}}}}"#
+                ),
+            )
+        };
+        let ndjson = [
+            event(
+                "signal",
+                r#"{"signal":{"type":"glucose","value":5.4,"unit":"mmol/l"}}"#,
+            ),
+            event(
+                "signal",
+                r#"{"signal":{"type":"heart_rate_variability","value":60.0,"unit":"ms","quality":1.5}}"#,
+            ),
+            sleep("2024-01-14T23:00:00Z", "2024-01-15T07:00:00Z", "sleep"),
+            sleep("2024-01-15T13:00:00Z", "2024-01-15T13:30:00Z", "nap"),
+        ]
+        .join("\n");
+
+        let mut processor = FluxProcessor::new();
+        let payloads = processor.process_raw_events(&ndjson, "UTC", "dev").unwrap();
+        let output = processor.take_output(payloads);
+        assert_eq!(output.payloads.len(), 1);
+        let codes: Vec<WarningCode> = output.warnings.iter().map(|w| w.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                WarningCode::UnknownSignal,
+                WarningCode::DroppedSleepSession,
+                WarningCode::ClampedValue,
+            ]
+        );
+        assert!(output
+            .warnings
+            .iter()
+            .all(|w| w.date.as_deref() == Some("2024-01-15")));
+        assert!(processor.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_days_older_than_last_processed() {
+        let hrv = |date: &str| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{date}T07:00:00Z","source":{{"provider":"garmin"}},"record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":60.0,"unit":"ms"}}}}}}"#
+            )
+        };
+        let mut processor = FluxProcessor::new();
+        // Within a batch, events are sorted first
+        let shuffled = [hrv("2024-01-16"), hrv("2024-01-15")].join("\n");
+        assert_eq!(
+            processor
+                .process_raw_events(&shuffled, "UTC", "dev")
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // Across batches, an older day is rejected unless reprocessed
+        let older = hrv("2024-01-14");
+        assert!(matches!(
+            processor.process_raw_events(&older, "UTC", "dev"),
+            Err(ComputeError::OutOfOrder(_))
+        ));
+        let events = RawEventAdapter::parse_ndjson(&older).unwrap();
+        assert!(processor
+            .reprocess_day("2024-01-14", &events, "UTC")
+            .is_ok());
+
+        // The last date survives a save/load round trip
+        let saved = processor.save_baselines().unwrap();
+        let mut restored = FluxProcessor::new();
+        restored.load_baselines(&saved).unwrap();
+        assert!(restored.process_raw_events(&older, "UTC", "dev").is_err());
+        assert!(restored
+            .process_raw_events(&hrv("2024-01-16"), "UTC", "dev")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_process_event_stream_watermark() {
+        let hrv = |timestamp: &str| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{timestamp}","source":{{"provider":"garmin"}},"record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":60.0,"unit":"ms"}}}}}}"#
+            )
+        };
+        let stream = |timestamps: &[&str], lateness: Duration, policy: LateEventPolicy| {
+            let ndjson: Vec<String> = timestamps.iter().map(|t| hrv(t)).collect();
+            let events = RawEventAdapter::parse_ndjson(&ndjson.join("\n")).unwrap();
+            let mut processor = FluxProcessor::new();
+            let mut stream = processor
+                .process_event_stream(events.into_iter().map(Ok), "UTC", "dev")
+                .with_allowed_lateness(lateness)
+                .with_late_events(policy);
+            let dates: Vec<String> = stream
+                .by_ref()
+                .map(|p| {
+                    let payload: HsiPayload = serde_json::from_str(&p.unwrap()).unwrap();
+                    payload.windows[0].date.clone()
+                })
+                .collect();
+            (dates, stream.late_events())
+        };
+
+        // Out of order across midnight, within the allowed lateness
+        let (dates, late) = stream(
+            &[
+                "2024-01-15T23:50:00Z",
+                "2024-01-16T00:10:00Z",
+                "2024-01-15T23:55:00Z",
+            ],
+            Duration::hours(1),
+            LateEventPolicy::Error,
+        );
+        assert_eq!((dates.len(), late), (2, 0));
+
+        // A late event re-emits its day under Amend and is skipped under Drop
+        let timestamps = [
+            "2024-01-16T07:00:00Z",
+            "2024-01-17T07:00:00Z",
+            "2024-01-16T09:00:00Z",
+        ];
+        let (amended, late) = stream(&timestamps, Duration::zero(), LateEventPolicy::Amend);
+        assert_eq!((amended.len(), late), (3, 1));
+        assert_eq!(amended[0], amended[1]);
+        let (dropped, late) = stream(&timestamps, Duration::zero(), LateEventPolicy::Drop);
+        assert_eq!((dropped.len(), late), (2, 1));
+    }
+
     #[test]
     fn test_empty_payload() {
         let result = whoop_to_hsi_daily(
@@ -399,4 +2176,388 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_process_with_trace_matches_plain_output() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","event_id":"rhr-1","timestamp":"2024-01-16T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":55.0,"unit":"bpm"}}}"#;
+        let events = RawEventAdapter::parse_ndjson(ndjson).unwrap();
+        let canonical = RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        let computed_at = Utc::now();
+
+        let mut plain = FluxProcessor::new();
+        plain.set_deterministic(1, computed_at);
+        let mut traced = FluxProcessor::new();
+        traced.set_deterministic(1, computed_at);
+
+        let expected = plain.process_canonical(&canonical).unwrap();
+        let results = traced.process_with_trace(&canonical).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].hsi, expected[0]);
+
+        let trace = &results[0].trace;
+        assert_eq!(trace.date, "2024-01-16");
+        let rhr = trace
+            .metrics
+            .iter()
+            .find(|m| m.metric == "physiology.resting_hr_bpm")
+            .unwrap();
+        assert_eq!(rhr.value, 55.0);
+        assert_eq!(rhr.source_events, vec!["rhr-1"]);
+        assert!(trace.metrics.iter().any(|m| m.metric == "quality.coverage"));
+    }
+
+    #[test]
+    fn test_trace_respects_encoder_config() {
+        let mut processor = FluxProcessor::new()
+            .with_encoder_config(EncoderConfig::default().with_exclude_axes(["sleep"]));
+        let canonical = WhoopAdapter
+            .parse(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        let results = processor.process_with_trace(&canonical).unwrap();
+
+        let metrics = &results[0].trace.metrics;
+        assert!(metrics.iter().all(|m| !m.metric.starts_with("sleep.")));
+        assert!(metrics.iter().any(|m| m.metric.starts_with("physiology.")));
+    }
+
+    #[test]
+    fn test_hypnogram_metrics_marked_flux_computed() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"garmin"},"record_type":"session","payload":{"session":{"type":"sleep","start_time":"2024-01-14T23:00:00Z","end_time":"2024-01-15T02:00:00Z","metrics":{"hypnogram":[{"stage":"light","start":"2024-01-14T23:00:00Z","duration_minutes":60},{"stage":"awake","start":"2024-01-15T00:00:00Z","duration_minutes":20},{"stage":"deep","start":"2024-01-15T00:20:00Z","duration_minutes":60},{"stage":"wake","start":"2024-01-15T01:20:00Z","duration_minutes":40}]}}}}"#;
+
+        let results = FluxProcessor::new()
+            .process_raw_events(ndjson, "UTC", "dev")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&results[0]).unwrap();
+        let sleep = &payload["windows"][0]["sleep"];
+        assert_eq!(sleep["stage_transitions"], 2);
+        assert_eq!(sleep["waso_minutes"], 20.0);
+        assert_eq!(sleep["fragmentation_index"], 1.0);
+        assert_eq!(
+            payload["provenance"]["flux_computed"],
+            serde_json::json!([
+                "sleep.fragmentation_index",
+                "sleep.stage_transitions",
+                "sleep.waso_minutes"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_tagged_days_use_separate_baseline() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":60.0,"unit":"ms"}}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-16T08:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":40.0,"unit":"ms"}},"context":{"tags":["travel"]}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-17T08:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":60.0,"unit":"ms"}}}"#;
+
+        let mut processor = FluxProcessor::new().with_baseline_config(
+            BaselineConfig::default().with_tag_policy("travel", TagPolicy::Separate),
+        );
+        let results = processor.process_raw_events(ndjson, "UTC", "dev").unwrap();
+        let baseline = |i: usize| {
+            let payload: serde_json::Value = serde_json::from_str(&results[i]).unwrap();
+            payload["windows"][0]["baseline"].clone()
+        };
+        assert_eq!(baseline(1)["tag"], "travel");
+        assert_eq!(baseline(1)["hrv_ms"], 40.0);
+        assert!(baseline(2).get("tag").is_none());
+        assert_eq!(baseline(2)["hrv_ms"], 60.0);
+        assert_eq!(baseline(2)["days_in_baseline"], 2);
+    }
+
+    #[test]
+    fn test_quantile_normalization_uses_personal_history() {
+        let day = WhoopAdapter
+            .parse(sample_whoop_json(), "UTC", "dev")
+            .unwrap()
+            .remove(0);
+        let days: Vec<CanonicalWearSignals> = (0..10)
+            .map(|i| {
+                let mut day = day.clone();
+                day.date = format!("2024-01-{:02}", i + 1);
+                day.activity.vendor_strain_score = Some(5.0 + i as f64);
+                day
+            })
+            .collect();
+
+        let mut processor = FluxProcessor::new().with_normalization(
+            NormalizationConfig::default().with_strain(ScoreNormalization::Quantile),
+        );
+        let payloads = processor.process_canonical_payloads(&days).unwrap();
+
+        // Linear until a week of history exists, then ranked: the highest strain so far
+        let strain = |i: usize| payloads[i].windows[0].activity.strain_score.unwrap();
+        assert!((strain(6) - 11.0 / 21.0).abs() < 1e-9);
+        assert_eq!(strain(7), 1.0);
+        let provenance = |i: usize| payloads[i].provenance.score_normalization.clone().unwrap();
+        assert_eq!(provenance(6)["strain_score"], "linear");
+        assert_eq!(provenance(7)["strain_score"], "quantile");
+
+        let plain = FluxProcessor::new()
+            .process_canonical_payloads(&days)
+            .unwrap();
+        assert!(plain[7].provenance.score_normalization.is_none());
+    }
+
+    #[test]
+    fn test_series_emitted_only_when_enabled() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","event_id":"hr","timestamp":"2024-01-15T00:06:00Z","source":{"provider":"garmin"},"record_type":"series","payload":{"series":{"type":"heart_rate","unit":"bpm","samples":[{"timestamp":"2024-01-15T00:00:00Z","value":60.0},{"timestamp":"2024-01-15T00:01:00Z","value":62.0},{"timestamp":"2024-01-15T00:04:00Z","value":64.0},{"timestamp":"2024-01-15T00:05:00Z","value":70.0}]}}}"#;
+
+        let plain = FluxProcessor::new()
+            .process_raw_events(ndjson, "UTC", "dev")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&plain[0]).unwrap();
+        assert!(payload["windows"][0].get("series").is_none());
+
+        let mut processor = FluxProcessor::new()
+            .with_encoder_config(EncoderConfig::default().with_series_minutes(Some(5)));
+        let results = processor.process_raw_events(ndjson, "UTC", "dev").unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&results[0]).unwrap();
+        let series = &payload["windows"][0]["series"]["heart_rate_bpm"];
+        assert_eq!(series.as_array().unwrap().len(), 2);
+        assert_eq!(series[0]["value"], 62.0);
+        assert_eq!(series[0]["samples"], 3);
+        assert_eq!(series[1]["start_utc"], "2024-01-15T00:05:00+00:00");
+    }
+
+    #[test]
+    fn test_observer_sees_days_flags_and_errors() {
+        use crate::observer::RecordingObserver;
+
+        let observer = Arc::new(RecordingObserver::default());
+        let mut processor = FluxProcessor::new().with_observer(observer.clone());
+        processor
+            .process_whoop(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        assert_eq!(observer.count("Wearable processed 2024-01-15"), 1);
+
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-16T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":55.0,"unit":"bpm"}}}"#;
+        processor.process_raw_events(ndjson, "UTC", "dev").unwrap();
+        assert_eq!(observer.count("Wearable parsed 1"), 1);
+        assert_eq!(observer.count("Wearable processed 2024-01-16"), 1);
+        assert_eq!(observer.count("Wearable flag 2024-01-16 missing_sleep"), 1);
+
+        assert!(processor.process_whoop("not json", "UTC", "dev").is_err());
+        assert_eq!(observer.count("Wearable error"), 1);
+    }
+
+    #[test]
+    fn test_progress_counters_accumulate() {
+        use crate::observer::ProgressCounters;
+
+        let counters = Arc::new(ProgressCounters::new());
+        let mut processor = FluxProcessor::new().with_observer(counters.clone());
+        processor
+            .process_whoop(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-16T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"resting_heart_rate","value":55.0,"unit":"bpm"}}}"#;
+        processor.process_raw_events(ndjson, "UTC", "dev").unwrap();
+        assert!(processor.process_whoop("not json", "UTC", "dev").is_err());
+
+        assert_eq!(counters.events_parsed(), 1);
+        assert_eq!(counters.windows_processed(), 2);
+        assert!(counters.quality_flags() >= 1);
+        assert_eq!(counters.errors(), 1);
+    }
+
+    #[test]
+    fn test_activity_goal_attainment_and_trend() {
+        let day = |date: &str, steps: u32, minutes: f64| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{date}T20:00:00Z","source":{{"provider":"garmin"}},"record_type":"summary","payload":{{"summary":{{"period":"daily","date":"{date}","metrics":{{"steps":{steps},"active_minutes":{minutes}}}}}}}}}"#
+            )
+        };
+        let ndjson = [
+            day("2024-01-01", 2000, 90.0),
+            day("2024-01-02", 5000, 15.0),
+            day("2024-01-10", 12000, 30.0),
+            day("2024-01-12", 8000, 60.0),
+        ]
+        .join("\n");
+
+        let goal = ActivityGoal::default()
+            .with_steps(10000)
+            .with_active_minutes(30.0);
+        let mut processor = FluxProcessor::new().with_activity_goal(goal);
+        let payloads = processor.process_raw_events(&ndjson, "UTC", "dev").unwrap();
+        let activity = |i: usize| {
+            let payload: serde_json::Value = serde_json::from_str(&payloads[i]).unwrap();
+            payload["windows"][0]["activity"].clone()
+        };
+
+        // Steps 0.2 and active minutes capped at 1
+        assert_eq!(activity(0)["activity_goal_attainment"], 0.6);
+        assert_eq!(activity(1)["activity_goal_attainment"], 0.5);
+        assert_eq!(activity(1)["activity_goal_attainment_7d"], 0.55);
+        // January 1st and 2nd are outside the 7 days ending on the 10th
+        assert_eq!(activity(2)["activity_goal_attainment_7d"], 1.0);
+        assert_eq!(activity(3)["activity_goal_attainment"], 0.9);
+        assert_eq!(activity(3)["activity_goal_attainment_7d"], 0.95);
+
+        // Without a goal nothing is emitted
+        let payloads = FluxProcessor::new()
+            .process_raw_events(&ndjson, "UTC", "dev")
+            .unwrap();
+        assert!(!payloads[0].contains("activity_goal_attainment"));
+    }
+
+    #[test]
+    fn test_body_composition_and_bmi() {
+        let weigh_in = |date: &str, weight: f64, unit: &str, body_fat: f64| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{date}T07:00:00Z","source":{{"provider":"withings"}},"record_type":"signal","payload":{{"signal":{{"type":"weight","value":{weight},"unit":"{unit}"}}}}}}
+{{"schema_version":"wear.raw_event.v1","timestamp":"{date}T07:00:00Z","source":{{"provider":"withings"}},"record_type":"signal","payload":{{"signal":{{"type":"body_fat","value":{body_fat},"unit":"percent"}}}}}}"#
+            )
+        };
+        let ndjson = [
+            weigh_in("2024-01-01", 80.0, "kg", 20.0),
+            weigh_in("2024-01-02", 180.0, "lbs", 22.0),
+        ]
+        .join("\n");
+
+        let mut processor = FluxProcessor::new().with_height_cm(180.0);
+        let payloads = processor.process_raw_events(&ndjson, "UTC", "dev").unwrap();
+        let window = |i: usize| {
+            let payload: serde_json::Value = serde_json::from_str(&payloads[i]).unwrap();
+            payload["windows"][0].clone()
+        };
+
+        let body = window(1)["body"].clone();
+        let weight = 180.0 * 0.45359237;
+        assert!((body["weight_kg"].as_f64().unwrap() - weight).abs() < 1e-9);
+        assert_eq!(body["body_fat_percentage"], 22.0);
+        assert!((body["bmi"].as_f64().unwrap() - weight / 3.24).abs() < 1e-9);
+
+        let baseline = window(1)["baseline"].clone();
+        assert!((baseline["weight_kg"].as_f64().unwrap() - (80.0 + weight) / 2.0).abs() < 1e-9);
+        // Deviations compare against the baseline from before the day
+        let deviation = (weight - 80.0) / 80.0 * 100.0;
+        assert!((baseline["weight_deviation_pct"].as_f64().unwrap() - deviation).abs() < 1e-9);
+        assert!((baseline["body_fat_deviation_pct"].as_f64().unwrap() - 10.0).abs() < 1e-9);
+
+        // Without a height there is no BMI, and days without scale data have no block
+        let payloads = FluxProcessor::new()
+            .process_raw_events(&ndjson, "UTC", "dev")
+            .unwrap();
+        assert!(payloads[0].contains("\"body\""));
+        assert!(!payloads[0].contains("bmi"));
+    }
+
+    #[test]
+    fn test_slow_baseline_spread_and_zscores() {
+        let weigh_in = |date: &str, weight: f64| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{date}T07:00:00Z","source":{{"provider":"withings"}},"record_type":"signal","payload":{{"signal":{{"type":"weight","value":{weight},"unit":"kg"}}}}}}"#
+            )
+        };
+        let ndjson = [
+            weigh_in("2024-01-01", 80.0),
+            weigh_in("2024-01-02", 82.0),
+            weigh_in("2024-01-03", 84.0),
+            weigh_in("2024-01-04", 90.0),
+        ]
+        .join("\n");
+
+        let payloads = FluxProcessor::new()
+            .process_raw_events(&ndjson, "UTC", "dev")
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&payloads[3]).unwrap();
+        let baseline = &payload["windows"][0]["baseline"];
+
+        // The z-score compares against the spread from before the day...
+        assert!((baseline["weight_zscore"].as_f64().unwrap() - 4.0).abs() < 1e-9);
+        // ...while the emitted spread, like the mean, includes it
+        let std = (56.0f64 / 3.0).sqrt();
+        assert!((baseline["weight_std_kg"].as_f64().unwrap() - std).abs() < 1e-9);
+        assert!(baseline.get("body_fat_zscore").is_none());
+    }
+
+    #[test]
+    fn test_hrv_calibrated_onto_reference_vendor() {
+        let hrv = |date: &str, provider: &str, value: f64| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{date}T07:00:00Z","source":{{"provider":"{provider}"}},"record_type":"signal","payload":{{"signal":{{"type":"heart_rate_variability","value":{value},"unit":"ms"}}}}}}"#
+            )
+        };
+        let day = |processor: &mut FluxProcessor, line: String| -> serde_json::Value {
+            let payloads = processor.process_raw_events(&line, "UTC", "dev").unwrap();
+            serde_json::from_str(&payloads[0]).unwrap()
+        };
+        let mut processor =
+            FluxProcessor::new().with_hrv_calibration(HrvCalibrationConfig::new(Vendor::Whoop));
+        for (i, value) in [60.0, 70.0, 65.0, 80.0].into_iter().enumerate() {
+            day(
+                &mut processor,
+                hrv(&format!("2024-01-0{}", i + 1), "whoop", value),
+            );
+        }
+
+        // Not yet calibrated: Garmin HRV is not compared with the WHOOP baseline
+        let payload = day(&mut processor, hrv("2024-01-05", "garmin", 50.0));
+        assert!(payload["windows"][0]["baseline"]["hrv_deviation_pct"].is_null());
+        assert!(payload["provenance"].get("hrv_calibrated_from").is_none());
+
+        // Garmin reads 0.8x WHOOP minus 4 ms on days both devices were worn
+        let overlap: Vec<String> = (1..=5)
+            .flat_map(|d| {
+                let date = format!("2023-12-0{d}");
+                let whoop = 50.0 + 5.0 * d as f64;
+                [
+                    hrv(&date, "whoop", whoop),
+                    hrv(&date, "garmin", whoop * 0.8 - 4.0),
+                ]
+            })
+            .collect();
+        let events = RawEventAdapter::parse_ndjson(&overlap.join("\n")).unwrap();
+        processor.observe_hrv(&RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap());
+
+        let payload = day(&mut processor, hrv("2024-01-06", "garmin", 52.0));
+        let window = &payload["windows"][0];
+        assert!((window["physiology"]["hrv_rmssd_ms"].as_f64().unwrap() - 70.0).abs() < 1e-9);
+        let expected = (70.0 - 68.75) / 68.75 * 100.0;
+        let deviation = window["baseline"]["hrv_deviation_pct"].as_f64().unwrap();
+        assert!((deviation - expected).abs() < 1e-6);
+        assert_eq!(payload["provenance"]["hrv_calibrated_from"], "garmin");
+    }
+
+    #[test]
+    fn test_typed_payloads_match_json() {
+        let computed_at = Utc::now();
+        let mut typed = FluxProcessor::new();
+        typed.set_deterministic(3, computed_at);
+        let mut json = FluxProcessor::new();
+        json.set_deterministic(3, computed_at);
+
+        let payloads = typed
+            .process_whoop_payloads(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        let strings = json
+            .process_whoop(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+        assert_eq!(payloads.len(), strings.len());
+        for (payload, string) in payloads.iter().zip(&strings) {
+            assert_eq!(&json.encoder.payload_to_json(payload).unwrap(), string);
+        }
+        // Baselines evolve the same way through either entry point
+        assert_eq!(
+            typed.save_baselines().unwrap(),
+            json.save_baselines().unwrap()
+        );
+
+        let stateless = whoop_to_hsi_payloads(sample_whoop_json(), "UTC", "dev").unwrap();
+        assert_eq!(stateless[0].windows[0].date, payloads[0].windows[0].date);
+    }
+
+    #[cfg(feature = "integrity")]
+    #[test]
+    fn test_integrity_survives_deterministic_mode() {
+        use crate::integrity::{verify_json, IntegrityConfig};
+
+        let mut processor = FluxProcessor::new().with_integrity(IntegrityConfig::hash_only());
+        processor.set_deterministic(7, Utc::now());
+        let results = processor
+            .process_whoop(sample_whoop_json(), "UTC", "dev")
+            .unwrap();
+
+        let integrity = verify_json(&results[0], None).unwrap();
+        assert!(integrity.signature.is_none());
+    }
 }
@@ -0,0 +1,420 @@
+//! Differential-privacy export mode
+//!
+//! Post-processes already-encoded HSI payloads for cohort datasets: every
+//! 0-1 score receives Laplace noise with scale `sensitivity / epsilon` and is
+//! clamped back into range, windows with too little data are suppressed, and
+//! values that cannot be noised meaningfully (raw measurements, vendor maps,
+//! baselines, session metadata) are removed. The payload's `meta` records the
+//! mechanism and parameters under `differential_privacy`.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::behavior::types::HsiPayload as BehaviorPayload;
+use crate::error::ComputeError;
+use crate::types::{HsiBaseline, HsiPayload as DailyPayload};
+
+/// Meta key annotating a noised payload
+pub const DP_META_KEY: &str = "differential_privacy";
+
+/// Default privacy budget spent per score
+pub const DEFAULT_EPSILON: f64 = 1.0;
+
+/// Default L1 sensitivity of a single 0-1 score
+pub const DEFAULT_SENSITIVITY: f64 = 1.0;
+
+/// Default minimum coverage for a window to be exported
+pub const DEFAULT_MIN_COVERAGE: f64 = 0.5;
+
+/// Laplace mechanism parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DpConfig {
+    /// Privacy budget spent on each score (smaller is more private)
+    pub epsilon: f64,
+    /// L1 sensitivity of a score
+    pub sensitivity: f64,
+    /// Windows whose coverage is below this are suppressed
+    pub min_coverage: f64,
+}
+
+impl Default for DpConfig {
+    fn default() -> Self {
+        Self {
+            epsilon: DEFAULT_EPSILON,
+            sensitivity: DEFAULT_SENSITIVITY,
+            min_coverage: DEFAULT_MIN_COVERAGE,
+        }
+    }
+}
+
+impl DpConfig {
+    /// Set the per-score privacy budget
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Set the score sensitivity
+    pub fn with_sensitivity(mut self, sensitivity: f64) -> Self {
+        self.sensitivity = sensitivity;
+        self
+    }
+
+    /// Set the minimum coverage for a window to be exported
+    pub fn with_min_coverage(mut self, min_coverage: f64) -> Self {
+        self.min_coverage = min_coverage;
+        self
+    }
+
+    /// Check that the parameters describe a valid Laplace mechanism
+    pub fn validate(&self) -> Result<(), ComputeError> {
+        if !(self.epsilon.is_finite() && self.epsilon > 0.0) {
+            return Err(ComputeError::InvalidConfig(format!(
+                "epsilon must be positive, got {}",
+                self.epsilon
+            )));
+        }
+        if !(self.sensitivity.is_finite() && self.sensitivity > 0.0) {
+            return Err(ComputeError::InvalidConfig(format!(
+                "sensitivity must be positive, got {}",
+                self.sensitivity
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.min_coverage) {
+            return Err(ComputeError::InvalidConfig(format!(
+                "min_coverage must be within 0-1, got {}",
+                self.min_coverage
+            )));
+        }
+        Ok(())
+    }
+
+    /// Laplace scale `b = sensitivity / epsilon`
+    pub fn scale(&self) -> f64 {
+        self.sensitivity / self.epsilon
+    }
+
+    fn annotation(&self) -> serde_json::Value {
+        serde_json::json!({
+            "mechanism": "laplace",
+            "epsilon": self.epsilon,
+            "sensitivity": self.sensitivity,
+            "scope": "per_score",
+            "min_coverage": self.min_coverage,
+        })
+    }
+}
+
+/// Applies the Laplace mechanism to HSI payloads
+pub struct DpExporter {
+    config: DpConfig,
+    rng: StdRng,
+}
+
+impl DpExporter {
+    /// Create an exporter seeded from OS entropy
+    pub fn new(config: DpConfig) -> Result<Self, ComputeError> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            rng: StdRng::from_entropy(),
+        })
+    }
+
+    /// Create an exporter with reproducible noise (for tests; not private)
+    pub fn seeded(config: DpConfig, seed: u64) -> Result<Self, ComputeError> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    /// The mechanism parameters
+    pub fn config(&self) -> &DpConfig {
+        &self.config
+    }
+
+    /// Noise a behavioral payload, or `None` when its coverage is too low
+    pub fn privatize_behavior(&mut self, payload: &BehaviorPayload) -> Option<BehaviorPayload> {
+        let coverage = payload
+            .sources
+            .as_ref()
+            .and_then(|sources| {
+                sources
+                    .values()
+                    .map(|s| s.quality)
+                    .min_by(|a, b| a.total_cmp(b))
+            })
+            .unwrap_or(0.0);
+        if coverage < self.config.min_coverage {
+            return None;
+        }
+
+        let mut out = payload.clone();
+        if let Some(axes) = out.axes.as_mut() {
+            for domain in axes.domains_mut() {
+                for reading in &mut domain.readings {
+                    reading.score = reading.score.map(|s| self.noisy_score(s));
+                    reading.notes = None;
+                }
+            }
+        }
+        out.meta = Some(
+            [(DP_META_KEY.to_string(), self.config.annotation())]
+                .into_iter()
+                .collect(),
+        );
+        Some(out)
+    }
+
+    /// Noise a wearable payload, or `None` when its coverage is too low
+    pub fn privatize_daily(&mut self, payload: &DailyPayload) -> Option<DailyPayload> {
+        if payload.quality.coverage < self.config.min_coverage {
+            return None;
+        }
+
+        let mut out = payload.clone();
+        for window in &mut out.windows {
+            let sleep = &mut window.sleep;
+            for score in [
+                &mut sleep.efficiency,
+                &mut sleep.fragmentation,
+                &mut sleep.deep_ratio,
+                &mut sleep.rem_ratio,
+                &mut sleep.score,
+            ] {
+                *score = score.map(|s| self.noisy_score(s));
+            }
+            sleep.duration_minutes = None;
+            sleep.latency_minutes = None;
+            sleep.fragmentation_index = None;
+            sleep.stage_transitions = None;
+            sleep.waso_minutes = None;
+            sleep.vendor.clear();
+
+            let physiology = &mut window.physiology;
+            physiology.recovery_score = physiology.recovery_score.map(|s| self.noisy_score(s));
+            physiology.hrv_rmssd_ms = None;
+            physiology.hrv_cv = None;
+            physiology.recovery_balance = None;
+            physiology.vendor_stress = None;
+            physiology.stress_ratios = None;
+            physiology.cycle_phase_context = None;
+            physiology.resting_hr_bpm = None;
+            physiology.respiratory_rate = None;
+            physiology.spo2_percentage = None;
+            physiology.vendor.clear();
+
+            let activity = &mut window.activity;
+            activity.strain_score = activity.strain_score.map(|s| self.noisy_score(s));
+            activity.normalized_load = None;
+            activity.calories = None;
+            activity.active_calories = None;
+            activity.resting_calories = None;
+            activity.steps = None;
+            activity.active_minutes = None;
+            activity.workouts.clear();
+            activity.hourly.clear();
+            activity.hr_zone_minutes = None;
+            activity.activity_goal_attainment = None;
+            activity.activity_goal_attainment_7d = None;
+            activity.vo2_max = None;
+            activity.fitness_age = None;
+            activity.distance_meters = None;
+            activity.moderate_intensity_minutes = None;
+            activity.vigorous_intensity_minutes = None;
+            activity.floors_climbed = None;
+            activity.elevation_gain_meters = None;
+            activity.vendor.clear();
+            window.series = None;
+            window.body = None;
+
+            window.baseline = HsiBaseline {
+                hrv_ms: None,
+                resting_hr_bpm: None,
+                sleep_duration_minutes: None,
+                sleep_efficiency: None,
+                hrv_deviation_pct: None,
+                rhr_deviation_pct: None,
+                sleep_deviation_pct: None,
+                hrv_zscore: None,
+                rhr_zscore: None,
+                sleep_zscore: None,
+                hrv_std_ms: None,
+                rhr_std_bpm: None,
+                sleep_std_minutes: None,
+                hrv_anomaly: false,
+                rhr_anomaly: false,
+                sleep_anomaly: false,
+                vo2_max: None,
+                vo2_max_deviation_pct: None,
+                vo2_max_std: None,
+                vo2_max_zscore: None,
+                weight_kg: None,
+                body_fat_percentage: None,
+                weight_deviation_pct: None,
+                body_fat_deviation_pct: None,
+                weight_std_kg: None,
+                weight_zscore: None,
+                body_fat_std_pct: None,
+                body_fat_zscore: None,
+                hrv_trend: None,
+                rhr_trend: None,
+                sleep_trend: None,
+                baseline_shift_detected: None,
+                baseline_shift_date: None,
+                device_changed: None,
+                days_since_device_switch: None,
+                days_in_baseline: 0,
+                tag: None,
+            };
+        }
+        out.meta = Some(
+            [(DP_META_KEY.to_string(), self.config.annotation())]
+                .into_iter()
+                .collect(),
+        );
+        Some(out)
+    }
+
+    /// Add Laplace noise to a 0-1 score and clamp back into range
+    fn noisy_score(&mut self, score: f64) -> f64 {
+        (score + self.laplace()).clamp(0.0, 1.0)
+    }
+
+    /// Sample Laplace(0, b) by inverting the CDF
+    fn laplace(&mut self) -> f64 {
+        let u: f64 = self.rng.gen_range(-0.5..0.5);
+        -self.config.scale() * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior::behavior_to_hsi;
+
+    fn sample_behavior_payload() -> BehaviorPayload {
+        let session = r#"{
+            "session_id": "sess-1",
+            "device_id": "device-1",
+            "timezone": "UTC",
+            "start_time": "2024-01-15T14:00:00Z",
+            "end_time": "2024-01-15T14:10:00Z",
+            "events": [
+                {"timestamp": "2024-01-15T14:01:00Z", "event_type": "scroll", "scroll": {"velocity": 150.0, "direction": "down"}},
+                {"timestamp": "2024-01-15T14:02:00Z", "event_type": "tap", "tap": {"tap_duration_ms": 120}},
+                {"timestamp": "2024-01-15T14:05:00Z", "event_type": "scroll", "scroll": {"velocity": 90.0, "direction": "up"}}
+            ]
+        }"#;
+        serde_json::from_str(&behavior_to_hsi(session.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_behavior_scores_stay_in_range_and_are_annotated() {
+        let payload = sample_behavior_payload();
+        let config = DpConfig::default().with_min_coverage(0.0);
+        let mut exporter = DpExporter::seeded(config, 42).unwrap();
+        let noised = exporter.privatize_behavior(&payload).unwrap();
+
+        let readings = &noised
+            .axes
+            .as_ref()
+            .unwrap()
+            .behavior
+            .as_ref()
+            .unwrap()
+            .readings;
+        assert!(readings
+            .iter()
+            .filter_map(|r| r.score)
+            .all(|s| (0.0..=1.0).contains(&s)));
+        let meta = noised.meta.unwrap();
+        assert_eq!(meta.len(), 1);
+        assert_eq!(meta[DP_META_KEY]["mechanism"], "laplace");
+    }
+
+    #[test]
+    fn test_daily_payload_keeps_only_noised_scores() {
+        let whoop = r#"{
+            "sleep": [{
+                "id": 1,
+                "start": "2024-01-15T22:30:00.000Z",
+                "end": "2024-01-16T06:30:00.000Z",
+                "score": {
+                    "stage_summary": {
+                        "total_in_bed_time_milli": 28800000,
+                        "total_awake_time_milli": 1800000,
+                        "total_light_sleep_time_milli": 12600000,
+                        "total_slow_wave_sleep_time_milli": 7200000,
+                        "total_rem_sleep_time_milli": 7200000,
+                        "total_sleep_time_milli": 27000000,
+                        "disturbance_count": 3
+                    },
+                    "sleep_efficiency_percentage": 93.75
+                }
+            }],
+            "recovery": [{
+                "cycle_id": 1,
+                "created_at": "2024-01-16T06:30:00.000Z",
+                "score": { "recovery_score": 75.0, "resting_heart_rate": 52.0, "hrv_rmssd_milli": 65.0 }
+            }],
+            "cycle": []
+        }"#;
+        let json = crate::pipeline::whoop_to_hsi_daily(whoop.to_string(), "UTC".into(), "d".into())
+            .unwrap()
+            .remove(0);
+        let payload: DailyPayload = serde_json::from_str(&json).unwrap();
+
+        let mut exporter =
+            DpExporter::seeded(DpConfig::default().with_min_coverage(0.0), 3).unwrap();
+        let noised = exporter.privatize_daily(&payload).unwrap();
+        let window = &noised.windows[0];
+
+        assert!(window.physiology.hrv_rmssd_ms.is_none());
+        assert!(window.sleep.duration_minutes.is_none());
+        assert!(window.sleep.vendor.is_empty());
+        let efficiency = window.sleep.efficiency.unwrap();
+        assert!((0.0..=1.0).contains(&efficiency));
+        assert!(noised.meta.unwrap().contains_key(DP_META_KEY));
+    }
+
+    #[test]
+    fn test_low_coverage_is_suppressed() {
+        let payload = sample_behavior_payload();
+        let mut exporter =
+            DpExporter::seeded(DpConfig::default().with_min_coverage(1.0), 1).unwrap();
+        let coverage = payload
+            .sources
+            .as_ref()
+            .unwrap()
+            .values()
+            .next()
+            .unwrap()
+            .quality;
+        assert_eq!(
+            exporter.privatize_behavior(&payload).is_none(),
+            coverage < 1.0
+        );
+    }
+
+    #[test]
+    fn test_laplace_noise_has_expected_scale() {
+        let mut exporter = DpExporter::seeded(DpConfig::default().with_epsilon(2.0), 7).unwrap();
+        let n = 20_000;
+        let mean_abs = (0..n).map(|_| exporter.laplace().abs()).sum::<f64>() / n as f64;
+        // E|X| = b for Laplace(0, b); b = 1 / 2
+        assert!((mean_abs - 0.5).abs() < 0.02, "mean |noise| = {mean_abs}");
+    }
+
+    #[test]
+    fn test_invalid_epsilon_rejected() {
+        assert!(matches!(
+            DpExporter::new(DpConfig::default().with_epsilon(0.0)),
+            Err(ComputeError::InvalidConfig(_))
+        ));
+    }
+}
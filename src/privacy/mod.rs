@@ -0,0 +1,208 @@
+//! Privacy policy configuration and enforcement
+//!
+//! A [`PrivacyPolicy`] drives the HSI `privacy` declaration (consent,
+//! purposes, embedding permission) and an enforcement pass that strips
+//! fields the policy does not allow from payload metadata before encoding.
+
+#[cfg(feature = "dp")]
+pub mod dp;
+pub mod scrub;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::behavior::types::{HsiConsent, HsiPrivacy};
+
+pub use scrub::{ScrubMode, Scrubber};
+
+/// Purpose declared when no policy is configured
+pub const DEFAULT_PURPOSE: &str = "behavioral_research";
+
+/// Meta key holding echoed vendor payloads in wearable windows
+pub const VENDOR_RAW_KEY: &str = "raw";
+
+/// Consent, purposes and field-level permissions applied to emitted payloads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyPolicy {
+    /// Consent level declared in `privacy.consent`
+    pub consent: Option<HsiConsent>,
+    /// Purposes declared in `privacy.purposes` (omitted when empty)
+    pub purposes: Vec<String>,
+    /// Whether downstream embedding of the payload is allowed
+    pub embedding_allowed: Option<bool>,
+    /// Keep app identifiers (`app_id`, `*_app_id`, `*_app_ids`) in metadata
+    pub allow_app_identifiers: bool,
+    /// Keep vendor payload echoes (`vendor.raw`) in wearable windows
+    pub allow_vendor_raw: bool,
+    /// Hash or drop user IDs, app identifiers and device serials
+    pub scrub: Option<Scrubber>,
+    /// Emit a hashed user ID in wearable `provenance.source_user_id`
+    pub include_user_id: bool,
+}
+
+impl Default for PrivacyPolicy {
+    fn default() -> Self {
+        Self {
+            consent: None,
+            purposes: vec![DEFAULT_PURPOSE.to_string()],
+            embedding_allowed: None,
+            allow_app_identifiers: false,
+            allow_vendor_raw: true,
+            scrub: None,
+            include_user_id: false,
+        }
+    }
+}
+
+impl PrivacyPolicy {
+    /// Declare the consent level
+    pub fn with_consent(mut self, consent: HsiConsent) -> Self {
+        self.consent = Some(consent);
+        self
+    }
+
+    /// Declare the purposes the data may be used for
+    pub fn with_purposes<I, S>(mut self, purposes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.purposes = purposes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Declare whether embeddings may be derived from the payload
+    pub fn with_embedding_allowed(mut self, allowed: bool) -> Self {
+        self.embedding_allowed = Some(allowed);
+        self
+    }
+
+    /// Keep or strip app identifiers in metadata
+    pub fn with_app_identifiers_allowed(mut self, allowed: bool) -> Self {
+        self.allow_app_identifiers = allowed;
+        self
+    }
+
+    /// Keep or strip echoed vendor payloads
+    pub fn with_vendor_raw_allowed(mut self, allowed: bool) -> Self {
+        self.allow_vendor_raw = allowed;
+        self
+    }
+
+    /// Scrub identifiers from events and metadata before encoding
+    pub fn with_scrubber(mut self, scrubber: Scrubber) -> Self {
+        self.scrub = Some(scrubber);
+        self
+    }
+
+    /// Emit or omit a hashed user ID in payload provenance
+    pub fn with_user_id_included(mut self, included: bool) -> Self {
+        self.include_user_id = included;
+        self
+    }
+
+    /// Pseudonym of a user ID to emit, if the policy includes user IDs.
+    ///
+    /// Hashed with the configured scrubber's salt (unsalted without one);
+    /// a scrubber in drop mode keeps the ID out. Values that are already
+    /// pseudonyms are emitted unchanged.
+    pub fn user_pseudonym(&self, user_id: &str) -> Option<String> {
+        if !self.include_user_id {
+            return None;
+        }
+        let scrubber = self.scrub.clone().unwrap_or_default();
+        match scrubber.mode {
+            ScrubMode::Drop => None,
+            ScrubMode::Hash if Scrubber::is_pseudonym(user_id) => Some(user_id.to_string()),
+            ScrubMode::Hash => Some(scrubber.pseudonym(user_id)),
+        }
+    }
+
+    /// The HSI privacy declaration for this policy
+    pub fn to_hsi_privacy(&self) -> HsiPrivacy {
+        HsiPrivacy {
+            contains_pii: false,
+            raw_biosignals_allowed: false,
+            derived_metrics_allowed: true,
+            embedding_allowed: self.embedding_allowed,
+            consent: self.consent,
+            purposes: (!self.purposes.is_empty()).then(|| self.purposes.clone()),
+            notes: None,
+        }
+    }
+
+    /// Strip disallowed entries from a metadata map (recursing into nested values)
+    pub fn enforce(&self, meta: &mut HashMap<String, Value>) {
+        if !self.allow_vendor_raw {
+            meta.remove(VENDOR_RAW_KEY);
+        }
+        if !self.allow_app_identifiers {
+            meta.retain(|key, _| !is_app_identifier(key));
+            meta.values_mut().for_each(strip_app_identifiers);
+        }
+        if let Some(scrubber) = &self.scrub {
+            scrubber.scrub_map(meta);
+        }
+    }
+}
+
+/// Whether a metadata key names an app identifier
+fn is_app_identifier(key: &str) -> bool {
+    key == "app_id" || key.ends_with("_app_id") || key == "app_ids" || key.ends_with("_app_ids")
+}
+
+fn strip_app_identifiers(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !is_app_identifier(key));
+            map.values_mut().for_each(strip_app_identifiers);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_app_identifiers),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_default_policy_matches_legacy_declaration() {
+        let privacy = PrivacyPolicy::default().to_hsi_privacy();
+        assert!(!privacy.contains_pii);
+        assert_eq!(privacy.purposes, Some(vec![DEFAULT_PURPOSE.to_string()]));
+        assert!(privacy.consent.is_none());
+    }
+
+    #[test]
+    fn test_enforce_strips_app_identifiers_and_raw() {
+        let mut meta = HashMap::new();
+        meta.insert("source_app_id".to_string(), json!("com.example.chat"));
+        meta.insert(
+            "sessions".to_string(),
+            json!([{ "to_app_id": "com.example.mail", "duration": 12 }]),
+        );
+        meta.insert("raw".to_string(), json!({ "score": 80 }));
+
+        PrivacyPolicy::default()
+            .with_vendor_raw_allowed(false)
+            .enforce(&mut meta);
+
+        assert!(!meta.contains_key("source_app_id"));
+        assert!(!meta.contains_key("raw"));
+        assert_eq!(meta["sessions"], json!([{ "duration": 12 }]));
+    }
+
+    #[test]
+    fn test_policy_deserializes_with_defaults() {
+        let policy: PrivacyPolicy =
+            serde_json::from_str(r#"{"consent": "explicit", "purposes": ["wellness"]}"#).unwrap();
+        assert_eq!(policy.consent, Some(HsiConsent::Explicit));
+        assert!(policy.allow_vendor_raw);
+        assert!(!policy.allow_app_identifiers);
+    }
+}
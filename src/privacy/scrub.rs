@@ -0,0 +1,203 @@
+//! PII scrubbing of identifiers before they reach HSI output
+//!
+//! App identifiers, device serials and user IDs can enter the pipeline through
+//! behavioral events and echoed vendor payloads. A [`Scrubber`] either drops
+//! them or replaces them with salted SHA-256 pseudonyms, which keep equality
+//! (the same app always maps to the same token) without revealing the value.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::behavior::types::BehaviorSession;
+
+/// Keys treated as personally identifying wherever they appear
+pub const PII_KEYS: &[&str] = &[
+    "user_id",
+    "userId",
+    "app_id",
+    "source_app_id",
+    "from_app_id",
+    "to_app_id",
+    "serial",
+    "serial_number",
+    "serialNumber",
+    "device_serial",
+    "deviceSerial",
+];
+
+/// Prefix marking a pseudonymized value
+const PSEUDONYM_PREFIX: &str = "h_";
+
+/// Hex characters kept from the digest (64 bits)
+const PSEUDONYM_HEX_LEN: usize = 16;
+
+fn is_pii_key(key: &str) -> bool {
+    PII_KEYS.contains(&key)
+}
+
+/// What to do with an identifying value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrubMode {
+    /// Replace with a salted hash pseudonym
+    #[default]
+    Hash,
+    /// Remove the value entirely
+    Drop,
+}
+
+/// Sanitization stage for identifiers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scrubber {
+    /// Hash or drop identifiers
+    pub mode: ScrubMode,
+    /// Salt mixed into pseudonyms so tokens cannot be matched across deployments
+    pub salt: String,
+}
+
+impl Scrubber {
+    /// Create a scrubber with the given mode and no salt
+    pub fn new(mode: ScrubMode) -> Self {
+        Self {
+            mode,
+            salt: String::new(),
+        }
+    }
+
+    /// Mix a salt into hashed pseudonyms
+    pub fn with_salt(mut self, salt: impl Into<String>) -> Self {
+        self.salt = salt.into();
+        self
+    }
+
+    /// Salted pseudonym for an identifier (`h_` + 16 hex characters)
+    pub fn pseudonym(&self, value: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update([0]);
+        hasher.update(value.as_bytes());
+        let hex: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        format!("{}{}", PSEUDONYM_PREFIX, &hex[..PSEUDONYM_HEX_LEN])
+    }
+
+    /// Whether a value is already a pseudonym produced by [`Self::pseudonym`]
+    pub fn is_pseudonym(value: &str) -> bool {
+        value.strip_prefix(PSEUDONYM_PREFIX).is_some_and(|hex| {
+            hex.len() == PSEUDONYM_HEX_LEN && hex.bytes().all(|b| b.is_ascii_hexdigit())
+        })
+    }
+
+    /// Scrub an optional identifier field
+    pub fn scrub_field(&self, field: &mut Option<String>) {
+        *field = match self.mode {
+            ScrubMode::Hash => field.as_deref().map(|v| self.pseudonym(v)),
+            ScrubMode::Drop => None,
+        };
+    }
+
+    /// Scrub identifying keys anywhere inside a JSON value
+    pub fn scrub_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                if self.mode == ScrubMode::Drop {
+                    map.retain(|key, _| !is_pii_key(key));
+                }
+                for (key, child) in map.iter_mut() {
+                    self.scrub_entry(key, child);
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.scrub_value(v)),
+            _ => {}
+        }
+    }
+
+    /// Scrub identifying keys in a metadata map
+    pub fn scrub_map(&self, map: &mut HashMap<String, Value>) {
+        if self.mode == ScrubMode::Drop {
+            map.retain(|key, _| !is_pii_key(key));
+        }
+        for (key, child) in map.iter_mut() {
+            self.scrub_entry(key, child);
+        }
+    }
+
+    /// Scrub app identifiers carried by behavioral events
+    pub fn scrub_session(&self, session: &mut BehaviorSession) {
+        for event in &mut session.events {
+            if let Some(interruption) = event.interruption.as_mut() {
+                self.scrub_field(&mut interruption.source_app_id);
+            }
+            if let Some(app_switch) = event.app_switch.as_mut() {
+                self.scrub_field(&mut app_switch.from_app_id);
+                self.scrub_field(&mut app_switch.to_app_id);
+            }
+        }
+    }
+
+    fn scrub_entry(&self, key: &str, value: &mut Value) {
+        if is_pii_key(key) {
+            self.hash_leaf(value);
+        } else {
+            self.scrub_value(value);
+        }
+    }
+
+    fn hash_leaf(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.pseudonym(s),
+            Value::Number(n) => *value = Value::String(self.pseudonym(&n.to_string())),
+            Value::Array(items) => items.iter_mut().for_each(|v| self.hash_leaf(v)),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hash_mode_pseudonymizes_nested_keys() {
+        let scrubber = Scrubber::new(ScrubMode::Hash).with_salt("tenant-a");
+        let mut raw = json!({
+            "user_id": 10129,
+            "device": { "serialNumber": "G1234", "model": "Forerunner" },
+            "score": 80
+        });
+        scrubber.scrub_value(&mut raw);
+
+        assert_eq!(raw["user_id"], json!(scrubber.pseudonym("10129")));
+        assert!(raw["device"]["serialNumber"]
+            .as_str()
+            .unwrap()
+            .starts_with("h_"));
+        assert_eq!(raw["device"]["model"], "Forerunner");
+        assert_eq!(raw["score"], 80);
+    }
+
+    #[test]
+    fn test_drop_mode_removes_keys() {
+        let mut raw = json!({ "userId": "u-1", "nested": [{ "to_app_id": "com.example" }] });
+        Scrubber::new(ScrubMode::Drop).scrub_value(&mut raw);
+        assert_eq!(raw, json!({ "nested": [{}] }));
+    }
+
+    #[test]
+    fn test_pseudonyms_depend_on_salt() {
+        let a = Scrubber::new(ScrubMode::Hash).pseudonym("com.example.chat");
+        let b = Scrubber::new(ScrubMode::Hash)
+            .with_salt("other")
+            .pseudonym("com.example.chat");
+        assert_ne!(a, b);
+        assert_eq!(a.len(), PSEUDONYM_PREFIX.len() + PSEUDONYM_HEX_LEN);
+    }
+}
@@ -0,0 +1,405 @@
+//! Validation and diagnostic reports
+//!
+//! The reports `flux validate` and `flux doctor` print, as library types, so
+//! server integrations can produce the same JSON without shelling out to the
+//! CLI. Checks that depend on the CLI's environment (files, stdin) are left to
+//! the caller and added with [`DoctorReport::add_check`].
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::baseline::BaselineStore;
+use crate::encoder::HsiEncoder;
+use crate::features::FeatureDeriver;
+use crate::normalizer::Normalizer;
+use crate::schema::{
+    ParseMode, ParsedEvents, RawEvent, RawEventAdapter, ValidationResult, SCHEMA_VERSION,
+};
+use crate::validation::validate_hsi;
+use crate::{FLUX_VERSION, PRODUCER_NAME};
+
+/// Schema validation of a batch of raw events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub total_events: usize,
+    pub valid_events: usize,
+    pub invalid_events: usize,
+    pub errors: Vec<ValidationErrorDetail>,
+    /// Readings no canonical field takes, by key
+    #[serde(default)]
+    pub unmapped_signals: BTreeMap<String, usize>,
+}
+
+impl ValidationReport {
+    /// Validate each event and count the readings no canonical field takes
+    pub fn from_events(events: &[RawEvent]) -> Self {
+        let errors: Vec<ValidationErrorDetail> = RawEventAdapter::validate_events(events)
+            .iter()
+            .map(ValidationErrorDetail::from)
+            .collect();
+        Self {
+            total_events: events.len(),
+            valid_events: events.len() - errors.len(),
+            invalid_events: errors.len(),
+            errors,
+            unmapped_signals: RawEventAdapter::unmapped_signals(events),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.invalid_events == 0
+    }
+}
+
+/// An event that failed validation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationErrorDetail {
+    /// Position of the event in the batch
+    pub index: usize,
+    pub event_id: Option<String>,
+    pub error: String,
+}
+
+impl From<&ValidationResult> for ValidationErrorDetail {
+    fn from(result: &ValidationResult) -> Self {
+        Self {
+            index: result.index,
+            event_id: result.event_id.clone(),
+            error: result
+                .result
+                .as_ref()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Pipeline health checks, with diagnostics from an optional sample run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DoctorReport {
+    pub producer: String,
+    pub version: String,
+    pub checks: Vec<DoctorCheck>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<SampleDiagnostics>,
+}
+
+impl Default for DoctorReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DoctorReport {
+    /// Report holding the Flux version and input schema checks
+    pub fn new() -> Self {
+        Self {
+            producer: PRODUCER_NAME.to_string(),
+            version: FLUX_VERSION.to_string(),
+            checks: vec![
+                DoctorCheck::new(
+                    "flux_version",
+                    CheckStatus::Ok,
+                    format!("Flux version {}", FLUX_VERSION),
+                ),
+                DoctorCheck::new(
+                    "schema_version",
+                    CheckStatus::Ok,
+                    format!("Input schema: {}", SCHEMA_VERSION),
+                ),
+            ],
+            sample: None,
+        }
+    }
+
+    pub fn add_check(&mut self, check: DoctorCheck) {
+        self.checks.push(check);
+    }
+
+    /// Run raw-event NDJSON through each pipeline stage on top of `store`,
+    /// adding the `sample_input` and `sample_output` checks and the sample's
+    /// diagnostics.
+    pub fn add_sample(&mut self, ndjson: &str, store: BaselineStore, timezone: &str) {
+        let (checks, sample) = sample_checks(ndjson, store, timezone);
+        self.checks.extend(checks);
+        self.sample = sample;
+    }
+
+    /// Whether any check failed
+    pub fn has_errors(&self) -> bool {
+        self.checks.iter().any(|c| c.status == CheckStatus::Error)
+    }
+}
+
+/// One named health check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+impl DoctorCheck {
+    pub fn new(name: impl Into<String>, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// Check that a saved baseline store is valid JSON
+    pub fn baselines(json: &str) -> Self {
+        match serde_json::from_str::<serde_json::Value>(json) {
+            Ok(value) => {
+                let days = value
+                    .get("baseline_days")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                Self::new(
+                    "baselines",
+                    CheckStatus::Ok,
+                    format!("Baselines file valid ({} days of data)", days),
+                )
+            }
+            Err(e) => Self::new(
+                "baselines",
+                CheckStatus::Error,
+                format!("Invalid baselines JSON: {}", e),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Counts, stage timings and baseline movement from a sample run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SampleDiagnostics {
+    pub events_total: usize,
+    pub events_parsed: usize,
+    pub events_dropped: usize,
+    pub days_produced: usize,
+    pub stage_timings: Vec<StageTiming>,
+    /// Days carrying each quality flag
+    pub quality_flags: BTreeMap<String, usize>,
+    /// Baselines before and after the sample, by metric
+    pub baseline_delta: BTreeMap<String, BaselineDelta>,
+}
+
+impl SampleDiagnostics {
+    fn record_stage(&mut self, stage: &str, started: Instant) {
+        self.stage_timings.push(StageTiming {
+            stage: stage.to_string(),
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub duration_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BaselineDelta {
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+}
+
+/// Run each pipeline stage on a sample, timing stages and collecting diagnostics
+fn sample_checks(
+    ndjson: &str,
+    mut store: BaselineStore,
+    timezone: &str,
+) -> (Vec<DoctorCheck>, Option<SampleDiagnostics>) {
+    let check = |status, message: String| DoctorCheck::new("sample_input", status, message);
+
+    let mut diagnostics = SampleDiagnostics::default();
+    let started = Instant::now();
+    let ParsedEvents { events, dropped } =
+        match RawEventAdapter::parse_ndjson_with_mode(ndjson, ParseMode::Lenient) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let message = format!("Cannot parse sample input: {}", e);
+                return (vec![check(CheckStatus::Error, message)], None);
+            }
+        };
+    diagnostics.events_parsed = events.len();
+    diagnostics.events_dropped = dropped.len();
+    diagnostics.events_total = events.len() + dropped.len();
+    diagnostics.record_stage("parse", started);
+
+    let before = store.get_baselines();
+
+    let started = Instant::now();
+    let canonical = match RawEventAdapter::to_canonical(&events, timezone, "doctor") {
+        Ok(canonical) => canonical,
+        Err(e) => {
+            let message = format!("Adapter stage failed: {}", e);
+            return (vec![check(CheckStatus::Error, message)], Some(diagnostics));
+        }
+    };
+    diagnostics.record_stage("adapt", started);
+
+    let started = Instant::now();
+    let normalized: Vec<_> = canonical.iter().map(Normalizer::normalize).collect();
+    diagnostics.record_stage("normalize", started);
+    for flag in normalized.iter().flat_map(|n| &n.quality_flags) {
+        *diagnostics
+            .quality_flags
+            .entry(flag.as_str().to_string())
+            .or_default() += 1;
+    }
+
+    let started = Instant::now();
+    let derived: Vec<_> = normalized.into_iter().map(FeatureDeriver::derive).collect();
+    diagnostics.record_stage("derive", started);
+
+    let started = Instant::now();
+    let contextual: Vec<_> = derived
+        .into_iter()
+        .map(|d| store.update_and_contextualize(d))
+        .collect();
+    diagnostics.record_stage("baseline", started);
+
+    let started = Instant::now();
+    let encoder = HsiEncoder::new();
+    let encoded: Result<Vec<String>, _> = contextual
+        .iter()
+        .map(|c| encoder.encode_to_json(c))
+        .collect();
+    diagnostics.record_stage("encode", started);
+    diagnostics.days_produced = contextual.len();
+
+    let after = store.get_baselines();
+    for (metric, before, after) in [
+        ("hrv_ms", before.hrv_baseline_ms, after.hrv_baseline_ms),
+        ("rhr_bpm", before.rhr_baseline_bpm, after.rhr_baseline_bpm),
+        (
+            "sleep_minutes",
+            before.sleep_baseline_minutes,
+            after.sleep_baseline_minutes,
+        ),
+    ] {
+        diagnostics
+            .baseline_delta
+            .insert(metric.to_string(), BaselineDelta { before, after });
+    }
+
+    let mut checks = Vec::new();
+    let invalid_payloads = match encoded {
+        Ok(payloads) => payloads
+            .iter()
+            .filter(|json| {
+                serde_json::from_str(json)
+                    .map(|value| !validate_hsi(&value).is_empty())
+                    .unwrap_or(true)
+            })
+            .count(),
+        Err(e) => {
+            let message = format!("Encoder stage failed: {}", e);
+            return (vec![check(CheckStatus::Error, message)], Some(diagnostics));
+        }
+    };
+
+    checks.push(if diagnostics.days_produced == 0 {
+        check(
+            CheckStatus::Error,
+            "Sample produced no HSI days".to_string(),
+        )
+    } else if diagnostics.events_dropped > 0 {
+        check(
+            CheckStatus::Warning,
+            format!(
+                "{} of {} events dropped; {} days produced",
+                diagnostics.events_dropped, diagnostics.events_total, diagnostics.days_produced
+            ),
+        )
+    } else {
+        check(
+            CheckStatus::Ok,
+            format!(
+                "{} events processed into {} days",
+                diagnostics.events_total, diagnostics.days_produced
+            ),
+        )
+    });
+    checks.push(if invalid_payloads > 0 {
+        DoctorCheck::new(
+            "sample_output",
+            CheckStatus::Error,
+            format!("{} produced payloads fail HSI validation", invalid_payloads),
+        )
+    } else {
+        DoctorCheck::new(
+            "sample_output",
+            CheckStatus::Ok,
+            "Produced payloads pass HSI validation",
+        )
+    });
+
+    (checks, Some(diagnostics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = concat!(
+        r#"{"schema_version":"wear.raw_event.v1","event_id":"e1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":58,"unit":"ms"}}}"#,
+        "\n",
+        r#"{"schema_version":"wear.raw_event.v1","event_id":"e2","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"garmin"},"record_type":"score","payload":{"signal":{"type":"steps","value":500,"unit":"count"}}}"#,
+    );
+
+    #[test]
+    fn test_validation_report_from_events() {
+        let events = RawEventAdapter::parse_ndjson(SAMPLE).unwrap();
+        let report = ValidationReport::from_events(&events);
+        assert_eq!(report.total_events, 2);
+        assert_eq!(report.invalid_events, 1);
+        assert_eq!(report.errors[0].index, 1);
+        assert_eq!(report.errors[0].event_id.as_deref(), Some("e2"));
+        assert!(!report.is_valid());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["valid_events"], 1);
+    }
+
+    #[test]
+    fn test_doctor_report_sample() {
+        let mut report = DoctorReport::new();
+        report.add_check(DoctorCheck::baselines("{not json"));
+        assert!(report.has_errors());
+
+        let mut report = DoctorReport::new();
+        report.add_sample(
+            SAMPLE.lines().next().unwrap(),
+            BaselineStore::default(),
+            "UTC",
+        );
+        assert!(!report.has_errors());
+        let names: Vec<_> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "flux_version",
+                "schema_version",
+                "sample_input",
+                "sample_output"
+            ]
+        );
+        let sample = report.sample.unwrap();
+        assert_eq!(sample.days_produced, 1);
+        assert_eq!(sample.stage_timings.len(), 6);
+        assert_eq!(sample.baseline_delta["hrv_ms"].after, Some(58.0));
+    }
+}
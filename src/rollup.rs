@@ -0,0 +1,353 @@
+//! Weekly and monthly rollups
+//!
+//! Aggregates emitted daily windows into one summary per ISO week (Monday to
+//! Sunday) or calendar month. For each metric a summary carries the mean,
+//! range and a least-squares trend across the period. It also records how
+//! many of the period's days had data and, when an activity goal was set, on
+//! how many of them the goal was met.
+//!
+//! [`rollup`] works on emitted payloads (e.g. `flux rollup` over an HSI NDJSON
+//! file); [`crate::FluxProcessor::rollup`] works on the days the processor
+//! has emitted.
+
+use crate::baseline::linear_slope;
+use crate::encoder::HSI_VERSION;
+use crate::error::ComputeError;
+use crate::types::{HsiDailyWindow, HsiPayload, HsiProducer};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Days of emitted windows a [`crate::FluxProcessor`] keeps for rollups
+pub const ROLLUP_HISTORY_DAYS: usize = 400;
+
+/// Goal attainment at which a day counts toward goal adherence
+pub const GOAL_MET_ATTAINMENT: f64 = 1.0;
+
+/// Accessor for one metric of a daily window
+type WindowMetric = fn(&HsiDailyWindow) -> Option<f64>;
+
+/// Daily window metrics that are rolled up, by output name
+const METRICS: [(&str, WindowMetric); 12] = [
+    ("sleep.duration_minutes", |w| w.sleep.duration_minutes),
+    ("sleep.efficiency", |w| w.sleep.efficiency),
+    ("sleep.score", |w| w.sleep.score),
+    ("physiology.hrv_rmssd_ms", |w| w.physiology.hrv_rmssd_ms),
+    ("physiology.resting_hr_bpm", |w| w.physiology.resting_hr_bpm),
+    ("physiology.recovery_score", |w| w.physiology.recovery_score),
+    ("activity.strain_score", |w| w.activity.strain_score),
+    ("activity.steps", |w| w.activity.steps.map(f64::from)),
+    ("activity.active_minutes", |w| w.activity.active_minutes),
+    ("activity.calories", |w| w.activity.calories),
+    ("activity.activity_goal_attainment", |w| {
+        w.activity.activity_goal_attainment
+    }),
+    ("body.weight_kg", |w| {
+        w.body.as_ref().and_then(|b| b.weight_kg)
+    }),
+];
+
+/// Rollup period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupPeriod {
+    /// ISO weeks, Monday to Sunday
+    Weekly,
+    /// Calendar months
+    Monthly,
+}
+
+impl RollupPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollupPeriod::Weekly => "weekly",
+            RollupPeriod::Monthly => "monthly",
+        }
+    }
+
+    /// First and last day of the period containing `date`
+    pub fn bounds(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            RollupPeriod::Weekly => {
+                let start = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+                (start, start + Duration::days(6))
+            }
+            RollupPeriod::Monthly => {
+                let start = date.with_day(1).expect("every month has a first day");
+                let end = (start + Months::new(1))
+                    .pred_opt()
+                    .expect("a month start has a previous day");
+                (start, end)
+            }
+        }
+    }
+}
+
+/// Metrics of one emitted day, as kept for rollups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollupDay {
+    pub date: NaiveDate,
+    /// The day's `quality.confidence`
+    pub confidence: f64,
+    /// Rolled-up metric values, by output name
+    pub metrics: BTreeMap<String, f64>,
+}
+
+impl RollupDay {
+    /// Rollup days of a daily payload, one per window
+    pub fn from_payload(payload: &HsiPayload) -> Result<Vec<Self>, ComputeError> {
+        payload
+            .windows
+            .iter()
+            .map(|window| Self::from_window(window, payload.quality.confidence))
+            .collect()
+    }
+
+    /// Rollup day of one daily window
+    pub fn from_window(window: &HsiDailyWindow, confidence: f64) -> Result<Self, ComputeError> {
+        let date = NaiveDate::parse_from_str(&window.date, "%Y-%m-%d")
+            .map_err(|e| ComputeError::DateParseError(format!("{}: {e}", window.date)))?;
+        let metrics = METRICS
+            .iter()
+            .filter_map(|(name, metric)| Some((name.to_string(), metric(window)?)))
+            .collect();
+        Ok(Self {
+            date,
+            confidence,
+            metrics,
+        })
+    }
+}
+
+/// Summary of one metric over a period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollupMetric {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Days with a value
+    pub days: u32,
+    /// Least-squares slope (units per day); needs values on two or more days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trend_per_day: Option<f64>,
+}
+
+/// Data coverage and goal adherence over a period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollupQuality {
+    /// Days in the period, clipped to the requested range
+    pub days_expected: u32,
+    /// Days with an emitted window
+    pub days_with_data: u32,
+    /// `days_with_data / days_expected`
+    pub coverage: f64,
+    /// Mean `quality.confidence` of the days with data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_confidence: Option<f64>,
+    /// Share of days with goal attainment on which the goal was met
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal_adherence: Option<f64>,
+}
+
+/// Rollup of the daily windows in one week or month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HsiRollup {
+    pub hsi_version: String,
+    pub producer: HsiProducer,
+    pub period: RollupPeriod,
+    /// First day covered (YYYY-MM-DD)
+    pub start_date: String,
+    /// Last day covered (YYYY-MM-DD)
+    pub end_date: String,
+    pub quality: RollupQuality,
+    /// Metric summaries, keyed like the daily window fields they summarize
+    pub metrics: BTreeMap<String, RollupMetric>,
+}
+
+/// Roll up daily payloads into one summary per period.
+///
+/// A later payload for a date replaces an earlier one. `range` defaults to
+/// the first through last date present; periods are clipped to it. The
+/// producer is taken from the last payload.
+pub fn rollup(
+    payloads: &[HsiPayload],
+    period: RollupPeriod,
+    range: Option<RangeInclusive<NaiveDate>>,
+) -> Result<Vec<HsiRollup>, ComputeError> {
+    let Some(last) = payloads.last() else {
+        return Ok(Vec::new());
+    };
+    let mut days = Vec::new();
+    for payload in payloads {
+        days.extend(RollupDay::from_payload(payload)?);
+    }
+    Ok(rollup_days(&days, period, range, &last.producer))
+}
+
+/// Roll up days into one summary per period (see [`rollup`])
+pub fn rollup_days(
+    days: &[RollupDay],
+    period: RollupPeriod,
+    range: Option<RangeInclusive<NaiveDate>>,
+    producer: &HsiProducer,
+) -> Vec<HsiRollup> {
+    let by_date: BTreeMap<NaiveDate, &RollupDay> = days.iter().map(|d| (d.date, d)).collect();
+    let range = match range {
+        Some(range) => range,
+        None => match (by_date.keys().next(), by_date.keys().next_back()) {
+            (Some(first), Some(last)) => *first..=*last,
+            _ => return Vec::new(),
+        },
+    };
+
+    let mut rollups = Vec::new();
+    let mut cursor = *range.start();
+    while cursor <= *range.end() {
+        let (_, period_end) = period.bounds(cursor);
+        let end = period_end.min(*range.end());
+        let days: Vec<&RollupDay> = by_date.range(cursor..=end).map(|(_, d)| *d).collect();
+        rollups.push(summarize(&days, period, cursor, end, producer));
+        match end.succ_opt() {
+            Some(next) => cursor = next,
+            None => break,
+        }
+    }
+    rollups
+}
+
+fn summarize(
+    days: &[&RollupDay],
+    period: RollupPeriod,
+    start: NaiveDate,
+    end: NaiveDate,
+    producer: &HsiProducer,
+) -> HsiRollup {
+    let days_expected = ((end - start).num_days() + 1) as u32;
+    let days_with_data = days.len() as u32;
+
+    let mut series: BTreeMap<&str, Vec<(f64, f64)>> = BTreeMap::new();
+    for day in days {
+        let x = (day.date - start).num_days() as f64;
+        for (name, value) in &day.metrics {
+            series.entry(name).or_default().push((x, *value));
+        }
+    }
+    let metrics = series
+        .into_iter()
+        .map(|(name, points)| (name.to_string(), summarize_metric(&points)))
+        .collect();
+
+    let attainment: Vec<f64> = days
+        .iter()
+        .filter_map(|d| d.metrics.get("activity.activity_goal_attainment").copied())
+        .collect();
+    let goal_adherence = (!attainment.is_empty()).then(|| {
+        attainment
+            .iter()
+            .filter(|a| **a >= GOAL_MET_ATTAINMENT)
+            .count() as f64
+            / attainment.len() as f64
+    });
+
+    HsiRollup {
+        hsi_version: HSI_VERSION.to_string(),
+        producer: producer.clone(),
+        period,
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        quality: RollupQuality {
+            days_expected,
+            days_with_data,
+            coverage: f64::from(days_with_data) / f64::from(days_expected),
+            mean_confidence: (!days.is_empty())
+                .then(|| days.iter().map(|d| d.confidence).sum::<f64>() / days.len() as f64),
+            goal_adherence,
+        },
+        metrics,
+    }
+}
+
+/// Mean, range and trend of `(day offset, value)` points
+fn summarize_metric(points: &[(f64, f64)]) -> RollupMetric {
+    RollupMetric {
+        mean: points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64,
+        min: points.iter().map(|(_, y)| *y).fold(f64::INFINITY, f64::min),
+        max: points
+            .iter()
+            .map(|(_, y)| *y)
+            .fold(f64::NEG_INFINITY, f64::max),
+        days: points.len() as u32,
+        trend_per_day: linear_slope(points),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn day(d: &str, steps: f64, attainment: f64) -> RollupDay {
+        RollupDay {
+            date: date(d),
+            confidence: 0.8,
+            metrics: [
+                ("activity.steps".to_string(), steps),
+                ("activity.activity_goal_attainment".to_string(), attainment),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    fn producer() -> HsiProducer {
+        HsiProducer {
+            name: "synheart-flux".to_string(),
+            version: "test".to_string(),
+            instance_id: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        // 2024-01-03 is a Wednesday
+        let (start, end) = RollupPeriod::Weekly.bounds(date("2024-01-03"));
+        assert_eq!((start, end), (date("2024-01-01"), date("2024-01-07")));
+        let (start, end) = RollupPeriod::Monthly.bounds(date("2024-02-10"));
+        assert_eq!((start, end), (date("2024-02-01"), date("2024-02-29")));
+    }
+
+    #[test]
+    fn test_weekly_rollup() {
+        let days = [
+            day("2024-01-01", 6000.0, 0.6),
+            day("2024-01-02", 8000.0, 1.0),
+            day("2024-01-04", 12000.0, 1.0),
+            day("2024-01-08", 5000.0, 0.5),
+        ];
+        let rollups = rollup_days(&days, RollupPeriod::Weekly, None, &producer());
+        assert_eq!(rollups.len(), 2);
+
+        let week = &rollups[0];
+        assert_eq!(
+            (week.start_date.as_str(), week.end_date.as_str()),
+            ("2024-01-01", "2024-01-07")
+        );
+        assert_eq!(week.quality.days_expected, 7);
+        assert_eq!(week.quality.days_with_data, 3);
+        assert!((week.quality.goal_adherence.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        let steps = &week.metrics["activity.steps"];
+        assert!((steps.mean - 26000.0 / 3.0).abs() < 1e-9);
+        assert_eq!((steps.min, steps.max), (6000.0, 12000.0));
+        // Least squares through (0, 6000), (1, 8000), (3, 12000)
+        assert!((steps.trend_per_day.unwrap() - 2000.0).abs() < 1e-9);
+
+        // The second week is clipped to the last day with data
+        let week = &rollups[1];
+        assert_eq!(week.quality.days_expected, 1);
+        assert_eq!(week.metrics["activity.steps"].trend_per_day, None);
+    }
+}
@@ -1,15 +1,149 @@
 //! Adapter for converting wear.raw_event.v1 to CanonicalWearSignals
 //!
 //! This module handles aggregating individual raw events into daily canonical
-//! signals that can be processed through the existing Flux pipeline.
+//! signals that can be processed through the existing Flux pipeline. The
+//! mode-aware and `parse_any` entry points also accept newer schema versions,
+//! converting them to v1 first.
 
 use crate::error::ComputeError;
 use crate::schema::raw_event::*;
+use crate::schema::raw_event_ref::RawEventRef;
+use crate::schema::raw_event_v2::RawEventV2;
 use crate::types::{
-    CanonicalActivity, CanonicalRecovery, CanonicalSleep, CanonicalWearSignals, Vendor,
+    BodyBattery, CanonicalActivity, CanonicalBody, CanonicalRecovery, CanonicalSleep,
+    CanonicalWearSignals, CyclePhase, HourlyActivity, HrZoneMinutes, HypnogramSegment,
+    IntradaySample, RespirationSummary, SleepStage, StressSummary, UnmappedSignal, Vendor,
 };
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use crate::warnings::{Warning, WarningCode};
+use chrono::{DateTime, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::BufRead;
+
+/// Kilograms per pound, for scale readings reported in lbs
+const KG_PER_LB: f64 = 0.45359237;
+
+/// Signal quality below which averaged readings (heart rate, HRV, SpO2, ...)
+/// are dropped; readings at or above it are weighted by their quality
+pub const MIN_SIGNAL_QUALITY: f64 = 0.5;
+
+/// How malformed lines and invalid events are handled when parsing input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseMode {
+    /// Fail on the first malformed line
+    #[default]
+    Strict,
+    /// Skip malformed lines and invalid events, recording each one
+    Lenient,
+}
+
+/// An input event skipped in lenient mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DroppedEvent {
+    /// 1-based line number (NDJSON) or array position (JSON)
+    pub line: usize,
+    /// Event ID, when the line parsed far enough to have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    /// Why the event was dropped
+    pub reason: String,
+}
+
+/// Events parsed in a given [`ParseMode`]
+#[derive(Debug, Default)]
+pub struct ParsedEvents {
+    pub events: Vec<RawEvent>,
+    /// Events skipped in lenient mode (always empty in strict mode)
+    pub dropped: Vec<DroppedEvent>,
+}
+
+/// Return a line's event, or handle its failure according to `mode`
+fn settle_line(
+    result: Result<RawEvent, LineError>,
+    line: usize,
+    mode: ParseMode,
+    dropped: &mut Vec<DroppedEvent>,
+) -> Result<Option<RawEvent>, ComputeError> {
+    let event = result.and_then(|event| {
+        if mode == ParseMode::Lenient {
+            event.validate().map_err(|e| LineError {
+                event_id: event.event_id.clone(),
+                reason: e.to_string(),
+            })?;
+        }
+        Ok(event)
+    });
+    match event {
+        Ok(event) => Ok(Some(event)),
+        Err(e) if mode == ParseMode::Strict => Err(ComputeError::ParseError(format!(
+            "Failed to parse line {}: {}",
+            line, e.reason
+        ))),
+        Err(e) => {
+            dropped.push(DroppedEvent {
+                line,
+                event_id: e.event_id,
+                reason: e.reason,
+            });
+            Ok(None)
+        }
+    }
+}
+
+/// Why one input line could not be turned into events
+struct LineError {
+    event_id: Option<String>,
+    reason: String,
+}
+
+/// Envelope fields read before choosing a schema version to decode with
+#[derive(Deserialize)]
+struct VersionProbe {
+    schema_version: Option<String>,
+    event_id: Option<String>,
+}
+
+impl VersionProbe {
+    fn from_str(line: &str) -> Result<Self, LineError> {
+        serde_json::from_str(line).map_err(|e| LineError {
+            event_id: None,
+            reason: e.to_string(),
+        })
+    }
+
+    fn from_value(value: &serde_json::Value) -> Result<Self, LineError> {
+        Self::deserialize(value).map_err(|e| LineError {
+            event_id: None,
+            reason: e.to_string(),
+        })
+    }
+
+    /// Decode with the function matching the probed version and convert to v1
+    fn convert(
+        self,
+        v1: impl FnOnce() -> serde_json::Result<RawEvent>,
+        v2: impl FnOnce() -> serde_json::Result<RawEventV2>,
+    ) -> Result<RawEvent, LineError> {
+        let event_id = self.event_id;
+        let fail = |reason: String| LineError {
+            event_id: event_id.clone(),
+            reason,
+        };
+        let version = self
+            .schema_version
+            .ok_or_else(|| fail("missing field `schema_version`".to_string()))?;
+        match SchemaVersion::parse(&version).map_err(|e| fail(e.to_string()))? {
+            SchemaVersion::V1 => v1().map_err(|e| fail(e.to_string())),
+            SchemaVersion::V2 => {
+                let event = v2().map_err(|e| fail(e.to_string()))?;
+                event.validate().map_err(|e| fail(e.to_string()))?;
+                Ok(event.into_v1())
+            }
+        }
+    }
+}
 
 /// Adapter for converting raw events to canonical signals
 pub struct RawEventAdapter;
@@ -43,45 +177,214 @@ impl RawEventAdapter {
         Ok(events)
     }
 
+    /// Parse NDJSON of any supported schema version, converting to v1.
+    ///
+    /// Each line is dispatched on its `schema_version`. Unknown versions are
+    /// a parse error.
+    pub fn parse_any(ndjson: &str) -> Result<Vec<RawEvent>, ComputeError> {
+        Ok(Self::parse_ndjson_with_mode(ndjson, ParseMode::Strict)?.events)
+    }
+
+    /// Parse NDJSON RawEvents, skipping and recording bad lines in lenient mode.
+    ///
+    /// Lines are dispatched on `schema_version` like [`Self::parse_any`].
+    /// Lenient mode also drops events that fail validation, so they cannot
+    /// abort [`Self::to_canonical`] later.
+    pub fn parse_ndjson_with_mode(
+        ndjson: &str,
+        mode: ParseMode,
+    ) -> Result<ParsedEvents, ComputeError> {
+        let mut parsed = ParsedEvents::default();
+        for (line_num, line) in ndjson.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(event) = Self::parse_line(line, line_num + 1, mode, &mut parsed.dropped)? {
+                parsed.events.push(event);
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Parse a JSON array of RawEvents, skipping and recording bad items in lenient mode.
+    ///
+    /// Items are dispatched on `schema_version` like [`Self::parse_any`].
+    pub fn parse_array_with_mode(
+        json: &str,
+        mode: ParseMode,
+    ) -> Result<ParsedEvents, ComputeError> {
+        let mut parsed = ParsedEvents::default();
+        let items: Vec<serde_json::Value> = serde_json::from_str(json)?;
+        for (index, item) in items.into_iter().enumerate() {
+            let result = VersionProbe::from_value(&item).and_then(|probe| {
+                probe.convert(
+                    || RawEvent::deserialize(&item),
+                    || RawEventV2::deserialize(&item),
+                )
+            });
+            if let Some(event) = settle_line(result, index + 1, mode, &mut parsed.dropped)? {
+                parsed.events.push(event);
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Parse one NDJSON line of any supported schema version (`line_num` is 1-based).
+    ///
+    /// In strict mode a malformed line is an error. In lenient mode the event
+    /// is also validated, and failures are appended to `dropped` instead.
+    pub fn parse_line(
+        line: &str,
+        line_num: usize,
+        mode: ParseMode,
+        dropped: &mut Vec<DroppedEvent>,
+    ) -> Result<Option<RawEvent>, ComputeError> {
+        let line = line.trim();
+        let result = VersionProbe::from_str(line).and_then(|probe| {
+            probe.convert(
+                || serde_json::from_str::<RawEvent>(line),
+                || serde_json::from_str::<RawEventV2>(line),
+            )
+        });
+        settle_line(result, line_num, mode, dropped)
+    }
+
+    /// Lazily parse NDJSON RawEvents from a reader, one line at a time.
+    ///
+    /// Blank lines are skipped; each item carries its own parse error, so large
+    /// archives can be processed without loading them into memory.
+    pub fn iter_ndjson<R: BufRead>(
+        reader: R,
+    ) -> impl Iterator<Item = Result<RawEvent, ComputeError>> {
+        reader
+            .lines()
+            .enumerate()
+            .filter_map(|(line_num, line)| match line {
+                Ok(line) if line.trim().is_empty() => None,
+                Ok(line) => Some(serde_json::from_str::<RawEvent>(line.trim()).map_err(|e| {
+                    ComputeError::ParseError(format!(
+                        "Failed to parse line {}: {}",
+                        line_num + 1,
+                        e
+                    ))
+                })),
+                Err(e) => Some(Err(ComputeError::ParseError(format!(
+                    "Failed to read line {}: {}",
+                    line_num + 1,
+                    e
+                )))),
+            })
+    }
+
+    /// Calendar date (`YYYY-MM-DD`) an event is aggregated under
+    pub fn event_date(event: &RawEvent) -> String {
+        extract_date(&event.timestamp, event.context.as_ref())
+    }
+
     /// Convert raw events to canonical daily signals
     ///
     /// Groups events by date and provider, then aggregates into daily canonical signals.
+    /// Events are taken in chronological order (ties keep their input order),
+    /// whatever order they arrive in.
     pub fn to_canonical(
         events: &[RawEvent],
         timezone: &str,
         device_id: &str,
     ) -> Result<Vec<CanonicalWearSignals>, ComputeError> {
-        // Group events by (date, provider)
-        let mut by_date_provider: HashMap<(String, String), DayAccumulator> = HashMap::new();
+        let mut ordered: Vec<&RawEvent> = events.iter().collect();
+        ordered.sort_by_key(|event| event.timestamp);
+        accumulate_days(
+            ordered.into_iter().map(|event| {
+                event.validate().map_err(invalid_event)?;
+                Ok(EventFields {
+                    timestamp: &event.timestamp,
+                    provider: &event.source.provider,
+                    payload: &event.payload,
+                    context: event.context.as_ref(),
+                    event_id: event.event_id.as_deref(),
+                    user_id: event.user_id.as_deref(),
+                    vendor_raw: event.vendor_raw.clone(),
+                })
+            }),
+            timezone,
+            device_id,
+        )
+    }
 
+    /// Count the signals, summary metrics and scores in `events` that no
+    /// canonical field takes, keyed like [`CanonicalWearSignals::unmapped_signals`]
+    pub fn unmapped_signals(events: &[RawEvent]) -> BTreeMap<String, usize> {
+        let Some(first) = events.first() else {
+            return BTreeMap::new();
+        };
+        let mut accumulator = DayAccumulator::new(first.source.provider.clone());
         for event in events {
-            // Validate each event
-            if let Err(e) = event.validate() {
-                return Err(ComputeError::ParseError(format!("Invalid event: {e}")));
-            }
-
-            let date = extract_date(&event.timestamp, event.context.as_ref());
-            let provider = event.source.provider.as_str().to_string();
-            let key = (date, provider);
-
-            let accumulator = by_date_provider
-                .entry(key)
-                .or_insert_with(|| DayAccumulator::new(event.source.provider.clone()));
-
-            accumulator.add_event(event);
+            accumulator.add_event(EventFields {
+                timestamp: &event.timestamp,
+                provider: &event.source.provider,
+                payload: &event.payload,
+                context: event.context.as_ref(),
+                event_id: None,
+                user_id: None,
+                vendor_raw: None,
+            });
         }
+        accumulator
+            .unmapped
+            .into_iter()
+            .map(|(key, unmapped)| (key, unmapped.count))
+            .collect()
+    }
 
-        // Convert accumulators to canonical signals
-        let mut signals = Vec::new();
-        for ((date, _), accumulator) in by_date_provider {
-            let canonical = accumulator.into_canonical(&date, timezone, device_id)?;
-            signals.push(canonical);
+    /// Parse NDJSON into borrowed [`RawEventRef`]s without copying strings
+    /// or materializing `vendor_raw`
+    pub fn parse_ndjson_borrowed(ndjson: &str) -> Result<Vec<RawEventRef<'_>>, ComputeError> {
+        let mut events = Vec::new();
+        for (line_num, line) in ndjson.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let event = RawEventRef::parse(trimmed).map_err(|e| {
+                ComputeError::ParseError(format!("Failed to parse line {}: {}", line_num + 1, e))
+            })?;
+            events.push(event);
         }
+        Ok(events)
+    }
 
-        // Sort by date
-        signals.sort_by(|a, b| a.date.cmp(&b.date));
-
-        Ok(signals)
+    /// Convert borrowed raw events to canonical daily signals.
+    ///
+    /// `vendor_raw` is only parsed into the canonical output when
+    /// `include_vendor_raw` is set.
+    pub fn to_canonical_borrowed(
+        events: &[RawEventRef<'_>],
+        timezone: &str,
+        device_id: &str,
+        include_vendor_raw: bool,
+    ) -> Result<Vec<CanonicalWearSignals>, ComputeError> {
+        let mut ordered: Vec<&RawEventRef<'_>> = events.iter().collect();
+        ordered.sort_by_key(|event| event.timestamp);
+        accumulate_days(
+            ordered.into_iter().map(|event| {
+                event.validate().map_err(invalid_event)?;
+                Ok(EventFields {
+                    timestamp: &event.timestamp,
+                    provider: &event.source.provider,
+                    payload: &event.payload,
+                    context: event.context.as_ref(),
+                    event_id: event.event_id.as_deref(),
+                    user_id: event.user_id.as_deref(),
+                    vendor_raw: if include_vendor_raw {
+                        event.vendor_raw_value()?
+                    } else {
+                        None
+                    },
+                })
+            }),
+            timezone,
+            device_id,
+        )
     }
 
     /// Validate a batch of events
@@ -99,6 +402,70 @@ impl RawEventAdapter {
     }
 }
 
+fn invalid_event(e: ValidationError) -> ComputeError {
+    ComputeError::ParseError(format!("Invalid event: {e}"))
+}
+
+/// The parts of an event the day accumulator reads, from owned or borrowed events
+struct EventFields<'e> {
+    timestamp: &'e DateTime<Utc>,
+    provider: &'e Provider,
+    payload: &'e Payload,
+    context: Option<&'e Context>,
+    event_id: Option<&'e str>,
+    user_id: Option<&'e str>,
+    vendor_raw: Option<serde_json::Value>,
+}
+
+/// Group events by (date, provider, user) and aggregate each group into a canonical day
+fn accumulate_days<'e>(
+    events: impl Iterator<Item = Result<EventFields<'e>, ComputeError>>,
+    timezone: &str,
+    device_id: &str,
+) -> Result<Vec<CanonicalWearSignals>, ComputeError> {
+    trace_span!(DEBUG, "adapt");
+    let mut by_day: HashMap<(String, String, Option<String>), DayAccumulator> = HashMap::new();
+
+    for event in events {
+        let event = event?;
+        let date = extract_date(event.timestamp, event.context);
+        let key = (
+            date,
+            event.provider.as_str().to_string(),
+            event.user_id.map(str::to_string),
+        );
+
+        let accumulator = by_day
+            .entry(key)
+            .or_insert_with(|| DayAccumulator::new(event.provider.clone()));
+
+        accumulator.add_event(event);
+    }
+
+    // Convert accumulators to canonical signals
+    let mut signals = Vec::new();
+    for ((date, _, _), accumulator) in by_day {
+        trace_event!(
+            DEBUG,
+            date = %date,
+            provider = accumulator.provider.as_str(),
+            events = accumulator.event_count,
+            ignored_signals = accumulator.ignored_signals,
+            low_quality_signals = accumulator.low_quality_signals,
+            "day accumulated"
+        );
+        let canonical = accumulator.into_canonical(&date, timezone, device_id)?;
+        signals.push(canonical);
+    }
+
+    // Sort by date (and vendor and user, so same-day output order is stable)
+    signals.sort_by(|a, b| {
+        (&a.date, a.vendor.as_str(), &a.user_id).cmp(&(&b.date, b.vendor.as_str(), &b.user_id))
+    });
+
+    Ok(signals)
+}
+
 /// Result of event validation
 #[derive(Debug)]
 pub struct ValidationResult {
@@ -107,29 +474,103 @@ pub struct ValidationResult {
     pub result: Option<ValidationError>,
 }
 
+/// Readings of an averaged signal, each with a weight
+#[derive(Default)]
+struct Readings {
+    values: Vec<(f64, f64)>,
+}
+
+impl Readings {
+    /// Add a reading at full weight
+    fn push(&mut self, value: f64) {
+        self.push_weighted(value, 1.0);
+    }
+
+    fn push_weighted(&mut self, value: f64, weight: f64) {
+        self.values.push((value, weight));
+    }
+
+    /// Weighted mean of the readings
+    fn mean(&self) -> Option<f64> {
+        let total: f64 = self.values.iter().map(|(_, w)| w).sum();
+        (total > 0.0).then(|| self.values.iter().map(|(v, w)| v * w).sum::<f64>() / total)
+    }
+}
+
 /// Accumulator for aggregating events into a single day
 struct DayAccumulator {
     provider: Provider,
     // Sleep data
     sleep_sessions: Vec<SleepData>,
     // Recovery/physiology data
-    hrv_readings: Vec<f64>,
-    resting_hr_readings: Vec<f64>,
-    spo2_readings: Vec<f64>,
-    skin_temp_readings: Vec<f64>,
-    respiratory_rate_readings: Vec<f64>,
+    hrv_readings: Readings,
+    resting_hr_readings: Readings,
+    spo2_readings: Readings,
+    skin_temp_readings: Readings,
+    respiratory_rate_readings: Readings,
     recovery_score: Option<f64>,
+    cycle_phase: Option<CyclePhase>,
+    body_battery: BodyBattery,
+    stress: StressSummary,
+    respiration: RespirationSummary,
     // Activity data
     strain_score: Option<f64>,
     total_calories: Option<f64>,
     active_calories: Option<f64>,
+    resting_calories: Option<f64>,
     total_steps: Option<u32>,
     distance_meters: Option<f64>,
     active_minutes: Option<f64>,
-    hr_readings: Vec<f64>,
+    moderate_intensity_minutes: Option<f64>,
+    vigorous_intensity_minutes: Option<f64>,
+    floors_climbed: Option<u32>,
+    elevation_gain_meters: Option<f64>,
+    hr_zone_minutes: HrZoneMinutes,
+    vo2_max: Option<f64>,
+    fitness_age: Option<f64>,
+    hr_readings: Readings,
+    // Hour-level activity by hour start
+    hourly: BTreeMap<DateTime<Utc>, HourlyActivity>,
+    // Body composition
+    weight_readings: Readings,
+    body_fat_readings: Readings,
     max_hr: Option<f64>,
     // Raw vendor data
     vendor_raw: HashMap<String, serde_json::Value>,
+    // Timezone reported in event context (last one wins)
+    timezone: Option<String>,
+    // UTC offset of that timezone at the event that reported it
+    utc_offset_minutes: Option<i32>,
+    // User the events belong to (last one wins)
+    user_id: Option<String>,
+    // Events added to this day
+    event_count: usize,
+    // Signals of types the canonical model does not use
+    ignored_signals: usize,
+    // Quality reported by the day's signals
+    signal_qualities: Vec<f64>,
+    // Averaged signals dropped for quality below MIN_SIGNAL_QUALITY
+    low_quality_signals: usize,
+    // Qualities and hour coverages outside 0-1, clamped into range
+    clamped_values: usize,
+    // Signals and metrics with no canonical field, by `kind.name` key
+    unmapped: BTreeMap<String, UnmappedReadings>,
+    // Event IDs behind each field, keyed like `field_sources`
+    field_events: HashMap<&'static str, Vec<String>>,
+    // Series samples of the fields kept at intraday resolution
+    intraday: BTreeMap<&'static str, Vec<IntradaySample>>,
+    // Context tags of the day's events
+    tags: BTreeSet<String>,
+    // Newest timestamp of an event that fed each HSI domain
+    latest_event_at: BTreeMap<&'static str, DateTime<Utc>>,
+}
+
+/// Readings of one unmapped signal type or metric
+#[derive(Default)]
+struct UnmappedReadings {
+    readings: Readings,
+    count: usize,
+    unit: Option<String>,
 }
 
 struct SleepData {
@@ -143,90 +584,345 @@ impl DayAccumulator {
         DayAccumulator {
             provider,
             sleep_sessions: Vec::new(),
-            hrv_readings: Vec::new(),
-            resting_hr_readings: Vec::new(),
-            spo2_readings: Vec::new(),
-            skin_temp_readings: Vec::new(),
-            respiratory_rate_readings: Vec::new(),
+            hrv_readings: Readings::default(),
+            resting_hr_readings: Readings::default(),
+            spo2_readings: Readings::default(),
+            skin_temp_readings: Readings::default(),
+            respiratory_rate_readings: Readings::default(),
             recovery_score: None,
+            cycle_phase: None,
+            body_battery: BodyBattery::default(),
+            stress: StressSummary::default(),
+            respiration: RespirationSummary::default(),
             strain_score: None,
             total_calories: None,
             active_calories: None,
+            resting_calories: None,
             total_steps: None,
             distance_meters: None,
             active_minutes: None,
-            hr_readings: Vec::new(),
+            moderate_intensity_minutes: None,
+            vigorous_intensity_minutes: None,
+            floors_climbed: None,
+            elevation_gain_meters: None,
+            hr_zone_minutes: HrZoneMinutes::default(),
+            vo2_max: None,
+            fitness_age: None,
+            hr_readings: Readings::default(),
+            hourly: BTreeMap::new(),
+            weight_readings: Readings::default(),
+            body_fat_readings: Readings::default(),
             max_hr: None,
             vendor_raw: HashMap::new(),
+            timezone: None,
+            utc_offset_minutes: None,
+            user_id: None,
+            event_count: 0,
+            ignored_signals: 0,
+            signal_qualities: Vec::new(),
+            low_quality_signals: 0,
+            clamped_values: 0,
+            unmapped: BTreeMap::new(),
+            field_events: HashMap::new(),
+            intraday: BTreeMap::new(),
+            tags: BTreeSet::new(),
+            latest_event_at: BTreeMap::new(),
         }
     }
 
-    fn add_event(&mut self, event: &RawEvent) {
+    fn add_event(&mut self, event: EventFields<'_>) {
+        self.event_count += 1;
+        if let Some(context) = event.context {
+            self.tags.extend(context.tags.iter().cloned());
+        }
+        if let Some(tz) = event.context.and_then(|c| c.timezone.as_ref()) {
+            self.timezone = Some(tz.clone());
+            self.utc_offset_minutes = tz.parse::<Tz>().ok().map(|tz| {
+                tz.offset_from_utc_datetime(&event.timestamp.naive_utc())
+                    .fix()
+                    .local_minus_utc()
+                    / 60
+            });
+        }
+        if let Some(user_id) = event.user_id {
+            self.user_id = Some(user_id.to_string());
+        }
+
         // Preserve vendor raw if present
-        if let Some(raw) = &event.vendor_raw {
+        if let Some(raw) = event.vendor_raw {
             let key = event
                 .event_id
-                .clone()
+                .map(str::to_string)
                 .unwrap_or_else(|| format!("event_{}", self.vendor_raw.len()));
-            self.vendor_raw.insert(key, raw.clone());
+            self.vendor_raw.insert(key, raw);
         }
 
-        match &event.payload {
+        let fields = match event.payload {
             Payload::Signal { signal } => self.add_signal(signal),
             Payload::Session { session } => self.add_session(session),
+            Payload::Summary { summary } if summary.period == SummaryPeriod::Hourly => {
+                self.add_hourly_summary(summary, event.timestamp)
+            }
             Payload::Summary { summary } => self.add_summary(summary),
             Payload::Score { score } => self.add_score(score),
+            Payload::Series { series } => self.add_series(series),
+        };
+        for field in &fields {
+            let latest = self
+                .latest_event_at
+                .entry(field_domain(field))
+                .or_insert(*event.timestamp);
+            *latest = (*latest).max(*event.timestamp);
+        }
+        if let Some(event_id) = event.event_id {
+            for field in fields {
+                self.field_events
+                    .entry(field)
+                    .or_default()
+                    .push(event_id.to_string());
+            }
+        }
+    }
+
+    /// Add an hourly summary, returning the fields it fed.
+    ///
+    /// The hour comes from the summary's `YYYY-MM-DDTHH` date (UTC), falling
+    /// back to the event timestamp. A later summary for the same hour replaces
+    /// the earlier one. Coverage is the `coverage` metric, else `wear_minutes`
+    /// over 60, else the share of steps and average heart rate reported.
+    fn add_hourly_summary(
+        &mut self,
+        summary: &SummaryPayload,
+        timestamp: &DateTime<Utc>,
+    ) -> Vec<&'static str> {
+        let start =
+            NaiveDateTime::parse_from_str(&format!("{}:00", summary.date), "%Y-%m-%dT%H:%M")
+                .map(|t| t.and_utc())
+                .ok()
+                .or_else(|| {
+                    DateTime::from_timestamp(timestamp.timestamp().div_euclid(3600) * 3600, 0)
+                })
+                .unwrap_or(*timestamp);
+        let mut hour = HourlyActivity {
+            start,
+            ..Default::default()
+        };
+        let mut wear_minutes = None;
+        let mut fields = Vec::new();
+        for (key, value) in &summary.metrics {
+            let field = match key.as_str() {
+                "total_steps" | "steps" => value.as_i64().map(|v| {
+                    hour.steps = Some(v as u32);
+                    "steps"
+                }),
+                "total_calories" | "calories" => value.as_f64().map(|v| {
+                    hour.calories = Some(v);
+                    "calories"
+                }),
+                "active_minutes" => value.as_f64().map(|v| {
+                    hour.active_minutes = Some(v);
+                    "active_minutes"
+                }),
+                "average_hr" | "avg_hr" | "heart_rate_avg" => value.as_f64().map(|v| {
+                    hour.average_hr_bpm = Some(v);
+                    "heart_rate"
+                }),
+                "max_hr" | "heart_rate_max" => value.as_f64().map(|v| {
+                    hour.max_hr_bpm = Some(v);
+                    "heart_rate"
+                }),
+                "coverage" => {
+                    hour.coverage = value.as_f64();
+                    None
+                }
+                "wear_minutes" => {
+                    wear_minutes = value.as_f64();
+                    None
+                }
+                _ => None,
+            };
+            if let Some(field) = field {
+                if !fields.contains(&field) {
+                    fields.push(field);
+                }
+            }
         }
+        let reported = [hour.steps.is_some(), hour.average_hr_bpm.is_some()];
+        let coverage = hour
+            .coverage
+            .or(wear_minutes.map(|m| m / 60.0))
+            .unwrap_or(reported.iter().filter(|r| **r).count() as f64 / reported.len() as f64);
+        if !(0.0..=1.0).contains(&coverage) {
+            self.clamped_values += 1;
+        }
+        hour.coverage = Some(coverage.clamp(0.0, 1.0));
+        self.hourly.insert(start, hour);
+        fields
     }
 
-    fn add_signal(&mut self, signal: &SignalPayload) {
-        match signal.signal_type {
+    /// Add a signal, returning the fields it fed
+    fn add_signal(&mut self, signal: &SignalPayload) -> Vec<&'static str> {
+        let weight = signal.quality.map_or(1.0, |q| q.clamp(0.0, 1.0));
+        if let Some(quality) = signal.quality {
+            if !(0.0..=1.0).contains(&quality) {
+                self.clamped_values += 1;
+            }
+            self.signal_qualities.push(weight);
+        }
+        let averaged = matches!(
+            signal.signal_type,
+            SignalType::HeartRate
+                | SignalType::HeartRateVariability
+                | SignalType::RestingHeartRate
+                | SignalType::Spo2
+                | SignalType::SkinTemperature
+                | SignalType::RespiratoryRate
+                | SignalType::Weight
+                | SignalType::BodyFat
+        );
+        if averaged && weight < MIN_SIGNAL_QUALITY {
+            self.low_quality_signals += 1;
+            return Vec::new();
+        }
+
+        let field = match signal.signal_type {
             SignalType::HeartRate => {
-                self.hr_readings.push(signal.value);
+                self.hr_readings.push_weighted(signal.value, weight);
                 if self.max_hr.is_none_or(|m| signal.value > m) {
                     self.max_hr = Some(signal.value);
                 }
+                "heart_rate"
             }
             SignalType::HeartRateVariability => {
-                self.hrv_readings.push(signal.value);
+                self.hrv_readings.push_weighted(signal.value, weight);
+                "hrv_rmssd_ms"
             }
             SignalType::RestingHeartRate => {
-                self.resting_hr_readings.push(signal.value);
+                self.resting_hr_readings.push_weighted(signal.value, weight);
+                "resting_hr_bpm"
             }
             SignalType::Spo2 => {
-                self.spo2_readings.push(signal.value);
+                self.spo2_readings.push_weighted(signal.value, weight);
+                "spo2_percentage"
             }
             SignalType::SkinTemperature => {
-                self.skin_temp_readings.push(signal.value);
+                self.skin_temp_readings.push_weighted(signal.value, weight);
+                "skin_temp_deviation_c"
             }
             SignalType::RespiratoryRate => {
-                self.respiratory_rate_readings.push(signal.value);
+                self.respiratory_rate_readings
+                    .push_weighted(signal.value, weight);
+                "sleep"
             }
             SignalType::Steps => {
                 let current = self.total_steps.unwrap_or(0);
                 self.total_steps = Some(current + signal.value as u32);
+                "steps"
             }
             SignalType::Calories => {
                 let current = self.total_calories.unwrap_or(0.0);
                 self.total_calories = Some(current + signal.value);
+                "calories"
             }
             SignalType::ActiveCalories => {
                 let current = self.active_calories.unwrap_or(0.0);
                 self.active_calories = Some(current + signal.value);
+                "calories"
             }
             SignalType::Distance => {
                 let current = self.distance_meters.unwrap_or(0.0);
                 self.distance_meters = Some(current + signal.value);
+                "distance_meters"
             }
             SignalType::ActiveMinutes => {
                 let current = self.active_minutes.unwrap_or(0.0);
                 self.active_minutes = Some(current + signal.value);
+                "active_minutes"
+            }
+            SignalType::Floors => {
+                let current = self.floors_climbed.unwrap_or(0);
+                self.floors_climbed = Some(current + signal.value as u32);
+                "floors"
+            }
+            // Estimates, so the latest reading wins
+            SignalType::Vo2Max => {
+                self.vo2_max = Some(signal.value);
+                "vo2_max"
+            }
+            SignalType::FitnessAge => {
+                self.fitness_age = Some(signal.value);
+                "vo2_max"
+            }
+            SignalType::Weight => {
+                let kg = match signal.unit {
+                    Unit::Lbs => signal.value * KG_PER_LB,
+                    _ => signal.value,
+                };
+                self.weight_readings.push_weighted(kg, weight);
+                "body"
+            }
+            SignalType::BodyFat => {
+                self.body_fat_readings.push_weighted(signal.value, weight);
+                "body"
+            }
+            SignalType::CyclePhase => match CyclePhase::from_code(signal.value) {
+                Some(phase) => {
+                    self.cycle_phase = Some(phase);
+                    "cycle_phase"
+                }
+                None => {
+                    self.ignored_signals += 1;
+                    return Vec::new();
+                }
+            },
+            _ => {
+                // Count signal types the canonical model does not use
+                self.ignored_signals += 1;
+                self.record_unmapped(
+                    format!("signal.{}", serde_name(&signal.signal_type)),
+                    Some(signal.value),
+                    Some(serde_name(&signal.unit)),
+                );
+                return Vec::new();
+            }
+        };
+        vec![field]
+    }
+
+    /// Add an intraday series, returning the fields it fed.
+    ///
+    /// Each sample is aggregated like a signal; heart rate and HRV samples are
+    /// also kept with their timestamps.
+    fn add_series(&mut self, series: &SeriesPayload) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        for sample in &series.samples {
+            let signal = SignalPayload {
+                signal_type: series.signal_type.clone(),
+                value: sample.value,
+                unit: series.unit.clone(),
+                quality: sample.quality,
+            };
+            for field in self.add_signal(&signal) {
+                if matches!(field, "heart_rate" | "hrv_rmssd_ms") {
+                    self.intraday
+                        .entry(field)
+                        .or_default()
+                        .push(IntradaySample {
+                            timestamp: sample.timestamp,
+                            value: sample.value,
+                        });
+                }
+                if !fields.contains(&field) {
+                    fields.push(field);
+                }
             }
-            _ => {} // Ignore unknown signal types
         }
+        fields
     }
 
-    fn add_session(&mut self, session: &SessionPayload) {
+    /// Add a session, returning the fields it fed
+    fn add_session(&mut self, session: &SessionPayload) -> Vec<&'static str> {
+        let mut fields = Vec::new();
         match session.session_type {
             SessionType::Sleep | SessionType::Nap => {
                 self.sleep_sessions.push(SleepData {
@@ -234,12 +930,14 @@ impl DayAccumulator {
                     end_time: session.end_time,
                     metrics: session.metrics.clone(),
                 });
+                fields.push("sleep");
             }
             SessionType::Workout => {
                 // Extract workout metrics
                 if let Some(v) = session.metrics.get("calories").and_then(|m| m.as_f64()) {
                     let current = self.active_calories.unwrap_or(0.0);
                     self.active_calories = Some(current + v);
+                    fields.push("calories");
                 }
                 if let Some(v) = session
                     .metrics
@@ -248,129 +946,378 @@ impl DayAccumulator {
                 {
                     let current = self.distance_meters.unwrap_or(0.0);
                     self.distance_meters = Some(current + v);
+                    fields.push("distance_meters");
                 }
             }
             _ => {}
         }
+        fields
     }
 
-    fn add_summary(&mut self, summary: &SummaryPayload) {
+    /// Add a daily summary, returning the fields it fed
+    fn add_summary(&mut self, summary: &SummaryPayload) -> Vec<&'static str> {
+        let mut fields = Vec::new();
         // Daily summaries typically contain aggregated data
         for (key, value) in &summary.metrics {
-            match key.as_str() {
-                "total_steps" | "steps" => {
-                    if let Some(v) = value.as_i64() {
-                        self.total_steps = Some(v as u32);
-                    }
-                }
-                "total_calories" | "calories" => {
-                    if let Some(v) = value.as_f64() {
-                        self.total_calories = Some(v);
-                    }
-                }
-                "active_calories" => {
-                    if let Some(v) = value.as_f64() {
-                        self.active_calories = Some(v);
-                    }
-                }
-                "distance_meters" | "distance" => {
-                    if let Some(v) = value.as_f64() {
-                        self.distance_meters = Some(v);
-                    }
-                }
-                "active_minutes" => {
-                    if let Some(v) = value.as_f64() {
-                        self.active_minutes = Some(v);
-                    }
-                }
-                "resting_heart_rate" | "resting_hr" => {
-                    if let Some(v) = value.as_f64() {
-                        self.resting_hr_readings.push(v);
-                    }
-                }
-                "hrv" | "hrv_rmssd" => {
-                    if let Some(v) = value.as_f64() {
-                        self.hrv_readings.push(v);
-                    }
-                }
-                "spo2" | "avg_spo2" => {
-                    if let Some(v) = value.as_f64() {
-                        self.spo2_readings.push(v);
-                    }
-                }
-                "body_battery" => {
-                    if let Some(v) = value.as_f64() {
-                        self.recovery_score = Some(v);
-                    }
+            let field = match key.as_str() {
+                "total_steps" | "steps" => value.as_i64().map(|v| {
+                    self.total_steps = Some(v as u32);
+                    "steps"
+                }),
+                "total_calories" | "calories" => value.as_f64().map(|v| {
+                    self.total_calories = Some(v);
+                    "calories"
+                }),
+                "active_calories" => value.as_f64().map(|v| {
+                    self.active_calories = Some(v);
+                    "calories"
+                }),
+                "resting_calories" | "bmr_calories" | "bmr_kilocalories" => {
+                    value.as_f64().map(|v| {
+                        self.resting_calories = Some(v);
+                        "calories"
+                    })
                 }
-                "training_load" | "strain" => {
-                    if let Some(v) = value.as_f64() {
-                        self.strain_score = Some(v);
-                    }
+                "distance_meters" | "distance" => value.as_f64().map(|v| {
+                    self.distance_meters = Some(v);
+                    "distance_meters"
+                }),
+                "active_minutes" => value.as_f64().map(|v| {
+                    self.active_minutes = Some(v);
+                    "active_minutes"
+                }),
+                "moderate_intensity_minutes" => value.as_f64().map(|v| {
+                    self.moderate_intensity_minutes = Some(v);
+                    "active_minutes"
+                }),
+                "vigorous_intensity_minutes" => value.as_f64().map(|v| {
+                    self.vigorous_intensity_minutes = Some(v);
+                    "active_minutes"
+                }),
+                "floors_climbed" | "floors_ascended" | "floors" => value.as_i64().map(|v| {
+                    self.floors_climbed = Some(v as u32);
+                    "floors"
+                }),
+                "elevation_gain_meters" | "elevation_gain" => value.as_f64().map(|v| {
+                    self.elevation_gain_meters = Some(v);
+                    "floors"
+                }),
+                "vo2_max" | "vo2max" => value.as_f64().map(|v| {
+                    self.vo2_max = Some(v);
+                    "vo2_max"
+                }),
+                "fitness_age" => value.as_f64().map(|v| {
+                    self.fitness_age = Some(v);
+                    "vo2_max"
+                }),
+                "weight_kg" | "weight" => value.as_f64().map(|v| {
+                    self.weight_readings.push(v);
+                    "body"
+                }),
+                "body_fat_percentage" | "body_fat" => value.as_f64().map(|v| {
+                    self.body_fat_readings.push(v);
+                    "body"
+                }),
+                // hr_zone_0_minutes through hr_zone_5_minutes
+                key if key.starts_with("hr_zone_") => key
+                    .strip_prefix("hr_zone_")
+                    .and_then(|k| k.strip_suffix("_minutes"))
+                    .and_then(|zone| zone.parse().ok())
+                    .and_then(|zone| self.hr_zone_minutes.zone_mut(zone))
+                    .zip(value.as_f64())
+                    .map(|(zone, v)| {
+                        *zone = Some(v);
+                        "hr_zone_minutes"
+                    }),
+                "resting_heart_rate" | "resting_hr" => value.as_f64().map(|v| {
+                    self.resting_hr_readings.push(v);
+                    "resting_hr_bpm"
+                }),
+                "hrv" | "hrv_rmssd" => value.as_f64().map(|v| {
+                    self.hrv_readings.push(v);
+                    "hrv_rmssd_ms"
+                }),
+                "spo2" | "avg_spo2" => value.as_f64().map(|v| {
+                    self.spo2_readings.push(v);
+                    "spo2_percentage"
+                }),
+                "body_battery" => value.as_f64().map(|v| {
+                    self.recovery_score = Some(v);
+                    "recovery_score"
+                }),
+                "body_battery_charged" => value.as_f64().map(|v| {
+                    self.body_battery.charged = Some(v);
+                    "body_battery"
+                }),
+                "body_battery_drained" => value.as_f64().map(|v| {
+                    self.body_battery.drained = Some(v);
+                    "body_battery"
+                }),
+                "body_battery_lowest" | "body_battery_min" => value.as_f64().map(|v| {
+                    self.body_battery.lowest = Some(v);
+                    "body_battery"
+                }),
+                "body_battery_highest" | "body_battery_max" => value.as_f64().map(|v| {
+                    self.body_battery.highest = Some(v);
+                    "body_battery"
+                }),
+                // Negative stress levels are "not enough data" sentinels
+                "stress_avg" | "average_stress" => value.as_f64().filter(|v| *v >= 0.0).map(|v| {
+                    self.stress.average = Some(v);
+                    "stress"
+                }),
+                "stress_max" | "max_stress" => value.as_f64().filter(|v| *v >= 0.0).map(|v| {
+                    self.stress.max = Some(v);
+                    "stress"
+                }),
+                "rest_stress_minutes" => value.as_f64().map(|v| {
+                    self.stress.rest_minutes = Some(v);
+                    "stress"
+                }),
+                "low_stress_minutes" => value.as_f64().map(|v| {
+                    self.stress.low_minutes = Some(v);
+                    "stress"
+                }),
+                "medium_stress_minutes" => value.as_f64().map(|v| {
+                    self.stress.medium_minutes = Some(v);
+                    "stress"
+                }),
+                "high_stress_minutes" => value.as_f64().map(|v| {
+                    self.stress.high_minutes = Some(v);
+                    "stress"
+                }),
+                "waking_respiratory_rate" => value.as_f64().map(|v| {
+                    self.respiration.waking_average = Some(v);
+                    "respiration"
+                }),
+                "respiratory_rate_lowest" | "respiratory_rate_min" => value.as_f64().map(|v| {
+                    self.respiration.lowest = Some(v);
+                    "respiration"
+                }),
+                "respiratory_rate_highest" | "respiratory_rate_max" => value.as_f64().map(|v| {
+                    self.respiration.highest = Some(v);
+                    "respiration"
+                }),
+                "training_load" | "strain" => value.as_f64().map(|v| {
+                    self.strain_score = Some(v);
+                    "strain_score"
+                }),
+                "cycle_phase" => value
+                    .as_str()
+                    .and_then(CyclePhase::parse)
+                    .or_else(|| value.as_f64().and_then(CyclePhase::from_code))
+                    .map(|phase| {
+                        self.cycle_phase = Some(phase);
+                        "cycle_phase"
+                    }),
+                _ => {
+                    self.record_unmapped(format!("summary.{key}"), value.as_f64(), None);
+                    None
                 }
-                _ => {}
-            }
+            };
+            fields.extend(field.filter(|f| !fields.contains(f)));
         }
+        fields
     }
 
-    fn add_score(&mut self, score: &ScorePayload) {
+    /// Add a score, returning the fields it fed
+    fn add_score(&mut self, score: &ScorePayload) -> Vec<&'static str> {
         // Normalize score to 0-100 range
         let normalized = normalize_score(score.value, score.scale.min, score.scale.max);
 
         match score.score_type {
             ScoreType::Recovery | ScoreType::BodyBattery => {
                 self.recovery_score = Some(normalized);
+                vec!["recovery_score"]
             }
             ScoreType::Strain | ScoreType::TrainingLoad => {
                 self.strain_score = Some(normalized);
+                vec!["strain_score"]
+            }
+            _ => {
+                self.record_unmapped(
+                    format!("score.{}", serde_name(&score.score_type)),
+                    Some(score.value),
+                    None,
+                );
+                Vec::new()
             }
-            _ => {}
+        }
+    }
+
+    /// Count a reading that no canonical field takes
+    fn record_unmapped(&mut self, key: String, value: Option<f64>, unit: Option<String>) {
+        let unmapped = self.unmapped.entry(key).or_default();
+        unmapped.count += 1;
+        if let Some(value) = value.filter(|v| v.is_finite()) {
+            unmapped.readings.push(value);
+        }
+        if unit.is_some() {
+            unmapped.unit = unit;
         }
     }
 
     fn into_canonical(
-        self,
+        mut self,
         date: &str,
         timezone: &str,
         device_id: &str,
     ) -> Result<CanonicalWearSignals, ComputeError> {
         // Build canonical sleep from sessions
         let sleep = self.build_canonical_sleep();
+        let warnings = self.warnings(date);
+
+        for samples in self.intraday.values_mut() {
+            samples.sort_by_key(|s| s.timestamp);
+        }
+        // An HRV series takes precedence over point readings
+        let (hrv_rmssd_ms, hrv_cv) = match self.intraday.get("hrv_rmssd_ms") {
+            Some(samples) => nightly_hrv(samples, &sleep),
+            None => (self.hrv_readings.mean(), None),
+        };
 
         // Build canonical recovery from readings
         let recovery = CanonicalRecovery {
-            hrv_rmssd_ms: average(&self.hrv_readings),
-            resting_hr_bpm: average(&self.resting_hr_readings),
+            hrv_rmssd_ms,
+            hrv_cv,
+            hrv_scale: None,
+            resting_hr_bpm: self.resting_hr_readings.mean(),
             vendor_recovery_score: self.recovery_score,
-            skin_temp_deviation_c: average(&self.skin_temp_readings),
-            spo2_percentage: average(&self.spo2_readings),
+            skin_temp_deviation_c: self.skin_temp_readings.mean(),
+            spo2_percentage: self.spo2_readings.mean(),
+            cycle_phase: self.cycle_phase,
+            body_battery: self.body_battery,
+            stress: self.stress,
+            respiration: self.respiration,
         };
 
         // Build canonical activity
+        // Hourly summaries fill in day totals that nothing else reported
+        let hourly: Vec<HourlyActivity> = std::mem::take(&mut self.hourly).into_values().collect();
+        let hourly_sum = |metric: fn(&HourlyActivity) -> Option<f64>| {
+            hourly.iter().filter_map(metric).reduce(|a, b| a + b)
+        };
+        let mut hourly_hr = Readings::default();
+        for hour in &hourly {
+            if let Some(hr) = hour.average_hr_bpm {
+                hourly_hr.push_weighted(hr, hour.coverage.unwrap_or(1.0));
+            }
+        }
+
         let activity = CanonicalActivity {
             vendor_strain_score: self.strain_score,
-            calories: self.total_calories,
+            calories: self.total_calories.or_else(|| hourly_sum(|h| h.calories)),
             active_calories: self.active_calories,
-            average_hr_bpm: average(&self.hr_readings),
-            max_hr_bpm: self.max_hr,
+            resting_calories: self.resting_calories,
+            average_hr_bpm: self.hr_readings.mean().or_else(|| hourly_hr.mean()),
+            max_hr_bpm: self
+                .max_hr
+                .or_else(|| hourly.iter().filter_map(|h| h.max_hr_bpm).reduce(f64::max)),
             distance_meters: self.distance_meters,
-            steps: self.total_steps,
-            active_minutes: self.active_minutes,
+            steps: self
+                .total_steps
+                .or_else(|| hourly.iter().filter_map(|h| h.steps).reduce(|a, b| a + b)),
+            active_minutes: self
+                .active_minutes
+                .or_else(|| hourly_sum(|h| h.active_minutes))
+                .or_else(|| {
+                    self.moderate_intensity_minutes
+                        .zip(self.vigorous_intensity_minutes)
+                        .map(|(m, v)| m + v)
+                }),
+            moderate_intensity_minutes: self.moderate_intensity_minutes,
+            vigorous_intensity_minutes: self.vigorous_intensity_minutes,
+            floors_climbed: self.floors_climbed,
+            elevation_gain_meters: self.elevation_gain_meters,
+            workouts: Vec::new(),
+            hr_zone_minutes: self.hr_zone_minutes,
+            vo2_max: self.vo2_max,
+            fitness_age: self.fitness_age,
+            hourly,
         };
 
         Ok(CanonicalWearSignals {
             vendor: provider_to_vendor(&self.provider),
             date: date.to_string(),
             device_id: device_id.to_string(),
-            timezone: timezone.to_string(),
+            user_id: self.user_id,
+            // Prefer the timezone reported by the events themselves
+            timezone: self.timezone.unwrap_or_else(|| timezone.to_string()),
+            utc_offset_minutes: self.utc_offset_minutes,
             observed_at: Utc::now(),
             sleep,
             recovery,
             activity,
+            body: CanonicalBody {
+                weight_kg: self.weight_readings.mean(),
+                body_fat_percentage: self.body_fat_readings.mean(),
+            },
+            input_quality: average(&self.signal_qualities),
             vendor_raw: self.vendor_raw,
+            field_sources: HashMap::new(),
+            field_events: self
+                .field_events
+                .into_iter()
+                .map(|(field, events)| (field.to_string(), events))
+                .collect(),
+            intraday: self
+                .intraday
+                .into_iter()
+                .map(|(field, samples)| (field.to_string(), samples))
+                .collect(),
+            latest_event_at: self
+                .latest_event_at
+                .into_iter()
+                .map(|(domain, at)| (domain.to_string(), at))
+                .collect(),
+            tags: self.tags.into_iter().collect(),
+            warnings,
+            unmapped_signals: self
+                .unmapped
+                .into_iter()
+                .map(|(key, unmapped)| {
+                    let signal = UnmappedSignal {
+                        count: unmapped.count,
+                        mean: unmapped.readings.mean(),
+                        unit: unmapped.unit,
+                    };
+                    (key, signal)
+                })
+                .collect(),
         })
     }
 
+    /// Warnings for input the day left out or adjusted
+    fn warnings(&self, date: &str) -> Vec<Warning> {
+        let dropped_sleep = self.sleep_sessions.len().saturating_sub(1);
+        [
+            (
+                WarningCode::UnknownSignal,
+                self.ignored_signals,
+                "signals of unknown type or with unrecognized values ignored",
+            ),
+            (
+                WarningCode::LowQualitySignal,
+                self.low_quality_signals,
+                "signals ignored for quality below the minimum",
+            ),
+            (
+                WarningCode::DroppedSleepSession,
+                dropped_sleep,
+                "sleep sessions left out in favor of the longest one",
+            ),
+            (
+                WarningCode::ClampedValue,
+                self.clamped_values,
+                "quality or coverage values outside 0-1 clamped",
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, count, _)| *count > 0)
+        .map(|(code, count, what)| Warning::for_day(code, date, format!("{count} {what}")))
+        .collect()
+    }
+
     fn build_canonical_sleep(&self) -> CanonicalSleep {
         if self.sleep_sessions.is_empty() {
             return CanonicalSleep::default();
@@ -425,7 +1372,12 @@ impl DayAccumulator {
                         .metrics
                         .get("respiratory_rate")
                         .and_then(|v| v.as_f64())
-                        .or_else(|| average(&self.respiratory_rate_readings)),
+                        .or_else(|| self.respiratory_rate_readings.mean()),
+                    hypnogram: sleep
+                        .metrics
+                        .get("hypnogram")
+                        .map(parse_hypnogram)
+                        .unwrap_or_default(),
                 }
             }
             None => CanonicalSleep::default(),
@@ -433,6 +1385,14 @@ impl DayAccumulator {
     }
 }
 
+/// Serialized name of a unit-like enum variant (`floors`, `kcal`, custom names as-is)
+fn serde_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => "unknown".to_string(),
+    }
+}
+
 fn extract_date(timestamp: &DateTime<Utc>, context: Option<&Context>) -> String {
     // Try to use timezone from context if available
     let date = if let Some(ctx) = context {
@@ -449,6 +1409,24 @@ fn extract_date(timestamp: &DateTime<Utc>, context: Option<&Context>) -> String
     date
 }
 
+/// HSI domain (window namespace) a canonical field is emitted in
+fn field_domain(field: &str) -> &'static str {
+    match field {
+        "sleep" => "sleep",
+        "body" => "body",
+        "hrv_rmssd_ms"
+        | "resting_hr_bpm"
+        | "recovery_score"
+        | "spo2_percentage"
+        | "skin_temp_deviation_c"
+        | "cycle_phase"
+        | "stress"
+        | "respiration"
+        | "body_battery" => "physiology",
+        _ => "activity",
+    }
+}
+
 fn provider_to_vendor(provider: &Provider) -> Vendor {
     match provider {
         Provider::Whoop => Vendor::Whoop,
@@ -466,6 +1444,73 @@ fn average(values: &[f64]) -> Option<f64> {
     }
 }
 
+/// Parse a `hypnogram` session metric: an array of
+/// `{"stage", "start", "duration_minutes"}` objects. Malformed entries are skipped.
+fn parse_hypnogram(value: &MetricValue) -> Vec<HypnogramSegment> {
+    let MetricValue::Array(items) = value else {
+        return Vec::new();
+    };
+    let mut segments: Vec<HypnogramSegment> = items
+        .iter()
+        .filter_map(|item| {
+            let MetricValue::Object(fields) = item else {
+                return None;
+            };
+            let stage = match fields.get("stage")?.as_str()? {
+                "awake" | "wake" => SleepStage::Awake,
+                "light" | "core" => SleepStage::Light,
+                "deep" => SleepStage::Deep,
+                "rem" => SleepStage::Rem,
+                _ => SleepStage::Unknown,
+            };
+            Some(HypnogramSegment {
+                stage,
+                start: fields.get("start")?.as_str()?.parse().ok()?,
+                duration_minutes: fields.get("duration_minutes")?.as_f64()?,
+            })
+        })
+        .collect();
+    segments.sort_by_key(|s| s.start);
+    segments
+}
+
+/// Median and coefficient of variation of the RMSSD samples taken during the
+/// main sleep (all samples when none fall inside it)
+fn nightly_hrv(samples: &[IntradaySample], sleep: &CanonicalSleep) -> (Option<f64>, Option<f64>) {
+    let during_sleep = |s: &&IntradaySample| match (sleep.start_time, sleep.end_time) {
+        (Some(start), Some(end)) => s.timestamp >= start && s.timestamp <= end,
+        _ => false,
+    };
+    let mut values: Vec<f64> = samples
+        .iter()
+        .filter(during_sleep)
+        .map(|s| s.value)
+        .collect();
+    if values.is_empty() {
+        values = samples.iter().map(|s| s.value).collect();
+    }
+    if values.is_empty() {
+        return (None, None);
+    }
+
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    let median = if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    };
+
+    let cv = average(&values)
+        .filter(|&mean| values.len() > 1 && mean > 0.0)
+        .map(|mean| {
+            let variance =
+                values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+            variance.sqrt() / mean
+        });
+    (Some(median), cv)
+}
+
 fn normalize_score(value: f64, min: f64, max: f64) -> f64 {
     if (max - min).abs() < f64::EPSILON {
         return value;
@@ -566,6 +1611,234 @@ mod tests {
         assert_eq!(sig.recovery.vendor_recovery_score, Some(78.0));
     }
 
+    #[test]
+    fn test_to_canonical_records_field_events() {
+        let events = create_test_events();
+        let signals = RawEventAdapter::to_canonical(&events, "UTC", "test-device").unwrap();
+
+        let id = |i: usize| events[i].event_id.clone().unwrap();
+        let field_events = &signals[0].field_events;
+        assert_eq!(field_events["sleep"], vec![id(0)]);
+        assert_eq!(field_events["recovery_score"], vec![id(1)]);
+        assert_eq!(field_events["hrv_rmssd_ms"], vec![id(2)]);
+        assert_eq!(field_events["resting_hr_bpm"], vec![id(3)]);
+    }
+
+    #[test]
+    fn test_signal_quality_weights_averages() {
+        let source = Source {
+            provider: Provider::Garmin,
+            device_model: None,
+            device_id: None,
+            firmware_version: None,
+        };
+        let timestamp = "2024-01-15T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let hrv = |value: f64, quality: Option<f64>| {
+            let signal = SignalPayload {
+                signal_type: SignalType::HeartRateVariability,
+                value,
+                unit: Unit::Ms,
+                quality,
+            };
+            RawEvent::signal(timestamp, source.clone(), signal)
+        };
+
+        // The 0.2-quality reading is dropped; the rest are weighted 1 : 0.5
+        let events = vec![hrv(60.0, None), hrv(90.0, Some(0.5)), hrv(10.0, Some(0.2))];
+        let signals = RawEventAdapter::to_canonical(&events, "UTC", "test-device").unwrap();
+        assert_eq!(signals[0].recovery.hrv_rmssd_ms, Some(70.0));
+        assert!((signals[0].input_quality.unwrap() - 0.35).abs() < 1e-9);
+
+        // All readings below the floor leave HRV missing
+        let signals =
+            RawEventAdapter::to_canonical(&[hrv(10.0, Some(0.1))], "UTC", "test-device").unwrap();
+        assert_eq!(signals[0].recovery.hrv_rmssd_ms, None);
+    }
+
+    #[test]
+    fn test_series_aggregates_nightly_hrv() {
+        let source = Source {
+            provider: Provider::Garmin,
+            device_model: None,
+            device_id: None,
+            firmware_version: None,
+        };
+        let ts = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let sample = |t: &str, value: f64| SeriesSample {
+            timestamp: ts(t),
+            value,
+            quality: None,
+        };
+        let sleep = RawEvent::session(
+            ts("2024-01-15T07:00:00Z"),
+            source.clone(),
+            SessionPayload {
+                session_type: SessionType::Sleep,
+                start_time: ts("2024-01-14T23:00:00Z"),
+                end_time: ts("2024-01-15T07:00:00Z"),
+                metrics: HashMap::new(),
+            },
+        );
+        let mut hrv = RawEvent::series(
+            ts("2024-01-15T07:00:00Z"),
+            source.clone(),
+            SeriesPayload {
+                signal_type: SignalType::HeartRateVariability,
+                unit: Unit::Ms,
+                samples: vec![
+                    sample("2024-01-15T01:00:00Z", 40.0),
+                    sample("2024-01-15T03:00:00Z", 60.0),
+                    sample("2024-01-15T02:00:00Z", 50.0),
+                    // Daytime sample, outside the sleep window
+                    sample("2024-01-15T12:00:00Z", 100.0),
+                ],
+            },
+        );
+        hrv.event_id = Some("hrv-series".to_string());
+        // Point readings are superseded by the series
+        let point = RawEvent::signal(
+            ts("2024-01-15T07:00:00Z"),
+            source,
+            SignalPayload {
+                signal_type: SignalType::HeartRateVariability,
+                value: 80.0,
+                unit: Unit::Ms,
+                quality: None,
+            },
+        );
+
+        let canonical = RawEventAdapter::to_canonical(&[sleep, hrv, point], "UTC", "dev").unwrap();
+        let day = &canonical[0];
+        assert_eq!(day.recovery.hrv_rmssd_ms, Some(50.0));
+        let cv = day.recovery.hrv_cv.unwrap();
+        assert!((cv - (200.0f64 / 3.0).sqrt() / 50.0).abs() < 1e-9, "{cv}");
+
+        let samples = &day.intraday["hrv_rmssd_ms"];
+        assert_eq!(samples.len(), 4);
+        assert!(samples.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+        assert!(day.field_events["hrv_rmssd_ms"].contains(&"hrv-series".to_string()));
+    }
+
+    #[test]
+    fn test_cycle_phase_signal_and_summary() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","event_id":"cp-1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"oura"},"record_type":"signal","payload":{"signal":{"type":"cycle_phase","value":3,"unit":"level"}}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-16T08:00:00Z","source":{"provider":"oura"},"record_type":"summary","payload":{"summary":{"period":"daily","date":"2024-01-16","metrics":{"cycle_phase":"ovulation"}}}}
+{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-17T08:00:00Z","source":{"provider":"oura"},"record_type":"signal","payload":{"signal":{"type":"cycle_phase","value":7,"unit":"level"}}}"#;
+
+        let events = RawEventAdapter::parse_ndjson(ndjson).unwrap();
+        let canonical = RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        assert_eq!(canonical.len(), 3);
+        assert_eq!(canonical[0].recovery.cycle_phase, Some(CyclePhase::Luteal));
+        assert_eq!(canonical[0].field_events["cycle_phase"], vec!["cp-1"]);
+        assert_eq!(
+            canonical[1].recovery.cycle_phase,
+            Some(CyclePhase::Ovulatory)
+        );
+        // Unknown phase codes are ignored
+        assert_eq!(canonical[2].recovery.cycle_phase, None);
+    }
+
+    #[test]
+    fn test_hr_zone_summary_metrics() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"polar"},"record_type":"summary","payload":{"summary":{"period":"daily","date":"2024-01-15","metrics":{"hr_zone_1_minutes":40,"hr_zone_5_minutes":3.5,"hr_zone_9_minutes":1}}}}"#;
+
+        let events = RawEventAdapter::parse_ndjson(ndjson).unwrap();
+        let canonical = RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        let zones = canonical[0].activity.hr_zone_minutes;
+        assert_eq!(zones.zone_1, Some(40.0));
+        assert_eq!(zones.zone_5, Some(3.5));
+        assert_eq!(zones.zone_2, None);
+    }
+
+    #[test]
+    fn test_floor_signals_summed() {
+        let floors = |hour: u32, value: u32| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T{hour:02}:00:00Z","source":{{"provider":"fitbit"}},"record_type":"signal","payload":{{"signal":{{"type":"floors","value":{value},"unit":"count"}}}}}}"#
+            )
+        };
+        let ndjson = [floors(9, 3), floors(12, 2), floors(18, 4)].join("\n");
+        let events = RawEventAdapter::parse_ndjson(&ndjson).unwrap();
+        let canonical = RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        assert_eq!(canonical[0].activity.floors_climbed, Some(9));
+        assert!(canonical[0].unmapped_signals.is_empty());
+
+        let summary = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T20:00:00Z","source":{"provider":"garmin"},"record_type":"summary","payload":{"summary":{"period":"daily","date":"2024-01-15","metrics":{"floors_ascended":11,"elevation_gain_meters":33.5}}}}"#;
+        let events = RawEventAdapter::parse_ndjson(summary).unwrap();
+        let canonical = RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        assert_eq!(canonical[0].activity.floors_climbed, Some(11));
+        assert_eq!(canonical[0].activity.elevation_gain_meters, Some(33.5));
+    }
+
+    #[test]
+    fn test_unmapped_signals_accounted() {
+        let ndjson = [
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"glucose","value":5.0,"unit":"mmol/l"}}}"#,
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T09:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"glucose","value":6.0,"unit":"mmol/l"}}}"#,
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T20:00:00Z","source":{"provider":"garmin"},"record_type":"summary","payload":{"summary":{"period":"daily","date":"2024-01-15","metrics":{"steps":9000,"intensity_minutes":42}}}}"#,
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T20:00:00Z","source":{"provider":"garmin"},"record_type":"score","payload":{"score":{"type":"readiness","value":71,"scale":{"min":0,"max":100}}}}"#,
+        ]
+        .join("\n");
+        let events = RawEventAdapter::parse_ndjson(&ndjson).unwrap();
+
+        let counts = RawEventAdapter::unmapped_signals(&events);
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("score.readiness".to_string(), 1),
+                ("signal.glucose".to_string(), 2),
+                ("summary.intensity_minutes".to_string(), 1),
+            ]
+        );
+
+        let canonical = RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        let glucose = &canonical[0].unmapped_signals["signal.glucose"];
+        assert_eq!(glucose.count, 2);
+        assert_eq!(glucose.mean, Some(5.5));
+        assert_eq!(glucose.unit.as_deref(), Some("mmol/l"));
+        assert_eq!(canonical[0].activity.steps, Some(9000));
+    }
+
+    #[test]
+    fn test_hourly_summaries() {
+        let hour = |hour: &str, metrics: &str| {
+            format!(
+                r#"{{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T{hour}:59:00Z","source":{{"provider":"garmin"}},"record_type":"summary","payload":{{"summary":{{"period":"hourly","date":"2024-01-15T{hour}","metrics":{metrics}}}}}}}"#
+            )
+        };
+        let ndjson = [
+            hour(
+                "08",
+                r#"{"steps":1000,"average_hr":70,"max_hr":95,"wear_minutes":60}"#,
+            ),
+            hour("09", r#"{"steps":100,"average_hr":90}"#),
+            // A later summary for an hour replaces the earlier one
+            hour(
+                "09",
+                r#"{"steps":500,"average_hr":100,"max_hr":130,"wear_minutes":30}"#,
+            ),
+            hour("10", r#"{"steps":200}"#),
+        ]
+        .join("\n");
+        let events = RawEventAdapter::parse_ndjson(&ndjson).unwrap();
+        let signals = RawEventAdapter::to_canonical(&events, "UTC", "test-device").unwrap();
+        let activity = &signals[0].activity;
+
+        assert_eq!(activity.hourly.len(), 3);
+        assert_eq!(
+            activity.hourly[1].start.to_rfc3339(),
+            "2024-01-15T09:00:00+00:00"
+        );
+        assert_eq!(activity.hourly[1].steps, Some(500));
+        assert_eq!(activity.hourly[1].coverage, Some(0.5));
+        assert_eq!(activity.hourly[2].coverage, Some(0.5));
+
+        // Day totals come from the hours; heart rate is weighted by coverage
+        assert_eq!(activity.steps, Some(1700));
+        assert_eq!(activity.average_hr_bpm, Some(80.0));
+        assert_eq!(activity.max_hr_bpm, Some(130.0));
+    }
+
     #[test]
     fn test_parse_ndjson() {
         let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"whoop"},"record_type":"signal","payload":{"signal":{"type":"heart_rate","value":72.0,"unit":"bpm"}}}
@@ -575,6 +1848,59 @@ mod tests {
         assert_eq!(events.len(), 2);
     }
 
+    #[test]
+    fn test_parse_modes() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"whoop"},"record_type":"signal","payload":{"signal":{"type":"heart_rate","value":72.0,"unit":"bpm"}}}
+not json
+{"schema_version":"wear.raw_event.v0","event_id":"old-1","timestamp":"2024-01-15T08:01:00Z","source":{"provider":"whoop"},"record_type":"signal","payload":{"signal":{"type":"heart_rate","value":74.0,"unit":"bpm"}}}"#;
+
+        let err = RawEventAdapter::parse_ndjson_with_mode(ndjson, ParseMode::Strict).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+
+        let parsed = RawEventAdapter::parse_ndjson_with_mode(ndjson, ParseMode::Lenient).unwrap();
+        assert_eq!(parsed.events.len(), 1);
+        assert_eq!(parsed.dropped.len(), 2);
+        assert_eq!(parsed.dropped[0].line, 2);
+        assert_eq!(parsed.dropped[1].line, 3);
+        assert_eq!(parsed.dropped[1].event_id.as_deref(), Some("old-1"));
+
+        let array = format!("[{}, 42]", ndjson.lines().next().unwrap());
+        assert!(RawEventAdapter::parse_array_with_mode(&array, ParseMode::Strict).is_err());
+        let parsed = RawEventAdapter::parse_array_with_mode(&array, ParseMode::Lenient).unwrap();
+        assert_eq!(parsed.events.len(), 1);
+        assert_eq!(parsed.dropped[0].line, 2);
+    }
+
+    #[test]
+    fn test_parse_any_dispatches_on_version() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"whoop"},"record_type":"signal","payload":{"signal":{"type":"heart_rate","value":72.0,"unit":"bpm"}}}
+{"schema_version":"wear.raw_event.v2","event_id":"hr","timestamp":"2024-01-15T09:00:00Z","source":{"provider":"whoop"},"record_type":"series","payload":{"series":{"type":"heart_rate","unit":"bpm","samples":[{"timestamp":"2024-01-15T09:00:00Z","value":70.0},{"timestamp":"2024-01-15T09:01:00Z","value":68.0}]}}}"#;
+
+        let events = RawEventAdapter::parse_any(ndjson).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.schema_version == SCHEMA_VERSION));
+        let canonical = RawEventAdapter::to_canonical(&events, "UTC", "device").unwrap();
+        assert_eq!(canonical[0].activity.average_hr_bpm, Some(70.0));
+
+        let future = ndjson.replace("wear.raw_event.v2", "wear.raw_event.v3");
+        let err = RawEventAdapter::parse_any(&future).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+        assert!(err.to_string().contains("wear.raw_event.v3"), "{err}");
+    }
+
+    #[test]
+    fn test_iter_ndjson() {
+        let ndjson = r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"whoop"},"record_type":"signal","payload":{"signal":{"type":"heart_rate","value":72.0,"unit":"bpm"}}}
+
+not json"#;
+
+        let results: Vec<_> = RawEventAdapter::iter_ndjson(ndjson.as_bytes()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        let err = results[1].as_ref().unwrap_err().to_string();
+        assert!(err.contains("line 3"), "{err}");
+    }
+
     #[test]
     fn test_validate_events() {
         let events = create_test_events();
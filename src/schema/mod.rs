@@ -2,10 +2,17 @@
 //!
 //! This module defines the vendor-agnostic input schema for wearable data.
 //! It supports both individual signal events (streaming) and session/summary
-//! records (batch processing).
+//! records (batch processing). Newer schema versions (`wear.raw_event.v2`)
+//! are converted to v1 on input.
 
 mod adapter;
+mod ordering;
 mod raw_event;
+mod raw_event_ref;
+mod raw_event_v2;
 
 pub use adapter::*;
+pub use ordering::*;
 pub use raw_event::*;
+pub use raw_event_ref::*;
+pub use raw_event_v2::*;
@@ -0,0 +1,149 @@
+//! Chronological order of input events
+//!
+//! Canonical conversion sorts events by timestamp, so within-day order never
+//! changes the output. Heavily shuffled input usually means something upstream
+//! is wrong (merged archives, clock resets), so the amount of disorder is
+//! measured and can be warned about or rejected.
+
+use crate::error::ComputeError;
+use crate::schema::raw_event::RawEvent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Share of out-of-order events above which input counts as heavily out of order
+pub const DEFAULT_MAX_OUT_OF_ORDER: f64 = 0.05;
+
+/// How far a batch of events is from chronological order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventOrder {
+    /// Events in the batch
+    pub events: usize,
+    /// Events earlier than some event before them
+    pub out_of_order: usize,
+}
+
+impl EventOrder {
+    /// Measure the order of timestamps as they arrive
+    pub fn measure(timestamps: impl IntoIterator<Item = DateTime<Utc>>) -> Self {
+        let mut order = Self::default();
+        let mut latest: Option<DateTime<Utc>> = None;
+        for timestamp in timestamps {
+            order.events += 1;
+            if latest.is_some_and(|latest| timestamp < latest) {
+                order.out_of_order += 1;
+            }
+            latest = latest.max(Some(timestamp));
+        }
+        order
+    }
+
+    /// Share of events that are out of order (0 for an empty batch)
+    pub fn fraction(&self) -> f64 {
+        if self.events == 0 {
+            0.0
+        } else {
+            self.out_of_order as f64 / self.events as f64
+        }
+    }
+}
+
+impl fmt::Display for EventOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} events ({:.1}%) are earlier than an event before them",
+            self.out_of_order,
+            self.events,
+            self.fraction() * 100.0
+        )
+    }
+}
+
+/// What happens when input is heavily out of order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisorderPolicy {
+    /// Sort the events and report the disorder
+    #[default]
+    Warn,
+    /// Reject the batch
+    Fail,
+}
+
+/// Tolerance for out-of-order input
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OrderingConfig {
+    /// Share of out-of-order events tolerated without a warning or failure
+    pub max_out_of_order: f64,
+    pub policy: DisorderPolicy,
+}
+
+impl Default for OrderingConfig {
+    fn default() -> Self {
+        Self {
+            max_out_of_order: DEFAULT_MAX_OUT_OF_ORDER,
+            policy: DisorderPolicy::default(),
+        }
+    }
+}
+
+impl OrderingConfig {
+    pub fn with_max_out_of_order(mut self, fraction: f64) -> Self {
+        self.max_out_of_order = fraction;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: DisorderPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Check a batch of events, in the order they arrived.
+    ///
+    /// Returns the measured order when the batch is heavily out of order under
+    /// [`DisorderPolicy::Warn`], and an error under [`DisorderPolicy::Fail`].
+    pub fn check(&self, events: &[RawEvent]) -> Result<Option<EventOrder>, ComputeError> {
+        let order = EventOrder::measure(events.iter().map(|e| e.timestamp));
+        if order.fraction() <= self.max_out_of_order {
+            return Ok(None);
+        }
+        match self.policy {
+            DisorderPolicy::Warn => Ok(Some(order)),
+            DisorderPolicy::Fail => Err(ComputeError::OutOfOrder(order.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_event_order_and_policy() {
+        let at = |hour| Utc.with_ymd_and_hms(2024, 1, 15, hour, 0, 0).unwrap();
+        let order = EventOrder::measure([at(1), at(3), at(2), at(4), at(0)]);
+        assert_eq!(order.events, 5);
+        assert_eq!(order.out_of_order, 2);
+        assert_eq!(EventOrder::measure([at(1), at(1), at(2)]).out_of_order, 0);
+
+        let events = crate::schema::RawEventAdapter::parse_ndjson(concat!(
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-16T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"steps","value":1,"unit":"count"}}}"#,
+            "\n",
+            r#"{"schema_version":"wear.raw_event.v1","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"garmin"},"record_type":"signal","payload":{"signal":{"type":"steps","value":1,"unit":"count"}}}"#,
+        ))
+        .unwrap();
+        let warn = OrderingConfig::default();
+        assert_eq!(warn.check(&events).unwrap().unwrap().out_of_order, 1);
+        assert!(warn
+            .with_max_out_of_order(0.5)
+            .check(&events)
+            .unwrap()
+            .is_none());
+        assert!(warn
+            .with_policy(DisorderPolicy::Fail)
+            .check(&events)
+            .is_err());
+    }
+}
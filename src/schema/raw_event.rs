@@ -2,6 +2,7 @@
 //!
 //! A scalable, vendor-agnostic schema for wearable data that supports:
 //! - Individual signal events (for real-time streaming)
+//! - Intraday series (many timestamped samples of one signal)
 //! - Session records (sleep, workouts)
 //! - Daily/hourly summaries (batch aggregates)
 //! - Vendor scores (recovery, strain, readiness)
@@ -13,6 +14,41 @@ use std::collections::HashMap;
 /// Current schema version
 pub const SCHEMA_VERSION: &str = "wear.raw_event.v1";
 
+/// Raw event schema versions this crate can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchemaVersion {
+    /// `wear.raw_event.v1`, the internal model
+    V1,
+    /// `wear.raw_event.v2`, converted to v1 on input
+    V2,
+}
+
+impl SchemaVersion {
+    /// Every supported version, oldest first
+    pub const ALL: [SchemaVersion; 2] = [SchemaVersion::V1, SchemaVersion::V2];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchemaVersion::V1 => SCHEMA_VERSION,
+            SchemaVersion::V2 => crate::schema::SCHEMA_VERSION_V2,
+        }
+    }
+
+    /// Look up a `schema_version` string
+    pub fn parse(version: &str) -> Result<Self, ValidationError> {
+        Self::ALL
+            .into_iter()
+            .find(|v| v.as_str() == version)
+            .ok_or_else(|| ValidationError::UnsupportedSchemaVersion(version.to_string()))
+    }
+}
+
+impl std::fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Supported wearable providers
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -78,6 +114,8 @@ pub enum RecordType {
     Summary,
     /// Vendor-computed score (recovery, strain, readiness)
     Score,
+    /// Timestamped samples of one signal (per-minute HR, 5-min HRV)
+    Series,
 }
 
 /// Signal types for individual readings
@@ -101,6 +139,12 @@ pub enum SignalType {
     Floors,
     ActiveMinutes,
 
+    // Fitness
+    /// Estimated VO2max (ml/kg/min)
+    Vo2Max,
+    /// Vendor-estimated fitness age (years)
+    FitnessAge,
+
     // Body
     SkinTemperature,
     BodyTemperature,
@@ -114,6 +158,8 @@ pub enum SignalType {
     Stress,
     Energy,
     BodyBattery,
+    /// Menstrual cycle phase code (0 menstrual, 1 follicular, 2 ovulatory, 3 luteal)
+    CyclePhase,
 
     /// For extensibility
     #[serde(untagged)]
@@ -152,6 +198,10 @@ pub enum Unit {
     Kg,
     Lbs,
 
+    // Fitness
+    MlPerKgPerMin, // VO2max
+    Years,         // fitness age
+
     // Generic
     Score, // normalized 0-100 or 0-1
     Level, // categorical (awake, light, deep, rem)
@@ -176,6 +226,28 @@ pub struct SignalPayload {
     pub quality: Option<f64>,
 }
 
+/// One sample of a series
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesSample {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    /// Data quality/confidence (0.0 - 1.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<f64>,
+}
+
+/// Intraday series of one signal type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesPayload {
+    /// Type of signal
+    #[serde(rename = "type")]
+    pub signal_type: SignalType,
+    /// Measurement unit of every sample
+    pub unit: Unit,
+    /// Samples in time order
+    pub samples: Vec<SeriesSample>,
+}
+
 /// Session types
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -334,7 +406,7 @@ impl MetricValue {
     }
 }
 
-/// Event payload - one of the four record types
+/// Event payload - one of the five record types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Payload {
@@ -342,6 +414,7 @@ pub enum Payload {
     Session { session: SessionPayload },
     Summary { summary: SummaryPayload },
     Score { score: ScorePayload },
+    Series { series: SeriesPayload },
 }
 
 /// Optional context for the event
@@ -434,6 +507,21 @@ impl RawEvent {
         }
     }
 
+    /// Create a new series event
+    pub fn series(timestamp: DateTime<Utc>, source: Source, series: SeriesPayload) -> Self {
+        RawEvent {
+            schema_version: SCHEMA_VERSION.to_string(),
+            event_id: Some(uuid::Uuid::new_v4().to_string()),
+            timestamp,
+            source,
+            user_id: None,
+            record_type: RecordType::Series,
+            payload: Payload::Series { series },
+            context: None,
+            vendor_raw: None,
+        }
+    }
+
     /// Create a new score event
     pub fn score(timestamp: DateTime<Utc>, source: Source, score: ScorePayload) -> Self {
         RawEvent {
@@ -469,34 +557,45 @@ impl RawEvent {
 
     /// Validate the event schema
     pub fn validate(&self) -> Result<(), ValidationError> {
-        // Check schema version
-        if self.schema_version != SCHEMA_VERSION {
-            return Err(ValidationError::InvalidSchemaVersion {
-                expected: SCHEMA_VERSION.to_string(),
-                actual: self.schema_version.clone(),
-            });
-        }
+        validate_record(&self.schema_version, &self.record_type, &self.payload)
+    }
+}
 
-        // Validate payload matches record type
-        match (&self.record_type, &self.payload) {
-            (RecordType::Signal, Payload::Signal { .. }) => Ok(()),
-            (RecordType::Session, Payload::Session { .. }) => Ok(()),
-            (RecordType::Summary, Payload::Summary { .. }) => Ok(()),
-            (RecordType::Score, Payload::Score { .. }) => Ok(()),
-            _ => Err(ValidationError::PayloadTypeMismatch {
-                record_type: format!("{:?}", self.record_type),
-                payload_type: self.payload_type_name(),
-            }),
-        }
+/// Check the schema version and that the payload matches the record type
+pub(crate) fn validate_record(
+    schema_version: &str,
+    record_type: &RecordType,
+    payload: &Payload,
+) -> Result<(), ValidationError> {
+    // Check schema version
+    if schema_version != SCHEMA_VERSION {
+        return Err(ValidationError::InvalidSchemaVersion {
+            expected: SCHEMA_VERSION.to_string(),
+            actual: schema_version.to_string(),
+        });
     }
 
-    fn payload_type_name(&self) -> String {
-        match &self.payload {
-            Payload::Signal { .. } => "signal".to_string(),
-            Payload::Session { .. } => "session".to_string(),
-            Payload::Summary { .. } => "summary".to_string(),
-            Payload::Score { .. } => "score".to_string(),
-        }
+    // Validate payload matches record type
+    match (record_type, payload) {
+        (RecordType::Signal, Payload::Signal { .. }) => Ok(()),
+        (RecordType::Session, Payload::Session { .. }) => Ok(()),
+        (RecordType::Summary, Payload::Summary { .. }) => Ok(()),
+        (RecordType::Score, Payload::Score { .. }) => Ok(()),
+        (RecordType::Series, Payload::Series { .. }) => Ok(()),
+        _ => Err(ValidationError::PayloadTypeMismatch {
+            record_type: format!("{:?}", record_type),
+            payload_type: payload_type_name(payload).to_string(),
+        }),
+    }
+}
+
+fn payload_type_name(payload: &Payload) -> &'static str {
+    match payload {
+        Payload::Signal { .. } => "signal",
+        Payload::Session { .. } => "session",
+        Payload::Summary { .. } => "summary",
+        Payload::Score { .. } => "score",
+        Payload::Series { .. } => "series",
     }
 }
 
@@ -506,6 +605,9 @@ pub enum ValidationError {
     #[error("Invalid schema version: expected {expected}, got {actual}")]
     InvalidSchemaVersion { expected: String, actual: String },
 
+    #[error("Unsupported schema version: {0}")]
+    UnsupportedSchemaVersion(String),
+
     #[error("Payload type mismatch: record_type is {record_type} but payload is {payload_type}")]
     PayloadTypeMismatch {
         record_type: String,
@@ -649,4 +751,20 @@ mod tests {
         let event = RawEvent::signal(Utc::now(), source, signal);
         assert!(event.validate().is_ok());
     }
+
+    #[test]
+    fn test_schema_version_parse() {
+        assert_eq!(
+            SchemaVersion::parse("wear.raw_event.v1").unwrap(),
+            SchemaVersion::V1
+        );
+        assert_eq!(
+            SchemaVersion::parse("wear.raw_event.v2").unwrap(),
+            SchemaVersion::V2
+        );
+        assert!(matches!(
+            SchemaVersion::parse("wear.raw_event.v9"),
+            Err(ValidationError::UnsupportedSchemaVersion(_))
+        ));
+    }
 }
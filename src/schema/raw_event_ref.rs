@@ -0,0 +1,148 @@
+//! Borrowed wear.raw_event.v1 parsing
+//!
+//! [`RawEventRef`] borrows string fields from the input buffer and keeps
+//! `vendor_raw` as unparsed JSON, avoiding most allocations when ingesting
+//! large volumes of events. Convert to an owned [`RawEvent`] when needed.
+
+use crate::error::ComputeError;
+use crate::schema::raw_event::{
+    validate_record, Context, Payload, Provider, RawEvent, RecordType, Source, ValidationError,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+
+/// Serde only borrows a `Cow` that is a direct field, not one inside `Option`
+fn borrow_opt<'de: 'a, 'a, D>(deserializer: D) -> Result<Option<Cow<'a, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Borrowed>::deserialize(deserializer)?.map(|b| b.0))
+}
+
+/// Borrowed data source information
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceRef<'a> {
+    pub provider: Provider,
+    #[serde(borrow, default, deserialize_with = "borrow_opt")]
+    pub device_model: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrow_opt")]
+    pub device_id: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "borrow_opt")]
+    pub firmware_version: Option<Cow<'a, str>>,
+}
+
+/// Borrowed variant of [`RawEvent`]
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawEventRef<'a> {
+    #[serde(borrow)]
+    pub schema_version: Cow<'a, str>,
+    #[serde(borrow, default, deserialize_with = "borrow_opt")]
+    pub event_id: Option<Cow<'a, str>>,
+    pub timestamp: DateTime<Utc>,
+    #[serde(borrow)]
+    pub source: SourceRef<'a>,
+    #[serde(borrow, default, deserialize_with = "borrow_opt")]
+    pub user_id: Option<Cow<'a, str>>,
+    pub record_type: RecordType,
+    pub payload: Payload,
+    #[serde(default)]
+    pub context: Option<Context>,
+    /// Unparsed vendor data; see [`RawEventRef::vendor_raw_value`]
+    #[serde(borrow, default)]
+    pub vendor_raw: Option<&'a RawValue>,
+}
+
+impl<'a> RawEventRef<'a> {
+    /// Parse a single JSON event, borrowing from `json`
+    pub fn parse(json: &'a str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Validate the event schema
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_record(&self.schema_version, &self.record_type, &self.payload)
+    }
+
+    /// Parse the preserved vendor data, if any
+    pub fn vendor_raw_value(&self) -> Result<Option<serde_json::Value>, ComputeError> {
+        self.vendor_raw
+            .map(|raw| serde_json::from_str(raw.get()))
+            .transpose()
+            .map_err(ComputeError::from)
+    }
+
+    /// Convert to an owned [`RawEvent`], parsing `vendor_raw` only if requested
+    pub fn to_owned_event(&self, include_vendor_raw: bool) -> Result<RawEvent, ComputeError> {
+        Ok(RawEvent {
+            schema_version: self.schema_version.to_string(),
+            event_id: self.event_id.as_deref().map(str::to_string),
+            timestamp: self.timestamp,
+            source: Source {
+                provider: self.source.provider.clone(),
+                device_model: self.source.device_model.as_deref().map(str::to_string),
+                device_id: self.source.device_id.as_deref().map(str::to_string),
+                firmware_version: self.source.firmware_version.as_deref().map(str::to_string),
+            },
+            user_id: self.user_id.as_deref().map(str::to_string),
+            record_type: self.record_type.clone(),
+            payload: self.payload.clone(),
+            context: self.context.clone(),
+            vendor_raw: if include_vendor_raw {
+                self.vendor_raw_value()?
+            } else {
+                None
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::RawEventAdapter;
+
+    const EVENT: &str = r#"{"schema_version":"wear.raw_event.v1","event_id":"e1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"whoop","device_id":"dev-1"},"record_type":"signal","payload":{"signal":{"type":"heart_rate_variability","value":65.0,"unit":"ms"}},"vendor_raw":{"hrv":65}}"#;
+
+    #[test]
+    fn test_borrows_strings() {
+        let event = RawEventRef::parse(EVENT).unwrap();
+        assert!(matches!(event.schema_version, Cow::Borrowed(_)));
+        assert!(matches!(
+            event.source.device_id,
+            Some(Cow::Borrowed("dev-1"))
+        ));
+        assert_eq!(event.vendor_raw.unwrap().get(), r#"{"hrv":65}"#);
+        assert!(event.validate().is_ok());
+    }
+
+    #[test]
+    fn test_matches_owned_parsing() {
+        let borrowed = RawEventAdapter::parse_ndjson_borrowed(EVENT).unwrap();
+        let owned = RawEventAdapter::parse_ndjson(EVENT).unwrap();
+
+        let from_borrowed =
+            RawEventAdapter::to_canonical_borrowed(&borrowed, "UTC", "dev", true).unwrap();
+        let from_owned = RawEventAdapter::to_canonical(&owned, "UTC", "dev").unwrap();
+        assert_eq!(
+            from_borrowed[0].recovery.hrv_rmssd_ms,
+            from_owned[0].recovery.hrv_rmssd_ms
+        );
+        assert_eq!(from_borrowed[0].vendor_raw, from_owned[0].vendor_raw);
+
+        let lean = RawEventAdapter::to_canonical_borrowed(&borrowed, "UTC", "dev", false).unwrap();
+        assert!(lean[0].vendor_raw.is_empty());
+
+        let converted = borrowed[0].to_owned_event(true).unwrap();
+        assert_eq!(converted.vendor_raw, owned[0].vendor_raw);
+        assert!(borrowed[0]
+            .to_owned_event(false)
+            .unwrap()
+            .vendor_raw
+            .is_none());
+    }
+}
@@ -0,0 +1,298 @@
+//! wear.raw_event.v2 schema and conversion to v1
+//!
+//! v2 keeps the v1 envelope and record types, and lets sleep sessions carry
+//! a typed `stages` array of timed segments. [`RawEventV2::into_v1`] folds
+//! the array into the v1 stage minute totals and `hypnogram` metric before
+//! aggregation.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::schema::raw_event::*;
+
+/// v2 schema version identifier
+pub const SCHEMA_VERSION_V2: &str = "wear.raw_event.v2";
+
+/// Sleep stage of a hypnogram segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SleepStage {
+    Awake,
+    Light,
+    Deep,
+    Rem,
+}
+
+/// One contiguous sleep stage segment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SleepStageSegment {
+    pub stage: SleepStage,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+impl SleepStageSegment {
+    fn minutes(&self) -> f64 {
+        (self.end_time - self.start_time).num_seconds().max(0) as f64 / 60.0
+    }
+}
+
+/// v2 session record, optionally with a sleep stage array
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPayloadV2 {
+    #[serde(rename = "type")]
+    pub session_type: SessionType,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// Sleep stage segments in time order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<SleepStageSegment>,
+    #[serde(default)]
+    pub metrics: HashMap<String, MetricValue>,
+}
+
+/// v2 event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PayloadV2 {
+    Signal { signal: SignalPayload },
+    Session { session: SessionPayloadV2 },
+    Summary { summary: SummaryPayload },
+    Score { score: ScorePayload },
+    Series { series: SeriesPayload },
+}
+
+/// The wear.raw_event.v2 schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawEventV2 {
+    pub schema_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    pub source: Source,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    pub record_type: RecordType,
+    pub payload: PayloadV2,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<Context>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor_raw: Option<serde_json::Value>,
+}
+
+impl RawEventV2 {
+    /// Validate the schema version and that the payload matches the record type
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.schema_version != SCHEMA_VERSION_V2 {
+            return Err(ValidationError::InvalidSchemaVersion {
+                expected: SCHEMA_VERSION_V2.to_string(),
+                actual: self.schema_version.clone(),
+            });
+        }
+        let matches = matches!(
+            (&self.record_type, &self.payload),
+            (RecordType::Signal, PayloadV2::Signal { .. })
+                | (RecordType::Session, PayloadV2::Session { .. })
+                | (RecordType::Summary, PayloadV2::Summary { .. })
+                | (RecordType::Score, PayloadV2::Score { .. })
+                | (RecordType::Series, PayloadV2::Series { .. })
+        );
+        if matches {
+            Ok(())
+        } else {
+            Err(ValidationError::PayloadTypeMismatch {
+                record_type: format!("{:?}", self.record_type),
+                payload_type: payload_type_name(&self.payload).to_string(),
+            })
+        }
+    }
+
+    /// Convert to a v1 event.
+    ///
+    /// A session's stage array is summed into the v1 stage minute metrics;
+    /// metrics the producer set explicitly take precedence.
+    pub fn into_v1(self) -> RawEvent {
+        let payload = match self.payload {
+            PayloadV2::Signal { signal } => Payload::Signal { signal },
+            PayloadV2::Summary { summary } => Payload::Summary { summary },
+            PayloadV2::Score { score } => Payload::Score { score },
+            PayloadV2::Series { series } => Payload::Series { series },
+            PayloadV2::Session { session } => Payload::Session {
+                session: session_to_v1(session),
+            },
+        };
+
+        RawEvent {
+            schema_version: SCHEMA_VERSION.to_string(),
+            event_id: self.event_id,
+            timestamp: self.timestamp,
+            source: self.source,
+            user_id: self.user_id,
+            record_type: self.record_type,
+            payload,
+            context: self.context,
+            vendor_raw: self.vendor_raw,
+        }
+    }
+}
+
+fn payload_type_name(payload: &PayloadV2) -> &'static str {
+    match payload {
+        PayloadV2::Signal { .. } => "signal",
+        PayloadV2::Session { .. } => "session",
+        PayloadV2::Summary { .. } => "summary",
+        PayloadV2::Score { .. } => "score",
+        PayloadV2::Series { .. } => "series",
+    }
+}
+
+fn stage_name(stage: SleepStage) -> &'static str {
+    match stage {
+        SleepStage::Awake => "awake",
+        SleepStage::Light => "light",
+        SleepStage::Deep => "deep",
+        SleepStage::Rem => "rem",
+    }
+}
+
+/// Fold a stage array into v1 session metrics
+fn session_to_v1(session: SessionPayloadV2) -> SessionPayload {
+    let mut metrics = session.metrics;
+    if !session.stages.is_empty() {
+        let minutes = |stage: SleepStage| -> f64 {
+            session
+                .stages
+                .iter()
+                .filter(|s| s.stage == stage)
+                .map(SleepStageSegment::minutes)
+                .sum()
+        };
+        let light = minutes(SleepStage::Light);
+        let deep = minutes(SleepStage::Deep);
+        let rem = minutes(SleepStage::Rem);
+
+        // Awakenings are awake segments between the first and last asleep segment
+        let asleep: Vec<usize> = session
+            .stages
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.stage != SleepStage::Awake)
+            .map(|(i, _)| i)
+            .collect();
+        let awakenings = match (asleep.first(), asleep.last()) {
+            (Some(&first), Some(&last)) => session.stages[first..last]
+                .iter()
+                .filter(|s| s.stage == SleepStage::Awake)
+                .count(),
+            _ => 0,
+        };
+
+        let derived = [
+            ("light_sleep_minutes", MetricValue::Number(light)),
+            ("deep_sleep_minutes", MetricValue::Number(deep)),
+            ("rem_sleep_minutes", MetricValue::Number(rem)),
+            (
+                "awake_minutes",
+                MetricValue::Number(minutes(SleepStage::Awake)),
+            ),
+            (
+                "total_sleep_minutes",
+                MetricValue::Number(light + deep + rem),
+            ),
+            ("awakenings", MetricValue::Integer(awakenings as i64)),
+        ];
+        for (key, value) in derived {
+            metrics.entry(key.to_string()).or_insert(value);
+        }
+
+        // Keep the sequence itself for hypnogram-based continuity metrics
+        let hypnogram = session
+            .stages
+            .iter()
+            .map(|s| {
+                MetricValue::Object(HashMap::from([
+                    (
+                        "stage".to_string(),
+                        MetricValue::String(stage_name(s.stage).to_string()),
+                    ),
+                    (
+                        "start".to_string(),
+                        MetricValue::String(s.start_time.to_rfc3339()),
+                    ),
+                    (
+                        "duration_minutes".to_string(),
+                        MetricValue::Number(s.minutes()),
+                    ),
+                ]))
+            })
+            .collect();
+        metrics
+            .entry("hypnogram".to_string())
+            .or_insert(MetricValue::Array(hypnogram));
+        if let Some(&first) = asleep.first() {
+            let latency = (session.stages[first].start_time - session.start_time)
+                .num_seconds()
+                .max(0) as f64
+                / 60.0;
+            metrics
+                .entry("latency_minutes".to_string())
+                .or_insert(MetricValue::Number(latency));
+        }
+    }
+
+    SessionPayload {
+        session_type: session.session_type,
+        start_time: session.start_time,
+        end_time: session.end_time,
+        metrics,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_passes_through() {
+        let json = r#"{"schema_version":"wear.raw_event.v2","event_id":"hr-1","timestamp":"2024-01-15T08:00:00Z","source":{"provider":"garmin"},"record_type":"series","payload":{"series":{"type":"heart_rate","unit":"bpm","samples":[{"timestamp":"2024-01-15T08:00:00Z","value":60.0},{"timestamp":"2024-01-15T08:01:00Z","value":64.0,"quality":0.9}]}}}"#;
+        let event: RawEventV2 = serde_json::from_str(json).unwrap();
+        event.validate().unwrap();
+
+        let v1 = event.into_v1();
+        v1.validate().unwrap();
+        assert_eq!(v1.event_id.as_deref(), Some("hr-1"));
+        match &v1.payload {
+            Payload::Series { series } => assert_eq!(series.samples.len(), 2),
+            other => panic!("expected series, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_sleep_stages_fold_into_metrics() {
+        let json = r#"{"schema_version":"wear.raw_event.v2","timestamp":"2024-01-15T07:00:00Z","source":{"provider":"oura"},"record_type":"session","payload":{"session":{"type":"sleep","start_time":"2024-01-14T23:00:00Z","end_time":"2024-01-15T07:00:00Z","metrics":{"sleep_score":82},"stages":[
+            {"stage":"awake","start_time":"2024-01-14T23:00:00Z","end_time":"2024-01-14T23:15:00Z"},
+            {"stage":"light","start_time":"2024-01-14T23:15:00Z","end_time":"2024-01-15T01:15:00Z"},
+            {"stage":"deep","start_time":"2024-01-15T01:15:00Z","end_time":"2024-01-15T02:45:00Z"},
+            {"stage":"awake","start_time":"2024-01-15T02:45:00Z","end_time":"2024-01-15T03:00:00Z"},
+            {"stage":"rem","start_time":"2024-01-15T03:00:00Z","end_time":"2024-01-15T07:00:00Z"}]}}}"#;
+        let event: RawEventV2 = serde_json::from_str(json).unwrap();
+        let v1 = event.into_v1();
+        let Payload::Session { session } = &v1.payload else {
+            panic!("expected session");
+        };
+        let metric = |key: &str| session.metrics[key].as_f64().unwrap();
+        assert_eq!(metric("light_sleep_minutes"), 120.0);
+        assert_eq!(metric("deep_sleep_minutes"), 90.0);
+        assert_eq!(metric("rem_sleep_minutes"), 240.0);
+        assert_eq!(metric("awake_minutes"), 30.0);
+        assert_eq!(metric("total_sleep_minutes"), 450.0);
+        assert_eq!(metric("latency_minutes"), 15.0);
+        assert_eq!(metric("awakenings"), 1.0);
+        assert_eq!(metric("sleep_score"), 82.0);
+        match &session.metrics["hypnogram"] {
+            MetricValue::Array(segments) => assert_eq!(segments.len(), 5),
+            other => panic!("expected hypnogram array, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,502 @@
+//! HTTP ingestion server (`serve` feature)
+//!
+//! Runs Flux as a sidecar: hosts POST data over HTTP and get HSI payloads
+//! back, without writing their own wrapper service. Each user gets a
+//! stateful [`FluxProcessor`] and [`BehaviorProcessor`], created on first use
+//! and, when a state directory is configured, loaded from and periodically
+//! saved to `<dir>/<user>.wear.json` and `<dir>/<user>.behavior.json`.
+//!
+//! Endpoints (all responses are JSON):
+//!
+//! - `GET /healthz`
+//! - `POST /v1/users/{user}/raw-events?timezone=&device_id=`: raw event
+//!   NDJSON body, returns an array of daily HSI payloads
+//! - `POST /v1/users/{user}/behavior-sessions`: one behavioral session,
+//!   returns its HSI payload and updates the user's behavior baselines
+//! - `POST /v1/users/{user}/snapshot`: a session in progress (`end_time`
+//!   replaced by an optional `as_of`, defaulting to now), returns a partial
+//!   HSI payload without touching baselines
+//!
+//! Errors are returned as `{"code": ..., "message": ...}`. Requests are
+//! handled one at a time, so per-user state needs no locking.
+
+use crate::behavior::types::BehaviorEvent;
+use crate::behavior::BehaviorSessionBuilder;
+use crate::error::ComputeError;
+use crate::{BehaviorProcessor, FluxProcessor};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Server settings
+#[derive(Debug, Clone)]
+pub struct ServeConfig {
+    /// Directory for per-user baseline files (in-memory only when `None`)
+    pub state_dir: Option<PathBuf>,
+    /// How often changed baselines are written to the state directory
+    pub persist_interval: Duration,
+    /// Largest accepted request body
+    pub max_body_bytes: usize,
+    /// Wearable baseline window in days
+    pub baseline_days: usize,
+    /// Behavioral baseline window in sessions
+    pub baseline_sessions: usize,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        Self {
+            state_dir: None,
+            persist_interval: Duration::from_secs(60),
+            max_body_bytes: 16 * 1024 * 1024,
+            baseline_days: 14,
+            baseline_sessions: 20,
+        }
+    }
+}
+
+impl ServeConfig {
+    /// Persist baselines under `dir`
+    pub fn with_state_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.state_dir = Some(dir.into());
+        self
+    }
+
+    /// Set the persistence interval
+    pub fn with_persist_interval(mut self, interval: Duration) -> Self {
+        self.persist_interval = interval;
+        self
+    }
+
+    /// Set the request body limit
+    pub fn with_max_body_bytes(mut self, bytes: usize) -> Self {
+        self.max_body_bytes = bytes;
+        self
+    }
+}
+
+/// An HTTP response produced by [`FluxService::handle`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    /// JSON body
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: &Value) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, code: &str, message: impl Into<String>) -> Self {
+        Self::json(status, &json!({ "code": code, "message": message.into() }))
+    }
+}
+
+impl From<ComputeError> for Response {
+    fn from(e: ComputeError) -> Self {
+        Self::error(400, "COMPUTE_ERROR", e.to_string())
+    }
+}
+
+struct UserState {
+    wear: FluxProcessor,
+    behavior: BehaviorProcessor,
+    dirty: bool,
+}
+
+/// Session in progress posted to the snapshot endpoint
+#[derive(Deserialize)]
+struct SnapshotRequest {
+    session_id: String,
+    device_id: String,
+    #[serde(default = "default_timezone")]
+    timezone: String,
+    start_time: DateTime<Utc>,
+    #[serde(default)]
+    as_of: Option<DateTime<Utc>>,
+    #[serde(default)]
+    events: Vec<BehaviorEvent>,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+/// Request router holding per-user processors
+pub struct FluxService {
+    config: ServeConfig,
+    users: HashMap<String, UserState>,
+    last_persist: Instant,
+}
+
+impl FluxService {
+    /// Create a service with no users loaded
+    pub fn new(config: ServeConfig) -> Self {
+        Self {
+            config,
+            users: HashMap::new(),
+            last_persist: Instant::now(),
+        }
+    }
+
+    /// Handle one request. `url` may carry a query string.
+    pub fn handle(&mut self, method: &str, url: &str, body: &str) -> Response {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        match (method, segments.as_slice()) {
+            ("GET", ["healthz"]) => Response::json(
+                200,
+                &json!({ "status": "ok", "version": crate::FLUX_VERSION, "users": self.users.len() }),
+            ),
+            ("POST", ["v1", "users", user, endpoint]) => {
+                if !is_valid_user_id(user) {
+                    return Response::error(
+                        400,
+                        "INVALID_USER",
+                        "user IDs may only contain letters, digits, '-', '_' and '.'",
+                    );
+                }
+                let query = parse_query(query);
+                let result = match *endpoint {
+                    "raw-events" => self.raw_events(user, body, &query),
+                    "behavior-sessions" => self.behavior_session(user, body),
+                    "snapshot" => self.snapshot(user, body),
+                    _ => return not_found(path),
+                };
+                result.unwrap_or_else(Response::from)
+            }
+            (_, ["healthz"] | ["v1", "users", _, _]) => {
+                Response::error(405, "METHOD_NOT_ALLOWED", format!("{method} {path}"))
+            }
+            _ => not_found(path),
+        }
+    }
+
+    /// Write baselines for users changed since the last save, returning how many were written
+    pub fn persist(&mut self) -> Result<usize, ComputeError> {
+        self.last_persist = Instant::now();
+        let Some(dir) = self.config.state_dir.clone() else {
+            return Ok(0);
+        };
+        std::fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+
+        let mut written = 0;
+        for (user, state) in self.users.iter_mut().filter(|(_, s)| s.dirty) {
+            write_atomic(
+                &dir.join(format!("{user}.wear.json")),
+                &state.wear.save_baselines()?,
+            )?;
+            write_atomic(
+                &dir.join(format!("{user}.behavior.json")),
+                &state.behavior.save_baselines()?,
+            )?;
+            state.dirty = false;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// [`Self::persist`] if the persistence interval has elapsed
+    pub fn persist_if_due(&mut self) -> Result<usize, ComputeError> {
+        if self.last_persist.elapsed() >= self.config.persist_interval {
+            self.persist()
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn raw_events(
+        &mut self,
+        user: &str,
+        body: &str,
+        query: &HashMap<String, String>,
+    ) -> Result<Response, ComputeError> {
+        let timezone = query.get("timezone").map_or("UTC", String::as_str);
+        let device_id = query.get("device_id").map_or("unknown", String::as_str);
+        let state = self.user(user)?;
+        let payloads = state
+            .wear
+            .process_raw_event_payloads(body, timezone, device_id)?;
+        state.dirty = true;
+        Ok(Response::json(200, &serde_json::to_value(payloads)?))
+    }
+
+    fn behavior_session(&mut self, user: &str, body: &str) -> Result<Response, ComputeError> {
+        let state = self.user(user)?;
+        let payload = state.behavior.process_payload(body)?;
+        state.dirty = true;
+        Ok(Response::json(200, &serde_json::to_value(payload)?))
+    }
+
+    fn snapshot(&mut self, user: &str, body: &str) -> Result<Response, ComputeError> {
+        let request: SnapshotRequest = serde_json::from_str(body)?;
+        let mut builder =
+            BehaviorSessionBuilder::new(request.session_id, request.device_id, request.start_time)
+                .with_timezone(request.timezone);
+        for event in request.events {
+            builder.push_event(event);
+        }
+        let as_of = request.as_of.unwrap_or_else(Utc::now);
+        let json = self.user(user)?.behavior.snapshot(&builder, as_of)?;
+        Ok(Response {
+            status: 200,
+            body: json,
+        })
+    }
+
+    /// The user's processors, loading saved baselines on first use
+    fn user(&mut self, user: &str) -> Result<&mut UserState, ComputeError> {
+        if !self.users.contains_key(user) {
+            let mut state = UserState {
+                wear: FluxProcessor::with_baseline_window(self.config.baseline_days),
+                behavior: BehaviorProcessor::with_baseline_window(self.config.baseline_sessions),
+                dirty: false,
+            };
+            if let Some(dir) = &self.config.state_dir {
+                if let Some(json) = read_optional(&dir.join(format!("{user}.wear.json")))? {
+                    state.wear.load_baselines(&json)?;
+                }
+                if let Some(json) = read_optional(&dir.join(format!("{user}.behavior.json")))? {
+                    state.behavior.load_baselines(&json)?;
+                }
+            }
+            self.users.insert(user.to_string(), state);
+        }
+        Ok(self
+            .users
+            .get_mut(user)
+            .expect("user state was just inserted"))
+    }
+}
+
+/// Serve on `listen` (e.g. `127.0.0.1:8080`) until the process exits
+pub fn serve(listen: &str, config: ServeConfig) -> Result<(), ComputeError> {
+    let server = tiny_http::Server::http(listen)
+        .map_err(|e| ComputeError::InvalidConfig(format!("cannot listen on {listen}: {e}")))?;
+    let max_body_bytes = config.max_body_bytes;
+    let poll = config.persist_interval.min(Duration::from_secs(1));
+    let mut service = FluxService::new(config);
+
+    loop {
+        let request = server
+            .recv_timeout(poll)
+            .map_err(|e| ComputeError::ParseError(format!("accept failed: {e}")))?;
+        if let Some(mut request) = request {
+            let response = match read_body(&mut request, max_body_bytes) {
+                Ok(body) => service.handle(request.method().as_str(), request.url(), &body),
+                Err(response) => response,
+            };
+            let header = tiny_http::Header::from_bytes("Content-Type", "application/json")
+                .expect("static header is valid");
+            // A client that disconnected early is not a server error
+            let _ = request.respond(
+                tiny_http::Response::from_string(response.body)
+                    .with_status_code(response.status)
+                    .with_header(header),
+            );
+        }
+        if let Err(e) = service.persist_if_due() {
+            eprintln!("failed to persist baselines: {e}");
+        }
+    }
+}
+
+fn read_body(request: &mut tiny_http::Request, limit: usize) -> Result<String, Response> {
+    if request.body_length().is_some_and(|len| len > limit) {
+        return Err(too_large(limit));
+    }
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(limit as u64 + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| Response::error(400, "INVALID_BODY", e.to_string()))?;
+    if body.len() > limit {
+        return Err(too_large(limit));
+    }
+    Ok(body)
+}
+
+fn too_large(limit: usize) -> Response {
+    Response::error(
+        413,
+        "BODY_TOO_LARGE",
+        format!("request body exceeds {limit} bytes"),
+    )
+}
+
+fn not_found(path: &str) -> Response {
+    Response::error(404, "NOT_FOUND", format!("no endpoint at {path}"))
+}
+
+/// User IDs become file names, so keep them to a safe character set
+fn is_valid_user_id(user: &str) -> bool {
+    !user.is_empty()
+        && !user.starts_with('.')
+        && user
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Parse `a=1&b=2`, decoding `%XX` escapes and `+`
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (_, Some(byte)) => {
+                out.push(byte);
+                i += 2;
+            }
+            (b'+', None) => out.push(b' '),
+            (byte, None) => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, ComputeError> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => Ok(Some(json)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(io_error(path, e)),
+    }
+}
+
+/// Write via a temporary file so a crash never leaves a truncated store
+fn write_atomic(path: &Path, contents: &str) -> Result<(), ComputeError> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, contents).map_err(|e| io_error(&tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| io_error(path, e))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> ComputeError {
+    ComputeError::ParseError(format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{Generator, SyntheticConfig};
+
+    fn generator() -> Generator {
+        Generator::new(SyntheticConfig::default().with_days(2)).unwrap()
+    }
+
+    fn raw_events_ndjson() -> String {
+        generator()
+            .raw_events("whoop")
+            .unwrap()
+            .iter()
+            .map(|event| serde_json::to_string(event).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_routes_requests_per_user() {
+        let mut service = FluxService::new(ServeConfig::default());
+        let url = "/v1/users/alice/raw-events?timezone=America%2FNew_York&device_id=dev-1";
+
+        let response = service.handle("POST", url, &raw_events_ndjson());
+        assert_eq!(response.status, 200, "{}", response.body);
+        let payloads: Vec<Value> = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0]["windows"][0]["timezone"], "America/New_York");
+        assert_eq!(payloads[0]["provenance"]["source_device_id"], "dev-1");
+
+        let session = generator().behavior_sessions().unwrap().remove(0);
+        let response = service.handle(
+            "POST",
+            "/v1/users/alice/behavior-sessions",
+            &serde_json::to_string(&session).unwrap(),
+        );
+        assert_eq!(response.status, 200, "{}", response.body);
+        assert_eq!(service.users["alice"].behavior.baseline_session_count(), 1);
+
+        let snapshot = json!({
+            "session_id": "live",
+            "device_id": session.device_id,
+            "start_time": session.start_time,
+            "as_of": session.end_time,
+            "events": session.events,
+        });
+        let response = service.handle("POST", "/v1/users/alice/snapshot", &snapshot.to_string());
+        assert_eq!(response.status, 200, "{}", response.body);
+        assert_eq!(service.users["alice"].behavior.baseline_session_count(), 1);
+
+        assert_eq!(
+            service.handle("POST", "/v1/users/alice/nope", "").status,
+            404
+        );
+        assert_eq!(
+            service.handle("GET", "/v1/users/alice/snapshot", "").status,
+            405
+        );
+        assert_eq!(
+            service
+                .handle("POST", "/v1/users/..%2F/snapshot", "")
+                .status,
+            400
+        );
+        let bad = service.handle("POST", "/v1/users/bob/behavior-sessions", "{");
+        assert_eq!(bad.status, 400);
+        assert!(bad.body.contains("COMPUTE_ERROR"));
+    }
+
+    #[test]
+    fn test_persists_and_reloads_baselines() {
+        let dir = std::env::temp_dir().join(format!("flux-serve-test-{}", std::process::id()));
+        let config = ServeConfig::default()
+            .with_state_dir(&dir)
+            .with_persist_interval(Duration::ZERO);
+
+        let mut service = FluxService::new(config.clone());
+        let response = service.handle("POST", "/v1/users/u1/raw-events", &raw_events_ndjson());
+        assert_eq!(response.status, 200, "{}", response.body);
+        assert_eq!(service.persist_if_due().unwrap(), 1);
+        // Nothing changed since the last write
+        assert_eq!(service.persist().unwrap(), 0);
+
+        let mut restarted = FluxService::new(config);
+        let saved: Value =
+            serde_json::from_str(&restarted.user("u1").unwrap().wear.save_baselines().unwrap())
+                .unwrap();
+        let dates: Vec<_> = saved["days"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|day| day["date"].as_str().unwrap())
+            .collect();
+        assert_eq!(dates, vec!["2024-01-01", "2024-01-02"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
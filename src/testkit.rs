@@ -0,0 +1,607 @@
+//! Synthetic input generator
+//!
+//! Produces realistic WHOOP and Garmin payloads, `wear.raw_event.v1` streams
+//! and behavioral sessions for integration tests and demos without real user
+//! data. Output is fully determined by [`SyntheticConfig::seed`].
+//!
+//! Daily sleep quality starts at [`BASE_SLEEP_QUALITY`] and moves by
+//! `sleep_quality_trend` per day; HRV, resting heart rate and recovery follow
+//! it. Each daily record (and each behavioral session) is omitted with
+//! probability `missing_probability`.
+
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use serde_json::{json, Value};
+
+use crate::behavior::BehaviorSession;
+use crate::error::ComputeError;
+use crate::schema::RawEvent;
+
+/// Sleep quality (0-1) on the first generated day
+pub const BASE_SLEEP_QUALITY: f64 = 0.7;
+
+/// Behavioral session length in minutes
+const SESSION_MINUTES: i64 = 30;
+
+/// Mean gap between interactions during active use, in seconds
+const MEAN_INTERACTION_GAP_SEC: f64 = 12.0;
+
+/// Parameters for synthetic data
+#[derive(Debug, Clone)]
+pub struct SyntheticConfig {
+    /// RNG seed; identical configs produce identical output
+    pub seed: u64,
+    /// Number of days (and behavioral sessions) to generate
+    pub days: u32,
+    /// First generated date
+    pub start_date: NaiveDate,
+    /// Change in sleep quality per day (e.g. -0.02 for a worsening trend)
+    pub sleep_quality_trend: f64,
+    /// Notifications per hour during behavioral sessions
+    pub notification_rate_per_hour: f64,
+    /// Probability (0-1) that a daily record or session is missing
+    pub missing_probability: f64,
+    /// Device ID stamped on raw events and sessions
+    pub device_id: String,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            days: 14,
+            start_date: NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date"),
+            sleep_quality_trend: 0.0,
+            notification_rate_per_hour: 6.0,
+            missing_probability: 0.0,
+            device_id: "synthetic-device".to_string(),
+        }
+    }
+}
+
+impl SyntheticConfig {
+    /// Set the RNG seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the number of days
+    pub fn with_days(mut self, days: u32) -> Self {
+        self.days = days;
+        self
+    }
+
+    /// Set the first generated date
+    pub fn with_start_date(mut self, start_date: NaiveDate) -> Self {
+        self.start_date = start_date;
+        self
+    }
+
+    /// Set the per-day change in sleep quality
+    pub fn with_sleep_quality_trend(mut self, trend: f64) -> Self {
+        self.sleep_quality_trend = trend;
+        self
+    }
+
+    /// Set the notification rate for behavioral sessions
+    pub fn with_notification_rate_per_hour(mut self, rate: f64) -> Self {
+        self.notification_rate_per_hour = rate;
+        self
+    }
+
+    /// Set the probability that a record is missing
+    pub fn with_missing_probability(mut self, probability: f64) -> Self {
+        self.missing_probability = probability;
+        self
+    }
+
+    /// Set the device ID
+    pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.device_id = device_id.into();
+        self
+    }
+
+    /// Check that the parameters are usable
+    pub fn validate(&self) -> Result<(), ComputeError> {
+        if !(0.0..=1.0).contains(&self.missing_probability) {
+            return Err(ComputeError::InvalidConfig(format!(
+                "missing_probability must be within 0-1, got {}",
+                self.missing_probability
+            )));
+        }
+        if !(self.notification_rate_per_hour.is_finite() && self.notification_rate_per_hour >= 0.0)
+        {
+            return Err(ComputeError::InvalidConfig(format!(
+                "notification_rate_per_hour must be non-negative, got {}",
+                self.notification_rate_per_hour
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Physiology for one synthetic day
+struct Day {
+    date: NaiveDate,
+    quality: f64,
+    sleep_minutes: f64,
+    awake_minutes: f64,
+    deep_minutes: f64,
+    rem_minutes: f64,
+    awakenings: u32,
+    hrv_ms: f64,
+    rhr_bpm: f64,
+    strain: f64,
+    steps: u32,
+    active_calories: f64,
+}
+
+impl Day {
+    fn light_minutes(&self) -> f64 {
+        self.sleep_minutes - self.deep_minutes - self.rem_minutes
+    }
+
+    fn in_bed_minutes(&self) -> f64 {
+        self.sleep_minutes + self.awake_minutes
+    }
+
+    /// Bedtime, shortly after midnight so every vendor files the night under `date`
+    fn sleep_start(&self) -> chrono::DateTime<Utc> {
+        Utc.from_utc_datetime(&self.date.and_hms_opt(0, 30, 0).expect("valid time"))
+    }
+
+    fn sleep_end(&self) -> chrono::DateTime<Utc> {
+        self.sleep_start() + Duration::minutes(self.in_bed_minutes().round() as i64)
+    }
+}
+
+/// Deterministic generator for synthetic inputs
+pub struct Generator {
+    config: SyntheticConfig,
+    rng: SplitMix64,
+}
+
+impl Generator {
+    /// Create a generator
+    pub fn new(config: SyntheticConfig) -> Result<Self, ComputeError> {
+        config.validate()?;
+        let rng = SplitMix64(config.seed);
+        Ok(Self { config, rng })
+    }
+
+    /// A WHOOP API payload (`sleep`, `recovery`, `cycle` arrays)
+    pub fn whoop_payload(&mut self) -> Value {
+        let (mut sleep, mut recovery, mut cycle) = (Vec::new(), Vec::new(), Vec::new());
+        for (i, day) in self.days().into_iter().enumerate() {
+            let id = i as i64 + 1;
+            if self.present() {
+                sleep.push(json!({
+                    "id": id,
+                    "start": day.sleep_start().to_rfc3339(),
+                    "end": day.sleep_end().to_rfc3339(),
+                    "score": {
+                        "stage_summary": {
+                            "total_in_bed_time_milli": minutes_to_millis(day.in_bed_minutes()),
+                            "total_awake_time_milli": minutes_to_millis(day.awake_minutes),
+                            "total_light_sleep_time_milli": minutes_to_millis(day.light_minutes()),
+                            "total_slow_wave_sleep_time_milli": minutes_to_millis(day.deep_minutes),
+                            "total_rem_sleep_time_milli": minutes_to_millis(day.rem_minutes),
+                            "total_sleep_time_milli": minutes_to_millis(day.sleep_minutes),
+                            "disturbance_count": day.awakenings
+                        },
+                        "sleep_performance_percentage": round1(day.quality * 100.0),
+                        "sleep_efficiency_percentage": round1(day.sleep_minutes / day.in_bed_minutes() * 100.0),
+                        "respiratory_rate": round1(self.rng.range(13.5, 16.0))
+                    }
+                }));
+            }
+            if self.present() {
+                recovery.push(json!({
+                    "cycle_id": id,
+                    "created_at": day.sleep_end().to_rfc3339(),
+                    "score": {
+                        "recovery_score": round1(day.quality * 100.0),
+                        "resting_heart_rate": round1(day.rhr_bpm),
+                        "hrv_rmssd_milli": round1(day.hrv_ms),
+                        "spo2_percentage": round1(self.rng.range(95.0, 99.0))
+                    }
+                }));
+            }
+            if self.present() {
+                cycle.push(json!({
+                    "id": id,
+                    "start": day.sleep_end().to_rfc3339(),
+                    "end": (day.sleep_end() + Duration::hours(16)).to_rfc3339(),
+                    "score": {
+                        "strain": round1(day.strain),
+                        "kilojoule": round1((1600.0 + day.active_calories) * 4.184),
+                        "average_heart_rate": round1(day.rhr_bpm + 15.0),
+                        "max_heart_rate": round1(day.rhr_bpm + 60.0 + day.strain * 4.0)
+                    }
+                }));
+            }
+        }
+        json!({ "sleep": sleep, "recovery": recovery, "cycle": cycle })
+    }
+
+    /// A Garmin Health API payload (`dailies`, `sleep` arrays)
+    pub fn garmin_payload(&mut self) -> Value {
+        let (mut dailies, mut sleep) = (Vec::new(), Vec::new());
+        for day in self.days() {
+            let date = day.date.to_string();
+            if self.present() {
+                dailies.push(json!({
+                    "calendarDate": date,
+                    "totalSteps": day.steps,
+                    "totalDistanceMeters": (day.steps as f64 * 0.78).round() as i64,
+                    "totalKilocalories": (1600.0 + day.active_calories).round() as i64,
+                    "activeKilocalories": day.active_calories.round() as i64,
+                    "restingHeartRate": day.rhr_bpm.round() as i64,
+                    "restingHeartRateHrv": round1(day.hrv_ms),
+                    "averageHeartRate": (day.rhr_bpm + 15.0).round() as i64,
+                    "maxHeartRate": (day.rhr_bpm + 60.0 + day.strain * 4.0).round() as i64,
+                    "avgSpo2Value": round1(self.rng.range(95.0, 99.0)),
+                    "bodyBatteryChargedValue": (day.quality * 100.0).round() as i64,
+                    "trainingLoadBalance": round1(day.strain * 5.0),
+                    "moderateIntensityMinutes": (day.strain * 3.0).round() as i64,
+                    "vigorousIntensityMinutes": day.strain.round() as i64
+                }));
+            }
+            if self.present() {
+                sleep.push(json!({
+                    "calendarDate": date,
+                    "sleepStartTimestampGmt": day.sleep_start().timestamp_millis(),
+                    "sleepEndTimestampGmt": day.sleep_end().timestamp_millis(),
+                    "sleepTimeSeconds": minutes_to_seconds(day.sleep_minutes),
+                    "awakeSleepSeconds": minutes_to_seconds(day.awake_minutes),
+                    "lightSleepSeconds": minutes_to_seconds(day.light_minutes()),
+                    "deepSleepSeconds": minutes_to_seconds(day.deep_minutes),
+                    "remSleepSeconds": minutes_to_seconds(day.rem_minutes),
+                    "awakeCount": day.awakenings,
+                    "avgSleepRespiration": round1(self.rng.range(13.5, 16.0)),
+                    "sleepScores": { "overallScore": (day.quality * 100.0).round() }
+                }));
+            }
+        }
+        json!({ "dailies": dailies, "sleep": sleep })
+    }
+
+    /// A `wear.raw_event.v1` stream for `provider`, in chronological order
+    pub fn raw_events(&mut self, provider: &str) -> Result<Vec<RawEvent>, ComputeError> {
+        let device_id = self.config.device_id.clone();
+        let source = json!({ "provider": provider, "device_id": device_id });
+        let mut events = Vec::new();
+        for day in self.days() {
+            let morning = day.sleep_end().to_rfc3339();
+            if self.present() {
+                events.push(json!({
+                    "schema_version": crate::SCHEMA_VERSION,
+                    "timestamp": morning,
+                    "source": source,
+                    "record_type": "session",
+                    "payload": { "session": {
+                        "type": "sleep",
+                        "start_time": day.sleep_start().to_rfc3339(),
+                        "end_time": morning,
+                        "metrics": {
+                            "total_sleep_minutes": day.sleep_minutes.round(),
+                            "deep_sleep_minutes": day.deep_minutes.round(),
+                            "rem_sleep_minutes": day.rem_minutes.round(),
+                            "light_sleep_minutes": day.light_minutes().round(),
+                            "awake_minutes": day.awake_minutes.round(),
+                            "awakenings": day.awakenings
+                        }
+                    }}
+                }));
+            }
+            if self.present() {
+                for (signal, value, unit) in [
+                    ("heart_rate_variability", round1(day.hrv_ms), "ms"),
+                    ("resting_heart_rate", round1(day.rhr_bpm), "bpm"),
+                ] {
+                    events.push(json!({
+                        "schema_version": crate::SCHEMA_VERSION,
+                        "timestamp": morning,
+                        "source": source,
+                        "record_type": "signal",
+                        "payload": { "signal": { "type": signal, "value": value, "unit": unit } }
+                    }));
+                }
+                events.push(json!({
+                    "schema_version": crate::SCHEMA_VERSION,
+                    "timestamp": morning,
+                    "source": source,
+                    "record_type": "score",
+                    "payload": { "score": {
+                        "type": "recovery",
+                        "value": round1(day.quality * 100.0),
+                        "scale": { "min": 0, "max": 100 }
+                    }}
+                }));
+            }
+            if self.present() {
+                let evening = (day.sleep_end() + Duration::hours(15)).to_rfc3339();
+                events.push(json!({
+                    "schema_version": crate::SCHEMA_VERSION,
+                    "timestamp": evening,
+                    "source": source,
+                    "record_type": "summary",
+                    "payload": { "summary": {
+                        "period": "daily",
+                        "date": day.date.to_string(),
+                        "metrics": {
+                            "steps": day.steps,
+                            "active_calories": day.active_calories.round(),
+                            "distance_meters": (day.steps as f64 * 0.78).round()
+                        }
+                    }}
+                }));
+            }
+        }
+        events
+            .into_iter()
+            .map(|event| Ok(serde_json::from_value(event)?))
+            .collect()
+    }
+
+    /// One behavioral session per day (missing days are skipped)
+    pub fn behavior_sessions(&mut self) -> Result<Vec<BehaviorSession>, ComputeError> {
+        let mut sessions = Vec::new();
+        for (i, day) in self.days().into_iter().enumerate() {
+            if !self.present() {
+                continue;
+            }
+            let hour = 9 + (self.rng.next_u64() % 12) as u32;
+            let start =
+                Utc.from_utc_datetime(&day.date.and_hms_opt(hour, 0, 0).expect("valid time"));
+            let end = start + Duration::minutes(SESSION_MINUTES);
+            let session_sec = (SESSION_MINUTES * 60) as f64;
+
+            let mut events = Vec::new();
+            let mut t = self.rng.exp(MEAN_INTERACTION_GAP_SEC);
+            while t < session_sec {
+                events.push(self.interaction_event(start + seconds(t)));
+                // Occasional idle stretches break the session into engagement blocks
+                t += if self.rng.next_f64() < 0.05 {
+                    self.rng.range(45.0, 180.0)
+                } else {
+                    self.rng.exp(MEAN_INTERACTION_GAP_SEC)
+                };
+            }
+            if self.config.notification_rate_per_hour > 0.0 {
+                let mean_gap = 3600.0 / self.config.notification_rate_per_hour;
+                let mut t = self.rng.exp(mean_gap);
+                while t < session_sec {
+                    let action = if self.rng.next_f64() < 0.4 {
+                        "opened"
+                    } else {
+                        "ignored"
+                    };
+                    events.push(json!({
+                        "timestamp": (start + seconds(t)).to_rfc3339(),
+                        "event_type": "notification",
+                        "interruption": { "action": action }
+                    }));
+                    t += self.rng.exp(mean_gap);
+                }
+            }
+            events.sort_by_key(|e| e["timestamp"].as_str().unwrap_or_default().to_string());
+
+            sessions.push(serde_json::from_value(json!({
+                "session_id": format!("synthetic-{}", i + 1),
+                "device_id": self.config.device_id,
+                "timezone": "UTC",
+                "start_time": start.to_rfc3339(),
+                "end_time": end.to_rfc3339(),
+                "events": events
+            }))?);
+        }
+        Ok(sessions)
+    }
+
+    fn interaction_event(&mut self, at: chrono::DateTime<Utc>) -> Value {
+        let timestamp = at.to_rfc3339();
+        let roll = self.rng.next_f64();
+        if roll < 0.5 {
+            let direction = if self.rng.next_f64() < 0.7 {
+                "down"
+            } else {
+                "up"
+            };
+            json!({
+                "timestamp": timestamp,
+                "event_type": "scroll",
+                "scroll": {
+                    "velocity": round1(self.rng.range(60.0, 400.0)),
+                    "direction": direction,
+                    "direction_reversal": direction == "up"
+                }
+            })
+        } else if roll < 0.8 {
+            json!({
+                "timestamp": timestamp,
+                "event_type": "tap",
+                "tap": {
+                    "tap_duration_ms": self.rng.range(60.0, 250.0).round() as u32,
+                    "long_press": false
+                }
+            })
+        } else if roll < 0.93 {
+            json!({
+                "timestamp": timestamp,
+                "event_type": "typing",
+                "typing": {
+                    "typing_speed_cpm": round1(self.rng.range(120.0, 260.0)),
+                    "cadence_stability": round1(self.rng.range(0.5, 0.95) * 10.0) / 10.0,
+                    "duration_sec": round1(self.rng.range(5.0, 40.0))
+                }
+            })
+        } else {
+            let from = 1 + self.rng.next_u64() % 5;
+            let to = 1 + (from + self.rng.next_u64() % 4) % 5;
+            json!({
+                "timestamp": timestamp,
+                "event_type": "app_switch",
+                "app_switch": {
+                    "from_app_id": format!("com.synthetic.app{from}"),
+                    "to_app_id": format!("com.synthetic.app{to}")
+                }
+            })
+        }
+    }
+
+    /// Physiology for every generated day
+    fn days(&mut self) -> Vec<Day> {
+        (0..self.config.days)
+            .map(|i| {
+                let quality = (BASE_SLEEP_QUALITY
+                    + self.config.sleep_quality_trend * i as f64
+                    + self.rng.range(-0.05, 0.05))
+                .clamp(0.05, 0.98);
+                let sleep_minutes = 330.0 + 150.0 * quality + self.rng.range(-15.0, 15.0);
+                let strain = self.rng.range(6.0, 16.0);
+                Day {
+                    date: self.config.start_date + Duration::days(i as i64),
+                    quality,
+                    sleep_minutes,
+                    awake_minutes: 15.0 + 45.0 * (1.0 - quality),
+                    deep_minutes: sleep_minutes * (0.12 + 0.1 * quality),
+                    rem_minutes: sleep_minutes * (0.18 + 0.06 * quality),
+                    awakenings: (1.0 + 6.0 * (1.0 - quality)).round() as u32,
+                    hrv_ms: 35.0 + 45.0 * quality + self.rng.range(-5.0, 5.0),
+                    rhr_bpm: 66.0 - 14.0 * quality + self.rng.range(-2.0, 2.0),
+                    strain,
+                    steps: (3000.0 + strain * 600.0 + self.rng.range(-800.0, 800.0)) as u32,
+                    active_calories: 150.0 + strain * 35.0,
+                }
+            })
+            .collect()
+    }
+
+    /// Roll whether the next record is present
+    fn present(&mut self) -> bool {
+        self.rng.next_f64() >= self.config.missing_probability
+    }
+}
+
+fn minutes_to_millis(minutes: f64) -> i64 {
+    (minutes * 60_000.0).round() as i64
+}
+
+fn minutes_to_seconds(minutes: f64) -> i64 {
+    (minutes * 60.0).round() as i64
+}
+
+fn seconds(sec: f64) -> Duration {
+    Duration::milliseconds((sec * 1000.0).round() as i64)
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// splitmix64: small, seedable and good enough for synthetic data
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+
+    /// Exponential with the given mean
+    fn exp(&mut self, mean: f64) -> f64 {
+        -mean * (1.0 - self.next_f64()).ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior::BehaviorProcessor;
+    use crate::pipeline::FluxProcessor;
+
+    #[test]
+    fn test_whoop_and_garmin_payloads_process() {
+        let config = SyntheticConfig::default().with_days(5).with_seed(1);
+        let whoop = Generator::new(config.clone()).unwrap().whoop_payload();
+        let garmin = Generator::new(config).unwrap().garmin_payload();
+
+        let mut processor = FluxProcessor::new();
+        let out = processor
+            .process_whoop(&whoop.to_string(), "UTC", "dev")
+            .unwrap();
+        assert_eq!(out.len(), 5);
+        // Same dates again: a fresh processor, as days older than the last are rejected
+        let out = FluxProcessor::new()
+            .process_garmin(&garmin.to_string(), "UTC", "dev")
+            .unwrap();
+        assert_eq!(out.len(), 5);
+    }
+
+    #[test]
+    fn test_raw_events_and_sessions_process() {
+        let mut generator =
+            Generator::new(SyntheticConfig::default().with_days(3).with_seed(9)).unwrap();
+        let events = generator.raw_events("whoop").unwrap();
+        assert!(events.iter().all(|e| e.validate().is_ok()));
+        let canonical =
+            crate::schema::RawEventAdapter::to_canonical(&events, "UTC", "dev").unwrap();
+        assert_eq!(canonical.len(), 3);
+
+        let sessions = generator.behavior_sessions().unwrap();
+        assert_eq!(sessions.len(), 3);
+        let mut processor = BehaviorProcessor::new();
+        for session in &sessions {
+            processor.process_session(session).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_same_seed_is_deterministic() {
+        let config = SyntheticConfig::default().with_seed(42);
+        let a = Generator::new(config.clone()).unwrap().whoop_payload();
+        let b = Generator::new(config).unwrap().whoop_payload();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_missing_probability_drops_records() {
+        let config = SyntheticConfig::default()
+            .with_days(50)
+            .with_missing_probability(0.5);
+        let payload = Generator::new(config).unwrap().whoop_payload();
+        let sleeps = payload["sleep"].as_array().unwrap().len();
+        assert!(
+            sleeps > 10 && sleeps < 40,
+            "{sleeps} of 50 sleep records kept"
+        );
+
+        let invalid = SyntheticConfig::default().with_missing_probability(1.5);
+        assert!(Generator::new(invalid).is_err());
+    }
+
+    #[test]
+    fn test_sleep_trend_moves_recovery() {
+        let config = SyntheticConfig::default()
+            .with_days(20)
+            .with_sleep_quality_trend(-0.02);
+        let payload = Generator::new(config).unwrap().whoop_payload();
+        let recovery = payload["recovery"].as_array().unwrap();
+        let first = recovery[0]["score"]["recovery_score"].as_f64().unwrap();
+        let last = recovery[19]["score"]["recovery_score"].as_f64().unwrap();
+        assert!(last < first - 20.0);
+    }
+}
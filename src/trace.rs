@@ -0,0 +1,32 @@
+//! Optional `tracing` instrumentation
+//!
+//! With the `tracing` feature, pipeline stages open spans (so subscribers can
+//! report per-stage timings) and emit events such as per-day event counts.
+//! Without it these macros expand to nothing and their arguments are never
+//! evaluated, so instrumentation costs nothing in default builds.
+
+/// Enter a span until the end of the enclosing block
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+    ($level:ident, $($arg:tt)+) => {
+        let _span = tracing::span!(tracing::Level::$level, $($arg)+).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_span {
+    ($level:ident, $($arg:tt)+) => {};
+}
+
+/// Emit an event
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        tracing::event!(tracing::Level::$level, $($arg)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {};
+}
@@ -3,12 +3,13 @@
 //! This module defines the data structures that flow through each stage of the
 //! pipeline: canonical signals, normalized signals, derived signals, and HSI output.
 
+use crate::warnings::Warning;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Vendor identifier for provenance tracking
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Vendor {
     Whoop,
@@ -35,6 +36,62 @@ pub enum SleepStage {
     Unknown,
 }
 
+/// Menstrual cycle phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CyclePhase {
+    Menstrual,
+    Follicular,
+    Ovulatory,
+    Luteal,
+}
+
+impl CyclePhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CyclePhase::Menstrual => "menstrual",
+            CyclePhase::Follicular => "follicular",
+            CyclePhase::Ovulatory => "ovulatory",
+            CyclePhase::Luteal => "luteal",
+        }
+    }
+
+    /// Parse a phase name (`menstrual`/`period`, `follicular`, `ovulatory`/`ovulation`, `luteal`)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "menstrual" | "period" => Some(CyclePhase::Menstrual),
+            "follicular" => Some(CyclePhase::Follicular),
+            "ovulatory" | "ovulation" => Some(CyclePhase::Ovulatory),
+            "luteal" => Some(CyclePhase::Luteal),
+            _ => None,
+        }
+    }
+
+    /// Phase from a numeric `cycle_phase` signal value
+    /// (0 menstrual, 1 follicular, 2 ovulatory, 3 luteal)
+    pub fn from_code(code: f64) -> Option<Self> {
+        if code.fract() != 0.0 {
+            return None;
+        }
+        match code as i64 {
+            0 => Some(CyclePhase::Menstrual),
+            1 => Some(CyclePhase::Follicular),
+            2 => Some(CyclePhase::Ovulatory),
+            3 => Some(CyclePhase::Luteal),
+            _ => None,
+        }
+    }
+}
+
+/// One segment of a sleep hypnogram
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HypnogramSegment {
+    pub stage: SleepStage,
+    /// Segment start (UTC)
+    pub start: DateTime<Utc>,
+    pub duration_minutes: f64,
+}
+
 /// Canonical sleep data extracted from vendor payloads
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CanonicalSleep {
@@ -62,6 +119,9 @@ pub struct CanonicalSleep {
     pub vendor_sleep_score: Option<f64>,
     /// Respiratory rate during sleep (breaths per minute)
     pub respiratory_rate: Option<f64>,
+    /// Sleep stage sequence in time order, when the source provides one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hypnogram: Vec<HypnogramSegment>,
 }
 
 /// Canonical recovery/physiology data extracted from vendor payloads
@@ -69,6 +129,9 @@ pub struct CanonicalSleep {
 pub struct CanonicalRecovery {
     /// Heart rate variability (ms, RMSSD)
     pub hrv_rmssd_ms: Option<f64>,
+    /// Coefficient of variation of nightly RMSSD samples (0-1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hrv_cv: Option<f64>,
     /// Resting heart rate (bpm)
     pub resting_hr_bpm: Option<f64>,
     /// Vendor-provided recovery score (raw, vendor-specific scale)
@@ -77,6 +140,113 @@ pub struct CanonicalRecovery {
     pub skin_temp_deviation_c: Option<f64>,
     /// Blood oxygen saturation (percentage, 0-100)
     pub spo2_percentage: Option<f64>,
+    /// Menstrual cycle phase reported for the day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle_phase: Option<CyclePhase>,
+    /// Garmin Body Battery dynamics for the day
+    #[serde(default, skip_serializing_if = "BodyBattery::is_empty")]
+    pub body_battery: BodyBattery,
+    /// All-day stress summary
+    #[serde(default, skip_serializing_if = "StressSummary::is_empty")]
+    pub stress: StressSummary,
+    /// All-day respiration summary (breaths per minute)
+    #[serde(default, skip_serializing_if = "RespirationSummary::is_empty")]
+    pub respiration: RespirationSummary,
+    /// Vendor whose HRV methodology `hrv_rmssd_ms` follows (set by HRV calibration)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hrv_scale: Option<Vendor>,
+}
+
+/// Body Battery charge and drain over a day (0-100 scale)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BodyBattery {
+    /// Energy gained during the day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charged: Option<f64>,
+    /// Energy spent during the day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drained: Option<f64>,
+    /// Lowest level reached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lowest: Option<f64>,
+    /// Highest level reached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highest: Option<f64>,
+}
+
+impl BodyBattery {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Net charge over the day, `(charged - drained) / 100` clamped to -1..1.
+    ///
+    /// Positive when the day restored more energy than it used.
+    pub fn recovery_balance(&self) -> Option<f64> {
+        let (charged, drained) = (self.charged?, self.drained?);
+        Some(((charged - drained) / 100.0).clamp(-1.0, 1.0))
+    }
+}
+
+/// All-day stress levels (0-100 scale) and time spent in each stress bucket
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StressSummary {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rest_minutes: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_minutes: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub medium_minutes: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_minutes: Option<f64>,
+}
+
+impl StressSummary {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Average stress level scaled to 0-1
+    pub fn normalized_average(&self) -> Option<f64> {
+        self.average.map(|s| (s / 100.0).clamp(0.0, 1.0))
+    }
+
+    /// Share of measured time in each bucket, when all four are reported
+    pub fn duration_ratios(&self) -> Option<HsiStressRatios> {
+        let rest = self.rest_minutes?;
+        let low = self.low_minutes?;
+        let medium = self.medium_minutes?;
+        let high = self.high_minutes?;
+        let total = rest + low + medium + high;
+        (total > 0.0).then(|| HsiStressRatios {
+            rest: rest / total,
+            low: low / total,
+            medium: medium / total,
+            high: high / total,
+        })
+    }
+}
+
+/// All-day respiration rates (breaths per minute)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RespirationSummary {
+    /// Average while awake
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waking_average: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lowest: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highest: Option<f64>,
+}
+
+impl RespirationSummary {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// Canonical activity/strain data extracted from vendor payloads
@@ -88,6 +258,9 @@ pub struct CanonicalActivity {
     pub calories: Option<f64>,
     /// Active calories burned
     pub active_calories: Option<f64>,
+    /// Resting (basal metabolic) calories burned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resting_calories: Option<f64>,
     /// Average heart rate during activity (bpm)
     pub average_hr_bpm: Option<f64>,
     /// Maximum heart rate during activity (bpm)
@@ -98,9 +271,182 @@ pub struct CanonicalActivity {
     pub steps: Option<u32>,
     /// Active duration (minutes)
     pub active_minutes: Option<f64>,
+    /// Moderate-intensity minutes, when the source splits active time by intensity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderate_intensity_minutes: Option<f64>,
+    /// Vigorous-intensity minutes, when the source splits active time by intensity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vigorous_intensity_minutes: Option<f64>,
+    /// Floors climbed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floors_climbed: Option<u32>,
+    /// Elevation gained (meters)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevation_gain_meters: Option<f64>,
+    /// Individual workouts started on the day, in start order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workouts: Vec<CanonicalWorkout>,
+    /// Time in each heart rate zone over the day
+    #[serde(default, skip_serializing_if = "HrZoneMinutes::is_empty")]
+    pub hr_zone_minutes: HrZoneMinutes,
+    /// Estimated VO2max (ml/kg/min)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vo2_max: Option<f64>,
+    /// Vendor-estimated fitness age (years)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fitness_age: Option<f64>,
+    /// Hour-level aggregates from hourly summaries, in time order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hourly: Vec<HourlyActivity>,
 }
 
-/// Canonical wear signals - vendor-agnostic representation of wearable data
+/// Activity and heart rate aggregated over one hour
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HourlyActivity {
+    /// Start of the hour (UTC)
+    pub start: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calories: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_minutes: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_hr_bpm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_hr_bpm: Option<f64>,
+    /// Share of the hour covered by data (0-1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+}
+
+/// One workout session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanonicalWorkout {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// Sport or activity name, when the vendor provides one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sport: Option<String>,
+    /// Vendor-provided strain score (raw, vendor-specific scale)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor_strain_score: Option<f64>,
+    /// Energy expended (kcal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calories: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_hr_bpm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_hr_bpm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_meters: Option<f64>,
+    #[serde(default, skip_serializing_if = "HrZoneMinutes::is_empty")]
+    pub hr_zone_minutes: HrZoneMinutes,
+}
+
+impl CanonicalWorkout {
+    pub fn duration_minutes(&self) -> f64 {
+        (self.end_time - self.start_time).num_seconds() as f64 / 60.0
+    }
+}
+
+/// Minutes spent in each heart rate zone (zone 0 is below zone 1)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct HrZoneMinutes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_0: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_1: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_2: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_3: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_4: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_5: Option<f64>,
+}
+
+impl HrZoneMinutes {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The minutes of zone 0-5
+    pub fn zone_mut(&mut self, zone: usize) -> Option<&mut Option<f64>> {
+        match zone {
+            0 => Some(&mut self.zone_0),
+            1 => Some(&mut self.zone_1),
+            2 => Some(&mut self.zone_2),
+            3 => Some(&mut self.zone_3),
+            4 => Some(&mut self.zone_4),
+            5 => Some(&mut self.zone_5),
+            _ => None,
+        }
+    }
+
+    /// Zone-wise total; a zone stays `None` unless some input reports it
+    pub fn sum<'a>(zones: impl IntoIterator<Item = &'a HrZoneMinutes>) -> Self {
+        let add = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        zones.into_iter().fold(Self::default(), |acc, z| Self {
+            zone_0: add(acc.zone_0, z.zone_0),
+            zone_1: add(acc.zone_1, z.zone_1),
+            zone_2: add(acc.zone_2, z.zone_2),
+            zone_3: add(acc.zone_3, z.zone_3),
+            zone_4: add(acc.zone_4, z.zone_4),
+            zone_5: add(acc.zone_5, z.zone_5),
+        })
+    }
+}
+
+/// Canonical body composition, typically from a smart scale
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CanonicalBody {
+    /// Body weight (kg)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_kg: Option<f64>,
+    /// Body fat (percentage, 0-100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_fat_percentage: Option<f64>,
+}
+
+impl CanonicalBody {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// One timestamped sample of an intraday series
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IntradaySample {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// Readings of a signal type or metric the canonical model does not map
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnmappedSignal {
+    /// Readings seen
+    pub count: usize,
+    /// Mean of the numeric readings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean: Option<f64>,
+    /// Unit of the most recent reading, when reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// Canonical wear signals - vendor-agnostic representation of wearable data.
+///
+/// One day of input from any source, in fixed units, before normalization.
+/// This is Flux's public intermediate format: it serializes with serde (see
+/// `flux transform --stop-after canonical`), and deserialized days can be fed
+/// back through [`FluxProcessor::process_canonical`].
+///
+/// [`FluxProcessor::process_canonical`]: crate::pipeline::FluxProcessor::process_canonical
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanonicalWearSignals {
     /// Source vendor
@@ -109,18 +455,64 @@ pub struct CanonicalWearSignals {
     pub date: String,
     /// Device identifier
     pub device_id: String,
+    /// User the input events belong to, if they carried one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
     /// Timezone of the user
     pub timezone: String,
+    /// UTC offset in minutes of the timezone reported in event context, at
+    /// the day's last such event (`None` when no event carried one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_offset_minutes: Option<i32>,
     /// When the data was observed/recorded by the vendor
     pub observed_at: DateTime<Utc>,
+    /// Newest contributing event per HSI domain (`sleep`, `physiology`,
+    /// `activity`, `body`), when the input carries timestamps
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub latest_event_at: BTreeMap<String, DateTime<Utc>>,
     /// Sleep data
     pub sleep: CanonicalSleep,
     /// Recovery/physiology data
     pub recovery: CanonicalRecovery,
     /// Activity/strain data
     pub activity: CanonicalActivity,
+    /// Body composition data
+    #[serde(default, skip_serializing_if = "CanonicalBody::is_empty")]
+    pub body: CanonicalBody,
+    /// Mean quality (0-1) reported by the input signals, if any reported one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_quality: Option<f64>,
     /// Raw vendor-specific metrics preserved for transparency
     pub vendor_raw: HashMap<String, serde_json::Value>,
+    /// Per-field source vendor for days fused from several devices
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub field_sources: HashMap<String, Vendor>,
+    /// Raw event IDs that fed each field, keyed like `field_sources`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub field_events: HashMap<String, Vec<String>>,
+    /// Intraday samples by field (`heart_rate`, `hrv_rmssd_ms`), in time order
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub intraday: BTreeMap<String, Vec<IntradaySample>>,
+    /// Context tags of the day's events (e.g. `travel`, `sick`), sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Input that was adjusted or left out while building the day
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+    /// Signals and metrics with no canonical field, keyed `signal.<type>`,
+    /// `summary.<metric>` or `score.<type>`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub unmapped_signals: BTreeMap<String, UnmappedSignal>,
+}
+
+impl CanonicalWearSignals {
+    /// Vendor that supplied the given field (falls back to the day's vendor)
+    pub fn source_of(&self, field: &str) -> Vendor {
+        self.field_sources
+            .get(field)
+            .copied()
+            .unwrap_or(self.vendor)
+    }
 }
 
 /// Normalized signals with consistent units and scales
@@ -134,24 +526,81 @@ pub struct NormalizedSignals {
     pub recovery_score: Option<f64>,
     /// Normalized strain/load score (0-1)
     pub strain_score: Option<f64>,
-    /// Data completeness (0-1)
+    /// Weighted completeness of the expected signal classes (0-1)
     pub coverage: f64,
+    /// Fraction of expected signal classes present (0-1)
+    #[serde(default)]
+    pub class_coverage: f64,
     /// Flags for missing or estimated data
     pub quality_flags: Vec<QualityFlag>,
+    /// Strategy used for each present score, recorded only when a
+    /// non-default normalization config is in effect
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub score_normalization: BTreeMap<String, ScoreNormalization>,
+}
+
+/// How a vendor score is scaled to 0-1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreNormalization {
+    /// Score divided by the vendor's full scale
+    #[default]
+    Linear,
+    /// `ln(1 + score) / ln(1 + full scale)`, which spreads out the low end
+    Log,
+    /// Mid-rank of the score among the user's recent scores
+    Quantile,
+}
+
+impl ScoreNormalization {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScoreNormalization::Linear => "linear",
+            ScoreNormalization::Log => "log",
+            ScoreNormalization::Quantile => "quantile",
+        }
+    }
 }
 
 /// Quality flag indicating data issues
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum QualityFlag {
-    MissingSleepData,
+    MissingSleep,
     MissingRecoveryData,
     MissingActivityData,
-    MissingHrv,
+    NoHrv,
     MissingRestingHr,
     EstimatedValue,
-    PartialDayData,
+    PartialDay,
     LowConfidence,
+    /// The user's timezone changed since the previous day
+    TimezoneShift,
+    /// A vendor value was outside its physiological range and was dropped or capped
+    ImplausibleValue,
+    /// The newest event behind the window is older than the staleness threshold
+    StaleData,
+    /// Total calories differ from active plus resting calories
+    InconsistentCalories,
+}
+
+impl QualityFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QualityFlag::MissingSleep => "missing_sleep",
+            QualityFlag::MissingRecoveryData => "missing_recovery_data",
+            QualityFlag::MissingActivityData => "missing_activity_data",
+            QualityFlag::NoHrv => "no_hrv",
+            QualityFlag::MissingRestingHr => "missing_resting_hr",
+            QualityFlag::EstimatedValue => "estimated_value",
+            QualityFlag::PartialDay => "partial_day",
+            QualityFlag::LowConfidence => "low_confidence",
+            QualityFlag::TimezoneShift => "timezone_shift",
+            QualityFlag::ImplausibleValue => "implausible_value",
+            QualityFlag::StaleData => "stale_data",
+            QualityFlag::InconsistentCalories => "inconsistent_calories",
+        }
+    }
 }
 
 /// Derived features computed from normalized signals
@@ -169,6 +618,26 @@ pub struct DerivedSignals {
     pub rem_sleep_ratio: Option<f64>,
     /// Normalized load (strain adjusted by recovery)
     pub normalized_load: Option<f64>,
+    /// Sleep continuity computed from the hypnogram, if there is one
+    #[serde(default)]
+    pub hypnogram: Option<HypnogramStats>,
+    /// Progress toward the processor's activity goal (0-1)
+    #[serde(default)]
+    pub goal_attainment: Option<f64>,
+    /// Body mass index, when the user's height is configured
+    #[serde(default)]
+    pub bmi: Option<f64>,
+}
+
+/// Sleep continuity computed by Flux from a hypnogram
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HypnogramStats {
+    /// Stage changes between sleep onset and the final awakening
+    pub stage_transitions: u32,
+    /// Wake after sleep onset (minutes)
+    pub waso_minutes: f64,
+    /// Stage transitions per hour of sleep
+    pub fragmentation_index: f64,
 }
 
 /// Baseline values for relative interpretation
@@ -182,8 +651,41 @@ pub struct Baselines {
     pub sleep_baseline_minutes: Option<f64>,
     /// Baseline sleep efficiency (rolling average, 0-1)
     pub sleep_efficiency_baseline: Option<f64>,
+    /// HRV standard deviation over the rolling window (ms)
+    #[serde(default)]
+    pub hrv_std_ms: Option<f64>,
+    /// Resting HR standard deviation over the rolling window (bpm)
+    #[serde(default)]
+    pub rhr_std_bpm: Option<f64>,
+    /// Sleep duration standard deviation over the rolling window (minutes)
+    #[serde(default)]
+    pub sleep_std_minutes: Option<f64>,
+    /// Baseline VO2max (average over the slow window, ml/kg/min)
+    #[serde(default)]
+    pub vo2_max_baseline: Option<f64>,
+    /// Baseline body weight (average over the slow window, kg)
+    #[serde(default)]
+    pub weight_baseline_kg: Option<f64>,
+    /// Baseline body fat (average over the slow window, percentage)
+    #[serde(default)]
+    pub body_fat_baseline_pct: Option<f64>,
+    /// VO2max standard deviation over the slow window (ml/kg/min)
+    #[serde(default)]
+    pub vo2_max_std: Option<f64>,
+    /// Body weight standard deviation over the slow window (kg)
+    #[serde(default)]
+    pub weight_std_kg: Option<f64>,
+    /// Body fat standard deviation over the slow window (percentage points)
+    #[serde(default)]
+    pub body_fat_std_pct: Option<f64>,
     /// Number of days used to compute baselines
     pub baseline_days: u32,
+    /// Rolling window size the baselines were computed over (days; 0 if unknown)
+    #[serde(default)]
+    pub window_days: u32,
+    /// Context tag of the separate baseline used, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
 }
 
 /// Contextual signals with baseline comparisons
@@ -199,6 +701,66 @@ pub struct ContextualSignals {
     pub rhr_deviation_pct: Option<f64>,
     /// Sleep duration deviation from baseline (percentage)
     pub sleep_duration_deviation_pct: Option<f64>,
+    /// HRV z-score against the rolling baseline
+    #[serde(default)]
+    pub hrv_zscore: Option<f64>,
+    /// RHR z-score against the rolling baseline
+    #[serde(default)]
+    pub rhr_zscore: Option<f64>,
+    /// Sleep duration z-score against the rolling baseline
+    #[serde(default)]
+    pub sleep_duration_zscore: Option<f64>,
+    /// HRV z-score magnitude exceeds the anomaly threshold
+    #[serde(default)]
+    pub hrv_anomaly: bool,
+    /// RHR z-score magnitude exceeds the anomaly threshold
+    #[serde(default)]
+    pub rhr_anomaly: bool,
+    /// Sleep duration z-score magnitude exceeds the anomaly threshold
+    #[serde(default)]
+    pub sleep_anomaly: bool,
+    /// Mean goal attainment over the 7 days ending on this one
+    #[serde(default)]
+    pub goal_attainment_7d: Option<f64>,
+    /// VO2max deviation from its slow baseline (percentage)
+    #[serde(default)]
+    pub vo2_max_deviation_pct: Option<f64>,
+    /// Weight deviation from its slow baseline (percentage)
+    #[serde(default)]
+    pub weight_deviation_pct: Option<f64>,
+    /// Body fat deviation from its slow baseline (percentage)
+    #[serde(default)]
+    pub body_fat_deviation_pct: Option<f64>,
+    /// VO2max z-score against its slow baseline
+    #[serde(default)]
+    pub vo2_max_zscore: Option<f64>,
+    /// Weight z-score against its slow baseline
+    #[serde(default)]
+    pub weight_zscore: Option<f64>,
+    /// Body fat z-score against its slow baseline
+    #[serde(default)]
+    pub body_fat_zscore: Option<f64>,
+    /// HRV change over the trend window relative to its mean (-1 to 1)
+    #[serde(default)]
+    pub hrv_trend: Option<f64>,
+    /// RHR change over the trend window relative to its mean (-1 to 1)
+    #[serde(default)]
+    pub rhr_trend: Option<f64>,
+    /// Sleep duration change over the trend window relative to its mean (-1 to 1)
+    #[serde(default)]
+    pub sleep_trend: Option<f64>,
+    /// Whether change-point detection flags a sustained shift (unset when disabled)
+    #[serde(default)]
+    pub baseline_shift_detected: Option<bool>,
+    /// First day of the flagged shift (YYYY-MM-DD)
+    #[serde(default)]
+    pub baseline_shift_date: Option<String>,
+    /// Whether the day is the first from a new device (unset before any switch)
+    #[serde(default)]
+    pub device_changed: Option<bool>,
+    /// Days since the most recent device switch
+    #[serde(default)]
+    pub days_since_device_switch: Option<u32>,
 }
 
 /// HSI producer metadata
@@ -214,8 +776,41 @@ pub struct HsiProducer {
 pub struct HsiProvenance {
     pub source_vendor: String,
     pub source_device_id: String,
+    /// Pseudonymized user ID, present only when the privacy policy opts in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_user_id: Option<String>,
     pub observed_at_utc: String,
     pub computed_at_utc: String,
+    /// Per-field source vendor, present only for days fused from several devices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_sources: Option<BTreeMap<String, String>>,
+    /// Revision counter, present only when an already-emitted day was amended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u32>,
+    /// Axes Flux computed from raw data (e.g. a hypnogram) rather than vendor totals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flux_computed: Option<Vec<String>>,
+    /// Normalization strategy per vendor score, present only when configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_normalization: Option<BTreeMap<String, String>>,
+    /// Vendor whose HRV was mapped onto the calibration reference vendor's scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hrv_calibrated_from: Option<String>,
+    /// Raw event IDs the window was computed from, present only when configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_events: Option<HsiSourceEvents>,
+}
+
+/// Raw events behind a window, for tracing outputs back to their inputs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HsiSourceEvents {
+    /// Number of distinct event IDs
+    pub count: usize,
+    /// `sha256:` digest of the sorted IDs, each followed by a newline
+    pub digest: String,
+    /// The sorted IDs, present only when listing is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ids: Option<Vec<String>>,
 }
 
 /// HSI quality metrics
@@ -223,10 +818,21 @@ pub struct HsiProvenance {
 pub struct HsiQuality {
     /// Data completeness (0-1)
     pub coverage: f64,
-    /// Seconds since observation
+    /// Fraction of expected signal classes (sleep, HRV, RHR, activity) present
+    #[serde(default)]
+    pub class_coverage: f64,
+    /// Seconds since the newest contributing event (since ingest when the
+    /// input carries no event timestamps)
     pub freshness_sec: i64,
+    /// Seconds since the newest event per domain (`sleep`, `physiology`,
+    /// `activity`, `body`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub domain_freshness_sec: BTreeMap<String, i64>,
     /// Overall confidence in the signals (0-1)
     pub confidence: f64,
+    /// Mean quality (0-1) reported by the input signals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_quality: Option<f64>,
     /// Quality flags
     pub flags: Vec<String>,
 }
@@ -241,6 +847,15 @@ pub struct HsiSleep {
     pub rem_ratio: Option<f64>,
     pub latency_minutes: Option<f64>,
     pub score: Option<f64>,
+    /// Stage transitions per hour of sleep (hypnogram input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragmentation_index: Option<f64>,
+    /// Stage changes between sleep onset and final awakening (hypnogram input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage_transitions: Option<u32>,
+    /// Wake after sleep onset in minutes (hypnogram input only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waso_minutes: Option<f64>,
     pub vendor: HashMap<String, serde_json::Value>,
 }
 
@@ -248,13 +863,74 @@ pub struct HsiSleep {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HsiPhysiology {
     pub hrv_rmssd_ms: Option<f64>,
+    /// Coefficient of variation of nightly RMSSD, present only for series input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hrv_cv: Option<f64>,
     pub resting_hr_bpm: Option<f64>,
     pub respiratory_rate: Option<f64>,
     pub spo2_percentage: Option<f64>,
     pub recovery_score: Option<f64>,
+    /// Net Body Battery charge over the day (-1 to 1), Garmin only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery_balance: Option<f64>,
+    /// Average all-day stress level scaled to 0-1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor_stress: Option<f64>,
+    /// Share of measured time spent in each stress bucket
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stress_ratios: Option<HsiStressRatios>,
+    /// Menstrual cycle phase, context for HRV and resting HR shifts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle_phase_context: Option<CyclePhase>,
     pub vendor: HashMap<String, serde_json::Value>,
 }
 
+/// Fractions of measured stress time per bucket (sum to 1)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HsiStressRatios {
+    pub rest: f64,
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+}
+
+/// One hour of the HSI activity namespace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HsiHourlyActivity {
+    /// Hour start (RFC3339, UTC)
+    pub start_utc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calories: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_minutes: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_hr_bpm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_hr_bpm: Option<f64>,
+    /// Share of the hour covered by data (0-1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+}
+
+/// HSI body namespace signals
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HsiBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_kg: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_fat_percentage: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bmi: Option<f64>,
+}
+
+impl HsiBody {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// HSI activity namespace signals
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HsiActivity {
@@ -262,12 +938,73 @@ pub struct HsiActivity {
     pub normalized_load: Option<f64>,
     pub calories: Option<f64>,
     pub active_calories: Option<f64>,
+    /// Resting (basal metabolic) calories, when the source reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resting_calories: Option<f64>,
     pub steps: Option<u32>,
     pub active_minutes: Option<f64>,
     pub distance_meters: Option<f64>,
+    /// Moderate-intensity minutes, when the source reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderate_intensity_minutes: Option<f64>,
+    /// Vigorous-intensity minutes, when the source reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vigorous_intensity_minutes: Option<f64>,
+    /// Floors climbed, when the source reports them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floors_climbed: Option<u32>,
+    /// Elevation gained (meters), when the source reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevation_gain_meters: Option<f64>,
+    /// Individual workouts, present only when the source reports them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workouts: Vec<HsiWorkout>,
+    /// Hour-level activity, present only when the source reports hourly summaries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hourly: Vec<HsiHourlyActivity>,
+    /// Minutes in each heart rate zone, when the source reports zone times
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hr_zone_minutes: Option<HrZoneMinutes>,
+    /// Progress toward the configured step/active-minute goal (0-1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity_goal_attainment: Option<f64>,
+    /// Mean goal attainment over the last 7 days, including this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity_goal_attainment_7d: Option<f64>,
+    /// Estimated VO2max (ml/kg/min)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vo2_max: Option<f64>,
+    /// Vendor-estimated fitness age (years)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fitness_age: Option<f64>,
     pub vendor: HashMap<String, serde_json::Value>,
 }
 
+/// One workout within a daily window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HsiWorkout {
+    /// Workout start (RFC3339, UTC)
+    pub start_utc: String,
+    /// Workout end (RFC3339, UTC)
+    pub end_utc: String,
+    pub duration_minutes: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sport: Option<String>,
+    /// Vendor strain scaled to 0-1, as for the day's `strain_score`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strain_score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calories: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_hr_bpm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_hr_bpm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_meters: Option<f64>,
+    #[serde(default, skip_serializing_if = "HrZoneMinutes::is_empty")]
+    pub hr_zone_minutes: HrZoneMinutes,
+}
+
 /// HSI baseline namespace signals
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HsiBaseline {
@@ -278,7 +1015,90 @@ pub struct HsiBaseline {
     pub hrv_deviation_pct: Option<f64>,
     pub rhr_deviation_pct: Option<f64>,
     pub sleep_deviation_pct: Option<f64>,
+    #[serde(default)]
+    pub hrv_zscore: Option<f64>,
+    #[serde(default)]
+    pub rhr_zscore: Option<f64>,
+    #[serde(default)]
+    pub sleep_zscore: Option<f64>,
+    /// Rolling-window standard deviations behind the z-scores
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hrv_std_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rhr_std_bpm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleep_std_minutes: Option<f64>,
+    #[serde(default)]
+    pub hrv_anomaly: bool,
+    #[serde(default)]
+    pub rhr_anomaly: bool,
+    #[serde(default)]
+    pub sleep_anomaly: bool,
+    /// VO2max averaged over the slow (90-day) window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vo2_max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vo2_max_deviation_pct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vo2_max_std: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vo2_max_zscore: Option<f64>,
+    /// Weight (kg) averaged over the slow (30-day) window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_kg: Option<f64>,
+    /// Body fat (percentage) averaged over the slow (30-day) window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_fat_percentage: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_deviation_pct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_fat_deviation_pct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_std_kg: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_zscore: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_fat_std_pct: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_fat_zscore: Option<f64>,
+    /// HRV change over the last 7 days relative to its mean (-1 to 1, positive when rising)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hrv_trend: Option<f64>,
+    /// Resting HR change over the last 7 days relative to its mean (-1 to 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rhr_trend: Option<f64>,
+    /// Sleep duration change over the last 7 days relative to its mean (-1 to 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleep_trend: Option<f64>,
+    /// Sustained shift in HRV, RHR or sleep flagged by change-point detection
+    /// (absent unless detection is enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline_shift_detected: Option<bool>,
+    /// First day of the flagged shift (YYYY-MM-DD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline_shift_date: Option<String>,
+    /// Whether the day is the first from a new device (vendor or device ID);
+    /// absent until the first switch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_changed: Option<bool>,
+    /// Days since the most recent device switch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days_since_device_switch: Option<u32>,
     pub days_in_baseline: u32,
+    /// Context tag whose separate baseline the day was compared against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// One downsampled bucket of an intraday series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HsiSeriesPoint {
+    /// Bucket start (RFC3339, UTC)
+    pub start_utc: String,
+    /// Mean of the samples in the bucket
+    pub value: f64,
+    /// Number of samples in the bucket
+    pub samples: u32,
 }
 
 /// HSI daily window
@@ -290,6 +1110,12 @@ pub struct HsiDailyWindow {
     pub physiology: HsiPhysiology,
     pub activity: HsiActivity,
     pub baseline: HsiBaseline,
+    /// Body composition, present only on days with scale data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<HsiBody>,
+    /// Downsampled intraday series, present only when enabled in the encoder config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<BTreeMap<String, Vec<HsiSeriesPoint>>>,
 }
 
 /// Complete HSI payload
@@ -300,4 +1126,7 @@ pub struct HsiPayload {
     pub provenance: HsiProvenance,
     pub quality: HsiQuality,
     pub windows: Vec<HsiDailyWindow>,
+    /// Additional metadata (e.g. the integrity block of a sealed payload)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<BTreeMap<String, serde_json::Value>>,
 }
@@ -0,0 +1,481 @@
+//! HSI output validation
+//!
+//! This module checks HSI payloads (produced by Flux or by third parties)
+//! against the rules Flux encodes. Two payload shapes are recognized:
+//!
+//! - **Daily** (wearable pipeline): `windows` is an array of daily windows
+//! - **Snapshot** (behavior pipeline): `windows` is a map keyed by `window_ids`
+//!
+//! Validation works on untyped JSON so that every violation can be reported,
+//! not just the first deserialization failure.
+
+use crate::error::ComputeError;
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// A single rule violation found in an HSI payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HsiViolation {
+    /// JSON path of the offending field (e.g. `axes.behavior.readings[0].score`)
+    pub path: String,
+    /// Human-readable description of the violation
+    pub message: String,
+}
+
+/// Validate an HSI payload given as a JSON string.
+///
+/// Returns an error only if the input is not valid JSON.
+pub fn validate_hsi_json(json: &str) -> Result<Vec<HsiViolation>, ComputeError> {
+    let value: Value = serde_json::from_str(json)?;
+    Ok(validate_hsi(&value))
+}
+
+/// Validate an HSI payload and return every violation found.
+pub fn validate_hsi(payload: &Value) -> Vec<HsiViolation> {
+    let mut v = Validator::default();
+
+    let Some(obj) = payload.as_object() else {
+        v.push("", "payload must be a JSON object");
+        return v.violations;
+    };
+
+    v.require_string(payload, "hsi_version");
+    v.check_producer(payload);
+
+    match obj.get("windows") {
+        Some(Value::Array(_)) => v.check_daily(payload),
+        Some(Value::Object(_)) => v.check_snapshot(payload),
+        Some(_) => v.push("windows", "must be an array or an object"),
+        None => v.push("windows", "required field is missing"),
+    }
+
+    v.violations
+}
+
+#[derive(Default)]
+struct Validator {
+    violations: Vec<HsiViolation>,
+}
+
+impl Validator {
+    fn push(&mut self, path: &str, message: impl Into<String>) {
+        self.violations.push(HsiViolation {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn require_string<'a>(&mut self, parent: &'a Value, path: &str) -> Option<&'a str> {
+        match lookup(parent, path) {
+            Some(Value::String(s)) if !s.is_empty() => Some(s),
+            Some(Value::String(_)) => {
+                self.push(path, "must not be empty");
+                None
+            }
+            Some(_) => {
+                self.push(path, "must be a string");
+                None
+            }
+            None => {
+                self.push(path, "required field is missing");
+                None
+            }
+        }
+    }
+
+    fn require_timestamp(&mut self, parent: &Value, path: &str) {
+        if let Some(s) = self.require_string(parent, path) {
+            if DateTime::parse_from_rfc3339(s).is_err() {
+                self.push(path, format!("'{s}' is not an RFC3339 timestamp"));
+            }
+        }
+    }
+
+    fn require_bool(&mut self, parent: &Value, path: &str) -> Option<bool> {
+        match lookup(parent, path) {
+            Some(Value::Bool(b)) => Some(*b),
+            Some(_) => {
+                self.push(path, "must be a boolean");
+                None
+            }
+            None => {
+                self.push(path, "required field is missing");
+                None
+            }
+        }
+    }
+
+    /// Check that a value, if present and non-null, is a number within 0-1
+    fn check_unit_range(&mut self, value: Option<&Value>, path: &str) {
+        match value {
+            None | Some(Value::Null) => {}
+            Some(Value::Number(n)) => {
+                let x = n.as_f64().unwrap_or(f64::NAN);
+                if !(0.0..=1.0).contains(&x) {
+                    self.push(path, format!("{x} is outside the 0-1 range"));
+                }
+            }
+            Some(_) => self.push(path, "must be a number or null"),
+        }
+    }
+
+    fn check_producer(&mut self, payload: &Value) {
+        match payload.get("producer") {
+            Some(Value::Object(_)) => {
+                self.require_string(payload, "producer.name");
+                self.require_string(payload, "producer.version");
+            }
+            Some(_) => self.push("producer", "must be an object"),
+            None => self.push("producer", "required field is missing"),
+        }
+    }
+
+    /// Rules for wearable daily payloads (`hsi_version` 1.0.0)
+    fn check_daily(&mut self, payload: &Value) {
+        self.require_string(payload, "producer.instance_id");
+        self.require_string(payload, "provenance.source_vendor");
+        self.require_string(payload, "provenance.source_device_id");
+        self.require_timestamp(payload, "provenance.observed_at_utc");
+        self.require_timestamp(payload, "provenance.computed_at_utc");
+
+        match lookup(payload, "quality") {
+            Some(Value::Object(_)) => {
+                for field in ["coverage", "confidence"] {
+                    let path = format!("quality.{field}");
+                    match lookup(payload, &path) {
+                        Some(Value::Number(_)) => {
+                            self.check_unit_range(lookup(payload, &path), &path)
+                        }
+                        _ => self.push(&path, "required number is missing"),
+                    }
+                }
+                for field in ["class_coverage", "input_quality"] {
+                    let path = format!("quality.{field}");
+                    self.check_unit_range(lookup(payload, &path), &path);
+                }
+                if !matches!(lookup(payload, "quality.freshness_sec"), Some(Value::Number(n)) if n.is_i64())
+                {
+                    self.push("quality.freshness_sec", "must be an integer");
+                }
+                match lookup(payload, "quality.flags") {
+                    Some(Value::Array(flags)) if flags.iter().all(Value::is_string) => {}
+                    _ => self.push("quality.flags", "must be an array of strings"),
+                }
+            }
+            _ => self.push("quality", "required object is missing"),
+        }
+
+        let windows = payload["windows"].as_array().cloned().unwrap_or_default();
+        if windows.is_empty() {
+            self.push("windows", "must contain at least one window");
+        }
+
+        for (i, window) in windows.iter().enumerate() {
+            let prefix = format!("windows[{i}]");
+            match window.get("date").and_then(Value::as_str) {
+                Some(date) if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() => {}
+                Some(date) => self.push(
+                    &format!("{prefix}.date"),
+                    format!("'{date}' is not a YYYY-MM-DD date"),
+                ),
+                None => self.push(&format!("{prefix}.date"), "required field is missing"),
+            }
+            if window.get("timezone").and_then(Value::as_str).is_none() {
+                self.push(&format!("{prefix}.timezone"), "required field is missing");
+            }
+
+            for field in [
+                "sleep.efficiency",
+                "sleep.fragmentation",
+                "sleep.deep_ratio",
+                "sleep.rem_ratio",
+                "sleep.score",
+                "physiology.recovery_score",
+                "activity.strain_score",
+            ] {
+                self.check_unit_range(lookup(window, field), &format!("{prefix}.{field}"));
+            }
+        }
+    }
+
+    /// Rules for HSI 1.0 snapshot payloads (behavior pipeline)
+    fn check_snapshot(&mut self, payload: &Value) {
+        self.require_timestamp(payload, "observed_at_utc");
+        self.require_timestamp(payload, "computed_at_utc");
+
+        // window_ids and windows must reference each other exactly
+        let window_ids = self.id_list(payload, "window_ids", true);
+        let windows = payload["windows"].as_object().cloned().unwrap_or_default();
+        if window_ids.is_empty() {
+            self.push("window_ids", "must contain at least one window id");
+        }
+        for id in &window_ids {
+            if !windows.contains_key(id) {
+                self.push("window_ids", format!("'{id}' has no entry in windows"));
+            }
+        }
+        for (id, window) in &windows {
+            let prefix = format!("windows.{id}");
+            if !window_ids.contains(id) {
+                self.push(&prefix, "window is not listed in window_ids");
+            }
+            let start = window.get("start").and_then(Value::as_str);
+            let end = window.get("end").and_then(Value::as_str);
+            match (
+                start.map(DateTime::parse_from_rfc3339),
+                end.map(DateTime::parse_from_rfc3339),
+            ) {
+                (Some(Ok(s)), Some(Ok(e))) if s > e => {
+                    self.push(&prefix, "window start is after window end")
+                }
+                (Some(Ok(_)), Some(Ok(_))) => {}
+                _ => self.push(&prefix, "start and end must be RFC3339 timestamps"),
+            }
+        }
+
+        // source_ids and sources must be present together and consistent
+        let source_ids = self.id_list(payload, "source_ids", false);
+        match (payload.get("source_ids"), payload.get("sources")) {
+            (Some(_), None) => self.push("sources", "required when source_ids is present"),
+            (None, Some(_)) => self.push("source_ids", "required when sources is present"),
+            _ => {}
+        }
+        if let Some(sources) = payload.get("sources").and_then(Value::as_object) {
+            for id in &source_ids {
+                if !sources.contains_key(id) {
+                    self.push("source_ids", format!("'{id}' has no entry in sources"));
+                }
+            }
+            for (id, source) in sources {
+                let prefix = format!("sources.{id}");
+                if !source_ids.contains(id) {
+                    self.push(&prefix, "source is not listed in source_ids");
+                }
+                self.check_unit_range(source.get("quality"), &format!("{prefix}.quality"));
+            }
+        }
+
+        // Axis readings
+        if let Some(axes) = payload.get("axes").and_then(Value::as_object) {
+            for (domain, body) in axes {
+                let readings = body
+                    .get("readings")
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default();
+                for (i, reading) in readings.iter().enumerate() {
+                    let prefix = format!("axes.{domain}.readings[{i}]");
+                    self.check_reading(reading, &prefix, &window_ids, &source_ids);
+                }
+            }
+        }
+
+        // Privacy invariants
+        match payload.get("privacy") {
+            Some(Value::Object(_)) => {
+                if self.require_bool(payload, "privacy.contains_pii") == Some(true) {
+                    self.push("privacy.contains_pii", "HSI payloads must not contain PII");
+                }
+                self.require_bool(payload, "privacy.raw_biosignals_allowed");
+                self.require_bool(payload, "privacy.derived_metrics_allowed");
+            }
+            _ => self.push("privacy", "required object is missing"),
+        }
+    }
+
+    fn check_reading(
+        &mut self,
+        reading: &Value,
+        prefix: &str,
+        window_ids: &HashSet<String>,
+        source_ids: &HashSet<String>,
+    ) {
+        match reading.get("axis").and_then(Value::as_str) {
+            Some(axis) if is_lower_snake_case(axis) => {}
+            Some(axis) => self.push(
+                &format!("{prefix}.axis"),
+                format!("'{axis}' is not lower_snake_case"),
+            ),
+            None => self.push(&format!("{prefix}.axis"), "required field is missing"),
+        }
+
+        self.check_unit_range(reading.get("score"), &format!("{prefix}.score"));
+        match reading.get("confidence") {
+            Some(Value::Number(_)) => {
+                self.check_unit_range(reading.get("confidence"), &format!("{prefix}.confidence"))
+            }
+            _ => self.push(
+                &format!("{prefix}.confidence"),
+                "required number is missing",
+            ),
+        }
+
+        match reading.get("window_id").and_then(Value::as_str) {
+            Some(id) if window_ids.contains(id) => {}
+            Some(id) => self.push(
+                &format!("{prefix}.window_id"),
+                format!("'{id}' does not reference a declared window"),
+            ),
+            None => self.push(&format!("{prefix}.window_id"), "required field is missing"),
+        }
+
+        if let Some(evidence) = reading.get("evidence_source_ids").and_then(Value::as_array) {
+            for id in evidence.iter().filter_map(Value::as_str) {
+                if !source_ids.contains(id) {
+                    self.push(
+                        &format!("{prefix}.evidence_source_ids"),
+                        format!("'{id}' does not reference a declared source"),
+                    );
+                }
+            }
+        }
+    }
+
+    fn id_list(&mut self, payload: &Value, field: &str, required: bool) -> HashSet<String> {
+        match payload.get(field) {
+            Some(Value::Array(ids)) => {
+                let mut seen = HashSet::new();
+                for id in ids {
+                    match id.as_str() {
+                        Some(id) if !seen.insert(id.to_string()) => {
+                            self.push(field, format!("duplicate id '{id}'"))
+                        }
+                        Some(_) => {}
+                        None => self.push(field, "ids must be strings"),
+                    }
+                }
+                seen
+            }
+            Some(_) => {
+                self.push(field, "must be an array of strings");
+                HashSet::new()
+            }
+            None => {
+                if required {
+                    self.push(field, "required field is missing");
+                }
+                HashSet::new()
+            }
+        }
+    }
+}
+
+/// Look up a dotted path (e.g. `provenance.source_vendor`) in a JSON value
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |v, key| v.get(key))
+}
+
+fn is_lower_snake_case(s: &str) -> bool {
+    !s.is_empty()
+        && s.starts_with(|c: char| c.is_ascii_lowercase())
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior::behavior_to_hsi;
+    use crate::pipeline::garmin_to_hsi_daily;
+
+    fn sample_behavior_session() -> &'static str {
+        r#"{
+            "session_id": "sess-1",
+            "device_id": "device-1",
+            "timezone": "UTC",
+            "start_time": "2024-01-15T14:00:00Z",
+            "end_time": "2024-01-15T14:30:00Z",
+            "events": [
+                {"timestamp": "2024-01-15T14:01:00Z", "event_type": "scroll", "scroll": {"velocity": 100.0, "direction": "down"}},
+                {"timestamp": "2024-01-15T14:02:00Z", "event_type": "tap", "tap": {"tap_duration_ms": 100}},
+                {"timestamp": "2024-01-15T14:03:00Z", "event_type": "notification", "interruption": {"action": "ignored"}}
+            ]
+        }"#
+    }
+
+    fn sample_garmin_json() -> &'static str {
+        r#"{
+            "dailies": [{
+                "calendarDate": "2024-01-15",
+                "totalSteps": 8500,
+                "restingHeartRate": 55,
+                "bodyBatteryChargedValue": 72
+            }],
+            "sleep": []
+        }"#
+    }
+
+    #[test]
+    fn test_flux_behavior_output_is_valid() {
+        let hsi = behavior_to_hsi(sample_behavior_session().to_string()).unwrap();
+        let violations = validate_hsi_json(&hsi).unwrap();
+        assert!(violations.is_empty(), "{violations:?}");
+    }
+
+    #[test]
+    fn test_flux_daily_output_is_valid() {
+        let out = garmin_to_hsi_daily(
+            sample_garmin_json().to_string(),
+            "UTC".to_string(),
+            "device-1".to_string(),
+        )
+        .unwrap();
+        let violations = validate_hsi_json(&out[0]).unwrap();
+        assert!(violations.is_empty(), "{violations:?}");
+    }
+
+    #[test]
+    fn test_score_out_of_range() {
+        let hsi = behavior_to_hsi(sample_behavior_session().to_string()).unwrap();
+        let mut value: Value = serde_json::from_str(&hsi).unwrap();
+        value["axes"]["behavior"]["readings"][0]["score"] = Value::from(1.5);
+
+        let violations = validate_hsi(&value);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "axes.behavior.readings[0].score");
+    }
+
+    #[test]
+    fn test_dangling_window_reference() {
+        let hsi = behavior_to_hsi(sample_behavior_session().to_string()).unwrap();
+        let mut value: Value = serde_json::from_str(&hsi).unwrap();
+        value["axes"]["behavior"]["readings"][0]["window_id"] = Value::from("w_missing");
+
+        let violations = validate_hsi(&value);
+        assert!(violations
+            .iter()
+            .any(|v| v.path == "axes.behavior.readings[0].window_id"));
+    }
+
+    #[test]
+    fn test_privacy_invariants() {
+        let hsi = behavior_to_hsi(sample_behavior_session().to_string()).unwrap();
+        let mut value: Value = serde_json::from_str(&hsi).unwrap();
+        value["privacy"]["contains_pii"] = Value::from(true);
+        value["privacy"]
+            .as_object_mut()
+            .unwrap()
+            .remove("derived_metrics_allowed");
+
+        let violations = validate_hsi(&value);
+        let paths: Vec<_> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert!(paths.contains(&"privacy.contains_pii"));
+        assert!(paths.contains(&"privacy.derived_metrics_allowed"));
+    }
+
+    #[test]
+    fn test_missing_required_fields() {
+        let violations = validate_hsi_json(r#"{"windows": []}"#).unwrap();
+        let paths: Vec<_> = violations.iter().map(|v| v.path.as_str()).collect();
+        assert!(paths.contains(&"hsi_version"));
+        assert!(paths.contains(&"producer"));
+        assert!(paths.contains(&"windows"));
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        assert!(validate_hsi_json("not json").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Non-fatal processing warnings
+//!
+//! Some input is adjusted or left out rather than rejected: signals of unknown
+//! types, low-quality readings, extra sleep sessions, out-of-range values.
+//! Processing still succeeds, but each such condition is recorded as a
+//! [`Warning`] so hosts can tell a clean day from a patched-up one.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Kind of condition a warning reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCode {
+    /// Signals of a type the canonical model does not use, or with an unrecognized value
+    UnknownSignal,
+    /// Averaged signals dropped for reporting a quality below the minimum
+    LowQualitySignal,
+    /// Sleep sessions (typically naps) left out because a longer one was used
+    DroppedSleepSession,
+    /// Values outside their valid range that were clamped into it
+    ClampedValue,
+    /// Physiologically implausible values dropped or capped by the normalizer
+    ImplausibleValue,
+    /// Input events far from chronological order
+    OutOfOrderInput,
+    /// Total calories that differ from active plus resting calories
+    InconsistentCalories,
+}
+
+impl WarningCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningCode::UnknownSignal => "unknown_signal",
+            WarningCode::LowQualitySignal => "low_quality_signal",
+            WarningCode::DroppedSleepSession => "dropped_sleep_session",
+            WarningCode::ClampedValue => "clamped_value",
+            WarningCode::ImplausibleValue => "implausible_value",
+            WarningCode::OutOfOrderInput => "out_of_order_input",
+            WarningCode::InconsistentCalories => "inconsistent_calories",
+        }
+    }
+}
+
+/// A condition that changed or dropped input without failing processing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Warning {
+    pub code: WarningCode,
+    /// Day the warning applies to (`YYYY-MM-DD`), or none for the whole input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    pub message: String,
+}
+
+impl Warning {
+    /// Warning about one day
+    pub fn for_day(code: WarningCode, date: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            date: Some(date.into()),
+            message: message.into(),
+        }
+    }
+
+    /// Warning about the whole input
+    pub fn for_input(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            date: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.date {
+            Some(date) => write!(f, "{date}: {} ({})", self.message, self.code.as_str()),
+            None => write!(f, "{} ({})", self.message, self.code.as_str()),
+        }
+    }
+}
+
+/// Payloads produced by a processing call, with the warnings raised on the way
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessOutput<P = String> {
+    pub payloads: Vec<P>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+}
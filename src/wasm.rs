@@ -0,0 +1,51 @@
+//! WebAssembly bindings for Synheart Flux
+//!
+//! Exposes the stateless pipelines to JavaScript via `wasm-bindgen`. Build with
+//! `--target wasm32-unknown-unknown --features wasm`. Wearable functions return
+//! a JSON array of HSI payloads, matching the FFI layer.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::ComputeError;
+
+fn to_js_error(e: ComputeError) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+fn to_json_array(payloads: Vec<String>) -> Result<String, JsError> {
+    let values = payloads
+        .iter()
+        .map(|p| serde_json::from_str::<serde_json::Value>(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(serde_json::to_string(&values)?)
+}
+
+/// Convert WHOOP JSON into a JSON array of HSI daily payloads
+#[wasm_bindgen(js_name = whoopToHsiDaily)]
+pub fn whoop_to_hsi_daily(
+    raw_json: String,
+    timezone: String,
+    device_id: String,
+) -> Result<String, JsError> {
+    let payloads =
+        crate::pipeline::whoop_to_hsi_daily(raw_json, timezone, device_id).map_err(to_js_error)?;
+    to_json_array(payloads)
+}
+
+/// Convert Garmin JSON into a JSON array of HSI daily payloads
+#[wasm_bindgen(js_name = garminToHsiDaily)]
+pub fn garmin_to_hsi_daily(
+    raw_json: String,
+    timezone: String,
+    device_id: String,
+) -> Result<String, JsError> {
+    let payloads =
+        crate::pipeline::garmin_to_hsi_daily(raw_json, timezone, device_id).map_err(to_js_error)?;
+    to_json_array(payloads)
+}
+
+/// Convert a behavioral session JSON into an HSI payload
+#[wasm_bindgen(js_name = behaviorToHsi)]
+pub fn behavior_to_hsi(session_json: String) -> Result<String, JsError> {
+    crate::behavior::behavior_to_hsi(session_json).map_err(to_js_error)
+}
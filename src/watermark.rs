@@ -0,0 +1,283 @@
+//! Event-time watermarking for streams
+//!
+//! A stream cannot know a day is over just because an event for the next day
+//! arrived: devices sync late and events around midnight arrive out of order.
+//! The watermark trails the newest event time by an allowed lateness, and a
+//! day completes once the watermark passes its end (midnight UTC, matching
+//! the dates events are grouped under). Events for days that already completed
+//! are late and handled per [`LateEventPolicy`].
+
+use crate::error::ComputeError;
+use crate::schema::{RawEvent, RawEventAdapter};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Days after completion during which a late event can still amend a day
+pub const MAX_AMEND_DAYS: i64 = 7;
+
+/// What happens to an event whose day already completed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LateEventPolicy {
+    /// Skip the event and count it
+    #[default]
+    Drop,
+    /// Re-emit the day with the event added, as a revision; days completed
+    /// more than [`MAX_AMEND_DAYS`] before the watermark are dropped instead
+    Amend,
+    /// Stop with an error
+    Error,
+}
+
+/// A day whose events are ready to be processed
+#[derive(Debug, Clone)]
+pub struct CompletedDay {
+    /// Calendar date (`YYYY-MM-DD`)
+    pub date: String,
+    /// All of the day's events, in arrival order
+    pub events: Vec<RawEvent>,
+    /// The day was emitted before and should be reprocessed as a revision
+    pub amended: bool,
+}
+
+/// Result of adding one event to a [`DayWatermark`]
+#[derive(Debug, Clone, Default)]
+pub struct Pushed {
+    /// Days completed by the event, oldest first
+    pub completed: Vec<CompletedDay>,
+    /// The event's day had already completed
+    pub late: bool,
+}
+
+/// Buffers events per day and releases days as the watermark passes them
+#[derive(Debug, Clone)]
+pub struct DayWatermark {
+    allowed_lateness: Duration,
+    late_events: LateEventPolicy,
+    newest: Option<DateTime<Utc>>,
+    /// Days still accepting events
+    open: BTreeMap<String, Vec<RawEvent>>,
+    /// Recently completed days, kept for amendment
+    completed: BTreeMap<String, Vec<RawEvent>>,
+    late_count: usize,
+}
+
+impl DayWatermark {
+    /// Watermark that trails the newest event time by `allowed_lateness`
+    pub fn new(allowed_lateness: Duration) -> Self {
+        Self {
+            allowed_lateness: allowed_lateness.max(Duration::zero()),
+            late_events: LateEventPolicy::default(),
+            newest: None,
+            open: BTreeMap::new(),
+            completed: BTreeMap::new(),
+            late_count: 0,
+        }
+    }
+
+    /// Set how far behind the newest event a day stays open
+    pub fn with_allowed_lateness(mut self, allowed_lateness: Duration) -> Self {
+        self.allowed_lateness = allowed_lateness.max(Duration::zero());
+        self
+    }
+
+    /// Set how late events are handled
+    pub fn with_late_events(mut self, policy: LateEventPolicy) -> Self {
+        self.late_events = policy;
+        self
+    }
+
+    /// Newest event time minus the allowed lateness
+    pub fn watermark(&self) -> Option<DateTime<Utc>> {
+        self.newest.map(|newest| newest - self.allowed_lateness)
+    }
+
+    /// Number of late events seen so far
+    pub fn late_events(&self) -> usize {
+        self.late_count
+    }
+
+    /// Add an event, returning the days it completes.
+    ///
+    /// Fails only for a late event under [`LateEventPolicy::Error`].
+    pub fn push(&mut self, event: RawEvent) -> Result<Pushed, ComputeError> {
+        let date = RawEventAdapter::event_date(&event);
+        if self.is_closed(&date) {
+            self.late_count += 1;
+            let amended = match self.late_events {
+                LateEventPolicy::Drop => None,
+                LateEventPolicy::Error => {
+                    return Err(ComputeError::ParseError(format!(
+                        "event for {date} arrived after the day was completed"
+                    )));
+                }
+                LateEventPolicy::Amend => self.amend(date, event),
+            };
+            return Ok(Pushed {
+                completed: amended.into_iter().collect(),
+                late: true,
+            });
+        }
+
+        self.newest = self.newest.max(Some(event.timestamp));
+        self.open.entry(date).or_default().push(event);
+        Ok(Pushed {
+            completed: self.complete_days(),
+            late: false,
+        })
+    }
+
+    /// Complete every open day, e.g. at the end of the input
+    pub fn finish(&mut self) -> Vec<CompletedDay> {
+        std::mem::take(&mut self.open)
+            .into_iter()
+            .map(|(date, events)| CompletedDay {
+                date,
+                events,
+                amended: false,
+            })
+            .collect()
+    }
+
+    /// Whether the watermark has passed the end of `date`
+    fn is_closed(&self, date: &str) -> bool {
+        match (day_end(date), self.watermark()) {
+            (Some(end), Some(watermark)) => end <= watermark,
+            _ => false,
+        }
+    }
+
+    /// Release open days the watermark has passed
+    fn complete_days(&mut self) -> Vec<CompletedDay> {
+        let closed: Vec<String> = self
+            .open
+            .keys()
+            .take_while(|date| self.is_closed(date))
+            .cloned()
+            .collect();
+        let completed = closed
+            .into_iter()
+            .filter_map(|date| {
+                let events = self.open.remove(&date)?;
+                if self.late_events == LateEventPolicy::Amend {
+                    self.completed.insert(date.clone(), events.clone());
+                }
+                Some(CompletedDay {
+                    date,
+                    events,
+                    amended: false,
+                })
+            })
+            .collect();
+        self.prune_completed();
+        completed
+    }
+
+    /// Add a late event to its day, if the day is recent enough to amend
+    fn amend(&mut self, date: String, event: RawEvent) -> Option<CompletedDay> {
+        if !self.is_amendable(&date) {
+            return None;
+        }
+        let events = self.completed.entry(date.clone()).or_default();
+        events.push(event);
+        Some(CompletedDay {
+            date,
+            events: events.clone(),
+            amended: true,
+        })
+    }
+
+    fn is_amendable(&self, date: &str) -> bool {
+        match (day_end(date), self.watermark()) {
+            (Some(end), Some(watermark)) => end > watermark - Duration::days(MAX_AMEND_DAYS),
+            _ => false,
+        }
+    }
+
+    fn prune_completed(&mut self) {
+        let stale: Vec<String> = self
+            .completed
+            .keys()
+            .take_while(|date| !self.is_amendable(date))
+            .cloned()
+            .collect();
+        for date in stale {
+            self.completed.remove(&date);
+        }
+    }
+}
+
+/// Midnight UTC after `date`
+fn day_end(date: &str) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.succ_opt()?.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: &str) -> RawEvent {
+        RawEventAdapter::parse_ndjson(&format!(
+            r#"{{"schema_version":"wear.raw_event.v1","timestamp":"{timestamp}","source":{{"provider":"garmin"}},"record_type":"signal","payload":{{"signal":{{"type":"steps","value":100,"unit":"count"}}}}}}"#
+        ))
+        .unwrap()
+        .remove(0)
+    }
+
+    fn dates(pushed: &Pushed) -> Vec<(&str, usize, bool)> {
+        pushed
+            .completed
+            .iter()
+            .map(|d| (d.date.as_str(), d.events.len(), d.amended))
+            .collect()
+    }
+
+    #[test]
+    fn test_days_complete_after_allowed_lateness() {
+        let mut watermark = DayWatermark::new(Duration::hours(1));
+        watermark.push(event("2024-01-15T22:00:00Z")).unwrap();
+        // Past midnight, but within the lateness: the 15th stays open
+        let pushed = watermark.push(event("2024-01-16T00:30:00Z")).unwrap();
+        assert!(pushed.completed.is_empty());
+        let pushed = watermark.push(event("2024-01-15T23:50:00Z")).unwrap();
+        assert!(!pushed.late);
+
+        let pushed = watermark.push(event("2024-01-16T01:00:00Z")).unwrap();
+        assert_eq!(dates(&pushed), vec![("2024-01-15", 2, false)]);
+
+        let finished = watermark.finish();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].events.len(), 2);
+    }
+
+    #[test]
+    fn test_late_event_policies() {
+        let run = |policy: LateEventPolicy| {
+            let mut watermark = DayWatermark::new(Duration::zero()).with_late_events(policy);
+            watermark.push(event("2024-01-15T08:00:00Z")).unwrap();
+            watermark.push(event("2024-01-16T08:00:00Z")).unwrap();
+            let late = watermark.push(event("2024-01-15T20:00:00Z"));
+            (late, watermark.late_events())
+        };
+
+        let (dropped, count) = run(LateEventPolicy::Drop);
+        let dropped = dropped.unwrap();
+        assert!(dropped.late && dropped.completed.is_empty());
+        assert_eq!(count, 1);
+
+        let (amended, _) = run(LateEventPolicy::Amend);
+        assert_eq!(dates(&amended.unwrap()), vec![("2024-01-15", 2, true)]);
+
+        assert!(run(LateEventPolicy::Error).0.is_err());
+
+        // Too old to amend
+        let mut watermark =
+            DayWatermark::new(Duration::zero()).with_late_events(LateEventPolicy::Amend);
+        watermark.push(event("2024-01-01T08:00:00Z")).unwrap();
+        watermark.push(event("2024-01-16T08:00:00Z")).unwrap();
+        let pushed = watermark.push(event("2024-01-01T20:00:00Z")).unwrap();
+        assert!(pushed.late && pushed.completed.is_empty());
+    }
+}